@@ -0,0 +1,193 @@
+//! Archive use case
+
+use crate::domain::JournalMode;
+use crate::error::{DjourError, Result};
+use crate::infrastructure::repository::JournalRepository;
+use crate::infrastructure::FileSystemRepository;
+use chrono::NaiveDate;
+
+#[derive(Debug, Clone)]
+pub struct ArchiveOptions {
+    /// Notes dated strictly before this date are archived
+    pub before: NaiveDate,
+    pub dry_run: bool,
+}
+
+/// A single planned or performed move
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveMove {
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ArchiveReport {
+    pub dry_run: bool,
+    pub moves: Vec<ArchiveMove>,
+}
+
+/// Move notes from the journal root into `archive/<YYYY>/` (or `archive/<YYYY>/<MM>/`).
+///
+/// Only notes directly in the root are considered, so archived notes are never
+/// moved twice. Every destination is checked before the first move; if any
+/// already exists, nothing is moved.
+pub fn archive_notes(
+    repository: &FileSystemRepository,
+    options: ArchiveOptions,
+) -> Result<ArchiveReport> {
+    let config = repository.load_config()?;
+    let mode = config.get_mode();
+    if matches!(mode, JournalMode::Single) {
+        return Err(DjourError::Config(
+            "Archiving is not available in single mode (there is only one note)".to_string(),
+        ));
+    }
+
+    let mut notes = repository.list_notes(config.naming(), None, None, None, false)?;
+    notes.retain(|note| note.date.is_some_and(|date| date < options.before));
+    // Oldest first reads naturally in the plan.
+    notes.reverse();
+
+    let moves: Vec<ArchiveMove> = notes
+        .iter()
+        .filter_map(|note| {
+            let date = note.date?;
+            Some(ArchiveMove {
+                from: note.filename.clone(),
+                to: config.archive_layout.archive_path(date, &note.filename),
+            })
+        })
+        .collect();
+
+    // Preflight: refuse to start if any destination is taken.
+    let collisions: Vec<&str> = moves
+        .iter()
+        .filter(|planned| repository.note_exists(&planned.to))
+        .map(|planned| planned.to.as_str())
+        .collect();
+    if !collisions.is_empty() {
+        return Err(DjourError::Config(format!(
+            "Archive destination already exists: {}. No notes were moved.",
+            collisions.join(", ")
+        )));
+    }
+
+    if !options.dry_run {
+        for planned in &moves {
+            repository.move_note(&planned.from, &planned.to)?;
+        }
+    }
+
+    Ok(ArchiveReport {
+        dry_run: options.dry_run,
+        moves,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::Config;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn setup(mode: JournalMode) -> (TempDir, FileSystemRepository) {
+        let temp = TempDir::new().unwrap();
+        let repo = FileSystemRepository::new(temp.path().to_path_buf());
+        repo.initialize().unwrap();
+        repo.save_config(&Config::new(mode)).unwrap();
+        (temp, repo)
+    }
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn archives_notes_strictly_before_cutoff() {
+        let (temp, repo) = setup(JournalMode::Daily);
+        for name in ["2022-12-31.md", "2023-05-17.md", "2024-01-01.md"] {
+            fs::write(temp.path().join(name), name).unwrap();
+        }
+
+        let report = archive_notes(
+            &repo,
+            ArchiveOptions {
+                before: date(2024, 1, 1),
+                dry_run: false,
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            report.moves,
+            vec![
+                ArchiveMove {
+                    from: "2022-12-31.md".to_string(),
+                    to: "archive/2022/2022-12-31.md".to_string(),
+                },
+                ArchiveMove {
+                    from: "2023-05-17.md".to_string(),
+                    to: "archive/2023/2023-05-17.md".to_string(),
+                },
+            ]
+        );
+        assert!(temp.path().join("archive/2023/2023-05-17.md").exists());
+        assert!(!temp.path().join("2023-05-17.md").exists());
+        assert!(temp.path().join("2024-01-01.md").exists());
+    }
+
+    #[test]
+    fn dry_run_moves_nothing() {
+        let (temp, repo) = setup(JournalMode::Daily);
+        fs::write(temp.path().join("2023-05-17.md"), "note").unwrap();
+
+        let report = archive_notes(
+            &repo,
+            ArchiveOptions {
+                before: date(2024, 1, 1),
+                dry_run: true,
+            },
+        )
+        .unwrap();
+
+        assert_eq!(report.moves.len(), 1);
+        assert!(temp.path().join("2023-05-17.md").exists());
+        assert!(!temp.path().join("archive").exists());
+    }
+
+    #[test]
+    fn collision_aborts_before_any_move() {
+        let (temp, repo) = setup(JournalMode::Daily);
+        fs::write(temp.path().join("2022-03-01.md"), "a").unwrap();
+        fs::write(temp.path().join("2023-05-17.md"), "b").unwrap();
+        fs::create_dir_all(temp.path().join("archive/2023")).unwrap();
+        fs::write(temp.path().join("archive/2023/2023-05-17.md"), "old").unwrap();
+
+        let err = archive_notes(
+            &repo,
+            ArchiveOptions {
+                before: date(2024, 1, 1),
+                dry_run: false,
+            },
+        )
+        .unwrap_err();
+
+        assert!(err.to_string().contains("archive/2023/2023-05-17.md"));
+        assert!(temp.path().join("2022-03-01.md").exists());
+        assert!(!temp.path().join("archive/2022").exists());
+    }
+
+    #[test]
+    fn rejects_single_mode() {
+        let (_temp, repo) = setup(JournalMode::Single);
+        let result = archive_notes(
+            &repo,
+            ArchiveOptions {
+                before: date(2024, 1, 1),
+                dry_run: false,
+            },
+        );
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,414 @@
+//! Quick capture use case
+//!
+//! `djour capture` edits a scratch buffer and appends what was written to
+//! today's note, under today's weekday section in weekly mode and under the
+//! `[sections]` heading for the time of day in daily mode.
+
+use crate::application::open_note::{ensure_note_created, new_note_content};
+use crate::application::tag_footer::refresh_tag_footer;
+use crate::domain::markdown::preserve_final_newline;
+use crate::domain::mode_migration::{expected_weekly, week_start};
+use crate::domain::tags::split_at_tag_footer;
+use crate::domain::{find_section, load_template_for_week, Diagnostics, JournalMode};
+use crate::error::Result;
+use crate::infrastructure::{Config, FileSystemRepository, JournalRepository};
+use chrono::{NaiveDate, NaiveDateTime};
+
+/// Custom capture template, looked up in `.djour/templates`
+const CAPTURE_TEMPLATE: &str = "capture.md";
+
+/// Where a capture was appended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaptureOutcome {
+    /// Note filename relative to the journal root
+    pub filename: String,
+    /// Heading the entry was added under (weekly mode, or a daily `[sections]`
+    /// heading), without the `#` marker
+    pub section: Option<String>,
+}
+
+/// Edit a buffer prefilled with a timestamp heading and the capture template,
+/// then append it to today's note.
+///
+/// The entry goes at the end of the section for `now` when the note has its
+/// heading: today's weekday in weekly mode, the first `[sections]` range
+/// containing the time in daily mode. Otherwise it goes at the end of the note.
+///
+/// `edit` gets the initial buffer and returns the saved text, or None when the
+/// edit was aborted. Returns None, leaving every file untouched, when the edit
+/// was aborted or nothing beyond the prefilled scaffold was written. Issues of
+/// the template today's note is created from go to `diagnostics`.
+pub fn capture<F>(
+    repository: &FileSystemRepository,
+    now: NaiveDateTime,
+    diagnostics: &mut Diagnostics,
+    edit: F,
+) -> Result<Option<CaptureOutcome>>
+where
+    F: FnOnce(&str) -> Result<Option<String>>,
+{
+    let config = repository.load_config()?;
+    let today = now.date();
+    let filename = config.naming().filename_for_date(today);
+    repository.ensure_writable("capture", &repository.root().join(&filename))?;
+
+    let section = target_section(repository, &config, &filename, now, diagnostics)?;
+    let scaffold = capture_scaffold(repository, &config, now, section.as_ref())?;
+    let Some(buffer) = edit(&scaffold)? else {
+        return Ok(None);
+    };
+    if !has_captured_text(&buffer, &scaffold) {
+        return Ok(None);
+    }
+
+    ensure_note_created(
+        repository,
+        &config,
+        today,
+        &filename,
+        today,
+        diagnostics,
+        |_, _| true,
+    )?;
+    let section = section.map(|section| section.title);
+    let existing = repository.read_note(&filename)?;
+    let (body, footer) = split_at_tag_footer(&existing);
+    let (updated, found) = append_entry(body, section.as_deref(), buffer.trim());
+    let content = if footer.is_empty() {
+        updated
+    } else {
+        format!("{}\n\n{}", updated.trim_end(), footer)
+    };
+    let content = preserve_final_newline(&existing, refresh_tag_footer(&config, content));
+    repository.write_note(&filename, &content)?;
+
+    Ok(Some(CaptureOutcome {
+        filename,
+        section: section.filter(|_| found),
+    }))
+}
+
+/// Section of today's note a capture at `now` goes under
+struct TargetSection {
+    /// Heading text without the `#` marker
+    title: String,
+    level: usize,
+}
+
+/// Where a capture at `now` goes: today's weekday section in weekly mode, or
+/// in daily mode the `[sections]` heading for the time of day when today's
+/// note (or the template it will be created from) has it
+fn target_section(
+    repository: &FileSystemRepository,
+    config: &Config,
+    filename: &str,
+    now: NaiveDateTime,
+    diagnostics: &mut Diagnostics,
+) -> Result<Option<TargetSection>> {
+    match config.get_mode() {
+        JournalMode::Weekly => {
+            Ok(weekday_heading(config, now.date()).map(|title| TargetSection { title, level: 2 }))
+        }
+        JournalMode::Daily => {
+            let Some(title) = config.sections.section_at(now.time()) else {
+                return Ok(None);
+            };
+            let content = if repository.note_exists(filename) {
+                repository.read_note(filename)?
+            } else {
+                new_note_content(repository, config, now.date(), diagnostics)?
+            };
+            Ok(find_section(&content, title).map(|span| TargetSection {
+                title: title.trim().to_string(),
+                level: span.level,
+            }))
+        }
+        JournalMode::Monthly | JournalMode::Single => Ok(None),
+    }
+}
+
+/// Initial buffer: a timestamp heading, then the rendered capture template
+fn capture_scaffold(
+    repository: &FileSystemRepository,
+    config: &Config,
+    now: NaiveDateTime,
+    section: Option<&TargetSection>,
+) -> Result<String> {
+    // Weekly notes already have a `##` section per day, so the time goes one level down,
+    // like below a daily section; notes spanning several days also get the date
+    let heading = match (config.get_mode(), section) {
+        (JournalMode::Daily, Some(section)) => {
+            format!(
+                "{} {}",
+                "#".repeat((section.level + 1).min(6)),
+                now.format("%H:%M")
+            )
+        }
+        (JournalMode::Daily, None) => format!("## {}", now.format("%H:%M")),
+        (JournalMode::Weekly, _) => format!("### {}", now.format("%H:%M")),
+        (JournalMode::Monthly | JournalMode::Single, _) => {
+            format!("## {}", now.format("%d-%m-%Y %H:%M"))
+        }
+    };
+    let template = load_template_for_week(repository.root(), CAPTURE_TEMPLATE, config.week_start)?
+        .render_with_context(&config.render_context(now.date()));
+    Ok(format!("{}\n\n{}", heading, template))
+}
+
+/// Whether `buffer` has a non-blank line that is not part of the scaffold
+fn has_captured_text(buffer: &str, scaffold: &str) -> bool {
+    let scaffold_lines: Vec<&str> = scaffold.lines().map(str::trim).collect();
+    buffer
+        .lines()
+        .map(str::trim)
+        .any(|line| !line.is_empty() && !scaffold_lines.contains(&line))
+}
+
+/// Text of the `## Weekday (date)` heading for `date` in weekly mode
+pub(crate) fn weekday_heading(config: &Config, date: NaiveDate) -> Option<String> {
+    let start = week_start(date, config.week_start);
+    let expected = expected_weekly(
+        start,
+        config.week_start,
+        config.week_numbering,
+        config.locale,
+    );
+    let index = (date - start).num_days() as usize;
+    expected
+        .weekday_headings
+        .get(index)
+        .map(|heading| heading.trim_start_matches('#').trim().to_string())
+}
+
+/// Append `entry` to `body`: at the end of the section titled `section` when
+/// the body has that heading, otherwise at the end of the body.
+///
+/// A section ends at the next heading of the same or a higher level (see
+/// [`find_section`]). Returns the new body and whether the heading was found.
+pub(crate) fn append_entry(body: &str, section: Option<&str>, entry: &str) -> (String, bool) {
+    let section_end = section
+        .and_then(|title| find_section(body, title))
+        .map(|span| span.end);
+
+    let end = section_end.unwrap_or(body.len());
+    let before = body[..end].trim_end();
+    let after = &body[end..];
+    let mut out = String::with_capacity(body.len() + entry.len() + 4);
+    if !before.is_empty() {
+        out.push_str(before);
+        out.push_str("\n\n");
+    }
+    out.push_str(entry);
+    out.push('\n');
+    if !after.is_empty() {
+        out.push('\n');
+        out.push_str(after);
+    }
+    (out, section_end.is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::init;
+    use crate::domain::DaySections;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn at(y: i32, m: u32, d: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, m, d)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    fn journal(mode: JournalMode) -> (TempDir, FileSystemRepository) {
+        let temp = TempDir::new().unwrap();
+        init(temp.path(), mode).unwrap();
+        let repo = FileSystemRepository::new(temp.path().to_path_buf());
+        (temp, repo)
+    }
+
+    #[test]
+    fn test_append_entry_goes_to_end_of_section() {
+        let body = "# Week\n\n## Monday\n\nearlier\n\n## Tuesday\n\n";
+        let (out, found) = append_entry(body, Some("Monday"), "### 09:00\n\nnew");
+        assert!(found);
+        assert_eq!(
+            out,
+            "# Week\n\n## Monday\n\nearlier\n\n### 09:00\n\nnew\n\n## Tuesday\n\n"
+        );
+
+        let (out, found) = append_entry(body, Some("Tuesday"), "last");
+        assert!(found);
+        assert_eq!(
+            out,
+            "# Week\n\n## Monday\n\nearlier\n\n## Tuesday\n\nlast\n"
+        );
+    }
+
+    #[test]
+    fn test_append_entry_without_section_appends_at_end() {
+        let (out, found) = append_entry("# Day\n", Some("Friday"), "entry");
+        assert!(!found);
+        assert_eq!(out, "# Day\n\nentry\n");
+        assert_eq!(
+            append_entry("", None, "entry"),
+            ("entry\n".to_string(), false)
+        );
+    }
+
+    #[test]
+    fn test_scaffold_only_buffer_captures_nothing() {
+        let scaffold = "## 09:30\n\nMood:\n";
+        assert!(!has_captured_text(scaffold, scaffold));
+        assert!(!has_captured_text("## 09:30\n\n  \n", scaffold));
+        assert!(has_captured_text("## 09:30\n\nMood: good\n", scaffold));
+    }
+
+    #[test]
+    fn test_capture_uses_custom_template_and_appends_to_daily_note() {
+        let (temp, repo) = journal(JournalMode::Daily);
+        fs::create_dir_all(temp.path().join(".djour/templates")).unwrap();
+        fs::write(
+            temp.path().join(".djour/templates/capture.md"),
+            "Logged on {DATE}\n",
+        )
+        .unwrap();
+
+        let mut seen = String::new();
+        let outcome = capture(
+            &repo,
+            at(2025, 1, 15, 9, 30),
+            &mut Diagnostics::new(),
+            |initial| {
+                seen = initial.to_string();
+                Ok(Some(format!("{}Idea #work\n", initial)))
+            },
+        )
+        .unwrap()
+        .unwrap();
+
+        assert!(seen.starts_with("## 09:30\n\nLogged on "), "{:?}", seen);
+        assert_eq!(outcome.filename, "2025-01-15.md");
+        assert_eq!(outcome.section, None);
+        let note = fs::read_to_string(temp.path().join("2025-01-15.md")).unwrap();
+        assert!(note.starts_with("# "), "{}", note);
+        assert!(
+            note.ends_with("## 09:30\n\nLogged on January 15, 2025\nIdea #work\n"),
+            "{:?}",
+            note
+        );
+    }
+
+    #[test]
+    fn test_capture_in_weekly_mode_lands_under_weekday() {
+        let (temp, repo) = journal(JournalMode::Weekly);
+        let outcome = capture(
+            &repo,
+            at(2025, 1, 15, 14, 5),
+            &mut Diagnostics::new(),
+            |initial| Ok(Some(format!("{}Call the bank\n", initial))),
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(outcome.filename, "2025-W03-2025-01-13.md");
+        let section = outcome.section.unwrap();
+        assert!(section.starts_with("Wednesday"), "{}", section);
+        let note = fs::read_to_string(temp.path().join(&outcome.filename)).unwrap();
+        let entry = note.find("### 14:05\n\nCall the bank").unwrap();
+        assert!(note.find(&format!("## {}", section)).unwrap() < entry);
+        assert!(entry < note.find("## Thursday").unwrap());
+    }
+
+    #[test]
+    fn test_daily_capture_goes_to_time_of_day_section() {
+        let (temp, repo) = journal(JournalMode::Daily);
+        fs::create_dir_all(temp.path().join(".djour/templates")).unwrap();
+        fs::write(
+            temp.path().join(".djour/templates/daily.md"),
+            "# {DATE}\n\n## Morning\n\n## Evening\n",
+        )
+        .unwrap();
+        let mut config = repo.load_config().unwrap();
+        config.sections = DaySections(vec![
+            ("Morning".to_string(), "05:00-12:00".parse().unwrap()),
+            ("Evening".to_string(), "18:00-02:00".parse().unwrap()),
+        ]);
+        config.save_to_dir(temp.path()).unwrap();
+
+        let mut scaffolds = Vec::new();
+        for (hour, text) in [(9, "Run"), (23, "Read"), (15, "Call"), (10, "Coffee")] {
+            let outcome = capture(
+                &repo,
+                at(2025, 1, 15, hour, 0),
+                &mut Diagnostics::new(),
+                |initial| {
+                    scaffolds.push(initial.lines().next().unwrap().to_string());
+                    Ok(Some(format!("{}{}\n", initial, text)))
+                },
+            )
+            .unwrap()
+            .unwrap();
+            let expected = match hour {
+                9 | 10 => Some("Morning"),
+                23 => Some("Evening"),
+                _ => None,
+            };
+            assert_eq!(outcome.section.as_deref(), expected);
+        }
+
+        assert_eq!(
+            scaffolds,
+            ["### 09:00", "### 23:00", "## 15:00", "### 10:00"]
+        );
+        let note = fs::read_to_string(temp.path().join("2025-01-15.md")).unwrap();
+        assert!(
+            note.ends_with(
+                "## Morning\n\n### 09:00\n\nRun\n\n### 10:00\n\nCoffee\n\n\
+                ## Evening\n\n### 23:00\n\nRead\n\n## 15:00\n\nCall\n"
+            ),
+            "{:?}",
+            note
+        );
+    }
+
+    #[test]
+    fn test_capture_keeps_missing_final_newline() {
+        let (temp, repo) = journal(JournalMode::Daily);
+        fs::write(temp.path().join("2025-01-15.md"), "# Wednesday\n\nEarlier").unwrap();
+
+        capture(
+            &repo,
+            at(2025, 1, 15, 9, 30),
+            &mut Diagnostics::new(),
+            |initial| Ok(Some(format!("{}Later\n", initial))),
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(
+            fs::read_to_string(temp.path().join("2025-01-15.md")).unwrap(),
+            "# Wednesday\n\nEarlier\n\n## 09:30\n\nLater"
+        );
+    }
+
+    #[test]
+    fn test_aborted_or_untouched_capture_writes_nothing() {
+        let (temp, repo) = journal(JournalMode::Daily);
+        let now = at(2025, 1, 15, 9, 30);
+        assert_eq!(
+            capture(&repo, now, &mut Diagnostics::new(), |_| Ok(None)).unwrap(),
+            None
+        );
+        assert_eq!(
+            capture(&repo, now, &mut Diagnostics::new(), |initial| Ok(Some(
+                initial.to_string()
+            )))
+            .unwrap(),
+            None
+        );
+        assert!(!temp.path().join("2025-01-15.md").exists());
+    }
+}
@@ -0,0 +1,24 @@
+//! Clean use case: leftovers of interrupted writes (`djour clean`)
+
+use crate::error::Result;
+use crate::infrastructure::FileSystemRepository;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CleanReport {
+    pub dry_run: bool,
+    /// Repository-relative paths of the orphaned temp files found
+    pub removed: Vec<String>,
+}
+
+/// Delete the temp files interrupted atomic writes left in the journal (see
+/// [`FileSystemRepository::find_orphaned_temp_files`]), or only list them
+/// with `dry_run`.
+pub fn clean_temp_files(repository: &FileSystemRepository, dry_run: bool) -> Result<CleanReport> {
+    let removed = repository.find_orphaned_temp_files()?;
+    if !dry_run {
+        for filename in &removed {
+            repository.remove_temp_file(filename)?;
+        }
+    }
+    Ok(CleanReport { dry_run, removed })
+}
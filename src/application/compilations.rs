@@ -0,0 +1,387 @@
+//! Generated compilations (`djour compile --clean`, `djour compile --refresh-all`)
+//!
+//! Every compilation djour writes records the command that produced it in an
+//! HTML comment on its second line:
+//!
+//! ```text
+//! # Compilation: work AND urgent
+//! <!-- generated by djour compile 'work AND urgent' at 2025-01-17T09:00:00 -->
+//! ```
+//!
+//! When a custom template opens with YAML front matter, the comment follows
+//! its closing fence instead. A file in `.compilations/` counts as generated
+//! when it has that comment, a `# Compilation:` first line, or a
+//! `.manifest.json` sidecar. Other files there are never deleted or rewritten.
+
+use crate::application::compile_replay::{command_line, parse_command_line};
+use crate::application::compile_tags::{
+    compilation_output_path, manifest_path, CompileOptions, COMPILATIONS_DIR,
+};
+use crate::error::{DjourError, Result};
+use crate::infrastructure::{FileSystemRepository, JournalRepository};
+use chrono::NaiveDateTime;
+use std::fmt;
+use std::fs::File;
+use std::hash::{DefaultHasher, Hasher};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+/// Start of the first line of a compilation rendered with the default template
+const TITLE_MARKER: &str = "# Compilation:";
+
+const PROVENANCE_START: &str = "<!-- generated by ";
+const PROVENANCE_END: &str = " -->";
+
+/// Timestamp format of the provenance comment
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+/// A compilation djour generated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Compilation {
+    /// Path relative to the journal root, with `/` separators
+    pub path: String,
+    /// The recorded `djour compile` command line; None for compilations
+    /// written before djour recorded one
+    pub command: Option<String>,
+    pub generated_at: Option<NaiveDateTime>,
+    /// Path of the `.manifest.json` sidecar, when there is one
+    pub manifest: Option<String>,
+}
+
+/// The markdown files in `.compilations/`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompilationFiles {
+    pub generated: Vec<Compilation>,
+    /// Files without any marker of djour, which are left alone
+    pub other: Vec<String>,
+}
+
+/// The provenance comment recording that `options` produced a compilation at `at`
+pub(crate) fn provenance_line(options: &CompileOptions, at: NaiveDateTime) -> String {
+    // A `-->` in the command would end the comment early
+    let command = command_line(options).replace("-->", "--\\>");
+    format!(
+        "{}{} at {}{}",
+        PROVENANCE_START,
+        command,
+        at.format(TIMESTAMP_FORMAT),
+        PROVENANCE_END
+    )
+}
+
+/// The command line and time a provenance comment records
+fn parse_provenance(line: &str) -> Option<(String, NaiveDateTime)> {
+    let inner = line
+        .trim_end()
+        .strip_prefix(PROVENANCE_START)?
+        .strip_suffix(PROVENANCE_END)?;
+    let (command, at) = inner.rsplit_once(" at ")?;
+    let at = NaiveDateTime::parse_from_str(at, TIMESTAMP_FORMAT).ok()?;
+    Some((command.replace("--\\>", "-->"), at))
+}
+
+/// The line of a compilation that holds its provenance comment, given its
+/// first line and the lines after it: the second line, or the one after the
+/// closing fence of YAML front matter (the last line when the fence is missing)
+fn provenance_candidate(first: &str, lines: impl Iterator<Item = String>) -> String {
+    let mut lines = lines;
+    if first.trim_end() != "---" {
+        return lines.next().unwrap_or_default();
+    }
+    let mut last = String::new();
+    for line in lines.by_ref() {
+        let text = line.trim_end();
+        if text == "---" || text == "..." {
+            return lines.next().unwrap_or_default();
+        }
+        last = line;
+    }
+    last
+}
+
+/// `fmt::Write` feeding a hasher, to compare documents without holding them
+struct HashWriter(DefaultHasher);
+
+impl fmt::Write for HashWriter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0.write(s.as_bytes());
+        Ok(())
+    }
+}
+
+/// Whether the file at `path` already holds the document `write_body`
+/// renders, as compiled by `options`, so rewriting it would only move its
+/// timestamp
+pub(crate) fn is_unchanged(
+    path: &Path,
+    options: &CompileOptions,
+    write_body: impl FnOnce(&mut dyn fmt::Write) -> fmt::Result,
+) -> bool {
+    let Ok(file) = File::open(path) else {
+        return false;
+    };
+    let command = command_line(options);
+    let mut reader = BufReader::new(file);
+    let mut old = HashWriter(DefaultHasher::new());
+    let mut recorded = false;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        match reader.read_line(&mut line) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(_) => return false,
+        }
+        if !recorded && parse_provenance(&line).is_some_and(|(found, _)| found == command) {
+            recorded = true;
+            continue;
+        }
+        old.0.write(line.as_bytes());
+    }
+
+    let mut new = HashWriter(DefaultHasher::new());
+    recorded && write_body(&mut new).is_ok() && old.0.finish() == new.0.finish()
+}
+
+/// Sort the markdown files in `.compilations/` (and below) into the ones djour
+/// generated and the rest
+pub fn find_compilations(repository: &FileSystemRepository) -> Result<CompilationFiles> {
+    let mut files = CompilationFiles::default();
+    for path in repository.list_markdown_files_in(COMPILATIONS_DIR)? {
+        let absolute = repository.root().join(&path);
+        let manifest = manifest_path(Path::new(&path));
+        let manifest = repository
+            .root()
+            .join(&manifest)
+            .is_file()
+            .then(|| manifest.to_string_lossy().into_owned());
+
+        let mut lines = BufReader::new(File::open(&absolute)?)
+            .lines()
+            .map_while(|line| line.ok());
+        let first = lines.next().unwrap_or_default();
+        let provenance = parse_provenance(&provenance_candidate(&first, lines));
+
+        if provenance.is_none() && manifest.is_none() && !first.starts_with(TITLE_MARKER) {
+            files.other.push(path);
+            continue;
+        }
+        let (command, generated_at) = provenance.unzip();
+        files.generated.push(Compilation {
+            path,
+            command,
+            generated_at,
+            manifest,
+        });
+    }
+    Ok(files)
+}
+
+/// Delete generated compilations along with their manifests
+pub fn remove_compilations(
+    repository: &FileSystemRepository,
+    compilations: &[Compilation],
+) -> Result<()> {
+    for compilation in compilations {
+        repository.remove_file(&compilation.path)?;
+        if let Some(manifest) = &compilation.manifest {
+            repository.remove_file(manifest)?;
+        }
+    }
+    Ok(())
+}
+
+/// The options that regenerate `compilation` in place from its recorded
+/// command, writing to its current path should it have been moved
+pub fn refresh_options(
+    repository: &FileSystemRepository,
+    compilation: &Compilation,
+) -> Result<CompileOptions> {
+    let command = compilation.command.as_deref().ok_or_else(|| {
+        DjourError::Config("No query recorded; compile its query once to record one".to_string())
+    })?;
+    let mut options = parse_command_line(command)
+        .map_err(|reason| DjourError::Config(format!("Recorded command {}", reason)))?;
+    if compilation_output_path(repository, &options) != repository.root().join(&compilation.path) {
+        options.output = Some(PathBuf::from(&compilation.path));
+    }
+    Ok(options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::tags::{CompilationFormat, CompileBudget, ContextOptions};
+    use crate::domain::JournalMode;
+    use crate::infrastructure::Config;
+    use chrono::NaiveDate;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn options(query: &str) -> CompileOptions {
+        CompileOptions {
+            query: query.to_string(),
+            output: None,
+            from: None,
+            to: None,
+            changed_since: None,
+            format: CompilationFormat::Chronological,
+            group_by: None,
+            date_format: None,
+            context: ContextOptions::off(),
+            recursive: false,
+            include_archived: false,
+            manifest: false,
+            strict: false,
+            embed_links: false,
+            notes: Vec::new(),
+            exclude: Vec::new(),
+            title_filter: None,
+            attribution: true,
+            budget: CompileBudget::default(),
+            suggest_limit: 5,
+            include_private: false,
+            allow_exclusion_only: false,
+        }
+    }
+
+    fn at() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 1, 17)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_provenance_line_round_trip() {
+        let line = provenance_line(&options("work AND urgent"), at());
+        assert_eq!(
+            line,
+            "<!-- generated by djour compile 'work AND urgent' at 2025-01-17T09:00:00 -->"
+        );
+        assert_eq!(
+            parse_provenance(&line),
+            Some(("djour compile 'work AND urgent'".to_string(), at()))
+        );
+
+        let arrow = provenance_line(&options("a-->b"), at());
+        assert_eq!(arrow.matches("-->").count(), 1, "{}", arrow);
+        assert_eq!(parse_provenance(&arrow).unwrap().0, "djour compile 'a-->b'");
+
+        assert_eq!(parse_provenance("<!-- a note -->"), None);
+        assert_eq!(parse_provenance("plain text"), None);
+    }
+
+    #[test]
+    fn test_find_compilations_detects_markers() {
+        let temp = TempDir::new().unwrap();
+        let repo = FileSystemRepository::new(temp.path().to_path_buf());
+        repo.initialize().unwrap();
+        repo.save_config(&Config::new(JournalMode::Daily)).unwrap();
+        let dir = temp.path().join(COMPILATIONS_DIR);
+        fs::create_dir_all(&dir).unwrap();
+        let provenance = provenance_line(&options("work"), at());
+
+        fs::write(dir.join("titled.md"), "# Compilation: old\n\nBody\n").unwrap();
+        fs::write(
+            dir.join("custom.md"),
+            format!("ACME report\n{}\n\nBody\n", provenance),
+        )
+        .unwrap();
+        fs::write(
+            dir.join("fronted.md"),
+            format!("---\ntitle: Work\n---\n{}\n\nBody\n", provenance),
+        )
+        .unwrap();
+        fs::write(dir.join("sidecar.md"), "Body\n").unwrap();
+        fs::write(dir.join("sidecar.md.manifest.json"), "{}").unwrap();
+        fs::write(dir.join("mine.md"), "# My notes\n").unwrap();
+        fs::write(dir.join("empty.md"), "").unwrap();
+
+        let files = find_compilations(&repo).unwrap();
+        let generated: Vec<_> = files.generated.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(
+            generated,
+            [
+                ".compilations/custom.md",
+                ".compilations/fronted.md",
+                ".compilations/sidecar.md",
+                ".compilations/titled.md"
+            ]
+        );
+        assert_eq!(
+            files.generated[0].command.as_deref(),
+            Some("djour compile work")
+        );
+        assert_eq!(files.generated[0].generated_at, Some(at()));
+        assert_eq!(files.generated[1].generated_at, Some(at()));
+        assert_eq!(
+            files.generated[2].manifest.as_deref(),
+            Some(".compilations/sidecar.md.manifest.json")
+        );
+        assert_eq!(files.generated[3].command, None);
+        assert_eq!(
+            files.other,
+            [".compilations/empty.md", ".compilations/mine.md"]
+        );
+
+        remove_compilations(&repo, &files.generated).unwrap();
+        let mut left: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        left.sort();
+        assert_eq!(left, ["empty.md", "mine.md"]);
+    }
+
+    #[test]
+    fn test_is_unchanged_ignores_only_the_provenance_line() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("work.md");
+        let body = |out: &mut dyn fmt::Write| out.write_str("# Compilation: work\n\nBody\n");
+        assert!(!is_unchanged(&path, &options("work"), body));
+
+        let provenance = provenance_line(&options("work"), at());
+        fs::write(
+            &path,
+            format!("# Compilation: work\n{}\n\nBody\n", provenance),
+        )
+        .unwrap();
+        assert!(is_unchanged(&path, &options("work"), body));
+        assert!(!is_unchanged(&path, &options("errand"), body));
+        assert!(!is_unchanged(&path, &options("work"), |out| out
+            .write_str("# Compilation: work\n\nNew body\n")));
+    }
+
+    #[test]
+    fn test_refresh_options_write_back_in_place() {
+        let repo = FileSystemRepository::new(PathBuf::from("/journal"));
+        let mut compilation = Compilation {
+            path: ".compilations/work.md".to_string(),
+            command: Some("djour compile work --format grouped".to_string()),
+            generated_at: Some(at()),
+            manifest: None,
+        };
+        let refreshed = refresh_options(&repo, &compilation).unwrap();
+        assert_eq!(refreshed.query, "work");
+        assert_eq!(refreshed.format, CompilationFormat::Grouped);
+        assert_eq!(refreshed.output, None);
+
+        compilation.path = ".compilations/renamed.md".to_string();
+        assert_eq!(
+            refresh_options(&repo, &compilation).unwrap().output,
+            Some(PathBuf::from(".compilations/renamed.md"))
+        );
+
+        compilation.command = None;
+        let err = refresh_options(&repo, &compilation)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("No query recorded"), "{}", err);
+        compilation.command = Some("djour compile work --bogus".to_string());
+        let err = refresh_options(&repo, &compilation)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("unknown option --bogus"), "{}", err);
+    }
+}
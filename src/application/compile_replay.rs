@@ -0,0 +1,501 @@
+//! Saved compile invocations for `djour compile --again`
+//!
+//! Every compile that writes a compilation records its options in the cache
+//! directory (`last_compile.toml`); `--save-as NAME` also keeps them in a
+//! named slot (`compile-NAME.toml`). Dates are stored resolved, so a replayed
+//! `--changed-since yesterday` means the day it first ran. Paths are stored
+//! as given: `--output` and `--note` are relative to the journal root, which
+//! replay resolves again.
+
+use crate::application::CompileOptions;
+use crate::domain::tags::{
+    CompilationFormat, CompileBudget, ContextDepth, ContextOptions, ContextStyle, KeepOrder,
+};
+use crate::error::{DjourError, Result};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Cache file holding the most recent compile
+pub const LAST_COMPILE_FILE: &str = "last_compile.toml";
+
+/// Version of the saved format; files with another version are ignored
+const SAVED_COMPILE_VERSION: u32 = 1;
+
+/// `--suggest-limit` when not given
+const DEFAULT_SUGGEST_LIMIT: usize = 5;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedFile {
+    version: u32,
+    options: CompileOptions,
+}
+
+/// What the cache holds for a slot
+// Read once per run, so the size of `Found` does not matter
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SavedCompile {
+    Found(CompileOptions),
+    /// Nothing was saved
+    Missing,
+    /// A file exists but cannot be used; the reason
+    Stale(String),
+}
+
+/// Check a `--save-as` / `--again` slot name
+pub fn check_slot_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(DjourError::Config(format!(
+            "Invalid compile slot name: '{}'. Use letters, digits, '-' and '_'",
+            name
+        )))
+    }
+}
+
+/// Cache file for `slot` (`None` = the last compile)
+pub fn saved_compile_path(cache_dir: &Path, slot: Option<&str>) -> PathBuf {
+    match slot {
+        Some(name) => cache_dir.join(format!("compile-{}.toml", name)),
+        None => cache_dir.join(LAST_COMPILE_FILE),
+    }
+}
+
+/// Record `options` as the last compile and, with `slot`, under that name too
+pub fn save_compile(cache_dir: &Path, slot: Option<&str>, options: &CompileOptions) -> Result<()> {
+    if let Some(name) = slot {
+        check_slot_name(name)?;
+    }
+    let saved = SavedFile {
+        version: SAVED_COMPILE_VERSION,
+        options: options.clone(),
+    };
+    let contents = toml::to_string_pretty(&saved)
+        .map_err(|e| DjourError::Config(format!("Failed to serialize compile options: {}", e)))?;
+    fs::create_dir_all(cache_dir)?;
+    fs::write(saved_compile_path(cache_dir, None), &contents)?;
+    if slot.is_some() {
+        fs::write(saved_compile_path(cache_dir, slot), &contents)?;
+    }
+    Ok(())
+}
+
+/// Read the compile saved in `slot` (`None` = the last compile)
+pub fn load_compile(cache_dir: &Path, slot: Option<&str>) -> Result<SavedCompile> {
+    if let Some(name) = slot {
+        check_slot_name(name)?;
+    }
+    let path = saved_compile_path(cache_dir, slot);
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(SavedCompile::Missing),
+        Err(e) => return Err(e.into()),
+    };
+    Ok(parse_saved(&contents))
+}
+
+fn parse_saved(contents: &str) -> SavedCompile {
+    let table: toml::Table = match toml::from_str(contents) {
+        Ok(table) => table,
+        Err(e) => return SavedCompile::Stale(format!("cannot be read: {}", e.message())),
+    };
+    match table.get("version").and_then(toml::Value::as_integer) {
+        Some(version) if version == i64::from(SAVED_COMPILE_VERSION) => {}
+        Some(version) => {
+            return SavedCompile::Stale(format!(
+                "saved in format version {} (this djour uses {})",
+                version, SAVED_COMPILE_VERSION
+            ))
+        }
+        None => return SavedCompile::Stale("has no format version".to_string()),
+    }
+    match toml::Value::Table(table).try_into::<SavedFile>() {
+        Ok(saved) => SavedCompile::Found(saved.options),
+        Err(e) => SavedCompile::Stale(format!("cannot be read: {}", e.message())),
+    }
+}
+
+/// The `djour compile` command line that produces `options`
+pub fn command_line(options: &CompileOptions) -> String {
+    let mut args = vec![
+        "djour".to_string(),
+        "compile".to_string(),
+        quote(&options.query),
+    ];
+    let mut flag = |name: &str, value: Option<String>| {
+        args.push(format!("--{}", name));
+        if let Some(value) = value {
+            args.push(quote(&value));
+        }
+    };
+    let date = |date: chrono::NaiveDate| date.format("%d-%m-%Y").to_string();
+
+    if let Some(output) = &options.output {
+        flag("output", Some(output.to_string_lossy().into_owned()));
+    }
+    if let Some(from) = options.from {
+        flag("from", Some(date(from)));
+    }
+    if let Some(to) = options.to {
+        flag("to", Some(date(to)));
+    }
+    if let Some(since) = options.changed_since {
+        flag("changed-since", Some(date(since)));
+    }
+    if options.format != CompilationFormat::Chronological {
+        flag("format", Some(options.format.as_str().to_string()));
+    }
+    if let Some(group_by) = options.group_by {
+        flag("group-by", Some(group_by.as_str().to_string()));
+    }
+    if let Some(format) = &options.date_format {
+        flag("date-format", Some(format.clone()));
+    }
+    match options.context.depth {
+        ContextDepth::Levels(0) => {}
+        ContextDepth::Levels(1) => flag("include-context", None),
+        ContextDepth::Levels(n) => flag("context-depth", Some(n.to_string())),
+        ContextDepth::All => flag("context-depth", Some("all".to_string())),
+    }
+    if options.context.style == ContextStyle::Breadcrumb {
+        flag("context-style", Some("breadcrumb".to_string()));
+    }
+    if options.include_archived {
+        flag("include-archived", None);
+    } else if options.recursive {
+        flag("recursive", None);
+    }
+    if options.manifest {
+        flag("manifest", None);
+    }
+    if options.strict {
+        flag("strict", None);
+    }
+    if options.embed_links {
+        flag("embed-links", None);
+    }
+    for note in &options.notes {
+        flag("note", Some(note.clone()));
+    }
+    for pattern in &options.exclude {
+        flag("exclude", Some(pattern.clone()));
+    }
+    if let Some(title) = &options.title_filter {
+        flag("title-filter", Some(title.clone()));
+    }
+    if !options.attribution {
+        flag("no-attribution", None);
+    }
+    if let Some(n) = options.budget.max_blocks {
+        flag("max-blocks", Some(n.to_string()));
+    }
+    if let Some(n) = options.budget.max_bytes {
+        flag("max-bytes", Some(n.to_string()));
+    }
+    if options.budget.keep == KeepOrder::Oldest {
+        flag("keep", Some("oldest".to_string()));
+    }
+    if options.suggest_limit != DEFAULT_SUGGEST_LIMIT {
+        flag("suggest-limit", Some(options.suggest_limit.to_string()));
+    }
+    if options.include_private {
+        flag("include-private", None);
+    }
+    if options.allow_exclusion_only {
+        flag("allow-exclusion-only", None);
+    }
+    args.join(" ")
+}
+
+/// `arg` as typed in a POSIX shell, quoted only when it needs to be
+fn quote(arg: &str) -> String {
+    let plain = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=,@+%".contains(c));
+    if plain {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+/// The options of a `djour compile` command line as [`command_line`] writes
+/// it: the query first, then only the flags it emits. Paths stay as written.
+pub fn parse_command_line(line: &str) -> std::result::Result<CompileOptions, String> {
+    let words = split_words(line)?;
+    let mut words = words.into_iter();
+    if words.next().as_deref() != Some("djour") || words.next().as_deref() != Some("compile") {
+        return Err("does not start with 'djour compile'".to_string());
+    }
+    let query = words.next().ok_or("has no query")?;
+    let mut options = CompileOptions {
+        query,
+        output: None,
+        from: None,
+        to: None,
+        changed_since: None,
+        format: CompilationFormat::Chronological,
+        group_by: None,
+        date_format: None,
+        context: ContextOptions::off(),
+        recursive: false,
+        include_archived: false,
+        manifest: false,
+        strict: false,
+        embed_links: false,
+        notes: Vec::new(),
+        exclude: Vec::new(),
+        title_filter: None,
+        attribution: true,
+        budget: CompileBudget::default(),
+        suggest_limit: DEFAULT_SUGGEST_LIMIT,
+        include_private: false,
+        allow_exclusion_only: false,
+    };
+
+    while let Some(word) = words.next() {
+        let name = word
+            .strip_prefix("--")
+            .ok_or_else(|| format!("has an unexpected argument '{}'", word))?;
+        let mut value = || {
+            words
+                .next()
+                .ok_or_else(|| format!("has no value for --{}", name))
+        };
+        let date = |value: String| {
+            NaiveDate::parse_from_str(&value, "%d-%m-%Y")
+                .map_err(|_| format!("has an invalid date for --{}: {}", name, value))
+        };
+        let number = |value: String| {
+            value
+                .parse::<usize>()
+                .map_err(|_| format!("has an invalid number for --{}: {}", name, value))
+        };
+        match name {
+            "output" => options.output = Some(PathBuf::from(value()?)),
+            "from" => options.from = Some(date(value()?)?),
+            "to" => options.to = Some(date(value()?)?),
+            "changed-since" => options.changed_since = Some(date(value()?)?),
+            "format" => options.format = value()?.parse()?,
+            "group-by" => options.group_by = Some(value()?.parse()?),
+            "date-format" => options.date_format = Some(value()?),
+            "include-context" => options.context.depth = ContextDepth::Levels(1),
+            "context-depth" => options.context.depth = value()?.parse()?,
+            "context-style" => match value()?.as_str() {
+                "headings" => options.context.style = ContextStyle::Headings,
+                "breadcrumb" => options.context.style = ContextStyle::Breadcrumb,
+                other => return Err(format!("has an invalid context style: {}", other)),
+            },
+            "recursive" => options.recursive = true,
+            "include-archived" => {
+                options.recursive = true;
+                options.include_archived = true;
+            }
+            "manifest" => options.manifest = true,
+            "strict" => options.strict = true,
+            "embed-links" => options.embed_links = true,
+            "note" => options.notes.push(value()?),
+            "exclude" => options.exclude.push(value()?),
+            "title-filter" => options.title_filter = Some(value()?),
+            "no-attribution" => options.attribution = false,
+            "max-blocks" => options.budget.max_blocks = Some(number(value()?)?),
+            "max-bytes" => options.budget.max_bytes = Some(number(value()?)?),
+            "keep" => options.budget.keep = value()?.parse()?,
+            "suggest-limit" => options.suggest_limit = number(value()?)?,
+            "include-private" => options.include_private = true,
+            "allow-exclusion-only" => options.allow_exclusion_only = true,
+            _ => return Err(format!("has an unknown option --{}", name)),
+        }
+    }
+    Ok(options)
+}
+
+/// Split a command line into words the way a POSIX shell does for the
+/// quoting [`quote`] produces: single quotes and backslash escapes
+fn split_words(line: &str) -> std::result::Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => words.extend(word.take()),
+            '\'' => {
+                let current = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => current.push(c),
+                        None => return Err("has an unclosed quote".to_string()),
+                    }
+                }
+            }
+            '\\' => {
+                let escaped = chars.next().ok_or("ends with a backslash")?;
+                word.get_or_insert_with(String::new).push(escaped);
+            }
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::tags::GroupBy;
+    use tempfile::TempDir;
+
+    fn options() -> CompileOptions {
+        CompileOptions {
+            query: "work AND NOT meeting".to_string(),
+            output: Some(PathBuf::from("reports/work.md")),
+            from: NaiveDate::from_ymd_opt(2025, 1, 6),
+            to: None,
+            changed_since: NaiveDate::from_ymd_opt(2025, 1, 10),
+            format: CompilationFormat::Grouped,
+            group_by: None,
+            date_format: None,
+            context: ContextOptions {
+                depth: ContextDepth::All,
+                style: ContextStyle::Breadcrumb,
+            },
+            recursive: true,
+            include_archived: false,
+            manifest: false,
+            strict: false,
+            embed_links: false,
+            notes: Vec::new(),
+            exclude: vec!["drafts/**".to_string()],
+            title_filter: Some("Team's log".to_string()),
+            attribution: true,
+            budget: CompileBudget {
+                max_blocks: Some(20),
+                max_bytes: None,
+                keep: KeepOrder::Oldest,
+            },
+            suggest_limit: 5,
+            include_private: false,
+            allow_exclusion_only: false,
+        }
+    }
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let temp = TempDir::new().unwrap();
+        let cache = temp.path().join("cache");
+        save_compile(&cache, Some("weekly"), &options()).unwrap();
+
+        let saved = fs::read_to_string(cache.join(LAST_COMPILE_FILE)).unwrap();
+        assert!(saved.starts_with("version = 1\n"), "{}", saved);
+        assert!(saved.contains("from = \"2025-01-06\""), "{}", saved);
+        assert_eq!(
+            load_compile(&cache, None).unwrap(),
+            SavedCompile::Found(options())
+        );
+        assert_eq!(
+            load_compile(&cache, Some("weekly")).unwrap(),
+            SavedCompile::Found(options())
+        );
+        assert_eq!(
+            load_compile(&cache, Some("monthly")).unwrap(),
+            SavedCompile::Missing
+        );
+        assert!(load_compile(&cache, Some("../x")).is_err());
+
+        let mut levels = options();
+        levels.context.depth = ContextDepth::Levels(2);
+        levels.to = NaiveDate::from_ymd_opt(2025, 1, 31);
+        save_compile(&cache, None, &levels).unwrap();
+        assert_eq!(
+            load_compile(&cache, None).unwrap(),
+            SavedCompile::Found(levels)
+        );
+    }
+
+    #[test]
+    fn test_other_versions_and_damaged_files_are_stale() {
+        let saved = toml::to_string(&SavedFile {
+            version: SAVED_COMPILE_VERSION,
+            options: options(),
+        })
+        .unwrap();
+        assert_eq!(parse_saved(&saved), SavedCompile::Found(options()));
+
+        let newer = saved.replace("version = 1", "version = 2");
+        assert_eq!(
+            parse_saved(&newer),
+            SavedCompile::Stale("saved in format version 2 (this djour uses 1)".to_string())
+        );
+        assert!(matches!(
+            parse_saved("[options]\nquery = \"work\"\n"),
+            SavedCompile::Stale(_)
+        ));
+        assert!(matches!(
+            parse_saved("version = 1\n[options]\nquery = \"work\"\n"),
+            SavedCompile::Stale(_)
+        ));
+        assert!(matches!(parse_saved("version = "), SavedCompile::Stale(_)));
+    }
+
+    #[test]
+    fn test_command_line_lists_non_default_options() {
+        assert_eq!(
+            command_line(&options()),
+            "djour compile 'work AND NOT meeting' --output reports/work.md --from 06-01-2025 \
+             --changed-since 10-01-2025 --format grouped --context-depth all \
+             --context-style breadcrumb --recursive --exclude 'drafts/**' \
+             --title-filter 'Team'\\''s log' --max-blocks 20 --keep oldest"
+        );
+        let by_hour = CompileOptions {
+            format: CompilationFormat::Chronological,
+            group_by: Some(GroupBy::Hour),
+            date_format: None,
+            ..options()
+        };
+        assert!(command_line(&by_hour)
+            .contains(" --from 06-01-2025 --changed-since 10-01-2025 --group-by hour "));
+        let weekdays = CompileOptions {
+            date_format: Some("%A, %d %B %Y".to_string()),
+            ..options()
+        };
+        assert!(command_line(&weekdays).contains(" --format grouped --date-format '%A, %d %B %Y' "));
+    }
+
+    #[test]
+    fn test_parse_command_line_reverses_command_line() {
+        let by_hour = CompileOptions {
+            format: CompilationFormat::Chronological,
+            group_by: Some(GroupBy::Hour),
+            date_format: Some("%A, %d %B %Y".to_string()),
+            context: ContextOptions::from(true),
+            include_archived: true,
+            notes: vec!["notes/it's here.md".to_string()],
+            attribution: false,
+            suggest_limit: 0,
+            include_private: true,
+            ..options()
+        };
+        for options in [options(), by_hour] {
+            assert_eq!(parse_command_line(&command_line(&options)), Ok(options));
+        }
+
+        assert_eq!(
+            parse_command_line("djour compile work").unwrap().query,
+            "work"
+        );
+        assert!(parse_command_line("djour tags work").is_err());
+        assert!(parse_command_line("djour compile 'work").is_err());
+        assert!(parse_command_line("djour compile work --from").is_err());
+        assert!(parse_command_line("djour compile work --watch").is_err());
+        assert!(parse_command_line("djour compile work extra").is_err());
+    }
+}
@@ -0,0 +1,264 @@
+//! Compile rules use case
+//!
+//! Runs the standing compilations of `.djour/rules.toml` over one scan of the notes.
+
+use crate::application::compile_tags::{
+    compilation_output_path, compile_scope, compile_sources, note_sources, write_compilation,
+    CompileOptions, CompileReport,
+};
+use crate::application::scan::{scan_tagged_content, ScanProgress};
+use crate::domain::Diagnostics;
+use crate::error::{DjourError, Result};
+use crate::infrastructure::repository::JournalRepository;
+use crate::infrastructure::rules::RULES_FILE;
+use crate::infrastructure::{CompileRule, FileSystemRepository, RuleSet};
+
+/// Which rules `djour compile` runs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuleSelection {
+    /// `--rule <name>`: a rule matching nothing is an error
+    One(String),
+    /// `--all-rules`: every rule in file order; a rule matching nothing is skipped
+    All,
+}
+
+/// Outcome of one rule
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleReport {
+    pub name: String,
+    /// None when the rule matched nothing and nothing was written for it
+    pub report: Option<CompileReport>,
+}
+
+/// Compile options for `rule`; date filters, recursion, `strict`,
+/// `embed_links` and the budget come from `base`, the command line
+pub fn rule_options(rule: &CompileRule, base: &CompileOptions) -> CompileOptions {
+    CompileOptions {
+        query: rule.query.clone(),
+        output: rule.output.clone(),
+        format: rule.format,
+        group_by: None,
+        context: rule.context,
+        manifest: rule.manifest,
+        ..base.clone()
+    }
+}
+
+/// Run the selected rules, reading the notes once for all of them.
+///
+/// Every rule is compiled before anything is written, so an error leaves all
+/// outputs untouched. Structure warnings are reported once, not per rule.
+pub fn compile_rules(
+    repository: &FileSystemRepository,
+    selection: &RuleSelection,
+    base: &CompileOptions,
+    diagnostics: &mut Diagnostics,
+    progress: Option<ScanProgress<'_>>,
+) -> Result<Vec<RuleReport>> {
+    let rule_set = RuleSet::load_from_dir(repository.root())?;
+    let rules: Vec<&CompileRule> = match selection {
+        RuleSelection::One(name) => vec![rule_set.get(name)?],
+        RuleSelection::All => rule_set.rules.iter().collect(),
+    };
+    if rules.is_empty() {
+        return Err(DjourError::Config(format!(
+            "No rules in {}. Add [[rule]] entries with a name and a query",
+            RULES_FILE
+        )));
+    }
+
+    let options: Vec<CompileOptions> = rules.iter().map(|rule| rule_options(rule, base)).collect();
+    let outputs: Vec<_> = options
+        .iter()
+        .map(|options| compilation_output_path(repository, options))
+        .collect();
+    for output in &outputs {
+        repository.ensure_writable("write", output)?;
+    }
+
+    // One scan for every rule, never reading back any rule's output
+    let config = repository.load_config()?;
+    let scope = compile_scope(
+        repository,
+        &config,
+        base,
+        outputs.iter().map(|path| path.as_path()),
+    )?;
+    let notes = scan_tagged_content(repository, &config, &scope, progress, diagnostics)?;
+    if notes.is_empty() {
+        return Err(DjourError::TagNotFound(
+            "No notes found for compilation rules".to_string(),
+        ));
+    }
+    let sources = note_sources(repository, &config, base, notes, diagnostics)?;
+
+    let mut compiled = Vec::with_capacity(rules.len());
+    for ((rule, options), output) in rules.iter().zip(&options).zip(outputs) {
+        let query = options.parse_query(config.value_tags)?;
+        match compile_sources(
+            repository,
+            &config,
+            options,
+            &query,
+            output,
+            &sources,
+            diagnostics,
+        ) {
+            Ok(document) => compiled.push((rule, options, Some(document))),
+            Err(DjourError::TagNotFound(_)) if *selection == RuleSelection::All => {
+                compiled.push((rule, options, None))
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    let mut reports = Vec::with_capacity(compiled.len());
+    for (rule, options, document) in compiled {
+        if let Some((document, report)) = &document {
+            write_compilation(repository, options, document, report)?;
+        }
+        reports.push(RuleReport {
+            name: rule.name.clone(),
+            report: document.map(|(_, report)| report),
+        });
+    }
+    Ok(reports)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::init;
+    use crate::domain::tags::{CompilationFormat, CompileBudget, ContextOptions};
+    use crate::domain::JournalMode;
+    use std::cell::Cell;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn base() -> CompileOptions {
+        CompileOptions {
+            query: String::new(),
+            output: None,
+            from: None,
+            to: None,
+            changed_since: None,
+            format: CompilationFormat::Chronological,
+            group_by: None,
+            date_format: None,
+            context: ContextOptions::off(),
+            recursive: false,
+            include_archived: false,
+            manifest: false,
+            strict: false,
+            embed_links: false,
+            notes: Vec::new(),
+            exclude: Vec::new(),
+            title_filter: None,
+            attribution: true,
+            budget: CompileBudget::unlimited(),
+            suggest_limit: 0,
+            include_private: false,
+            allow_exclusion_only: false,
+        }
+    }
+
+    fn journal(rules: &str) -> (TempDir, FileSystemRepository) {
+        let temp = TempDir::new().unwrap();
+        init(temp.path(), JournalMode::Daily).unwrap();
+        fs::write(
+            temp.path().join("2025-01-15.md"),
+            "## Alex #1on1\n\nTalked about goals.\n\nFinished a book. #reading\n",
+        )
+        .unwrap();
+        fs::write(
+            temp.path().join("2025-01-16.md"),
+            "Another chapter #reading\n",
+        )
+        .unwrap();
+        fs::write(temp.path().join(RULES_FILE), rules).unwrap();
+        let repo = FileSystemRepository::new(temp.path().to_path_buf());
+        (temp, repo)
+    }
+
+    const RULES: &str = r#"
+[[rule]]
+name = "1on1s"
+query = "1on1"
+output = "people/1on1s.md"
+format = "grouped"
+
+[[rule]]
+name = "reading"
+query = "reading"
+output = "reading-log.md"
+
+[[rule]]
+name = "travel"
+query = "travel"
+"#;
+
+    #[test]
+    fn test_all_rules_share_one_scan() {
+        let (temp, repo) = journal(RULES);
+        let reads = Cell::new(0);
+        let progress = |_: usize, _: usize| reads.set(reads.get() + 1);
+        let mut diagnostics = Diagnostics::new();
+
+        let reports = compile_rules(
+            &repo,
+            &RuleSelection::All,
+            &base(),
+            &mut diagnostics,
+            Some(&progress),
+        )
+        .unwrap();
+
+        // Each note was read once for all three rules
+        assert_eq!(reads.get(), 2);
+        let counts: Vec<_> = reports
+            .iter()
+            .map(|rule| {
+                (
+                    rule.name.as_str(),
+                    rule.report.as_ref().map(|r| r.block_count),
+                )
+            })
+            .collect();
+        assert_eq!(
+            counts,
+            vec![("1on1s", Some(1)), ("reading", Some(2)), ("travel", None)]
+        );
+
+        let one_on_ones = fs::read_to_string(temp.path().join("people/1on1s.md")).unwrap();
+        assert!(one_on_ones.contains("## From: 2025-01-15.md"));
+        assert!(one_on_ones.contains("Talked about goals."));
+        let reading = fs::read_to_string(temp.path().join("reading-log.md")).unwrap();
+        assert!(reading.contains("Another chapter #reading"));
+        assert!(!temp.path().join(".compilations/travel.md").exists());
+    }
+
+    #[test]
+    fn test_single_rule_matching_nothing_is_an_error() {
+        let (_temp, repo) = journal(RULES);
+        let result = compile_rules(
+            &repo,
+            &RuleSelection::One("travel".to_string()),
+            &base(),
+            &mut Diagnostics::new(),
+            None,
+        );
+        assert!(matches!(result, Err(DjourError::TagNotFound(_))));
+
+        let unknown = compile_rules(
+            &repo,
+            &RuleSelection::One("nope".to_string()),
+            &base(),
+            &mut Diagnostics::new(),
+            None,
+        );
+        assert!(unknown
+            .unwrap_err()
+            .to_string()
+            .contains("Unknown rule 'nope'"));
+    }
+}
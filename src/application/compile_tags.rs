@@ -2,18 +2,66 @@
 //!
 //! Orchestrates the full workflow of compiling tagged content from journal entries.
 
+use crate::application::compilations::{is_unchanged, provenance_line};
+use crate::application::list_tags::exclude_patterns;
+use crate::application::note_structure::StructureCheck;
+use crate::application::scan::{scan_notes, ScanProgress, ScanScope, ScannedNote};
+use crate::domain::links::{inline_link_targets, linked_note_path, rewrite_markdown_targets};
+use crate::domain::tags::syntax::tag_in_subtree;
 use crate::domain::tags::{
-    CompilationDateStyle, CompilationFormat, TagCompiler, TagParser, TagQuery, TaggedContent,
+    parse_front_matter, rewrite_wikilinks, CompilationDateStyle, CompilationFormat, CompileBudget,
+    CompileStyle, ContentPayload, ContextDepth, ContextOptions, DateWindow, GroupBy, Redaction,
+    RenderOptions, SourceSpan, TagCompiler, TagContext, TagParseOptions, TagParser, TagQuery,
+    TaggedContent, Truncation, MAX_TAGS_PER_BLOCK,
+};
+use crate::domain::{
+    check_section_dates, check_unclosed_fence, load_template, CompilationTemplateVars, Diagnostics,
+    JournalMode, Template,
 };
-use crate::domain::JournalMode;
 use crate::error::{DjourError, Result};
 use crate::infrastructure::repository::JournalRepository;
-use crate::infrastructure::FileSystemRepository;
-use chrono::NaiveDate;
-use std::path::PathBuf;
+use crate::infrastructure::{Config, FileSystemRepository};
+use chrono::{Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+/// Default directory (relative to the journal root) for compiled output
+pub const COMPILATIONS_DIR: &str = ".compilations";
+
+/// Template wrapped around compiled output (`.djour/templates/compilation.md`)
+const COMPILATION_TEMPLATE: &str = "compilation.md";
+
+/// How many levels of links `--embed-links` follows (links inside embeds stay links)
+const EMBED_DEPTH: usize = 1;
+
+/// Source path given to blocks read by [`compile_document`]
+const STDIN_SOURCE: &str = "stdin";
+
+/// Query word turning section context on for one compilation
+const CONTEXT_DIRECTIVE: &str = "!context";
+
+/// Query word turning section context off for one compilation
+const NO_CONTEXT_DIRECTIVE: &str = "!nocontext";
+
+/// One note fed to the compile pipeline
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoteSource {
+    /// Path relative to the journal root
+    pub path: PathBuf,
+    pub content: String,
+    /// Date the note's blocks are attributed to
+    pub date: Option<NaiveDate>,
+}
 
 /// Options for compilation
-#[derive(Debug, Clone)]
+///
+/// Serializable so `compile --again` can replay a run; dates are stored as
+/// `YYYY-MM-DD`, already resolved.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CompileOptions {
     /// Tag query to filter by
     pub query: String,
@@ -27,36 +75,312 @@ pub struct CompileOptions {
     /// End date filter (inclusive)
     pub to: Option<NaiveDate>,
 
+    /// Only notes whose file was modified on or after this day (local time)
+    pub changed_since: Option<NaiveDate>,
+
     /// Output format
     pub format: CompilationFormat,
 
-    /// Include parent section headings for context
-    pub include_context: bool,
+    /// Sub-headers under each date (`--group-by`, chronological format only)
+    pub group_by: Option<GroupBy>,
+
+    /// strftime format of date headers (`--date-format`), overriding `compile.date_format`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date_format: Option<String>,
+
+    /// Which enclosing section headings to include, and how
+    pub context: ContextOptions,
 
     /// Search notes recursively (excluding directories that start with '.')
     pub recursive: bool,
+
+    /// With `recursive`, also read notes under `archive/` and `trash/`
+    pub include_archived: bool,
+
+    /// Also write `<output>.manifest.json` describing the compilation
+    pub manifest: bool,
+
+    /// Fail instead of warning when a note does not match its built-in template
+    pub strict: bool,
+
+    /// Quote linked journal notes below the blocks that link to them
+    pub embed_links: bool,
+
+    /// Compile only these files (root-relative) instead of scanning the journal
+    pub notes: Vec<String>,
+
+    /// Glob patterns of notes to leave out, in addition to `compile_exclude`;
+    /// files named in `notes` are compiled even when they match
+    pub exclude: Vec<String>,
+
+    /// Only notes whose first heading contains this text, ignoring case
+    pub title_filter: Option<String>,
+
+    /// Add the "*Name — compiled by Author on date*" line when the journal has a name or author
+    pub attribution: bool,
+
+    /// Limits on the blocks written; the rest is summarized in a footer
+    pub budget: CompileBudget,
+    /// Most related tags to report (0 = none)
+    pub suggest_limit: usize,
+    /// Write blocks tagged with the journal's `private_tag` instead of redacting them
+    pub include_private: bool,
+    /// Accept a query that only excludes tags (`NOT meeting`)
+    pub allow_exclusion_only: bool,
 }
 
-/// Compile tagged content into an output markdown file.
-///
-/// Returns the path to the generated compilation file.
-///
-/// # Errors
-///
-/// Returns an error if:
-/// - The query is invalid
-/// - No notes are found
-/// - No content matches the query
-/// - File I/O fails
-pub fn compile_tags(repository: &FileSystemRepository, options: CompileOptions) -> Result<PathBuf> {
-    // 1. Parse query
-    let query = TagQuery::parse(&options.query)?;
+impl CompileOptions {
+    /// These options with a `!context` / `!nocontext` word of the query applied
+    /// to `context` (overriding `--include-context`) and removed from the query
+    pub fn with_query_directives(&self) -> Result<Cow<'_, CompileOptions>> {
+        let (query, include_context) = split_context_directive(&self.query)?;
+        let Some(include_context) = include_context else {
+            return Ok(Cow::Borrowed(self));
+        };
+        let context = match (include_context, self.context.depth) {
+            // Already on: keep the requested depth and style
+            (true, depth) if depth != ContextDepth::Levels(0) => self.context,
+            (include_context, _) => ContextOptions::from(include_context),
+        };
+        Ok(Cow::Owned(CompileOptions {
+            query,
+            context,
+            ..self.clone()
+        }))
+    }
 
-    // 2. Load config to get mode
-    let config = repository.load_config()?;
+    /// The parsed query, refusing one that matches by exclusion only unless allowed
+    pub(crate) fn parse_query(&self, value_tags: bool) -> Result<TagQuery> {
+        let query = TagQuery::parse_with_value_tags(&self.query, value_tags)?;
+        if !self.allow_exclusion_only {
+            query.require_positive_atom()?;
+        }
+        Ok(query)
+    }
+}
 
-    // 3. Determine output path
-    let output_path = if let Some(path) = options.output.clone() {
+/// What a compilation run produced
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompileReport {
+    /// Path to the generated compilation file
+    pub output_path: PathBuf,
+
+    /// Query as given on the command line
+    pub query: String,
+
+    /// Number of content blocks that matched the query
+    pub block_count: usize,
+
+    /// Contributing notes (root-relative) with their block counts, in path order
+    pub files: Vec<(PathBuf, usize)>,
+
+    /// Earliest dated block, if any
+    pub min_date: Option<NaiveDate>,
+
+    /// Latest dated block, if any
+    pub max_date: Option<NaiveDate>,
+
+    /// Set when the budget left matching blocks out
+    pub truncation: Option<Truncation>,
+
+    /// Tags found most often on the matching blocks besides the query's, with
+    /// their block counts (see [`related_tags`])
+    pub related_tags: Vec<(String, usize)>,
+
+    /// Notes left out by exclude patterns
+    pub excluded: usize,
+}
+
+impl CompileReport {
+    /// Build a report from the blocks that were written to `output_path`.
+    pub fn from_content(output_path: PathBuf, query: &str, content: &[TaggedContent]) -> Self {
+        let mut files: BTreeMap<&Path, usize> = BTreeMap::new();
+        for tc in content {
+            *files.entry(tc.source_file.as_path()).or_insert(0) += 1;
+        }
+        let dates = content.iter().filter_map(|tc| tc.date);
+
+        CompileReport {
+            output_path,
+            query: query.to_string(),
+            block_count: content.len(),
+            files: files
+                .into_iter()
+                .map(|(path, count)| (path.to_path_buf(), count))
+                .collect(),
+            min_date: dates.clone().min(),
+            max_date: dates.max(),
+            truncation: None,
+            related_tags: Vec::new(),
+            excluded: 0,
+        }
+    }
+
+    /// Number of notes that contributed at least one block
+    pub fn note_count(&self) -> usize {
+        self.files.len()
+    }
+
+    /// One-line summary, e.g. `Compiled 37 blocks from 12 notes (02-01-2025 .. 28-03-2025)`
+    pub fn summary(&self) -> String {
+        let mut line = format!(
+            "Compiled {} from {}",
+            plural(self.block_count, "block"),
+            plural(self.note_count(), "note")
+        );
+        match (self.min_date, self.max_date) {
+            (Some(min), Some(max)) if min == max => {
+                line.push_str(&format!(" ({})", min.format("%d-%m-%Y")));
+            }
+            (Some(min), Some(max)) => {
+                line.push_str(&format!(
+                    " ({} .. {})",
+                    min.format("%d-%m-%Y"),
+                    max.format("%d-%m-%Y")
+                ));
+            }
+            _ => {}
+        }
+        if let Some(truncation) = &self.truncation {
+            line.push_str(&format!(
+                "; truncated, {} more {} omitted ({})",
+                truncation.omitted,
+                if truncation.omitted == 1 {
+                    "block"
+                } else {
+                    "blocks"
+                },
+                truncation.limit.flag()
+            ));
+        }
+        line
+    }
+
+    /// Related tags line, e.g. `related: #standup (21) #1on1 (9)`; None when there are none
+    pub fn related_line(&self) -> Option<String> {
+        if self.related_tags.is_empty() {
+            return None;
+        }
+        let tags: Vec<String> = self
+            .related_tags
+            .iter()
+            .map(|(tag, count)| format!("#{} ({})", tag, count))
+            .collect();
+        Some(format!("related: {}", tags.join(" ")))
+    }
+
+    /// Manifest JSON with paths relative to `root`, using forward slashes.
+    pub fn manifest_json(&self, root: &Path) -> Result<String> {
+        let output = self
+            .output_path
+            .strip_prefix(root)
+            .unwrap_or(&self.output_path);
+        let manifest = Manifest {
+            query: &self.query,
+            output: slash_path(output),
+            block_count: self.block_count,
+            note_count: self.note_count(),
+            min_date: self.min_date.map(|d| d.format("%Y-%m-%d").to_string()),
+            max_date: self.max_date.map(|d| d.format("%Y-%m-%d").to_string()),
+            omitted_blocks: self.truncation.map_or(0, |truncation| truncation.omitted),
+            files: self
+                .files
+                .iter()
+                .map(|(path, blocks)| ManifestFile {
+                    path: slash_path(path),
+                    blocks: *blocks,
+                })
+                .collect(),
+        };
+        serde_json::to_string_pretty(&manifest)
+            .map_err(|e| DjourError::Config(format!("Failed to serialize manifest: {}", e)))
+    }
+}
+
+/// On-disk form of a [`CompileReport`]
+#[derive(Serialize)]
+struct Manifest<'a> {
+    query: &'a str,
+    output: String,
+    block_count: usize,
+    note_count: usize,
+    min_date: Option<String>,
+    max_date: Option<String>,
+    #[serde(skip_serializing_if = "is_zero")]
+    omitted_blocks: usize,
+    files: Vec<ManifestFile>,
+}
+
+fn is_zero(value: &usize) -> bool {
+    *value == 0
+}
+
+#[derive(Serialize)]
+struct ManifestFile {
+    path: String,
+    blocks: usize,
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("{} {}", count, noun)
+    } else {
+        format!("{} {}s", count, noun)
+    }
+}
+
+/// Render a relative path with `/` separators regardless of platform.
+fn slash_path(path: &Path) -> String {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy().into_owned()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Split a `!context` or `!nocontext` word (any case) off `query`: the rest
+/// of the query, and whether the directive asks for section context
+fn split_context_directive(query: &str) -> Result<(String, Option<bool>)> {
+    let mut include_context = None;
+    let mut rest: Vec<&str> = Vec::new();
+    for word in query.split_whitespace() {
+        let directive = match word.to_lowercase().as_str() {
+            CONTEXT_DIRECTIVE => true,
+            NO_CONTEXT_DIRECTIVE => false,
+            _ => {
+                rest.push(word);
+                continue;
+            }
+        };
+        if include_context.is_some_and(|set| set != directive) {
+            return Err(DjourError::Config(format!(
+                "Query has both {} and {}; use one",
+                CONTEXT_DIRECTIVE, NO_CONTEXT_DIRECTIVE
+            )));
+        }
+        include_context = Some(directive);
+    }
+    Ok(match include_context {
+        Some(_) => (rest.join(" "), include_context),
+        None => (query.to_string(), None),
+    })
+}
+
+/// Path of the manifest written next to a compilation output.
+pub fn manifest_path(output_path: &Path) -> PathBuf {
+    let mut name = output_path.as_os_str().to_os_string();
+    name.push(".manifest.json");
+    PathBuf::from(name)
+}
+
+/// Resolve the absolute output path for a compilation.
+pub fn compilation_output_path(
+    repository: &FileSystemRepository,
+    options: &CompileOptions,
+) -> PathBuf {
+    if let Some(path) = options.output.clone() {
         // Use provided path
         if path.is_absolute() {
             path
@@ -68,18 +392,187 @@ pub fn compile_tags(repository: &FileSystemRepository, options: CompileOptions)
         let sanitized = sanitize_filename(&options.query);
         repository
             .root()
-            .join(".compilations")
+            .join(COMPILATIONS_DIR)
             .join(format!("{}.md", sanitized))
-    };
+    }
+}
 
-    // 4. List all note files (with date filters)
-    let notes = repository.list_notes(
-        config.get_mode(),
-        options.from,
-        options.to,
-        None, // No limit - get all notes
-        options.recursive,
-    )?;
+/// Compile tagged content into an output markdown file.
+///
+/// Returns a report describing the generated compilation. This is the
+/// scan + compile core shared with watch mode, so it can be called repeatedly
+/// with the same repository and options. In weekly and monthly mode, notes that
+/// do not match the built-in template are reported in `diagnostics`.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - The query is invalid
+/// - No notes are found
+/// - No content matches the query
+/// - `strict` is set and a note does not match the built-in template
+/// - File I/O fails
+pub fn compile_tags(
+    repository: &FileSystemRepository,
+    options: &CompileOptions,
+    diagnostics: &mut Diagnostics,
+    progress: Option<ScanProgress<'_>>,
+) -> Result<CompileReport> {
+    let options = options.with_query_directives()?;
+    let options = options.as_ref();
+    // Refuse before scanning when nothing may be written
+    repository.ensure_writable("write", &compilation_output_path(repository, options))?;
+    let (document, report) = compile(repository, options, diagnostics, progress)?;
+    write_compilation(repository, options, &document, &report)?;
+    Ok(report)
+}
+
+/// Write a compiled document (and its manifest, when asked for) to `report.output_path`
+pub(crate) fn write_compilation(
+    repository: &FileSystemRepository,
+    options: &CompileOptions,
+    document: &CompiledDocument,
+    report: &CompileReport,
+) -> Result<()> {
+    // Convert absolute path to relative for repository.write_note
+    let relative_path = report
+        .output_path
+        .strip_prefix(repository.root())
+        .map_err(|_| {
+            DjourError::Config("Output path must be within journal directory".to_string())
+        })?;
+
+    let relative_str = relative_path
+        .to_str()
+        .ok_or_else(|| DjourError::Config("Invalid output path".to_string()))?;
+
+    // An unchanged compilation keeps its file, and the time it was generated at
+    if !is_unchanged(&report.output_path, options, |out| document.write_to(out)) {
+        // Rendered straight into the file, so the document is never held in memory whole
+        let provenance = provenance_line(options, Local::now().naive_local());
+        repository.write_note_streamed(relative_str, |out| {
+            let mut out = SecondLine::new(out, &provenance);
+            document.write_to(&mut out)?;
+            out.finish()
+        })?;
+    }
+
+    if options.manifest {
+        let manifest = report.manifest_json(repository.root())?;
+        let manifest_relative = manifest_path(relative_path);
+        let manifest_str = manifest_relative
+            .to_str()
+            .ok_or_else(|| DjourError::Config("Invalid output path".to_string()))?;
+        repository.write_note(manifest_str, &manifest)?;
+    }
+
+    Ok(())
+}
+
+/// Writer inserting a line after the first line written through it, or after
+/// the closing fence when the document opens with YAML front matter
+struct SecondLine<'a, W: fmt::Write + ?Sized> {
+    out: &'a mut W,
+    line: Option<&'a str>,
+    /// The line being written, held back until it is complete while the
+    /// inserted line is still to come
+    pending: String,
+    /// Whether the first line was a front matter fence
+    front_matter: bool,
+    first: bool,
+}
+
+impl<'a, W: fmt::Write + ?Sized> SecondLine<'a, W> {
+    fn new(out: &'a mut W, line: &'a str) -> Self {
+        SecondLine {
+            out,
+            line: Some(line),
+            pending: String::new(),
+            front_matter: false,
+            first: true,
+        }
+    }
+
+    /// Write the line at the end when the document had a single line, or
+    /// front matter that never closed
+    fn finish(self) -> fmt::Result {
+        self.out.write_str(&self.pending)?;
+        match self.line {
+            Some(line) if self.pending.is_empty() && !self.first => writeln!(self.out, "{}", line),
+            Some(line) => write!(self.out, "\n{}\n", line),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<W: fmt::Write + ?Sized> fmt::Write for SecondLine<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut rest = s;
+        while let Some(line) = self.line {
+            let Some(newline) = rest.find('\n') else {
+                self.pending.push_str(rest);
+                return Ok(());
+            };
+            self.pending.push_str(&rest[..=newline]);
+            rest = &rest[newline + 1..];
+
+            let text = self.pending.trim_end();
+            let insert = if self.first {
+                self.front_matter = text == "---";
+                !self.front_matter
+            } else {
+                text == "---" || text == "..."
+            };
+            self.first = false;
+            self.out.write_str(&self.pending)?;
+            self.pending.clear();
+            if insert {
+                writeln!(self.out, "{}", line)?;
+                self.line = None;
+            }
+        }
+        self.out.write_str(rest)
+    }
+}
+
+/// Compile tagged content into markdown without writing anything.
+///
+/// Returns the document [`compile_tags`] would write, along with its report
+/// (whose `output_path` is where it would be written). Errors are the same as
+/// for [`compile_tags`], except that nothing touches the file system.
+/// `progress` follows the notes being read.
+pub fn compile_to_string(
+    repository: &FileSystemRepository,
+    options: &CompileOptions,
+    diagnostics: &mut Diagnostics,
+    progress: Option<ScanProgress<'_>>,
+) -> Result<(String, CompileReport)> {
+    let options = options.with_query_directives()?;
+    let (document, report) = compile(repository, &options, diagnostics, progress)?;
+    Ok((document.to_markdown(), report))
+}
+
+/// Scan the journal and compile the document for `options`, without rendering it
+fn compile(
+    repository: &FileSystemRepository,
+    options: &CompileOptions,
+    diagnostics: &mut Diagnostics,
+    progress: Option<ScanProgress<'_>>,
+) -> Result<(CompiledDocument, CompileReport)> {
+    // 1. Load config to get mode and tag syntax
+    let config = repository.load_config()?;
+
+    // 2. Parse query
+    let query = options.parse_query(config.value_tags)?;
+
+    // 3. Determine output path
+    let output_path = compilation_output_path(repository, options);
+
+    // 4. Read all notes (with date filters), never reading back compiled output
+    let scope = compile_scope(repository, &config, options, [output_path.as_path()])?;
+    let scan = scan_notes(repository, &config, &scope, progress)?;
+    diagnostics.append(scan.diagnostics);
+    let notes = scan.notes;
 
     if notes.is_empty() {
         return Err(DjourError::TagNotFound(format!(
@@ -88,32 +581,171 @@ pub fn compile_tags(repository: &FileSystemRepository, options: CompileOptions)
         )));
     }
 
-    // 5. Parse all files and extract tagged content
-    let mut all_content: Vec<TaggedContent> = Vec::new();
+    // 5. Check weekly/monthly structure of every non-empty note
+    let sources = note_sources(repository, &config, options, notes, diagnostics)?;
 
-    // Use repository-relative source paths so grouped output can include subdirectories.
-    let output_context = output_path.strip_prefix(repository.root()).ok();
+    // 6.-7. Match, render and wrap in the compilation template
+    let (document, report) = compile_sources(
+        repository,
+        &config,
+        options,
+        &query,
+        output_path,
+        &sources,
+        diagnostics,
+    )?;
+    let report = CompileReport {
+        excluded: scan.excluded,
+        ..report
+    };
+    Ok((document, report))
+}
 
+/// Notes a compilation reads: the date filters of `options`, minus the notes its
+/// exclude patterns and `compile_exclude` name, and never compiled output: the
+/// given `outputs`, wherever they go, and anything under .compilations/
+pub(crate) fn compile_scope<'a>(
+    repository: &FileSystemRepository,
+    config: &Config,
+    options: &CompileOptions,
+    outputs: impl IntoIterator<Item = &'a Path>,
+) -> Result<ScanScope> {
+    let mut exclude = vec![PathBuf::from(COMPILATIONS_DIR)];
+    exclude.extend(
+        outputs
+            .into_iter()
+            .filter_map(|output| output.strip_prefix(repository.root()).ok())
+            .map(Path::to_path_buf),
+    );
+    Ok(ScanScope {
+        from: options.from,
+        to: options.to,
+        recursive: options.recursive,
+        exclude,
+        exclude_patterns: exclude_patterns(config, &options.exclude)?,
+        include_archived: options.include_archived,
+        changed_since: options.changed_since,
+        title_filter: options.title_filter.clone(),
+        dated_sections: true,
+        notes: options.notes.clone(),
+    })
+}
+
+/// Turn scanned notes into compile input, checking the weekly/monthly structure
+/// of every non-empty note (an error under `strict`) and warning about code
+/// fences left open and `@DD-MM-YYYY` heading markers that are no date
+pub(crate) fn note_sources(
+    repository: &FileSystemRepository,
+    config: &Config,
+    options: &CompileOptions,
+    notes: Vec<ScannedNote>,
+    diagnostics: &mut Diagnostics,
+) -> Result<Vec<NoteSource>> {
+    let structure_check = StructureCheck::new(repository, config);
+    let mut parse_warnings = Diagnostics::new();
+    let mut sources = Vec::with_capacity(notes.len());
     for note in notes {
-        let content = repository.read_note(&note.filename)?;
-        if content.is_empty() {
+        if note.content.is_empty() {
             continue;
         }
+        if let Some(check) = &structure_check {
+            check.check(&note.entry, &note.content, diagnostics);
+        }
+        if let Some(warning) = check_unclosed_fence(&note.content) {
+            parse_warnings.warn(&note.entry.filename, warning);
+        }
+        for warning in check_section_dates(&note.content) {
+            parse_warnings.warn(&note.entry.filename, warning);
+        }
+        let path = PathBuf::from(&note.entry.filename);
+        let content = if config.wikilinks {
+            rewrite_wikilinks(&note.content, &path, |target| {
+                resolve_wikilink(repository, config, &path, target)
+            })
+        } else {
+            note.content
+        };
+        sources.push(NoteSource {
+            path,
+            content,
+            date: note.entry.date,
+        });
+    }
 
-        let file_path = PathBuf::from(&note.filename);
-        let tagged = TagParser::extract_from_markdown_for_output(
-            &content,
-            &file_path,
-            note.date,
-            output_context,
-        );
+    if options.strict && !diagnostics.is_empty() {
+        diagnostics.escalate();
+        return Err(DjourError::Config(format!(
+            "{} note(s) do not match the built-in template (--strict); nothing was written",
+            diagnostics.len()
+        )));
+    }
+    // Only template mismatches fail under --strict
+    diagnostics.append(parse_warnings);
+    Ok(sources)
+}
 
-        all_content.extend(tagged);
+/// Journal-relative path of the note a `[[target]]` wiki link in `source` names.
+///
+/// Tried in order, next to `source` and then at the journal root: the exact
+/// filename, then the filename plus `.md`. A date-like target (`2025-01-10`)
+/// also names the note for that day in the active mode.
+fn resolve_wikilink(
+    repository: &FileSystemRepository,
+    config: &Config,
+    source: &Path,
+    target: &str,
+) -> Option<PathBuf> {
+    let source_dir = source.parent().unwrap_or_else(|| Path::new(""));
+    let with_extension = format!("{}.md", target);
+    let mut candidates = Vec::new();
+    for dir in [source_dir, Path::new("")] {
+        candidates.push(dir.join(target));
+        candidates.push(dir.join(&with_extension));
+    }
+    let naming = config.naming();
+    let date = naming
+        .date_from_filename(&with_extension)
+        .or_else(|| JournalMode::Daily.date_from_filename(&with_extension));
+    if let Some(date) = date {
+        candidates.push(PathBuf::from(naming.filename_for_date(date)));
     }
 
-    // 6. Filter by query
-    let filtered = TagCompiler::filter(all_content, &query);
+    candidates.into_iter().find(|candidate| {
+        let inside = candidate
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+        let is_markdown = candidate
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("md"));
+        inside && is_markdown && repository.root().join(candidate).is_file()
+    })
+}
+
+/// Compile already read `sources` into the document for `output_path`
+pub(crate) fn compile_sources(
+    repository: &FileSystemRepository,
+    config: &Config,
+    options: &CompileOptions,
+    query: &TagQuery,
+    output_path: PathBuf,
+    sources: &[NoteSource],
+    diagnostics: &mut Diagnostics,
+) -> Result<(CompiledDocument, CompileReport)> {
+    // Extract tagged content and filter by query. Source paths are
+    // repository-relative so grouped output can include subdirectories.
+    let output_relative = output_path.strip_prefix(repository.root()).ok();
+    let output_context = output_relative;
+    let private_tag = config.private_tag().filter(|_| !options.include_private);
+    let filtered = matching_content(
+        sources,
+        query,
+        config,
+        output_context,
+        private_tag.as_deref(),
+    );
 
+    let window = DateWindow::new(options.from, options.to);
+    let filtered = within_window(filtered, sources, window, config.get_mode());
     if filtered.is_empty() {
         return Err(DjourError::TagNotFound(format!(
             "No content found matching query: {}",
@@ -121,35 +753,497 @@ pub fn compile_tags(repository: &FileSystemRepository, options: CompileOptions)
         )));
     }
 
-    // 7. Generate markdown output
-    let date_style = match config.get_mode() {
-        JournalMode::Weekly => CompilationDateStyle::WeekRange,
-        JournalMode::Monthly => CompilationDateStyle::MonthRange,
-        _ => CompilationDateStyle::SingleDate,
+    let capped: BTreeSet<&Path> = filtered
+        .iter()
+        .filter(|block| block.tags.len() >= MAX_TAGS_PER_BLOCK)
+        .map(|block| block.source_file.as_path())
+        .collect();
+    for file in capped {
+        diagnostics.warn(
+            slash_path(file),
+            format!(
+                "a block has {} or more tags; only the first {} are used",
+                MAX_TAGS_PER_BLOCK, MAX_TAGS_PER_BLOCK
+            ),
+        );
+    }
+
+    let related = related_tags(&filtered, query, options.suggest_limit);
+    let (filtered, truncation) =
+        TagCompiler::apply_budget(filtered, &options.budget, output_context);
+    let report = CompileReport {
+        truncation,
+        related_tags: related,
+        ..CompileReport::from_content(output_path.clone(), &options.query, &filtered)
+    };
+
+    if let Some(relative) = output_relative {
+        if repository.is_note_path(config.naming(), relative, true) {
+            diagnostics.warn(
+                slash_path(relative),
+                "compile output is named like a journal note. compile skips it, \
+                but list, tags and other commands will read it as a note; choose another --output",
+            );
+        }
+    }
+
+    // Embeds are added after filtering, so tags inside linked notes never match anything
+    let filtered = if options.embed_links {
+        filtered
+            .into_iter()
+            .map(|block| embed_block_links(repository, block, config, private_tag.as_deref()))
+            .collect()
+    } else {
+        filtered
     };
 
-    let markdown = TagCompiler::to_markdown_for_output(
+    // Generate markdown output, wrapped in .djour/templates/compilation.md
+    // (the built-in fallback reproduces the plain output)
+    let template = load_template(repository.root(), COMPILATION_TEMPLATE)?;
+    let document = CompiledDocument::new(
         filtered,
-        &query,
-        options.format,
-        date_style,
-        options.include_context,
+        query,
+        options,
+        config,
+        template,
         output_context,
+        report.truncation.as_ref(),
     );
 
-    // 8. Write output file
-    // Convert absolute path to relative for repository.write_note
-    let relative_path = output_path.strip_prefix(repository.root()).map_err(|_| {
-        DjourError::Config("Output path must be within journal directory".to_string())
-    })?;
+    Ok((document, report))
+}
 
-    let relative_str = relative_path
-        .to_str()
-        .ok_or_else(|| DjourError::Config("Invalid output path".to_string()))?;
+/// Compile one markdown document, such as one read from stdin, without a journal.
+///
+/// Uses default settings and the built-in compilation template. Every block is
+/// dated `date`, and relative links are left as written. Date filters,
+/// recursion, output paths and embeds do not apply and are ignored.
+///
+/// # Errors
+///
+/// Returns an error if the query is invalid or nothing in `content` matches it.
+pub fn compile_document(
+    content: &str,
+    options: &CompileOptions,
+    date: Option<NaiveDate>,
+) -> Result<String> {
+    let options = options.with_query_directives()?;
+    let options = options.as_ref();
+    let config = Config::new(JournalMode::Daily);
+    let query = options.parse_query(config.value_tags)?;
+    let sources = [NoteSource {
+        path: PathBuf::from(STDIN_SOURCE),
+        content: content.to_string(),
+        date,
+    }];
 
-    repository.write_note(relative_str, &markdown)?;
+    let private_tag = config.private_tag().filter(|_| !options.include_private);
+    let filtered = matching_content(&sources, &query, &config, None, private_tag.as_deref());
+    if filtered.is_empty() {
+        return Err(DjourError::TagNotFound(format!(
+            "No content found matching query: {}",
+            options.query
+        )));
+    }
 
-    Ok(output_path)
+    let (filtered, truncation) = TagCompiler::apply_budget(filtered, &options.budget, None);
+    let template = Template::from_builtin(COMPILATION_TEMPLATE)?;
+    Ok(CompiledDocument::new(
+        filtered,
+        &query,
+        options,
+        &config,
+        template,
+        None,
+        truncation.as_ref(),
+    )
+    .to_markdown())
+}
+
+/// Tagged blocks of `sources` that match `query`.
+///
+/// `output_file` is where the compilation goes, relative to the journal root;
+/// relative links are rewritten against it, and left alone when it is `None`.
+/// With a `private_tag`, blocks with that tag inside matching sections are
+/// replaced by redaction lines (see [`TagCompiler::select`]).
+pub fn matching_content(
+    sources: &[NoteSource],
+    query: &TagQuery,
+    config: &Config,
+    output_file: Option<&Path>,
+    private_tag: Option<&str>,
+) -> Vec<TaggedContent> {
+    let parse_options = config.tag_parse_options();
+
+    let mut all_content: Vec<TaggedContent> = Vec::new();
+    for source in sources {
+        all_content.extend(TagParser::extract_from_markdown_with_options(
+            &source.content,
+            &source.path,
+            source.date,
+            output_file,
+            parse_options,
+        ));
+    }
+
+    TagCompiler::select(all_content, query, private_tag)
+}
+
+/// The blocks of `content` dated inside `window`. A block keeps the period of
+/// its note (a day, week or month) unless an `@DD-MM-YYYY` heading dated its
+/// section, which then has to fall inside the window itself; undated blocks
+/// always stay.
+fn within_window(
+    content: Vec<TaggedContent>,
+    sources: &[NoteSource],
+    window: DateWindow,
+    mode: JournalMode,
+) -> Vec<TaggedContent> {
+    if window == DateWindow::default() {
+        return content;
+    }
+    let note_dates: BTreeMap<&Path, Option<NaiveDate>> = sources
+        .iter()
+        .map(|source| (source.path.as_path(), source.date))
+        .collect();
+    content
+        .into_iter()
+        .filter(|block| {
+            let note_date = note_dates
+                .get(block.source_file.as_path())
+                .copied()
+                .flatten();
+            match block.date {
+                None => true,
+                Some(date) if Some(date) == note_date => {
+                    window.overlaps(date, mode.period_end(date))
+                }
+                Some(date) => window.overlaps(date, date),
+            }
+        })
+        .collect()
+}
+
+/// The `limit` tags found on the most `content` blocks, with their block
+/// counts, most frequent first (ties by name).
+///
+/// Tags of the query and their nested tags are left out, so the result points
+/// at what else the matching content is about, e.g. `standup` to exclude next time.
+pub fn related_tags(
+    content: &[TaggedContent],
+    query: &TagQuery,
+    limit: usize,
+) -> Vec<(String, usize)> {
+    let query_tags = query.tags();
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for block in content {
+        for tag in &block.tags {
+            if !query_tags
+                .iter()
+                .any(|pattern| tag_in_subtree(tag, pattern))
+            {
+                *counts.entry(tag.as_str()).or_insert(0) += 1;
+            }
+        }
+    }
+
+    let mut related: Vec<(String, usize)> = counts
+        .into_iter()
+        .map(|(tag, count)| (tag.to_string(), count))
+        .collect();
+    // Stable sort: equal counts stay in name order
+    related.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    related.truncate(limit);
+    related
+}
+
+/// The compiled document for already filtered blocks.
+///
+/// Keeps the blocks rather than the rendered markdown, which is produced by
+/// [`write_to`](Self::write_to) as it is written.
+pub(crate) struct CompiledDocument {
+    blocks: Vec<TaggedContent>,
+    template: Template,
+    /// How the body is rendered; the private tag is None with `--include-private`
+    render: RenderOptions,
+    attribution: Option<String>,
+    truncation_footer: Option<String>,
+    query: String,
+    title: String,
+    date_range: String,
+    generated_at: String,
+    journal_name: String,
+    author: String,
+}
+
+impl CompiledDocument {
+    fn new(
+        blocks: Vec<TaggedContent>,
+        query: &TagQuery,
+        options: &CompileOptions,
+        config: &Config,
+        template: Template,
+        output_file: Option<&Path>,
+        truncation: Option<&Truncation>,
+    ) -> Self {
+        let date_style = match config.get_mode() {
+            JournalMode::Weekly => CompilationDateStyle::WeekRange,
+            JournalMode::Monthly => CompilationDateStyle::MonthRange,
+            _ => CompilationDateStyle::SingleDate,
+        };
+        let now = Local::now();
+        CompiledDocument {
+            date_range: format_date_range(options.from, options.to, &blocks),
+            blocks,
+            template,
+            render: RenderOptions {
+                format: options.format,
+                date_style,
+                window: DateWindow::new(options.from, options.to),
+                context: options.context,
+                output_file: output_file.map(Path::to_path_buf),
+                style: CompileStyle {
+                    date_format: options
+                        .date_format
+                        .clone()
+                        .unwrap_or_else(|| config.compile.date_format.clone()),
+                    ..config.compile.clone()
+                },
+                private_tag: config.private_tag().filter(|_| !options.include_private),
+                group_by: options.group_by,
+                locale: config.locale,
+            },
+            attribution: attribution_line(config, now.date_naive()).filter(|_| options.attribution),
+            truncation_footer: truncation.map(Truncation::footer),
+            query: query.to_string(),
+            title: TagCompiler::title_markdown(query),
+            generated_at: now.format("%Y-%m-%d %H:%M").to_string(),
+            journal_name: config.name.clone(),
+            author: config.author.clone(),
+        }
+    }
+
+    /// Render the document into `out`
+    pub(crate) fn write_to<W: fmt::Write + ?Sized>(&self, out: &mut W) -> fmt::Result {
+        let vars = CompilationTemplateVars {
+            query: &self.query,
+            date_range: &self.date_range,
+            generated_at: &self.generated_at,
+            title: &self.title,
+            body: "",
+            journal_name: &self.journal_name,
+            author: &self.author,
+        };
+        self.template.write_compilation(out, &vars, |out| {
+            if let Some(line) = &self.attribution {
+                out.write_str(line)?;
+            }
+            self.render.write_body(out, &self.blocks)?;
+            match &self.truncation_footer {
+                Some(footer) => write!(out, "\n{}", footer),
+                None => Ok(()),
+            }
+        })
+    }
+
+    /// The whole document as one string, for output that is not a file
+    pub(crate) fn to_markdown(&self) -> String {
+        let mut markdown = String::new();
+        self.write_to(&mut markdown)
+            .expect("writing to a String cannot fail");
+        markdown
+    }
+}
+
+/// `*Work Log — compiled by Tunç on 2025-01-18*`, or None when the journal has
+/// neither a name nor an author
+fn attribution_line(config: &Config, date: NaiveDate) -> Option<String> {
+    let date = date.format("%Y-%m-%d");
+    match (config.name.as_str(), config.author.as_str()) {
+        ("", "") => None,
+        (name, "") => Some(format!("*{} — compiled on {}*\n", name, date)),
+        ("", author) => Some(format!("*Compiled by {} on {}*\n", author, date)),
+        (name, author) => Some(format!("*{} — compiled by {} on {}*\n", name, author, date)),
+    }
+}
+
+/// Append the notes linked from a matched block as quoted embeds.
+///
+/// With a `private_tag`, the private blocks of linked notes are redacted like
+/// those of the compiled notes.
+fn embed_block_links(
+    repository: &FileSystemRepository,
+    block: TaggedContent,
+    config: &Config,
+    private_tag: Option<&str>,
+) -> TaggedContent {
+    let content = block.raw_payload_content();
+    let embed = EmbedOptions {
+        max_bytes: config.embed_max_bytes,
+        redaction: private_tag.map(|tag| (tag, config.tag_parse_options())),
+    };
+    let embedded = with_embeds(repository, content, &block.source_file, EMBED_DEPTH, &embed);
+    if embedded == content {
+        return block;
+    }
+    TaggedContent {
+        position: block.position,
+        time: block.time,
+        ..TaggedContent::new(
+            block.tags,
+            embedded,
+            block.source_file,
+            block.date,
+            block.context,
+        )
+    }
+}
+
+/// How linked notes are embedded
+struct EmbedOptions<'a> {
+    /// Longest embed in bytes (0 = no limit)
+    max_bytes: usize,
+    /// Private tag whose blocks are redacted, and how to parse notes to find them
+    redaction: Option<(&'a str, TagParseOptions)>,
+}
+
+/// `content` followed by a blockquote for every distinct journal note it links to.
+///
+/// Each level of embedding uses up one unit of `depth`, which is what stops
+/// link cycles. Targets that cannot be read are left as plain links.
+fn with_embeds(
+    repository: &FileSystemRepository,
+    content: &str,
+    source_file: &Path,
+    depth: usize,
+    embed: &EmbedOptions<'_>,
+) -> String {
+    if depth == 0 {
+        return content.to_string();
+    }
+
+    let mut seen = BTreeSet::new();
+    let mut embeds = Vec::new();
+    for target in inline_link_targets(content) {
+        let Some(path) = linked_note_path(&target, source_file) else {
+            continue;
+        };
+        if !seen.insert(path.clone()) {
+            continue;
+        }
+        let Some(linked) = path
+            .to_str()
+            .and_then(|relative| repository.read_note(relative).ok())
+        else {
+            continue;
+        };
+
+        let linked = match embed.redaction {
+            Some((private_tag, options)) => redact_note(&linked, &path, private_tag, options),
+            None => linked,
+        };
+        let body = match parse_front_matter(&linked) {
+            Some(front_matter) => &linked[front_matter.len..],
+            None => linked.as_str(),
+        };
+        let body = with_embeds(repository, body.trim(), &path, depth - 1, embed);
+        let body = truncate_embed(&body, embed.max_bytes);
+        // Links in the embed are relative to the linked note; make them relative
+        // to the embedding note so output-relative rewriting still works.
+        let body = rewrite_markdown_targets(&body, &path, Some(source_file));
+        embeds.push(quote_embed(&slash_path(&path), &body));
+    }
+
+    if embeds.is_empty() {
+        return content.to_string();
+    }
+    let mut embedded = content.trim_end().to_string();
+    for embed in embeds {
+        embedded.push_str("\n\n");
+        embedded.push_str(&embed);
+    }
+    embedded
+}
+
+/// `note` with its blocks tagged `private_tag` replaced by redaction lines,
+/// as a compiled section would show them
+fn redact_note(note: &str, path: &Path, private_tag: &str, options: TagParseOptions) -> String {
+    // The trailing newline keeps every block strictly inside the note's span
+    let source = format!("{}\n", note);
+    let blocks = TagParser::extract_from_markdown_with_options(&source, path, None, None, options);
+    let whole = TaggedContent::with_payload(
+        Vec::new(),
+        ContentPayload::Span {
+            span: SourceSpan::new(0, source.len()),
+            source: Arc::from(source.as_str()),
+        },
+        path.to_path_buf(),
+        None,
+        TagContext::Section {
+            heading: String::new(),
+            level: 1,
+            parents: Vec::new(),
+        },
+    );
+    Redaction::new(&blocks, private_tag)
+        .redact(vec![whole])
+        .pop()
+        .map_or_else(
+            || note.to_string(),
+            |block| block.raw_payload_content().to_string(),
+        )
+}
+
+/// Cut `text` to at most `max_bytes` (0 = no limit), marking the cut.
+fn truncate_embed(text: &str, max_bytes: usize) -> String {
+    if max_bytes == 0 || text.len() <= max_bytes {
+        return text.to_string();
+    }
+    let mut end = max_bytes;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}\n\n*(truncated)*", text[..end].trim_end())
+}
+
+/// Render `body` as a blockquote captioned with the note it came from.
+fn quote_embed(path: &str, body: &str) -> String {
+    let mut quoted = format!("> *Embedded from {}*", path);
+    if !body.is_empty() {
+        quoted.push_str("\n>");
+        for line in body.lines() {
+            quoted.push('\n');
+            if line.trim().is_empty() {
+                quoted.push('>');
+            } else {
+                quoted.push_str("> ");
+                quoted.push_str(line);
+            }
+        }
+    }
+    quoted
+}
+
+/// Describe the covered date range, preferring explicit --from/--to bounds
+/// and falling back to the earliest/latest dated content.
+fn format_date_range(
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    content: &[TaggedContent],
+) -> String {
+    let dates = content.iter().filter_map(|tc| tc.date);
+    let start = from.or_else(|| dates.clone().min());
+    let end = to.or_else(|| dates.max());
+
+    match (start, end) {
+        (Some(start), Some(end)) if start == end => start.format("%Y-%m-%d").to_string(),
+        (Some(start), Some(end)) => {
+            format!("{} - {}", start.format("%Y-%m-%d"), end.format("%Y-%m-%d"))
+        }
+        (Some(start), None) => format!("{} -", start.format("%Y-%m-%d")),
+        (None, Some(end)) => format!("- {}", end.format("%Y-%m-%d")),
+        (None, None) => String::new(),
+    }
 }
 
 /// Sanitize query string for use as filename
@@ -172,6 +1266,35 @@ fn sanitize_filename(query: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::fmt::Write;
+
+    fn with_second_line(chunks: &[&str]) -> String {
+        let mut out = String::new();
+        let mut writer = SecondLine::new(&mut out, "<!-- p -->");
+        for chunk in chunks {
+            writer.write_str(chunk).unwrap();
+        }
+        writer.finish().unwrap();
+        out
+    }
+
+    #[test]
+    fn test_second_line_goes_after_title_or_front_matter() {
+        assert_eq!(
+            with_second_line(&["# Title\n", "\nBody\n"]),
+            "# Title\n<!-- p -->\n\nBody\n"
+        );
+        assert_eq!(with_second_line(&["# Title"]), "# Title\n<!-- p -->\n");
+        assert_eq!(
+            with_second_line(&["---\ntitle: Work\n-", "--\n# Work\n"]),
+            "---\ntitle: Work\n---\n<!-- p -->\n# Work\n"
+        );
+        // Front matter that never closes keeps the line out of it
+        assert_eq!(
+            with_second_line(&["---\ntitle: Work\n"]),
+            "---\ntitle: Work\n<!-- p -->\n"
+        );
+    }
 
     #[test]
     fn test_sanitize_filename() {
@@ -195,12 +1318,341 @@ mod tests {
         assert_eq!(sanitize_filename("Work"), "work");
     }
 
+    #[test]
+    fn test_resolve_wikilink_candidates() {
+        let temp = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(temp.path().join("projects")).unwrap();
+        for file in ["2025-W02-2025-01-06.md", "projects/alpha.md", "ideas.md"] {
+            std::fs::write(temp.path().join(file), "").unwrap();
+        }
+        let repo = FileSystemRepository::new(temp.path().to_path_buf());
+        let config = Config::new(JournalMode::Weekly);
+        let resolve = |source: &str, target: &str| {
+            resolve_wikilink(&repo, &config, Path::new(source), target)
+        };
+
+        // A day names the weekly note containing it
+        assert_eq!(
+            resolve("ideas.md", "2025-01-10"),
+            Some(PathBuf::from("2025-W02-2025-01-06.md"))
+        );
+        // Next to the source note first, then at the root
+        assert_eq!(
+            resolve("projects/beta.md", "alpha"),
+            Some(PathBuf::from("projects/alpha.md"))
+        );
+        assert_eq!(
+            resolve("projects/beta.md", "ideas.md"),
+            Some(PathBuf::from("ideas.md"))
+        );
+        assert_eq!(resolve("ideas.md", "../outside"), None);
+        assert_eq!(resolve("ideas.md", "2025-02-10"), None);
+    }
+
+    fn dated(date: Option<NaiveDate>) -> TaggedContent {
+        TaggedContent::new(
+            vec!["work".to_string()],
+            "text".to_string(),
+            PathBuf::from("note.md"),
+            date,
+            crate::domain::tags::TagContext::Paragraph,
+        )
+    }
+
+    #[test]
+    fn test_format_date_range() {
+        let d = |day| NaiveDate::from_ymd_opt(2025, 1, day);
+        let content = vec![dated(d(17)), dated(None), dated(d(15))];
+
+        assert_eq!(
+            format_date_range(None, None, &content),
+            "2025-01-15 - 2025-01-17"
+        );
+        assert_eq!(
+            format_date_range(d(1), None, &content),
+            "2025-01-01 - 2025-01-17"
+        );
+        assert_eq!(format_date_range(None, None, &content[..1]), "2025-01-17");
+        assert_eq!(format_date_range(None, None, &[dated(None)]), "");
+    }
+
     #[test]
     fn test_sanitize_filename_special_chars() {
         assert_eq!(sanitize_filename("work!urgent"), "work_urgent");
         assert_eq!(sanitize_filename("work(test)"), "work_test");
     }
 
+    fn block(file: &str, date: Option<NaiveDate>) -> TaggedContent {
+        TaggedContent::new(
+            vec!["work".to_string()],
+            "text".to_string(),
+            PathBuf::from(file),
+            date,
+            crate::domain::tags::TagContext::Paragraph,
+        )
+    }
+
+    #[test]
+    fn test_report_counts_blocks_per_file() {
+        let d = |day| NaiveDate::from_ymd_opt(2025, 1, day);
+        let content = vec![
+            block("2025-01-17.md", d(17)),
+            block("2025-01-02.md", d(2)),
+            block("2025-01-17.md", d(17)),
+            block("sub/2025-01-09.md", d(9)),
+        ];
+
+        let report = CompileReport::from_content(
+            PathBuf::from("/j/.compilations/work.md"),
+            "work",
+            &content,
+        );
+
+        assert_eq!(report.block_count, 4);
+        assert_eq!(
+            report.files,
+            vec![
+                (PathBuf::from("2025-01-02.md"), 1),
+                (PathBuf::from("2025-01-17.md"), 2),
+                (PathBuf::from("sub/2025-01-09.md"), 1),
+            ]
+        );
+        assert_eq!(report.min_date, d(2));
+        assert_eq!(report.max_date, d(17));
+        assert_eq!(
+            report.summary(),
+            "Compiled 4 blocks from 3 notes (02-01-2025 .. 17-01-2025)"
+        );
+    }
+
+    #[test]
+    fn test_report_summary_without_dates() {
+        let report = CompileReport::from_content(
+            PathBuf::from("/j/.compilations/work.md"),
+            "work",
+            &[block("journal.md", None)],
+        );
+        assert_eq!(report.summary(), "Compiled 1 block from 1 note");
+    }
+
+    fn tagged(tags: &[&str]) -> TaggedContent {
+        TaggedContent::new(
+            tags.iter().map(|tag| tag.to_string()).collect(),
+            "text".to_string(),
+            PathBuf::from("2025-01-15.md"),
+            None,
+            crate::domain::tags::TagContext::Paragraph,
+        )
+    }
+
+    #[test]
+    fn test_related_tags_exclude_query_tags_and_rank_by_count() {
+        let content = vec![
+            tagged(&["work", "standup", "review"]),
+            tagged(&["work/meeting", "standup", "1on1"]),
+            tagged(&["work", "1on1", "urgent"]),
+            tagged(&["work", "standup", "alpha"]),
+        ];
+        let query = TagQuery::parse("work AND NOT urgent").unwrap();
+
+        assert_eq!(
+            related_tags(&content, &query, 3),
+            vec![
+                ("standup".to_string(), 3),
+                ("1on1".to_string(), 2),
+                ("alpha".to_string(), 1),
+            ]
+        );
+        assert!(related_tags(&content, &query, 0).is_empty());
+    }
+
+    #[test]
+    fn test_related_line_lists_tags_with_counts() {
+        let mut report = CompileReport::from_content(
+            PathBuf::from("/j/.compilations/work.md"),
+            "work",
+            &[block("2025-01-15.md", None)],
+        );
+        assert_eq!(report.related_line(), None);
+
+        report.related_tags = vec![("standup".to_string(), 21), ("1on1".to_string(), 9)];
+        assert_eq!(
+            report.related_line().unwrap(),
+            "related: #standup (21) #1on1 (9)"
+        );
+    }
+
+    #[test]
+    fn test_manifest_json_uses_relative_slash_paths() {
+        let d = |day| NaiveDate::from_ymd_opt(2025, 1, day);
+        let root = PathBuf::from("/j");
+        let report = CompileReport::from_content(
+            root.join(".compilations").join("work.md"),
+            "work",
+            &[block("sub/2025-01-09.md", d(9))],
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&report.manifest_json(&root).unwrap()).unwrap();
+        assert_eq!(json["output"], ".compilations/work.md");
+        assert_eq!(json["files"][0]["path"], "sub/2025-01-09.md");
+        assert_eq!(json["files"][0]["blocks"], 1);
+        assert_eq!(json["min_date"], "2025-01-09");
+    }
+
+    #[test]
+    fn test_manifest_path_appends_suffix() {
+        assert_eq!(
+            manifest_path(Path::new(".compilations/work.md")),
+            PathBuf::from(".compilations/work.md.manifest.json")
+        );
+    }
+
+    #[test]
+    fn test_quote_embed_captions_and_quotes_every_line() {
+        assert_eq!(
+            quote_embed("design/auth.md", "# Auth\n\nTokens #work"),
+            "> *Embedded from design/auth.md*\n>\n> # Auth\n>\n> Tokens #work"
+        );
+        assert_eq!(quote_embed("empty.md", ""), "> *Embedded from empty.md*");
+    }
+
+    #[test]
+    fn test_truncate_embed_respects_char_boundaries() {
+        assert_eq!(truncate_embed("short", 10), "short");
+        assert_eq!(truncate_embed("short", 0), "short");
+        assert_eq!(truncate_embed("añb", 2), "a\n\n*(truncated)*");
+    }
+
+    fn stdin_options(query: &str) -> CompileOptions {
+        CompileOptions {
+            query: query.to_string(),
+            output: None,
+            from: None,
+            to: None,
+            changed_since: None,
+            format: CompilationFormat::Chronological,
+            group_by: None,
+            date_format: None,
+            context: ContextOptions::off(),
+            recursive: false,
+            include_archived: false,
+            manifest: false,
+            strict: false,
+            embed_links: false,
+            notes: Vec::new(),
+            exclude: Vec::new(),
+            title_filter: None,
+            attribution: true,
+            budget: CompileBudget::unlimited(),
+            suggest_limit: 0,
+            include_private: false,
+            allow_exclusion_only: false,
+        }
+    }
+
+    #[test]
+    fn test_matching_content_across_sources() {
+        let sources = vec![
+            NoteSource {
+                path: PathBuf::from("2025-01-15.md"),
+                content: "Ship it #work\n\nGym #life\n".to_string(),
+                date: NaiveDate::from_ymd_opt(2025, 1, 15),
+            },
+            NoteSource {
+                path: PathBuf::from("projects/2025-01-16.md"),
+                content: "Review #work\n".to_string(),
+                date: NaiveDate::from_ymd_opt(2025, 1, 16),
+            },
+        ];
+        let config = Config::new(JournalMode::Daily);
+
+        let query = TagQuery::parse("work AND NOT dir:projects").unwrap();
+        let matched = matching_content(&sources, &query, &config, None, None);
+        assert_eq!(matched.len(), 1);
+        assert_eq!(matched[0].source_file, PathBuf::from("2025-01-15.md"));
+        assert_eq!(matched[0].date, NaiveDate::from_ymd_opt(2025, 1, 15));
+    }
+
+    #[test]
+    fn test_split_context_directive() {
+        assert_eq!(
+            split_context_directive("work  AND urgent").unwrap(),
+            ("work  AND urgent".to_string(), None)
+        );
+        assert_eq!(
+            split_context_directive("work !context AND urgent").unwrap(),
+            ("work AND urgent".to_string(), Some(true))
+        );
+        assert_eq!(
+            split_context_directive("!NoContext work").unwrap(),
+            ("work".to_string(), Some(false))
+        );
+        assert!(split_context_directive("work !context !nocontext").is_err());
+    }
+
+    #[test]
+    fn test_query_directive_overrides_context_option() {
+        let note = "# Log\n\n## Standup\n\nShipped #work\n";
+        let date = NaiveDate::from_ymd_opt(2025, 1, 15);
+
+        let markdown = compile_document(note, &stdin_options("work !context"), date).unwrap();
+        assert!(markdown.starts_with("# Compilation: #work\n"), "{markdown}");
+        assert!(markdown.contains("#### Standup\n\nShipped"), "{markdown}");
+
+        let options = CompileOptions {
+            context: ContextOptions::from(true),
+            ..stdin_options("!nocontext work")
+        };
+        let markdown = compile_document(note, &options, date).unwrap();
+        assert!(!markdown.contains("Standup"), "{markdown}");
+    }
+
+    #[test]
+    fn test_compile_document_dates_blocks_and_keeps_links() {
+        let markdown = compile_document(
+            "See [spec](docs/spec.md) #work\n\nGym #life\n",
+            &stdin_options("work"),
+            NaiveDate::from_ymd_opt(2025, 1, 15),
+        )
+        .unwrap();
+        assert!(markdown.starts_with("# Compilation: #work\n"), "{markdown}");
+        assert!(markdown.contains("## 15-01-2025"), "{markdown}");
+        assert!(markdown.contains("[spec](docs/spec.md)"), "{markdown}");
+        assert!(!markdown.contains("Gym"), "{markdown}");
+
+        let err = compile_document("Gym #life\n", &stdin_options("work"), None).unwrap_err();
+        assert!(matches!(err, DjourError::TagNotFound(_)));
+    }
+
+    #[test]
+    fn test_date_format_changes_headers_not_output_name() {
+        let options = CompileOptions {
+            date_format: Some("%A, %d %B %Y".to_string()),
+            ..stdin_options("work")
+        };
+        let markdown = compile_document(
+            "Ship #work\n",
+            &options,
+            NaiveDate::from_ymd_opt(2025, 1, 15),
+        )
+        .unwrap();
+        assert!(
+            markdown.contains("## Wednesday, 15 January 2025\n"),
+            "{markdown}"
+        );
+
+        let repo = FileSystemRepository::new(PathBuf::from("/j"));
+        assert_eq!(
+            compilation_output_path(&repo, &options),
+            compilation_output_path(&repo, &stdin_options("work"))
+        );
+        assert_eq!(
+            compilation_output_path(&repo, &options),
+            PathBuf::from("/j/.compilations/work.md")
+        );
+    }
+
     // Integration tests would require setting up a FileSystemRepository with temp directories
     // Those will be covered in the integration test file
 }
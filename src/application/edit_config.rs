@@ -0,0 +1,162 @@
+//! Edit config.toml in the editor use case
+
+use crate::error::{DjourError, Result};
+use crate::infrastructure::{
+    Config, EditorRole, EditorSession, FileSystemRepository, JournalRepository,
+};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+
+/// One key whose value differs between config.toml before and after an edit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigChange {
+    /// Dotted key, e.g. `mode` or `compile.date_format`
+    pub key: String,
+    /// TOML value before the edit (None = key was added)
+    pub before: Option<String>,
+    /// TOML value after the edit (None = key was removed)
+    pub after: Option<String>,
+}
+
+impl fmt::Display for ConfigChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.before, &self.after) {
+            (Some(before), Some(after)) => write!(f, "~ {}: {} -> {}", self.key, before, after),
+            (None, Some(after)) => write!(f, "+ {} = {}", self.key, after),
+            (Some(before), None) => write!(f, "- {} = {}", self.key, before),
+            (None, None) => write!(f, "  {}", self.key),
+        }
+    }
+}
+
+/// Open .djour/config.toml in the editor and validate it once the editor exits.
+///
+/// config.toml is copied to config.toml.bak first. When the edited file has
+/// problems, `reopen` is called with all of them and decides whether to edit
+/// again; otherwise (and whenever the editor fails) the backup is restored.
+/// On success the backup is removed and the changed keys are returned.
+pub fn edit_config<F>(repository: &FileSystemRepository, mut reopen: F) -> Result<Vec<ConfigChange>>
+where
+    F: FnMut(&[String]) -> bool,
+{
+    let config_path = repository.config_path();
+    let before = fs::read_to_string(&config_path)?;
+
+    // A config that no longer loads is exactly what --edit is for, so fall back
+    // to the environment's editor instead of failing.
+    let editor = match repository.load_config() {
+        Ok(config) => EditorSession::for_role(&config, EditorRole::Editor),
+        Err(_) => EditorSession::new(Config::detect_default_editor()),
+    };
+
+    repository.backup_config()?;
+    loop {
+        let edited = editor
+            .edit(&config_path)
+            .and_then(|()| fs::read_to_string(&config_path).map_err(DjourError::Io));
+        let after = match edited {
+            Ok(after) => after,
+            Err(e) => {
+                repository.restore_config_backup()?;
+                return Err(e);
+            }
+        };
+
+        match Config::parse_validated(&after) {
+            Ok(_) => {
+                repository.discard_config_backup()?;
+                return Ok(config_changes(&before, &after));
+            }
+            Err(problems) => {
+                if !reopen(&problems) {
+                    repository.restore_config_backup()?;
+                    return Err(DjourError::Config(format!(
+                        "config.toml had {} problem(s); restored the previous version",
+                        problems.len()
+                    )));
+                }
+            }
+        }
+    }
+}
+
+/// Keys whose values differ between two config.toml texts, in key order
+fn config_changes(before: &str, after: &str) -> Vec<ConfigChange> {
+    let before = flatten_toml(before);
+    let mut after = flatten_toml(after);
+
+    let mut changes = Vec::new();
+    for (key, old) in before {
+        match after.remove(&key) {
+            Some(new) if new == old => {}
+            new => changes.push(ConfigChange {
+                key,
+                before: Some(old),
+                after: new,
+            }),
+        }
+    }
+    changes.extend(after.into_iter().map(|(key, new)| ConfigChange {
+        key,
+        before: None,
+        after: Some(new),
+    }));
+    changes.sort_by(|a, b| a.key.cmp(&b.key));
+    changes
+}
+
+/// Leaf values of a TOML document keyed by dotted path (unparsable text has none)
+fn flatten_toml(contents: &str) -> BTreeMap<String, String> {
+    fn walk(prefix: &str, table: &toml::Table, out: &mut BTreeMap<String, String>) {
+        for (key, value) in table {
+            let path = if prefix.is_empty() {
+                key.clone()
+            } else {
+                format!("{}.{}", prefix, key)
+            };
+            match value {
+                toml::Value::Table(inner) => walk(&path, inner, out),
+                other => {
+                    out.insert(path, other.to_string());
+                }
+            }
+        }
+    }
+
+    let mut out = BTreeMap::new();
+    if let Ok(table) = contents.parse::<toml::Table>() {
+        walk("", &table, &mut out);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_changes_reports_changed_added_and_removed_keys() {
+        let before = "mode = \"daily\"\neditor = \"vim\"\nlocale = \"de\"\n";
+        let after = "mode = \"weekly\"\neditor = \"vim\"\n\n[queries]\nstandup = \"work\"\n";
+
+        let lines: Vec<String> = config_changes(before, after)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                "- locale = \"de\"",
+                "~ mode: \"daily\" -> \"weekly\"",
+                "+ queries.standup = \"work\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_config_changes_empty_when_unchanged() {
+        let text = "mode = \"daily\"\neditor = \"vim\"\n";
+        assert!(config_changes(text, &format!("# comment\n{}", text)).is_empty());
+    }
+}
@@ -1,16 +1,105 @@
 //! Initialize journal use case
 
-use crate::domain::JournalMode;
-use crate::error::Result;
+use crate::application::mode_hint::scan_mode_counts;
+use crate::application::tag_footer::refresh_tag_footer;
+use crate::domain::{load_template_for_week, JournalMode};
+use crate::error::{DjourError, Result};
+use crate::infrastructure::STATE_SUBDIRS;
 use crate::infrastructure::{Config, FileSystemRepository, JournalRepository};
+use chrono::{Duration, NaiveDate};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Onboarding note written by `init --with-examples` (matches no note pattern)
+pub const ONBOARDING_NOTE: &str = "README-NOTES.md";
+
+/// How many directory levels below an `init` target are searched for journals
+const NESTED_SCAN_DEPTH: usize = 4;
+
+/// Example added to the note two days before today
+const EARLIER_EXAMPLE: &str = "## First steps #example
+
+- Wrote my first djour note #example
+- Anything tagged like this shows up in `djour compile example`
+";
+
+/// Example added to today's note
+const TODAY_EXAMPLE: &str = "## Getting started #example
+
+- Tags on a heading apply to everything below it
+- Tag a single line to collect just that line #idea
+- Run `djour tags` to see every tag in the journal
+";
+
+const ONBOARDING_TEXT: &str = "# Welcome to your djour journal
+
+This file is not a dated note, so it never shows up in `djour list` or compilations.
+Delete it whenever you like.
+
+## Tags
+
+- Write `#word` anywhere in a note to tag it: `Call the bank #todo`
+- A tag on a heading applies to the whole section below it
+- A tag on a list item applies to its sub-items
+- Tags are case-insensitive and may nest with `/`: `#project/alpha`
+
+## Three commands to try
+
+1. `djour list` shows your notes, newest first
+2. `djour tags` counts every tag you have used
+3. `djour compile example` collects everything tagged `#example` into
+   `.compilations/example.md`
+
+Open today's note with `djour --open today`.
+";
+
+/// Mode for `djour init`, given the note files already in the directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InitModeChoice {
+    pub mode: JournalMode,
+    /// Mode most existing files are named for (at least two thirds), with their count
+    pub detected: Option<(JournalMode, usize)>,
+    /// Whether `mode` was asked for with `--mode`
+    pub explicit: bool,
+}
+
+impl InitModeChoice {
+    /// The detected mode and its count, when it differs from the chosen mode
+    pub fn mismatch(&self) -> Option<(JournalMode, usize)> {
+        self.detected.filter(|(mode, _)| *mode != self.mode)
+    }
+}
+
+/// Pick the journal mode for `init` at `path`.
+///
+/// Without a `requested` mode, a clear majority of existing note files picks
+/// the mode (daily otherwise); a requested mode always wins, and
+/// [`InitModeChoice::mismatch`] tells the caller to warn about it.
+pub fn choose_init_mode(path: &Path, requested: Option<JournalMode>) -> Result<InitModeChoice> {
+    let counts = scan_mode_counts(path, "")?;
+    let detected = counts.majority().map(|mode| (mode, counts.count(mode)));
+    let mode = requested
+        .or(detected.map(|(mode, _)| mode))
+        .unwrap_or_default();
+    Ok(InitModeChoice {
+        mode,
+        detected,
+        explicit: requested.is_some(),
+    })
+}
 
 /// Initialize a new journal at the specified path.
 pub fn init(path: &Path, mode: JournalMode) -> Result<()> {
+    init_with_config(path, &Config::new(mode))
+}
+
+/// Initialize a new journal at the specified path with `config`
+/// (for example one carrying the journal's name and author).
+pub fn init_with_config(path: &Path, config: &Config) -> Result<()> {
     // Create the directory if it doesn't exist
     if !path.exists() {
-        fs::create_dir_all(path)?;
+        fs::create_dir_all(path).map_err(|e| DjourError::write_failed(e, "create", path))?;
     }
 
     // Create repository for this path
@@ -19,14 +108,250 @@ pub fn init(path: &Path, mode: JournalMode) -> Result<()> {
     // Initialize .djour directory
     repo.initialize()?;
 
-    // Create default config
-    let config = Config::new(mode);
-
     // Save config
-    repo.save_config(&config)?;
-
-    println!("Initialized djour journal at {}", path.display());
-    println!("Mode: {:?}", mode);
+    repo.save_config(config)?;
 
     Ok(())
 }
+
+/// Root of the journal `path` lies inside, if one of its parent directories is a journal.
+///
+/// Discovery from inside `path` would stop at a journal created there, so
+/// `init` asks for `--nested` before hiding the outer journal that way.
+pub fn enclosing_journal(path: &Path) -> Result<Option<PathBuf>> {
+    let path = std::env::current_dir()?.join(path);
+    // Walk up from the closest directory that exists; missing ones are created by init
+    let start = match path.ancestors().find(|dir| dir.exists()) {
+        Some(dir) if dir == path => fs::canonicalize(dir)?.parent().map(Path::to_path_buf),
+        Some(dir) => Some(fs::canonicalize(dir)?),
+        None => None,
+    };
+    Ok(start
+        .and_then(|start| FileSystemRepository::discover_from(&start).ok())
+        .map(|repo| repo.root))
+}
+
+/// Journals already below `path` (up to [`NESTED_SCAN_DEPTH`] levels down),
+/// which would keep answering for their own directories after `init`
+pub fn nested_journals(path: &Path) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    let mut walk = WalkDir::new(path)
+        .min_depth(1)
+        .max_depth(NESTED_SCAN_DEPTH)
+        .into_iter();
+    while let Some(entry) = walk.next() {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        if !entry.file_type().is_dir() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy();
+        if name == ".djour" {
+            if entry.depth() > 1 {
+                found.extend(entry.path().parent().map(Path::to_path_buf));
+            }
+            walk.skip_current_dir();
+        } else if name.starts_with('.') {
+            walk.skip_current_dir();
+        }
+    }
+    found.sort();
+    found
+}
+
+/// Undo `init` at `path`: remove `.djour/` if it holds nothing but
+/// `config.toml` and empty `templates/`, cache or trash directories.
+///
+/// Anything else (custom templates, saved state, backups) makes it an error
+/// listing what is in the way; notes outside `.djour/` are never touched.
+pub fn undo_init(path: &Path) -> Result<()> {
+    let djour_dir = path.join(".djour");
+    if !djour_dir.is_dir() {
+        return Err(DjourError::NotDjourDirectory(path.to_path_buf()));
+    }
+
+    let mut standard_dirs = Vec::new();
+    let mut blocking = Vec::new();
+    for entry in fs::read_dir(&djour_dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let file_type = entry.file_type()?;
+        if name == "config.toml" && file_type.is_file() {
+            continue;
+        }
+        let standard = name == "templates" || STATE_SUBDIRS.contains(&name.as_str());
+        if !(standard && file_type.is_dir()) {
+            blocking.push(name);
+            continue;
+        }
+        let before = blocking.len();
+        for inner in fs::read_dir(entry.path())? {
+            blocking.push(format!("{}/{}", name, inner?.file_name().to_string_lossy()));
+        }
+        if blocking.len() == before {
+            standard_dirs.push(entry.path());
+        }
+    }
+
+    if !blocking.is_empty() {
+        blocking.sort();
+        let list: Vec<String> = blocking.iter().map(|name| format!("  {}", name)).collect();
+        return Err(DjourError::Config(format!(
+            "Not removing {}: it holds more than a new journal does:\n{}",
+            djour_dir.display(),
+            list.join("\n")
+        )));
+    }
+
+    let remove = |result: std::io::Result<()>, target: &Path| {
+        result.map_err(|e| DjourError::write_failed(e, "remove", target))
+    };
+    for dir in &standard_dirs {
+        remove(fs::remove_dir(dir), dir)?;
+    }
+    let config = djour_dir.join("config.toml");
+    if config.exists() {
+        remove(fs::remove_file(&config), &config)?;
+    }
+    remove(fs::remove_dir(&djour_dir), &djour_dir)
+}
+
+/// Seed a freshly initialized journal with example notes for new users.
+///
+/// Creates today's note and one from two days earlier from the mode's
+/// template, each with a short tagged example, plus [`ONBOARDING_NOTE`].
+/// Returns the written files in creation order (notes sharing a file, as in
+/// weekly or single mode, are listed once).
+pub fn seed_examples(repository: &FileSystemRepository, today: NaiveDate) -> Result<Vec<String>> {
+    let config = repository.load_config()?;
+    let mode = config.get_mode();
+    let template =
+        load_template_for_week(repository.root(), mode.template_name(), config.week_start)?;
+
+    let mut written: Vec<String> = Vec::new();
+    for (date, example) in [
+        (today - Duration::days(2), EARLIER_EXAMPLE),
+        (today, TODAY_EXAMPLE),
+    ] {
+        let filename = config.naming().filename_for_date(date);
+        let rendered = template.render_with_context(&config.render_context(date));
+        let mut content = if repository.note_exists(&filename) {
+            let existing = repository.read_note(&filename)?;
+            if matches!(mode, JournalMode::Single) {
+                // A new entry per date, as `djour --open` would append
+                format!("{}\n{}", existing, rendered)
+            } else {
+                existing
+            }
+        } else {
+            rendered
+        };
+        if !content.is_empty() && !content.ends_with("\n\n") {
+            content.push_str(if content.ends_with('\n') {
+                "\n"
+            } else {
+                "\n\n"
+            });
+        }
+        content.push_str(example);
+        repository.write_note(&filename, &refresh_tag_footer(&config, content))?;
+
+        if !written.contains(&filename) {
+            written.push(filename);
+        }
+    }
+
+    repository.write_note(ONBOARDING_NOTE, ONBOARDING_TEXT)?;
+    written.push(ONBOARDING_NOTE.to_string());
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_choose_init_mode_detects_majority() {
+        let temp = TempDir::new().unwrap();
+        let missing = choose_init_mode(&temp.path().join("new"), None).unwrap();
+        assert_eq!(missing.mode, JournalMode::Daily);
+        assert_eq!(missing.detected, None);
+
+        for name in [
+            "2025-W02-2025-01-06.md",
+            "2025-W03-2025-01-13.md",
+            "notes.md",
+        ] {
+            fs::write(temp.path().join(name), "").unwrap();
+        }
+        let auto = choose_init_mode(temp.path(), None).unwrap();
+        assert_eq!(auto.mode, JournalMode::Weekly);
+        assert_eq!(auto.detected, Some((JournalMode::Weekly, 2)));
+        assert!(!auto.explicit);
+        assert_eq!(auto.mismatch(), None);
+
+        let forced = choose_init_mode(temp.path(), Some(JournalMode::Daily)).unwrap();
+        assert_eq!(forced.mode, JournalMode::Daily);
+        assert_eq!(forced.mismatch(), Some((JournalMode::Weekly, 2)));
+    }
+
+    #[test]
+    fn test_enclosing_and_nested_journals() {
+        let temp = TempDir::new().unwrap();
+        let outer = temp.path().join("outer");
+        init(&outer, JournalMode::Daily).unwrap();
+        let outer = fs::canonicalize(&outer).unwrap();
+
+        assert_eq!(enclosing_journal(&outer).unwrap(), None);
+        assert_eq!(
+            enclosing_journal(&outer.join("work")).unwrap(),
+            Some(outer.clone())
+        );
+        assert_eq!(
+            enclosing_journal(&outer.join("work/../new/deeper")).unwrap(),
+            Some(outer.clone())
+        );
+
+        init(&outer.join("a/b"), JournalMode::Daily).unwrap();
+        init(&outer.join("a/b/c/d/e"), JournalMode::Daily).unwrap();
+        fs::create_dir_all(outer.join(".hidden/.djour")).unwrap();
+        assert_eq!(
+            nested_journals(temp.path()),
+            vec![temp.path().join("outer"), temp.path().join("outer/a/b")]
+        );
+        assert_eq!(nested_journals(&outer), vec![outer.join("a/b")]);
+    }
+
+    fn seeded(mode: JournalMode) -> (TempDir, Vec<String>) {
+        let temp = TempDir::new().unwrap();
+        init(temp.path(), mode).unwrap();
+        let repo = FileSystemRepository::new(temp.path().to_path_buf());
+        let today = NaiveDate::from_ymd_opt(2026, 10, 17).unwrap();
+        let written = seed_examples(&repo, today).unwrap();
+        (temp, written)
+    }
+
+    #[test]
+    fn test_seed_daily_writes_two_notes_and_onboarding() {
+        let (temp, written) = seeded(JournalMode::Daily);
+        assert_eq!(
+            written,
+            vec!["2026-10-15.md", "2026-10-17.md", ONBOARDING_NOTE]
+        );
+        let today = fs::read_to_string(temp.path().join("2026-10-17.md")).unwrap();
+        assert!(today.starts_with("# October 17, 2026\n\n## Getting started #example\n"));
+    }
+
+    #[test]
+    fn test_seed_single_appends_entries_in_date_order() {
+        let (temp, written) = seeded(JournalMode::Single);
+        assert_eq!(written, vec!["journal.md", ONBOARDING_NOTE]);
+        let journal = fs::read_to_string(temp.path().join("journal.md")).unwrap();
+        let first = journal.find("## First steps").unwrap();
+        let second = journal.find("## Getting started").unwrap();
+        assert!(first < second);
+        assert_eq!(journal.matches("# October").count(), 2);
+    }
+}
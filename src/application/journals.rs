@@ -0,0 +1,130 @@
+//! Journal registry use cases (`djour journal add|list|remove`)
+
+use crate::error::{DjourError, Result};
+use crate::infrastructure::{FileSystemRepository, JournalRegistry, JournalRepository};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A registered journal as shown by `djour journal list`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JournalListing {
+    pub name: String,
+    pub path: PathBuf,
+    /// Whether this is the journal commands would currently use
+    pub current: bool,
+}
+
+fn validate_journal_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(DjourError::Config(format!(
+            "Invalid journal name: '{}'. Use letters, numbers, '-' and '_'",
+            name
+        )))
+    }
+}
+
+/// Register the journal at `path` under `name` and return its absolute root.
+///
+/// The path must already be a journal (`djour init`). An existing name is
+/// only replaced when `replace` is set.
+pub fn add_journal(
+    registry: &mut JournalRegistry,
+    name: &str,
+    path: &Path,
+    replace: bool,
+) -> Result<PathBuf> {
+    validate_journal_name(name)?;
+    if !FileSystemRepository::new(path.to_path_buf()).is_initialized() {
+        return Err(DjourError::Config(format!(
+            "No .djour directory found in '{}'. Run 'djour init {}' first.",
+            path.display(),
+            path.display()
+        )));
+    }
+    if let Some(existing) = registry.get(name) {
+        if !replace {
+            return Err(DjourError::Config(format!(
+                "Journal '{}' is already registered at '{}'. Use --replace to point it elsewhere.",
+                name,
+                existing.display()
+            )));
+        }
+    }
+
+    let root = fs::canonicalize(path)?;
+    registry.journals.insert(name.to_string(), root.clone());
+    Ok(root)
+}
+
+/// Unregister `name`; the journal itself is left untouched.
+pub fn remove_journal(registry: &mut JournalRegistry, name: &str) -> Result<PathBuf> {
+    registry
+        .journals
+        .remove(name)
+        .ok_or_else(|| DjourError::Config(format!("Unknown journal '{}'; nothing removed", name)))
+}
+
+/// Registered journals in name order, marking the one rooted at `current_root`
+pub fn list_journals(
+    registry: &JournalRegistry,
+    current_root: Option<&Path>,
+) -> Vec<JournalListing> {
+    let current = current_root.and_then(|root| registry.name_of(root));
+    registry
+        .journals
+        .iter()
+        .map(|(name, path)| JournalListing {
+            name: name.clone(),
+            path: path.clone(),
+            current: current == Some(name.as_str()),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::init;
+    use crate::domain::JournalMode;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_add_list_remove() {
+        let temp = TempDir::new().unwrap();
+        let work = temp.path().join("work");
+        init(&work, JournalMode::Daily).unwrap();
+
+        let mut registry = JournalRegistry::default();
+        let root = add_journal(&mut registry, "work", &work, false).unwrap();
+        assert_eq!(root, fs::canonicalize(&work).unwrap());
+
+        let listed = list_journals(&registry, Some(&work));
+        assert_eq!(listed.len(), 1);
+        assert!(listed[0].current);
+        assert!(!list_journals(&registry, Some(temp.path()))[0].current);
+
+        assert!(remove_journal(&mut registry, "work").is_ok());
+        assert!(remove_journal(&mut registry, "work").is_err());
+    }
+
+    #[test]
+    fn test_add_rejects_bad_name_duplicate_and_non_journal() {
+        let temp = TempDir::new().unwrap();
+        init(temp.path(), JournalMode::Daily).unwrap();
+        let mut registry = JournalRegistry::default();
+
+        assert!(add_journal(&mut registry, "my work", temp.path(), false).is_err());
+        assert!(add_journal(&mut registry, "work", &temp.path().join("missing"), false).is_err());
+
+        add_journal(&mut registry, "work", temp.path(), false).unwrap();
+        let err = add_journal(&mut registry, "work", temp.path(), false).unwrap_err();
+        assert!(err.to_string().contains("--replace"), "{err}");
+        assert!(add_journal(&mut registry, "work", temp.path(), true).is_ok());
+    }
+}
@@ -1,18 +1,114 @@
 //! List notes use case
 
-use crate::domain::JournalMode;
+use crate::application::mode_hint::other_numbering_notes;
+use crate::application::note_structure::StructureCheck;
+use crate::domain::{first_heading, Diagnostics, NoteNaming, ARCHIVED_DIRS};
 use crate::error::Result;
-use crate::infrastructure::{FileSystemRepository, NoteEntry};
+use crate::infrastructure::{FileSystemRepository, JournalRepository, NoteEntry};
 use chrono::NaiveDate;
+use std::path::PathBuf;
 
 /// List notes with optional date range and limit.
+///
+/// `changed_since` keeps only notes whose file was modified on or after local
+/// midnight of that day, whatever date their filename carries. It is applied
+/// before `limit`.
+///
+/// In weekly and monthly mode each listed note is also checked against its
+/// built-in template; problems are recorded in `diagnostics` and never fail the listing.
+/// So are weekly notes named by the other week numbering than the configured one.
+///
+/// Recursive listings skip `archive/` and `trash/` unless `include_archived` is set.
+#[allow(clippy::too_many_arguments)]
 pub fn list_notes(
     repository: &FileSystemRepository,
-    mode: JournalMode,
+    naming: impl Into<NoteNaming>,
     from: Option<NaiveDate>,
     to: Option<NaiveDate>,
     limit: Option<usize>,
     recursive: bool,
+    include_archived: bool,
+    changed_since: Option<NaiveDate>,
+    diagnostics: &mut Diagnostics,
 ) -> Result<Vec<NoteEntry>> {
-    repository.list_notes(mode, from, to, limit, recursive)
+    let naming = naming.into();
+    for filename in other_numbering_notes(repository.root(), &naming)? {
+        diagnostics.warn(
+            filename,
+            format!(
+                "named by {} week numbering, but week_numbering is {}; a journal must use one system",
+                naming.week_numbering.other().code(),
+                naming.week_numbering.code()
+            ),
+        );
+    }
+
+    let exclude: Vec<PathBuf> = if include_archived {
+        Vec::new()
+    } else {
+        ARCHIVED_DIRS.iter().map(PathBuf::from).collect()
+    };
+    let notes = match changed_since {
+        None => repository.list_notes_excluding(
+            naming,
+            from,
+            to,
+            limit,
+            recursive,
+            &exclude,
+            diagnostics,
+        )?,
+        Some(since) => {
+            let mut notes = repository.list_notes_excluding(
+                naming,
+                from,
+                to,
+                None,
+                recursive,
+                &exclude,
+                diagnostics,
+            )?;
+            notes.retain(|note| note.changed_since(since));
+            if let Some(n) = limit {
+                notes.truncate(n);
+            }
+            notes
+        }
+    };
+
+    let config = repository.load_config()?;
+    if let Some(check) = StructureCheck::new(repository, &config) {
+        for note in &notes {
+            // Unreadable or empty notes are left to the commands that use their content.
+            match repository.read_note(&note.filename) {
+                Ok(content) if !content.trim().is_empty() => {
+                    check.check(note, &content, diagnostics)
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Ok(notes)
+}
+
+/// Set the title of every note to its first heading; unreadable notes get none
+pub fn read_titles(repository: &FileSystemRepository, notes: &mut [NoteEntry]) {
+    for note in notes {
+        note.title = repository
+            .read_note(&note.filename)
+            .ok()
+            .and_then(|content| first_heading(&content));
+    }
+}
+
+/// Order notes by title, ignoring case; untitled notes come last, ties by path
+pub fn sort_by_titles(notes: &mut [NoteEntry]) {
+    notes.sort_by_cached_key(|note| {
+        (
+            note.title.is_none(),
+            note.title.as_ref().map(|title| title.to_lowercase()),
+            note.filename.clone(),
+        )
+    });
 }
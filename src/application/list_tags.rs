@@ -1,22 +1,56 @@
 //! List tags use case
 
-use crate::error::Result;
+use crate::application::scan::{scan_notes, ScanProgress, ScanScope};
+use crate::domain::tags::{tags_in_note, TagGroup, TagNormalizer, TagParseOptions};
+use crate::domain::{check_unclosed_fence, Diagnostics, ExcludePatterns};
+use crate::error::{DjourError, Result};
 use crate::infrastructure::repository::JournalRepository;
-use crate::infrastructure::FileSystemRepository;
+use crate::infrastructure::{Config, FileSystemRepository};
 use chrono::NaiveDate;
-use regex::Regex;
-use std::collections::BTreeSet;
-use std::sync::OnceLock;
+use std::collections::BTreeMap;
 
-fn tag_regex() -> &'static Regex {
-    static REGEX: OnceLock<Regex> = OnceLock::new();
-    REGEX.get_or_init(|| Regex::new(r"#([a-zA-Z0-9_-]+)").unwrap())
+/// Count the tags in `content` (see [`tags_in_note`] for what is skipped)
+fn collect_tags_from_text(
+    content: &str,
+    options: TagParseOptions,
+    output: &mut BTreeMap<String, usize>,
+) {
+    for tag in tags_in_note(content, options) {
+        *output.entry(tag).or_default() += 1;
+    }
 }
 
-fn collect_tags_from_text(text: &str, output: &mut BTreeSet<String>) {
-    for captures in tag_regex().captures_iter(text) {
-        output.insert(captures[1].to_lowercase());
-    }
+/// Options for `djour tags`
+#[derive(Debug, Clone, Default)]
+pub struct TagOptions {
+    /// Start date filter (inclusive)
+    pub from: Option<NaiveDate>,
+    /// End date filter (inclusive)
+    pub to: Option<NaiveDate>,
+    /// Search subdirectories (excluding dot directories)
+    pub recursive: bool,
+    /// With `recursive`, also read notes under `archive/` and `trash/`
+    pub include_archived: bool,
+    /// Glob patterns of notes to leave out, in addition to `compile_exclude`
+    pub exclude: Vec<String>,
+}
+
+/// Tag occurrence counts of the notes `djour tags` read
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TagCounts {
+    pub counts: BTreeMap<String, usize>,
+    /// Earliest and latest date of the dated notes using each tag
+    pub dates: BTreeMap<String, (NaiveDate, NaiveDate)>,
+    /// Notes left out by exclude patterns
+    pub excluded: usize,
+    /// Problems found in the notes read, such as unclosed code fences
+    pub diagnostics: Diagnostics,
+}
+
+/// The `exclude` patterns given for one command plus the journal's `compile_exclude`
+pub fn exclude_patterns(config: &Config, exclude: &[String]) -> Result<ExcludePatterns> {
+    let patterns: Vec<&String> = exclude.iter().chain(&config.compile_exclude).collect();
+    ExcludePatterns::new(&patterns).map_err(DjourError::Config)
 }
 
 /// List all tags used in notes with optional date filters.
@@ -25,40 +59,182 @@ pub fn list_tags(
     from: Option<NaiveDate>,
     to: Option<NaiveDate>,
     recursive: bool,
+    include_archived: bool,
 ) -> Result<Vec<String>> {
+    let counts = list_tag_counts(repository, from, to, recursive, include_archived, None)?;
+    Ok(counts.into_keys().collect())
+}
+
+/// Count tag occurrences in notes with optional date filters.
+///
+/// Tags are keyed by their full (lowercased) name; parents of nested tags are
+/// only present when used directly. `progress` follows the notes being read.
+/// Recursive counts skip `archive/` and `trash/` unless `include_archived` is set.
+/// The tags of a tag-only paragraph followed by a list count once per list
+/// item carrying them, the way `compile` emits them.
+/// Exclude patterns do not apply: every note in range counts, as `retag` needs.
+pub fn list_tag_counts(
+    repository: &FileSystemRepository,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    recursive: bool,
+    include_archived: bool,
+    progress: Option<ScanProgress<'_>>,
+) -> Result<BTreeMap<String, usize>> {
+    let config = repository.load_config()?;
+    let scope = ScanScope {
+        from,
+        to,
+        recursive,
+        include_archived,
+        ..ScanScope::default()
+    };
+    Ok(count_in_scope(repository, &config, &scope, progress)?.counts)
+}
+
+/// Count tag occurrences for `djour tags`, leaving out notes that match the
+/// `exclude` patterns or the journal's `compile_exclude`
+pub fn count_tags(
+    repository: &FileSystemRepository,
+    options: &TagOptions,
+    progress: Option<ScanProgress<'_>>,
+) -> Result<TagCounts> {
     let config = repository.load_config()?;
-    let notes = repository.list_notes(config.get_mode(), from, to, None, recursive)?;
+    let scope = ScanScope {
+        from: options.from,
+        to: options.to,
+        recursive: options.recursive,
+        include_archived: options.include_archived,
+        exclude_patterns: exclude_patterns(&config, &options.exclude)?,
+        ..ScanScope::default()
+    };
+    count_in_scope(repository, &config, &scope, progress)
+}
 
-    let mut tags = BTreeSet::new();
-    for note in notes {
-        let content = repository.read_note(&note.filename)?;
-        collect_tags_from_text(&content, &mut tags);
+fn count_in_scope(
+    repository: &FileSystemRepository,
+    config: &Config,
+    scope: &ScanScope,
+    progress: Option<ScanProgress<'_>>,
+) -> Result<TagCounts> {
+    let scan = scan_notes(repository, config, scope, progress)?;
+
+    let mut tags = BTreeMap::new();
+    let mut dates: BTreeMap<String, (NaiveDate, NaiveDate)> = BTreeMap::new();
+    let mut diagnostics = scan.diagnostics;
+    for note in scan.notes {
+        if let Some(warning) = check_unclosed_fence(&note.content) {
+            diagnostics.warn(&note.entry.filename, warning);
+        }
+        let mut note_tags = BTreeMap::new();
+        collect_tags_from_text(&note.content, config.tag_parse_options(), &mut note_tags);
+
+        for (tag, count) in note_tags {
+            if let Some(date) = note.entry.date {
+                dates
+                    .entry(tag.clone())
+                    .and_modify(|(first, last)| {
+                        *first = (*first).min(date);
+                        *last = (*last).max(date);
+                    })
+                    .or_insert((date, date));
+            }
+            *tags.entry(tag).or_default() += count;
+        }
     }
 
-    Ok(tags.into_iter().collect())
+    Ok(TagCounts {
+        counts: tags,
+        dates,
+        excluded: scan.excluded,
+        diagnostics,
+    })
+}
+
+/// Count tags (as [`count_tags`]) and group spellings that differ only in `-`, `_` or case.
+///
+/// Spellings listed in the `normalize_exempt` config key are kept in groups of
+/// their own. Also returns the counts the groups were made from.
+pub fn list_tag_groups(
+    repository: &FileSystemRepository,
+    options: &TagOptions,
+    progress: Option<ScanProgress<'_>>,
+) -> Result<(Vec<TagGroup>, TagCounts)> {
+    let config = repository.load_config()?;
+    let counts = count_tags(repository, options, progress)?;
+    let groups = TagNormalizer::new(&config.normalize_exempt).group(&counts.counts);
+    Ok((groups, counts))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn hierarchical(hierarchical_tags: bool) -> TagParseOptions {
+        TagParseOptions {
+            hierarchical_tags,
+            ..TagParseOptions::default()
+        }
+    }
+
     #[test]
     fn collect_tags_normalizes_and_deduplicates() {
-        let mut tags = BTreeSet::new();
-        collect_tags_from_text("one #Work and #work and #team_ops", &mut tags);
+        let mut tags = BTreeMap::new();
+        collect_tags_from_text(
+            "one #Work and #work and #team_ops",
+            hierarchical(true),
+            &mut tags,
+        );
         assert_eq!(
-            tags.into_iter().collect::<Vec<String>>(),
-            vec!["team_ops".to_string(), "work".to_string()]
+            tags.into_iter().collect::<Vec<(String, usize)>>(),
+            vec![("team_ops".to_string(), 1), ("work".to_string(), 2)]
         );
     }
 
     #[test]
     fn collect_tags_supports_dash_and_numbers() {
-        let mut tags = BTreeSet::new();
-        collect_tags_from_text("Tasks: #project-alpha #task1", &mut tags);
+        let mut tags = BTreeMap::new();
+        collect_tags_from_text(
+            "Tasks: #project-alpha #task1",
+            hierarchical(true),
+            &mut tags,
+        );
         assert_eq!(
-            tags.into_iter().collect::<Vec<String>>(),
+            tags.into_keys().collect::<Vec<String>>(),
             vec!["project-alpha".to_string(), "task1".to_string()]
         );
     }
+
+    #[test]
+    fn collect_tags_reads_pasted_characters() {
+        let mut tags = BTreeMap::new();
+        collect_tags_from_text(
+            "\u{FF03}Work and #work\u{00A0}#team\u{2013}ops",
+            hierarchical(true),
+            &mut tags,
+        );
+        assert_eq!(
+            tags.into_iter().collect::<Vec<(String, usize)>>(),
+            vec![("team-ops".to_string(), 1), ("work".to_string(), 2)]
+        );
+    }
+
+    #[test]
+    fn collect_tags_respects_hierarchy_switch() {
+        let text = "Working on #project/alpha/backend today";
+
+        let mut tags = BTreeMap::new();
+        collect_tags_from_text(text, hierarchical(true), &mut tags);
+        assert_eq!(
+            tags.into_keys().collect::<Vec<String>>(),
+            vec!["project/alpha/backend".to_string()]
+        );
+
+        let mut tags = BTreeMap::new();
+        collect_tags_from_text(text, hierarchical(false), &mut tags);
+        assert_eq!(
+            tags.into_keys().collect::<Vec<String>>(),
+            vec!["project".to_string()]
+        );
+    }
 }
@@ -0,0 +1,175 @@
+//! List todos use case
+
+use crate::application::scan::{scan_tagged_content, ScanProgress, ScanScope};
+use crate::domain::tags::{MatchContext, TagQuery};
+use crate::domain::{extract_tasks, Diagnostics, TaskItem};
+use crate::error::Result;
+use crate::infrastructure::repository::JournalRepository;
+use crate::infrastructure::FileSystemRepository;
+use chrono::NaiveDate;
+use std::path::PathBuf;
+
+/// Which task items `djour todos` lists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TaskStatus {
+    /// Unchecked items only
+    #[default]
+    Open,
+    /// Checked items only (`--done`)
+    Done,
+    /// Both (`--all`)
+    All,
+}
+
+impl TaskStatus {
+    fn includes(&self, task: &TaskItem) -> bool {
+        match self {
+            TaskStatus::Open => !task.checked,
+            TaskStatus::Done => task.checked,
+            TaskStatus::All => true,
+        }
+    }
+}
+
+/// Options for listing task items
+#[derive(Debug, Clone, Default)]
+pub struct TodoOptions {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub recursive: bool,
+    /// Tag query a task's tags (including inherited ones) must match
+    pub query: Option<String>,
+    pub status: TaskStatus,
+}
+
+/// List task items of the journal, oldest first so stale tasks come first.
+///
+/// Undated notes (single mode) come last; items of one note keep their order.
+/// Warnings from listing the notes go to `diagnostics`.
+pub fn list_todos(
+    repository: &FileSystemRepository,
+    options: &TodoOptions,
+    diagnostics: &mut Diagnostics,
+    progress: Option<ScanProgress<'_>>,
+) -> Result<Vec<TaskItem>> {
+    let config = repository.load_config()?;
+    let query = options
+        .query
+        .as_deref()
+        .map(|query| TagQuery::parse_with_value_tags(query, config.value_tags))
+        .transpose()?;
+    let scope = ScanScope {
+        from: options.from,
+        to: options.to,
+        recursive: options.recursive,
+        include_archived: true,
+        ..ScanScope::default()
+    };
+    let parse_options = config.tag_parse_options();
+
+    let mut tasks = Vec::new();
+    for note in scan_tagged_content(repository, &config, &scope, progress, diagnostics)? {
+        let path = PathBuf::from(&note.entry.filename);
+        tasks.extend(
+            extract_tasks(&note.content, &path, note.entry.date, parse_options)
+                .into_iter()
+                .filter(|task| options.status.includes(task))
+                .filter(|task| {
+                    query.as_ref().is_none_or(|query| {
+                        query.matches_context(&MatchContext {
+                            tags: &task.tags,
+                            source_file: &task.source_file,
+                        })
+                    })
+                }),
+        );
+    }
+
+    tasks.sort_by(|a, b| {
+        let date_order = match (a.date, b.date) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        };
+        date_order
+            .then_with(|| a.source_file.cmp(&b.source_file))
+            .then(a.line.cmp(&b.line))
+    });
+    Ok(tasks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::init;
+    use crate::domain::JournalMode;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn journal() -> (TempDir, FileSystemRepository) {
+        let temp = TempDir::new().unwrap();
+        init(temp.path(), JournalMode::Daily).unwrap();
+        fs::write(
+            temp.path().join("2025-01-16.md"),
+            "## Client X #clientx\n\n- [ ] send invoice\n- [x] call back\n",
+        )
+        .unwrap();
+        fs::write(
+            temp.path().join("2025-01-10.md"),
+            "- [ ] follow up with legal #work\n",
+        )
+        .unwrap();
+        let repo = FileSystemRepository::new(temp.path().to_path_buf());
+        (temp, repo)
+    }
+
+    fn texts(tasks: &[TaskItem]) -> Vec<&str> {
+        tasks.iter().map(|task| task.text.as_str()).collect()
+    }
+
+    #[test]
+    fn test_open_tasks_oldest_first() {
+        let (_temp, repo) = journal();
+        let tasks = list_todos(
+            &repo,
+            &TodoOptions::default(),
+            &mut Diagnostics::new(),
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            texts(&tasks),
+            vec!["follow up with legal #work", "send invoice"]
+        );
+    }
+
+    #[test]
+    fn test_status_and_query_filters() {
+        let (_temp, repo) = journal();
+        let done = TodoOptions {
+            status: TaskStatus::Done,
+            ..TodoOptions::default()
+        };
+        assert_eq!(
+            texts(&list_todos(&repo, &done, &mut Diagnostics::new(), None).unwrap()),
+            vec!["call back"]
+        );
+
+        let client = TodoOptions {
+            status: TaskStatus::All,
+            query: Some("clientx".to_string()),
+            ..TodoOptions::default()
+        };
+        assert_eq!(
+            texts(&list_todos(&repo, &client, &mut Diagnostics::new(), None).unwrap()),
+            vec!["send invoice", "call back"]
+        );
+
+        let bad = TodoOptions {
+            query: Some("AND".to_string()),
+            ..TodoOptions::default()
+        };
+        assert!(list_todos(&repo, &bad, &mut Diagnostics::new(), None).is_err());
+    }
+}
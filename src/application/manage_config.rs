@@ -1,10 +1,60 @@
 //! Config management use case
 
-use crate::domain::JournalMode;
+use crate::application::choose_init_mode;
+use crate::domain::tags::syntax::is_valid_tag_name;
+use crate::domain::{
+    split_patterns, ArchiveLayout, ExcludePatterns, JournalMode, Locale, WeekNumbering, WeekStart,
+};
 use crate::error::{DjourError, Result};
-use crate::infrastructure::{Config, FileSystemRepository, JournalRepository};
+use crate::infrastructure::config::check_batch_template;
+use crate::infrastructure::{
+    config_repair, Config, FileSystemRepository, JournalRepository, StateLocation,
+};
+use std::fs;
+use std::path::PathBuf;
 use std::str::FromStr;
 
+/// Keys accepted by `djour config`.
+pub const CONFIG_KEYS: &[&str] = &[
+    "mode",
+    "editor",
+    "editor_shell",
+    "viewer",
+    "editor_max_files",
+    "editor_batch_template",
+    "name",
+    "author",
+    "locale",
+    "frontmatter_tags",
+    "hierarchical_tags",
+    "value_tags",
+    "date_guard_days",
+    "create_on_resolve",
+    "archive_layout",
+    "week_start",
+    "week_numbering",
+    "filename_prefix",
+    "allow_filename_suffix",
+    "wikilinks",
+    "follow_symlinks",
+    "embed_max_bytes",
+    "normalize_exempt",
+    "compile_exclude",
+    "private_tag",
+    "auto_tag_footer",
+    "state_dir",
+    "update_check",
+    "update_url",
+];
+
+fn unknown_key_error(key: &str) -> DjourError {
+    DjourError::Config(format!(
+        "Unknown config key: '{}'. Valid keys are: {}",
+        key,
+        CONFIG_KEYS.join(", ")
+    ))
+}
+
 /// Get a single config value.
 pub fn get_config(repository: &FileSystemRepository, key: &str) -> Result<String> {
     let config = repository.load_config()?;
@@ -12,16 +62,42 @@ pub fn get_config(repository: &FileSystemRepository, key: &str) -> Result<String
     match key {
         "mode" => Ok(format!("{:?}", config.mode).to_lowercase()),
         "editor" => Ok(config.editor.clone()),
-        _ => Err(DjourError::Config(format!(
-            "Unknown config key: '{}'. Valid keys are: mode, editor",
-            key
-        ))),
+        "editor_shell" => Ok(config.editor_shell.to_string()),
+        "viewer" => Ok(config.viewer.clone().unwrap_or_default()),
+        "editor_max_files" => Ok(config.editor_max_files.to_string()),
+        "editor_batch_template" => Ok(config.editor_batch_template.clone().unwrap_or_default()),
+        "name" => Ok(config.name.clone()),
+        "author" => Ok(config.author.clone()),
+        "locale" => Ok(config.locale.code().to_string()),
+        "frontmatter_tags" => Ok(config.frontmatter_tags.to_string()),
+        "hierarchical_tags" => Ok(config.hierarchical_tags.to_string()),
+        "value_tags" => Ok(config.value_tags.to_string()),
+        "date_guard_days" => Ok(config.date_guard_days.to_string()),
+        "create_on_resolve" => Ok(config.create_on_resolve.to_string()),
+        "archive_layout" => Ok(config.archive_layout.code().to_string()),
+        "week_start" => Ok(config.week_start.code().to_string()),
+        "week_numbering" => Ok(config.week_numbering.code().to_string()),
+        "filename_prefix" => Ok(config.filename_prefix.clone()),
+        "allow_filename_suffix" => Ok(config.allow_filename_suffix.to_string()),
+        "wikilinks" => Ok(config.wikilinks.to_string()),
+        "follow_symlinks" => Ok(config.follow_symlinks.to_string()),
+        "embed_max_bytes" => Ok(config.embed_max_bytes.to_string()),
+        "normalize_exempt" => Ok(config.normalize_exempt.join(",")),
+        "compile_exclude" => Ok(config.compile_exclude.join(",")),
+        "private_tag" => Ok(config.private_tag.clone()),
+        "auto_tag_footer" => Ok(config.auto_tag_footer.to_string()),
+        "state_dir" => Ok(config.state_dir.code().to_string()),
+        "update_check" => Ok(config.update_check.to_string()),
+        "update_url" => Ok(config.update_url.clone()),
+        _ => Err(unknown_key_error(key)),
     }
 }
 
 /// Set a config value.
 pub fn set_config(repository: &FileSystemRepository, key: &str, value: &str) -> Result<()> {
-    let mut config = repository.load_config()?;
+    // Refuse before `state_dir` moves anything
+    repository.ensure_writable("write", &repository.config_path())?;
+    let mut config = load_or_bootstrap(repository)?;
 
     match key {
         "mode" => {
@@ -31,11 +107,206 @@ pub fn set_config(repository: &FileSystemRepository, key: &str, value: &str) ->
         "editor" => {
             config.editor = value.to_string();
         }
+        "editor_shell" => {
+            config.editor_shell = value.parse().map_err(|_| {
+                DjourError::Config(format!(
+                    "Invalid value for editor_shell: '{}'. Use 'true' or 'false'",
+                    value
+                ))
+            })?;
+        }
+        "viewer" => {
+            if value.trim().is_empty() {
+                return Err(DjourError::Config(
+                    "Invalid value for viewer: the viewer command is empty".to_string(),
+                ));
+            }
+            config.viewer = Some(value.to_string());
+        }
+        "editor_max_files" => {
+            config.editor_max_files = value
+                .parse()
+                .ok()
+                .filter(|&n: &usize| n > 0)
+                .ok_or_else(|| {
+                    DjourError::Config(format!(
+                        "Invalid value for editor_max_files: '{}'. Use a number of files of at least 1",
+                        value
+                    ))
+                })?;
+        }
+        // An empty value clears the template
+        "editor_batch_template" => {
+            let template = value.trim();
+            if template.is_empty() {
+                config.editor_batch_template = None;
+            } else {
+                check_batch_template(template).map_err(DjourError::Config)?;
+                config.editor_batch_template = Some(template.to_string());
+            }
+        }
+        // An empty value clears the name or author
+        "name" => {
+            config.name = value.trim().to_string();
+        }
+        "author" => {
+            config.author = value.trim().to_string();
+        }
+        "locale" => {
+            config.locale = Locale::from_str(value).map_err(DjourError::Config)?;
+        }
+        "frontmatter_tags" => {
+            config.frontmatter_tags = value.parse().map_err(|_| {
+                DjourError::Config(format!(
+                    "Invalid value for frontmatter_tags: '{}'. Use 'true' or 'false'",
+                    value
+                ))
+            })?;
+        }
+        "hierarchical_tags" => {
+            config.hierarchical_tags = value.parse().map_err(|_| {
+                DjourError::Config(format!(
+                    "Invalid value for hierarchical_tags: '{}'. Use 'true' or 'false'",
+                    value
+                ))
+            })?;
+        }
+        "value_tags" => {
+            config.value_tags = value.parse().map_err(|_| {
+                DjourError::Config(format!(
+                    "Invalid value for value_tags: '{}'. Use 'true' or 'false'",
+                    value
+                ))
+            })?;
+        }
+        "date_guard_days" => {
+            config.date_guard_days = value.parse().map_err(|_| {
+                DjourError::Config(format!(
+                    "Invalid value for date_guard_days: '{}'. Use a number of days (0 disables)",
+                    value
+                ))
+            })?;
+        }
+        "create_on_resolve" => {
+            config.create_on_resolve = value.parse().map_err(|_| {
+                DjourError::Config(format!(
+                    "Invalid value for create_on_resolve: '{}'. Use 'true' or 'false'",
+                    value
+                ))
+            })?;
+        }
+        "archive_layout" => {
+            config.archive_layout = ArchiveLayout::from_str(value).map_err(DjourError::Config)?;
+        }
+        "week_start" => {
+            config.week_start = WeekStart::from_str(value).map_err(DjourError::Config)?;
+        }
+        "week_numbering" => {
+            config.week_numbering = WeekNumbering::from_str(value).map_err(DjourError::Config)?;
+        }
+        "filename_prefix" => {
+            if value.contains(['/', '\\']) {
+                return Err(DjourError::Config(format!(
+                    "Invalid value for filename_prefix: '{}'. The prefix cannot contain path separators",
+                    value
+                )));
+            }
+            config.filename_prefix = value.to_string();
+        }
+        "allow_filename_suffix" => {
+            config.allow_filename_suffix = value.parse().map_err(|_| {
+                DjourError::Config(format!(
+                    "Invalid value for allow_filename_suffix: '{}'. Use 'true' or 'false'",
+                    value
+                ))
+            })?;
+        }
+        "wikilinks" => {
+            config.wikilinks = value.parse().map_err(|_| {
+                DjourError::Config(format!(
+                    "Invalid value for wikilinks: '{}'. Use 'true' or 'false'",
+                    value
+                ))
+            })?;
+        }
+        "follow_symlinks" => {
+            config.follow_symlinks = value.parse().map_err(|_| {
+                DjourError::Config(format!(
+                    "Invalid value for follow_symlinks: '{}'. Use 'true' or 'false'",
+                    value
+                ))
+            })?;
+        }
+        "embed_max_bytes" => {
+            config.embed_max_bytes = value.parse().map_err(|_| {
+                DjourError::Config(format!(
+                    "Invalid value for embed_max_bytes: '{}'. Use a number of bytes (0 = no limit)",
+                    value
+                ))
+            })?;
+        }
+        "normalize_exempt" => {
+            // Comma-separated tag names; an empty value clears the list.
+            let mut tags = Vec::new();
+            for tag in value.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+                let name = tag.strip_prefix('#').unwrap_or(tag);
+                if !is_valid_tag_name(name) {
+                    return Err(DjourError::Config(format!(
+                        "Invalid tag in normalize_exempt: '{}'",
+                        tag
+                    )));
+                }
+                tags.push(name.to_lowercase());
+            }
+            config.normalize_exempt = tags;
+        }
+        // Comma-separated globs; an empty value clears the list
+        "compile_exclude" => {
+            let patterns = split_patterns(value);
+            ExcludePatterns::new(&patterns).map_err(|e| {
+                DjourError::Config(format!("Invalid value for compile_exclude: {}", e))
+            })?;
+            config.compile_exclude = patterns;
+        }
+        // An empty value disables redaction
+        "private_tag" => {
+            let tag = value.trim();
+            let name = tag.strip_prefix('#').unwrap_or(tag);
+            if !name.is_empty() && !is_valid_tag_name(name) {
+                return Err(DjourError::Config(format!(
+                    "Invalid value for private_tag: '{}'. Use a tag name, or an empty value to disable redaction",
+                    value
+                )));
+            }
+            config.private_tag = name.to_lowercase();
+        }
+        "auto_tag_footer" => {
+            config.auto_tag_footer = value.parse().map_err(|_| {
+                DjourError::Config(format!(
+                    "Invalid value for auto_tag_footer: '{}'. Use 'true' or 'false'",
+                    value
+                ))
+            })?;
+        }
+        "state_dir" => {
+            config.state_dir = StateLocation::from_str(value).map_err(DjourError::Config)?;
+            // Move existing caches and trash now, before the config points elsewhere
+            repository.state_paths(&config)?;
+        }
+        "update_check" => {
+            config.update_check = value.parse().map_err(|_| {
+                DjourError::Config(format!(
+                    "Invalid value for update_check: '{}'. Use 'true' or 'false'",
+                    value
+                ))
+            })?;
+        }
+        // An empty value restores the default endpoint
+        "update_url" => {
+            config.update_url = value.trim().to_string();
+        }
         _ => {
-            return Err(DjourError::Config(format!(
-                "Unknown config key: '{}'. Valid keys are: mode, editor",
-                key
-            )));
+            return Err(unknown_key_error(key));
         }
     }
 
@@ -43,6 +314,63 @@ pub fn set_config(repository: &FileSystemRepository, key: &str, value: &str) ->
     Ok(())
 }
 
+/// Load config.toml, or start from defaults when `.djour/` exists without one
+fn load_or_bootstrap(repository: &FileSystemRepository) -> Result<Config> {
+    match repository.load_config() {
+        Err(DjourError::NotDjourDirectory(_))
+            if repository.is_initialized() && !repository.config_path().exists() =>
+        {
+            Ok(Config::new(choose_init_mode(repository.root(), None)?.mode))
+        }
+        result => result,
+    }
+}
+
+/// Outcome of `djour config --repair`
+#[derive(Debug, Clone)]
+pub struct ConfigRepairReport {
+    /// Copy of the damaged file (None when config.toml was missing)
+    pub backup: Option<PathBuf>,
+    /// Lines written back as `# djour-repair: ` comments
+    pub commented: Vec<String>,
+}
+
+/// Rewrite config.toml keeping every value that loads.
+///
+/// Unparseable lines, unknown keys and wrong values are commented out with
+/// `# djour-repair: `; the original is first copied to
+/// config.toml.broken-<timestamp>. A missing config.toml is written from
+/// defaults. Returns None when the file already loads and has nothing to drop.
+pub fn repair_config(repository: &FileSystemRepository) -> Result<Option<ConfigRepairReport>> {
+    let path = repository.config_path();
+    repository.ensure_writable("write", &path)?;
+
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            repository.save_config(&load_or_bootstrap(repository)?)?;
+            return Ok(Some(ConfigRepairReport {
+                backup: None,
+                commented: Vec::new(),
+            }));
+        }
+        Err(e) => return Err(DjourError::Io(e)),
+    };
+
+    let repair = config_repair::repair(&contents);
+    if repair.is_clean() && repository.load_config().is_ok() {
+        return Ok(None);
+    }
+
+    let backup = repository.backup_broken_config()?;
+    let repaired = repair.contents()?;
+    fs::write(&path, repaired).map_err(|e| DjourError::write_failed(e, "write", &path))?;
+    Ok(Some(ConfigRepairReport {
+        backup: Some(backup),
+        commented: repair.commented,
+    }))
+}
+
 /// List all config values.
 pub fn list_config(repository: &FileSystemRepository) -> Result<Config> {
     repository.load_config()
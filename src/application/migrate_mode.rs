@@ -2,22 +2,43 @@
 //!
 //! Changes configured mode and migrates existing notes on disk.
 
+use crate::application::tag_footer::refresh_tag_footer;
+use crate::domain::markdown::preserve_final_newline;
+use crate::domain::mode_migration::daily_prefix;
+use crate::domain::tags::split_at_tag_footer;
 use crate::domain::{
-    inject_daily_into_weekly, split_weekly_into_daily_bodies, strip_daily_prefix, week_start,
-    JournalMode, Template,
+    check_note_structure, inject_daily_into_weekly, reset_weekly_sections,
+    split_weekly_into_daily_bodies, strip_daily_prefix, unified_diff, week_start, Diagnostics,
+    DiffOptions, JournalMode, RenderContext, Template, WeekNumbering,
 };
 use crate::error::{DjourError, Result};
-use crate::infrastructure::{FileSystemRepository, JournalRepository, NoteEntry};
-use chrono::{Duration, Utc};
+use crate::infrastructure::{Config, FileSystemRepository, JournalRepository, NoteEntry};
+use chrono::{Duration, NaiveDate, Utc};
+use std::collections::hash_map::DefaultHasher;
 use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
 use std::path::PathBuf;
 
+/// Where weekly -> daily keeps a copy of each weekly note it splits, so
+/// daily -> weekly can rebuild the note with its untouched days byte for byte
+const SPLIT_WEEKLY_DIR: &str = ".djour/split-weekly";
+
 #[derive(Debug, Clone)]
 pub struct ModeMigrationOptions {
     pub to_mode: JournalMode,
     pub from_mode: Option<JournalMode>,
     pub dry_run: bool,
     pub archive_dir: Option<PathBuf>,
+    /// Suppress progress output (dry-run plans are still printed)
+    pub quiet: bool,
+    /// Nest daily headings under weekday headings (daily -> weekly) and move
+    /// sub-headings up a level (weekly -> daily)
+    pub promote_headings: bool,
+    /// Print each planned file as a diff of its current content (`--show-diff`)
+    pub show_diff: Option<DiffOptions>,
+    /// Set the mode when there is nothing to migrate but notes in the target
+    /// mode's format already exist
+    pub force: bool,
 }
 
 pub fn migrate_mode(
@@ -31,10 +52,23 @@ struct MigrateModeContext<'a> {
     repository: &'a FileSystemRepository,
 }
 
+/// Notes on disk before planning, telling a migration from a plain mode switch
+#[derive(Debug, Clone)]
+enum NoteInventory {
+    /// There are notes in the source mode to migrate
+    Migrate,
+    /// No notes in the source mode, but these are already in the target mode's format
+    AlreadyInTarget(Vec<NoteEntry>),
+    /// No notes in either mode
+    Empty,
+}
+
 #[derive(Debug, Clone)]
 struct DailyEntry {
     filename: String,
     date: chrono::NaiveDate,
+    /// The note as read, to tell whether it changed since a weekly note was split
+    content: String,
     body: String,
 }
 
@@ -43,8 +77,12 @@ struct WeekPlan {
     week_start: chrono::NaiveDate,
     target_weekly: String, // new format
     target_existed: bool,
+    /// Fingerprint of the target content the plan was computed from (None if it did not exist)
+    base_fingerprint: Option<u64>,
     updated_content: String,
     daily_entries: Vec<DailyEntry>,
+    /// Copy of the split weekly note the target was rebuilt from, archived once written
+    split_record: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -64,6 +102,8 @@ struct WeeklyFilePlan {
     filename: String,
     week_start: chrono::NaiveDate,
     daily_creates: Vec<DailyCreate>,
+    /// Content of the weekly note, kept in SPLIT_WEEKLY_DIR
+    original: String,
 }
 
 #[derive(Debug, Clone)]
@@ -80,10 +120,12 @@ impl MigrateModeContext<'_> {
         let to_mode = options.to_mode;
 
         if from_mode == to_mode {
-            println!(
-                "Mode already set to {}. Nothing to do.",
-                format!("{:?}", to_mode).to_lowercase()
-            );
+            if !options.quiet {
+                println!(
+                    "Mode already set to {}. Nothing to do.",
+                    format!("{:?}", to_mode).to_lowercase()
+                );
+            }
             return Ok(());
         }
 
@@ -100,12 +142,24 @@ impl MigrateModeContext<'_> {
 
         self.refuse_custom_templates()?;
 
+        let inventory = self.inventory(&config, from_mode, to_mode)?;
+        if !matches!(inventory, NoteInventory::Migrate) {
+            return self.switch_without_notes(config, from_mode, to_mode, inventory, &options);
+        }
+
         let archive_dir = self.resolve_archive_dir(options.archive_dir)?;
+        let show_plan = options.dry_run || !options.quiet;
 
         match (from_mode, to_mode) {
             (JournalMode::Daily, JournalMode::Weekly) => {
-                let plan = self.plan_daily_to_weekly()?;
-                self.print_plan_daily_to_weekly(&archive_dir, &plan);
+                let plan = self.plan_daily_to_weekly(&config, options.promote_headings)?;
+                if show_plan {
+                    self.print_plan_daily_to_weekly(
+                        &archive_dir,
+                        &plan,
+                        options.show_diff.as_ref(),
+                    )?;
+                }
 
                 if options.dry_run {
                     return Ok(());
@@ -115,11 +169,20 @@ impl MigrateModeContext<'_> {
                 config.mode = JournalMode::Weekly;
                 self.backup_config(&archive_dir)?;
                 self.repository.save_config(&config)?;
-                println!("Migration complete. Mode set to weekly.");
+                if !options.quiet {
+                    println!("Migration complete. Mode set to weekly.");
+                }
             }
             (JournalMode::Weekly, JournalMode::Daily) => {
-                let plan = self.plan_weekly_to_daily(&archive_dir)?;
-                self.print_plan_weekly_to_daily(&archive_dir, &plan);
+                let plan =
+                    self.plan_weekly_to_daily(&archive_dir, &config, options.promote_headings)?;
+                if show_plan {
+                    self.print_plan_weekly_to_daily(
+                        &archive_dir,
+                        &plan,
+                        options.show_diff.as_ref(),
+                    );
+                }
 
                 if options.dry_run {
                     return Ok(());
@@ -129,7 +192,9 @@ impl MigrateModeContext<'_> {
                 config.mode = JournalMode::Daily;
                 self.backup_config(&archive_dir)?;
                 self.repository.save_config(&config)?;
-                println!("Migration complete. Mode set to daily.");
+                if !options.quiet {
+                    println!("Migration complete. Mode set to daily.");
+                }
             }
             _ => unreachable!(),
         }
@@ -137,6 +202,121 @@ impl MigrateModeContext<'_> {
         Ok(())
     }
 
+    /// Take stock of the notes in `from` and `to` mode before planning
+    fn inventory(
+        &self,
+        config: &Config,
+        from: JournalMode,
+        to: JournalMode,
+    ) -> Result<NoteInventory> {
+        if !self.mode_notes(config, from)?.is_empty() {
+            return Ok(NoteInventory::Migrate);
+        }
+        let existing = self.mode_notes(config, to)?;
+        Ok(if existing.is_empty() {
+            NoteInventory::Empty
+        } else {
+            NoteInventory::AlreadyInTarget(existing)
+        })
+    }
+
+    /// Notes named for `mode`; for weekly, only the current format is migrated
+    fn mode_notes(&self, config: &Config, mode: JournalMode) -> Result<Vec<NoteEntry>> {
+        let naming = config.naming_for(mode);
+        let notes = self
+            .repository
+            .list_notes(naming.clone(), None, None, None, false)?;
+        Ok(match mode {
+            JournalMode::Weekly => notes
+                .into_iter()
+                .filter(|n| is_current_weekly_filename(naming.strip_prefix(&n.filename)))
+                .collect(),
+            _ => notes,
+        })
+    }
+
+    /// Check pre-existing weekly notes against the built-in weekly structure,
+    /// which later migrations rely on
+    fn check_existing_notes(
+        &self,
+        config: &Config,
+        mode: JournalMode,
+        notes: &[NoteEntry],
+    ) -> Result<Diagnostics> {
+        let mut diagnostics = Diagnostics::new();
+        if mode != JournalMode::Weekly {
+            return Ok(diagnostics);
+        }
+        for note in notes {
+            let Some(date) = note.date else { continue };
+            let content = self.repository.read_note(&note.filename)?;
+            if let Some(problem) = check_note_structure(
+                mode,
+                &content,
+                date,
+                config.week_start,
+                config.week_numbering,
+                config.locale,
+            ) {
+                diagnostics.warn(&note.filename, problem);
+            }
+        }
+        Ok(diagnostics)
+    }
+
+    /// Nothing to migrate: explain what is on disk and only set the mode.
+    /// When notes already use the target format this is most likely a
+    /// `djour config mode` meant, so it takes `--force`.
+    fn switch_without_notes(
+        &self,
+        mut config: Config,
+        from: JournalMode,
+        to: JournalMode,
+        inventory: NoteInventory,
+        options: &ModeMigrationOptions,
+    ) -> Result<()> {
+        let summary = match &inventory {
+            NoteInventory::AlreadyInTarget(existing) => {
+                for diagnostic in self.check_existing_notes(&config, to, existing)?.iter() {
+                    eprintln!("{}", diagnostic);
+                }
+                let summary = format!(
+                    "No {} notes found; {} {}-format file{} already exist{}.",
+                    from,
+                    existing.len(),
+                    to,
+                    if existing.len() == 1 { "" } else { "s" },
+                    if existing.len() == 1 { "s" } else { "" },
+                );
+                if !options.force {
+                    let hint = format!(
+                        "{} Did you mean to just set the mode? Use 'djour config mode {}', or pass --force to set it with 'djour mode'.",
+                        summary, to
+                    );
+                    if options.dry_run {
+                        println!("{}", hint);
+                        return Ok(());
+                    }
+                    return Err(DjourError::Config(hint));
+                }
+                summary
+            }
+            _ => format!("No {} or {} notes found; nothing to migrate.", from, to),
+        };
+
+        if options.dry_run {
+            println!("{} Would only set the mode to {}.", summary, to);
+            return Ok(());
+        }
+
+        config.mode = to;
+        self.repository.save_config(&config)?;
+        if !options.quiet {
+            println!("{} Mode set to {}.", summary, to);
+        }
+        Ok(())
+    }
+
     fn refuse_custom_templates(&self) -> Result<()> {
         let root = self.repository.root();
         let custom_daily = root.join(".djour").join("templates").join("daily.md");
@@ -177,10 +357,22 @@ impl MigrateModeContext<'_> {
     // Daily -> Weekly
     // --------------------
 
-    fn plan_daily_to_weekly(&self) -> Result<DailyToWeeklyPlan> {
-        let notes = self
-            .repository
-            .list_notes(JournalMode::Daily, None, None, None, false)?;
+    fn plan_daily_to_weekly(
+        &self,
+        config: &Config,
+        promote_headings: bool,
+    ) -> Result<DailyToWeeklyPlan> {
+        let locale = config.locale;
+        let week = config.week_start;
+        let numbering = config.week_numbering;
+        let weekly_naming = config.naming_for(JournalMode::Weekly);
+        let notes = self.repository.list_notes(
+            config.naming_for(JournalMode::Daily),
+            None,
+            None,
+            None,
+            false,
+        )?;
 
         let mut by_week: BTreeMap<chrono::NaiveDate, Vec<DailyEntry>> = BTreeMap::new();
         let mut daily_files_to_archive: Vec<String> = Vec::new();
@@ -190,15 +382,18 @@ impl MigrateModeContext<'_> {
                 DjourError::Config(format!("Daily note missing date: {}", note.filename))
             })?;
             let content = self.repository.read_note(&note.filename)?;
-            let body = strip_daily_prefix(&content, date)?;
+            // Daily tag footers are dropped; the weekly note gets its own
+            let (without_footer, _) = split_at_tag_footer(&content);
+            let body = strip_daily_prefix(without_footer, date, locale)?;
 
             daily_files_to_archive.push(note.filename.clone());
             by_week
-                .entry(week_start(date))
+                .entry(week_start(date, week))
                 .or_default()
                 .push(DailyEntry {
                     filename: note.filename,
                     date,
+                    content,
                     body,
                 });
         }
@@ -207,32 +402,84 @@ impl MigrateModeContext<'_> {
         for (ws, mut entries) in by_week {
             entries.sort_by_key(|e| e.date);
 
-            let target_weekly = JournalMode::Weekly.filename_for_date(ws);
+            let target_weekly = weekly_naming.filename_for_date(ws);
             let target_existed = self.repository.note_exists(&target_weekly);
 
+            let mut base_fingerprint = None;
+            let mut split_record = None;
+            let mut restored: Vec<NaiveDate> = Vec::new();
             let base_content = if target_existed {
                 let c = self.repository.read_note(&target_weekly)?;
                 // Validate structure (weekday headings must match built-in template for that week).
-                crate::domain::mode_migration::parse_weekly(&c, ws)?;
+                crate::domain::mode_migration::parse_weekly(&c, ws, week, numbering, locale)?;
+                base_fingerprint = Some(content_fingerprint(&c));
                 c
             } else {
-                // Create new weekly file from built-in template. Use Thursday to make {YEAR} match ISO week-year.
-                let template = Template::from_builtin("weekly.md")?;
-                template.render(ws + Duration::days(3))
+                // Create new weekly file from built-in template. Render it for the week's ISO
+                // Thursday (US: its last day) to make {YEAR} match the week's year.
+                let template = Template::from_builtin_for_week("weekly.md", week)?;
+                let render_date = match numbering {
+                    WeekNumbering::Iso => week.iso_anchor(ws) + Duration::days(3),
+                    WeekNumbering::Us => ws + Duration::days(6),
+                };
+                let blank = template.render_with_context(&RenderContext {
+                    numbering,
+                    ..RenderContext::new(render_date, locale, week)
+                });
+                let record = format!("{}/{}", SPLIT_WEEKLY_DIR, target_weekly);
+                let original = if self.repository.note_exists(&record) {
+                    self.repository.read_note(&record)?
+                } else {
+                    String::new()
+                };
+                match rebuild_split_weekly(
+                    config,
+                    &original,
+                    &blank,
+                    ws,
+                    &entries,
+                    promote_headings,
+                ) {
+                    Some((rebuilt, days)) => {
+                        split_record = Some(record);
+                        restored = days;
+                        rebuilt
+                    }
+                    None => blank,
+                }
             };
 
             // Apply injections to compute updated content (still preflight, no writes).
-            let mut updated = base_content;
-            for e in &entries {
-                updated = inject_daily_into_weekly(&updated, ws, e.date, &e.filename, &e.body)?;
+            // Days are injected above the tag footer, which is refreshed afterwards.
+            let (base_body, footer) = split_at_tag_footer(&base_content);
+            let mut updated = base_body.to_string();
+            for e in entries.iter().filter(|e| !restored.contains(&e.date)) {
+                updated = inject_daily_into_weekly(
+                    &updated,
+                    ws,
+                    week,
+                    numbering,
+                    e.date,
+                    &e.filename,
+                    &e.body,
+                    locale,
+                    promote_headings,
+                )?;
             }
+            if !footer.is_empty() {
+                updated = format!("{}\n\n{}", updated.trim_end(), footer);
+            }
+            let updated =
+                preserve_final_newline(&base_content, refresh_tag_footer(config, updated));
 
             weeks.push(WeekPlan {
                 week_start: ws,
                 target_weekly,
                 target_existed,
+                base_fingerprint,
                 updated_content: updated,
                 daily_entries: entries,
+                split_record,
             });
         }
 
@@ -242,7 +489,12 @@ impl MigrateModeContext<'_> {
         })
     }
 
-    fn print_plan_daily_to_weekly(&self, archive_dir: &str, plan: &DailyToWeeklyPlan) {
+    fn print_plan_daily_to_weekly(
+        &self,
+        archive_dir: &str,
+        plan: &DailyToWeeklyPlan,
+        diff: Option<&DiffOptions>,
+    ) -> Result<()> {
         println!("Mode migration plan: daily -> weekly");
         println!("Archive dir: {}", archive_dir);
         println!(
@@ -260,13 +512,43 @@ impl MigrateModeContext<'_> {
                 w.daily_entries.len()
             );
         }
+
+        if let Some(diff) = diff {
+            for w in &plan.weeks {
+                let current = if w.target_existed {
+                    self.repository.read_note(&w.target_weekly)?
+                } else {
+                    String::new()
+                };
+                print_file_diff(&w.target_weekly, &current, &w.updated_content, diff);
+            }
+        }
+        Ok(())
     }
 
     fn apply_daily_to_weekly(&self, archive_dir: &str, plan: DailyToWeeklyPlan) -> Result<()> {
         self.repository.create_dir_all(archive_dir)?;
 
-        // 1) For each weekly target: back up existing targets, then write the updated content.
+        // 1) For each weekly target: make sure it is still what the plan was based on,
+        //    back up existing targets, then write the updated content.
+        let mut written: Vec<&str> = Vec::new();
         for w in &plan.weeks {
+            if !self.target_unchanged(w)? {
+                let done = if written.is_empty() {
+                    "No weekly notes were written.".to_string()
+                } else {
+                    format!(
+                        "Already written: {} (originals backed up in {}).",
+                        written.join(", "),
+                        archive_dir
+                    )
+                };
+                return Err(DjourError::Config(format!(
+                    "Weekly note {} changed after the migration was planned; it was left untouched. {} Daily notes were not archived and the mode was not changed. Re-run the migration to continue.",
+                    w.target_weekly, done
+                )));
+            }
+
             if w.target_existed {
                 let backup = format!("{}/{}", archive_dir, w.target_weekly);
                 self.repository.copy_note(&w.target_weekly, &backup)?;
@@ -274,6 +556,12 @@ impl MigrateModeContext<'_> {
 
             self.repository
                 .write_note_atomic(&w.target_weekly, &w.updated_content)?;
+            written.push(&w.target_weekly);
+
+            if let Some(record) = &w.split_record {
+                let dest = format!("{}/split-weekly/{}", archive_dir, w.target_weekly);
+                self.repository.move_note(record, &dest)?;
+            }
         }
 
         // 2) Move daily files into archive.
@@ -285,21 +573,38 @@ impl MigrateModeContext<'_> {
         Ok(())
     }
 
+    /// Whether a weekly target still matches the content its plan was computed from.
+    fn target_unchanged(&self, week: &WeekPlan) -> Result<bool> {
+        let exists = self.repository.note_exists(&week.target_weekly);
+        match week.base_fingerprint {
+            None => Ok(!exists),
+            Some(expected) => {
+                if !exists {
+                    return Ok(false);
+                }
+                let current = self.repository.read_note(&week.target_weekly)?;
+                Ok(content_fingerprint(&current) == expected)
+            }
+        }
+    }
+
     // --------------------
     // Weekly -> Daily
     // --------------------
 
-    fn plan_weekly_to_daily(&self, archive_dir: &str) -> Result<WeeklyToDailyPlan> {
-        let notes = self
-            .repository
-            .list_notes(JournalMode::Weekly, None, None, None, false)?;
-
+    fn plan_weekly_to_daily(
+        &self,
+        archive_dir: &str,
+        config: &Config,
+        promote_headings: bool,
+    ) -> Result<WeeklyToDailyPlan> {
+        let locale = config.locale;
+        let week = config.week_start;
+        let numbering = config.week_numbering;
+        let daily_naming = config.naming_for(JournalMode::Daily);
         // Ignore legacy weekly filenames (YYYY-Www.md). Only process the current weekly format
         // (YYYY-Www-YYYY-MM-DD.md) for migration.
-        let notes: Vec<NoteEntry> = notes
-            .into_iter()
-            .filter(|n| is_current_weekly_filename(&n.filename))
-            .collect();
+        let notes = self.mode_notes(config, JournalMode::Weekly)?;
 
         // Detect duplicate weekly files for the same week start date.
         let mut by_week: BTreeMap<chrono::NaiveDate, Vec<NoteEntry>> = BTreeMap::new();
@@ -323,18 +628,25 @@ impl MigrateModeContext<'_> {
         let mut weekly_files: Vec<WeeklyFilePlan> = Vec::new();
         for (ws, v) in by_week {
             let note = &v[0];
-            let content = self.repository.read_note(&note.filename)?;
-
-            let day_bodies = split_weekly_into_daily_bodies(&content, ws)?;
+            let original = self.repository.read_note(&note.filename)?;
+            let (content, _) = split_at_tag_footer(&original);
+
+            let day_bodies = split_weekly_into_daily_bodies(
+                content,
+                ws,
+                week,
+                numbering,
+                locale,
+                promote_headings,
+            )?;
 
             let mut daily_creates: Vec<DailyCreate> = Vec::new();
             for (day, body) in day_bodies {
-                let body_no_leading_blank = body.trim_start_matches(['\n', '\r']);
-                if body_no_leading_blank.trim().is_empty() {
+                let Some(daily_content) = daily_note_content(config, day, &body) else {
                     continue;
-                }
+                };
 
-                let daily_filename = JournalMode::Daily.filename_for_date(day);
+                let daily_filename = daily_naming.filename_for_date(day);
                 if self.repository.note_exists(&daily_filename) {
                     return Err(DjourError::Config(format!(
                         "Target daily note already exists: {}",
@@ -342,8 +654,6 @@ impl MigrateModeContext<'_> {
                     )));
                 }
 
-                let mut daily_content = crate::domain::mode_migration::daily_prefix(day);
-                daily_content.push_str(body_no_leading_blank);
                 daily_creates.push(DailyCreate {
                     filename: daily_filename,
                     content: daily_content,
@@ -363,13 +673,19 @@ impl MigrateModeContext<'_> {
                 filename: note.filename.clone(),
                 week_start: ws,
                 daily_creates,
+                original,
             });
         }
 
         Ok(WeeklyToDailyPlan { weekly_files })
     }
 
-    fn print_plan_weekly_to_daily(&self, archive_dir: &str, plan: &WeeklyToDailyPlan) {
+    fn print_plan_weekly_to_daily(
+        &self,
+        archive_dir: &str,
+        plan: &WeeklyToDailyPlan,
+        diff: Option<&DiffOptions>,
+    ) {
         println!("Mode migration plan: weekly -> daily");
         println!("Archive dir: {}", archive_dir);
         println!("Weekly files to archive: {}", plan.weekly_files.len());
@@ -387,6 +703,12 @@ impl MigrateModeContext<'_> {
                 w.daily_creates.len()
             );
         }
+
+        if let Some(diff) = diff {
+            for d in plan.weekly_files.iter().flat_map(|w| &w.daily_creates) {
+                print_file_diff(&d.filename, "", &d.content, diff);
+            }
+        }
     }
 
     fn apply_weekly_to_daily(&self, archive_dir: &str, plan: WeeklyToDailyPlan) -> Result<()> {
@@ -397,6 +719,9 @@ impl MigrateModeContext<'_> {
                 self.repository.write_note_atomic(&d.filename, &d.content)?;
             }
 
+            let record = format!("{}/{}", SPLIT_WEEKLY_DIR, w.filename);
+            self.repository.write_note_atomic(&record, &w.original)?;
+
             let archived = format!("{}/{}", archive_dir, w.filename);
             self.repository.move_note(&w.filename, &archived)?;
         }
@@ -405,6 +730,103 @@ impl MigrateModeContext<'_> {
     }
 }
 
+/// Rebuild a weekly note from `original`, its content when weekly -> daily
+/// split it. Days whose daily note is unchanged since, and days that were
+/// and still are blank, keep their original section byte for byte; the
+/// rest get the blank padding of `template`, ready for injection.
+///
+/// Returns the rebuilt note and the days restored from it, or None when
+/// there is no usable copy (missing, or no longer matching the week).
+fn rebuild_split_weekly(
+    config: &Config,
+    original: &str,
+    template: &str,
+    ws: NaiveDate,
+    entries: &[DailyEntry],
+    promote_headings: bool,
+) -> Option<(String, Vec<NaiveDate>)> {
+    if original.is_empty() {
+        return None;
+    }
+    let (body, footer) = split_at_tag_footer(original);
+    let day_bodies = split_weekly_into_daily_bodies(
+        body,
+        ws,
+        config.week_start,
+        config.week_numbering,
+        config.locale,
+        promote_headings,
+    )
+    .ok()?;
+
+    let mut restored = Vec::new();
+    let mut reset = Vec::new();
+    for (i, (day, day_body)) in day_bodies.iter().enumerate() {
+        let entry = entries.iter().find(|e| e.date == *day);
+        match (daily_note_content(config, *day, day_body), entry) {
+            (None, None) => {}
+            (Some(expected), Some(entry)) if entry.content == expected => {
+                restored.push(*day);
+            }
+            _ => reset.push(i),
+        }
+    }
+
+    let rebuilt = reset_weekly_sections(
+        body,
+        template,
+        ws,
+        config.week_start,
+        config.week_numbering,
+        config.locale,
+        &reset,
+    )
+    .ok()?;
+    Some((format!("{}{}", rebuilt, footer), restored))
+}
+
+/// Daily note weekly -> daily writes for a weekday section's `body`, or None for a blank day
+fn daily_note_content(config: &Config, day: NaiveDate, body: &str) -> Option<String> {
+    let body_no_leading_blank = body.trim_start_matches(['\n', '\r']);
+    if body_no_leading_blank.trim().is_empty() {
+        return None;
+    }
+    let mut content = daily_prefix(day, config.locale);
+    content.push_str(body_no_leading_blank);
+    Some(preserve_final_newline(
+        body,
+        refresh_tag_footer(config, content),
+    ))
+}
+
+/// Print the planned change to `filename`; new files are diffed against /dev/null
+fn print_file_diff(filename: &str, current: &str, updated: &str, options: &DiffOptions) {
+    let old_label = if current.is_empty() {
+        "/dev/null".to_string()
+    } else {
+        format!("a/{}", filename)
+    };
+    let diff = unified_diff(
+        current,
+        updated,
+        &old_label,
+        &format!("b/{}", filename),
+        options,
+    );
+    println!();
+    if diff.is_empty() {
+        println!("{}: unchanged", filename);
+    } else {
+        print!("{}", diff);
+    }
+}
+
+fn content_fingerprint(content: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
 fn is_current_weekly_filename(filename: &str) -> bool {
     let stem = match filename.strip_suffix(".md") {
         Some(s) => s,
@@ -441,3 +863,199 @@ fn is_current_weekly_filename(filename: &str) -> bool {
     let date_str = format!("{}-{}-{}", parts[2], parts[3], parts[4]);
     chrono::NaiveDate::parse_from_str(&date_str, "%Y-%m-%d").is_ok()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::mode_migration::daily_prefix;
+    use crate::domain::Locale;
+    use chrono::NaiveDate;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn setup_daily(days: &[NaiveDate]) -> (TempDir, FileSystemRepository) {
+        let temp = TempDir::new().unwrap();
+        let repo = FileSystemRepository::new(temp.path().to_path_buf());
+        repo.initialize().unwrap();
+        repo.save_config(&Config::new(JournalMode::Daily)).unwrap();
+        for day in days {
+            let content = format!(
+                "{}Entry for {}\n",
+                daily_prefix(*day, Locale::default()),
+                day
+            );
+            fs::write(
+                temp.path().join(day.format("%Y-%m-%d.md").to_string()),
+                content,
+            )
+            .unwrap();
+        }
+        (temp, repo)
+    }
+
+    fn weekly_template(ws: NaiveDate) -> String {
+        Template::from_builtin("weekly.md")
+            .unwrap()
+            .render_with_locale(ws + Duration::days(3), Locale::default())
+    }
+
+    #[test]
+    fn apply_refuses_target_created_after_plan() {
+        let (temp, repo) = setup_daily(&[date(2025, 1, 15), date(2025, 1, 22)]);
+        let context = MigrateModeContext { repository: &repo };
+        let config = repo.load_config().unwrap();
+
+        let plan = context.plan_daily_to_weekly(&config, false).unwrap();
+
+        // Someone creates the second week's note between plan and apply.
+        let concurrent = format!("{}My own notes\n", weekly_template(date(2025, 1, 20)));
+        fs::write(temp.path().join("2025-W04-2025-01-20.md"), &concurrent).unwrap();
+
+        let err = context
+            .apply_daily_to_weekly(".djour/archive/test", plan)
+            .unwrap_err()
+            .to_string();
+
+        assert!(err.contains("2025-W04-2025-01-20.md"), "{err}");
+        assert!(
+            err.contains("Already written: 2025-W03-2025-01-13.md"),
+            "{err}"
+        );
+        assert!(temp.path().join("2025-W03-2025-01-13.md").exists());
+        assert_eq!(
+            fs::read_to_string(temp.path().join("2025-W04-2025-01-20.md")).unwrap(),
+            concurrent
+        );
+        assert!(temp.path().join("2025-01-15.md").exists());
+        assert!(temp.path().join("2025-01-22.md").exists());
+    }
+
+    #[test]
+    fn apply_refuses_existing_target_edited_after_plan() {
+        let (temp, repo) = setup_daily(&[date(2025, 1, 15)]);
+        let target = temp.path().join("2025-W03-2025-01-13.md");
+        fs::write(&target, weekly_template(date(2025, 1, 13))).unwrap();
+        let context = MigrateModeContext { repository: &repo };
+        let config = repo.load_config().unwrap();
+
+        let plan = context.plan_daily_to_weekly(&config, false).unwrap();
+
+        let edited = format!("{}Edited meanwhile\n", weekly_template(date(2025, 1, 13)));
+        fs::write(&target, &edited).unwrap();
+
+        let err = context
+            .apply_daily_to_weekly(".djour/archive/test", plan)
+            .unwrap_err()
+            .to_string();
+
+        assert!(err.contains("No weekly notes were written."), "{err}");
+        assert_eq!(fs::read_to_string(&target).unwrap(), edited);
+        assert!(temp.path().join("2025-01-15.md").exists());
+    }
+
+    #[test]
+    fn apply_writes_unchanged_targets() {
+        let (temp, repo) = setup_daily(&[date(2025, 1, 15)]);
+        let target = temp.path().join("2025-W03-2025-01-13.md");
+        fs::write(&target, weekly_template(date(2025, 1, 13))).unwrap();
+        let context = MigrateModeContext { repository: &repo };
+        let config = repo.load_config().unwrap();
+
+        let plan = context.plan_daily_to_weekly(&config, false).unwrap();
+        context
+            .apply_daily_to_weekly(".djour/archive/test", plan)
+            .unwrap();
+
+        assert!(fs::read_to_string(&target)
+            .unwrap()
+            .contains("Entry for 2025-01-15"));
+        assert!(!temp.path().join("2025-01-15.md").exists());
+    }
+
+    fn options(to_mode: JournalMode, force: bool) -> ModeMigrationOptions {
+        ModeMigrationOptions {
+            to_mode,
+            from_mode: None,
+            dry_run: false,
+            archive_dir: None,
+            quiet: true,
+            promote_headings: false,
+            show_diff: None,
+            force,
+        }
+    }
+
+    #[test]
+    fn inventory_finds_source_notes_to_migrate() {
+        let (_temp, repo) = setup_daily(&[date(2025, 1, 15)]);
+        let context = MigrateModeContext { repository: &repo };
+        let config = repo.load_config().unwrap();
+
+        let inventory = context
+            .inventory(&config, JournalMode::Daily, JournalMode::Weekly)
+            .unwrap();
+        assert!(matches!(inventory, NoteInventory::Migrate), "{inventory:?}");
+    }
+
+    #[test]
+    fn inventory_reports_notes_already_in_target_mode() {
+        let (temp, repo) = setup_daily(&[]);
+        fs::write(
+            temp.path().join("2025-W03-2025-01-13.md"),
+            weekly_template(date(2025, 1, 13)),
+        )
+        .unwrap();
+        fs::write(temp.path().join("2025-W04-2025-01-20.md"), "# Not a week\n").unwrap();
+        let context = MigrateModeContext { repository: &repo };
+        let config = repo.load_config().unwrap();
+
+        let inventory = context
+            .inventory(&config, JournalMode::Daily, JournalMode::Weekly)
+            .unwrap();
+        let NoteInventory::AlreadyInTarget(existing) = inventory else {
+            panic!("expected weekly notes, got {inventory:?}");
+        };
+        assert_eq!(existing.len(), 2);
+
+        let problems = context
+            .check_existing_notes(&config, JournalMode::Weekly, &existing)
+            .unwrap();
+        let files: Vec<&str> = problems.iter().map(|d| d.file.as_str()).collect();
+        assert_eq!(files, ["2025-W04-2025-01-20.md"]);
+
+        let err = context
+            .execute(options(JournalMode::Weekly, false))
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("No daily notes found; 2 weekly-format files already exist."),
+            "{err}"
+        );
+        assert!(err.contains("djour config mode weekly"), "{err}");
+        assert_eq!(repo.load_config().unwrap().mode, JournalMode::Daily);
+
+        context.execute(options(JournalMode::Weekly, true)).unwrap();
+        assert_eq!(repo.load_config().unwrap().mode, JournalMode::Weekly);
+    }
+
+    #[test]
+    fn inventory_of_empty_journal_switches_mode() {
+        let (_temp, repo) = setup_daily(&[]);
+        let context = MigrateModeContext { repository: &repo };
+        let config = repo.load_config().unwrap();
+
+        let inventory = context
+            .inventory(&config, JournalMode::Daily, JournalMode::Weekly)
+            .unwrap();
+        assert!(matches!(inventory, NoteInventory::Empty), "{inventory:?}");
+
+        context
+            .execute(options(JournalMode::Weekly, false))
+            .unwrap();
+        assert_eq!(repo.load_config().unwrap().mode, JournalMode::Weekly);
+    }
+}
@@ -1,19 +1,82 @@
 //! Application layer - Use cases and orchestration
 
+pub mod archive;
+pub mod capture;
+pub mod clean;
+pub mod compilations;
+pub mod compile_replay;
+pub mod compile_rules;
 pub mod compile_tags;
+pub mod edit_config;
 pub mod init;
+pub mod journals;
 pub mod list_notes;
 pub mod list_tags;
+pub mod list_todos;
 pub mod manage_config;
 pub mod migrate_mode;
+pub mod mode_hint;
+pub mod note_path;
+mod note_structure;
 pub mod open_note;
+pub mod pins;
+pub mod recurring;
+pub mod reminders;
+pub mod rename;
 pub mod retag;
+pub mod scan;
+pub mod show_env;
+pub mod stats;
+mod tag_footer;
+pub mod templates;
+pub mod watch_compile;
 
-pub use compile_tags::{compile_tags, CompileOptions};
-pub use init::init;
-pub use list_notes::list_notes;
-pub use list_tags::list_tags;
-pub use manage_config::{get_config, list_config, set_config};
+pub use archive::{archive_notes, ArchiveMove, ArchiveOptions, ArchiveReport};
+pub use capture::{capture, CaptureOutcome};
+pub use clean::{clean_temp_files, CleanReport};
+pub use compilations::{
+    find_compilations, refresh_options, remove_compilations, Compilation, CompilationFiles,
+};
+pub use compile_replay::{
+    check_slot_name, command_line, load_compile, save_compile, saved_compile_path, SavedCompile,
+    LAST_COMPILE_FILE,
+};
+pub use compile_rules::{compile_rules, rule_options, RuleReport, RuleSelection};
+pub use compile_tags::{
+    compile_document, compile_tags, compile_to_string, matching_content, CompileOptions,
+    CompileReport, NoteSource,
+};
+pub use edit_config::{edit_config, ConfigChange};
+pub use init::{
+    choose_init_mode, enclosing_journal, init, init_with_config, nested_journals, seed_examples,
+    undo_init, InitModeChoice, ONBOARDING_NOTE,
+};
+pub use journals::{add_journal, list_journals, remove_journal, JournalListing};
+pub use list_notes::{list_notes, read_titles, sort_by_titles};
+pub use list_tags::{
+    count_tags, exclude_patterns, list_tag_counts, list_tag_groups, list_tags, TagCounts,
+    TagOptions,
+};
+pub use list_todos::{list_todos, TaskStatus, TodoOptions};
+pub use manage_config::{
+    get_config, list_config, repair_config, set_config, ConfigRepairReport, CONFIG_KEYS,
+};
 pub use migrate_mode::{migrate_mode, ModeMigrationOptions};
-pub use open_note::open_note;
-pub use retag::{retag_notes, RetagFileChange, RetagOptions, RetagReport};
+pub use mode_hint::{mode_mismatch_hint, other_numbering_notes, scan_mode_counts};
+pub use note_path::{locate_note, NoteLocation, RefKind};
+pub use open_note::{
+    ensure_note_created, open_note, open_note_with_confirm, open_note_with_options,
+    OpenNoteOptions, OpenedNote,
+};
+pub use pins::{pin_note, pinned_notes, unpin_note, PinOutcome, PinnedNote};
+pub use recurring::{list_recurring, RECURRING_FILE};
+pub use reminders::{due_reminders, notify_reminders, NOTIFY_LIMIT};
+pub use rename::{rename_note, RenameFileChange, RenameReport};
+pub use retag::{retag_notes, RetagFileChange, RetagOptions, RetagQueryChange, RetagReport};
+pub use scan::{scan_notes, scan_tagged_content, Scan, ScanProgress, ScanScope, ScannedNote};
+pub use show_env::{effective_settings, EffectiveSettings};
+pub use stats::{
+    current_streak, journal_stats, write_stats_block, JournalStats, STATS_END, STATS_START,
+};
+pub use templates::{check_templates, template_warnings, TemplateCheck, TEMPLATES_DIR};
+pub use watch_compile::watch_compilation;
@@ -0,0 +1,144 @@
+//! Spotting journals whose notes are named for a different mode or week numbering
+
+use crate::domain::{JournalMode, ModeCounts, NoteNaming};
+use crate::error::Result;
+use crate::infrastructure::{Config, FileSystemRepository, JournalRepository};
+use std::fs;
+use std::path::Path;
+
+/// Count the files directly in `dir` by the mode they look like (empty if `dir` does not exist)
+pub fn scan_mode_counts(dir: &Path, prefix: &str) -> Result<ModeCounts> {
+    let filenames = file_names(dir)?;
+    Ok(ModeCounts::tally(
+        filenames.iter().map(String::as_str),
+        prefix,
+    ))
+}
+
+/// Names of the files directly in `dir`, sorted (empty if `dir` does not exist)
+fn file_names(dir: &Path) -> Result<Vec<String>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut filenames = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        if entry.file_type()?.is_file() {
+            filenames.push(entry.file_name().to_string_lossy().into_owned());
+        }
+    }
+    filenames.sort();
+    Ok(filenames)
+}
+
+/// Weekly notes in `dir` named by the other week numbering than `naming`'s:
+/// they parse under the other system but not under the configured one.
+///
+/// A journal must stick to one system, so these are notes to rename (or a
+/// sign `week_numbering` was changed after they were created).
+pub fn other_numbering_notes(dir: &Path, naming: &NoteNaming) -> Result<Vec<String>> {
+    if naming.mode != JournalMode::Weekly {
+        return Ok(Vec::new());
+    }
+    let other = naming
+        .clone()
+        .with_week_numbering(naming.week_numbering.other());
+    Ok(file_names(dir)?
+        .into_iter()
+        .filter(|name| {
+            naming.date_from_filename(name).is_none() && other.date_from_filename(name).is_some()
+        })
+        .collect())
+}
+
+/// Hint for when no note in the journal root matches the active mode but
+/// files named for another mode are there, e.g. weekly mode over daily notes.
+///
+/// Returns `None` whenever the active mode finds at least one note.
+pub fn mode_mismatch_hint(
+    repository: &FileSystemRepository,
+    config: &Config,
+) -> Result<Option<String>> {
+    let active = config.get_mode();
+    if !repository
+        .list_notes(config.naming(), None, None, Some(1), false)?
+        .is_empty()
+    {
+        return Ok(None);
+    }
+
+    let counts = scan_mode_counts(repository.root(), &config.filename_prefix)?;
+    Ok(counts.best_other_than(active).map(|(mode, count)| {
+        format!(
+            "Found {} {}-looking note(s) but mode is {}; see 'djour mode' or 'djour config mode'",
+            count,
+            format!("{:?}", mode).to_lowercase(),
+            format!("{:?}", active).to_lowercase()
+        )
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::init;
+    use crate::domain::{WeekNumbering, WeekStart};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_hint_for_weekly_mode_over_daily_notes() {
+        let temp = TempDir::new().unwrap();
+        init(temp.path(), JournalMode::Weekly).unwrap();
+        fs::write(temp.path().join("2025-01-15.md"), "# Day\n").unwrap();
+        fs::write(temp.path().join("2025-01-16.md"), "# Day\n").unwrap();
+
+        let repo = FileSystemRepository::new(temp.path().to_path_buf());
+        let config = repo.load_config().unwrap();
+        let hint = mode_mismatch_hint(&repo, &config).unwrap().unwrap();
+        assert!(hint.starts_with("Found 2 daily-looking note(s) but mode is weekly"));
+
+        fs::write(temp.path().join("2025-W03-2025-01-13.md"), "# Week\n").unwrap();
+        assert_eq!(mode_mismatch_hint(&repo, &config).unwrap(), None);
+    }
+
+    #[test]
+    fn test_notes_named_by_the_other_week_numbering() {
+        let temp = TempDir::new().unwrap();
+        init(temp.path(), JournalMode::Weekly).unwrap();
+        // 2021-12-26..2022-01-01 is ISO 2021-W52 and US 2022-W01
+        for name in [
+            "2021-W52-2021-12-26.md",
+            "2022-W01-2021-12-26.md",
+            "2025-W03-2025-01-12.md",
+        ] {
+            fs::write(temp.path().join(name), "# Week\n").unwrap();
+        }
+
+        let iso = NoteNaming::from(JournalMode::Weekly).with_week_start(WeekStart::Sunday);
+        assert_eq!(
+            other_numbering_notes(temp.path(), &iso).unwrap(),
+            vec!["2022-W01-2021-12-26.md"]
+        );
+        let us = iso.clone().with_week_numbering(WeekNumbering::Us);
+        assert_eq!(
+            other_numbering_notes(temp.path(), &us).unwrap(),
+            vec!["2021-W52-2021-12-26.md"]
+        );
+        let daily = NoteNaming::from(JournalMode::Daily);
+        assert!(other_numbering_notes(temp.path(), &daily)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_no_hint_for_empty_journal() {
+        let temp = TempDir::new().unwrap();
+        init(temp.path(), JournalMode::Daily).unwrap();
+        let repo = FileSystemRepository::new(temp.path().to_path_buf());
+        let config = repo.load_config().unwrap();
+        assert_eq!(mode_mismatch_hint(&repo, &config).unwrap(), None);
+    }
+}
@@ -0,0 +1,427 @@
+//! Note path resolution use case
+
+use crate::domain::{JournalMode, NoteNaming, TimeReference};
+use crate::error::{DjourError, Result};
+use crate::infrastructure::{Config, FileSystemRepository, JournalRepository};
+use chrono::{Local, NaiveDate};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Where a time reference points to in the journal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoteLocation {
+    /// Resolved date of the time reference
+    pub date: NaiveDate,
+
+    /// Note filename relative to the journal root
+    pub filename: String,
+
+    /// Absolute path of the note
+    pub path: PathBuf,
+
+    /// Whether the note file exists
+    pub exists: bool,
+}
+
+/// How `--as` reads a `TIME_REF` that could be a date or a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefKind {
+    /// A time reference or a note name of the journal's mode
+    Date,
+    /// An existing file in the journal root
+    File,
+}
+
+impl FromStr for RefKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "date" => Ok(RefKind::Date),
+            "file" => Ok(RefKind::File),
+            _ => Err(format!("Invalid --as value: '{}'. Use 'date' or 'file'", s)),
+        }
+    }
+}
+
+/// One way to read a `TIME_REF` argument
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Interpretation {
+    /// The note for a date: a time reference (`today`, `17-01-2025`) or a
+    /// note name of the journal's mode (`2025-01` in monthly mode)
+    Date { date: NaiveDate, filename: String },
+    /// An existing file in the journal root, named with or without `.md`
+    File { filename: String },
+    /// A note name of another mode; never opened, only used to explain errors
+    OtherMode { mode: JournalMode, date: NaiveDate },
+}
+
+/// Every reading of `input`, most preferred first: the date, then an
+/// existing file, then other modes' note names.
+///
+/// `file_exists` tells whether a name is a file in the journal root.
+pub(crate) fn interpretations<F>(
+    config: &Config,
+    input: &str,
+    today: NaiveDate,
+    file_exists: F,
+) -> Vec<Interpretation>
+where
+    F: Fn(&str) -> bool,
+{
+    let naming = config.naming();
+    let trimmed = input.trim();
+    let note_name = if trimmed.ends_with(".md") {
+        trimmed.to_string()
+    } else {
+        format!("{}.md", trimmed)
+    };
+    let mut found = Vec::new();
+
+    let date = match TimeReference::parse_with_locale(input, config.locale) {
+        Ok(time_ref) => Some(time_ref.resolve(today)),
+        Err(_) => naming.date_from_filename(&note_name),
+    };
+    if let Some(date) = date {
+        // A note name keeps its own spelling when that note exists (an
+        // unprefixed or titled daily note), and is made canonical otherwise
+        let filename = if naming.date_from_filename(&note_name).is_some() && file_exists(&note_name)
+        {
+            note_name.clone()
+        } else {
+            naming.filename_for_date(date)
+        };
+        found.push(Interpretation::Date { date, filename });
+    }
+
+    // Plain names only: the file must sit in the root itself
+    let plain =
+        !trimmed.is_empty() && !trimmed.contains(['/', '\\']) && trimmed != "." && trimmed != "..";
+    if plain {
+        if let Some(filename) = [trimmed, note_name.as_str()]
+            .into_iter()
+            .find(|name| file_exists(name))
+        {
+            found.push(Interpretation::File {
+                filename: filename.to_string(),
+            });
+        }
+    }
+
+    if date.is_none() {
+        for mode in [
+            JournalMode::Daily,
+            JournalMode::Weekly,
+            JournalMode::Monthly,
+        ] {
+            if mode == naming.mode {
+                continue;
+            }
+            let other = NoteNaming {
+                mode,
+                ..naming.clone()
+            };
+            if let Some(date) = other.date_from_filename(&note_name) {
+                found.push(Interpretation::OtherMode { mode, date });
+            }
+        }
+    }
+
+    found
+}
+
+/// The interpretation of `input` to act on.
+///
+/// With `kind`, only readings of that kind count. A date and a file naming
+/// the same note are one reading. More than one reading is an error listing
+/// them; so is none, with the closest valid reference when there is one.
+pub(crate) fn pick_interpretation(
+    input: &str,
+    active: JournalMode,
+    candidates: Vec<Interpretation>,
+    kind: Option<RefKind>,
+) -> Result<Interpretation> {
+    let mut usable: Vec<&Interpretation> = candidates
+        .iter()
+        .filter(|candidate| match candidate {
+            Interpretation::Date { .. } => kind != Some(RefKind::File),
+            Interpretation::File { .. } => kind != Some(RefKind::Date),
+            Interpretation::OtherMode { .. } => false,
+        })
+        .collect();
+    if let [Interpretation::Date { filename: note, .. }, Interpretation::File { filename }] =
+        usable.as_slice()
+    {
+        if note == filename {
+            usable.truncate(1);
+        }
+    }
+
+    match usable.as_slice() {
+        [only] => Ok((*only).clone()),
+        [] => Err(no_interpretation(input, active, &candidates, kind)),
+        several => {
+            let mut message = format!("Ambiguous TIME_REF '{}'. It could mean:", input);
+            for candidate in several {
+                match candidate {
+                    Interpretation::Date { date, filename } => message.push_str(&format!(
+                        "\n  --as date: the note for {} ({})",
+                        date.format("%d-%m-%Y"),
+                        filename
+                    )),
+                    Interpretation::File { filename } => {
+                        message.push_str(&format!("\n  --as file: the existing file {}", filename))
+                    }
+                    Interpretation::OtherMode { .. } => {}
+                }
+            }
+            message.push_str("\nPass --as date or --as file to choose.");
+            Err(DjourError::Config(message))
+        }
+    }
+}
+
+/// Error for an input without a usable reading in `active` mode
+fn no_interpretation(
+    input: &str,
+    active: JournalMode,
+    candidates: &[Interpretation],
+    kind: Option<RefKind>,
+) -> DjourError {
+    if kind == Some(RefKind::File) {
+        return DjourError::Config(format!("No file named '{}' in the journal root", input));
+    }
+    let other_mode = candidates.iter().find_map(|candidate| match candidate {
+        Interpretation::OtherMode { mode, date } => Some((*mode, *date)),
+        _ => None,
+    });
+    match other_mode {
+        Some((mode, date)) => DjourError::Config(format!(
+            "'{}' is a {} note name, but this journal uses {} notes. \
+            Nearest valid reference: {}",
+            input,
+            format!("{:?}", mode).to_lowercase(),
+            format!("{:?}", active).to_lowercase(),
+            date.format("%d-%m-%Y")
+        )),
+        None => TimeReference::calendar_error(input)
+            .unwrap_or_else(|| DjourError::InvalidTimeReference(input.to_string())),
+    }
+}
+
+/// Resolve a time reference to its date and note filename for the configured mode.
+pub(crate) fn resolve_note_filename(
+    config: &Config,
+    time_ref_str: &str,
+    today: NaiveDate,
+) -> Result<(NaiveDate, String)> {
+    let candidates = interpretations(config, time_ref_str, today, |_| false);
+    match pick_interpretation(
+        time_ref_str,
+        config.get_mode(),
+        candidates,
+        Some(RefKind::Date),
+    )? {
+        Interpretation::Date { date, filename } => Ok((date, filename)),
+        _ => unreachable!("only dates are picked with RefKind::Date"),
+    }
+}
+
+/// Locate the note for a time reference without creating or opening anything.
+pub fn locate_note(repository: &FileSystemRepository, time_ref_str: &str) -> Result<NoteLocation> {
+    let config = repository.load_config()?;
+    let (date, filename) = resolve_note_filename(&config, time_ref_str, Local::now().date_naive())?;
+
+    let path = std::path::absolute(repository.root().join(&filename))?;
+    let exists = path.is_file();
+
+    Ok(NoteLocation {
+        date,
+        filename,
+        path,
+        exists,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::JournalMode;
+    use tempfile::TempDir;
+
+    fn setup(mode: JournalMode) -> (TempDir, FileSystemRepository) {
+        let temp = TempDir::new().unwrap();
+        let repo = FileSystemRepository::new(temp.path().to_path_buf());
+        repo.initialize().unwrap();
+        repo.save_config(&Config::new(mode)).unwrap();
+        (temp, repo)
+    }
+
+    #[test]
+    fn locates_missing_note_without_creating_it() {
+        let (temp, repo) = setup(JournalMode::Daily);
+
+        let location = locate_note(&repo, "17-01-2025").unwrap();
+        assert_eq!(location.filename, "2025-01-17.md");
+        assert_eq!(location.path, temp.path().join("2025-01-17.md"));
+        assert!(location.path.is_absolute());
+        assert!(!location.exists);
+        assert!(!temp.path().join("2025-01-17.md").exists());
+    }
+
+    #[test]
+    fn locates_existing_note_for_mode() {
+        let (temp, repo) = setup(JournalMode::Weekly);
+        std::fs::write(temp.path().join("2025-W03-2025-01-13.md"), "# Week 3").unwrap();
+
+        let location = locate_note(&repo, "17-01-2025").unwrap();
+        assert_eq!(location.filename, "2025-W03-2025-01-13.md");
+        assert!(location.exists);
+    }
+
+    #[test]
+    fn rejects_invalid_time_reference() {
+        let (_temp, repo) = setup(JournalMode::Daily);
+        assert!(locate_note(&repo, "someday").is_err());
+    }
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 1, 17).unwrap()
+    }
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    /// Pick `input` in `mode` with `files` present in the journal root
+    fn pick(
+        mode: JournalMode,
+        input: &str,
+        files: &[&str],
+        kind: Option<RefKind>,
+    ) -> Result<Interpretation> {
+        let config = Config::new(mode);
+        let candidates = interpretations(&config, input, today(), |name| files.contains(&name));
+        pick_interpretation(input, mode, candidates, kind)
+    }
+
+    fn dated(date: NaiveDate, filename: &str) -> Interpretation {
+        Interpretation::Date {
+            date,
+            filename: filename.to_string(),
+        }
+    }
+
+    fn file(filename: &str) -> Interpretation {
+        Interpretation::File {
+            filename: filename.to_string(),
+        }
+    }
+
+    #[test]
+    fn daily_mode_reads_note_names_files_and_dates() {
+        let daily = JournalMode::Daily;
+        assert_eq!(
+            pick(daily, "2025-01-15", &[], None).unwrap(),
+            dated(date(2025, 1, 15), "2025-01-15.md")
+        );
+        // The date and the file are the same note
+        assert_eq!(
+            pick(daily, "15-01-2025", &["2025-01-15.md"], None).unwrap(),
+            dated(date(2025, 1, 15), "2025-01-15.md")
+        );
+        assert_eq!(
+            pick(daily, "2025-01", &["2025-01.md"], None).unwrap(),
+            file("2025-01.md")
+        );
+
+        let err = pick(daily, "2025-01", &[], None).unwrap_err().to_string();
+        assert!(err.contains("is a monthly note name"), "{}", err);
+        assert!(err.contains("uses daily notes"), "{}", err);
+        assert!(err.contains("01-01-2025"), "{}", err);
+    }
+
+    #[test]
+    fn date_and_different_file_are_ambiguous_until_chosen() {
+        let daily = JournalMode::Daily;
+        let err = pick(daily, "friday", &["friday.md"], None)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Ambiguous TIME_REF 'friday'"), "{}", err);
+        assert!(
+            err.contains("--as date: the note for 17-01-2025 (2025-01-17.md)"),
+            "{}",
+            err
+        );
+        assert!(
+            err.contains("--as file: the existing file friday.md"),
+            "{}",
+            err
+        );
+
+        assert_eq!(
+            pick(daily, "friday", &["friday.md"], Some(RefKind::Date)).unwrap(),
+            dated(today(), "2025-01-17.md")
+        );
+        assert_eq!(
+            pick(daily, "friday", &["friday.md"], Some(RefKind::File)).unwrap(),
+            file("friday.md")
+        );
+        assert!(pick(daily, "friday", &[], Some(RefKind::File)).is_err());
+    }
+
+    #[test]
+    fn weekly_mode_reads_week_names() {
+        let weekly = JournalMode::Weekly;
+        assert_eq!(
+            pick(weekly, "2025-W03", &[], None).unwrap(),
+            dated(date(2025, 1, 13), "2025-W03-2025-01-13.md")
+        );
+        assert_eq!(
+            pick(weekly, "15-01-2025", &[], None).unwrap(),
+            dated(date(2025, 1, 15), "2025-W03-2025-01-13.md")
+        );
+        let err = pick(weekly, "2025-01-15", &[], None)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("is a daily note name"), "{}", err);
+        assert!(err.contains("15-01-2025"), "{}", err);
+    }
+
+    #[test]
+    fn monthly_mode_reads_month_names() {
+        let monthly = JournalMode::Monthly;
+        assert_eq!(
+            pick(monthly, "2025-01", &[], None).unwrap(),
+            dated(date(2025, 1, 1), "2025-01.md")
+        );
+        assert_eq!(
+            pick(monthly, "2025-01.md", &["2025-01.md"], None).unwrap(),
+            dated(date(2025, 1, 1), "2025-01.md")
+        );
+    }
+
+    #[test]
+    fn single_mode_reads_files_and_dates() {
+        let single = JournalMode::Single;
+        assert_eq!(
+            pick(single, "today", &["journal.md"], None).unwrap(),
+            dated(today(), "journal.md")
+        );
+        assert_eq!(
+            pick(single, "ideas", &["journal.md", "ideas.md"], None).unwrap(),
+            file("ideas.md")
+        );
+        assert!(matches!(
+            pick(single, "someday", &["journal.md"], None),
+            Err(DjourError::InvalidTimeReference(_))
+        ));
+    }
+
+    #[test]
+    fn files_outside_the_root_are_not_candidates() {
+        let config = Config::new(JournalMode::Daily);
+        let candidates = interpretations(&config, "notes/ideas", today(), |_| true);
+        assert!(candidates.is_empty(), "{:?}", candidates);
+    }
+}
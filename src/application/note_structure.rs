@@ -0,0 +1,48 @@
+//! Structural checks of dated notes, shared by commands that read them
+
+use crate::domain::{check_note_structure, Diagnostics, JournalMode};
+use crate::infrastructure::{Config, FileSystemRepository, JournalRepository, NoteEntry};
+
+/// Checks notes against the built-in template of the journal mode.
+///
+/// Journals using a custom template for the mode are not checked, since their
+/// layout is the user's own.
+pub(crate) struct StructureCheck<'a> {
+    config: &'a Config,
+}
+
+impl<'a> StructureCheck<'a> {
+    /// A check for the configured mode, or None when there is nothing to check.
+    pub(crate) fn new(repository: &FileSystemRepository, config: &'a Config) -> Option<Self> {
+        let mode = config.get_mode();
+        if !matches!(mode, JournalMode::Weekly | JournalMode::Monthly) {
+            return None;
+        }
+        let custom = repository
+            .root()
+            .join(".djour")
+            .join("templates")
+            .join(mode.template_name());
+        if custom.exists() {
+            return None;
+        }
+        Some(StructureCheck { config })
+    }
+
+    /// Record the first structural problem of `note` (if any) as a warning.
+    pub(crate) fn check(&self, note: &NoteEntry, content: &str, diagnostics: &mut Diagnostics) {
+        let Some(date) = note.date else {
+            return;
+        };
+        if let Some(problem) = check_note_structure(
+            self.config.get_mode(),
+            content,
+            date,
+            self.config.week_start,
+            self.config.week_numbering,
+            self.config.locale,
+        ) {
+            diagnostics.warn(&note.filename, problem);
+        }
+    }
+}
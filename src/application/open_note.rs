@@ -1,69 +1,232 @@
 //! Open note use case
 
-use crate::domain::{load_template, JournalMode, TimeReference};
-use crate::error::Result;
-use crate::infrastructure::{EditorSession, FileSystemRepository, JournalRepository};
-use chrono::Local;
+use crate::application::note_path::{
+    interpretations, pick_interpretation, Interpretation, RefKind,
+};
+use crate::application::recurring::with_recurring_entries;
+use crate::application::tag_footer::refresh_tag_footer;
+use crate::application::templates::{unreported_template_issues, warn_template_issues};
+use crate::domain::{load_template_for_week, Diagnostics};
+use crate::error::{DjourError, Result};
+use crate::infrastructure::{
+    Config, EditorRole, EditorSession, FileSystemRepository, JournalRepository,
+};
+use chrono::{Local, NaiveDate};
+
+/// What `djour <TIME_REF>` should do with the resolved note
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct OpenNoteOptions {
+    /// Open the note in the configured editor (creating it if needed)
+    pub open_in_editor: bool,
+    /// Create the note if it does not exist
+    pub create: bool,
+    /// `--as`: read the argument only as a date or only as a file name
+    pub interpret_as: Option<RefKind>,
+}
+
+/// Outcome of resolving (and possibly creating) a note
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenedNote {
+    /// Note filename relative to the journal root
+    pub filename: String,
+    /// Whether the note was created by this call
+    pub created: bool,
+    /// Created only because `create_on_resolve` is on (neither `--open` nor `--create` was given)
+    pub implicitly_created: bool,
+    /// Date the argument resolved to (None for a file named directly)
+    pub date: Option<NaiveDate>,
+    /// Days the note covers in weekly and monthly mode, e.g. "February 2025"
+    pub period: Option<String>,
+}
 
 /// Resolve time reference to note filename, creating the note if needed.
 /// Opens the file in editor only when `open_in_editor` is true.
+///
+/// Creating a note beyond the configured `date_guard_days` is refused; use
+/// `open_note_with_confirm` to ask the user instead. Template warnings are
+/// dropped; use `open_note_with_options` to get them.
 pub fn open_note(
     repository: &FileSystemRepository,
     time_ref_str: &str,
     open_in_editor: bool,
 ) -> Result<String> {
-    // 1. Load config to get mode and editor
-    let config = repository.load_config()?;
-
-    // 2. Parse time reference
-    let time_ref = TimeReference::parse(time_ref_str)?;
-
-    // 3. Resolve to date
-    let date = time_ref.resolve(Local::now().date_naive());
+    open_note_with_confirm(repository, time_ref_str, open_in_editor, |_, _| false)
+}
 
-    // 4. Generate filename based on mode
-    let mode = config.get_mode();
-    let filename = mode.filename_for_date(date);
+/// Like `open_note`, but asks `confirm_far_date` before creating a note whose date
+/// is more than `date_guard_days` away from today.
+///
+/// The callback receives the note date and its signed distance from today in days
+/// (negative for past dates). Existing notes never trigger the guard.
+pub fn open_note_with_confirm<F>(
+    repository: &FileSystemRepository,
+    time_ref_str: &str,
+    open_in_editor: bool,
+    confirm_far_date: F,
+) -> Result<String>
+where
+    F: FnOnce(NaiveDate, i64) -> bool,
+{
+    let options = OpenNoteOptions {
+        open_in_editor,
+        create: true,
+        interpret_as: None,
+    };
+    let mut diagnostics = Diagnostics::new();
+    open_note_with_options(
+        repository,
+        time_ref_str,
+        options,
+        &mut diagnostics,
+        confirm_far_date,
+    )
+    .map(|opened| opened.filename)
+}
 
-    // 5. Check if file exists
-    if !repository.note_exists(&filename) {
-        // 6. Create file with template
-        let template_name = mode.template_name();
-        let template = load_template(repository.root(), template_name)?;
-        let content = template.render(date);
+/// Resolve a time reference and create/open its note as `options` ask.
+///
+/// The argument may also name an existing file in the journal root; when it
+/// reads as both a date and a different file, `options.interpret_as` must
+/// choose. Without `--open` or `--create`, a missing note is only created
+/// while the deprecated `create_on_resolve` config key is on (its current default).
+/// Issues of the template a new note is created from go to `diagnostics`.
+pub fn open_note_with_options<F>(
+    repository: &FileSystemRepository,
+    time_ref_str: &str,
+    options: OpenNoteOptions,
+    diagnostics: &mut Diagnostics,
+    confirm_far_date: F,
+) -> Result<OpenedNote>
+where
+    F: FnOnce(NaiveDate, i64) -> bool,
+{
+    // 1. Load config to get mode and editor
+    let config = repository.load_config()?;
 
-        // Special handling for Single mode
-        if matches!(mode, JournalMode::Single) {
-            // Append to existing file
-            let existing = repository.read_note(&filename)?;
-            let new_content = if existing.is_empty() {
-                content
-            } else {
-                format!("{}\n{}", existing, content)
-            };
-            repository.write_note(&filename, &new_content)?;
-        } else {
-            // Create new file
-            repository.write_note(&filename, &content)?;
+    // 2. Resolve the argument to a dated note of the configured mode or an existing file
+    let today = Local::now().date_naive();
+    let candidates = interpretations(&config, time_ref_str, today, |name| {
+        repository.root().join(name).is_file()
+    });
+    let picked = pick_interpretation(
+        time_ref_str,
+        config.get_mode(),
+        candidates,
+        options.interpret_as,
+    )?;
+    let (date, filename) = match picked {
+        Interpretation::Date { date, filename } => (Some(date), filename),
+        Interpretation::File { filename } => (None, filename),
+        Interpretation::OtherMode { .. } => {
+            unreachable!("other modes' note names are never picked")
         }
+    };
+
+    // 3. Create the note when asked to (or implicitly, while create_on_resolve is on)
+    let explicit = options.open_in_editor || options.create;
+    if explicit {
+        repository.ensure_writable("open", &repository.root().join(&filename))?;
     }
+    // Implicit creation is skipped under --read-only; the filename is still printed
+    // Files named directly already exist
+    let created = match date {
+        Some(date) if explicit || (config.create_on_resolve && !repository.is_read_only()) => {
+            ensure_note_created(
+                repository,
+                &config,
+                date,
+                &filename,
+                today,
+                diagnostics,
+                confirm_far_date,
+            )?
+        }
+        _ => false,
+    };
 
-    // 7. Open in editor when requested
-    if open_in_editor {
-        let editor_cmd = config.get_editor();
-        let editor = EditorSession::new(editor_cmd);
+    // 4. Open in editor when requested
+    if options.open_in_editor {
+        let editor = EditorSession::for_role(&config, EditorRole::Editor);
 
         let file_path = repository.root().join(&filename);
         editor.open(&file_path)?;
     }
 
-    Ok(filename)
+    let mode = config.get_mode();
+    let period = date
+        .filter(|_| mode.is_period_based())
+        .map(|date| mode.period_description(date, config.week_start, config.week_numbering));
+    Ok(OpenedNote {
+        filename,
+        created,
+        implicitly_created: created && !explicit,
+        date,
+        period,
+    })
+}
+
+/// Create the note for `date` from its template unless it already exists,
+/// adding the entries of matching `.djour/recurring.md` directives.
+///
+/// Returns whether this call created the note; a note that appears while the
+/// template is being rendered is left as it is.
+pub fn ensure_note_created<F>(
+    repository: &FileSystemRepository,
+    config: &Config,
+    date: NaiveDate,
+    filename: &str,
+    today: NaiveDate,
+    diagnostics: &mut Diagnostics,
+    confirm_far_date: F,
+) -> Result<bool>
+where
+    F: FnOnce(NaiveDate, i64) -> bool,
+{
+    if repository.note_exists(filename) {
+        return Ok(false);
+    }
+
+    // Guard against typos like 17-01-2052 creating far-away notes.
+    let distance = (date - today).num_days();
+    let guard = i64::from(config.date_guard_days);
+    if guard > 0 && distance.abs() > guard && !confirm_far_date(date, distance) {
+        return Err(DjourError::Config(format!(
+            "Refusing to create note for {}: it is {} day(s) {} today (date_guard_days = {}).\n\
+            Use --yes to create it anyway, or 'djour config date_guard_days 0' to disable this check.",
+            date.format("%d-%m-%Y"),
+            distance.abs(),
+            if distance > 0 { "after" } else { "before" },
+            config.date_guard_days
+        )));
+    }
+
+    // Create file with template. Another process may have created the note
+    // since the check above; then it keeps what it holds.
+    let content = new_note_content(repository, config, date, diagnostics)?;
+    let content = with_recurring_entries(repository, config, date, content)?;
+    repository.create_note_exclusive(filename, &refresh_tag_footer(config, content))
+}
+
+/// The mode's template rendered for `date`: what a new note starts with.
+///
+/// Issues of the template go to `diagnostics` the first time it is used.
+pub(crate) fn new_note_content(
+    repository: &FileSystemRepository,
+    config: &Config,
+    date: NaiveDate,
+    diagnostics: &mut Diagnostics,
+) -> Result<String> {
+    let template_name = config.get_mode().template_name();
+    let template = load_template_for_week(repository.root(), template_name, config.week_start)?;
+    let issues = unreported_template_issues(repository, config, &template);
+    warn_template_issues(diagnostics, template_name, &issues);
+    Ok(template.render_with_context(&config.render_context(date)))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::JournalMode;
+    use crate::domain::{load_template, JournalMode, TimeReference};
     use crate::infrastructure::Config;
     use tempfile::TempDir;
 
@@ -189,6 +352,109 @@ mod tests {
         assert_eq!(content, "# Existing content");
     }
 
+    fn init_daily(temp: &TempDir) -> FileSystemRepository {
+        let repo = FileSystemRepository::new(temp.path().to_path_buf());
+        repo.initialize().unwrap();
+        repo.save_config(&Config::new(JournalMode::Daily)).unwrap();
+        repo
+    }
+
+    #[test]
+    fn test_far_date_guard_blocks_creation_without_confirmation() {
+        let temp = TempDir::new().unwrap();
+        let repo = init_daily(&temp);
+
+        let err = open_note(&repo, "17-01-2152", false).unwrap_err();
+        assert!(err.to_string().contains("date_guard_days"));
+        assert!(!repo.note_exists("2152-01-17.md"));
+    }
+
+    #[test]
+    fn test_far_date_guard_allows_confirmed_creation() {
+        let temp = TempDir::new().unwrap();
+        let repo = init_daily(&temp);
+
+        let mut asked = None;
+        let filename = open_note_with_confirm(&repo, "17-01-1952", false, |date, distance| {
+            asked = Some((date, distance));
+            true
+        })
+        .unwrap();
+
+        assert_eq!(filename, "1952-01-17.md");
+        assert!(repo.note_exists(&filename));
+        let (date, distance) = asked.unwrap();
+        assert_eq!(date, NaiveDate::from_ymd_opt(1952, 1, 17).unwrap());
+        assert!(distance < 0);
+    }
+
+    #[test]
+    fn test_far_date_guard_skips_existing_and_near_notes() {
+        let temp = TempDir::new().unwrap();
+        let repo = init_daily(&temp);
+        repo.write_note("2152-01-17.md", "# Existing").unwrap();
+
+        assert_eq!(
+            open_note(&repo, "17-01-2152", false).unwrap(),
+            "2152-01-17.md"
+        );
+        assert_eq!(repo.read_note("2152-01-17.md").unwrap(), "# Existing");
+        assert!(open_note(&repo, "tomorrow", false).is_ok());
+    }
+
+    #[test]
+    fn test_far_date_guard_disabled_with_zero() {
+        let temp = TempDir::new().unwrap();
+        let repo = init_daily(&temp);
+        let mut config = repo.load_config().unwrap();
+        config.date_guard_days = 0;
+        repo.save_config(&config).unwrap();
+
+        assert!(open_note(&repo, "17-01-2152", false).is_ok());
+        assert!(repo.note_exists("2152-01-17.md"));
+    }
+
+    #[test]
+    fn test_racing_creators_never_reset_a_written_note() {
+        let temp = TempDir::new().unwrap();
+        let repo = init_daily(&temp);
+        let config = repo.load_config().unwrap();
+        let today = NaiveDate::from_ymd_opt(2026, 10, 17).unwrap();
+
+        for offset in 0..20 {
+            let date = today + chrono::Duration::days(offset);
+            let filename = config.mode.filename_for_date(date);
+            let barrier = std::sync::Barrier::new(2);
+            std::thread::scope(|scope| {
+                for writer in 0..2 {
+                    let (repo, config, filename, barrier) = (&repo, &config, &filename, &barrier);
+                    scope.spawn(move || {
+                        barrier.wait();
+                        let created = ensure_note_created(
+                            repo,
+                            config,
+                            date,
+                            filename,
+                            today,
+                            &mut Diagnostics::new(),
+                            |_, _| true,
+                        )
+                        .unwrap();
+                        if created {
+                            // The creator goes on to write in its note
+                            let content = repo.read_note(filename).unwrap();
+                            let typed = format!("{}\n- typed by writer {}\n", content, writer);
+                            repo.write_note(filename, &typed).unwrap();
+                        }
+                    });
+                }
+            });
+
+            let content = repo.read_note(&filename).unwrap();
+            assert!(content.contains("- typed by writer"), "{}", content);
+        }
+    }
+
     #[test]
     fn test_parse_invalid_time_reference() {
         let result = TimeReference::parse("invaliddate");
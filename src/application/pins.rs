@@ -0,0 +1,195 @@
+//! Pinned notes use case (`djour pin`, `djour unpin`)
+
+use crate::application::note_path::{interpretations, pick_interpretation, Interpretation};
+use crate::error::{DjourError, Result};
+use crate::infrastructure::{Config, FileSystemRepository, JournalRepository, NoteEntry};
+use chrono::Local;
+use std::path::{Component, Path};
+
+/// A note named in the `pinned` config list
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PinnedNote {
+    pub entry: NoteEntry,
+    /// Whether the note file still exists; missing pins stay listed
+    pub exists: bool,
+}
+
+/// Outcome of [`pin_note`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PinOutcome {
+    /// Note path relative to the journal root
+    pub filename: String,
+    /// False when the note was pinned already
+    pub added: bool,
+}
+
+/// Pin a note, named by its path relative to the journal root or by a time
+/// reference. The note must exist.
+pub fn pin_note(repository: &FileSystemRepository, target: &str) -> Result<PinOutcome> {
+    let mut config = repository.load_config()?;
+    let filename = resolve_existing_note(repository, &config, target)?;
+
+    let added = !config.pinned.contains(&filename);
+    if added {
+        config.pinned.push(filename.clone());
+        repository.save_config(&config)?;
+    }
+    Ok(PinOutcome { filename, added })
+}
+
+/// Unpin a note and return its path. Pins of notes that no longer exist are
+/// removed by the path they were pinned under.
+pub fn unpin_note(repository: &FileSystemRepository, target: &str) -> Result<String> {
+    let mut config = repository.load_config()?;
+    let path = normalize_path(target);
+    let mut candidates = vec![path.clone(), format!("{}.md", path)];
+    let today = Local::now().date_naive();
+    for candidate in interpretations(&config, target, today, |name| {
+        repository.root().join(name).is_file()
+    }) {
+        match candidate {
+            Interpretation::Date { filename, .. } | Interpretation::File { filename } => {
+                candidates.push(filename)
+            }
+            Interpretation::OtherMode { .. } => {}
+        }
+    }
+
+    let position = config
+        .pinned
+        .iter()
+        .position(|pinned| candidates.contains(pinned))
+        .ok_or_else(|| DjourError::Config(format!("'{}' is not pinned", target)))?;
+    let filename = config.pinned.remove(position);
+    repository.save_config(&config)?;
+    Ok(filename)
+}
+
+/// The pinned notes in pinning order, each with its date and whether it exists
+pub fn pinned_notes(repository: &FileSystemRepository, config: &Config) -> Vec<PinnedNote> {
+    let naming = config.naming();
+    config
+        .pinned
+        .iter()
+        .map(|filename| {
+            let metadata = std::fs::metadata(repository.root().join(filename)).ok();
+            let exists = metadata.as_ref().is_some_and(|m| m.is_file());
+            let file_name = Path::new(filename)
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let entry = NoteEntry::new(filename.clone(), naming.date_from_filename(&file_name))
+                .with_modified(metadata.and_then(|m| m.modified().ok()));
+            PinnedNote { entry, exists }
+        })
+        .collect()
+}
+
+/// `target` with `/` separators and without a leading `./`
+fn normalize_path(target: &str) -> String {
+    let path = target.trim().replace('\\', "/");
+    path.trim_start_matches("./").to_string()
+}
+
+/// Root-relative path of the existing note `target` names: a path in the
+/// journal (with or without `.md`), else a time reference or note name.
+fn resolve_existing_note(
+    repository: &FileSystemRepository,
+    config: &Config,
+    target: &str,
+) -> Result<String> {
+    let path = normalize_path(target);
+    let inside_root = Path::new(&path)
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)));
+    if inside_root && !path.is_empty() {
+        for candidate in [path.clone(), format!("{}.md", path)] {
+            if repository.root().join(&candidate).is_file() {
+                return Ok(candidate);
+            }
+        }
+    }
+
+    let file_exists = |name: &str| repository.root().join(name).is_file();
+    let today = Local::now().date_naive();
+    let candidates = interpretations(config, target, today, file_exists);
+    let filename = match pick_interpretation(target, config.get_mode(), candidates, None)? {
+        Interpretation::Date { filename, .. } | Interpretation::File { filename } => filename,
+        Interpretation::OtherMode { .. } => unreachable!("other modes are never picked"),
+    };
+    if !file_exists(&filename) {
+        return Err(DjourError::Config(format!(
+            "Cannot pin '{}': {} does not exist",
+            target, filename
+        )));
+    }
+    Ok(filename)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::JournalMode;
+    use tempfile::TempDir;
+
+    fn setup() -> (TempDir, FileSystemRepository) {
+        let temp = TempDir::new().unwrap();
+        let repo = FileSystemRepository::new(temp.path().to_path_buf());
+        repo.initialize().unwrap();
+        repo.save_config(&Config::new(JournalMode::Daily)).unwrap();
+        (temp, repo)
+    }
+
+    #[test]
+    fn pins_by_path_and_time_reference() {
+        let (temp, repo) = setup();
+        std::fs::create_dir(temp.path().join("projects")).unwrap();
+        std::fs::write(temp.path().join("projects/goals.md"), "# Goals").unwrap();
+        std::fs::write(temp.path().join("2025-01-17.md"), "# Friday").unwrap();
+
+        assert_eq!(
+            pin_note(&repo, "./projects/goals").unwrap(),
+            PinOutcome {
+                filename: "projects/goals.md".to_string(),
+                added: true
+            }
+        );
+        assert_eq!(
+            pin_note(&repo, "17-01-2025").unwrap().filename,
+            "2025-01-17.md"
+        );
+        assert!(!pin_note(&repo, "projects/goals.md").unwrap().added);
+
+        let config = repo.load_config().unwrap();
+        assert_eq!(config.pinned, vec!["projects/goals.md", "2025-01-17.md"]);
+    }
+
+    #[test]
+    fn refuses_missing_notes() {
+        let (_temp, repo) = setup();
+        let err = pin_note(&repo, "17-01-2025").unwrap_err().to_string();
+        assert!(err.contains("2025-01-17.md does not exist"), "{}", err);
+        assert!(pin_note(&repo, "../outside.md").is_err());
+        assert!(repo.load_config().unwrap().pinned.is_empty());
+    }
+
+    #[test]
+    fn unpins_notes_that_no_longer_exist() {
+        let (temp, repo) = setup();
+        std::fs::write(temp.path().join("2025-01-17.md"), "").unwrap();
+        pin_note(&repo, "2025-01-17.md").unwrap();
+        std::fs::remove_file(temp.path().join("2025-01-17.md")).unwrap();
+
+        let pins = pinned_notes(&repo, &repo.load_config().unwrap());
+        assert_eq!(pins.len(), 1);
+        assert!(!pins[0].exists);
+        assert_eq!(
+            pins[0].entry.date,
+            chrono::NaiveDate::from_ymd_opt(2025, 1, 17)
+        );
+
+        assert_eq!(unpin_note(&repo, "17-01-2025").unwrap(), "2025-01-17.md");
+        assert!(repo.load_config().unwrap().pinned.is_empty());
+        assert!(unpin_note(&repo, "17-01-2025").is_err());
+    }
+}
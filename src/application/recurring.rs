@@ -0,0 +1,144 @@
+//! Recurring entries use cases (`.djour/recurring.md`, `djour recurring list`)
+
+use crate::application::capture::{append_entry, weekday_heading};
+use crate::domain::mode_migration::week_start;
+use crate::domain::{JournalMode, RecurringDirectives};
+use crate::error::{DjourError, Result};
+use crate::infrastructure::{Config, FileSystemRepository};
+use chrono::{Datelike, Duration, NaiveDate};
+
+/// Directives file, relative to the journal root
+pub const RECURRING_FILE: &str = ".djour/recurring.md";
+
+/// Parse the journal's directives file; no file means no directives
+pub fn list_recurring(repository: &FileSystemRepository) -> Result<RecurringDirectives> {
+    Ok(RecurringDirectives::parse(
+        &repository.read_note(RECURRING_FILE)?,
+    ))
+}
+
+/// Add the entries of the directives matching `date` to `content`, the
+/// rendered template of a new note.
+///
+/// Daily and single notes get the entries for `date` at the end. Weekly notes
+/// get each day's entries at the end of its weekday section, and monthly notes
+/// each directive matching a day of the month once. An invalid directives file
+/// fails, so no note is created without its entries.
+pub(crate) fn with_recurring_entries(
+    repository: &FileSystemRepository,
+    config: &Config,
+    date: NaiveDate,
+    content: String,
+) -> Result<String> {
+    let recurring = list_recurring(repository)?;
+    if let Some(error) = recurring.errors.first() {
+        return Err(DjourError::Config(format!(
+            "Invalid {} ({}). Run 'djour recurring list' to check the file.",
+            RECURRING_FILE, error
+        )));
+    }
+    if recurring.directives.is_empty() {
+        return Ok(content);
+    }
+
+    let add = |content: String, section: Option<&str>, entries: Vec<&str>| {
+        if entries.is_empty() {
+            content
+        } else {
+            append_entry(&content, section, &entries.join("\n")).0
+        }
+    };
+    Ok(match config.get_mode() {
+        JournalMode::Daily | JournalMode::Single => {
+            add(content, None, recurring.entries_for([date]))
+        }
+        JournalMode::Weekly => {
+            let start = week_start(date, config.week_start);
+            (0..7)
+                .map(|offset| start + Duration::days(offset))
+                .fold(content, |content, day| {
+                    let heading = weekday_heading(config, day);
+                    add(content, heading.as_deref(), recurring.entries_for([day]))
+                })
+        }
+        JournalMode::Monthly => {
+            let first = date.with_day(1).unwrap_or(date);
+            let month = first
+                .iter_days()
+                .take_while(|day| day.month() == first.month());
+            add(content, None, recurring.entries_for(month))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::open_note::new_note_content;
+    use crate::domain::Diagnostics;
+    use crate::infrastructure::JournalRepository;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn journal(mode: JournalMode, directives: &str) -> (TempDir, FileSystemRepository, Config) {
+        let temp = TempDir::new().unwrap();
+        let repo = FileSystemRepository::new(temp.path().to_path_buf());
+        repo.initialize().unwrap();
+        let config = Config::new(mode);
+        repo.save_config(&config).unwrap();
+        fs::write(temp.path().join(RECURRING_FILE), directives).unwrap();
+        (temp, repo, config)
+    }
+
+    fn new_note(repo: &FileSystemRepository, config: &Config, date: NaiveDate) -> String {
+        let content = new_note_content(repo, config, date, &mut Diagnostics::new()).unwrap();
+        with_recurring_entries(repo, config, date, content).unwrap()
+    }
+
+    #[test]
+    fn test_weekly_entries_go_under_their_weekday() {
+        let (_temp, repo, config) = journal(
+            JournalMode::Weekly,
+            "@every monday: - plan the week\n@every 15th: - pay rent\n",
+        );
+        // Week of Monday 12 October 2026
+        let note = new_note(
+            &repo,
+            &config,
+            NaiveDate::from_ymd_opt(2026, 10, 14).unwrap(),
+        );
+
+        let monday = note.find("## Monday").unwrap();
+        let tuesday = note.find("## Tuesday").unwrap();
+        let thursday = note.find("## Thursday").unwrap();
+        let friday = note.find("## Friday").unwrap();
+        let plan = note.find("- plan the week").unwrap();
+        let rent = note.find("- pay rent").unwrap();
+        assert!(monday < plan && plan < tuesday);
+        assert!(thursday < rent && rent < friday);
+        assert_eq!(note.matches("- plan the week").count(), 1);
+    }
+
+    #[test]
+    fn test_monthly_note_gets_each_matching_entry_once() {
+        let (_temp, repo, config) = journal(
+            JournalMode::Monthly,
+            "@every monday: - plan the week\n@every 31st: - never in this month\n",
+        );
+        let note = new_note(
+            &repo,
+            &config,
+            NaiveDate::from_ymd_opt(2026, 11, 5).unwrap(),
+        );
+        assert_eq!(note.matches("- plan the week").count(), 1);
+        assert!(!note.contains("never in this month"));
+    }
+
+    #[test]
+    fn test_invalid_directives_file_fails() {
+        let (_temp, repo, config) = journal(JournalMode::Daily, "@every someday: - x\n");
+        let date = NaiveDate::from_ymd_opt(2026, 10, 17).unwrap();
+        let err = with_recurring_entries(&repo, &config, date, String::new()).unwrap_err();
+        assert!(err.to_string().contains("line 1: Invalid rule: 'someday'"));
+    }
+}
@@ -0,0 +1,131 @@
+//! Reminders use case (`djour remind`)
+
+use crate::application::scan::{scan_tagged_content, ScanProgress, ScanScope};
+use crate::domain::{extract_reminders, Diagnostics, ReminderItem};
+use crate::error::Result;
+use crate::infrastructure::repository::JournalRepository;
+use crate::infrastructure::{FileSystemRepository, Notifier};
+use chrono::NaiveDate;
+use std::path::PathBuf;
+
+/// Most reminders sent as a notification each; more are batched into one
+pub const NOTIFY_LIMIT: usize = 3;
+
+/// Reminders due on or before `today`, most overdue first.
+///
+/// Notes under `archive/` and `trash/` are not read. Reminders without a
+/// usable due date are reported in `diagnostics`.
+pub fn due_reminders(
+    repository: &FileSystemRepository,
+    recursive: bool,
+    today: NaiveDate,
+    diagnostics: &mut Diagnostics,
+    progress: Option<ScanProgress<'_>>,
+) -> Result<Vec<ReminderItem>> {
+    let config = repository.load_config()?;
+    let scope = ScanScope {
+        recursive,
+        ..ScanScope::default()
+    };
+
+    let mut reminders = Vec::new();
+    for note in scan_tagged_content(repository, &config, &scope, progress, diagnostics)? {
+        let path = PathBuf::from(&note.entry.filename);
+        reminders.extend(
+            extract_reminders(&note.content, &path, today, diagnostics)
+                .into_iter()
+                .filter(ReminderItem::is_due),
+        );
+    }
+    reminders.sort_by(|a, b| {
+        a.due
+            .cmp(&b.due)
+            .then_with(|| a.source_file.cmp(&b.source_file))
+            .then_with(|| a.line.cmp(&b.line))
+    });
+    Ok(reminders)
+}
+
+/// Send `reminders` to `notifier`: one notification each, or a single
+/// "N reminders due" one listing the first few beyond [`NOTIFY_LIMIT`].
+///
+/// Returns the number of notifications sent.
+pub fn notify_reminders(notifier: &mut dyn Notifier, reminders: &[ReminderItem]) -> Result<usize> {
+    if reminders.len() <= NOTIFY_LIMIT {
+        for reminder in reminders {
+            notifier.send(&reminder_summary(reminder), &reminder.text)?;
+        }
+        return Ok(reminders.len());
+    }
+
+    let mut body: Vec<String> = reminders
+        .iter()
+        .take(NOTIFY_LIMIT)
+        .map(|reminder| reminder.text.clone())
+        .collect();
+    body.push(format!("… and {} more", reminders.len() - NOTIFY_LIMIT));
+    notifier.send(
+        &format!("{} reminders due", reminders.len()),
+        &body.join("\n"),
+    )?;
+    Ok(1)
+}
+
+/// Notification title of one reminder
+fn reminder_summary(reminder: &ReminderItem) -> String {
+    match reminder.overdue_days {
+        0 => "Reminder due today".to_string(),
+        1 => "Reminder overdue by 1 day".to_string(),
+        days => format!("Reminder overdue by {} days", days),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::MemoryNotifier;
+
+    fn reminder(text: &str, overdue_days: i64) -> ReminderItem {
+        let today = NaiveDate::from_ymd_opt(2025, 2, 15).unwrap();
+        ReminderItem {
+            text: text.to_string(),
+            due: today - chrono::Duration::days(overdue_days),
+            source_file: PathBuf::from("2025-02-01.md"),
+            line: 1,
+            overdue_days,
+        }
+    }
+
+    #[test]
+    fn test_notifies_each_reminder_up_to_the_limit() {
+        let mut notifier = MemoryNotifier::default();
+        let reminders = [reminder("call dentist", 0), reminder("pay rent", 3)];
+        assert_eq!(notify_reminders(&mut notifier, &reminders).unwrap(), 2);
+        assert_eq!(
+            notifier.sent,
+            [
+                ("Reminder due today".to_string(), "call dentist".to_string()),
+                (
+                    "Reminder overdue by 3 days".to_string(),
+                    "pay rent".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_batches_reminders_beyond_the_limit() {
+        let mut notifier = MemoryNotifier::default();
+        let reminders: Vec<ReminderItem> = (0..5)
+            .map(|i| reminder(&format!("item {}", i), 0))
+            .collect();
+        assert_eq!(notify_reminders(&mut notifier, &reminders).unwrap(), 1);
+        assert_eq!(
+            notifier.sent,
+            [(
+                "5 reminders due".to_string(),
+                "item 0\nitem 1\nitem 2\n… and 2 more".to_string()
+            )]
+        );
+    }
+}
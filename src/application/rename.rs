@@ -0,0 +1,225 @@
+//! Rename note use case
+//!
+//! Moves a note and rewrites the relative links that pointed at it.
+
+use crate::application::note_path::resolve_note_filename;
+use crate::domain::links::retarget_links;
+use crate::error::{DjourError, Result};
+use crate::infrastructure::repository::JournalRepository;
+use crate::infrastructure::{Config, FileSystemRepository};
+use chrono::Local;
+use std::path::{Component, Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenameFileChange {
+    /// Referring file, at its path after the rename
+    pub filename: String,
+    pub links: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenameReport {
+    pub from: String,
+    pub to: String,
+    pub dry_run: bool,
+    pub changes: Vec<RenameFileChange>,
+}
+
+impl RenameReport {
+    pub fn total_links(&self) -> usize {
+        self.changes.iter().map(|change| change.links).sum()
+    }
+}
+
+/// Rename the note `old` to `new` and update the links to it in every
+/// markdown file of the journal (directories starting with `.` excluded).
+///
+/// Both arguments are filenames relative to the journal root, or time
+/// references resolved by the configured mode. Refuses to overwrite an
+/// existing file. With `dry_run`, reports the changes without writing.
+pub fn rename_note(
+    repository: &FileSystemRepository,
+    old: &str,
+    new: &str,
+    dry_run: bool,
+) -> Result<RenameReport> {
+    let config = repository.load_config()?;
+    let from = resolve_rename_argument(repository, &config, old)?;
+    let to = resolve_rename_argument(repository, &config, new)?;
+    if from == to {
+        return Err(DjourError::Config(format!(
+            "'{}' and '{}' are the same file: {}",
+            old, new, from
+        )));
+    }
+
+    let from_path = repository.root().join(&from);
+    let to_path = repository.root().join(&to);
+    if !from_path.is_file() {
+        return Err(DjourError::Config(format!(
+            "Note does not exist: {}",
+            from_path.display()
+        )));
+    }
+    if to_path.exists() {
+        return Err(DjourError::Config(format!(
+            "Destination already exists: {}",
+            to_path.display()
+        )));
+    }
+
+    let mut updates = Vec::new();
+    for filename in repository.list_markdown_files_in("")? {
+        if filename.split('/').any(|part| part.starts_with('.')) {
+            continue;
+        }
+        let content = repository.read_note(&filename)?;
+        let moved = filename == from;
+        let (updated, links) = retarget_links(
+            &content,
+            Path::new(&filename),
+            moved.then(|| Path::new(&to)),
+            Path::new(&from),
+            Path::new(&to),
+        );
+        if links > 0 {
+            let filename = if moved { to.clone() } else { filename };
+            updates.push((filename, updated, links));
+        }
+    }
+
+    if !dry_run {
+        repository.ensure_writable("rename", &from_path)?;
+        for (filename, _, _) in &updates {
+            repository.ensure_writable("rename", &repository.root().join(filename))?;
+        }
+        repository.move_note(&from, &to)?;
+        for (filename, updated, _) in &updates {
+            repository.write_note_atomic(filename, updated)?;
+        }
+    }
+
+    Ok(RenameReport {
+        from,
+        to,
+        dry_run,
+        changes: updates
+            .into_iter()
+            .map(|(filename, _, links)| RenameFileChange { filename, links })
+            .collect(),
+    })
+}
+
+/// Journal-relative filename for a `rename` argument: a path (anything with
+/// an extension or a `/`, or an existing file) or a time reference
+fn resolve_rename_argument(
+    repository: &FileSystemRepository,
+    config: &Config,
+    argument: &str,
+) -> Result<String> {
+    let path = Path::new(argument);
+    let is_path = path.extension().is_some()
+        || argument.contains(['/', '\\'])
+        || repository.root().join(argument).is_file();
+    if !is_path {
+        let today = Local::now().date_naive();
+        return resolve_note_filename(config, argument, today).map(|(_, filename)| filename);
+    }
+
+    // Only plain names below the root: no `..`, no other drive or root
+    let rel = if path.is_absolute() {
+        path.strip_prefix(repository.root()).ok()
+    } else {
+        Some(path)
+    };
+    let parts: Option<PathBuf> = rel.and_then(|rel| {
+        rel.components()
+            .filter(|c| *c != Component::CurDir)
+            .map(|c| matches!(c, Component::Normal(_)).then_some(c))
+            .collect()
+    });
+    parts
+        .filter(|rel| rel.file_name().is_some())
+        .and_then(|rel| {
+            rel.iter()
+                .map(|part| part.to_str())
+                .collect::<Option<Vec<_>>>()
+                .map(|parts| parts.join("/"))
+        })
+        .ok_or_else(|| DjourError::Config(format!("Note is outside the journal: {}", argument)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::init;
+    use crate::domain::JournalMode;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn journal() -> (TempDir, FileSystemRepository) {
+        let temp = TempDir::new().unwrap();
+        init(temp.path(), JournalMode::Daily).unwrap();
+        fs::write(
+            temp.path().join("2025-01-18.md"),
+            "# Friday\n\n[next](2025-01-20.md)\n",
+        )
+        .unwrap();
+        fs::write(
+            temp.path().join("2025-01-20.md"),
+            "See [Friday](2025-01-18.md#standup) and [again](./2025-01-18.md).\n",
+        )
+        .unwrap();
+        let repo = FileSystemRepository::new(temp.path().to_path_buf());
+        (temp, repo)
+    }
+
+    #[test]
+    fn test_rename_by_time_reference_updates_links() {
+        let (temp, repo) = journal();
+        let report = rename_note(&repo, "18-01-2025", "17-01-2025", false).unwrap();
+
+        assert_eq!(report.from, "2025-01-18.md");
+        assert_eq!(report.to, "2025-01-17.md");
+        assert_eq!(
+            report.changes,
+            vec![RenameFileChange {
+                filename: "2025-01-20.md".to_string(),
+                links: 2
+            }]
+        );
+        assert!(!temp.path().join("2025-01-18.md").exists());
+        assert_eq!(
+            fs::read_to_string(temp.path().join("2025-01-17.md")).unwrap(),
+            "# Friday\n\n[next](2025-01-20.md)\n"
+        );
+        assert_eq!(
+            fs::read_to_string(temp.path().join("2025-01-20.md")).unwrap(),
+            "See [Friday](2025-01-17.md#standup) and [again](2025-01-17.md).\n"
+        );
+    }
+
+    #[test]
+    fn test_dry_run_and_existing_destination_write_nothing() {
+        let (temp, repo) = journal();
+        let report = rename_note(&repo, "2025-01-18.md", "2025-01-17.md", true).unwrap();
+        assert_eq!(report.total_links(), 2);
+        assert!(temp.path().join("2025-01-18.md").exists());
+        assert!(!temp.path().join("2025-01-17.md").exists());
+
+        let err = rename_note(&repo, "2025-01-18.md", "2025-01-20.md", false).unwrap_err();
+        assert!(
+            err.to_string().contains("Destination already exists"),
+            "{}",
+            err
+        );
+        assert!(temp.path().join("2025-01-18.md").exists());
+    }
+
+    #[test]
+    fn test_rename_argument_must_stay_in_journal() {
+        let (_temp, repo) = journal();
+        let err = rename_note(&repo, "2025-01-18.md", "../elsewhere.md", true).unwrap_err();
+        assert!(err.to_string().contains("outside the journal"), "{}", err);
+    }
+}
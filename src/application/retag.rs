@@ -1,10 +1,23 @@
 //! Retag use case
 
-use crate::domain::tags::retag_markdown;
+use crate::application::compile_tags::COMPILATIONS_DIR;
+use crate::application::list_tags::list_tag_counts;
+use crate::application::tag_footer::refresh_tag_footer;
+use crate::domain::markdown::preserve_final_newline;
+use crate::domain::tags::syntax::{is_valid_tag_name, is_valid_value_tag_name, split_value_tag};
+use crate::domain::tags::{
+    retag_markdown, retag_markdown_many, retag_query, retag_query_many, split_at_tag_footer,
+    RetagResult, TagNormalizer,
+};
+use crate::domain::Diagnostics;
 use crate::error::{DjourError, Result};
 use crate::infrastructure::repository::JournalRepository;
-use crate::infrastructure::FileSystemRepository;
-use chrono::NaiveDate;
+use crate::infrastructure::{Config, FileSystemRepository};
+use chrono::{NaiveDate, Utc};
+use std::collections::BTreeMap;
+
+/// Path of the config file, relative to the journal root
+const CONFIG_FILE: &str = ".djour/config.toml";
 
 #[derive(Debug, Clone)]
 pub struct RetagOptions {
@@ -14,6 +27,16 @@ pub struct RetagOptions {
     pub to: Option<NaiveDate>,
     pub recursive: bool,
     pub dry_run: bool,
+    /// Also rewrite files in the compilations directory
+    pub update_compilations: bool,
+    /// Also rewrite saved queries in config.toml
+    pub update_queries: bool,
+    /// Ignore `from_tag`/`to_tag` and merge spelling variants of every tag
+    /// into its most frequent spelling
+    pub auto_normalize: bool,
+    /// Copy every file into `.djour/archive/retag-<stamp>/` before rewriting it,
+    /// so a failed run can be rolled back
+    pub backup: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -22,6 +45,13 @@ pub struct RetagFileChange {
     pub replacements: usize,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetagQueryChange {
+    pub name: String,
+    pub before: String,
+    pub after: String,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RetagReport {
     pub scanned_files: usize,
@@ -29,26 +59,99 @@ pub struct RetagReport {
     pub total_replacements: usize,
     pub dry_run: bool,
     pub changes: Vec<RetagFileChange>,
+    pub compilation_files_changed: Vec<RetagFileChange>,
+    pub queries_changed: Vec<RetagQueryChange>,
+    /// Renames that were applied (one entry for a plain retag)
+    pub renames: Vec<(String, String)>,
+    /// Where the originals of the rewritten files were copied (None for dry
+    /// runs, runs without backups and runs that changed nothing)
+    pub archive_dir: Option<String>,
+    /// Warnings from listing the notes
+    pub diagnostics: Diagnostics,
+}
+
+/// The renames a retag run applies to each file
+enum Renames {
+    /// One tag and its nested tags
+    Subtree { from: String, to: String },
+    /// Several exact tag names, keyed by lowercased name
+    Exact(BTreeMap<String, String>),
 }
 
+impl Renames {
+    fn markdown(&self, content: &str, value_tags: bool) -> RetagResult {
+        match self {
+            Renames::Subtree { from, to } => retag_markdown(content, from, to, value_tags),
+            Renames::Exact(map) => retag_markdown_many(content, map, value_tags),
+        }
+    }
+
+    fn query(&self, query: &str) -> RetagResult {
+        match self {
+            Renames::Subtree { from, to } => retag_query(query, from, to),
+            Renames::Exact(map) => retag_query_many(query, map),
+        }
+    }
+
+    fn pairs(&self) -> Vec<(String, String)> {
+        match self {
+            Renames::Subtree { from, to } => vec![(from.clone(), to.clone())],
+            Renames::Exact(map) => map.iter().map(|(f, t)| (f.clone(), t.clone())).collect(),
+        }
+    }
+}
+
+/// Rename tags across notes (and, when asked, compilations and saved queries).
+///
+/// Every rewrite is computed before anything is written. Unless `backup` is
+/// off, the files are first copied into `.djour/archive/retag-<stamp>/`; when
+/// a write then fails, the files already rewritten are restored from there and
+/// the error says what was rolled back and what could not be.
 pub fn retag_notes(
     repository: &FileSystemRepository,
     options: RetagOptions,
 ) -> Result<RetagReport> {
-    let from_tag = normalize_tag_argument(&options.from_tag)?;
-    let to_tag = normalize_tag_argument(&options.to_tag)?;
+    let mut config = repository.load_config()?;
+    let renames = if options.auto_normalize {
+        // Variants are derived from the same notes the run will rewrite.
+        let counts = list_tag_counts(
+            repository,
+            options.from,
+            options.to,
+            options.recursive,
+            // Retag rewrites archived notes too
+            true,
+            None,
+        )?;
+        let normalizer = TagNormalizer::new(&config.normalize_exempt);
+        Renames::Exact(normalizer.renames(&counts).into_iter().collect())
+    } else {
+        let from = normalize_tag_argument(&options.from_tag, config.value_tags)?;
+        let to = normalize_tag_argument(&options.to_tag, config.value_tags)?;
+        if split_value_tag(&to).is_some() && split_value_tag(&from).is_none() {
+            return Err(DjourError::Config(format!(
+                "Cannot rename #{} to the value tag #{}: its values would be lost. \
+                Rename one value at a time (#{}:<value>)",
+                from, to, from
+            )));
+        }
+        Renames::Subtree { from, to }
+    };
 
-    let config = repository.load_config()?;
-    let notes = repository.list_notes(
-        config.get_mode(),
+    let mut diagnostics = Diagnostics::new();
+    let notes = repository.list_notes_excluding(
+        config.naming(),
         options.from,
         options.to,
         None,
         options.recursive,
+        &[],
+        &mut diagnostics,
     )?;
 
     let mut changes = Vec::new();
     let mut total_replacements = 0usize;
+    let mut writes = Vec::new();
 
     for note in &notes {
         let content = repository.read_note(&note.filename)?;
@@ -56,13 +159,19 @@ pub fn retag_notes(
             continue;
         }
 
-        let result = retag_markdown(&content, &from_tag, &to_tag);
+        // The footer only repeats the note's tags; it is rebuilt, not counted
+        let (body, footer) = split_at_tag_footer(&content);
+        let result = renames.markdown(body, config.value_tags);
         if result.replacements == 0 {
             continue;
         }
 
         if !options.dry_run {
-            repository.write_note_atomic(&note.filename, &result.content)?;
+            let updated = preserve_final_newline(
+                &content,
+                refresh_tag_footer(&config, format!("{}{}", result.content, footer)),
+            );
+            writes.push((note.filename.clone(), updated));
         }
 
         total_replacements += result.replacements;
@@ -72,28 +181,211 @@ pub fn retag_notes(
         });
     }
 
+    let mut compilation_files_changed = Vec::new();
+    if options.update_compilations {
+        for filename in repository.list_markdown_files_in(COMPILATIONS_DIR)? {
+            let content = repository.read_note(&filename)?;
+            let result = renames.markdown(&content, config.value_tags);
+            if result.replacements == 0 {
+                continue;
+            }
+
+            if !options.dry_run {
+                writes.push((filename.clone(), result.content));
+            }
+
+            compilation_files_changed.push(RetagFileChange {
+                filename,
+                replacements: result.replacements,
+            });
+        }
+    }
+
+    let mut queries_changed = Vec::new();
+    if options.update_queries {
+        for (name, query) in config.queries.iter_mut() {
+            let result = renames.query(query);
+            if result.replacements == 0 {
+                continue;
+            }
+
+            queries_changed.push(RetagQueryChange {
+                name: name.clone(),
+                before: std::mem::replace(query, result.content.clone()),
+                after: result.content,
+            });
+        }
+    }
+
+    let plan = RetagPlan {
+        writes,
+        config: (!options.dry_run && !queries_changed.is_empty()).then_some(config),
+    };
+    // Runs within the same second get `-2`, `-3`, ... rather than sharing
+    // (and overwriting) one backup directory
+    let archive_dir = if options.backup && !plan.is_empty() {
+        let stamp = Utc::now().format("%Y%m%d-%H%M%S");
+        Some(repository.create_dir_unique(&format!(".djour/archive/retag-{}", stamp))?)
+    } else {
+        None
+    };
+    plan.apply(repository, archive_dir.as_deref())?;
+
     Ok(RetagReport {
         scanned_files: notes.len(),
         changed_files: changes.len(),
         total_replacements,
         dry_run: options.dry_run,
         changes,
+        compilation_files_changed,
+        queries_changed,
+        renames: renames.pairs(),
+        archive_dir,
+        diagnostics,
     })
 }
 
-fn normalize_tag_argument(input: &str) -> Result<String> {
+/// The file operations applying a retag needs
+trait RetagWriter {
+    fn copy_note(&self, from: &str, to: &str) -> Result<()>;
+    fn write_note_atomic(&self, filename: &str, content: &str) -> Result<()>;
+    fn save_config(&self, config: &Config) -> Result<()>;
+}
+
+impl RetagWriter for FileSystemRepository {
+    fn copy_note(&self, from: &str, to: &str) -> Result<()> {
+        FileSystemRepository::copy_note(self, from, to)
+    }
+
+    fn write_note_atomic(&self, filename: &str, content: &str) -> Result<()> {
+        FileSystemRepository::write_note_atomic(self, filename, content)
+    }
+
+    fn save_config(&self, config: &Config) -> Result<()> {
+        JournalRepository::save_config(self, config)
+    }
+}
+
+/// Everything a retag run writes, computed before the first write
+struct RetagPlan {
+    /// New content of each rewritten note or compilation
+    writes: Vec<(String, String)>,
+    /// The config, when saved queries changed
+    config: Option<Config>,
+}
+
+impl RetagPlan {
+    fn is_empty(&self) -> bool {
+        self.writes.is_empty() && self.config.is_none()
+    }
+
+    /// Files rewritten, in write order; the config comes last
+    fn targets(&self) -> Vec<&str> {
+        let mut targets: Vec<&str> = self.writes.iter().map(|(f, _)| f.as_str()).collect();
+        if self.config.is_some() {
+            targets.push(CONFIG_FILE);
+        }
+        targets
+    }
+
+    /// Back every target up into `archive_dir` (when given), then write them.
+    ///
+    /// A failed backup fails before anything is written. A failed write rolls
+    /// back the files written so far, the failed one included.
+    fn apply(&self, writer: &impl RetagWriter, archive_dir: Option<&str>) -> Result<()> {
+        let targets = self.targets();
+        if let Some(dir) = archive_dir {
+            for target in &targets {
+                writer
+                    .copy_note(target, &format!("{}/{}", dir, target))
+                    .map_err(|e| {
+                        DjourError::Config(format!(
+                            "Could not back up {} into {}: {}. No file was changed.",
+                            target, dir, e
+                        ))
+                    })?;
+            }
+        }
+
+        for (i, (filename, content)) in self.writes.iter().enumerate() {
+            writer
+                .write_note_atomic(filename, content)
+                .map_err(|e| rollback(writer, &targets[..=i], archive_dir, e))?;
+        }
+        if let Some(config) = &self.config {
+            writer
+                .save_config(config)
+                .map_err(|e| rollback(writer, &targets, archive_dir, e))?;
+        }
+        Ok(())
+    }
+}
+
+/// Restore `touched` (the files written so far, the failed one last) from
+/// `archive_dir` and describe what happened
+fn rollback(
+    writer: &impl RetagWriter,
+    touched: &[&str],
+    archive_dir: Option<&str>,
+    error: DjourError,
+) -> DjourError {
+    let (failed, written) = touched.split_last().expect("a write failed");
+    let mut message = format!("Retag failed writing {}: {}.", failed, error);
+
+    let Some(dir) = archive_dir else {
+        if written.is_empty() {
+            message.push_str(" No other file was changed.");
+        } else {
+            message.push_str(&format!(
+                " No backups were made (--no-backup); already rewritten: {}.",
+                written.join(", ")
+            ));
+        }
+        return DjourError::Config(message);
+    };
+
+    let mut restored = Vec::new();
+    let mut lost = Vec::new();
+    for filename in touched {
+        match writer.copy_note(&format!("{}/{}", dir, filename), filename) {
+            Ok(()) => restored.push(*filename),
+            Err(e) => lost.push(format!("{} ({})", filename, e)),
+        }
+    }
+    if !restored.is_empty() {
+        message.push_str(&format!(" Rolled back: {}.", restored.join(", ")));
+    }
+    if !lost.is_empty() {
+        message.push_str(&format!(
+            " Could not restore: {}; their originals are in {}.",
+            lost.join(", "),
+            dir
+        ));
+    }
+    message.push_str(&format!(" Backups are in {}.", dir));
+    DjourError::Config(message)
+}
+
+fn normalize_tag_argument(input: &str, value_tags: bool) -> Result<String> {
     let tag = input.strip_prefix('#').unwrap_or(input);
     if tag.is_empty() {
         return Err(DjourError::Config(format!("Invalid tag: {}", input)));
     }
 
-    if !tag
-        .chars()
-        .all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_')
-    {
+    let valid = if value_tags {
+        is_valid_value_tag_name(tag)
+    } else {
+        is_valid_tag_name(tag)
+    };
+    if !tag.is_ascii() || !valid {
         return Err(DjourError::Config(format!(
-            "Invalid tag: {}. Allowed characters: letters, numbers, '-', '_'",
-            input
+            "Invalid tag: {}. Allowed characters: letters, numbers, '-', '_' and '/' between segments{}",
+            input,
+            if value_tags {
+                ", then an optional ':value'"
+            } else {
+                ""
+            }
         )));
     }
 
@@ -103,17 +395,161 @@ fn normalize_tag_argument(input: &str) -> Result<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::application::init;
+    use crate::domain::JournalMode;
+    use std::fs;
+    use tempfile::TempDir;
+
+    /// Fails every write of `fail_on`, like a full disk would
+    struct FailingWriter {
+        inner: FileSystemRepository,
+        fail_on: &'static str,
+    }
+
+    impl RetagWriter for FailingWriter {
+        fn copy_note(&self, from: &str, to: &str) -> Result<()> {
+            self.inner.copy_note(from, to)
+        }
+
+        fn write_note_atomic(&self, filename: &str, content: &str) -> Result<()> {
+            if filename == self.fail_on {
+                return Err(DjourError::Config("No space left on device".to_string()));
+            }
+            self.inner.write_note_atomic(filename, content)
+        }
+
+        fn save_config(&self, config: &Config) -> Result<()> {
+            if self.fail_on == CONFIG_FILE {
+                return Err(DjourError::Config("No space left on device".to_string()));
+            }
+            JournalRepository::save_config(&self.inner, config)
+        }
+    }
+
+    fn journal() -> (TempDir, FileSystemRepository) {
+        let temp = TempDir::new().unwrap();
+        init(temp.path(), JournalMode::Daily).unwrap();
+        for (name, body) in [("a.md", "old a #work\n"), ("b.md", "old b #work\n")] {
+            fs::write(temp.path().join(name), body).unwrap();
+        }
+        let repo = FileSystemRepository::new(temp.path().to_path_buf());
+        (temp, repo)
+    }
+
+    fn plan(config: Option<Config>) -> RetagPlan {
+        RetagPlan {
+            writes: vec![
+                ("a.md".to_string(), "new a #focus\n".to_string()),
+                ("b.md".to_string(), "new b #focus\n".to_string()),
+            ],
+            config,
+        }
+    }
+
+    #[test]
+    fn test_failed_write_rolls_back_files_already_written() {
+        let (temp, repo) = journal();
+        let writer = FailingWriter {
+            inner: repo,
+            fail_on: "b.md",
+        };
+
+        let err = plan(None)
+            .apply(&writer, Some(".djour/archive/retag-test"))
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("Retag failed writing b.md: ")
+                && err.contains("No space left on device")
+                && err.contains("Rolled back: a.md, b.md.")
+                && err.contains("Backups are in .djour/archive/retag-test."),
+            "{}",
+            err
+        );
+        assert_eq!(
+            fs::read_to_string(temp.path().join("a.md")).unwrap(),
+            "old a #work\n"
+        );
+        assert_eq!(
+            fs::read_to_string(temp.path().join("b.md")).unwrap(),
+            "old b #work\n"
+        );
+        assert_eq!(
+            fs::read_to_string(temp.path().join(".djour/archive/retag-test/a.md")).unwrap(),
+            "old a #work\n"
+        );
+    }
+
+    #[test]
+    fn test_failed_config_save_rolls_back_every_note() {
+        let (temp, repo) = journal();
+        let config_before = fs::read_to_string(temp.path().join(CONFIG_FILE)).unwrap();
+        let mut config = repo.load_config().unwrap();
+        config.queries.insert("w".to_string(), "focus".to_string());
+        let writer = FailingWriter {
+            inner: repo,
+            fail_on: CONFIG_FILE,
+        };
+
+        let err = plan(Some(config))
+            .apply(&writer, Some(".djour/archive/retag-test"))
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("Rolled back: a.md, b.md, .djour/config.toml."),
+            "{}",
+            err
+        );
+        assert_eq!(
+            fs::read_to_string(temp.path().join("a.md")).unwrap(),
+            "old a #work\n"
+        );
+        assert_eq!(
+            fs::read_to_string(temp.path().join(CONFIG_FILE)).unwrap(),
+            config_before
+        );
+    }
+
+    #[test]
+    fn test_failed_write_without_backups_names_rewritten_files() {
+        let (temp, repo) = journal();
+        let writer = FailingWriter {
+            inner: repo,
+            fail_on: "b.md",
+        };
+
+        let err = plan(None).apply(&writer, None).unwrap_err().to_string();
+        assert!(
+            err.contains("No backups were made (--no-backup); already rewritten: a.md."),
+            "{}",
+            err
+        );
+        assert_eq!(
+            fs::read_to_string(temp.path().join("a.md")).unwrap(),
+            "new a #focus\n"
+        );
+        assert!(!temp.path().join(".djour/archive").exists());
+    }
 
     #[test]
     fn normalize_tag_argument_accepts_hash_prefix() {
-        assert_eq!(normalize_tag_argument("#Work").unwrap(), "work");
-        assert_eq!(normalize_tag_argument("Work").unwrap(), "work");
+        assert_eq!(normalize_tag_argument("#Work", false).unwrap(), "work");
+        assert_eq!(normalize_tag_argument("Work", false).unwrap(), "work");
     }
 
     #[test]
     fn normalize_tag_argument_rejects_invalid() {
-        assert!(normalize_tag_argument("work@email").is_err());
-        assert!(normalize_tag_argument("#").is_err());
-        assert!(normalize_tag_argument("##work").is_err());
+        assert!(normalize_tag_argument("work@email", false).is_err());
+        assert!(normalize_tag_argument("#", false).is_err());
+        assert!(normalize_tag_argument("##work", false).is_err());
+        assert!(normalize_tag_argument("project/", false).is_err());
+    }
+
+    #[test]
+    fn normalize_tag_argument_accepts_nested_tags() {
+        assert_eq!(
+            normalize_tag_argument("#Project/Alpha", false).unwrap(),
+            "project/alpha"
+        );
     }
 }
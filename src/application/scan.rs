@@ -0,0 +1,391 @@
+//! Reading every note in scope, shared by `compile` and `tags`
+
+use crate::domain::tags::{section_dates, DateWindow};
+use crate::domain::{first_heading, Diagnostics, ExcludePatterns, ARCHIVED_DIRS};
+use crate::error::Result;
+use crate::infrastructure::{Config, FileSystemRepository, NoteEntry};
+use chrono::NaiveDate;
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+/// Called after each note is read with `(notes read, notes in scope)`
+pub type ScanProgress<'a> = &'a dyn Fn(usize, usize);
+
+/// Which notes a scan reads
+#[derive(Debug, Clone, Default)]
+pub struct ScanScope {
+    /// Start date (inclusive)
+    pub from: Option<NaiveDate>,
+    /// End date (inclusive)
+    pub to: Option<NaiveDate>,
+    /// Search subdirectories (excluding dot directories)
+    pub recursive: bool,
+    /// Files or directories (relative to the journal root) never read
+    pub exclude: Vec<PathBuf>,
+    /// Listed notes whose root-relative path matches one of these are not read
+    pub exclude_patterns: ExcludePatterns,
+    /// Also read notes under `archive/` and `trash/` (see [`ARCHIVED_DIRS`])
+    pub include_archived: bool,
+    /// Only notes modified on or after this day (local time)
+    pub changed_since: Option<NaiveDate>,
+    /// Only notes whose first heading contains this text, ignoring case;
+    /// their entries carry the title
+    pub title_filter: Option<String>,
+    /// Also read notes outside `from`/`to` with a section dated into the range by
+    /// an `@DD-MM-YYYY` heading (see [`section_dates`]); every listed note is
+    /// then read to find them
+    pub dated_sections: bool,
+    /// Read exactly these files (root-relative) instead of listing the journal;
+    /// `recursive`, `exclude`, `exclude_patterns` and `include_archived` do not apply to them
+    pub notes: Vec<String>,
+}
+
+/// A note and its content as read by [`scan_tagged_content`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScannedNote {
+    pub entry: NoteEntry,
+    pub content: String,
+}
+
+/// Notes read by [`scan_notes`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Scan {
+    pub notes: Vec<ScannedNote>,
+    /// How many listed notes `exclude_patterns` left unread
+    pub excluded: usize,
+    /// Warnings from listing the notes
+    pub diagnostics: Diagnostics,
+}
+
+/// Read every note of the active mode in `scope`, newest first.
+///
+/// The notes are listed before any is read, so `progress` always knows the
+/// total. Tags are parsed by the callers: `compile` extracts tagged blocks and
+/// `tags` counts occurrences. Warnings from listing the notes go to `diagnostics`.
+pub fn scan_tagged_content(
+    repository: &FileSystemRepository,
+    config: &Config,
+    scope: &ScanScope,
+    progress: Option<ScanProgress<'_>>,
+    diagnostics: &mut Diagnostics,
+) -> Result<Vec<ScannedNote>> {
+    let scan = scan_notes(repository, config, scope, progress)?;
+    diagnostics.append(scan.diagnostics);
+    Ok(scan.notes)
+}
+
+/// Like [`scan_tagged_content`], also counting the notes `exclude_patterns` dropped
+/// and returning the warnings
+pub fn scan_notes(
+    repository: &FileSystemRepository,
+    config: &Config,
+    scope: &ScanScope,
+    progress: Option<ScanProgress<'_>>,
+) -> Result<Scan> {
+    let window = DateWindow::new(scope.from, scope.to);
+    // Later notes are listed too and kept below only when they date a section
+    // into the range. Markers date things written up on a later day, so notes
+    // dated before `from` are never read for them, and without `to` every
+    // later note is in range anyway.
+    let dated_sections = scope.dated_sections && scope.to.is_some();
+    let list = |from, to, diagnostics: &mut Diagnostics| -> Result<Vec<NoteEntry>> {
+        if scope.notes.is_empty() {
+            let mut exclude = scope.exclude.clone();
+            if !scope.include_archived {
+                exclude.extend(ARCHIVED_DIRS.iter().map(PathBuf::from));
+            }
+            repository.list_notes_excluding(
+                config.naming(),
+                from,
+                to,
+                None,
+                scope.recursive,
+                &exclude,
+                diagnostics,
+            )
+        } else {
+            repository.named_notes(config.naming(), &scope.notes, from, to)
+        }
+    };
+    let mut diagnostics = Diagnostics::new();
+    let mut notes = list(scope.from, scope.to, &mut diagnostics)?;
+    let mut in_range = HashSet::new();
+    if dated_sections {
+        in_range = notes.into_iter().map(|note| note.filename).collect();
+        // The wider listing warns about the same directories again
+        diagnostics = Diagnostics::new();
+        notes = list(scope.from, None, &mut diagnostics)?;
+    }
+    let mut excluded = 0;
+    if scope.notes.is_empty() && !scope.exclude_patterns.is_empty() {
+        let listed = notes.len();
+        notes.retain(|note| !scope.exclude_patterns.is_match(&note.filename));
+        excluded = listed - notes.len();
+    }
+    if let Some(since) = scope.changed_since {
+        notes.retain(|note| note.changed_since(since));
+    }
+
+    let title_filter = scope
+        .title_filter
+        .as_ref()
+        .map(|filter| filter.to_lowercase());
+    let total = notes.len();
+    let mut scanned = Vec::with_capacity(total);
+    for (index, mut entry) in notes.into_iter().enumerate() {
+        let content = repository.read_note(&entry.filename)?;
+        if let Some(progress) = progress {
+            progress(index + 1, total);
+        }
+        if dated_sections
+            && !in_range.contains(&entry.filename)
+            && !section_dates(&content)
+                .iter()
+                .any(|section| section.date.is_some_and(|date| window.overlaps(date, date)))
+        {
+            continue;
+        }
+        if let Some(filter) = &title_filter {
+            let title = first_heading(&content);
+            if !title
+                .as_ref()
+                .is_some_and(|title| title.to_lowercase().contains(filter))
+            {
+                continue;
+            }
+            entry.title = title;
+        }
+        scanned.push(ScannedNote { entry, content });
+    }
+    Ok(Scan {
+        notes: scanned,
+        excluded,
+        diagnostics,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::{init, list_tag_counts};
+    use crate::domain::JournalMode;
+    use crate::infrastructure::JournalRepository;
+    use std::cell::RefCell;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn journal() -> (TempDir, FileSystemRepository) {
+        let temp = TempDir::new().unwrap();
+        init(temp.path(), JournalMode::Daily).unwrap();
+        fs::write(temp.path().join("2025-01-15.md"), "Ship #work\n").unwrap();
+        fs::write(temp.path().join("2025-01-16.md"), "").unwrap();
+        fs::create_dir(temp.path().join("old")).unwrap();
+        fs::write(temp.path().join("old/2024-12-01.md"), "Plan #work #home\n").unwrap();
+        let repo = FileSystemRepository::new(temp.path().to_path_buf());
+        (temp, repo)
+    }
+
+    #[test]
+    fn test_scan_matches_note_listing_and_reports_progress() {
+        let (_temp, repo) = journal();
+        let config = repo.load_config().unwrap();
+        let calls = RefCell::new(Vec::new());
+        let progress = |done: usize, total: usize| calls.borrow_mut().push((done, total));
+
+        let scope = ScanScope {
+            recursive: true,
+            ..ScanScope::default()
+        };
+        let scanned = scan_tagged_content(
+            &repo,
+            &config,
+            &scope,
+            Some(&progress),
+            &mut Diagnostics::new(),
+        )
+        .unwrap();
+
+        // Same notes, order and contents as listing and reading them one by one
+        let listed = repo
+            .list_notes(config.naming(), None, None, None, true)
+            .unwrap();
+        assert_eq!(
+            scanned.iter().map(|n| &n.entry).collect::<Vec<_>>(),
+            listed.iter().collect::<Vec<_>>()
+        );
+        for note in &scanned {
+            assert_eq!(note.content, repo.read_note(&note.entry.filename).unwrap());
+        }
+        assert_eq!(calls.into_inner(), vec![(1, 3), (2, 3), (3, 3)]);
+    }
+
+    #[test]
+    fn test_scan_applies_scope() {
+        let (_temp, repo) = journal();
+        let config = repo.load_config().unwrap();
+
+        let top_level = scan_tagged_content(
+            &repo,
+            &config,
+            &ScanScope::default(),
+            None,
+            &mut Diagnostics::new(),
+        )
+        .unwrap();
+        assert_eq!(top_level.len(), 2);
+
+        let scope = ScanScope {
+            recursive: true,
+            exclude: vec![PathBuf::from("old")],
+            ..ScanScope::default()
+        };
+        let excluded =
+            scan_tagged_content(&repo, &config, &scope, None, &mut Diagnostics::new()).unwrap();
+        assert_eq!(excluded, top_level);
+    }
+
+    #[test]
+    fn test_scan_drops_excluded_notes_before_reading() {
+        let (temp, repo) = journal();
+        let config = repo.load_config().unwrap();
+        fs::create_dir(temp.path().join("old/deep")).unwrap();
+        fs::write(temp.path().join("old/deep/2024-11-01.md"), "#work\n").unwrap();
+
+        let scope = ScanScope {
+            recursive: true,
+            exclude_patterns: ExcludePatterns::new(&["old/**", "2025-01-16.md"]).unwrap(),
+            ..ScanScope::default()
+        };
+        let scan = scan_notes(&repo, &config, &scope, None).unwrap();
+        let names: Vec<_> = scan
+            .notes
+            .iter()
+            .map(|n| n.entry.filename.as_str())
+            .collect();
+        assert_eq!(names, vec!["2025-01-15.md"]);
+        assert_eq!(scan.excluded, 3);
+
+        // Named notes are read even when a pattern matches them
+        let named = ScanScope {
+            notes: vec!["2025-01-16.md".to_string()],
+            ..scope
+        };
+        let scan = scan_notes(&repo, &config, &named, None).unwrap();
+        assert_eq!(scan.notes.len(), 1);
+        assert_eq!(scan.excluded, 0);
+    }
+
+    #[test]
+    fn test_scan_title_filter_keeps_matching_headings() {
+        let (temp, repo) = journal();
+        let config = repo.load_config().unwrap();
+        fs::write(
+            temp.path().join("2025-01-16.md"),
+            "# Sprint RETRO #team\n\nWent well #work\n",
+        )
+        .unwrap();
+
+        let scope = ScanScope {
+            recursive: true,
+            title_filter: Some("retro".to_string()),
+            ..ScanScope::default()
+        };
+        let scanned =
+            scan_tagged_content(&repo, &config, &scope, None, &mut Diagnostics::new()).unwrap();
+        assert_eq!(scanned.len(), 1);
+        assert_eq!(scanned[0].entry.filename, "2025-01-16.md");
+        assert_eq!(scanned[0].entry.title.as_deref(), Some("Sprint RETRO"));
+    }
+
+    #[test]
+    fn test_scan_keeps_notes_dating_a_section_into_range() {
+        let (temp, repo) = journal();
+        let config = repo.load_config().unwrap();
+        fs::write(
+            temp.path().join("2025-01-20.md"),
+            "## Catch-up @15-01-2025 #work\nLate\n",
+        )
+        .unwrap();
+        fs::write(
+            temp.path().join("2025-01-21.md"),
+            "## Catch-up @10-01-2025 #work\nEarlier\n",
+        )
+        .unwrap();
+
+        let scope = ScanScope {
+            from: NaiveDate::from_ymd_opt(2025, 1, 15),
+            to: NaiveDate::from_ymd_opt(2025, 1, 15),
+            ..ScanScope::default()
+        };
+        let names = |scope: &ScanScope| -> Vec<String> {
+            scan_tagged_content(&repo, &config, scope, None, &mut Diagnostics::new())
+                .unwrap()
+                .into_iter()
+                .map(|note| note.entry.filename)
+                .collect()
+        };
+        assert_eq!(names(&scope), ["2025-01-15.md"]);
+        let scope = ScanScope {
+            dated_sections: true,
+            ..scope
+        };
+        assert_eq!(names(&scope), ["2025-01-20.md", "2025-01-15.md"]);
+    }
+
+    #[test]
+    fn test_scan_reads_only_named_notes() {
+        let (temp, repo) = journal();
+        let config = repo.load_config().unwrap();
+        fs::write(temp.path().join("old/ideas.md"), "Loose #idea\n").unwrap();
+
+        let scope = ScanScope {
+            notes: vec![
+                "old/2024-12-01.md".to_string(),
+                "./old/ideas.md".to_string(),
+            ],
+            ..ScanScope::default()
+        };
+        let scanned =
+            scan_tagged_content(&repo, &config, &scope, None, &mut Diagnostics::new()).unwrap();
+        let names: Vec<_> = scanned
+            .iter()
+            .map(|note| (note.entry.filename.as_str(), note.entry.date))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("old/2024-12-01.md", NaiveDate::from_ymd_opt(2024, 12, 1)),
+                ("old/ideas.md", None),
+            ]
+        );
+
+        let dated = ScanScope {
+            from: NaiveDate::from_ymd_opt(2025, 1, 1),
+            ..scope.clone()
+        };
+        let in_range =
+            scan_tagged_content(&repo, &config, &dated, None, &mut Diagnostics::new()).unwrap();
+        assert_eq!(in_range.len(), 1);
+        assert_eq!(in_range[0].entry.filename, "old/ideas.md");
+
+        for bad in ["missing.md", "../outside.md", "/etc/hosts"] {
+            let scope = ScanScope {
+                notes: vec![bad.to_string()],
+                ..ScanScope::default()
+            };
+            let err = scan_tagged_content(&repo, &config, &scope, None, &mut Diagnostics::new())
+                .unwrap_err();
+            assert!(err.to_string().contains(bad), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_tag_counts_unchanged_by_shared_scan() {
+        let (_temp, repo) = journal();
+        let counts = list_tag_counts(&repo, None, None, true, false, None).unwrap();
+        assert_eq!(
+            counts.into_iter().collect::<Vec<_>>(),
+            vec![("home".to_string(), 1), ("work".to_string(), 2)]
+        );
+    }
+}
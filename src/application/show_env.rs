@@ -0,0 +1,90 @@
+//! Effective settings use case (`djour env`)
+
+use crate::domain::JournalMode;
+use crate::infrastructure::resolved::{resolve_editor, resolve_mode, EnvLookup};
+use crate::infrastructure::{FileSystemRepository, JournalRepository, Resolved};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// The settings commands would use here, each with where it came from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EffectiveSettings {
+    /// `None` when no journal was found
+    pub root: Option<Resolved<PathBuf>>,
+    /// Why the journal or its config.toml could not be used
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub problem: Option<String>,
+    pub mode: Resolved<JournalMode>,
+    pub editor: Resolved<String>,
+}
+
+/// Resolve root, mode and editor the way other commands would.
+///
+/// Never fails: outside a journal, or with an unreadable config.toml, the root
+/// is reported missing and mode and editor fall back to env vars and defaults.
+pub fn effective_settings(
+    journal: Option<&str>,
+    env: EnvLookup,
+    current_dir: &Path,
+) -> EffectiveSettings {
+    let (root, config, problem) =
+        match FileSystemRepository::resolve_root(journal, env, current_dir) {
+            Ok(root) => match FileSystemRepository::new(root.value.clone()).load_config() {
+                Ok(config) => (Some(root), Some(config), None),
+                Err(e) => (Some(root), None, Some(e.to_string())),
+            },
+            Err(e) => (None, None, Some(e.to_string())),
+        };
+    EffectiveSettings {
+        root,
+        problem,
+        mode: resolve_mode(config.as_ref(), env),
+        editor: resolve_editor(config.as_ref(), env),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::{Config, Source};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_effective_settings_in_journal() {
+        let temp = TempDir::new().unwrap();
+        let repo = FileSystemRepository::new(temp.path().to_path_buf());
+        repo.initialize().unwrap();
+        let config = Config {
+            editor: "vi".to_string(),
+            ..Config::new(JournalMode::Weekly)
+        };
+        repo.save_config(&config).unwrap();
+
+        let env = |name: &str| (name == "EDITOR").then(|| "nvim".to_string());
+        let settings = effective_settings(None, &env, temp.path());
+        assert_eq!(
+            settings.root,
+            Some(Resolved::new(temp.path().to_path_buf(), Source::Discovered))
+        );
+        assert_eq!(settings.problem, None);
+        assert_eq!(settings.mode.to_string(), "weekly (from config.toml)");
+        assert_eq!(
+            settings.editor.to_string(),
+            "nvim (from EDITOR, overriding config 'vi')"
+        );
+    }
+
+    #[test]
+    fn test_effective_settings_outside_journal() {
+        let temp = TempDir::new().unwrap();
+        let env = |name: &str| (name == "DJOUR_MODE").then(|| "monthly".to_string());
+        let settings = effective_settings(None, &env, temp.path());
+        assert_eq!(settings.root, None);
+        assert!(settings.problem.is_some());
+        assert_eq!(
+            settings.mode,
+            Resolved::new(JournalMode::Monthly, Source::Env("DJOUR_MODE"))
+        );
+        assert_eq!(settings.editor.source, Source::Default);
+    }
+}
@@ -0,0 +1,174 @@
+//! Journal statistics use case (`djour stats`)
+
+use crate::application::list_tags::{count_tags, TagOptions};
+use crate::domain::markdown::splice_between_markers;
+use crate::domain::{JournalMode, WeekStart};
+use crate::error::{DjourError, Result};
+use crate::infrastructure::{FileSystemRepository, JournalRepository};
+use chrono::{Datelike, Duration, NaiveDate};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+/// Marker line opening the stats block `--write-badge` keeps in a file
+pub const STATS_START: &str = "<!-- djour-stats:start -->";
+
+/// Marker line closing the stats block
+pub const STATS_END: &str = "<!-- djour-stats:end -->";
+
+/// How many of the most used tags `djour stats` reports
+const TOP_TAGS: usize = 3;
+
+/// Numbers `djour stats` reports
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JournalStats {
+    pub mode: JournalMode,
+    /// Notes in the journal root, in the current mode
+    pub notes: usize,
+    /// Consecutive periods (days, weeks or months) with a note, up to the current one
+    pub streak: usize,
+    /// Most used tags with their counts, most used first
+    pub top_tags: Vec<(String, usize)>,
+}
+
+/// Collect the stats of the journal as of `today`.
+///
+/// Tags are counted like `djour tags` counts them, so `compile_exclude` applies.
+pub fn journal_stats(repository: &FileSystemRepository, today: NaiveDate) -> Result<JournalStats> {
+    let config = repository.load_config()?;
+    let mode = config.get_mode();
+    let notes = repository.list_notes(config.naming(), None, None, None, false)?;
+    let streak = current_streak(
+        mode,
+        config.week_start,
+        notes.iter().filter_map(|note| note.date),
+        today,
+    );
+
+    let counts = count_tags(repository, &TagOptions::default(), None)?.counts;
+    let mut top_tags: Vec<(String, usize)> = counts.into_iter().collect();
+    // Stable sort: equal counts stay in name order
+    top_tags.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+    top_tags.truncate(TOP_TAGS);
+
+    Ok(JournalStats {
+        mode,
+        notes: notes.len(),
+        streak,
+        top_tags,
+    })
+}
+
+/// Number of consecutive periods with a note, ending at the period of
+/// `today`. A current period without a note yet does not break the streak;
+/// it then ends at the period before.
+pub fn current_streak(
+    mode: JournalMode,
+    week: WeekStart,
+    dates: impl IntoIterator<Item = NaiveDate>,
+    today: NaiveDate,
+) -> usize {
+    if mode == JournalMode::Single {
+        return 0;
+    }
+    let period_start = |date: NaiveDate| match mode {
+        JournalMode::Weekly => week.start_of_week(date),
+        JournalMode::Monthly => date.with_day(1).unwrap_or(date),
+        JournalMode::Daily | JournalMode::Single => date,
+    };
+    let periods: BTreeSet<NaiveDate> = dates.into_iter().map(period_start).collect();
+
+    let mut period = period_start(today);
+    if !periods.contains(&period) {
+        period = period_start(period - Duration::days(1));
+    }
+    let mut streak = 0;
+    while periods.contains(&period) {
+        streak += 1;
+        period = period_start(period - Duration::days(1));
+    }
+    streak
+}
+
+/// Put `block` between the stats markers in the file at `path`, adding the
+/// markers at its end when absent; a missing file is created.
+///
+/// Returns whether the file changed; the rest of it is kept byte for byte.
+pub fn write_stats_block(
+    repository: &FileSystemRepository,
+    path: &Path,
+    block: &str,
+) -> Result<bool> {
+    repository.ensure_writable("update", path)?;
+    let current = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => {
+            return Err(DjourError::Config(format!(
+                "Cannot read {}: {}",
+                path.display(),
+                e
+            )))
+        }
+    };
+
+    let updated =
+        splice_between_markers(&current, STATS_START, STATS_END, block).map_err(|problem| {
+            DjourError::Config(format!(
+                "Cannot update the stats in {}: {}",
+                path.display(),
+                problem
+            ))
+        })?;
+    if updated == current {
+        return Ok(false);
+    }
+    fs::write(path, updated).map_err(|e| DjourError::write_failed(e, "update", path))?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_current_streak() {
+        let days = [
+            date(2025, 1, 10),
+            date(2025, 1, 13),
+            date(2025, 1, 14),
+            date(2025, 1, 15),
+        ];
+        let streak = |today| current_streak(JournalMode::Daily, WeekStart::Monday, days, today);
+        assert_eq!(streak(date(2025, 1, 15)), 3);
+        // Today's note is not written yet
+        assert_eq!(streak(date(2025, 1, 16)), 3);
+        assert_eq!(streak(date(2025, 1, 17)), 0);
+
+        let weeks = [date(2024, 12, 30), date(2025, 1, 6), date(2025, 1, 13)];
+        assert_eq!(
+            current_streak(
+                JournalMode::Weekly,
+                WeekStart::Monday,
+                weeks,
+                date(2025, 1, 19)
+            ),
+            3
+        );
+
+        let months = [date(2024, 12, 1), date(2025, 1, 1)];
+        assert_eq!(
+            current_streak(
+                JournalMode::Monthly,
+                WeekStart::Monday,
+                months,
+                date(2025, 2, 3)
+            ),
+            2
+        );
+    }
+}
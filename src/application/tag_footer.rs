@@ -0,0 +1,79 @@
+//! Keeping tag summary footers current on notes djour writes
+
+use crate::domain::tags::{has_tag_footer, tags_in_note, with_tag_footer};
+use crate::infrastructure::Config;
+
+/// Distinct tags of a note in document order (front matter first), lowercased,
+/// as [`tags_in_note`] finds them
+fn tags_in_order(content: &str, config: &Config) -> Vec<String> {
+    let tags = tags_in_note(content, config.tag_parse_options());
+
+    let mut distinct: Vec<String> = Vec::new();
+    for tag in tags {
+        if !distinct.contains(&tag) {
+            distinct.push(tag);
+        }
+    }
+    distinct
+}
+
+/// Post-write hook: the content to write for a note djour created or changed.
+///
+/// Adds the footer when `auto_tag_footer` is on and refreshes one that is
+/// already there either way, so it never goes stale. Callers only pass
+/// content they are writing anyway; untouched notes never get a footer.
+pub(crate) fn refresh_tag_footer(config: &Config, content: String) -> String {
+    if !config.auto_tag_footer && !has_tag_footer(&content) {
+        return content;
+    }
+    let tags = tags_in_order(&content, config);
+    with_tag_footer(&content, &tags)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::JournalMode;
+
+    #[test]
+    fn test_footer_lists_distinct_tags_in_order() {
+        let mut config = Config::new(JournalMode::Daily);
+        let note = "---\ntags: [home]\n---\n# Day\n\n#Work call, #health walk, #work again\n";
+        assert_eq!(refresh_tag_footer(&config, note.to_string()), note);
+
+        config.auto_tag_footer = true;
+        let refreshed = refresh_tag_footer(&config, note.to_string());
+        assert!(
+            refreshed.ends_with("Tags in this note: #home #work #health <!-- /djour:tags -->\n")
+        );
+        assert_eq!(refresh_tag_footer(&config, refreshed.clone()), refreshed);
+    }
+
+    #[test]
+    fn test_footer_leaves_out_tags_in_code() {
+        let mut config = Config::new(JournalMode::Daily);
+        config.auto_tag_footer = true;
+        let note = "Fixed #bug today\n\n```sh\ngrep '#todo' notes\n```\n\nSee `#draft`.\n";
+        assert!(refresh_tag_footer(&config, note.to_string())
+            .ends_with("Tags in this note: #bug <!-- /djour:tags -->\n"));
+    }
+
+    #[test]
+    fn test_footer_keeps_tags_after_a_comment_opener_in_code() {
+        let mut config = Config::new(JournalMode::Daily);
+        config.auto_tag_footer = true;
+        let note = "Use `<!--` to open a comment #work\n\n## Later #home\n";
+        assert!(refresh_tag_footer(&config, note.to_string())
+            .ends_with("Tags in this note: #work #home <!-- /djour:tags -->\n"));
+    }
+
+    #[test]
+    fn test_existing_footer_refreshed_when_disabled() {
+        let config = Config::new(JournalMode::Daily);
+        let note = "Now #b\n\n<!-- djour:tags -->\nTags in this note: #a <!-- /djour:tags -->\n";
+        assert_eq!(
+            refresh_tag_footer(&config, note.to_string()),
+            "Now #b\n\n<!-- djour:tags -->\nTags in this note: #b <!-- /djour:tags -->\n"
+        );
+    }
+}
@@ -0,0 +1,182 @@
+//! Template checks (`djour template check`, warnings for new notes)
+
+use crate::domain::{load_template_for_week, Diagnostics, Template, TemplateIssue, TEMPLATE_NAMES};
+use crate::error::{DjourError, Result};
+use crate::infrastructure::state::fnv1a;
+use crate::infrastructure::{Config, FileSystemRepository, JournalRepository};
+use std::fs;
+use std::io::Write as _;
+
+/// Custom templates, relative to the journal root
+pub const TEMPLATES_DIR: &str = ".djour/templates";
+
+/// Cache of templates whose issues were already reported when a note was created
+pub const TEMPLATE_WARNINGS_FILE: &str = "template-warnings";
+
+/// Lint result of one template
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateCheck {
+    /// File name, e.g. `daily.md`
+    pub name: String,
+    /// Whether the template is a file in [`TEMPLATES_DIR`] rather than built in
+    pub custom: bool,
+    pub issues: Vec<TemplateIssue>,
+}
+
+/// Lint the template called `name` (`daily` or `daily.md`) as djour would
+/// use it, or every custom template of the journal when `name` is `None`.
+///
+/// Files in [`TEMPLATES_DIR`] that djour never uses are not checked.
+pub fn check_templates(
+    repository: &FileSystemRepository,
+    config: &Config,
+    name: Option<&str>,
+) -> Result<Vec<TemplateCheck>> {
+    let templates_dir = repository.root().join(TEMPLATES_DIR);
+    let names: Vec<String> = match name {
+        Some(name) => {
+            let name = if name.ends_with(".md") {
+                name.to_string()
+            } else {
+                format!("{}.md", name)
+            };
+            if !TEMPLATE_NAMES.contains(&name.as_str()) {
+                return Err(DjourError::Template(format!(
+                    "Unknown template: {}. Valid templates are: {}",
+                    name,
+                    TEMPLATE_NAMES.join(", ")
+                )));
+            }
+            vec![name]
+        }
+        None => TEMPLATE_NAMES
+            .iter()
+            .filter(|name| templates_dir.join(name).is_file())
+            .map(|name| name.to_string())
+            .collect(),
+    };
+
+    names
+        .into_iter()
+        .map(|name| {
+            let template = load_template_for_week(repository.root(), &name, config.week_start)?;
+            Ok(TemplateCheck {
+                custom: templates_dir.join(&name).is_file(),
+                issues: template.lint(),
+                name,
+            })
+        })
+        .collect()
+}
+
+/// Warnings for the issues of the custom templates among `checks`
+pub fn template_warnings(checks: &[TemplateCheck]) -> Diagnostics {
+    let mut diagnostics = Diagnostics::new();
+    for check in checks.iter().filter(|check| check.custom) {
+        warn_template_issues(&mut diagnostics, &check.name, &check.issues);
+    }
+    diagnostics
+}
+
+/// Add one warning per issue of the template file `name`
+pub(crate) fn warn_template_issues(
+    diagnostics: &mut Diagnostics,
+    name: &str,
+    issues: &[TemplateIssue],
+) {
+    for issue in issues {
+        diagnostics.warn(format!("{}/{}", TEMPLATES_DIR, name), issue.to_string());
+    }
+}
+
+/// Issues of `template` not yet reported for its current content.
+///
+/// They are recorded in the state cache as reported, so a note created from
+/// the same template later does not repeat them; editing the template makes
+/// its issues new again. Cache failures only mean reporting them again.
+pub(crate) fn unreported_template_issues(
+    repository: &FileSystemRepository,
+    config: &Config,
+    template: &Template,
+) -> Vec<TemplateIssue> {
+    let issues = template.lint();
+    if issues.is_empty() {
+        return issues;
+    }
+    let Ok(paths) = repository.state_paths(config) else {
+        return issues;
+    };
+    let cache_file = paths.cache_dir().join(TEMPLATE_WARNINGS_FILE);
+    let key = format!(
+        "{} {:016x}",
+        template.name(),
+        fnv1a(template.content().as_bytes())
+    );
+    let reported = fs::read_to_string(&cache_file).unwrap_or_default();
+    if reported.lines().any(|line| line == key) {
+        return Vec::new();
+    }
+
+    if !repository.is_read_only() {
+        let _ = fs::create_dir_all(paths.cache_dir());
+        if let Ok(mut file) = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&cache_file)
+        {
+            let _ = writeln!(file, "{}", key);
+        }
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::JournalMode;
+    use tempfile::TempDir;
+
+    fn journal_with_template(name: &str, content: &str) -> (TempDir, FileSystemRepository) {
+        let temp = TempDir::new().unwrap();
+        let templates = temp.path().join(TEMPLATES_DIR);
+        fs::create_dir_all(&templates).unwrap();
+        fs::write(templates.join(name), content).unwrap();
+        let repo = FileSystemRepository::new(temp.path().to_path_buf());
+        (temp, repo)
+    }
+
+    #[test]
+    fn test_check_templates_covers_custom_ones_or_the_named_one() {
+        let (_temp, repo) = journal_with_template("daily.md", "# {WEEKDAY}\n");
+        let config = Config::new(JournalMode::Daily);
+
+        let all = check_templates(&repo, &config, None).unwrap();
+        assert_eq!(all.len(), 1);
+        assert!(all[0].custom);
+        assert_eq!(all[0].issues.len(), 1);
+
+        let weekly = check_templates(&repo, &config, Some("weekly")).unwrap();
+        assert_eq!(weekly[0].name, "weekly.md");
+        assert!(!weekly[0].custom);
+        assert!(weekly[0].issues.is_empty());
+
+        assert!(check_templates(&repo, &config, Some("yearly")).is_err());
+    }
+
+    #[test]
+    fn test_issues_are_reported_once_per_template_content() {
+        let (temp, repo) = journal_with_template("daily.md", "# {WEEKDAY}\n");
+        let config = Config::new(JournalMode::Daily);
+        let path = temp.path().join(TEMPLATES_DIR).join("daily.md");
+        let unreported = || {
+            let template = Template::from_file(&path).unwrap();
+            unreported_template_issues(&repo, &config, &template).len()
+        };
+
+        assert_eq!(unreported(), 1);
+        assert_eq!(unreported(), 0);
+        fs::write(&path, "# {WEEKDAY} {DATE\n").unwrap();
+        assert_eq!(unreported(), 2);
+        assert_eq!(unreported(), 0);
+    }
+}
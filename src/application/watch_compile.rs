@@ -0,0 +1,139 @@
+//! Compile watch use case
+//!
+//! Recompiles a tag query whenever a matching note changes on disk.
+
+use crate::application::compile_tags::{
+    compilation_output_path, compile_tags, CompileOptions, CompileReport, COMPILATIONS_DIR,
+};
+use crate::domain::{is_archived_path, Diagnostics};
+use crate::error::{DjourError, Result};
+use crate::infrastructure::repository::JournalRepository;
+use crate::infrastructure::FileSystemRepository;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::Duration;
+
+/// Quiet period after the last relevant change before recompiling
+pub const WATCH_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Watch the journal tree and recompile after each burst of note changes.
+///
+/// The initial compilation is left to the caller. `on_cycle` receives the result
+/// and diagnostics of every recompilation; errors (for example, no content matching
+/// any more) are reported there instead of stopping the watch. Runs until the
+/// process exits.
+///
+/// # Errors
+///
+/// Returns an error if the file watcher cannot be started.
+pub fn watch_compilation<F>(
+    repository: &FileSystemRepository,
+    options: &CompileOptions,
+    mut on_cycle: F,
+) -> Result<()>
+where
+    F: FnMut(Result<CompileReport>, &Diagnostics),
+{
+    let filter = WatchFilter::new(repository, options);
+
+    let (tx, rx) = mpsc::channel::<notify::Result<Event>>();
+    let mut watcher: RecommendedWatcher = notify::recommended_watcher(tx).map_err(watch_error)?;
+    watcher
+        .watch(repository.root(), RecursiveMode::Recursive)
+        .map_err(watch_error)?;
+
+    loop {
+        // Block until something relevant changes.
+        let Ok(event) = rx.recv() else {
+            return Ok(());
+        };
+        if !filter.is_relevant(&event) {
+            continue;
+        }
+
+        // Debounce: keep draining until the tree has been quiet for a while.
+        loop {
+            match rx.recv_timeout(WATCH_DEBOUNCE) {
+                Ok(_) => continue,
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => return Ok(()),
+            }
+        }
+
+        let mut diagnostics = Diagnostics::new();
+        let result = compile_tags(repository, options, &mut diagnostics, None);
+        on_cycle(result, &diagnostics);
+    }
+}
+
+/// Decides which file system events should trigger a recompilation
+struct WatchFilter<'a> {
+    repository: &'a FileSystemRepository,
+    roots: Vec<PathBuf>,
+    output_path: PathBuf,
+    recursive: bool,
+    include_archived: bool,
+}
+
+impl<'a> WatchFilter<'a> {
+    fn new(repository: &'a FileSystemRepository, options: &CompileOptions) -> Self {
+        // Watchers may report canonical paths, so accept both spellings of the root.
+        let mut roots = vec![repository.root().to_path_buf()];
+        if let Ok(canonical) = repository.root().canonicalize() {
+            if canonical != roots[0] {
+                roots.push(canonical);
+            }
+        }
+
+        WatchFilter {
+            repository,
+            roots,
+            output_path: compilation_output_path(repository, options),
+            recursive: options.recursive,
+            include_archived: options.include_archived,
+        }
+    }
+
+    fn is_relevant(&self, event: &notify::Result<Event>) -> bool {
+        let Ok(event) = event else {
+            return false;
+        };
+        if matches!(event.kind, EventKind::Access(_)) {
+            return false;
+        }
+        event.paths.iter().any(|path| self.is_note_change(path))
+    }
+
+    fn is_note_change(&self, path: &Path) -> bool {
+        if path == self.output_path {
+            return false;
+        }
+        let Some(rel) = self
+            .roots
+            .iter()
+            .find_map(|root| path.strip_prefix(root).ok())
+        else {
+            return false;
+        };
+        if rel.starts_with(".djour") || rel.starts_with(COMPILATIONS_DIR) {
+            return false;
+        }
+        if !self.include_archived && is_archived_path(rel) {
+            return false;
+        }
+        // Re-read the mode each time so a `djour mode` switch mid-watch is honored.
+        let Ok(config) = self.repository.load_config() else {
+            return false;
+        };
+        self.repository
+            .is_note_path(config.naming(), rel, self.recursive)
+    }
+}
+
+fn watch_error(err: notify::Error) -> DjourError {
+    DjourError::Io(std::io::Error::other(format!(
+        "Failed to watch journal: {}",
+        err
+    )))
+}
@@ -1,13 +1,14 @@
 //! CLI command definitions
 
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand};
+use std::ffi::OsString;
 use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
 #[command(name = "djour")]
 #[command(about = "Terminal journal/notes application", long_about = None)]
 #[command(version)]
-#[command(args_conflicts_with_subcommands = true)]
+#[command(override_usage = "djour [OPTIONS] [TIME_REF]\n       djour [OPTIONS] <COMMAND>")]
 pub struct Cli {
     /// Time reference (e.g., today, yesterday, last monday, 17-01-2025)
     #[arg(value_name = "TIME_REF")]
@@ -17,10 +18,61 @@ pub struct Cli {
     #[arg(long, requires = "time_ref")]
     pub open: bool,
 
+    /// Create the selected note if it does not exist (without opening it)
+    #[arg(long, requires = "time_ref")]
+    pub create: bool,
+
+    /// Read TIME_REF only as a date or only as a file in the journal root: date, file
+    #[arg(long = "as", value_name = "KIND", requires = "time_ref")]
+    pub interpret_as: Option<String>,
+
+    /// Create far-past/far-future notes without asking for confirmation
+    #[arg(short, long, visible_alias = "force", requires = "time_ref")]
+    pub yes: bool,
+
+    /// Suppress informational output (data output is still printed)
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+
+    /// Disable colors in terminal output (also honored: NO_COLOR)
+    #[arg(long, global = true)]
+    pub no_color: bool,
+
+    /// Refuse every command that would write to the journal (viewing stays available)
+    #[arg(long, global = true)]
+    pub read_only: bool,
+
+    /// Use the registered journal NAME instead of discovering one (also: DJOUR_JOURNAL)
+    #[arg(long, global = true, value_name = "NAME")]
+    pub journal: Option<String>,
+
     #[command(subcommand)]
     pub command: Option<Commands>,
 }
 
+impl Cli {
+    /// Parse arguments and reject a TIME_REF combined with a subcommand.
+    ///
+    /// Clap's `args_conflicts_with_subcommands` would also reject global flags
+    /// such as `--quiet`, so the conflict is checked here instead.
+    pub fn try_parse_validated_from<I, T>(args: I) -> Result<Self, clap::Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        let cli = Self::try_parse_from(args)?;
+        if cli.time_ref.is_some() && cli.command.is_some() {
+            return Err(Self::command().error(
+                clap::error::ErrorKind::ArgumentConflict,
+                "TIME_REF cannot be used with a subcommand",
+            ));
+        }
+        Ok(cli)
+    }
+}
+
+// Parsed once per run, so the size of `Compile` does not matter
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand, Debug)]
 pub enum Commands {
     /// Initialize a new journal
@@ -29,9 +81,30 @@ pub enum Commands {
         #[arg(default_value = ".")]
         path: PathBuf,
 
-        /// Journal mode (daily, weekly, monthly, single)
-        #[arg(short, long, default_value = "daily")]
-        mode: String,
+        /// Journal mode (daily, weekly, monthly, single). Default: detected from
+        /// existing note files, else daily
+        #[arg(short, long)]
+        mode: Option<String>,
+
+        /// Add example notes and a README-NOTES.md walkthrough for new users
+        #[arg(long)]
+        with_examples: bool,
+
+        /// Display name of the journal (shown in compilation headers, {JOURNAL_NAME})
+        #[arg(long)]
+        name: Option<String>,
+
+        /// Default author (shown in compilation headers, {AUTHOR})
+        #[arg(long)]
+        author: Option<String>,
+
+        /// Create the journal even inside another journal's directory
+        #[arg(long)]
+        nested: bool,
+
+        /// Remove the .djour directory of a journal nothing was added to yet
+        #[arg(long, conflicts_with_all = ["mode", "with_examples", "name", "author", "nested"])]
+        undo: bool,
     },
 
     /// View or modify configuration
@@ -45,8 +118,19 @@ pub enum Commands {
         /// List all configuration
         #[arg(short, long)]
         list: bool,
+
+        /// Open config.toml in the editor and validate it after saving
+        #[arg(long, conflicts_with_all = ["key", "value", "list"])]
+        edit: bool,
+
+        /// Rewrite a damaged config.toml, keeping the values that load
+        #[arg(long, conflicts_with_all = ["key", "value", "list", "edit"])]
+        repair: bool,
     },
 
+    /// Write a quick entry in the editor and append it to today's note
+    Capture,
+
     /// Print the journal folder path
     Folder {
         /// Open the journal folder in configured editor
@@ -54,6 +138,14 @@ pub enum Commands {
         open: bool,
     },
 
+    /// Browse notes and tags in a terminal UI
+    #[cfg(feature = "tui")]
+    Browse {
+        /// Search notes recursively (excluding directories that start with '.')
+        #[arg(long)]
+        recursive: bool,
+    },
+
     /// List existing notes
     List {
         /// Start date (inclusive, format: DD-MM-YYYY)
@@ -71,12 +163,61 @@ pub enum Commands {
         /// Search notes recursively (excluding directories that start with '.')
         #[arg(long)]
         recursive: bool,
+
+        /// Also include notes under archive/ and trash/ (implies --recursive)
+        #[arg(long)]
+        include_archived: bool,
+
+        /// Exit with code 3 when no notes are found
+        #[arg(long)]
+        fail_empty: bool,
+
+        /// Fail when a weekly/monthly note does not match the built-in template
+        #[arg(long)]
+        strict: bool,
+
+        /// Only notes modified on or after this day, local time (TIME_REF or DD-MM-YYYY)
+        #[arg(long, value_name = "TIME_REF")]
+        changed_since: Option<String>,
+
+        /// Show each note's last modification time
+        #[arg(long)]
+        show_modified: bool,
+
+        /// Group notes under relative-date headings (Today, Yesterday, This week, ...).
+        /// --limit applies first: the newest N notes are grouped
+        #[arg(long)]
+        group: bool,
+
+        /// Show each note's first heading as its title
+        #[arg(long)]
+        titles: bool,
+
+        /// Order of the listed notes: date (newest first) or title.
+        /// --limit applies first: the newest N notes are sorted
+        #[arg(long, default_value = "date")]
+        sort: String,
     },
 
     /// Compile tagged content
     Compile {
         /// Tag query (e.g., "work", "work AND urgent", "work OR personal")
-        query: String,
+        #[arg(required_unless_present_any = ["rule", "all_rules", "again", "clean", "refresh_all"])]
+        query: Option<String>,
+
+        /// Run the rule NAME from .djour/rules.toml instead of a query
+        #[arg(long, value_name = "NAME", conflicts_with_all = [
+            "query", "output", "format", "group_by", "include_context", "context_depth",
+            "context_style", "open", "watch", "manifest", "stdin", "stdout",
+        ])]
+        rule: Option<String>,
+
+        /// Run every rule from .djour/rules.toml, reading the notes once
+        #[arg(long, conflicts_with_all = [
+            "query", "rule", "output", "format", "group_by", "include_context", "context_depth",
+            "context_style", "open", "watch", "manifest", "stdin", "stdout",
+        ])]
+        all_rules: bool,
 
         /// Output file path (default: .compilations/<tag>.md)
         #[arg(short, long)]
@@ -90,21 +231,155 @@ pub enum Commands {
         #[arg(long)]
         to: Option<String>,
 
-        /// Output format: chronological, grouped
+        /// Only notes modified on or after this day, local time (TIME_REF or DD-MM-YYYY)
+        #[arg(long, value_name = "TIME_REF")]
+        changed_since: Option<String>,
+
+        /// Output format: chronological, grouped, chrono-grouped
         #[arg(long, default_value = "chronological")]
         format: String,
 
+        /// Sub-headers under each date: hour (by leading HH:MM times; chronological format only)
+        #[arg(long, value_name = "UNIT")]
+        group_by: Option<String>,
+
+        /// strftime format of date headers, e.g. "%A, %d %B %Y" (default: compile.date_format)
+        #[arg(long, value_name = "FORMAT")]
+        date_format: Option<String>,
+
         /// Include parent section headings for context
         #[arg(long)]
         include_context: bool,
 
-        /// Open compiled file in configured editor
+        /// Enclosing headings to show: a number (0 = none) or 'all' (implies --include-context)
+        #[arg(long, value_name = "N")]
+        context_depth: Option<String>,
+
+        /// How to render context: headings, breadcrumb
+        #[arg(long, default_value = "headings")]
+        context_style: String,
+
+        /// Open compiled file in the configured viewer (the editor when unset)
         #[arg(long)]
         open: bool,
 
+        /// Open the compilation with this command instead of the configured viewer
+        #[arg(long = "with", value_name = "COMMAND", requires = "open")]
+        with_viewer: Option<String>,
+
         /// Search notes recursively (excluding directories that start with '.')
         #[arg(long)]
         recursive: bool,
+
+        /// Also include notes under archive/ and trash/ (implies --recursive)
+        #[arg(long)]
+        include_archived: bool,
+
+        /// Keep running and recompile whenever a matching note changes
+        #[arg(long)]
+        watch: bool,
+
+        /// Also write <output>.manifest.json listing contributing notes and counts
+        #[arg(long)]
+        manifest: bool,
+
+        /// Fail when a weekly/monthly note does not match the built-in template
+        #[arg(long)]
+        strict: bool,
+
+        /// Write at most N blocks; a footer counts the ones left out
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+        max_blocks: Option<u64>,
+
+        /// Write at most N bytes of block content; a footer counts the blocks left out
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+        max_bytes: Option<u64>,
+
+        /// Which blocks a truncated compilation keeps: newest, oldest
+        #[arg(long, default_value = "newest")]
+        keep: String,
+
+        /// Print up to N tags that often come with the matching blocks to stderr (0 = off)
+        #[arg(long, value_name = "N", default_value_t = 5)]
+        suggest_limit: usize,
+
+        /// Quote linked journal notes (e.g. [design](design/auth.md)) below matching blocks
+        #[arg(long)]
+        embed_links: bool,
+
+        /// Compile one markdown document read from stdin and print it; no journal needed
+        #[arg(long, conflicts_with_all = [
+            "output", "from", "to", "changed_since", "recursive", "include_archived", "open", "watch",
+            "manifest",
+            "strict", "embed_links", "notes", "exclude", "title_filter",
+        ])]
+        stdin: bool,
+
+        /// Date for every block read with --stdin (format: DD-MM-YYYY)
+        #[arg(long, value_name = "DATE", requires = "stdin")]
+        assume_date: Option<String>,
+
+        /// Compile only this note (path relative to the journal root); repeatable
+        #[arg(long = "note", value_name = "PATH")]
+        notes: Vec<String>,
+
+        /// Leave out notes whose root-relative path matches GLOB (e.g. 2025-01-01.md, drafts/**); repeatable
+        #[arg(long, value_name = "GLOB")]
+        exclude: Vec<String>,
+
+        /// Only notes whose first heading contains TEXT (ignoring case)
+        #[arg(long, value_name = "TEXT")]
+        title_filter: Option<String>,
+
+        /// Print the compilation instead of writing it; nothing in the journal changes
+        #[arg(long, conflicts_with_all = ["output", "open", "watch", "manifest", "stdin"])]
+        stdout: bool,
+
+        /// Leave out the "compiled by" line for journals with a name or author
+        #[arg(long)]
+        no_attribution: bool,
+
+        /// Show blocks tagged with the private tag instead of redacting them
+        #[arg(long)]
+        include_private: bool,
+
+        /// Accept a query that only excludes tags (e.g. "NOT meeting")
+        #[arg(long)]
+        allow_exclusion_only: bool,
+
+        /// Run the last compile again, or the one saved with --save-as NAME
+        #[arg(long, value_name = "NAME", num_args = 0..=1, conflicts_with_all = [
+            "query", "rule", "all_rules", "output", "from", "to", "changed_since", "format",
+            "group_by", "date_format", "include_context", "context_depth", "context_style", "recursive",
+            "include_archived",
+            "manifest", "strict", "max_blocks", "max_bytes", "keep", "suggest_limit", "embed_links",
+            "stdin", "notes", "exclude", "title_filter", "stdout", "no_attribution",
+            "include_private", "allow_exclusion_only",
+        ])]
+        again: Option<Option<String>>,
+
+        /// Also keep this compile's options under NAME for --again NAME
+        #[arg(long, value_name = "NAME", conflicts_with_all = ["rule", "all_rules", "stdin", "stdout"])]
+        save_as: Option<String>,
+
+        /// Delete the compilations djour generated in .compilations/ first (files without its marker stay)
+        #[arg(long, conflicts_with_all = ["stdin", "stdout", "refresh_all"])]
+        clean: bool,
+
+        /// Delete without asking for confirmation (with --clean)
+        #[arg(short, long, requires = "clean")]
+        yes: bool,
+
+        /// Regenerate every compilation in .compilations/ from the command recorded in it
+        #[arg(long, conflicts_with_all = [
+            "query", "rule", "all_rules", "output", "from", "to", "changed_since", "format",
+            "group_by", "date_format", "include_context", "context_depth", "context_style", "open",
+            "recursive", "include_archived", "watch", "manifest", "strict", "max_blocks",
+            "max_bytes", "keep", "suggest_limit", "embed_links", "stdin", "notes", "exclude",
+            "title_filter", "stdout", "no_attribution", "include_private", "allow_exclusion_only",
+            "again", "save_as",
+        ])]
+        refresh_all: bool,
     },
 
     /// List all tags used in notes
@@ -120,15 +395,68 @@ pub enum Commands {
         /// Search notes recursively (excluding directories that start with '.')
         #[arg(long)]
         recursive: bool,
+
+        /// Also include notes under archive/ and trash/ (implies --recursive)
+        #[arg(long)]
+        include_archived: bool,
+
+        /// Group spellings that differ only in '-', '_' or case
+        #[arg(long)]
+        normalize: bool,
+
+        /// Leave out notes whose root-relative path matches GLOB; repeatable
+        #[arg(long, value_name = "GLOB")]
+        exclude: Vec<String>,
+
+        /// Output format: text, csv (tag,count,first_date,last_date)
+        #[arg(long, default_value = "text", conflicts_with = "normalize")]
+        format: String,
+    },
+
+    /// List task items ("- [ ] ...") across notes, oldest first
+    Todos {
+        /// Start date filter (inclusive, format: DD-MM-YYYY)
+        #[arg(long)]
+        from: Option<String>,
+
+        /// End date filter (inclusive, format: DD-MM-YYYY)
+        #[arg(long)]
+        to: Option<String>,
+
+        /// Search notes recursively (excluding directories that start with '.')
+        #[arg(long)]
+        recursive: bool,
+
+        /// Only tasks whose tags (including inherited ones) match this tag query
+        #[arg(long)]
+        query: Option<String>,
+
+        /// List checked items instead of open ones
+        #[arg(long, conflicts_with = "all")]
+        done: bool,
+
+        /// List open and checked items with a status column
+        #[arg(long)]
+        all: bool,
+
+        /// Print tasks as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Output format: text, csv (date,file,line,status,text,tags)
+        #[arg(long, default_value = "text", conflicts_with = "json")]
+        format: String,
     },
 
     /// Convert one tag to another across notes
     Retag {
         /// Source tag name (with or without leading #)
-        from_tag: String,
+        #[arg(required_unless_present = "auto_normalize")]
+        from_tag: Option<String>,
 
         /// Destination tag name (with or without leading #)
-        to_tag: String,
+        #[arg(required_unless_present = "auto_normalize")]
+        to_tag: Option<String>,
 
         /// Start date filter (inclusive, format: DD-MM-YYYY)
         #[arg(long)]
@@ -145,6 +473,134 @@ pub enum Commands {
         /// Show planned changes without writing files
         #[arg(long)]
         dry_run: bool,
+
+        /// Also rewrite compiled files in .compilations/
+        #[arg(long)]
+        update_compilations: bool,
+
+        /// Also rewrite saved queries in .djour/config.toml
+        #[arg(long)]
+        update_queries: bool,
+
+        /// Rename minority spellings of each tag to its most frequent spelling
+        #[arg(long, conflicts_with_all = ["from_tag", "to_tag"])]
+        auto_normalize: bool,
+
+        /// Apply --auto-normalize renames without a dry run first
+        #[arg(short, long)]
+        yes: bool,
+
+        /// Skip copying files into .djour/archive/ before rewriting them (no rollback on failure)
+        #[arg(long)]
+        no_backup: bool,
+    },
+
+    /// Rename a note and update the links that point at it
+    Rename {
+        /// Note to rename: a filename relative to the journal root or a time reference
+        old: String,
+
+        /// New filename or time reference
+        new: String,
+
+        /// Show planned changes without writing files
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Move old notes from the journal root into archive/<YYYY>/
+    Archive {
+        /// Archive notes dated strictly before this date (format: DD-MM-YYYY)
+        #[arg(long)]
+        before: String,
+
+        /// Show the archive plan without moving files
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Pin a note so `list` always shows it first; without NOTE, list the pinned notes
+    Pin {
+        /// Note path relative to the journal root, or a TIME_REF
+        note: Option<String>,
+    },
+
+    /// Unpin a note pinned with `djour pin`
+    Unpin {
+        /// Note path relative to the journal root, or a TIME_REF
+        note: String,
+    },
+
+    /// Report problems in the journal, such as temp files left by interrupted writes
+    Doctor,
+
+    /// Delete temp files left in the journal by interrupted writes
+    Clean {
+        /// List the files without deleting them
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Show note count, current streak and most used tags
+    Stats {
+        /// Print the stats as markdown badges for a README
+        #[arg(long, conflicts_with = "write_badge")]
+        badge_markdown: bool,
+
+        /// Put the badges between <!-- djour-stats:start --> and <!-- djour-stats:end -->
+        /// in FILE, adding the markers at its end when absent
+        #[arg(long, value_name = "FILE")]
+        write_badge: Option<PathBuf>,
+    },
+
+    /// Show the effective root, mode and editor and where each came from
+    Env {
+        /// Print the settings as JSON (for bug reports)
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Print the djour version
+    Version {
+        /// Ask the release endpoint (update_url) whether a newer version exists
+        #[arg(long)]
+        check: bool,
+    },
+
+    /// Manage named journals usable from anywhere with --journal
+    Journal {
+        #[command(subcommand)]
+        action: JournalCommand,
+    },
+
+    /// Check the recurring entries added to new notes (.djour/recurring.md)
+    Recurring {
+        #[command(subcommand)]
+        action: RecurringCommand,
+    },
+
+    /// Check custom note and compilation templates (.djour/templates/)
+    Template {
+        #[command(subcommand)]
+        action: TemplateCommand,
+    },
+
+    /// List "#remind" items due today or earlier (exit code 5 when any is overdue)
+    Remind {
+        /// Search notes recursively (excluding directories that start with '.')
+        #[arg(long)]
+        recursive: bool,
+
+        /// Also show each reminder as a desktop notification (needs the
+        /// "notifications" build feature)
+        #[arg(long)]
+        notify: bool,
+    },
+
+    /// Inspect notes without creating or opening them
+    Note {
+        #[command(subcommand)]
+        action: NoteCommand,
     },
 
     /// Change journal mode and migrate existing notes (daily <-> weekly)
@@ -164,43 +620,149 @@ pub enum Commands {
         /// Archive directory (relative to journal root)
         #[arg(long)]
         archive_dir: Option<PathBuf>,
+
+        /// Nest daily headings under the weekday headings (to weekly), or move
+        /// sub-headings (### and deeper) up one level (to daily)
+        #[arg(long)]
+        promote_headings: bool,
+
+        /// Print the content each planned file would get, as a diff of its current content
+        #[arg(long)]
+        show_diff: bool,
+
+        /// Lines of diff shown per file with --show-diff
+        #[arg(long, value_name = "N", default_value_t = 200, requires = "show_diff")]
+        diff_lines: usize,
+
+        /// Set the mode even though no notes need migrating and files in the
+        /// target mode's format already exist
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum NoteCommand {
+    /// Print the absolute path of the note for TIME_REF (exit code 4 if it does not exist)
+    Path {
+        /// Time reference (e.g., today, yesterday, last monday, 17-01-2025)
+        #[arg(value_name = "TIME_REF")]
+        time_ref: String,
+    },
+
+    /// Exit with code 0 if the note for TIME_REF exists, 4 otherwise
+    Exists {
+        /// Time reference (e.g., today, yesterday, last monday, 17-01-2025)
+        #[arg(value_name = "TIME_REF")]
+        time_ref: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum RecurringCommand {
+    /// Validate .djour/recurring.md and print each rule with the next day it matches
+    List,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TemplateCommand {
+    /// Report unknown placeholders, unbalanced braces and missing weekly day headings
+    Check {
+        /// Template to check, e.g. daily or weekly.md (default: every custom template)
+        name: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum JournalCommand {
+    /// Register the journal at PATH under NAME
+    Add {
+        /// Name to use with --journal
+        name: String,
+
+        /// Journal root (a directory containing .djour)
+        path: PathBuf,
+
+        /// Point an already registered name at PATH
+        #[arg(long)]
+        replace: bool,
+    },
+
+    /// List registered journals; `*` marks the one commands would use here
+    List,
+
+    /// Unregister a journal (its files are left untouched)
+    Remove {
+        /// Registered journal name
+        name: String,
     },
 }
 
 #[cfg(test)]
 mod tests {
     use super::Cli;
-    use clap::Parser;
 
     #[test]
     fn parses_time_ref_without_open_flag() {
-        let cli = Cli::try_parse_from(["djour", "today"]).unwrap();
+        let cli = Cli::try_parse_validated_from(["djour", "today"]).unwrap();
         assert_eq!(cli.time_ref.as_deref(), Some("today"));
         assert!(!cli.open);
     }
 
     #[test]
     fn parses_open_flag_with_time_ref() {
-        let cli = Cli::try_parse_from(["djour", "--open", "today"]).unwrap();
+        let cli = Cli::try_parse_validated_from(["djour", "--open", "today"]).unwrap();
         assert_eq!(cli.time_ref.as_deref(), Some("today"));
         assert!(cli.open);
     }
 
+    #[test]
+    fn parses_global_journal_flag_after_subcommand() {
+        let cli = Cli::try_parse_validated_from(["djour", "list", "--journal", "work"]).unwrap();
+        assert_eq!(cli.journal.as_deref(), Some("work"));
+        let cli = Cli::try_parse_validated_from(["djour", "--journal", "work", "today"]).unwrap();
+        assert_eq!(cli.journal.as_deref(), Some("work"));
+    }
+
     #[test]
     fn rejects_open_without_time_ref() {
-        let result = Cli::try_parse_from(["djour", "--open"]);
+        let result = Cli::try_parse_validated_from(["djour", "--open"]);
         assert!(result.is_err());
     }
 
+    #[test]
+    fn parses_yes_flag_and_force_alias() {
+        let cli = Cli::try_parse_validated_from(["djour", "--yes", "17-01-2052"]).unwrap();
+        assert!(cli.yes);
+        let cli = Cli::try_parse_validated_from(["djour", "17-01-2052", "--force"]).unwrap();
+        assert!(cli.yes);
+    }
+
+    #[test]
+    fn rejects_time_ref_with_subcommand() {
+        let result = Cli::try_parse_validated_from(["djour", "today", "list"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn parses_global_quiet_flag_before_and_after_subcommand() {
+        let before = Cli::try_parse_validated_from(["djour", "--quiet", "list"]).unwrap();
+        assert!(before.quiet);
+        assert!(matches!(before.command, Some(super::Commands::List { .. })));
+
+        let after = Cli::try_parse_validated_from(["djour", "list", "-q"]).unwrap();
+        assert!(after.quiet);
+    }
+
     #[test]
     fn rejects_open_with_subcommand() {
-        let result = Cli::try_parse_from(["djour", "list", "--open"]);
+        let result = Cli::try_parse_validated_from(["djour", "list", "--open"]);
         assert!(result.is_err());
     }
 
     #[test]
     fn parses_compile_open_flag() {
-        let cli = Cli::try_parse_from(["djour", "compile", "work", "--open"]).unwrap();
+        let cli = Cli::try_parse_validated_from(["djour", "compile", "work", "--open"]).unwrap();
         match cli.command {
             Some(super::Commands::Compile {
                 open, recursive, ..
@@ -214,25 +776,114 @@ mod tests {
 
     #[test]
     fn parses_compile_recursive_flag() {
-        let cli = Cli::try_parse_from(["djour", "compile", "work", "--recursive"]).unwrap();
+        let cli =
+            Cli::try_parse_validated_from(["djour", "compile", "work", "--recursive"]).unwrap();
         match cli.command {
             Some(super::Commands::Compile { recursive, .. }) => assert!(recursive),
             _ => panic!("Expected compile command"),
         }
     }
 
+    #[test]
+    fn parses_compile_watch_flag() {
+        let cli = Cli::try_parse_validated_from(["djour", "compile", "work", "--watch"]).unwrap();
+        match cli.command {
+            Some(super::Commands::Compile { watch, .. }) => assert!(watch),
+            _ => panic!("Expected compile command"),
+        }
+    }
+
+    #[test]
+    fn parses_compile_rules_without_query() {
+        let cli = Cli::try_parse_validated_from(["djour", "compile", "--all-rules"]).unwrap();
+        match cli.command {
+            Some(super::Commands::Compile {
+                query, all_rules, ..
+            }) => {
+                assert_eq!(query, None);
+                assert!(all_rules);
+            }
+            _ => panic!("Expected compile command"),
+        }
+        assert!(Cli::try_parse_validated_from(["djour", "compile"]).is_err());
+        assert!(
+            Cli::try_parse_validated_from(["djour", "compile", "work", "--rule", "a"]).is_err()
+        );
+    }
+
+    #[test]
+    fn parses_compile_again_with_optional_slot() {
+        let again = |args: &[&str]| {
+            let cli = Cli::try_parse_validated_from(args.iter().copied()).unwrap();
+            match cli.command {
+                Some(super::Commands::Compile { query, again, .. }) => {
+                    assert_eq!(query, None);
+                    again
+                }
+                _ => panic!("Expected compile command"),
+            }
+        };
+        assert_eq!(again(&["djour", "compile", "--again"]), Some(None));
+        assert_eq!(
+            again(&["djour", "compile", "--again", "weekly", "--open"]),
+            Some(Some("weekly".to_string()))
+        );
+        assert!(Cli::try_parse_validated_from([
+            "djour",
+            "compile",
+            "--again",
+            "--from",
+            "01-01-2025"
+        ])
+        .is_err());
+        assert!(
+            Cli::try_parse_validated_from(["djour", "compile", "--again", "--stdout"]).is_err()
+        );
+    }
+
+    #[test]
+    fn parses_compile_clean_and_refresh_all() {
+        let parse = |args: &[&str]| Cli::try_parse_validated_from(args.iter().copied());
+        match parse(&["djour", "compile", "--clean", "-y"])
+            .unwrap()
+            .command
+        {
+            Some(super::Commands::Compile {
+                query, clean, yes, ..
+            }) => {
+                assert_eq!(query, None);
+                assert!(clean && yes);
+            }
+            _ => panic!("Expected compile command"),
+        }
+        assert!(parse(&["djour", "compile", "work", "--clean"]).is_ok());
+        assert!(parse(&["djour", "compile", "--refresh-all"]).is_ok());
+        assert!(parse(&["djour", "compile", "work", "--yes"]).is_err());
+        assert!(parse(&["djour", "compile", "work", "--refresh-all"]).is_err());
+        assert!(parse(&["djour", "compile", "--refresh-all", "--clean"]).is_err());
+        assert!(parse(&["djour", "compile", "work", "--clean", "--stdout"]).is_err());
+    }
+
     #[test]
     fn parses_tags_command() {
-        let cli = Cli::try_parse_from(["djour", "tags"]).unwrap();
+        let cli = Cli::try_parse_validated_from(["djour", "tags"]).unwrap();
         match cli.command {
             Some(super::Commands::Tags {
                 from,
                 to,
                 recursive,
+                include_archived,
+                normalize,
+                exclude,
+                format,
             }) => {
                 assert!(from.is_none());
                 assert!(to.is_none());
                 assert!(!recursive);
+                assert!(!include_archived);
+                assert!(!normalize);
+                assert!(exclude.is_empty());
+                assert_eq!(format, "text");
             }
             _ => panic!("Expected tags command"),
         }
@@ -240,7 +891,7 @@ mod tests {
 
     #[test]
     fn parses_tags_command_with_date_filters() {
-        let cli = Cli::try_parse_from([
+        let cli = Cli::try_parse_validated_from([
             "djour",
             "tags",
             "--from",
@@ -255,6 +906,7 @@ mod tests {
                 from,
                 to,
                 recursive,
+                ..
             }) => {
                 assert_eq!(from.as_deref(), Some("01-01-2025"));
                 assert_eq!(to.as_deref(), Some("31-01-2025"));
@@ -266,7 +918,7 @@ mod tests {
 
     #[test]
     fn parses_tags_recursive_flag() {
-        let cli = Cli::try_parse_from(["djour", "tags", "--recursive"]).unwrap();
+        let cli = Cli::try_parse_validated_from(["djour", "tags", "--recursive"]).unwrap();
         match cli.command {
             Some(super::Commands::Tags { recursive, .. }) => {
                 assert!(recursive);
@@ -275,9 +927,43 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parses_repeated_exclude_flags() {
+        let cli = Cli::try_parse_validated_from([
+            "djour",
+            "tags",
+            "--exclude",
+            "2025-01-01.md",
+            "--exclude",
+            "drafts/**",
+        ])
+        .unwrap();
+        match cli.command {
+            Some(super::Commands::Tags { exclude, .. }) => {
+                assert_eq!(exclude, vec!["2025-01-01.md", "drafts/**"]);
+            }
+            _ => panic!("Expected tags command"),
+        }
+        assert!(Cli::try_parse_validated_from([
+            "djour",
+            "compile",
+            "work",
+            "--stdin",
+            "--exclude",
+            "x.md"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn parses_capture_command() {
+        let cli = Cli::try_parse_validated_from(["djour", "capture"]).unwrap();
+        assert!(matches!(cli.command, Some(super::Commands::Capture)));
+    }
+
     #[test]
     fn parses_folder_command() {
-        let cli = Cli::try_parse_from(["djour", "folder"]).unwrap();
+        let cli = Cli::try_parse_validated_from(["djour", "folder"]).unwrap();
         match cli.command {
             Some(super::Commands::Folder { open }) => {
                 assert!(!open);
@@ -288,7 +974,7 @@ mod tests {
 
     #[test]
     fn parses_folder_command_with_open_flag() {
-        let cli = Cli::try_parse_from(["djour", "folder", "--open"]).unwrap();
+        let cli = Cli::try_parse_validated_from(["djour", "folder", "--open"]).unwrap();
         match cli.command {
             Some(super::Commands::Folder { open }) => {
                 assert!(open);
@@ -299,7 +985,7 @@ mod tests {
 
     #[test]
     fn parses_list_recursive_flag() {
-        let cli = Cli::try_parse_from(["djour", "list", "--recursive"]).unwrap();
+        let cli = Cli::try_parse_validated_from(["djour", "list", "--recursive"]).unwrap();
         match cli.command {
             Some(super::Commands::List { recursive, .. }) => {
                 assert!(recursive);
@@ -308,9 +994,29 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parses_rename_command() {
+        let cli = Cli::try_parse_validated_from([
+            "djour",
+            "rename",
+            "18-01-2025",
+            "17-01-2025",
+            "--dry-run",
+        ])
+        .unwrap();
+        match cli.command {
+            Some(super::Commands::Rename { old, new, dry_run }) => {
+                assert_eq!(old, "18-01-2025");
+                assert_eq!(new, "17-01-2025");
+                assert!(dry_run);
+            }
+            _ => panic!("Expected rename command"),
+        }
+    }
+
     #[test]
     fn parses_retag_command_defaults() {
-        let cli = Cli::try_parse_from(["djour", "retag", "work", "focus"]).unwrap();
+        let cli = Cli::try_parse_validated_from(["djour", "retag", "work", "focus"]).unwrap();
         match cli.command {
             Some(super::Commands::Retag {
                 from_tag,
@@ -319,21 +1025,56 @@ mod tests {
                 to,
                 recursive,
                 dry_run,
+                update_compilations,
+                update_queries,
+                auto_normalize,
+                yes,
+                no_backup,
             }) => {
-                assert_eq!(from_tag, "work");
-                assert_eq!(to_tag, "focus");
+                assert_eq!(from_tag.as_deref(), Some("work"));
+                assert!(!no_backup);
+                assert_eq!(to_tag.as_deref(), Some("focus"));
+                assert!(!auto_normalize);
+                assert!(!yes);
                 assert!(from.is_none());
                 assert!(to.is_none());
                 assert!(!recursive);
                 assert!(!dry_run);
+                assert!(!update_compilations);
+                assert!(!update_queries);
             }
             _ => panic!("Expected retag command"),
         }
     }
 
+    #[test]
+    fn parses_retag_auto_normalize_without_tags() {
+        let cli =
+            Cli::try_parse_validated_from(["djour", "retag", "--auto-normalize", "--yes"]).unwrap();
+        match cli.command {
+            Some(super::Commands::Retag {
+                from_tag,
+                auto_normalize,
+                yes,
+                ..
+            }) => {
+                assert!(from_tag.is_none());
+                assert!(auto_normalize);
+                assert!(yes);
+            }
+            _ => panic!("Expected retag command"),
+        }
+
+        assert!(Cli::try_parse_validated_from(["djour", "retag", "work"]).is_err());
+        assert!(
+            Cli::try_parse_validated_from(["djour", "retag", "a", "b", "--auto-normalize"])
+                .is_err()
+        );
+    }
+
     #[test]
     fn parses_retag_command_with_options() {
-        let cli = Cli::try_parse_from([
+        let cli = Cli::try_parse_validated_from([
             "djour",
             "retag",
             "#work",
@@ -344,6 +1085,8 @@ mod tests {
             "31-01-2025",
             "--recursive",
             "--dry-run",
+            "--update-compilations",
+            "--update-queries",
         ])
         .unwrap();
 
@@ -355,13 +1098,18 @@ mod tests {
                 to,
                 recursive,
                 dry_run,
+                update_compilations,
+                update_queries,
+                ..
             }) => {
-                assert_eq!(from_tag, "#work");
-                assert_eq!(to_tag, "project");
+                assert_eq!(from_tag.as_deref(), Some("#work"));
+                assert_eq!(to_tag.as_deref(), Some("project"));
                 assert_eq!(from.as_deref(), Some("01-01-2025"));
                 assert_eq!(to.as_deref(), Some("31-01-2025"));
                 assert!(recursive);
                 assert!(dry_run);
+                assert!(update_compilations);
+                assert!(update_queries);
             }
             _ => panic!("Expected retag command"),
         }
@@ -369,7 +1117,7 @@ mod tests {
 
     #[test]
     fn parses_mode_command_without_confirmation_flag() {
-        let cli = Cli::try_parse_from(["djour", "mode", "weekly"]).unwrap();
+        let cli = Cli::try_parse_validated_from(["djour", "mode", "weekly"]).unwrap();
 
         match cli.command {
             Some(super::Commands::Mode {
@@ -377,11 +1125,19 @@ mod tests {
                 from,
                 dry_run,
                 archive_dir,
+                promote_headings,
+                show_diff,
+                diff_lines,
+                force,
             }) => {
                 assert_eq!(to, "weekly");
                 assert!(from.is_none());
                 assert!(!dry_run);
                 assert!(archive_dir.is_none());
+                assert!(!promote_headings);
+                assert!(!show_diff);
+                assert_eq!(diff_lines, 200);
+                assert!(!force);
             }
             _ => panic!("Expected mode command"),
         }
@@ -389,7 +1145,28 @@ mod tests {
 
     #[test]
     fn rejects_removed_mode_yes_flag() {
-        let result = Cli::try_parse_from(["djour", "mode", "weekly", "--yes"]);
+        let result = Cli::try_parse_validated_from(["djour", "mode", "weekly", "--yes"]);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn parses_note_path_and_exists() {
+        let cli = Cli::try_parse_validated_from(["djour", "note", "path", "last monday"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(super::Commands::Note {
+                action: super::NoteCommand::Path { ref time_ref }
+            }) if time_ref == "last monday"
+        ));
+
+        let cli = Cli::try_parse_validated_from(["djour", "note", "exists", "today"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(super::Commands::Note {
+                action: super::NoteCommand::Exists { .. }
+            })
+        ));
+
+        assert!(Cli::try_parse_validated_from(["djour", "note", "path"]).is_err());
+    }
 }
@@ -0,0 +1,105 @@
+//! Spreadsheet exports (`--format csv`)
+//!
+//! Records follow RFC 4180: fields are separated by `,`, records end with
+//! `\r\n`, and a field is put in double quotes (with `"` doubled) when it
+//! holds a comma, a quote, a line break or leading/trailing spaces.
+
+use std::borrow::Cow;
+
+/// Output formats of commands that can export a table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// The human-readable listing
+    Text,
+    Csv,
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(ExportFormat::Text),
+            "csv" => Ok(ExportFormat::Csv),
+            _ => Err(format!("Invalid format: '{}'. Use 'text' or 'csv'", s)),
+        }
+    }
+}
+
+/// A CSV table: the header row, then one record per [`CsvTable::row`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvTable {
+    output: String,
+}
+
+impl CsvTable {
+    pub fn new(header: &[&str]) -> Self {
+        let mut table = CsvTable {
+            output: String::new(),
+        };
+        table.row(header);
+        table
+    }
+
+    pub fn row<S: AsRef<str>>(&mut self, fields: &[S]) {
+        for (i, field) in fields.iter().enumerate() {
+            if i > 0 {
+                self.output.push(',');
+            }
+            self.output.push_str(&csv_field(field.as_ref()));
+        }
+        self.output.push_str("\r\n");
+    }
+
+    pub fn finish(self) -> String {
+        self.output
+    }
+}
+
+/// `field` as written in a record, quoted only when it has to be
+fn csv_field(field: &str) -> Cow<'_, str> {
+    let needs_quotes = field.contains([',', '"', '\r', '\n'])
+        || field.starts_with([' ', '\t'])
+        || field.ends_with([' ', '\t']);
+    if needs_quotes {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_fields_are_left_alone() {
+        let mut table = CsvTable::new(&["tag", "count"]);
+        table.row(&["work", "12"]);
+        table.row(&["project/alpha", ""]);
+        assert_eq!(table.finish(), "tag,count\r\nwork,12\r\nproject/alpha,\r\n");
+    }
+
+    #[test]
+    fn test_quoting_edge_cases() {
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("\""), "\"\"\"\"");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field(" padded "), "\" padded \"");
+        assert_eq!(csv_field("trailing\t"), "\"trailing\t\"");
+        assert_eq!(csv_field("inner space"), "inner space");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+        assert_eq!(csv_field("crlf\r\nend"), "\"crlf\r\nend\"");
+        assert_eq!(csv_field(""), "");
+    }
+
+    #[test]
+    fn test_format_parses_text_and_csv() {
+        assert_eq!("CSV".parse::<ExportFormat>(), Ok(ExportFormat::Csv));
+        assert_eq!("text".parse::<ExportFormat>(), Ok(ExportFormat::Text));
+        assert_eq!(
+            "xlsx".parse::<ExportFormat>(),
+            Err("Invalid format: 'xlsx'. Use 'text' or 'csv'".to_string())
+        );
+    }
+}
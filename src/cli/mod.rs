@@ -1,7 +1,21 @@
 //! CLI layer - Command-line interface
 
 pub mod commands;
+pub mod export;
 pub mod output;
+pub mod progress;
+pub mod style;
+#[cfg(feature = "tui")]
+pub mod tui;
 
-pub use commands::{Cli, Commands};
-pub use output::{format_note_list, format_tag_list};
+pub use commands::{Cli, Commands, JournalCommand, NoteCommand, RecurringCommand, TemplateCommand};
+pub use export::{CsvTable, ExportFormat};
+pub use output::{
+    date_bucket, format_env, format_env_json, format_note_list, format_note_list_grouped,
+    format_note_list_with_modified, format_note_table, format_pinned_notes, format_reminder_list,
+    format_stats, format_stats_badges, format_tag_counts, format_tag_csv, format_tag_groups,
+    format_tag_list, format_tag_table, format_todo_csv, format_todo_json, format_todo_list,
+    DateBucket, NoteListContext,
+};
+pub use progress::ProgressLine;
+pub use style::OutputStyle;
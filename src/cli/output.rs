@@ -1,29 +1,415 @@
 //! Output formatting utilities
 
+use super::export::CsvTable;
+use super::style::{display_width, pad_left, pad_right, OutputStyle};
+use crate::application::{EffectiveSettings, JournalStats, PinnedNote, TagCounts};
+use crate::domain::tags::syntax::{split_value_tag, tag_ancestors};
+use crate::domain::tags::TagGroup;
+use crate::domain::{week_start, JournalMode, Locale, ReminderItem, TaskItem, WeekStart};
+use crate::error::{DjourError, Result};
 use crate::infrastructure::NoteEntry;
+use chrono::{Datelike, Duration, NaiveDate};
+use std::collections::BTreeMap;
 
-/// Format a list of note entries for display
+/// Longest tag count bar in rich `tags` output
+const TAG_BAR_WIDTH: usize = 20;
+
+/// What rich note listings need to know about the journal
+#[derive(Debug, Clone, Copy)]
+pub struct NoteListContext {
+    pub mode: JournalMode,
+    pub locale: Locale,
+    pub today: NaiveDate,
+    /// Add a column with each note's last modification time (`--show-modified`)
+    pub show_modified: bool,
+}
+
+/// Width of a formatted modification time (`YYYY-MM-DD HH:MM`)
+const MODIFIED_WIDTH: usize = 16;
+
+/// Format a list of note entries for display.
+///
+/// Notes carrying a title (`list --titles`) get it in a last column.
 pub fn format_note_list(notes: &[NoteEntry]) -> String {
     if notes.is_empty() {
         return "No notes found".to_string();
     }
 
+    let path_width = titled_path_width(notes);
     let mut output = String::new();
     for entry in notes {
-        if let Some(date) = entry.date {
-            output.push_str(&format!(
-                "{}  {}\n",
-                date.format("%d-%m-%Y"),
-                entry.filename
-            ));
-        } else {
+        let path = pad_right(&entry.filename, path_width);
+        let title = entry.title.as_deref().unwrap_or_default();
+        let line = match entry.date {
+            Some(date) => format!("{}  {}  {}", date.format("%d-%m-%Y"), path, title),
             // No date (single mode) - use spacing for alignment
-            output.push_str(&format!("           {}\n", entry.filename));
+            None => format!("           {}  {}", path, title),
+        };
+        output.push_str(line.trim_end());
+        output.push('\n');
+    }
+    output
+}
+
+/// Width of the path column when some note has a title, 0 otherwise
+fn titled_path_width(notes: &[NoteEntry]) -> usize {
+    if notes.iter().all(|entry| entry.title.is_none()) {
+        return 0;
+    }
+    notes
+        .iter()
+        .map(|entry| display_width(&entry.filename))
+        .max()
+        .unwrap_or(0)
+}
+
+/// Last modification time of a note in local time, or `-` when unknown
+fn modified_cell(entry: &NoteEntry) -> String {
+    entry
+        .modified_local()
+        .map(|modified| modified.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+/// Format note entries with their modification times (plain `--show-modified`)
+pub fn format_note_list_with_modified(notes: &[NoteEntry]) -> String {
+    if notes.is_empty() {
+        return "No notes found".to_string();
+    }
+
+    let path_width = titled_path_width(notes);
+    let mut output = String::new();
+    for entry in notes {
+        let date = entry
+            .date
+            .map(|date| date.format("%d-%m-%Y").to_string())
+            .unwrap_or_default();
+        let line = format!(
+            "{}  {}  {}  {}",
+            pad_right(&date, 10),
+            pad_right(&modified_cell(entry), MODIFIED_WIDTH),
+            pad_right(&entry.filename, path_width),
+            entry.title.as_deref().unwrap_or_default()
+        );
+        output.push_str(line.trim_end());
+        output.push('\n');
+    }
+    output
+}
+
+/// Format pinned notes, each marked with `*`: date, path and title, or
+/// `(missing)` (dimmed in rich output) for pins whose file is gone.
+pub fn format_pinned_notes<F>(notes: &[PinnedNote], style: OutputStyle, mut title_for: F) -> String
+where
+    F: FnMut(&NoteEntry) -> Option<String>,
+{
+    let path_width = notes
+        .iter()
+        .map(|pin| display_width(&pin.entry.filename))
+        .max()
+        .unwrap_or(0);
+    let mut output = String::new();
+    for pin in notes {
+        let date = pin
+            .entry
+            .date
+            .map(|date| date.format("%d-%m-%Y").to_string())
+            .unwrap_or_default();
+        let path = pad_right(&pin.entry.filename, path_width);
+        let line = if pin.exists {
+            let title = title_for(&pin.entry).unwrap_or_default();
+            format!("* {}  {}  {}", pad_right(&date, 10), path, title)
+        } else {
+            let line = format!("* {}  {}  (missing)", pad_right(&date, 10), path);
+            style.dim(&line)
+        };
+        output.push_str(line.trim_end());
+        output.push('\n');
+    }
+    output
+}
+
+/// Format notes for a terminal: date, weekday, relative age, path and title columns.
+///
+/// Falls back to [`format_note_list`] unless `style` is rich. `title_for` is
+/// only called for rich output, so plain listings never read note contents.
+pub fn format_note_table<F>(
+    notes: &[NoteEntry],
+    context: &NoteListContext,
+    style: OutputStyle,
+    mut title_for: F,
+) -> String
+where
+    F: FnMut(&NoteEntry) -> Option<String>,
+{
+    if !style.rich || notes.is_empty() {
+        return if context.show_modified {
+            format_note_list_with_modified(notes)
+        } else {
+            format_note_list(notes)
+        };
+    }
+
+    struct Row {
+        date: String,
+        weekday: String,
+        relative: String,
+        modified: String,
+        path: String,
+        title: String,
+        current: bool,
+    }
+
+    let rows: Vec<Row> = notes
+        .iter()
+        .map(|entry| {
+            let title = title_for(entry).unwrap_or_default();
+            match entry.date {
+                Some(date) => Row {
+                    date: date.format("%d-%m-%Y").to_string(),
+                    weekday: context
+                        .locale
+                        .weekday_name(date.weekday())
+                        .chars()
+                        .take(3)
+                        .collect(),
+                    relative: relative_age(date, context),
+                    modified: modified_cell(entry),
+                    path: entry.filename.clone(),
+                    title,
+                    current: is_current_period(date, context),
+                },
+                None => Row {
+                    date: String::new(),
+                    weekday: String::new(),
+                    relative: String::new(),
+                    modified: modified_cell(entry),
+                    path: entry.filename.clone(),
+                    title,
+                    current: false,
+                },
+            }
+        })
+        .collect();
+
+    let width = |cell: fn(&Row) -> &str| rows.iter().map(|r| display_width(cell(r))).max();
+    let date_width = width(|r| &r.date).unwrap_or(0);
+    let weekday_width = width(|r| &r.weekday).unwrap_or(0);
+    let relative_width = width(|r| &r.relative).unwrap_or(0);
+    let path_width = width(|r| &r.path).unwrap_or(0);
+
+    let mut output = String::new();
+    for row in &rows {
+        let date = pad_right(&row.date, date_width);
+        let date = if row.current {
+            style.current(&date)
+        } else {
+            style.accent(&date)
+        };
+        let modified = if context.show_modified {
+            format!("{}  ", style.dim(&pad_right(&row.modified, MODIFIED_WIDTH)))
+        } else {
+            String::new()
+        };
+        let line = format!(
+            "{}  {}  {}  {}{}  {}",
+            date,
+            pad_right(&row.weekday, weekday_width),
+            style.dim(&pad_right(&row.relative, relative_width)),
+            modified,
+            pad_right(&row.path, path_width),
+            row.title
+        );
+        output.push_str(line.trim_end());
+        output.push('\n');
+    }
+    output
+}
+
+/// Whether the period a note covers contains `today`
+fn is_current_period(date: NaiveDate, context: &NoteListContext) -> bool {
+    date <= context.today && context.today <= context.mode.period_end(date)
+}
+
+/// Human-friendly distance from today, e.g. `yesterday` or `3 days ago`
+fn relative_age(date: NaiveDate, context: &NoteListContext) -> String {
+    if is_current_period(date, context) {
+        return match context.mode {
+            JournalMode::Weekly => "this week".to_string(),
+            JournalMode::Monthly => "this month".to_string(),
+            _ => "today".to_string(),
+        };
+    }
+
+    match (context.today - date).num_days() {
+        1 => "yesterday".to_string(),
+        -1 => "tomorrow".to_string(),
+        days if days > 0 => format!("{} days ago", days),
+        days => format!("in {} days", -days),
+    }
+}
+
+/// Age bucket of a note in grouped listings (`list --group`), newest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DateBucket {
+    /// Dated after today
+    Upcoming,
+    Today,
+    Yesterday,
+    ThisWeek,
+    LastWeek,
+    EarlierThisMonth,
+    Older,
+    /// Notes without a date (single mode)
+    Undated,
+}
+
+impl DateBucket {
+    /// Heading printed above the bucket's notes
+    pub fn label(&self) -> &'static str {
+        match self {
+            DateBucket::Upcoming => "Upcoming",
+            DateBucket::Today => "Today",
+            DateBucket::Yesterday => "Yesterday",
+            DateBucket::ThisWeek => "This week",
+            DateBucket::LastWeek => "Last week",
+            DateBucket::EarlierThisMonth => "Earlier this month",
+            DateBucket::Older => "Older",
+            DateBucket::Undated => "Undated",
         }
     }
+}
+
+/// Bucket of a note dated `date`, seen from `today` with weeks starting on `week`'s first day
+pub fn date_bucket(date: NaiveDate, today: NaiveDate, week: WeekStart) -> DateBucket {
+    let this_week = week_start(today, week);
+    if date > today {
+        DateBucket::Upcoming
+    } else if date == today {
+        DateBucket::Today
+    } else if today.pred_opt() == Some(date) {
+        DateBucket::Yesterday
+    } else if date >= this_week {
+        DateBucket::ThisWeek
+    } else if date >= this_week - Duration::days(7) {
+        DateBucket::LastWeek
+    } else if (date.year(), date.month()) == (today.year(), today.month()) {
+        DateBucket::EarlierThisMonth
+    } else {
+        DateBucket::Older
+    }
+}
+
+/// Format notes under relative-date headings (`Today`, `Last week`, ...).
+///
+/// Buckets keep the order of `notes` (newest first) and empty ones are
+/// omitted; `format_bucket` renders each bucket's notes, e.g. [`format_note_list`].
+pub fn format_note_list_grouped<F>(
+    notes: &[NoteEntry],
+    today: NaiveDate,
+    week: WeekStart,
+    mut format_bucket: F,
+) -> String
+where
+    F: FnMut(&[NoteEntry]) -> String,
+{
+    if notes.is_empty() {
+        return "No notes found".to_string();
+    }
+
+    let mut buckets: BTreeMap<DateBucket, Vec<NoteEntry>> = BTreeMap::new();
+    for entry in notes {
+        let bucket = match entry.date {
+            Some(date) => date_bucket(date, today, week),
+            None => DateBucket::Undated,
+        };
+        buckets.entry(bucket).or_default().push(entry.clone());
+    }
+
+    let sections: Vec<String> = buckets
+        .iter()
+        .map(|(bucket, entries)| format!("{}\n{}", bucket.label(), format_bucket(entries)))
+        .collect();
+    sections.join("\n")
+}
+
+/// Format task items, one per line: date, `file:line` and the task text.
+///
+/// `show_status` adds a leading `[ ]`/`[x]` column (`todos --all`).
+pub fn format_todo_list(tasks: &[TaskItem], show_status: bool) -> String {
+    if tasks.is_empty() {
+        return "No tasks found".to_string();
+    }
+
+    let mut output = String::new();
+    for task in tasks {
+        if show_status {
+            output.push_str(if task.checked { "[x] " } else { "[ ] " });
+        }
+        let date = task
+            .date
+            .map(|date| date.format("%d-%m-%Y").to_string())
+            .unwrap_or_default();
+        output.push_str(&format!(
+            "{}  {}:{}  {}\n",
+            pad_right(&date, 10),
+            task.source_file.to_string_lossy().replace('\\', "/"),
+            task.line,
+            task.text
+        ));
+    }
+    output
+}
+
+/// Task items as a pretty-printed JSON array
+pub fn format_todo_json(tasks: &[TaskItem]) -> Result<String> {
+    let mut json = serde_json::to_string_pretty(tasks)
+        .map_err(|e| DjourError::Config(format!("Failed to serialize tasks: {}", e)))?;
+    json.push('\n');
+    Ok(json)
+}
+
+/// Task items as CSV: date, file, line, status, text and tags (space-separated)
+pub fn format_todo_csv(tasks: &[TaskItem]) -> String {
+    let mut table = CsvTable::new(&["date", "file", "line", "status", "text", "tags"]);
+    for task in tasks {
+        table.row(&[
+            task.date.map(|date| date.to_string()).unwrap_or_default(),
+            task.source_file.to_string_lossy().replace('\\', "/"),
+            task.line.to_string(),
+            if task.checked { "done" } else { "open" }.to_string(),
+            task.text.clone(),
+            task.tags.join(" "),
+        ]);
+    }
+    table.finish()
+}
+
+/// Effective settings as `key = value (from source)` lines for `djour env`
+pub fn format_env(settings: &EffectiveSettings) -> String {
+    let mut output = match &settings.root {
+        Some(root) => format!("root = {} (from {})\n", root.value.display(), root.source),
+        None => "root = <not found>\n".to_string(),
+    };
+    if let Some(problem) = &settings.problem {
+        output.push_str(&format!("  ({})\n", problem));
+    }
+    output.push_str(&format!("mode = {}\n", settings.mode));
+    if let Some(warning) = &settings.mode.warning {
+        output.push_str(&format!("  ({})\n", warning));
+    }
+    output.push_str(&format!("editor = {}\n", settings.editor));
     output
 }
 
+/// Effective settings as pretty-printed JSON, for bug reports
+pub fn format_env_json(settings: &EffectiveSettings) -> Result<String> {
+    let mut json = serde_json::to_string_pretty(settings)
+        .map_err(|e| DjourError::Config(format!("Failed to serialize settings: {}", e)))?;
+    json.push('\n');
+    Ok(json)
+}
+
 /// Format a list of tags for display.
 pub fn format_tag_list(tags: &[String]) -> String {
     if tags.is_empty() {
@@ -38,6 +424,272 @@ pub fn format_tag_list(tags: &[String]) -> String {
     output
 }
 
+/// Format tag counts for display.
+///
+/// One full tag per line, as [`format_tag_list`]: nested tags keep their path
+/// (`#project/alpha`) and value tags their value (`#run:5k`), so piped output
+/// stays one `#tag` per line whatever tags the notes use.
+pub fn format_tag_counts(counts: &BTreeMap<String, usize>) -> String {
+    let tags: Vec<String> = counts.keys().cloned().collect();
+    format_tag_list(&tags)
+}
+
+/// Tag counts as CSV: tag, count and the first and last dated note using it
+pub fn format_tag_csv(tags: &TagCounts) -> String {
+    let mut table = CsvTable::new(&["tag", "count", "first_date", "last_date"]);
+    for (tag, count) in &tags.counts {
+        let (first, last) = match tags.dates.get(tag) {
+            Some((first, last)) => (first.to_string(), last.to_string()),
+            None => (String::new(), String::new()),
+        };
+        table.row(&[tag.clone(), count.to_string(), first, last]);
+    }
+    table.finish()
+}
+
+/// Format tag counts for a terminal with right-aligned counts and bars.
+///
+/// Nested tags are shown as an indented tree where each parent's count
+/// includes its descendants. Value tags are counted under their name, followed
+/// by their values. Falls back to [`format_tag_counts`] unless `style` is rich.
+pub fn format_tag_table(counts: &BTreeMap<String, usize>, style: OutputStyle) -> String {
+    if !style.rich || counts.is_empty() {
+        return format_tag_counts(counts);
+    }
+
+    let (counts, values) = fold_value_tags(counts);
+    let rows = tag_rows(&counts);
+    let name_width = rows
+        .iter()
+        .map(|row| display_width(&row.indent()) + display_width(&row.label()))
+        .max()
+        .unwrap_or(0);
+    let max_count = rows.iter().map(|row| row.count).max().unwrap_or(1).max(1);
+    let count_width = max_count.to_string().len();
+
+    let mut output = String::new();
+    for row in &rows {
+        let bar_len = (row.count * TAG_BAR_WIDTH).div_ceil(max_count);
+        let name = pad_right(&row.label(), name_width - display_width(&row.indent()));
+        output.push_str(&format!(
+            "{}{}  {}  {}",
+            row.indent(),
+            style.accent(&name),
+            pad_left(&row.count.to_string(), count_width),
+            style.dim(&"\u{2588}".repeat(bar_len))
+        ));
+        if let Some(values) = values.get(row.tag.as_str()) {
+            output.push_str(&format!("  {}", format_tag_values(values)));
+        }
+        output.push('\n');
+    }
+    output
+}
+
+/// Values of value tags (`run:5k`) by tag name, most used first
+type TagValues<'a> = BTreeMap<&'a str, Vec<(&'a str, usize)>>;
+
+/// `counts` with every value tag counted under its name, and the values of each name
+fn fold_value_tags(counts: &BTreeMap<String, usize>) -> (BTreeMap<String, usize>, TagValues<'_>) {
+    let mut folded: BTreeMap<String, usize> = BTreeMap::new();
+    let mut values: TagValues<'_> = BTreeMap::new();
+    for (tag, count) in counts {
+        match split_value_tag(tag) {
+            Some((name, value)) => {
+                *folded.entry(name.to_string()).or_default() += count;
+                values.entry(name).or_default().push((value, *count));
+            }
+            None => *folded.entry(tag.clone()).or_default() += count,
+        }
+    }
+    for list in values.values_mut() {
+        // Stable sort: equal counts stay in value order
+        list.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    }
+    (folded, values)
+}
+
+/// `5k (12), 10k (8)`
+fn format_tag_values(values: &[(&str, usize)]) -> String {
+    values
+        .iter()
+        .map(|(value, count)| format!("{} ({})", value, count))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Format spelling groups, e.g. `#one-on-one (12) [also: one_on_one (3), oneonone (1)]`.
+pub fn format_tag_groups(groups: &[TagGroup]) -> String {
+    if groups.is_empty() {
+        return "No tags found".to_string();
+    }
+
+    let mut output = String::new();
+    for group in groups {
+        output.push_str(&format!(
+            "#{} ({})",
+            group.canonical.name, group.canonical.count
+        ));
+        if !group.variants.is_empty() {
+            let variants: Vec<String> = group
+                .variants
+                .iter()
+                .map(|v| format!("{} ({})", v.name, v.count))
+                .collect();
+            output.push_str(&format!(" [also: {}]", variants.join(", ")));
+        }
+        output.push('\n');
+    }
+    output
+}
+
+/// One line of tag output: a tag (or tree segment) and its rolled-up count
+struct TagRow<'a> {
+    depth: usize,
+    name: &'a str,
+    /// The full tag of the row (`project/alpha`)
+    tag: String,
+    count: usize,
+}
+
+impl TagRow<'_> {
+    fn indent(&self) -> String {
+        "  ".repeat(self.depth)
+    }
+
+    fn label(&self) -> String {
+        if self.depth == 0 {
+            format!("#{}", self.name)
+        } else {
+            self.name.to_string()
+        }
+    }
+}
+
+/// Tags as tree rows; each parent's count includes its descendants.
+fn tag_rows(counts: &BTreeMap<String, usize>) -> Vec<TagRow<'_>> {
+    // Keyed by segments so children sort directly below their parent
+    // (plain string order would put `project-x` between `project` and `project/a`).
+    let mut rolled_up: BTreeMap<Vec<&str>, usize> = BTreeMap::new();
+    for (tag, count) in counts {
+        *rolled_up.entry(tag.split('/').collect()).or_default() += count;
+        for ancestor in tag_ancestors(tag) {
+            *rolled_up.entry(ancestor.split('/').collect()).or_default() += count;
+        }
+    }
+
+    rolled_up
+        .into_iter()
+        .map(|(segments, count)| TagRow {
+            depth: segments.len() - 1,
+            name: segments[segments.len() - 1],
+            tag: segments.join("/"),
+            count,
+        })
+        .collect()
+}
+
+/// Format due reminders: the text, then the due date and where it was written
+pub fn format_reminder_list(reminders: &[ReminderItem]) -> String {
+    if reminders.is_empty() {
+        return "No reminders due\n".to_string();
+    }
+
+    let mut output = String::new();
+    for reminder in reminders {
+        let status = match reminder.overdue_days {
+            0 => "due today".to_string(),
+            1 => "1 day overdue".to_string(),
+            days => format!("{} days overdue", days),
+        };
+        output.push_str(&format!(
+            "{} ({}, {}) [{}:{}]\n",
+            reminder.text,
+            reminder.due.format("%d-%m-%Y"),
+            status,
+            reminder.source_file.to_string_lossy().replace('\\', "/"),
+            reminder.line
+        ));
+    }
+    output
+}
+
+/// Unit of a streak in `mode`, pluralized for `count`
+fn streak_unit(mode: JournalMode, count: usize) -> String {
+    let unit = match mode {
+        JournalMode::Weekly => "week",
+        JournalMode::Monthly => "month",
+        JournalMode::Daily | JournalMode::Single => "day",
+    };
+    format!("{} {}{}", count, unit, if count == 1 { "" } else { "s" })
+}
+
+/// Top tags as `#work (12), #health (8)`, or `none`
+fn stats_tag_summary(stats: &JournalStats) -> String {
+    if stats.top_tags.is_empty() {
+        return "none".to_string();
+    }
+    stats
+        .top_tags
+        .iter()
+        .map(|(tag, count)| format!("#{} ({})", tag, count))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Format journal stats for display
+pub fn format_stats(stats: &JournalStats) -> String {
+    format!(
+        "Notes: {}\nCurrent streak: {}\nTop tags: {}\n",
+        stats.notes,
+        streak_unit(stats.mode, stats.streak),
+        stats_tag_summary(stats)
+    )
+}
+
+/// Journal stats as shields.io badges for a README (`stats --badge-markdown`)
+pub fn format_stats_badges(stats: &JournalStats) -> String {
+    let badges = [
+        ("notes", stats.notes.to_string(), "blue"),
+        (
+            "streak",
+            streak_unit(stats.mode, stats.streak),
+            if stats.streak > 0 {
+                "brightgreen"
+            } else {
+                "lightgrey"
+            },
+        ),
+        ("top tags", stats_tag_summary(stats), "orange"),
+    ];
+
+    let mut output = String::new();
+    for (label, message, color) in badges {
+        output.push_str(&format!(
+            "![{}: {}](https://img.shields.io/static/v1?label={}&message={}&color={})\n",
+            label,
+            message,
+            query_escape(label),
+            query_escape(&message),
+            color
+        ));
+    }
+    output
+}
+
+/// Percent-encode `value` for a URL query parameter
+fn query_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~') {
+            escaped.push(byte as char);
+        } else {
+            escaped.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    escaped
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -50,6 +702,125 @@ mod tests {
         assert_eq!(output, "No notes found");
     }
 
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_date_bucket_around_week_boundaries() {
+        // Wednesday 15 Jan 2025; the Monday week began on the 13th
+        let today = date(2025, 1, 15);
+        let bucket = |d| date_bucket(d, today, WeekStart::Monday);
+        assert_eq!(bucket(date(2025, 1, 16)), DateBucket::Upcoming);
+        assert_eq!(bucket(today), DateBucket::Today);
+        assert_eq!(bucket(date(2025, 1, 14)), DateBucket::Yesterday);
+        assert_eq!(bucket(date(2025, 1, 13)), DateBucket::ThisWeek);
+        assert_eq!(bucket(date(2025, 1, 12)), DateBucket::LastWeek);
+        assert_eq!(bucket(date(2025, 1, 6)), DateBucket::LastWeek);
+        assert_eq!(bucket(date(2025, 1, 5)), DateBucket::EarlierThisMonth);
+        assert_eq!(bucket(date(2024, 12, 31)), DateBucket::Older);
+
+        // With Sunday weeks the 12th is already this week
+        let sunday = |d| date_bucket(d, today, WeekStart::Sunday);
+        assert_eq!(sunday(date(2025, 1, 12)), DateBucket::ThisWeek);
+        assert_eq!(sunday(date(2025, 1, 11)), DateBucket::LastWeek);
+        assert_eq!(sunday(date(2025, 1, 5)), DateBucket::LastWeek);
+        assert_eq!(sunday(date(2025, 1, 4)), DateBucket::EarlierThisMonth);
+    }
+
+    #[test]
+    fn test_date_bucket_around_month_boundaries() {
+        // Monday 3 Feb 2025: yesterday and last week lie in January
+        let today = date(2025, 2, 3);
+        let bucket = |d| date_bucket(d, today, WeekStart::Monday);
+        assert_eq!(bucket(date(2025, 2, 2)), DateBucket::Yesterday);
+        assert_eq!(bucket(date(2025, 1, 27)), DateBucket::LastWeek);
+        assert_eq!(bucket(date(2025, 1, 26)), DateBucket::Older);
+
+        // Late in the month, weeks before last are still this month
+        let today = date(2025, 1, 31);
+        assert_eq!(
+            date_bucket(date(2025, 1, 2), today, WeekStart::Monday),
+            DateBucket::EarlierThisMonth
+        );
+    }
+
+    #[test]
+    fn test_format_note_list_grouped_omits_empty_buckets() {
+        let today = date(2025, 1, 15);
+        let notes = vec![
+            NoteEntry::new("2025-01-15.md".to_string(), Some(today)),
+            NoteEntry::new("2025-01-10.md".to_string(), Some(date(2025, 1, 10))),
+            NoteEntry::new("2025-01-07.md".to_string(), Some(date(2025, 1, 7))),
+            NoteEntry::new("2024-11-30.md".to_string(), Some(date(2024, 11, 30))),
+        ];
+
+        let output = format_note_list_grouped(&notes, today, WeekStart::Monday, format_note_list);
+        assert_eq!(
+            output,
+            "Today\n15-01-2025  2025-01-15.md\n\n\
+             Last week\n10-01-2025  2025-01-10.md\n07-01-2025  2025-01-07.md\n\n\
+             Older\n30-11-2024  2024-11-30.md\n"
+        );
+        assert_eq!(
+            format_note_list_grouped(&[], today, WeekStart::Monday, format_note_list),
+            "No notes found"
+        );
+    }
+
+    #[test]
+    fn test_format_todo_list_with_and_without_status() {
+        let task = |text: &str, checked, date| TaskItem {
+            text: text.to_string(),
+            checked,
+            tags: vec!["work".to_string()],
+            source_file: "notes/2025-01-15.md".into(),
+            date,
+            line: 3,
+        };
+        let tasks = vec![
+            task("follow up #work", false, Some(date(2025, 1, 15))),
+            task("archived", true, None),
+        ];
+
+        assert_eq!(
+            format_todo_list(&tasks, false),
+            concat!(
+                "15-01-2025  notes/2025-01-15.md:3  follow up #work\n",
+                "            notes/2025-01-15.md:3  archived\n"
+            )
+        );
+        assert!(format_todo_list(&tasks, true).starts_with("[ ] 15-01-2025  "));
+        assert!(format_todo_list(&tasks, true).contains("\n[x]             notes/"));
+        assert_eq!(format_todo_list(&[], true), "No tasks found");
+
+        let json = format_todo_json(&tasks[..1]).unwrap();
+        assert!(json.contains("\"date\": \"2025-01-15\""), "{json}");
+        assert!(
+            json.contains("\"source_file\": \"notes/2025-01-15.md\""),
+            "{json}"
+        );
+    }
+
+    #[test]
+    fn test_format_note_list_with_modified() {
+        use chrono::{Local, TimeZone};
+
+        let modified = Local.with_ymd_and_hms(2025, 2, 3, 9, 30, 0).unwrap();
+        let notes = vec![
+            NoteEntry::new(
+                "2025-01-17.md".to_string(),
+                Some(NaiveDate::from_ymd_opt(2025, 1, 17).unwrap()),
+            )
+            .with_modified(Some(modified.into())),
+            NoteEntry::new("journal.md".to_string(), None),
+        ];
+
+        let output = format_note_list_with_modified(&notes);
+        assert!(output.contains("17-01-2025  2025-02-03 09:30  2025-01-17.md"));
+        assert!(output.contains("            -                 journal.md"));
+    }
+
     #[test]
     fn test_format_note_list() {
         let notes = vec![
@@ -68,6 +839,20 @@ mod tests {
         assert!(output.contains("16-01-2025  2025-01-16.md"));
     }
 
+    #[test]
+    fn test_format_note_list_with_titles() {
+        let mut notes = daily_notes();
+        notes[0].title = Some("Sprint retro".to_string());
+        notes[2].title = Some("Inbox".to_string());
+
+        assert_eq!(
+            format_note_list(&notes),
+            "17-01-2025  2025-01-17.md  Sprint retro\n\
+             14-01-2025  2025-01-14.md\n\
+             \x20          journal.md     Inbox\n"
+        );
+    }
+
     #[test]
     fn test_format_single_mode_entry() {
         let notes = vec![NoteEntry::new("journal.md".to_string(), None)];
@@ -106,4 +891,209 @@ mod tests {
         let output = format_tag_list(&tags);
         assert_eq!(output, "#personal\n#work\n");
     }
+
+    #[test]
+    fn test_format_flat_tag_counts_matches_tag_list() {
+        let counts = BTreeMap::from([("personal".to_string(), 2), ("work".to_string(), 5)]);
+        assert_eq!(format_tag_counts(&counts), "#personal\n#work\n");
+        assert_eq!(format_tag_counts(&BTreeMap::new()), "No tags found");
+    }
+
+    #[test]
+    fn test_format_tag_counts_lists_full_paths() {
+        let counts = BTreeMap::from([
+            ("project/alpha".to_string(), 2),
+            ("project/alpha/backend".to_string(), 3),
+            ("run:5k".to_string(), 12),
+            ("work".to_string(), 2),
+        ]);
+
+        assert_eq!(
+            format_tag_counts(&counts),
+            "#project/alpha\n#project/alpha/backend\n#run:5k\n#work\n"
+        );
+    }
+
+    #[test]
+    fn test_tag_table_rich_rolls_up_counts() {
+        let counts = BTreeMap::from([
+            ("project".to_string(), 1),
+            ("project/alpha".to_string(), 2),
+            ("project/alpha/backend".to_string(), 3),
+            ("project/beta".to_string(), 1),
+            ("project-x".to_string(), 1),
+            ("team/ops".to_string(), 4),
+            ("work".to_string(), 2),
+        ]);
+
+        assert_eq!(
+            format_tag_table(&counts, OutputStyle::rich(false)),
+            concat!(
+                "#project     7  ████████████████████\n",
+                "  alpha      5  ███████████████\n",
+                "    backend  3  █████████\n",
+                "  beta       1  ███\n",
+                "#project-x   1  ███\n",
+                "#team        4  ████████████\n",
+                "  ops        4  ████████████\n",
+                "#work        2  ██████\n",
+            )
+        );
+    }
+
+    fn list_context(mode: JournalMode) -> NoteListContext {
+        NoteListContext {
+            mode,
+            locale: Locale::default(),
+            today: NaiveDate::from_ymd_opt(2025, 1, 17).unwrap(),
+            show_modified: false,
+        }
+    }
+
+    fn daily_notes() -> Vec<NoteEntry> {
+        vec![
+            NoteEntry::new(
+                "2025-01-17.md".to_string(),
+                Some(NaiveDate::from_ymd_opt(2025, 1, 17).unwrap()),
+            ),
+            NoteEntry::new(
+                "2025-01-14.md".to_string(),
+                Some(NaiveDate::from_ymd_opt(2025, 1, 14).unwrap()),
+            ),
+            NoteEntry::new("journal.md".to_string(), None),
+        ]
+    }
+
+    #[test]
+    fn test_note_table_plain_matches_note_list_without_reading_titles() {
+        let notes = daily_notes();
+        let output = format_note_table(
+            &notes,
+            &list_context(JournalMode::Daily),
+            OutputStyle::plain(),
+            |_| panic!("plain output must not read notes"),
+        );
+        assert_eq!(output, format_note_list(&notes));
+    }
+
+    #[test]
+    fn test_note_table_rich_aligns_columns() {
+        let output = format_note_table(
+            &daily_notes(),
+            &list_context(JournalMode::Daily),
+            OutputStyle::rich(false),
+            |entry| (entry.filename == "2025-01-14.md").then(|| "Planning 日記".to_string()),
+        );
+        assert_eq!(
+            output,
+            "17-01-2025  Fri  today       2025-01-17.md\n\
+             14-01-2025  Tue  3 days ago  2025-01-14.md  Planning 日記\n\
+             \x20                            journal.md\n"
+        );
+    }
+
+    #[test]
+    fn test_note_table_colors_current_entry() {
+        let output = format_note_table(
+            &daily_notes()[..2],
+            &list_context(JournalMode::Daily),
+            OutputStyle::rich(true),
+            |_| None,
+        );
+        let lines: Vec<&str> = output.lines().collect();
+        assert!(lines[0].starts_with("\x1b[1;32m17-01-2025\x1b[0m"));
+        assert!(lines[1].starts_with("\x1b[36m14-01-2025\x1b[0m"));
+    }
+
+    #[test]
+    fn test_relative_age_for_periods() {
+        let weekly = list_context(JournalMode::Weekly);
+        let d = |m, day| NaiveDate::from_ymd_opt(2025, m, day).unwrap();
+        assert_eq!(relative_age(d(1, 13), &weekly), "this week");
+        assert_eq!(relative_age(d(1, 6), &weekly), "11 days ago");
+        assert_eq!(
+            relative_age(d(1, 16), &list_context(JournalMode::Daily)),
+            "yesterday"
+        );
+        assert_eq!(
+            relative_age(d(1, 20), &list_context(JournalMode::Daily)),
+            "in 3 days"
+        );
+    }
+
+    #[test]
+    fn test_format_tag_groups() {
+        use crate::domain::tags::TagNormalizer;
+
+        let counts = BTreeMap::from([
+            ("one-on-one".to_string(), 12),
+            ("one_on_one".to_string(), 3),
+            ("oneonone".to_string(), 1),
+            ("work".to_string(), 4),
+        ]);
+        assert_eq!(
+            format_tag_groups(&TagNormalizer::default().group(&counts)),
+            "#one-on-one (12) [also: one_on_one (3), oneonone (1)]\n#work (4)\n"
+        );
+        assert_eq!(format_tag_groups(&[]), "No tags found");
+    }
+
+    #[test]
+    fn test_tag_table_plain_matches_tag_counts() {
+        let counts = BTreeMap::from([("project/alpha".to_string(), 2), ("work".to_string(), 5)]);
+        assert_eq!(
+            format_tag_table(&counts, OutputStyle::plain()),
+            format_tag_counts(&counts)
+        );
+    }
+
+    #[test]
+    fn test_value_tags_are_grouped_under_their_name() {
+        let counts = BTreeMap::from([
+            ("mood:good".to_string(), 3),
+            ("run".to_string(), 3),
+            ("run:10k".to_string(), 8),
+            ("run:5k".to_string(), 12),
+            ("work".to_string(), 2),
+        ]);
+        assert_eq!(
+            format_tag_counts(&counts),
+            "#mood:good\n#run\n#run:10k\n#run:5k\n#work\n"
+        );
+        assert_eq!(
+            format_tag_table(&counts, OutputStyle::rich(false)),
+            "#mood   3  ███  good (3)\n\
+             #run   23  ████████████████████  5k (12), 10k (8)\n\
+             #work   2  ██\n"
+        );
+    }
+
+    #[test]
+    fn test_tag_table_rich_right_aligns_counts_with_bars() {
+        let counts = BTreeMap::from([("project/alpha".to_string(), 2), ("work".to_string(), 10)]);
+        assert_eq!(
+            format_tag_table(&counts, OutputStyle::rich(false)),
+            "#project   2  ████\n  alpha    2  ████\n#work     10  ████████████████████\n"
+        );
+    }
+
+    #[test]
+    fn test_format_stats_badges() {
+        let stats = JournalStats {
+            mode: JournalMode::Daily,
+            notes: 42,
+            streak: 1,
+            top_tags: vec![("work".to_string(), 12), ("project/alpha".to_string(), 3)],
+        };
+        assert_eq!(
+            format_stats_badges(&stats),
+            "![notes: 42](https://img.shields.io/static/v1?label=notes&message=42&color=blue)\n\
+             ![streak: 1 day](https://img.shields.io/static/v1?label=streak&message=1%20day&color=brightgreen)\n\
+             ![top tags: #work (12), #project/alpha (3)](https://img.shields.io/static/v1?label=top%20tags&message=%23work%20%2812%29%2C%20%23project%2Falpha%20%283%29&color=orange)\n"
+        );
+        assert_eq!(
+            format_stats(&stats),
+            "Notes: 42\nCurrent streak: 1 day\nTop tags: #work (12), #project/alpha (3)\n"
+        );
+    }
 }
@@ -0,0 +1,95 @@
+//! Self-updating progress line on stderr for scans of large journals
+
+use std::cell::Cell;
+use std::io::{IsTerminal, Write};
+
+/// Scans of at most this many notes finish quickly enough to need no progress line
+pub const PROGRESS_THRESHOLD: usize = 200;
+
+/// `scanning notes… 1243/5012`, redrawn in place with `\r` and cleared by [`ProgressLine::finish`]
+#[derive(Debug, Default)]
+pub struct ProgressLine {
+    enabled: bool,
+    /// Width of the last line drawn, 0 when nothing is on screen
+    drawn: Cell<usize>,
+}
+
+impl ProgressLine {
+    /// A progress line for stderr; disabled with `--quiet` or when stderr is not a terminal
+    pub fn for_stderr(quiet: bool) -> Self {
+        Self::new(!quiet && std::io::stderr().is_terminal())
+    }
+
+    fn new(enabled: bool) -> Self {
+        ProgressLine {
+            enabled,
+            drawn: Cell::new(0),
+        }
+    }
+
+    /// Record that `done` of `total` notes were read
+    pub fn update(&self, done: usize, total: usize) {
+        if let Some(line) = self.line(done, total) {
+            let mut stderr = std::io::stderr().lock();
+            let _ = write!(stderr, "\r{}", line);
+            let _ = stderr.flush();
+            self.drawn.set(line.chars().count());
+        }
+    }
+
+    /// Text to draw for an update, if any
+    fn line(&self, done: usize, total: usize) -> Option<String> {
+        if !self.enabled || total <= PROGRESS_THRESHOLD {
+            return None;
+        }
+        // Redraw about every percent
+        let step = (total / 100).max(1);
+        (done == 1 || done == total || done.is_multiple_of(step))
+            .then(|| format!("scanning notes… {}/{}", done, total))
+    }
+
+    /// Erase the line so later output starts on a clean line
+    pub fn finish(&self) {
+        let width = self.drawn.replace(0);
+        if width > 0 {
+            let mut stderr = std::io::stderr().lock();
+            let _ = write!(stderr, "\r{}\r", " ".repeat(width));
+            let _ = stderr.flush();
+        }
+    }
+}
+
+impl Drop for ProgressLine {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_small_scans_and_disabled_lines_draw_nothing() {
+        assert_eq!(ProgressLine::new(true).line(1, PROGRESS_THRESHOLD), None);
+        assert_eq!(ProgressLine::new(false).line(1, 5000), None);
+    }
+
+    #[test]
+    fn test_large_scans_redraw_about_every_percent() {
+        let progress = ProgressLine::new(true);
+        assert_eq!(
+            progress.line(1, 5012).as_deref(),
+            Some("scanning notes… 1/5012")
+        );
+        assert_eq!(progress.line(51, 5012), None);
+        assert_eq!(
+            progress.line(1250, 5012).as_deref(),
+            Some("scanning notes… 1250/5012")
+        );
+        assert_eq!(
+            progress.line(5012, 5012).as_deref(),
+            Some("scanning notes… 5012/5012")
+        );
+    }
+}
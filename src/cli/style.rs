@@ -0,0 +1,142 @@
+//! Terminal styling for human-facing output
+//!
+//! Rich output (aligned columns, extra detail, ANSI colors) is only used when
+//! stdout is a terminal, so piped output keeps its stable plain format.
+
+use std::io::IsTerminal;
+use unicode_width::UnicodeWidthStr;
+
+/// Environment variable that disables colors when set to a non-empty value
+pub const NO_COLOR_ENV: &str = "NO_COLOR";
+
+/// Environment variable that forces rich output even when stdout is not a terminal
+pub const FORCE_COLOR_ENV: &str = "CLICOLOR_FORCE";
+
+const RESET: &str = "\x1b[0m";
+const CYAN: &str = "\x1b[36m";
+const BOLD_GREEN: &str = "\x1b[1;32m";
+const DIM: &str = "\x1b[2m";
+
+/// How output should be rendered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct OutputStyle {
+    /// Use the columnar layout with extra detail
+    pub rich: bool,
+    /// Emit ANSI color codes (only meaningful with `rich`)
+    pub color: bool,
+}
+
+impl OutputStyle {
+    /// Plain, script-friendly output
+    pub fn plain() -> Self {
+        OutputStyle::default()
+    }
+
+    /// Rich output, optionally colored
+    pub fn rich(color: bool) -> Self {
+        OutputStyle { rich: true, color }
+    }
+
+    /// Pick a style for stdout from the terminal, `--no-color`, `NO_COLOR` and `CLICOLOR_FORCE`.
+    pub fn detect(no_color: bool) -> Self {
+        let env_set = |key: &str| std::env::var_os(key).is_some_and(|v| !v.is_empty() && v != "0");
+        Self::resolve(
+            std::io::stdout().is_terminal(),
+            env_set(FORCE_COLOR_ENV),
+            no_color || env_set(NO_COLOR_ENV),
+        )
+    }
+
+    fn resolve(is_terminal: bool, force: bool, no_color: bool) -> Self {
+        if is_terminal || force {
+            OutputStyle::rich(!no_color)
+        } else {
+            OutputStyle::plain()
+        }
+    }
+
+    /// Accent color for dates and tag names
+    pub fn accent(&self, text: &str) -> String {
+        self.paint(CYAN, text)
+    }
+
+    /// Highlight the entry for the current period
+    pub fn current(&self, text: &str) -> String {
+        self.paint(BOLD_GREEN, text)
+    }
+
+    /// De-emphasize secondary detail
+    pub fn dim(&self, text: &str) -> String {
+        self.paint(DIM, text)
+    }
+
+    fn paint(&self, code: &str, text: &str) -> String {
+        if self.color && !text.is_empty() {
+            format!("{}{}{}", code, text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+/// Display width of `text` in terminal columns
+pub fn display_width(text: &str) -> usize {
+    UnicodeWidthStr::width(text)
+}
+
+/// Pad `text` on the right to `width` terminal columns
+pub fn pad_right(text: &str, width: usize) -> String {
+    let pad = width.saturating_sub(display_width(text));
+    format!("{}{}", text, " ".repeat(pad))
+}
+
+/// Pad `text` on the left to `width` terminal columns
+pub fn pad_left(text: &str, width: usize) -> String {
+    let pad = width.saturating_sub(display_width(text));
+    format!("{}{}", " ".repeat(pad), text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_style() {
+        assert_eq!(
+            OutputStyle::resolve(false, false, false),
+            OutputStyle::plain()
+        );
+        assert_eq!(
+            OutputStyle::resolve(false, false, true),
+            OutputStyle::plain()
+        );
+        assert_eq!(
+            OutputStyle::resolve(true, false, false),
+            OutputStyle::rich(true)
+        );
+        assert_eq!(
+            OutputStyle::resolve(true, false, true),
+            OutputStyle::rich(false)
+        );
+        assert_eq!(
+            OutputStyle::resolve(false, true, false),
+            OutputStyle::rich(true)
+        );
+    }
+
+    #[test]
+    fn test_paint_only_when_colored() {
+        assert_eq!(OutputStyle::rich(false).accent("17-01-2025"), "17-01-2025");
+        assert_eq!(
+            OutputStyle::rich(true).accent("17-01-2025"),
+            "\x1b[36m17-01-2025\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn test_padding_uses_display_width() {
+        assert_eq!(pad_right("日記", 6), "日記  ");
+        assert_eq!(pad_left("7", 3), "  7");
+        assert_eq!(pad_right("toolong", 3), "toolong");
+    }
+}
@@ -0,0 +1,314 @@
+//! Browse state and key handling, independent of the terminal
+
+use crate::infrastructure::NoteEntry;
+use ratatui::crossterm::event::KeyCode;
+
+/// A listed note and the tags used in it (lowercased, without `#`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoteItem {
+    pub entry: NoteEntry,
+    pub tags: Vec<String>,
+}
+
+/// What the left pane shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pane {
+    Notes,
+    Tags,
+}
+
+/// What the event loop should do after a key press
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    None,
+    Quit,
+    /// Open this note (root-relative path) in the external editor
+    Open(String),
+}
+
+/// What the right pane should show for the current selection
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Selection {
+    Note(String),
+    Tag(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct BrowseApp {
+    notes: Vec<NoteItem>,
+    tags: Vec<(String, usize)>,
+    pane: Pane,
+    filter: String,
+    filtering: bool,
+    selected: usize,
+    /// Lines scrolled in the preview pane
+    pub scroll: u16,
+}
+
+impl BrowseApp {
+    /// `notes` are expected newest first, `tags` with their counts in display order
+    pub fn new(notes: Vec<NoteItem>, tags: Vec<(String, usize)>) -> Self {
+        BrowseApp {
+            notes,
+            tags,
+            pane: Pane::Notes,
+            filter: String::new(),
+            filtering: false,
+            selected: 0,
+            scroll: 0,
+        }
+    }
+
+    /// Replace the listed data (after editing a note), keeping the selection where possible
+    pub fn reload(&mut self, notes: Vec<NoteItem>, tags: Vec<(String, usize)>) {
+        let previous = self.selection();
+        self.notes = notes;
+        self.tags = tags;
+        self.selected = previous
+            .and_then(|selection| self.position_of(&selection))
+            .unwrap_or(0);
+        self.clamp_selection();
+    }
+
+    pub fn pane(&self) -> Pane {
+        self.pane
+    }
+
+    pub fn filter(&self) -> &str {
+        &self.filter
+    }
+
+    pub fn is_filtering(&self) -> bool {
+        self.filtering
+    }
+
+    /// Index of the selected row among the visible rows
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Notes matching the filter by filename or tag
+    pub fn visible_notes(&self) -> Vec<&NoteItem> {
+        let needle = self.needle();
+        self.notes
+            .iter()
+            .filter(|note| {
+                needle.is_empty()
+                    || note.entry.filename.to_lowercase().contains(&needle)
+                    || note.tags.iter().any(|tag| tag.contains(&needle))
+            })
+            .collect()
+    }
+
+    /// Tags matching the filter
+    pub fn visible_tags(&self) -> Vec<&(String, usize)> {
+        let needle = self.needle();
+        self.tags
+            .iter()
+            .filter(|(tag, _)| tag.contains(&needle))
+            .collect()
+    }
+
+    /// The selected note or tag, if any row is visible
+    pub fn selection(&self) -> Option<Selection> {
+        match self.pane {
+            Pane::Notes => self
+                .visible_notes()
+                .get(self.selected)
+                .map(|note| Selection::Note(note.entry.filename.clone())),
+            Pane::Tags => self
+                .visible_tags()
+                .get(self.selected)
+                .map(|(tag, _)| Selection::Tag(tag.clone())),
+        }
+    }
+
+    pub fn handle_key(&mut self, code: KeyCode) -> Action {
+        if self.filtering {
+            match code {
+                KeyCode::Enter => self.filtering = false,
+                KeyCode::Esc => {
+                    self.filtering = false;
+                    self.filter.clear();
+                }
+                KeyCode::Backspace => {
+                    self.filter.pop();
+                }
+                KeyCode::Char(c) => self.filter.push(c),
+                _ => return Action::None,
+            }
+            self.selected = 0;
+            self.scroll = 0;
+            return Action::None;
+        }
+
+        match code {
+            KeyCode::Char('q') => return Action::Quit,
+            KeyCode::Esc if self.filter.is_empty() => return Action::Quit,
+            KeyCode::Esc => {
+                self.filter.clear();
+                self.selected = 0;
+            }
+            KeyCode::Char('j') | KeyCode::Down => self.move_by(1),
+            KeyCode::Char('k') | KeyCode::Up => self.move_by(-1),
+            KeyCode::Char('g') | KeyCode::Home => self.select(0),
+            KeyCode::Char('G') | KeyCode::End => self.select(usize::MAX),
+            KeyCode::PageDown => self.scroll = self.scroll.saturating_add(10),
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+            KeyCode::Char('/') => self.filtering = true,
+            KeyCode::Char('t') => {
+                self.pane = match self.pane {
+                    Pane::Notes => Pane::Tags,
+                    Pane::Tags => Pane::Notes,
+                };
+                self.filter.clear();
+                self.select(0);
+            }
+            KeyCode::Enter => {
+                if let Some(Selection::Note(filename)) = self.selection() {
+                    return Action::Open(filename);
+                }
+            }
+            _ => {}
+        }
+        Action::None
+    }
+
+    fn needle(&self) -> String {
+        let filter = self.filter.trim().to_lowercase();
+        filter.strip_prefix('#').unwrap_or(&filter).to_string()
+    }
+
+    fn visible_len(&self) -> usize {
+        match self.pane {
+            Pane::Notes => self.visible_notes().len(),
+            Pane::Tags => self.visible_tags().len(),
+        }
+    }
+
+    fn position_of(&self, selection: &Selection) -> Option<usize> {
+        match (self.pane, selection) {
+            (Pane::Notes, Selection::Note(filename)) => self
+                .visible_notes()
+                .iter()
+                .position(|note| &note.entry.filename == filename),
+            (Pane::Tags, Selection::Tag(tag)) => {
+                self.visible_tags().iter().position(|(t, _)| t == tag)
+            }
+            _ => None,
+        }
+    }
+
+    fn move_by(&mut self, delta: isize) {
+        self.select(self.selected.saturating_add_signed(delta));
+    }
+
+    fn select(&mut self, index: usize) {
+        self.selected = index;
+        self.clamp_selection();
+        self.scroll = 0;
+    }
+
+    fn clamp_selection(&mut self) {
+        self.selected = self.selected.min(self.visible_len().saturating_sub(1));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn note(filename: &str, tags: &[&str]) -> NoteItem {
+        let date = NaiveDate::parse_from_str(&filename[..10], "%Y-%m-%d").ok();
+        NoteItem {
+            entry: NoteEntry::new(filename.to_string(), date),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+        }
+    }
+
+    fn app() -> BrowseApp {
+        BrowseApp::new(
+            vec![
+                note("2025-01-17.md", &["work"]),
+                note("2025-01-16.md", &["personal"]),
+                note("2025-01-15.md", &["work", "urgent"]),
+            ],
+            vec![
+                ("personal".to_string(), 1),
+                ("urgent".to_string(), 1),
+                ("work".to_string(), 2),
+            ],
+        )
+    }
+
+    fn type_filter(app: &mut BrowseApp, text: &str) {
+        app.handle_key(KeyCode::Char('/'));
+        for c in text.chars() {
+            app.handle_key(KeyCode::Char(c));
+        }
+        app.handle_key(KeyCode::Enter);
+    }
+
+    #[test]
+    fn test_navigation_is_clamped() {
+        let mut app = app();
+        app.handle_key(KeyCode::Char('k'));
+        assert_eq!(app.selected(), 0);
+        for _ in 0..5 {
+            app.handle_key(KeyCode::Char('j'));
+        }
+        assert_eq!(
+            app.selection(),
+            Some(Selection::Note("2025-01-15.md".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_filter_by_tag_or_filename() {
+        let mut app = app();
+        type_filter(&mut app, "#work");
+        let visible: Vec<_> = app
+            .visible_notes()
+            .iter()
+            .map(|n| n.entry.filename.as_str())
+            .collect();
+        assert_eq!(visible, vec!["2025-01-17.md", "2025-01-15.md"]);
+
+        app.handle_key(KeyCode::Esc);
+        type_filter(&mut app, "01-16");
+        assert_eq!(app.visible_notes().len(), 1);
+        // q while not typing quits; while typing it is part of the filter
+        app.handle_key(KeyCode::Char('/'));
+        assert_eq!(app.handle_key(KeyCode::Char('q')), Action::None);
+        assert_eq!(app.filter(), "01-16q");
+    }
+
+    #[test]
+    fn test_tag_pane_and_open() {
+        let mut app = app();
+        assert_eq!(
+            app.handle_key(KeyCode::Enter),
+            Action::Open("2025-01-17.md".to_string())
+        );
+
+        app.handle_key(KeyCode::Char('t'));
+        assert_eq!(app.pane(), Pane::Tags);
+        app.handle_key(KeyCode::Char('G'));
+        assert_eq!(app.selection(), Some(Selection::Tag("work".to_string())));
+        assert_eq!(app.handle_key(KeyCode::Enter), Action::None);
+    }
+
+    #[test]
+    fn test_reload_keeps_selection() {
+        let mut app = app();
+        app.handle_key(KeyCode::Char('j'));
+        let mut notes = vec![note("2025-01-18.md", &[])];
+        notes.extend(app.notes.clone());
+        app.reload(notes, app.tags.clone());
+        assert_eq!(
+            app.selection(),
+            Some(Selection::Note("2025-01-16.md".to_string()))
+        );
+    }
+}
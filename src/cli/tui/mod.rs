@@ -0,0 +1,186 @@
+//! Terminal UI for browsing notes (`djour browse`)
+//!
+//! The left pane lists notes newest first (or tags, after `t`); the right pane
+//! previews the selected note, or an in-memory compilation of the selected tag.
+
+mod app;
+mod ui;
+
+pub use app::{Action, BrowseApp, NoteItem, Pane, Selection};
+
+use crate::application::{compile_to_string, count_tags, list_notes, CompileOptions, TagOptions};
+use crate::domain::tags::{tags_in_note, CompilationFormat, CompileBudget, ContextOptions};
+use crate::domain::Diagnostics;
+use crate::error::{DjourError, Result};
+use crate::infrastructure::{
+    Config, EditorRole, EditorSession, FileSystemRepository, JournalRepository,
+};
+use ratatui::crossterm::event::{self, Event, KeyEventKind};
+use ratatui::DefaultTerminal;
+use std::collections::HashMap;
+use std::io::IsTerminal;
+
+/// Run the browser until the user quits.
+///
+/// Fails immediately when stdin or stdout is not a terminal.
+pub fn run(repository: &FileSystemRepository, recursive: bool) -> Result<()> {
+    if !std::io::stdin().is_terminal() || !std::io::stdout().is_terminal() {
+        return Err(DjourError::Config(
+            "djour browse needs an interactive terminal. \
+            Use 'djour list' or 'djour compile <QUERY> --open' in scripts and pipes"
+                .to_string(),
+        ));
+    }
+
+    let config = repository.load_config()?;
+    let (notes, tags) = load(repository, &config, recursive)?;
+    let mut browser = Browser {
+        repository,
+        config,
+        recursive,
+        app: BrowseApp::new(notes, tags),
+        previews: HashMap::new(),
+    };
+
+    let mut terminal = ratatui::init();
+    let result = browser.event_loop(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+struct Browser<'a> {
+    repository: &'a FileSystemRepository,
+    config: Config,
+    recursive: bool,
+    app: BrowseApp,
+    /// Rendered previews by selection, dropped whenever a note is edited
+    previews: HashMap<Selection, String>,
+}
+
+impl Browser<'_> {
+    fn event_loop(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            let preview = self.preview();
+            terminal.draw(|frame| {
+                ui::draw(frame, &self.app, &preview, self.config.hierarchical_tags)
+            })?;
+
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match self.app.handle_key(key.code) {
+                Action::None => {}
+                Action::Quit => return Ok(()),
+                // --read-only: notes can be browsed but not edited
+                Action::Open(_) if self.repository.is_read_only() => {}
+                Action::Open(filename) => {
+                    ratatui::restore();
+                    let edited = EditorSession::for_role(&self.config, EditorRole::Editor)
+                        .edit(&self.repository.root().join(&filename));
+                    *terminal = ratatui::init();
+                    edited?;
+
+                    let (notes, tags) = load(self.repository, &self.config, self.recursive)?;
+                    self.app.reload(notes, tags);
+                    self.previews.clear();
+                }
+            }
+        }
+    }
+
+    /// Text for the right pane; problems are shown there instead of ending the session
+    fn preview(&mut self) -> String {
+        let Some(selection) = self.app.selection() else {
+            return String::new();
+        };
+        if let Some(preview) = self.previews.get(&selection) {
+            return preview.clone();
+        }
+
+        let rendered = match &selection {
+            Selection::Note(filename) => self.repository.read_note(filename),
+            Selection::Tag(tag) => {
+                let options = CompileOptions {
+                    query: tag.clone(),
+                    output: None,
+                    from: None,
+                    to: None,
+                    changed_since: None,
+                    format: CompilationFormat::Chronological,
+                    group_by: None,
+                    date_format: None,
+                    context: ContextOptions::off(),
+                    recursive: self.recursive,
+                    include_archived: false,
+                    manifest: false,
+                    strict: false,
+                    embed_links: false,
+                    notes: Vec::new(),
+                    exclude: Vec::new(),
+                    title_filter: None,
+                    attribution: true,
+                    budget: CompileBudget::unlimited(),
+                    suggest_limit: 0,
+                    include_private: false,
+                    allow_exclusion_only: false,
+                };
+                compile_to_string(self.repository, &options, &mut Diagnostics::new(), None)
+                    .map(|(markdown, _)| markdown)
+            }
+        };
+        let preview = rendered.unwrap_or_else(|e| format!("Error: {}", e));
+        self.previews.insert(selection, preview.clone());
+        preview
+    }
+}
+
+/// Tags with their counts, in display order
+type TagCounts = Vec<(String, usize)>;
+
+/// Notes (newest first, with their tags) and tag counts for the browser
+fn load(
+    repository: &FileSystemRepository,
+    config: &Config,
+    recursive: bool,
+) -> Result<(Vec<NoteItem>, TagCounts)> {
+    let entries = list_notes(
+        repository,
+        config.naming(),
+        None,
+        None,
+        None,
+        recursive,
+        false,
+        None,
+        &mut Diagnostics::new(),
+    )?;
+
+    let mut notes = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let content = repository.read_note(&entry.filename)?;
+        let tags = note_tags(&content, config);
+        notes.push(NoteItem { entry, tags });
+    }
+
+    // Same notes as the compile preview: `compile_exclude` applies
+    let options = TagOptions {
+        recursive,
+        ..TagOptions::default()
+    };
+    let tags = count_tags(repository, &options, None)?
+        .counts
+        .into_iter()
+        .collect();
+    Ok((notes, tags))
+}
+
+/// Distinct tags used in a note, lowercased and sorted
+fn note_tags(content: &str, config: &Config) -> Vec<String> {
+    let mut tags = tags_in_note(content, config.tag_parse_options());
+    tags.sort();
+    tags.dedup();
+    tags
+}
@@ -0,0 +1,122 @@
+//! Drawing the browse screen
+
+use super::app::{BrowseApp, Pane};
+use crate::domain::tags::syntax::tag_regex;
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph};
+use ratatui::Frame;
+
+const HELP: &str = "j/k move  / filter  t notes/tags  Enter open  PgUp/PgDn scroll  q quit";
+
+/// Draw the list pane, the preview pane and the help line
+pub fn draw(frame: &mut Frame, app: &BrowseApp, preview: &str, hierarchical_tags: bool) {
+    let [main, help] =
+        Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+    let [left, right] =
+        Layout::horizontal([Constraint::Percentage(35), Constraint::Percentage(65)]).areas(main);
+
+    let (title, items): (&str, Vec<ListItem>) = match app.pane() {
+        Pane::Notes => (
+            "Notes",
+            app.visible_notes()
+                .into_iter()
+                .map(|note| {
+                    let date = note
+                        .entry
+                        .date
+                        .map(|date| date.format("%d-%m-%Y  ").to_string())
+                        .unwrap_or_default();
+                    ListItem::new(Line::from(vec![
+                        Span::styled(date, Style::default().fg(Color::Cyan)),
+                        Span::raw(note.entry.filename.clone()),
+                    ]))
+                })
+                .collect(),
+        ),
+        Pane::Tags => (
+            "Tags",
+            app.visible_tags()
+                .into_iter()
+                .map(|(tag, count)| {
+                    ListItem::new(Line::from(vec![
+                        Span::styled(format!("#{}", tag), tag_style()),
+                        Span::raw(format!(" ({})", count)),
+                    ]))
+                })
+                .collect(),
+        ),
+    };
+    let title = if app.is_filtering() || !app.filter().is_empty() {
+        format!(" {} /{} ", title, app.filter())
+    } else {
+        format!(" {} ", title)
+    };
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    let mut state = ListState::default().with_selected(Some(app.selected()));
+    frame.render_stateful_widget(list, left, &mut state);
+
+    let lines: Vec<Line> = preview
+        .lines()
+        .map(|line| highlight_tags(line, hierarchical_tags))
+        .collect();
+    let preview = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL))
+        .scroll((app.scroll, 0));
+    frame.render_widget(preview, right);
+
+    frame.render_widget(
+        Paragraph::new(HELP).style(Style::default().add_modifier(Modifier::DIM)),
+        help,
+    );
+}
+
+fn tag_style() -> Style {
+    Style::default()
+        .fg(Color::Yellow)
+        .add_modifier(Modifier::BOLD)
+}
+
+/// Split a line into spans with every `#tag` highlighted
+fn highlight_tags(line: &str, hierarchical_tags: bool) -> Line<'static> {
+    let mut spans = Vec::new();
+    let mut last = 0;
+    for found in tag_regex(hierarchical_tags).find_iter(line) {
+        if found.start() > last {
+            spans.push(Span::raw(line[last..found.start()].to_string()));
+        }
+        spans.push(Span::styled(found.as_str().to_string(), tag_style()));
+        last = found.end();
+    }
+    if last < line.len() {
+        spans.push(Span::raw(line[last..].to_string()));
+    }
+    Line::from(spans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_highlight_tags_splits_spans() {
+        let line = highlight_tags("Standup #work/team done #x", true);
+        let parts: Vec<(&str, bool)> = line
+            .spans
+            .iter()
+            .map(|span| (span.content.as_ref(), span.style == tag_style()))
+            .collect();
+        assert_eq!(
+            parts,
+            vec![
+                ("Standup ", false),
+                ("#work/team", true),
+                (" done ", false),
+                ("#x", true),
+            ]
+        );
+    }
+}
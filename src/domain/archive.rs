@@ -0,0 +1,104 @@
+//! Archive layout for notes moved out of the journal root
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::str::FromStr;
+
+/// Directory (relative to the journal root) that archived notes are moved into
+pub const ARCHIVE_DIR: &str = "archive";
+
+/// Directories at the journal root holding archived and trashed notes.
+///
+/// Recursive `list`, `tags` and `compile` skip them unless `--include-archived`
+/// is given, so old content does not dominate counts and compilations.
+pub const ARCHIVED_DIRS: &[&str] = &[ARCHIVE_DIR, "trash"];
+
+/// Whether the root-relative `path` is inside one of [`ARCHIVED_DIRS`]
+pub fn is_archived_path(path: &Path) -> bool {
+    ARCHIVED_DIRS.iter().any(|dir| path.starts_with(dir))
+}
+
+/// How archived notes are grouped below [`ARCHIVE_DIR`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum ArchiveLayout {
+    /// `archive/2023/2023-05-17.md`
+    #[default]
+    #[serde(rename = "year")]
+    Year,
+    /// `archive/2023/05/2023-05-17.md`
+    #[serde(rename = "year-month")]
+    YearMonth,
+}
+
+impl ArchiveLayout {
+    /// Name as written in config.toml
+    pub fn code(&self) -> &'static str {
+        match self {
+            ArchiveLayout::Year => "year",
+            ArchiveLayout::YearMonth => "year-month",
+        }
+    }
+
+    /// Whether this is the default (yearly) layout
+    pub fn is_default(&self) -> bool {
+        *self == ArchiveLayout::Year
+    }
+
+    /// Archive path for a note file dated `date`, relative to the journal root
+    pub fn archive_path(&self, date: NaiveDate, leaf: &str) -> String {
+        match self {
+            ArchiveLayout::Year => format!("{}/{}/{}", ARCHIVE_DIR, date.format("%Y"), leaf),
+            ArchiveLayout::YearMonth => format!(
+                "{}/{}/{}/{}",
+                ARCHIVE_DIR,
+                date.format("%Y"),
+                date.format("%m"),
+                leaf
+            ),
+        }
+    }
+}
+
+impl FromStr for ArchiveLayout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "year" => Ok(ArchiveLayout::Year),
+            "year-month" => Ok(ArchiveLayout::YearMonth),
+            _ => Err(format!(
+                "Invalid archive layout: '{}'. Valid layouts are: year, year-month",
+                s
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_paths() {
+        let date = NaiveDate::from_ymd_opt(2023, 5, 17).unwrap();
+        assert_eq!(
+            ArchiveLayout::Year.archive_path(date, "2023-05-17.md"),
+            "archive/2023/2023-05-17.md"
+        );
+        assert_eq!(
+            ArchiveLayout::YearMonth.archive_path(date, "2023-05-17.md"),
+            "archive/2023/05/2023-05-17.md"
+        );
+    }
+
+    #[test]
+    fn test_parse_layout() {
+        assert_eq!(ArchiveLayout::from_str("year"), Ok(ArchiveLayout::Year));
+        assert_eq!(
+            ArchiveLayout::from_str("Year-Month"),
+            Ok(ArchiveLayout::YearMonth)
+        );
+        assert!(ArchiveLayout::from_str("decade").is_err());
+    }
+}
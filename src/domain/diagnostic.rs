@@ -0,0 +1,243 @@
+//! Non-fatal problems found while reading notes
+//!
+//! Commands collect diagnostics while they work and print them once at the end,
+//! so they never interleave with normal output.
+
+use super::markdown::unclosed_fence_line;
+use super::mode_migration::parse_weekly;
+use super::tags::section_dates;
+use super::{JournalMode, Locale, Template, WeekNumbering, WeekStart};
+use crate::error::DjourError;
+use chrono::NaiveDate;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// One problem in one note
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// Note path relative to the journal root
+    pub file: String,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{}: {}: {}", label, self.file, self.message)
+    }
+}
+
+/// Collector threaded through commands that read notes
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Diagnostics {
+    items: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    pub fn new() -> Self {
+        Diagnostics::default()
+    }
+
+    pub fn warn(&mut self, file: impl Into<String>, message: impl Into<String>) {
+        self.items.push(Diagnostic {
+            severity: Severity::Warning,
+            file: file.into(),
+            message: message.into(),
+        });
+    }
+
+    /// Move the diagnostics of `other` to the end of this collector
+    pub fn append(&mut self, mut other: Diagnostics) {
+        self.items.append(&mut other.items);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.items.iter()
+    }
+
+    /// Upgrade every warning to an error (`--strict`)
+    pub fn escalate(&mut self) {
+        for item in &mut self.items {
+            item.severity = Severity::Error;
+        }
+    }
+}
+
+/// First structural problem of a note created from a built-in template, if any.
+///
+/// Weekly notes must keep the built-in header and weekday headings (the layout
+/// date attribution and mode migration rely on); monthly notes must keep their
+/// header. Other modes have no structure to check.
+pub fn check_note_structure(
+    mode: JournalMode,
+    content: &str,
+    date: NaiveDate,
+    week: WeekStart,
+    numbering: WeekNumbering,
+    locale: Locale,
+) -> Option<String> {
+    match mode {
+        JournalMode::Weekly => parse_weekly(content, date, week, numbering, locale)
+            .err()
+            .map(|e| match e {
+                DjourError::Config(message) => message,
+                other => other.to_string(),
+            }),
+        JournalMode::Monthly => {
+            let rendered = Template::from_builtin("monthly.md")
+                .expect("built-in monthly template exists")
+                .render_with_locale(date, locale);
+            let expected = rendered.lines().next().unwrap_or_default();
+            let found = content
+                .lines()
+                .find(|line| !line.trim().is_empty())
+                .unwrap_or_default();
+            (found.trim_end() != expected).then(|| {
+                format!(
+                    "Monthly note header does not match built-in template. Expected '{}'. Found: '{}'",
+                    expected, found
+                )
+            })
+        }
+        JournalMode::Daily | JournalMode::Single => None,
+    }
+}
+
+/// Warning for a note whose code fence is never closed, if it has one
+pub fn check_unclosed_fence(content: &str) -> Option<String> {
+    unclosed_fence_line(content).map(|line| {
+        format!(
+            "unclosed code fence opened at line {}; content after it is treated as code",
+            line
+        )
+    })
+}
+
+/// Warnings for section headings whose `@DD-MM-YYYY` marker is not a valid
+/// date; such a marker stays heading text and the section keeps the note's date
+pub fn check_section_dates(content: &str) -> Vec<String> {
+    section_dates(content)
+        .into_iter()
+        .filter(|section| section.date.is_none())
+        .map(|section| {
+            format!(
+                "heading at line {}: {} is not a valid date; the section keeps the note's date",
+                section.line, section.marker
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn weekly(week_start: NaiveDate) -> String {
+        Template::from_builtin("weekly.md")
+            .unwrap()
+            .render(week_start)
+    }
+
+    #[test]
+    fn test_builtin_weekly_has_no_problem() {
+        let ws = date(2025, 1, 13);
+        let content = format!("{}Notes #work\n", weekly(ws));
+        assert_eq!(
+            check_note_structure(
+                JournalMode::Weekly,
+                &content,
+                ws,
+                WeekStart::Monday,
+                WeekNumbering::Iso,
+                Locale::English
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_mangled_weekday_heading_is_reported() {
+        let ws = date(2025, 1, 13);
+        let content = weekly(ws).replace("## Tuesday (January 14, 2025)", "## Tuesday 14 Jan");
+        let problem = check_note_structure(
+            JournalMode::Weekly,
+            &content,
+            ws,
+            WeekStart::Monday,
+            WeekNumbering::Iso,
+            Locale::English,
+        )
+        .unwrap();
+        assert!(problem.contains("Tuesday (January 14, 2025)"), "{problem}");
+    }
+
+    #[test]
+    fn test_commented_out_weekday_heading_is_not_a_duplicate() {
+        let ws = date(2025, 1, 13);
+        let content = weekly(ws).replace(
+            "## Tuesday (January 14, 2025)",
+            "<!--\n## Saturday (January 18, 2025)\n-->\n\n## Tuesday (January 14, 2025)",
+        );
+        assert_eq!(
+            check_note_structure(
+                JournalMode::Weekly,
+                &content,
+                ws,
+                WeekStart::Monday,
+                WeekNumbering::Iso,
+                Locale::English
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_monthly_header_checked() {
+        let month = date(2025, 1, 1);
+        let check = |content: &str| {
+            check_note_structure(
+                JournalMode::Monthly,
+                content,
+                month,
+                WeekStart::Monday,
+                WeekNumbering::Iso,
+                Locale::English,
+            )
+        };
+        assert_eq!(check("# January 2025\n\n## Week 1\n"), None);
+        assert!(check("# Jan 2025\n").is_some());
+    }
+
+    #[test]
+    fn test_escalate_and_display() {
+        let mut diagnostics = Diagnostics::new();
+        diagnostics.warn("2025-W03-2025-01-13.md", "bad heading");
+        assert_eq!(
+            diagnostics.iter().next().unwrap().to_string(),
+            "warning: 2025-W03-2025-01-13.md: bad heading"
+        );
+        diagnostics.escalate();
+        assert_eq!(diagnostics.iter().next().unwrap().severity, Severity::Error);
+    }
+}
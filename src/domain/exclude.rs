@@ -0,0 +1,139 @@
+//! Glob patterns naming notes that `compile` and `tags` leave out
+//!
+//! Patterns match paths relative to the journal root, written with `/`.
+//! `*` and `?` stay within one path segment, `**` spans directories and
+//! `{a,b}` lists alternatives. Matching ignores case on Windows.
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+
+/// A compiled set of exclude patterns
+#[derive(Debug, Clone, Default)]
+pub struct ExcludePatterns {
+    patterns: Vec<String>,
+    set: GlobSet,
+}
+
+impl ExcludePatterns {
+    /// Compile `patterns`; the error names the first invalid one
+    pub fn new<S: AsRef<str>>(patterns: &[S]) -> Result<Self, String> {
+        let mut builder = GlobSetBuilder::new();
+        let mut kept = Vec::with_capacity(patterns.len());
+        for pattern in patterns {
+            let pattern = pattern.as_ref().trim();
+            if pattern.is_empty() {
+                continue;
+            }
+            let glob = GlobBuilder::new(pattern.trim_start_matches("./"))
+                .literal_separator(true)
+                .case_insensitive(cfg!(windows))
+                .build()
+                .map_err(|e| format!("Invalid exclude pattern '{}': {}", pattern, e.kind()))?;
+            builder.add(glob);
+            kept.push(pattern.to_string());
+        }
+        let set = builder
+            .build()
+            .map_err(|e| format!("Invalid exclude pattern: {}", e))?;
+        Ok(ExcludePatterns {
+            patterns: kept,
+            set,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// The first pattern matching `path` (root-relative, `/` or `\` separated)
+    pub fn matching(&self, path: &str) -> Option<&str> {
+        let path = path.replace('\\', "/");
+        self.set
+            .matches(&path)
+            .first()
+            .map(|&index| self.patterns[index].as_str())
+    }
+
+    /// Whether any pattern matches `path`
+    pub fn is_match(&self, path: &str) -> bool {
+        self.matching(path).is_some()
+    }
+}
+
+/// Split a comma-separated pattern list; commas inside `{...}` belong to the pattern
+pub fn split_patterns(value: &str) -> Vec<String> {
+    let mut patterns = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0usize;
+    for c in value.chars() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                patterns.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    patterns.push(current);
+    patterns
+        .into_iter()
+        .map(|pattern| pattern.trim().to_string())
+        .filter(|pattern| !pattern.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_root_relative_paths() {
+        let patterns =
+            ExcludePatterns::new(&["2025-01-01.md", "drafts/**", "**/scratch-*.md"]).unwrap();
+        assert!(patterns.is_match("2025-01-01.md"));
+        assert!(!patterns.is_match("projects/2025-01-01.md"));
+        assert!(patterns.is_match("drafts/2025-01-02.md"));
+        assert!(patterns.is_match("drafts/old/2024-12-01.md"));
+        assert!(patterns.is_match("scratch-a.md"));
+        assert!(patterns.is_match("projects/x/scratch-b.md"));
+        assert!(!patterns.is_match("2025-01-02.md"));
+    }
+
+    #[test]
+    fn test_star_stays_within_a_directory() {
+        let patterns = ExcludePatterns::new(&["projects/*.md", "./*-01-01.md"]).unwrap();
+        assert!(patterns.is_match("projects/2025-01-15.md"));
+        assert!(!patterns.is_match("projects/alpha/2025-01-15.md"));
+        assert!(patterns.is_match("2025-01-01.md"));
+        assert!(!patterns.is_match("archive/2024-01-01.md"));
+        assert!(patterns.is_match("projects\\2025-01-15.md"));
+    }
+
+    #[test]
+    fn test_reports_matching_pattern_and_invalid_ones() {
+        let patterns = ExcludePatterns::new(&["", "{2024,2025}-01-01.md"]).unwrap();
+        assert_eq!(
+            patterns.matching("2024-01-01.md"),
+            Some("{2024,2025}-01-01.md")
+        );
+        assert!(ExcludePatterns::new::<&str>(&[]).unwrap().is_empty());
+
+        let err = ExcludePatterns::new(&["notes/[a-"]).unwrap_err();
+        assert!(
+            err.starts_with("Invalid exclude pattern 'notes/[a-'"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_split_patterns_keeps_braces_together() {
+        assert_eq!(
+            split_patterns(" 2025-01-01.md, {a,b}/*.md ,,drafts/**"),
+            vec!["2025-01-01.md", "{a,b}/*.md", "drafts/**"]
+        );
+        assert!(split_patterns("").is_empty());
+    }
+}
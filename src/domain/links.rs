@@ -0,0 +1,519 @@
+//! Links between journal files: classifying link targets, resolving them
+//! against the file they appear in, and rewriting them.
+//!
+//! Used when compiled blocks move to another directory and when a note is renamed.
+
+use crate::domain::markdown::parse_fence_marker;
+use regex::Regex;
+use std::borrow::Cow;
+use std::ffi::{OsStr, OsString};
+use std::path::{Component, Path, PathBuf};
+use std::sync::OnceLock;
+
+fn html_attr_double_quote_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| Regex::new(r#"(?i)\b(src|href)\s*=\s*"([^"]+)""#).unwrap())
+}
+
+fn html_attr_single_quote_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| Regex::new(r#"(?i)\b(src|href)\s*=\s*'([^']+)'"#).unwrap())
+}
+
+fn markdown_link_or_image_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(
+            r#"(?P<prefix>!?\[[^\]\n]*\]\()(?P<dest><[^>\n]+>|[^)\s]+)(?P<title>\s+"[^"\n]*")?(?P<suffix>\))"#,
+        )
+        .unwrap()
+    })
+}
+
+fn markdown_reference_definition_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(
+            r#"(?m)^(?P<prefix>[ \t]{0,3}\[[^\]\n]+\]:[ \t]*)(?P<dest><[^>\n]+>|\S+)(?P<suffix>[^\n]*)$"#,
+        )
+        .unwrap()
+    })
+}
+fn has_uri_scheme(target: &str) -> bool {
+    let mut chars = target.chars();
+    let Some(first) = chars.next() else {
+        return false;
+    };
+
+    if !first.is_ascii_alphabetic() {
+        return false;
+    }
+
+    for c in chars {
+        match c {
+            ':' => return true,
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '+' | '-' | '.' => {}
+            _ => return false,
+        }
+    }
+
+    false
+}
+
+fn split_target_suffix(target: &str) -> (&str, &str) {
+    let query_pos = target.find('?');
+    let fragment_pos = target.find('#');
+
+    let split_pos = match (query_pos, fragment_pos) {
+        (Some(q), Some(f)) => Some(q.min(f)),
+        (Some(q), None) => Some(q),
+        (None, Some(f)) => Some(f),
+        (None, None) => None,
+    };
+
+    match split_pos {
+        Some(pos) => (&target[..pos], &target[pos..]),
+        None => (target, ""),
+    }
+}
+
+fn normalize_components(path: &Path) -> (Option<OsString>, bool, Vec<OsString>) {
+    let mut prefix: Option<OsString> = None;
+    let mut has_root = false;
+    let mut segments: Vec<OsString> = Vec::new();
+
+    for component in path.components() {
+        match component {
+            Component::Prefix(p) => prefix = Some(p.as_os_str().to_os_string()),
+            Component::RootDir => has_root = true,
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if let Some(last) = segments.last() {
+                    if last != OsStr::new("..") {
+                        segments.pop();
+                        continue;
+                    }
+                }
+
+                if !has_root {
+                    segments.push(OsString::from(".."));
+                }
+            }
+            Component::Normal(seg) => segments.push(seg.to_os_string()),
+        }
+    }
+
+    (prefix, has_root, segments)
+}
+
+pub(crate) fn relative_path(from: &Path, to: &Path) -> Option<PathBuf> {
+    let (from_prefix, from_root, from_segments) = normalize_components(from);
+    let (to_prefix, to_root, to_segments) = normalize_components(to);
+
+    if from_prefix != to_prefix || from_root != to_root {
+        return None;
+    }
+
+    let mut common_len = 0usize;
+    let max_common = from_segments.len().min(to_segments.len());
+    while common_len < max_common && from_segments[common_len] == to_segments[common_len] {
+        common_len += 1;
+    }
+
+    let mut rel = PathBuf::new();
+    for _ in common_len..from_segments.len() {
+        rel.push("..");
+    }
+    for segment in &to_segments[common_len..] {
+        rel.push(segment);
+    }
+
+    if rel.as_os_str().is_empty() {
+        rel.push(".");
+    }
+
+    Some(rel)
+}
+
+/// Whether a link target is a path relative to the note it appears in
+/// (not empty, an anchor, an absolute path or a URI).
+fn is_relative_target(target: &str) -> bool {
+    !(target.is_empty()
+        || target.starts_with('#')
+        || target.starts_with('?')
+        || target.starts_with("//")
+        || target.starts_with('/')
+        || target.starts_with('\\')
+        || has_uri_scheme(target))
+}
+
+/// Journal-relative path of the markdown note a link in `source_file` points at.
+///
+/// Uses the same internal/external classification as link rewriting. Targets
+/// that leave the journal root or do not end in `.md` give `None`.
+pub(crate) fn linked_note_path(target: &str, source_file: &Path) -> Option<PathBuf> {
+    if !is_relative_target(target) {
+        return None;
+    }
+    let (path_part, _) = split_target_suffix(target);
+    if path_part.is_empty() {
+        return None;
+    }
+
+    let source_dir = source_file.parent().unwrap_or_else(|| Path::new(""));
+    let (prefix, has_root, segments) = normalize_components(&source_dir.join(path_part));
+    if prefix.is_some() || has_root || segments.first().is_none_or(|first| first == "..") {
+        return None;
+    }
+
+    let path: PathBuf = segments.iter().collect();
+    let is_markdown = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("md"));
+    is_markdown.then_some(path)
+}
+
+/// Destinations of inline links (not images) outside fenced code blocks, in order
+pub(crate) fn inline_link_targets(markdown: &str) -> Vec<String> {
+    let mut targets = Vec::new();
+    rewrite_outside_fenced_code_blocks(markdown, |chunk| {
+        for caps in markdown_link_or_image_regex().captures_iter(chunk) {
+            if caps["prefix"].starts_with('!') {
+                continue;
+            }
+            let destination = &caps["dest"];
+            let destination = destination
+                .strip_prefix('<')
+                .and_then(|d| d.strip_suffix('>'))
+                .unwrap_or(destination);
+            targets.push(destination.to_string());
+        }
+        chunk.to_string()
+    });
+    targets
+}
+
+fn rewrite_link_target(target: &str, source_file: &Path, output_file: Option<&Path>) -> String {
+    let Some(output_file) = output_file else {
+        return target.to_string();
+    };
+
+    if !is_relative_target(target) {
+        return target.to_string();
+    }
+
+    let (path_part, suffix) = split_target_suffix(target);
+    if path_part.is_empty() {
+        return target.to_string();
+    }
+
+    let source_dir = source_file.parent().unwrap_or_else(|| Path::new(""));
+    let output_dir = output_file.parent().unwrap_or_else(|| Path::new(""));
+    let target_abs = source_dir.join(path_part);
+
+    if let Some(rel) = relative_path(output_dir, &target_abs) {
+        let mut rewritten = rel.to_string_lossy().replace('\\', "/");
+        rewritten.push_str(suffix);
+        rewritten
+    } else {
+        target.to_string()
+    }
+}
+
+/// Apply `rewrite_chunk` to the parts of `markdown` outside fenced code blocks
+pub(crate) fn rewrite_outside_fenced_code_blocks<F>(markdown: &str, mut rewrite_chunk: F) -> String
+where
+    F: FnMut(&str) -> String,
+{
+    let mut rewritten = String::new();
+    let mut pending_chunk = String::new();
+    let mut active_fence: Option<(char, usize)> = None;
+
+    for line in markdown.split_inclusive('\n') {
+        if let Some((fence_char, min_len)) = active_fence {
+            rewritten.push_str(line);
+            if let Some((marker_char, marker_len)) = parse_fence_marker(line) {
+                if marker_char == fence_char && marker_len >= min_len {
+                    active_fence = None;
+                }
+            }
+            continue;
+        }
+
+        if let Some((fence_char, marker_len)) = parse_fence_marker(line) {
+            if !pending_chunk.is_empty() {
+                rewritten.push_str(&rewrite_chunk(&pending_chunk));
+                pending_chunk.clear();
+            }
+            rewritten.push_str(line);
+            active_fence = Some((fence_char, marker_len));
+            continue;
+        }
+
+        pending_chunk.push_str(line);
+    }
+
+    if !pending_chunk.is_empty() {
+        rewritten.push_str(&rewrite_chunk(&pending_chunk));
+    }
+
+    rewritten
+}
+
+/// Rewrite every link destination of `markdown` outside fenced code blocks:
+/// inline links and images, reference definitions and HTML `src`/`href` attributes.
+///
+/// `rewrite` gets each destination (without `<>`) and returns its replacement,
+/// or None to leave the link as written.
+pub(crate) fn rewrite_link_destinations<F>(markdown: &str, mut rewrite: F) -> String
+where
+    F: FnMut(&str) -> Option<String>,
+{
+    // `<dest>` keeps its brackets around the replacement
+    fn replace_wrapped<F>(destination_raw: &str, rewrite: &mut F) -> Option<String>
+    where
+        F: FnMut(&str) -> Option<String>,
+    {
+        match destination_raw
+            .strip_prefix('<')
+            .and_then(|inner| inner.strip_suffix('>'))
+        {
+            Some(inner) => rewrite(inner).map(|rewritten| format!("<{}>", rewritten)),
+            None => rewrite(destination_raw),
+        }
+    }
+
+    rewrite_outside_fenced_code_blocks(markdown, |chunk| {
+        let rewritten_links = markdown_link_or_image_regex()
+            .replace_all(chunk, |caps: &regex::Captures<'_>| {
+                let Some(destination) = replace_wrapped(&caps["dest"], &mut rewrite) else {
+                    return caps[0].to_string();
+                };
+                let title = caps.name("title").map_or("", |m| m.as_str());
+                format!(
+                    "{}{}{}{}",
+                    &caps["prefix"], destination, title, &caps["suffix"]
+                )
+            })
+            .to_string();
+
+        let rewritten_reference_definitions = markdown_reference_definition_regex()
+            .replace_all(&rewritten_links, |caps: &regex::Captures<'_>| {
+                let Some(destination) = replace_wrapped(&caps["dest"], &mut rewrite) else {
+                    return caps[0].to_string();
+                };
+                format!("{}{}{}", &caps["prefix"], destination, &caps["suffix"])
+            })
+            .to_string();
+
+        let rewritten_double = html_attr_double_quote_regex()
+            .replace_all(
+                &rewritten_reference_definitions,
+                |caps: &regex::Captures<'_>| match rewrite(&caps[2]) {
+                    Some(destination) => format!(r#"{}="{}""#, &caps[1], destination),
+                    None => caps[0].to_string(),
+                },
+            )
+            .to_string();
+
+        html_attr_single_quote_regex()
+            .replace_all(
+                &rewritten_double,
+                |caps: &regex::Captures<'_>| match rewrite(&caps[2]) {
+                    Some(destination) => format!("{}='{}'", &caps[1], destination),
+                    None => caps[0].to_string(),
+                },
+            )
+            .to_string()
+    })
+}
+
+/// Make the relative links of `markdown`, written for `source_file`, work from `output_file`
+///
+/// Borrows `markdown` when nothing can change: without an output file, or
+/// when it has no link syntax at all.
+pub(crate) fn rewrite_markdown_targets<'a>(
+    markdown: &'a str,
+    source_file: &Path,
+    output_file: Option<&Path>,
+) -> Cow<'a, str> {
+    if output_file.is_none() || !may_contain_links(markdown) {
+        return Cow::Borrowed(markdown);
+    }
+    Cow::Owned(rewrite_link_destinations(markdown, |target| {
+        Some(rewrite_link_target(target, source_file, output_file))
+    }))
+}
+
+/// Cheap check that `markdown` has none of the syntax [`rewrite_link_destinations`]
+/// looks for: `](` links, `]:` reference definitions, `=` of HTML attributes
+fn may_contain_links(markdown: &str) -> bool {
+    markdown.contains("](") || markdown.contains("]:") || markdown.contains('=')
+}
+
+/// Point the links of `markdown` that resolve to the journal-relative path
+/// `from` at `to` instead, keeping anchors and queries.
+///
+/// The links are resolved against `source_file`; when the file itself moves,
+/// `moved_to` is its new path and its other relative links are rebased too.
+/// Returns the rewritten markdown and how many links changed.
+pub(crate) fn retarget_links(
+    markdown: &str,
+    source_file: &Path,
+    moved_to: Option<&Path>,
+    from: &Path,
+    to: &Path,
+) -> (String, usize) {
+    let source_dir = source_file.parent().unwrap_or_else(|| Path::new(""));
+    let new_dir = moved_to.and_then(Path::parent).unwrap_or(source_dir);
+    let rebase = new_dir != source_dir;
+    let from = normalize_components(from);
+
+    let mut changed = 0usize;
+    let rewritten = rewrite_link_destinations(markdown, |target| {
+        if !is_relative_target(target) {
+            return None;
+        }
+        let (path_part, suffix) = split_target_suffix(target);
+        if path_part.is_empty() {
+            return None;
+        }
+
+        let linked = source_dir.join(path_part);
+        let destination = if normalize_components(&linked) == from {
+            to
+        } else if rebase {
+            &linked
+        } else {
+            return None;
+        };
+        let mut rewritten = relative_path(new_dir, destination)?
+            .to_string_lossy()
+            .replace('\\', "/");
+        rewritten.push_str(suffix);
+        if rewritten == target {
+            return None;
+        }
+        changed += 1;
+        Some(rewritten)
+    });
+    (rewritten, changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_reference_link_definition_rewritten_for_output_file() {
+        let temp = TempDir::new().unwrap();
+        let source = temp.path().join("2025-01-15.md");
+        let output = temp.path().join(".compilations").join("work.md");
+        let markdown = r#"
+[Design Doc][design] #work
+
+[design]: ./docs/design.md
+[site]: https://example.com/docs
+"#;
+
+        let rewritten = rewrite_markdown_targets(markdown, &source, Some(&output));
+
+        assert!(rewritten.contains("[design]: ../docs/design.md"));
+        assert!(rewritten.contains("[site]: https://example.com/docs"));
+    }
+
+    #[test]
+    fn test_fenced_code_block_links_not_rewritten_for_output_file() {
+        let temp = TempDir::new().unwrap();
+        let source = temp.path().join("2025-01-15.md");
+        let output = temp.path().join(".compilations").join("work.md");
+        let markdown = r#"
+See [Design Doc](./docs/design.md). #work
+
+```md
+![Diagram](./images/diagram.png)
+```
+"#;
+
+        let rewritten = rewrite_markdown_targets(markdown, &source, Some(&output));
+
+        assert!(rewritten.contains("[Design Doc](../docs/design.md)"));
+        assert!(rewritten.contains("![Diagram](./images/diagram.png)"));
+        assert!(!rewritten.contains("![Diagram](../images/diagram.png)"));
+    }
+
+    #[test]
+    fn test_linked_note_path_classification() {
+        let source = Path::new("daily/2025-01-15.md");
+        assert_eq!(
+            linked_note_path("../design/auth.md#login", source),
+            Some(PathBuf::from("design/auth.md"))
+        );
+        assert_eq!(
+            linked_note_path("notes.MD", source),
+            Some(PathBuf::from("daily/notes.MD"))
+        );
+        assert_eq!(linked_note_path("https://example.com/a.md", source), None);
+        assert_eq!(linked_note_path("/etc/a.md", source), None);
+        assert_eq!(linked_note_path("#heading", source), None);
+        assert_eq!(linked_note_path("../../outside.md", source), None);
+        assert_eq!(linked_note_path("image.png", source), None);
+    }
+
+    #[test]
+    fn test_inline_link_targets_skip_images_and_code() {
+        let markdown = "See [a](a.md) and ![img](b.md) [c](<c d.md>)\n```\n[x](x.md)\n```\n";
+        assert_eq!(inline_link_targets(markdown), vec!["a.md", "c d.md"]);
+    }
+
+    fn retarget(markdown: &str, source: &str, moved_to: Option<&str>) -> (String, usize) {
+        retarget_links(
+            markdown,
+            Path::new(source),
+            moved_to.map(Path::new),
+            Path::new("2025-01-18.md"),
+            Path::new("2025-01-17.md"),
+        )
+    }
+
+    #[test]
+    fn test_retarget_keeps_anchors_queries_and_other_links() {
+        let markdown = "[a](2025-01-18.md#standup) [b](./2025-01-18.md?plain) [c](other.md)\n\n[ref]: <2025-01-18.md>\n";
+        let (out, changed) = retarget(markdown, "2025-01-20.md", None);
+        assert_eq!(changed, 3);
+        assert_eq!(
+            out,
+            "[a](2025-01-17.md#standup) [b](2025-01-17.md?plain) [c](other.md)\n\n[ref]: <2025-01-17.md>\n"
+        );
+    }
+
+    #[test]
+    fn test_retarget_from_subdirectory_and_html() {
+        let markdown = "![img](../2025-01-18.md) <a href='../2025-01-18.md#x'>old</a> <img src=\"../2025-01-18.md\">\n```\n[code](../2025-01-18.md)\n```\n";
+        let (out, changed) = retarget(markdown, "projects/alpha.md", None);
+        assert_eq!(changed, 3);
+        assert_eq!(
+            out,
+            "![img](../2025-01-17.md) <a href='../2025-01-17.md#x'>old</a> <img src=\"../2025-01-17.md\">\n```\n[code](../2025-01-18.md)\n```\n"
+        );
+        assert_eq!(
+            retarget("[x](2025-01-18.md)", "projects/alpha.md", None).1,
+            0
+        );
+    }
+
+    #[test]
+    fn test_moved_note_rebases_its_own_links() {
+        let (out, changed) = retarget(
+            "[up](projects/alpha.md) [web](https://example.com) [top](#top)",
+            "2025-01-18.md",
+            Some("archive/2025-01-17.md"),
+        );
+        assert_eq!(changed, 1);
+        assert_eq!(
+            out,
+            "[up](../projects/alpha.md) [web](https://example.com) [top](#top)"
+        );
+    }
+}
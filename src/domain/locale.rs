@@ -0,0 +1,338 @@
+//! Locale tables for weekday/month names and time reference keywords
+//!
+//! English is always available; the configured locale adds its own names on top.
+
+use chrono::{Datelike, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Languages supported for date names and time reference keywords
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum Locale {
+    /// English (always accepted when parsing)
+    #[default]
+    #[serde(rename = "en")]
+    English,
+    /// German
+    #[serde(rename = "de")]
+    German,
+    /// French
+    #[serde(rename = "fr")]
+    French,
+    /// Spanish
+    #[serde(rename = "es")]
+    Spanish,
+    /// Turkish
+    #[serde(rename = "tr")]
+    Turkish,
+}
+
+const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+impl Locale {
+    /// Short locale code as written in config.toml (e.g., "de")
+    pub fn code(&self) -> &'static str {
+        match self {
+            Locale::English => "en",
+            Locale::German => "de",
+            Locale::French => "fr",
+            Locale::Spanish => "es",
+            Locale::Turkish => "tr",
+        }
+    }
+
+    /// Whether this is the default (English) locale
+    pub fn is_default(&self) -> bool {
+        *self == Locale::English
+    }
+
+    /// Month names, January..December
+    fn month_names(&self) -> [&'static str; 12] {
+        match self {
+            Locale::English => [
+                "January",
+                "February",
+                "March",
+                "April",
+                "May",
+                "June",
+                "July",
+                "August",
+                "September",
+                "October",
+                "November",
+                "December",
+            ],
+            Locale::German => [
+                "Januar",
+                "Februar",
+                "März",
+                "April",
+                "Mai",
+                "Juni",
+                "Juli",
+                "August",
+                "September",
+                "Oktober",
+                "November",
+                "Dezember",
+            ],
+            Locale::French => [
+                "janvier",
+                "février",
+                "mars",
+                "avril",
+                "mai",
+                "juin",
+                "juillet",
+                "août",
+                "septembre",
+                "octobre",
+                "novembre",
+                "décembre",
+            ],
+            Locale::Spanish => [
+                "enero",
+                "febrero",
+                "marzo",
+                "abril",
+                "mayo",
+                "junio",
+                "julio",
+                "agosto",
+                "septiembre",
+                "octubre",
+                "noviembre",
+                "diciembre",
+            ],
+            Locale::Turkish => [
+                "Ocak", "Şubat", "Mart", "Nisan", "Mayıs", "Haziran", "Temmuz", "Ağustos", "Eylül",
+                "Ekim", "Kasım", "Aralık",
+            ],
+        }
+    }
+
+    /// Weekday names, Monday..Sunday
+    fn weekday_names(&self) -> [&'static str; 7] {
+        match self {
+            Locale::English => [
+                "Monday",
+                "Tuesday",
+                "Wednesday",
+                "Thursday",
+                "Friday",
+                "Saturday",
+                "Sunday",
+            ],
+            Locale::German => [
+                "Montag",
+                "Dienstag",
+                "Mittwoch",
+                "Donnerstag",
+                "Freitag",
+                "Samstag",
+                "Sonntag",
+            ],
+            Locale::French => [
+                "lundi", "mardi", "mercredi", "jeudi", "vendredi", "samedi", "dimanche",
+            ],
+            Locale::Spanish => [
+                "lunes",
+                "martes",
+                "miércoles",
+                "jueves",
+                "viernes",
+                "sábado",
+                "domingo",
+            ],
+            Locale::Turkish => [
+                "Pazartesi",
+                "Salı",
+                "Çarşamba",
+                "Perşembe",
+                "Cuma",
+                "Cumartesi",
+                "Pazar",
+            ],
+        }
+    }
+
+    /// Localized month name for a 1-based month number
+    pub fn month_name(&self, month: u32) -> &'static str {
+        self.month_names()[(month as usize).clamp(1, 12) - 1]
+    }
+
+    /// Localized weekday name
+    pub fn weekday_name(&self, weekday: Weekday) -> &'static str {
+        self.weekday_names()[weekday.num_days_from_monday() as usize]
+    }
+
+    /// Format a date as "<Month> <DD>, <YYYY>" using localized month names
+    pub fn format_long_date(&self, date: NaiveDate) -> String {
+        format!(
+            "{} {:02}, {}",
+            self.month_name(date.month()),
+            date.day(),
+            date.year()
+        )
+    }
+
+    /// `date` in the strftime `format`, with weekday and month names (`%A`,
+    /// `%a`, `%B`, `%b`, `%h`) in this locale; abbreviations are the first
+    /// three letters of the name. Fails as chrono does on an invalid format.
+    pub fn format_date(&self, date: NaiveDate, format: &str) -> Result<String, std::fmt::Error> {
+        use std::fmt::Write;
+
+        let mut localized = String::with_capacity(format.len());
+        if self.is_default() {
+            localized.push_str(format);
+        } else {
+            let short = |name: &str| name.chars().take(3).collect::<String>();
+            let mut chars = format.chars();
+            while let Some(c) = chars.next() {
+                if c != '%' {
+                    localized.push(c);
+                    continue;
+                }
+                match chars.next() {
+                    Some('A') => localized.push_str(self.weekday_name(date.weekday())),
+                    Some('a') => localized.push_str(&short(self.weekday_name(date.weekday()))),
+                    Some('B') => localized.push_str(self.month_name(date.month())),
+                    Some('b' | 'h') => localized.push_str(&short(self.month_name(date.month()))),
+                    Some(other) => {
+                        localized.push('%');
+                        localized.push(other);
+                    }
+                    None => localized.push('%'),
+                }
+            }
+        }
+
+        let mut formatted = String::new();
+        write!(formatted, "{}", date.format(&localized))?;
+        Ok(formatted)
+    }
+
+    /// Look up a weekday by its (lowercase) name in this locale
+    pub fn parse_weekday(&self, name: &str) -> Option<Weekday> {
+        self.weekday_names()
+            .iter()
+            .position(|candidate| candidate.to_lowercase() == name)
+            .map(|idx| WEEKDAYS[idx])
+    }
+
+    /// Keywords for today, yesterday, tomorrow
+    pub(crate) fn relative_day_keywords(
+        &self,
+    ) -> (&'static [&'static str], &'static str, &'static str) {
+        match self {
+            Locale::English => (&["today", "now"], "yesterday", "tomorrow"),
+            Locale::German => (&["heute", "jetzt"], "gestern", "morgen"),
+            Locale::French => (&["aujourd'hui", "maintenant"], "hier", "demain"),
+            Locale::Spanish => (&["hoy", "ahora"], "ayer", "mañana"),
+            Locale::Turkish => (&["bugün", "şimdi"], "dün", "yarın"),
+        }
+    }
+
+    /// Words marking the previous occurrence of a weekday ("last")
+    pub(crate) fn last_keywords(&self) -> &'static [&'static str] {
+        match self {
+            Locale::English => &["last"],
+            Locale::German => &["letzter", "letzten", "letzte", "vergangener", "vergangenen"],
+            Locale::French => &["dernier"],
+            Locale::Spanish => &["pasado", "último", "ultimo"],
+            Locale::Turkish => &["geçen", "gecen"],
+        }
+    }
+
+    /// Words marking the next occurrence of a weekday ("next")
+    pub(crate) fn next_keywords(&self) -> &'static [&'static str] {
+        match self {
+            Locale::English => &["next"],
+            Locale::German => &["nächster", "nächsten", "nächste", "naechster", "naechsten"],
+            Locale::French => &["prochain"],
+            Locale::Spanish => &["próximo", "proximo", "siguiente"],
+            Locale::Turkish => &["gelecek", "önümüzdeki"],
+        }
+    }
+}
+
+impl FromStr for Locale {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "en" | "english" => Ok(Locale::English),
+            "de" | "german" | "deutsch" => Ok(Locale::German),
+            "fr" | "french" | "français" => Ok(Locale::French),
+            "es" | "spanish" | "español" => Ok(Locale::Spanish),
+            "tr" | "turkish" | "türkçe" => Ok(Locale::Turkish),
+            _ => Err(format!(
+                "Invalid locale: '{}'. Valid locales are: en, de, fr, es, tr",
+                s
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_date_localizes_names() {
+        let date = NaiveDate::from_ymd_opt(2025, 3, 5).unwrap();
+        let format = "%a %A, %d %b %B %Y (100%%)";
+        assert_eq!(
+            Locale::English.format_date(date, format).unwrap(),
+            "Wed Wednesday, 05 Mar March 2025 (100%)"
+        );
+        assert_eq!(
+            Locale::French.format_date(date, format).unwrap(),
+            "mer mercredi, 05 mar mars 2025 (100%)"
+        );
+        assert!(Locale::German.format_date(date, "%H:%M").is_err());
+    }
+
+    #[test]
+    fn test_month_and_weekday_names() {
+        assert_eq!(Locale::German.month_name(1), "Januar");
+        assert_eq!(Locale::German.weekday_name(Weekday::Mon), "Montag");
+        assert_eq!(Locale::English.month_name(12), "December");
+        assert_eq!(Locale::French.weekday_name(Weekday::Sun), "dimanche");
+    }
+
+    #[test]
+    fn test_format_long_date_matches_english_chrono_format() {
+        let date = NaiveDate::from_ymd_opt(2025, 1, 7).unwrap();
+        assert_eq!(
+            Locale::English.format_long_date(date),
+            date.format("%B %d, %Y").to_string()
+        );
+        assert_eq!(Locale::German.format_long_date(date), "Januar 07, 2025");
+    }
+
+    #[test]
+    fn test_parse_weekday_case_insensitive_input() {
+        assert_eq!(Locale::German.parse_weekday("montag"), Some(Weekday::Mon));
+        assert_eq!(Locale::Turkish.parse_weekday("salı"), Some(Weekday::Tue));
+        assert_eq!(Locale::German.parse_weekday("monday"), None);
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!(Locale::from_str("DE").unwrap(), Locale::German);
+        assert_eq!(Locale::from_str("en").unwrap(), Locale::English);
+        assert!(Locale::from_str("xx")
+            .unwrap_err()
+            .contains("Invalid locale"));
+    }
+}
@@ -0,0 +1,445 @@
+//! Line-level markdown scanning shared by the tag parser and mode migration
+
+use pulldown_cmark::{CodeBlockKind, Event, Parser as MdParser, Tag, TagEnd};
+use std::ops::Range;
+
+/// Fence character and length of a code fence marker line (up to three spaces of indent)
+pub(crate) fn parse_fence_marker(line: &str) -> Option<(char, usize)> {
+    let line = line.trim_end_matches(['\r', '\n']);
+    let trimmed = line.trim_start_matches(' ');
+    let leading_spaces = line.len().saturating_sub(trimmed.len());
+    if leading_spaces > 3 {
+        return None;
+    }
+
+    let mut chars = trimmed.chars();
+    let fence_char = chars.next()?;
+    if fence_char != '`' && fence_char != '~' {
+        return None;
+    }
+
+    let count = trimmed.chars().take_while(|c| *c == fence_char).count();
+    if count < 3 {
+        return None;
+    }
+
+    Some((fence_char, count))
+}
+
+/// Line number (1-based) of a code fence that is still open at the end of
+/// `markdown`, if any: everything after it parses as code
+pub(crate) fn unclosed_fence_line(markdown: &str) -> Option<usize> {
+    MarkdownRegions::scan(markdown)
+        .unclosed_fence
+        .map(|start| markdown[..start].matches('\n').count() + 1)
+}
+
+/// Indentation and level of an ATX heading line (`## Title`)
+pub(crate) fn atx_heading(line: &str) -> Option<(usize, usize)> {
+    let trimmed = line.trim_start_matches(' ');
+    let indent = line.len() - trimmed.len();
+    if indent > 3 {
+        return None;
+    }
+    let level = trimmed.bytes().take_while(|b| *b == b'#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    match trimmed.as_bytes().get(level) {
+        None | Some(b' ' | b'\t' | b'\r' | b'\n') => Some((indent, level)),
+        _ => None,
+    }
+}
+
+/// Where a markdown text has code and HTML comments, and whether a code fence
+/// is left open at its end, found in one scan.
+///
+/// Code blocks and code spans come from the markdown parser, so a `<!--` in
+/// code never opens a comment. Comments are then found in the rest of the
+/// text, so one opened mid-paragraph still hides the heading lines after it,
+/// and a code fence inside a comment opens nothing.
+pub(crate) struct MarkdownRegions {
+    /// Code blocks and inline code spans, sorted and merged
+    pub code: Vec<Range<usize>>,
+    /// `<!-- ... -->` comments; they may span lines and do not nest, and an
+    /// unclosed one runs to the end of the text
+    pub comments: Vec<Range<usize>>,
+    /// Start of a fenced code block still open at the end of the text
+    unclosed_fence: Option<usize>,
+}
+
+impl MarkdownRegions {
+    pub(crate) fn scan(markdown: &str) -> Self {
+        let mut code: Vec<Range<usize>> = Vec::new();
+        // Fenced code blocks, and whether each has its closing fence
+        let mut fences: Vec<(Range<usize>, bool)> = Vec::new();
+        // Start of the open code block, fenced or not, and where its content ends so far
+        let mut open_block: Option<(usize, bool, usize)> = None;
+
+        for (event, range) in MdParser::new(markdown).into_offset_iter() {
+            match event {
+                Event::Start(Tag::CodeBlock(kind)) => {
+                    let first_line_end = markdown[range.clone()]
+                        .find('\n')
+                        .map_or(range.end, |end| range.start + end + 1);
+                    let fenced = matches!(kind, CodeBlockKind::Fenced(_));
+                    open_block = Some((range.start, fenced, first_line_end));
+                }
+                Event::Text(_) => {
+                    if let Some((_, _, content_end)) = open_block.as_mut() {
+                        *content_end = range.end;
+                    }
+                }
+                Event::End(TagEnd::CodeBlock) => {
+                    if let Some((start, fenced, content_end)) = open_block.take() {
+                        code.push(start..range.end);
+                        if fenced {
+                            // A closing fence is what follows the content
+                            let closed = !markdown[content_end.min(range.end)..range.end]
+                                .trim()
+                                .is_empty();
+                            fences.push((start..range.end, closed));
+                        }
+                    }
+                }
+                Event::Code(_) => code.push(range),
+                _ => {}
+            }
+        }
+
+        let code = merge_ranges(code);
+        let comments = comment_regions(markdown, &code);
+        // Fences inside a comment are commented out, not code
+        let code: Vec<Range<usize>> = code
+            .into_iter()
+            .filter(|range| !in_regions(&comments, range.start))
+            .collect();
+        let unclosed_fence = fences
+            .into_iter()
+            .find(|(range, closed)| {
+                !closed
+                    && markdown[range.end..].trim().is_empty()
+                    && !in_regions(&comments, range.start)
+            })
+            .map(|(range, _)| range.start);
+
+        MarkdownRegions {
+            code,
+            comments,
+            unclosed_fence,
+        }
+    }
+
+    /// Whether byte offset `offset` lies in code or in a comment
+    pub(crate) fn hides(&self, offset: usize) -> bool {
+        in_regions(&self.code, offset) || in_regions(&self.comments, offset)
+    }
+}
+
+/// `<!-- ... -->` comments of `markdown` whose `<!--` is outside `code`
+fn comment_regions(markdown: &str, code: &[Range<usize>]) -> Vec<Range<usize>> {
+    let mut regions = Vec::new();
+    let mut cursor = 0;
+    while let Some(pos) = markdown[cursor..].find("<!--") {
+        let start = cursor + pos;
+        if in_regions(code, start) {
+            cursor = start + 4;
+            continue;
+        }
+        match markdown[start + 4..].find("-->") {
+            Some(pos) => {
+                cursor = start + 4 + pos + 3;
+                regions.push(start..cursor);
+            }
+            None => {
+                regions.push(start..markdown.len());
+                break;
+            }
+        }
+    }
+    regions
+}
+
+/// Byte ranges of `<!-- ... -->` comments outside code (see [`MarkdownRegions`])
+pub(crate) fn html_comment_regions(markdown: &str) -> Vec<Range<usize>> {
+    MarkdownRegions::scan(markdown).comments
+}
+
+/// Byte ranges of the code blocks and inline code spans of `markdown`, sorted and merged
+pub(crate) fn code_ranges(markdown: &str) -> Vec<Range<usize>> {
+    MarkdownRegions::scan(markdown).code
+}
+
+fn merge_ranges(mut ranges: Vec<Range<usize>>) -> Vec<Range<usize>> {
+    if ranges.is_empty() {
+        return ranges;
+    }
+
+    ranges.sort_by(|a, b| a.start.cmp(&b.start).then(a.end.cmp(&b.end)));
+
+    let mut merged: Vec<Range<usize>> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        if let Some(last) = merged.last_mut() {
+            if range.start <= last.end {
+                if range.end > last.end {
+                    last.end = range.end;
+                }
+            } else {
+                merged.push(range);
+            }
+        } else {
+            merged.push(range);
+        }
+    }
+
+    merged
+}
+
+/// Whether byte offset `offset` lies inside one of `regions`
+pub(crate) fn in_regions(regions: &[Range<usize>], offset: usize) -> bool {
+    regions.iter().any(|region| region.contains(&offset))
+}
+
+/// `transformed` with the final newline of `original`: every write keeps a
+/// note's last line terminated, or unterminated, as it was.
+///
+/// A missing newline is added in the style of `original`'s line breaks
+/// (`\n` when it has none); an unwanted one is removed with any blank lines
+/// before it. An empty `original` has no last line, so nothing is changed.
+pub(crate) fn preserve_final_newline(original: &str, transformed: String) -> String {
+    let wants_newline = original.ends_with('\n');
+    if original.is_empty() || transformed.is_empty() || transformed.ends_with('\n') == wants_newline
+    {
+        return transformed;
+    }
+
+    if wants_newline {
+        let mut transformed = transformed;
+        transformed.push_str(if original.contains("\r\n") {
+            "\r\n"
+        } else {
+            "\n"
+        });
+        transformed
+    } else {
+        transformed.trim_end_matches(['\r', '\n']).to_string()
+    }
+}
+
+/// Replace the lines between the marker lines `start` and `end` with `block`.
+///
+/// Without either marker, the markers and `block` are appended after a blank
+/// line. Everything outside the markers is kept byte for byte, and `block`
+/// takes the line breaks `text` uses (`\n` when it has none). Fails, naming
+/// the problem, unless `text` has no markers or exactly one `start` line
+/// followed by one `end` line, so no user text is ever replaced.
+pub(crate) fn splice_between_markers(
+    text: &str,
+    start: &str,
+    end: &str,
+    block: &str,
+) -> std::result::Result<String, String> {
+    let newline = if text.contains("\r\n") { "\r\n" } else { "\n" };
+    let mut block = block.replace("\r\n", "\n");
+    if !block.is_empty() && !block.ends_with('\n') {
+        block.push('\n');
+    }
+    let block = block.replace('\n', newline);
+
+    if let Some(region) = marker_region(text, start, end)? {
+        return Ok(format!(
+            "{}{}{}",
+            &text[..region.start],
+            block,
+            &text[region.end..]
+        ));
+    }
+
+    let mut spliced = text.to_string();
+    if !spliced.is_empty() {
+        if !spliced.ends_with('\n') {
+            spliced.push_str(newline);
+        }
+        spliced.push_str(newline);
+    }
+    spliced.push_str(start);
+    spliced.push_str(newline);
+    spliced.push_str(&block);
+    spliced.push_str(end);
+    spliced.push_str(newline);
+    Ok(spliced)
+}
+
+/// Byte range of the lines between the `start` marker line and the `end`
+/// marker line after it; markers only count at the beginning of a line.
+/// None when there are no markers, an error when they do not form one pair.
+fn marker_region(
+    text: &str,
+    start: &str,
+    end: &str,
+) -> std::result::Result<Option<Range<usize>>, String> {
+    let at_line_start = |index: usize| index == 0 || text[..index].ends_with('\n');
+    let lines_starting_with = |marker: &str| -> Vec<usize> {
+        text.match_indices(marker)
+            .map(|(index, _)| index)
+            .filter(|&index| at_line_start(index))
+            .collect()
+    };
+    let starts = lines_starting_with(start);
+    let ends = lines_starting_with(end);
+
+    match (starts.as_slice(), ends.as_slice()) {
+        ([], []) => Ok(None),
+        ([start_index], [end_index]) => {
+            let after_start = start_index + start.len();
+            let inner_start = text[after_start..]
+                .find('\n')
+                .map(|offset| after_start + offset + 1)
+                .filter(|&inner_start| inner_start <= *end_index)
+                .ok_or_else(|| format!("`{}` comes before `{}`", end, start))?;
+            Ok(Some(inner_start..*end_index))
+        }
+        _ => Err(format!(
+            "expected one `{}` line and one `{}` line after it, found {} and {}",
+            start,
+            end,
+            starts.len(),
+            ends.len()
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const START: &str = "<!-- s -->";
+    const END: &str = "<!-- e -->";
+
+    #[test]
+    fn test_splice_replaces_between_markers_only() {
+        let text = "# Title\n\n<!-- s -->\nold\nlines\n<!-- e -->\nTail  \n";
+        assert_eq!(
+            splice_between_markers(text, START, END, "new").unwrap(),
+            "# Title\n\n<!-- s -->\nnew\n<!-- e -->\nTail  \n"
+        );
+        let again = splice_between_markers(text, START, END, "new\n").unwrap();
+        assert_eq!(
+            splice_between_markers(&again, START, END, "new").unwrap(),
+            again
+        );
+    }
+
+    #[test]
+    fn test_splice_appends_markers_when_absent() {
+        assert_eq!(
+            splice_between_markers("# Title", START, END, "a\nb").unwrap(),
+            "# Title\n\n<!-- s -->\na\nb\n<!-- e -->\n"
+        );
+        assert_eq!(
+            splice_between_markers("", START, END, "a").unwrap(),
+            "<!-- s -->\na\n<!-- e -->\n"
+        );
+        // A marker inside a line is not a marker
+        let text = "see `<!-- s -->` and `<!-- e -->`\n";
+        assert!(splice_between_markers(text, START, END, "a")
+            .unwrap()
+            .starts_with(text));
+    }
+
+    #[test]
+    fn test_splice_refuses_unpaired_or_repeated_markers() {
+        for text in [
+            "<!-- s -->\nKeep this paragraph\n",
+            "Keep\n<!-- e -->\n",
+            "<!-- e -->\nKeep\n<!-- s -->\n",
+            "<!-- s -->\nKeep\n<!-- s -->\nold\n<!-- e -->\n",
+            "<!-- s -->\nold\n<!-- e -->\nKeep\n<!-- e -->\n",
+        ] {
+            assert!(
+                splice_between_markers(text, START, END, "a").is_err(),
+                "{text:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_splice_keeps_crlf_line_breaks() {
+        let text = "Intro\r\n<!-- s -->\r\nold\r\n<!-- e -->\r\n";
+        assert_eq!(
+            splice_between_markers(text, START, END, "x\ny").unwrap(),
+            "Intro\r\n<!-- s -->\r\nx\r\ny\r\n<!-- e -->\r\n"
+        );
+        assert_eq!(
+            splice_between_markers("Intro\r\n", START, END, "x").unwrap(),
+            "Intro\r\n\r\n<!-- s -->\r\nx\r\n<!-- e -->\r\n"
+        );
+    }
+
+    fn commented(markdown: &str) -> Vec<&str> {
+        html_comment_regions(markdown)
+            .into_iter()
+            .map(|r| &markdown[r])
+            .collect()
+    }
+
+    #[test]
+    fn test_multi_line_and_inline_comments() {
+        let markdown = "a <!-- x --> b\n<!--\n## Saturday\n-->\nc <!-- open\nstill\n--> d\n";
+        assert_eq!(
+            commented(markdown),
+            vec![
+                "<!-- x -->",
+                "<!--\n## Saturday\n-->",
+                "<!-- open\nstill\n-->"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_comments_in_fences_are_ignored_and_fences_in_comments_too() {
+        let markdown = "```\n<!--\n```\n## Real\n<!--\n```\n-->\n## Also real\n";
+        assert_eq!(commented(markdown), vec!["<!--\n```\n-->"]);
+    }
+
+    #[test]
+    fn test_comment_openers_in_code_are_ignored() {
+        let markdown = "Use `<!--` to open one\n\n## Later\n\n    <!-- indented\n\n~~~\n<!--\n~~~\n## Last <!-- c -->\n";
+        assert_eq!(commented(markdown), vec!["<!-- c -->"]);
+    }
+
+    #[test]
+    fn test_unclosed_comment_runs_to_end() {
+        let markdown = "text\n<!--\n## Gone\n";
+        assert_eq!(commented(markdown), vec!["<!--\n## Gone\n"]);
+    }
+
+    #[test]
+    fn test_unclosed_fence_line() {
+        assert_eq!(
+            unclosed_fence_line("# Day\n\n```rust\nlet x = 1;\n"),
+            Some(3)
+        );
+        assert_eq!(unclosed_fence_line("```\ncode\n```\n~~~~\n~~~\n"), Some(4));
+        assert_eq!(unclosed_fence_line("````\n```\n````\n#work\n"), None);
+        assert_eq!(unclosed_fence_line("<!--\n```\n-->\ntext\n"), None);
+        assert_eq!(
+            unclosed_fence_line("Use `<!--` here\n\n```\ncode\n"),
+            Some(3)
+        );
+        assert_eq!(unclosed_fence_line("> ```\n> quoted\n\nafter\n"), None);
+        assert_eq!(unclosed_fence_line("no code here"), None);
+    }
+
+    #[test]
+    fn test_preserve_final_newline() {
+        assert_eq!(preserve_final_newline("a\n", "b".to_string()), "b\n");
+        assert_eq!(preserve_final_newline("a\r\n", "b".to_string()), "b\r\n");
+        assert_eq!(preserve_final_newline("a", "b\n\n".to_string()), "b");
+        assert_eq!(preserve_final_newline("a", "b\r\n".to_string()), "b");
+        assert_eq!(preserve_final_newline("a\n", "b\n\n".to_string()), "b\n\n");
+        assert_eq!(preserve_final_newline("a", "b".to_string()), "b");
+        assert_eq!(preserve_final_newline("a\n", String::new()), "");
+        assert_eq!(preserve_final_newline("", "new\n".to_string()), "new\n");
+    }
+}
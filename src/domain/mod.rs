@@ -1,16 +1,49 @@
 //! Domain layer - Business logic and domain models
 
+pub mod archive;
+pub mod diagnostic;
+pub mod exclude;
 pub mod journal;
+pub(crate) mod links;
+pub mod locale;
+pub(crate) mod markdown;
 pub mod mode;
 pub mod mode_migration;
+pub mod naming;
+pub mod recurrence;
+pub mod reminders;
+pub mod sections;
 pub mod tags;
+pub mod tasks;
 pub mod template;
+pub mod text_diff;
 pub mod time_ref;
+pub mod title;
+pub mod week;
 
+pub use archive::{is_archived_path, ArchiveLayout, ARCHIVED_DIRS, ARCHIVE_DIR};
+pub use diagnostic::{
+    check_note_structure, check_section_dates, check_unclosed_fence, Diagnostic, Diagnostics,
+    Severity,
+};
+pub use exclude::{split_patterns, ExcludePatterns};
 pub use journal::Journal;
-pub use mode::JournalMode;
+pub use locale::Locale;
+pub use mode::{path_date_from_components, JournalMode, ModeCounts};
 pub use mode_migration::{
-    inject_daily_into_weekly, split_weekly_into_daily_bodies, strip_daily_prefix, week_start,
+    inject_daily_into_weekly, reset_weekly_sections, split_weekly_into_daily_bodies,
+    strip_daily_prefix, week_start,
+};
+pub use naming::NoteNaming;
+pub use recurrence::{Directive, RecurringDirectives, Rule};
+pub use reminders::{extract_reminders, ReminderItem};
+pub use sections::{find_section, DaySections, SectionSpan, TimeRange};
+pub use tasks::{extract_tasks, TaskItem};
+pub use template::{
+    load_template, load_template_for_week, CompilationTemplateVars, RenderContext, Template,
+    TemplateIssue, TEMPLATE_NAMES,
 };
-pub use template::{load_template, Template};
+pub use text_diff::{unified_diff, DiffOptions};
 pub use time_ref::TimeReference;
+pub use title::first_heading;
+pub use week::{WeekNumbering, WeekStart};
@@ -1,7 +1,9 @@
 //! Journal mode definitions and file name generation
 
+use super::week::{WeekNumbering, WeekStart};
 use chrono::{Datelike, Duration, NaiveDate};
 use serde::{Deserialize, Serialize};
+use std::fmt;
 use std::str::FromStr;
 
 /// Journal modes determine how notes are organized
@@ -22,18 +24,35 @@ pub enum JournalMode {
 impl JournalMode {
     /// Generate filename for a given date based on the mode
     pub fn filename_for_date(&self, date: NaiveDate) -> String {
+        self.filename_for_date_in_week(date, WeekStart::Monday)
+    }
+
+    /// Generate filename for a date, with weekly notes starting on `week`.
+    ///
+    /// The weekly date suffix is the week's first day; the week number is the
+    /// ISO week of the Monday inside the week.
+    pub fn filename_for_date_in_week(&self, date: NaiveDate, week: WeekStart) -> String {
+        self.filename_for_date_numbered(date, week, WeekNumbering::Iso)
+    }
+
+    /// Like `filename_for_date_in_week`, with weeks numbered by `numbering`.
+    pub fn filename_for_date_numbered(
+        &self,
+        date: NaiveDate,
+        week: WeekStart,
+        numbering: WeekNumbering,
+    ) -> String {
         match self {
             JournalMode::Daily => {
                 format!("{}.md", date.format("%Y-%m-%d"))
             }
             JournalMode::Weekly => {
-                let week = date.iso_week();
-                let week_start =
-                    date - Duration::days(date.weekday().num_days_from_monday() as i64);
+                let week_start = week.start_of_week(date);
+                let (year, number) = week_label(week_start, week, numbering);
                 format!(
                     "{}-W{:02}-{}.md",
-                    week.year(),
-                    week.week(),
+                    year,
+                    number,
                     week_start.format("%Y-%m-%d")
                 )
             }
@@ -44,6 +63,59 @@ impl JournalMode {
         }
     }
 
+    /// Last day covered by a note that starts on `start`.
+    ///
+    /// Weekly notes cover their whole week and monthly notes their whole month,
+    /// so date filters must compare against this window rather than the start alone.
+    pub fn period_end(&self, start: NaiveDate) -> NaiveDate {
+        match self {
+            JournalMode::Daily | JournalMode::Single => start,
+            JournalMode::Weekly => start + Duration::days(6),
+            JournalMode::Monthly => {
+                let (year, month) = if start.month() == 12 {
+                    (start.year() + 1, 1)
+                } else {
+                    (start.year(), start.month() + 1)
+                };
+                NaiveDate::from_ymd_opt(year, month, 1)
+                    .and_then(|next| next.pred_opt())
+                    .unwrap_or(start)
+            }
+        }
+    }
+
+    /// Whether one note covers several days (weekly and monthly notes)
+    pub fn is_period_based(&self) -> bool {
+        matches!(self, JournalMode::Weekly | JournalMode::Monthly)
+    }
+
+    /// The days covered by the note for `date`, in words: "Friday, January 17, 2025",
+    /// "week 03 of 2025, January 13 - January 19, 2025", "January 2025" or "all dates"
+    pub fn period_description(
+        &self,
+        date: NaiveDate,
+        week: WeekStart,
+        numbering: WeekNumbering,
+    ) -> String {
+        match self {
+            JournalMode::Daily => date.format("%A, %B %-d, %Y").to_string(),
+            JournalMode::Weekly => {
+                let start = week.start_of_week(date);
+                let end = self.period_end(start);
+                let (year, number) = week_label(start, week, numbering);
+                format!(
+                    "week {:02} of {}, {} - {}",
+                    number,
+                    year,
+                    start.format("%B %-d"),
+                    end.format("%B %-d, %Y")
+                )
+            }
+            JournalMode::Monthly => date.format("%B %Y").to_string(),
+            JournalMode::Single => "all dates".to_string(),
+        }
+    }
+
     /// Get the template name for this mode
     pub fn template_name(&self) -> &'static str {
         match self {
@@ -57,6 +129,24 @@ impl JournalMode {
     /// Parse a filename and extract the date it represents
     /// Returns None if the filename doesn't match this mode's pattern
     pub fn date_from_filename(&self, filename: &str) -> Option<NaiveDate> {
+        self.date_from_filename_in_week(filename, WeekStart::Monday)
+    }
+
+    /// Like `date_from_filename`, with weekly notes starting on `week`.
+    pub fn date_from_filename_in_week(&self, filename: &str, week: WeekStart) -> Option<NaiveDate> {
+        self.date_from_filename_numbered(filename, week, WeekNumbering::Iso)
+    }
+
+    /// Like `date_from_filename_in_week`, with weeks numbered by `numbering`.
+    ///
+    /// A weekly filename only parses when its week number is the one
+    /// `numbering` gives its start date.
+    pub fn date_from_filename_numbered(
+        &self,
+        filename: &str,
+        week: WeekStart,
+        numbering: WeekNumbering,
+    ) -> Option<NaiveDate> {
         let stem = filename.strip_suffix(".md")?;
 
         match self {
@@ -70,23 +160,20 @@ impl JournalMode {
                 if parts.len() == 2 && parts[1].starts_with('W') {
                     let year: i32 = parts[0].parse().ok()?;
                     let week_str = &parts[1][1..]; // Skip 'W'
-                    let week: u32 = week_str.parse().ok()?;
+                    let week_num: u32 = week_str.parse().ok()?;
 
-                    // Get first day (Monday) of ISO week
-                    return NaiveDate::from_isoywd_opt(year, week, chrono::Weekday::Mon);
+                    return week_first_day(year, week_num, week, numbering);
                 }
 
                 if parts.len() == 5 && parts[1].starts_with('W') {
                     let year: i32 = parts[0].parse().ok()?;
                     let week_str = &parts[1][1..]; // Skip 'W'
-                    let week: u32 = week_str.parse().ok()?;
+                    let week_num: u32 = week_str.parse().ok()?;
                     let date_str = format!("{}-{}-{}", parts[2], parts[3], parts[4]);
                     let start_date = NaiveDate::parse_from_str(&date_str, "%Y-%m-%d").ok()?;
 
-                    let iso = start_date.iso_week();
-                    if iso.year() == year
-                        && iso.week() == week
-                        && start_date.weekday() == chrono::Weekday::Mon
+                    if week_label(start_date, week, numbering) == (year, week_num)
+                        && start_date.weekday() == week.weekday()
                     {
                         return Some(start_date);
                     }
@@ -107,6 +194,194 @@ impl JournalMode {
     }
 }
 
+/// Year and number of the week starting on `start` (a `week` first day),
+/// as `numbering` labels it
+pub fn week_label(start: NaiveDate, week: WeekStart, numbering: WeekNumbering) -> (i32, u32) {
+    match numbering {
+        WeekNumbering::Iso => {
+            let iso = week.iso_anchor(start).iso_week();
+            (iso.year(), iso.week())
+        }
+        WeekNumbering::Us => us_week_label(start, week),
+    }
+}
+
+/// US week of the year of `date`, as (year, week), for weeks starting on Sunday.
+///
+/// Week 1 is the week containing January 1, and a week belongs to the year its
+/// Saturday is in: Sunday 31-12-2023 is in 2024-W01, Saturday 01-01-2022 ends
+/// 2022-W01.
+pub fn week_of_year_us(date: NaiveDate) -> (i32, u32) {
+    us_week_label(WeekStart::Sunday.start_of_week(date), WeekStart::Sunday)
+}
+
+fn us_week_label(start: NaiveDate, week: WeekStart) -> (i32, u32) {
+    let year = (start + Duration::days(6)).year();
+    let first = week.start_of_week(NaiveDate::from_ymd_opt(year, 1, 1).expect("January 1 exists"));
+    (year, ((start - first).num_days() / 7 + 1) as u32)
+}
+
+/// First day of week `number` of `year`, or None if `numbering` has no such week
+fn week_first_day(
+    year: i32,
+    number: u32,
+    week: WeekStart,
+    numbering: WeekNumbering,
+) -> Option<NaiveDate> {
+    match numbering {
+        WeekNumbering::Iso => NaiveDate::from_isoywd_opt(year, number, chrono::Weekday::Mon)
+            .map(|monday| week.start_of_week(monday)),
+        WeekNumbering::Us => {
+            let first = week.start_of_week(NaiveDate::from_ymd_opt(year, 1, 1)?);
+            let start =
+                first.checked_add_signed(Duration::weeks(i64::from(number.checked_sub(1)?)))?;
+            (us_week_label(start, week) == (year, number)).then_some(start)
+        }
+    }
+}
+
+/// Date of a note kept in dated folders, from its relative path components
+/// (directories first, file name last).
+///
+/// Daily notes may live at `<YYYY>/<MM>/<DD>.md` or `<YYYY>/<MM>/<YYYY-MM-DD>.md`
+/// (the folders must agree with the file name), monthly notes at `<YYYY>/<MM>.md`.
+/// Only the trailing components are used, so the tree may sit in any folder.
+/// Years take four digits and months and days two; weekly and single journals
+/// have no dated folder layout.
+pub fn path_date_from_components(mode: JournalMode, parts: &[&str]) -> Option<NaiveDate> {
+    fn number(part: &str, digits: usize) -> Option<u32> {
+        (part.len() == digits && part.bytes().all(|b| b.is_ascii_digit()))
+            .then(|| part.parse().ok())
+            .flatten()
+    }
+
+    let (leaf, dirs) = parts.split_last()?;
+    let stem = leaf.strip_suffix(".md")?;
+
+    match mode {
+        JournalMode::Daily => {
+            let [.., year, month] = dirs else {
+                return None;
+            };
+            let (year, month) = (number(year, 4)?, number(month, 2)?);
+            if let Some(day) = number(stem, 2) {
+                return NaiveDate::from_ymd_opt(year as i32, month, day);
+            }
+            JournalMode::Daily
+                .date_from_filename(leaf)
+                .filter(|date| date.year() == year as i32 && date.month() == month)
+        }
+        JournalMode::Monthly => {
+            let [.., year] = dirs else {
+                return None;
+            };
+            NaiveDate::from_ymd_opt(number(year, 4)? as i32, number(stem, 2)?, 1)
+        }
+        JournalMode::Weekly | JournalMode::Single => None,
+    }
+}
+
+/// How many file names in a directory look like each mode's notes
+///
+/// Used to spot a journal initialized (or configured) with the wrong mode.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ModeCounts {
+    pub daily: usize,
+    pub weekly: usize,
+    pub monthly: usize,
+    pub single: usize,
+}
+
+impl ModeCounts {
+    /// Count `filenames` by the mode they look like, after stripping an optional
+    /// `prefix`. Weekly names count for any week start day and numbering.
+    pub fn tally<'a>(filenames: impl IntoIterator<Item = &'a str>, prefix: &str) -> Self {
+        let mut counts = ModeCounts::default();
+        for filename in filenames {
+            let name = filename
+                .strip_prefix(prefix)
+                .filter(|_| !prefix.is_empty())
+                .unwrap_or(filename);
+            if name == "journal.md" {
+                counts.single += 1;
+            } else if JournalMode::Daily.date_from_filename(name).is_some() {
+                counts.daily += 1;
+            } else if [WeekStart::Monday, WeekStart::Sunday, WeekStart::Saturday]
+                .into_iter()
+                .any(|week| {
+                    [WeekNumbering::Iso, WeekNumbering::Us]
+                        .into_iter()
+                        .any(|numbering| {
+                            JournalMode::Weekly
+                                .date_from_filename_numbered(name, week, numbering)
+                                .is_some()
+                        })
+                })
+            {
+                counts.weekly += 1;
+            } else if JournalMode::Monthly.date_from_filename(name).is_some() {
+                counts.monthly += 1;
+            }
+        }
+        counts
+    }
+
+    /// Number of files that look like `mode` notes
+    pub fn count(&self, mode: JournalMode) -> usize {
+        match mode {
+            JournalMode::Daily => self.daily,
+            JournalMode::Weekly => self.weekly,
+            JournalMode::Monthly => self.monthly,
+            JournalMode::Single => self.single,
+        }
+    }
+
+    /// Number of files that look like notes of any mode
+    pub fn total(&self) -> usize {
+        self.daily + self.weekly + self.monthly + self.single
+    }
+
+    /// The mode at least two thirds of the note-like files belong to, if any
+    pub fn majority(&self) -> Option<JournalMode> {
+        let total = self.total();
+        [
+            JournalMode::Daily,
+            JournalMode::Weekly,
+            JournalMode::Monthly,
+            JournalMode::Single,
+        ]
+        .into_iter()
+        .find(|mode| total > 0 && self.count(*mode) * 3 >= total * 2)
+    }
+
+    /// The mode other than `active` with the most note-like files, with its count
+    pub fn best_other_than(&self, active: JournalMode) -> Option<(JournalMode, usize)> {
+        [
+            JournalMode::Daily,
+            JournalMode::Weekly,
+            JournalMode::Monthly,
+            JournalMode::Single,
+        ]
+        .into_iter()
+        .filter(|mode| *mode != active)
+        .map(|mode| (mode, self.count(mode)))
+        .filter(|(_, count)| *count > 0)
+        .max_by_key(|(_, count)| *count)
+    }
+}
+
+/// Name as written in config.toml (`daily`, `weekly`, ...)
+impl fmt::Display for JournalMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            JournalMode::Daily => "daily",
+            JournalMode::Weekly => "weekly",
+            JournalMode::Monthly => "monthly",
+            JournalMode::Single => "single",
+        })
+    }
+}
+
 impl FromStr for JournalMode {
     type Err = String;
 
@@ -129,6 +404,66 @@ mod tests {
     use super::*;
     use chrono::NaiveDate;
 
+    fn path_date(mode: JournalMode, path: &str) -> Option<NaiveDate> {
+        let parts: Vec<&str> = path.split('/').collect();
+        path_date_from_components(mode, &parts)
+    }
+
+    #[test]
+    fn test_path_date_daily_layouts() {
+        let date = NaiveDate::from_ymd_opt(2025, 1, 17);
+        assert_eq!(path_date(JournalMode::Daily, "2025/01/17.md"), date);
+        assert_eq!(path_date(JournalMode::Daily, "2025/01/2025-01-17.md"), date);
+        assert_eq!(path_date(JournalMode::Daily, "notes/2025/01/17.md"), date);
+    }
+
+    #[test]
+    fn test_path_date_daily_rejects_invalid_components() {
+        for path in [
+            "2025/13/17.md",
+            "2025/02/30.md",
+            "2025/00/01.md",
+            "2025/1/17.md",
+            "2025/01/7.md",
+            "25/01/17.md",
+            "2025/01/17.txt",
+            "01/17.md",
+            "17.md",
+            "2025/01/readme.md",
+            "2025/02/2025-01-17.md",
+            "2024/01/2025-01-17.md",
+            "2025/+1/17.md",
+        ] {
+            assert_eq!(path_date(JournalMode::Daily, path), None, "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_path_date_monthly_layout() {
+        assert_eq!(
+            path_date(JournalMode::Monthly, "2025/03.md"),
+            NaiveDate::from_ymd_opt(2025, 3, 1)
+        );
+        assert_eq!(path_date(JournalMode::Monthly, "2025/13.md"), None);
+        assert_eq!(path_date(JournalMode::Monthly, "2025/3.md"), None);
+        assert_eq!(path_date(JournalMode::Monthly, "03.md"), None);
+    }
+
+    #[test]
+    fn test_path_date_ignores_plain_files_in_year_folders() {
+        for mode in [
+            JournalMode::Daily,
+            JournalMode::Weekly,
+            JournalMode::Monthly,
+            JournalMode::Single,
+        ] {
+            assert_eq!(path_date(mode, "2025/readme.md"), None);
+            assert_eq!(path_date(mode, "2025/01/readme.md"), None);
+        }
+        assert_eq!(path_date(JournalMode::Weekly, "2025/01/17.md"), None);
+        assert_eq!(path_date(JournalMode::Single, "2025/01/17.md"), None);
+    }
+
     #[test]
     fn test_daily_filename() {
         let mode = JournalMode::Daily;
@@ -249,6 +584,69 @@ mod tests {
         assert_eq!(date, NaiveDate::from_ymd_opt(2025, 1, 13).unwrap());
     }
 
+    #[test]
+    fn test_period_end() {
+        let d = |y, m, day| NaiveDate::from_ymd_opt(y, m, day).unwrap();
+        assert_eq!(
+            JournalMode::Daily.period_end(d(2025, 1, 13)),
+            d(2025, 1, 13)
+        );
+        assert_eq!(
+            JournalMode::Weekly.period_end(d(2025, 1, 13)),
+            d(2025, 1, 19)
+        );
+        assert_eq!(
+            JournalMode::Weekly.period_end(d(2024, 12, 30)),
+            d(2025, 1, 5)
+        );
+        assert_eq!(
+            JournalMode::Monthly.period_end(d(2024, 2, 1)),
+            d(2024, 2, 29)
+        );
+        assert_eq!(
+            JournalMode::Monthly.period_end(d(2025, 12, 1)),
+            d(2025, 12, 31)
+        );
+    }
+
+    #[test]
+    fn test_period_description() {
+        let d = |y, m, day| NaiveDate::from_ymd_opt(y, m, day).unwrap();
+        let describe = |mode: JournalMode, date| {
+            mode.period_description(date, WeekStart::Monday, WeekNumbering::Iso)
+        };
+
+        assert_eq!(
+            describe(JournalMode::Daily, d(2025, 1, 31)),
+            "Friday, January 31, 2025"
+        );
+        assert_eq!(
+            describe(JournalMode::Weekly, d(2025, 1, 31)),
+            "week 05 of 2025, January 27 - February 2, 2025"
+        );
+        assert_eq!(
+            describe(JournalMode::Weekly, d(2024, 12, 31)),
+            "week 01 of 2025, December 30 - January 5, 2025"
+        );
+        assert_eq!(
+            describe(JournalMode::Monthly, d(2025, 2, 1)),
+            "February 2025"
+        );
+        assert_eq!(describe(JournalMode::Single, d(2025, 2, 1)), "all dates");
+
+        // Weeks follow week_start and week_numbering
+        assert_eq!(
+            JournalMode::Weekly.period_description(
+                d(2024, 1, 2),
+                WeekStart::Sunday,
+                WeekNumbering::Us
+            ),
+            "week 01 of 2024, December 31 - January 6, 2024"
+        );
+        assert!(JournalMode::Monthly.is_period_based());
+        assert!(!JournalMode::Single.is_period_based());
+    }
+
     #[test]
     fn test_date_from_filename_weekly_legacy() {
         let mode = JournalMode::Weekly;
@@ -265,6 +663,113 @@ mod tests {
         assert!(mode.date_from_filename("2025-W03-2025-01-14.md").is_none()); // Date does not match ISO week
     }
 
+    #[test]
+    fn test_weekly_sunday_start_round_trip() {
+        let mode = JournalMode::Weekly;
+        // Friday 17-01-2025 falls in the Sunday week 12..18 January, labelled by Monday 13 (W03)
+        let date = NaiveDate::from_ymd_opt(2025, 1, 17).unwrap();
+        let filename = mode.filename_for_date_in_week(date, WeekStart::Sunday);
+        assert_eq!(filename, "2025-W03-2025-01-12.md");
+        let start = mode
+            .date_from_filename_in_week(&filename, WeekStart::Sunday)
+            .unwrap();
+        assert_eq!(start, NaiveDate::from_ymd_opt(2025, 1, 12).unwrap());
+        assert_eq!(
+            mode.filename_for_date_in_week(start, WeekStart::Sunday),
+            filename
+        );
+
+        // The Sunday before ISO week 1 starts the first week of 2025
+        let sunday = NaiveDate::from_ymd_opt(2024, 12, 29).unwrap();
+        assert_eq!(
+            mode.filename_for_date_in_week(sunday, WeekStart::Sunday),
+            "2025-W01-2024-12-29.md"
+        );
+
+        // A Monday-start filename is not a Sunday-start week
+        assert!(mode
+            .date_from_filename_in_week("2025-W03-2025-01-13.md", WeekStart::Sunday)
+            .is_none());
+        assert_eq!(
+            mode.date_from_filename_in_week("2025-W03.md", WeekStart::Sunday),
+            Some(NaiveDate::from_ymd_opt(2025, 1, 12).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_weekly_saturday_start_round_trip() {
+        let mode = JournalMode::Weekly;
+        let date = NaiveDate::from_ymd_opt(2025, 1, 17).unwrap();
+        let filename = mode.filename_for_date_in_week(date, WeekStart::Saturday);
+        assert_eq!(filename, "2025-W03-2025-01-11.md");
+        assert_eq!(
+            mode.date_from_filename_in_week(&filename, WeekStart::Saturday),
+            Some(NaiveDate::from_ymd_opt(2025, 1, 11).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_week_of_year_us() {
+        let d = |y, m, day| NaiveDate::from_ymd_opt(y, m, day).unwrap();
+        assert_eq!(week_of_year_us(d(2025, 1, 1)), (2025, 1));
+        assert_eq!(week_of_year_us(d(2025, 1, 5)), (2025, 2)); // Sunday
+        assert_eq!(week_of_year_us(d(2025, 1, 17)), (2025, 3));
+
+        // Dec 31 on a Sunday starts week 1 of the next year
+        assert_eq!(week_of_year_us(d(2023, 12, 30)), (2023, 52));
+        assert_eq!(week_of_year_us(d(2023, 12, 31)), (2024, 1));
+        assert_eq!(week_of_year_us(d(2024, 1, 6)), (2024, 1));
+
+        // Jan 1 on a Saturday ends week 1; the next day starts week 2
+        assert_eq!(week_of_year_us(d(2021, 12, 25)), (2021, 52));
+        assert_eq!(week_of_year_us(d(2021, 12, 26)), (2022, 1));
+        assert_eq!(week_of_year_us(d(2022, 1, 1)), (2022, 1));
+        assert_eq!(week_of_year_us(d(2022, 1, 2)), (2022, 2));
+
+        // A year can have 53 weeks
+        assert_eq!(week_of_year_us(d(2016, 12, 31)), (2016, 53));
+        assert_eq!(week_of_year_us(d(2017, 1, 1)), (2017, 1));
+    }
+
+    #[test]
+    fn test_weekly_us_numbering_round_trip() {
+        let mode = JournalMode::Weekly;
+        let d = |y, m, day| NaiveDate::from_ymd_opt(y, m, day).unwrap();
+        let us = |date| mode.filename_for_date_numbered(date, WeekStart::Sunday, WeekNumbering::Us);
+        let parse = |name: &str, numbering| {
+            mode.date_from_filename_numbered(name, WeekStart::Sunday, numbering)
+        };
+
+        assert_eq!(us(d(2024, 1, 3)), "2024-W01-2023-12-31.md");
+        assert_eq!(us(d(2022, 1, 1)), "2022-W01-2021-12-26.md");
+        assert_eq!(us(d(2016, 12, 31)), "2016-W53-2016-12-25.md");
+        for name in [
+            "2024-W01-2023-12-31.md",
+            "2022-W01-2021-12-26.md",
+            "2016-W53-2016-12-25.md",
+        ] {
+            let start = parse(name, WeekNumbering::Us).unwrap();
+            assert_eq!(us(start), name);
+        }
+        assert_eq!(parse("2022-W02.md", WeekNumbering::Us), Some(d(2022, 1, 2)));
+        assert_eq!(parse("2017-W53.md", WeekNumbering::Us), None);
+        assert_eq!(parse("2017-W00.md", WeekNumbering::Us), None);
+        assert_eq!(parse("2017-W4294967295.md", WeekNumbering::Us), None);
+
+        // 2025-W03 is the week of 12-01-2025 under both systems, but not 2022-W01
+        assert_eq!(
+            parse("2025-W03-2025-01-12.md", WeekNumbering::Us),
+            Some(d(2025, 1, 12))
+        );
+        assert_eq!(parse("2022-W01-2021-12-26.md", WeekNumbering::Iso), None);
+        assert_eq!(parse("2021-W52-2021-12-26.md", WeekNumbering::Us), None);
+
+        // ISO numbering is unchanged
+        assert_eq!(
+            mode.filename_for_date_numbered(d(2025, 1, 17), WeekStart::Monday, WeekNumbering::Iso),
+            mode.filename_for_date(d(2025, 1, 17))
+        );
+    }
     #[test]
     fn test_date_from_filename_monthly() {
         let mode = JournalMode::Monthly;
@@ -307,4 +812,75 @@ mod tests {
             assert_eq!(filename, roundtrip_filename);
         }
     }
+    #[test]
+    fn test_mode_counts_tally() {
+        let counts = ModeCounts::tally(
+            [
+                "2025-01-15.md",
+                "2025-01-16.md",
+                "2025-W03-2025-01-13.md",
+                "2025-W03-2025-01-12.md",
+                "2025-01.md",
+                "journal.md",
+                "README.md",
+                "2025-01-15.txt",
+            ],
+            "",
+        );
+        assert_eq!(
+            counts,
+            ModeCounts {
+                daily: 2,
+                weekly: 2,
+                monthly: 1,
+                single: 1,
+            }
+        );
+        assert_eq!(counts.total(), 6);
+        assert_eq!(counts.majority(), None);
+    }
+
+    #[test]
+    fn test_mode_counts_strip_prefix() {
+        let counts = ModeCounts::tally(["journal-2025-01-15.md", "2025-01-16.md"], "journal-");
+        assert_eq!(counts.daily, 2);
+    }
+
+    #[test]
+    fn test_mode_counts_majority_needs_two_thirds() {
+        let counts = ModeCounts {
+            daily: 2,
+            weekly: 1,
+            ..ModeCounts::default()
+        };
+        assert_eq!(counts.majority(), Some(JournalMode::Daily));
+        let counts = ModeCounts {
+            daily: 3,
+            weekly: 2,
+            ..ModeCounts::default()
+        };
+        assert_eq!(counts.majority(), None);
+        assert_eq!(ModeCounts::default().majority(), None);
+    }
+
+    #[test]
+    fn test_mode_counts_best_other_than() {
+        let counts = ModeCounts {
+            daily: 214,
+            monthly: 3,
+            ..ModeCounts::default()
+        };
+        assert_eq!(
+            counts.best_other_than(JournalMode::Weekly),
+            Some((JournalMode::Daily, 214))
+        );
+        assert_eq!(
+            counts.best_other_than(JournalMode::Daily),
+            Some((JournalMode::Monthly, 3))
+        );
+        assert_eq!(
+            ModeCounts::default().best_other_than(JournalMode::Daily),
+            None
+        );
+    }
 }
@@ -2,6 +2,10 @@
 //!
 //! This module is intentionally I/O-free: it validates and transforms note contents.
 
+use crate::domain::markdown::{
+    atx_heading, html_comment_regions, in_regions, parse_fence_marker, preserve_final_newline,
+};
+use crate::domain::{Locale, RenderContext, Template, WeekNumbering, WeekStart};
 use crate::error::{DjourError, Result};
 use chrono::{Datelike, Duration, NaiveDate};
 
@@ -85,59 +89,51 @@ fn scan_lines(s: &str) -> Vec<LineIdx> {
     out
 }
 
-pub fn week_start(date: NaiveDate) -> NaiveDate {
-    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+pub fn week_start(date: NaiveDate, week: WeekStart) -> NaiveDate {
+    week.start_of_week(date)
 }
 
 #[derive(Debug, Clone)]
 pub struct WeeklyExpected {
     pub header_variants: Vec<String>,
-    pub weekday_headings: Vec<String>, // Monday..Sunday
+    pub weekday_headings: Vec<String>, // In week order, from `week`'s first day
 }
 
-pub fn expected_weekly(week_start: NaiveDate) -> WeeklyExpected {
-    let week_end = week_start + Duration::days(6);
-    let week_num = week_start.iso_week().week();
-
-    let ws = week_start.format("%B %d, %Y").to_string();
-    let we = week_end.format("%B %d, %Y").to_string();
+pub fn expected_weekly(
+    week_start: NaiveDate,
+    week: WeekStart,
+    numbering: WeekNumbering,
+    locale: Locale,
+) -> WeeklyExpected {
+    // Derive expectations from the built-in weekly template so localized names stay in sync.
+    let template = Template::from_builtin_for_week("weekly.md", week)
+        .expect("built-in weekly template exists");
 
     // NOTE: The built-in weekly template uses {YEAR} which is derived from the date used to render
     // the template, not the ISO week-year. For weeks spanning a year boundary, the header could
     // plausibly contain either the start year or the end year, depending on which day created it.
-    let mut header_variants = vec![format!(
-        "# Week {:02}, {} ({} - {})",
-        week_num,
-        week_start.year(),
-        ws,
-        we
-    )];
-    let alt = format!(
-        "# Week {:02}, {} ({} - {})",
-        week_num,
-        week_end.year(),
-        ws,
-        we
-    );
-    if alt != header_variants[0] {
-        header_variants.push(alt);
-    }
-
-    let names = [
-        "Monday",
-        "Tuesday",
-        "Wednesday",
-        "Thursday",
-        "Friday",
-        "Saturday",
-        "Sunday",
-    ];
-    let weekday_headings = (0..7)
-        .map(|i| {
-            let day = week_start + Duration::days(i);
-            format!("## {} ({})", names[i as usize], day.format("%B %d, %Y"))
-        })
-        .collect::<Vec<_>>();
+    let mut header_variants: Vec<String> = Vec::new();
+    let mut weekday_headings: Vec<String> = Vec::new();
+    for render_date in [week_start, week_start + Duration::days(6)] {
+        let rendered = template.render_with_context(&RenderContext {
+            numbering,
+            ..RenderContext::new(render_date, locale, week)
+        });
+
+        if let Some(header) = rendered.lines().find(|line| !line.trim().is_empty()) {
+            if !header_variants.iter().any(|h| h == header) {
+                header_variants.push(header.to_string());
+            }
+        }
+
+        if weekday_headings.is_empty() {
+            weekday_headings = rendered
+                .lines()
+                .filter(|line| line.starts_with("## "))
+                .map(str::to_string)
+                .collect();
+        }
+    }
 
     WeeklyExpected {
         header_variants,
@@ -160,11 +156,17 @@ pub struct WeeklyParsed {
     pub header_line: String,
     pub header_start: usize,
     pub header_end_with_newline: usize,
-    pub days: Vec<DaySection>, // Monday..Sunday
+    pub days: Vec<DaySection>, // In week order
 }
 
-pub fn parse_weekly(content: &str, week_start: NaiveDate) -> Result<WeeklyParsed> {
-    let expected = expected_weekly(week_start);
+pub fn parse_weekly(
+    content: &str,
+    week_start: NaiveDate,
+    week: WeekStart,
+    numbering: WeekNumbering,
+    locale: Locale,
+) -> Result<WeeklyParsed> {
+    let expected = expected_weekly(week_start, week, numbering, locale);
     let newline = detect_newline_style(content);
     let lines = scan_lines(content);
 
@@ -192,11 +194,14 @@ pub fn parse_weekly(content: &str, week_start: NaiveDate) -> Result<WeeklyParsed
     let header_li = &lines[header_line_idx];
     let header_line = content[header_li.start..header_li.end].to_string();
 
-    // Find weekday headings (exact match, once each, in order).
+    // Find weekday headings (exact match, once each, in order). Lines inside
+    // HTML comments (e.g. commented-out template fragments) are not headings.
+    let comments = html_comment_regions(content);
+    let is_heading_line = |li: &LineIdx| !in_regions(&comments, li.start);
     let mut found: Vec<(usize, &LineIdx)> = Vec::new();
     let mut seen = std::collections::HashMap::<String, usize>::new();
 
-    for li in &lines {
+    for li in lines.iter().filter(|li| is_heading_line(li)) {
         let line = &content[li.start..li.end];
         if expected.weekday_headings.iter().any(|h| h == line) {
             if let Some(prev) = seen.insert(line.to_string(), li.start) {
@@ -212,7 +217,7 @@ pub fn parse_weekly(content: &str, week_start: NaiveDate) -> Result<WeeklyParsed
         let mut match_idx: Option<(usize, &LineIdx)> = None;
         for (i, li) in lines.iter().enumerate() {
             let line = &content[li.start..li.end];
-            if line == heading {
+            if line == heading && is_heading_line(li) {
                 match_idx = Some((i, li));
                 break;
             }
@@ -229,10 +234,12 @@ pub fn parse_weekly(content: &str, week_start: NaiveDate) -> Result<WeeklyParsed
     // Ensure headings are in increasing line order.
     for w in found.windows(2) {
         if w[0].0 >= w[1].0 {
-            return Err(DjourError::Config(
-                "Weekly weekday headings are not in the expected order (Monday..Sunday)"
-                    .to_string(),
-            ));
+            let days = week.days();
+            return Err(DjourError::Config(format!(
+                "Weekly weekday headings are not in the expected order ({}..{})",
+                Locale::English.weekday_name(days[0]),
+                Locale::English.weekday_name(days[6])
+            )));
         }
     }
 
@@ -266,7 +273,11 @@ pub fn parse_weekly(content: &str, week_start: NaiveDate) -> Result<WeeklyParsed
     })
 }
 
-pub fn validate_weekly_no_outside_content(content: &str, parsed: &WeeklyParsed) -> Result<()> {
+pub fn validate_weekly_no_outside_content(
+    content: &str,
+    parsed: &WeeklyParsed,
+    week: WeekStart,
+) -> Result<()> {
     // Only whitespace is allowed before the header.
     if !content[..parsed.header_start].trim().is_empty() {
         return Err(DjourError::Config(
@@ -275,32 +286,34 @@ pub fn validate_weekly_no_outside_content(content: &str, parsed: &WeeklyParsed)
         ));
     }
 
-    // Only whitespace is allowed between the header and the Monday section heading.
-    let monday_start = parsed
+    // Only whitespace is allowed between the header and the first weekday section heading.
+    let first_day_start = parsed
         .days
         .first()
         .ok_or_else(|| DjourError::Config("Weekly note has no weekday sections".to_string()))?
         .heading_start;
-    if !content[parsed.header_end_with_newline..monday_start]
+    if !content[parsed.header_end_with_newline..first_day_start]
         .trim()
         .is_empty()
     {
-        return Err(DjourError::Config(
-            "Weekly note has content between the header and Monday section; aborting migration"
-                .to_string(),
-        ));
+        return Err(DjourError::Config(format!(
+            "Weekly note has content between the header and {} section; aborting migration",
+            Locale::English.weekday_name(week.weekday())
+        )));
     }
 
     Ok(())
 }
 
-pub fn daily_prefix(date: NaiveDate) -> String {
+pub fn daily_prefix(date: NaiveDate, locale: Locale) -> String {
     // Built-in daily template is just the date heading and a blank line.
-    format!("# {}{}", date.format("%B %d, %Y"), "\n\n")
+    Template::from_builtin("daily.md")
+        .expect("built-in daily template exists")
+        .render_with_locale(date, locale)
 }
 
-pub fn strip_daily_prefix(content: &str, date: NaiveDate) -> Result<String> {
-    let prefix_lf = daily_prefix(date);
+pub fn strip_daily_prefix(content: &str, date: NaiveDate, locale: Locale) -> Result<String> {
+    let prefix_lf = daily_prefix(date, locale);
     let prefix_crlf = prefix_lf.replace("\n", "\r\n");
 
     let rest = if content.starts_with(&prefix_lf) {
@@ -318,41 +331,241 @@ pub fn strip_daily_prefix(content: &str, date: NaiveDate) -> Result<String> {
     Ok(rest.to_string())
 }
 
-fn marker_start(source_filename: &str) -> String {
-    format!("<!-- djour:migrated-from={}:start -->", source_filename)
+/// Prefix shared by every migration marker line
+const MARKER_PREFIX: &str = "<!-- djour:migrated-from=";
+
+/// Start marker of a migrated daily body; `demoted` records how many levels
+/// its headings were moved down and `final_newline` whether the body ended
+/// with a line break, so the reverse migration can undo both exactly.
+fn marker_start(source_filename: &str, demoted: usize, final_newline: bool) -> String {
+    let mut marker = format!("{}{}:start", MARKER_PREFIX, source_filename);
+    if demoted > 0 {
+        marker.push_str(&format!(" demoted={}", demoted));
+    }
+    if !final_newline {
+        marker.push_str(&format!(" {}", NO_FINAL_NEWLINE));
+    }
+    marker.push_str(" -->");
+    marker
 }
 
+/// Start marker attribute of a body whose last line had no line break
+const NO_FINAL_NEWLINE: &str = "no-final-newline";
+
 fn marker_end(source_filename: &str) -> String {
-    format!("<!-- djour:migrated-from={}:end -->", source_filename)
+    format!("{}{}:end -->", MARKER_PREFIX, source_filename)
 }
 
-pub fn strip_migration_markers(block: &str) -> String {
-    let mut out = String::new();
-    for line in block.replace("\r\n", "\n").lines() {
-        let trimmed = line.trim();
-        if trimmed.starts_with("<!-- djour:migrated-from=") && trimmed.ends_with("-->") {
+/// What a migration marker line says, if `line` is one
+enum MarkerLine {
+    Start { demoted: usize, final_newline: bool },
+    End,
+    Other,
+}
+
+/// Whether `line` is a marker written around a migrated daily body
+pub(crate) fn is_marker_line(line: &str) -> bool {
+    parse_marker_line(line).is_some()
+}
+
+fn parse_marker_line(line: &str) -> Option<MarkerLine> {
+    let inner = line
+        .trim()
+        .strip_prefix(MARKER_PREFIX)?
+        .strip_suffix("-->")?
+        .trim_end();
+    if inner.ends_with(":end") {
+        return Some(MarkerLine::End);
+    }
+    if let Some((_, attributes)) = inner.rsplit_once(":start") {
+        let mut demoted = 0;
+        let mut final_newline = true;
+        for attribute in attributes.split_whitespace() {
+            if let Some(n) = attribute.strip_prefix("demoted=") {
+                demoted = n.parse().unwrap_or(0);
+            } else if attribute == NO_FINAL_NEWLINE {
+                final_newline = false;
+            }
+        }
+        return Some(MarkerLine::Start {
+            demoted,
+            final_newline,
+        });
+    }
+    Some(MarkerLine::Other)
+}
+
+/// Move ATX headings of level `min_level` or deeper by `delta` levels
+/// (negative promotes, positive demotes).
+///
+/// Headings in fenced code blocks and HTML comments are left alone. A heading
+/// that would leave levels 1-6 is an error, so every shift can be undone exactly.
+fn shift_headings(text: &str, delta: isize, min_level: usize) -> Result<String> {
+    if delta == 0 {
+        return Ok(text.to_string());
+    }
+    let comments = html_comment_regions(text);
+    let mut out = String::with_capacity(text.len() + 16);
+    let mut active_fence: Option<(char, usize)> = None;
+    let mut offset = 0;
+
+    for line in text.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+
+        if let Some((fence_char, min_len)) = active_fence {
+            if matches!(parse_fence_marker(line), Some((c, len)) if c == fence_char && len >= min_len)
+            {
+                active_fence = None;
+            }
+            out.push_str(line);
+            continue;
+        }
+        if in_regions(&comments, line_start) {
+            out.push_str(line);
+            continue;
+        }
+        if let Some(marker) = parse_fence_marker(line) {
+            active_fence = Some(marker);
+            out.push_str(line);
+            continue;
+        }
+
+        match atx_heading(line) {
+            Some((indent, level)) if level >= min_level => {
+                let shifted = level as isize + delta;
+                if !(1..=6).contains(&shifted) {
+                    return Err(DjourError::Config(format!(
+                        "Cannot move heading '{}' to level {}; markdown headings go from 1 to 6",
+                        line.trim(),
+                        shifted
+                    )));
+                }
+                out.push_str(&line[..indent]);
+                out.push_str(&"#".repeat(shifted as usize));
+                out.push_str(&line[indent + level..]);
+            }
+            _ => out.push_str(line),
+        }
+    }
+
+    Ok(out)
+}
+
+/// Levels to move a daily body down so its shallowest heading sits below the
+/// weekday headings (`##`), or 0 if it already does
+fn demotion_for_weekly(body: &str) -> usize {
+    let comments = html_comment_regions(body);
+    let mut active_fence: Option<(char, usize)> = None;
+    let mut shallowest: Option<usize> = None;
+    let mut offset = 0;
+
+    for line in body.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+        if let Some((fence_char, min_len)) = active_fence {
+            if matches!(parse_fence_marker(line), Some((c, len)) if c == fence_char && len >= min_len)
+            {
+                active_fence = None;
+            }
+            continue;
+        }
+        if in_regions(&comments, line_start) {
             continue;
         }
-        out.push_str(line);
-        out.push('\n');
+        if let Some(marker) = parse_fence_marker(line) {
+            active_fence = Some(marker);
+            continue;
+        }
+        if let Some((_, level)) = atx_heading(line) {
+            shallowest = Some(shallowest.map_or(level, |s| s.min(level)));
+        }
     }
-    // Preserve no trailing newline preference by trimming one final '\n' if the input didn't end with it.
-    out
+
+    shallowest.map_or(0, |level| 3usize.saturating_sub(level))
 }
 
+/// Daily body for one weekday section of a weekly note.
+///
+/// Migration marker lines are dropped and headings inside a marked block are
+/// moved back up by the levels its start marker records. With
+/// `promote_headings`, sub-headings (`###` and deeper) outside marked blocks
+/// move up one level. Blank padding after the last marked block is dropped
+/// and its recorded final newline restored, so a daily body migrated to
+/// weekly and back is unchanged. Other bodies always end with a line break.
+pub fn daily_body_from_section(section: &str, promote_headings: bool) -> Result<String> {
+    let outside_delta = if promote_headings { -1 } else { 0 };
+    let mut out = String::new();
+    let mut pending = String::new();
+    let mut block: Option<usize> = None;
+    let mut after_block = false;
+    // Whether the open, then the last closed, marked block ended with a line break
+    let mut final_newline = true;
+
+    for line in section.replace("\r\n", "\n").lines() {
+        match parse_marker_line(line) {
+            Some(MarkerLine::Start {
+                demoted,
+                final_newline: block_final_newline,
+            }) => {
+                let delta = if block.is_some() { 0 } else { outside_delta };
+                out.push_str(&shift_headings(&pending, delta, 3)?);
+                pending.clear();
+                block = Some(demoted);
+                final_newline = block_final_newline;
+            }
+            Some(MarkerLine::End) => {
+                let demoted = block.take().unwrap_or(0);
+                out.push_str(&shift_headings(&pending, -(demoted as isize), 1 + demoted)?);
+                pending.clear();
+                after_block = true;
+            }
+            Some(MarkerLine::Other) => {}
+            None => {
+                pending.push_str(line);
+                pending.push('\n');
+            }
+        }
+    }
+
+    match block {
+        Some(demoted) => {
+            out.push_str(&shift_headings(&pending, -(demoted as isize), 1 + demoted)?);
+        }
+        None if after_block && pending.trim().is_empty() => {
+            if !final_newline {
+                out.truncate(out.trim_end_matches('\n').len());
+            }
+        }
+        None => out.push_str(&shift_headings(&pending, outside_delta, 3)?),
+    }
+
+    Ok(out)
+}
+
+/// Insert a daily body into its weekday section, wrapped in migration markers.
+///
+/// With `demote_headings`, the body's headings are moved down so none is
+/// shallower than `###` (nesting them under the weekday heading) and the
+/// shift is recorded in the start marker.
+#[allow(clippy::too_many_arguments)]
 pub fn inject_daily_into_weekly(
     weekly_content: &str,
     week_start: NaiveDate,
+    week: WeekStart,
+    numbering: WeekNumbering,
     day: NaiveDate,
     source_filename: &str,
     daily_body: &str,
+    locale: Locale,
+    demote_headings: bool,
 ) -> Result<String> {
-    let parsed = parse_weekly(weekly_content, week_start)?;
+    let parsed = parse_weekly(weekly_content, week_start, week, numbering, locale)?;
     let newline = parsed.newline;
 
-    let expected_heading = expected_weekly(week_start).weekday_headings
-        [day.weekday().num_days_from_monday() as usize]
-        .clone();
+    let expected_heading = expected_weekly(week_start, week, numbering, locale).weekday_headings
+        [week.day_index(day.weekday())]
+    .clone();
 
     let (day_idx, section) = parsed
         .days
@@ -368,9 +581,16 @@ pub fn inject_daily_into_weekly(
 
     // Idempotency: if we already injected this source file into this weekday section, skip.
     let section_text = &weekly_content[section.content_start..section.content_end];
-    let start_marker = marker_start(source_filename);
+    let demoted = if demote_headings {
+        demotion_for_weekly(daily_body)
+    } else {
+        0
+    };
+    let final_newline = daily_body.is_empty() || daily_body.ends_with('\n');
+    let start_marker = marker_start(source_filename, demoted, final_newline);
     let end_marker = marker_end(source_filename);
-    if section_text.contains(&start_marker) {
+    let start_prefix = format!("{}{}:start", MARKER_PREFIX, source_filename);
+    if section_text.contains(&start_prefix) {
         if !section_text.contains(&end_marker) {
             return Err(DjourError::Config(format!(
                 "Weekly note contains start marker but missing end marker for '{}'",
@@ -381,9 +601,13 @@ pub fn inject_daily_into_weekly(
     }
 
     // Insert before trailing whitespace in the weekday section to keep the blank-line padding before
-    // the next weekday heading.
-    let section_trimmed_len = section_text.trim_end().len();
-    let insert_at = section.content_start + section_trimmed_len;
+    // the next weekday heading. A blank section gets the block after its first line break, below
+    // the blank line the template leaves under each heading.
+    let insert_at = if section_text.trim().is_empty() {
+        section.content_start + section_text.find('\n').map_or(0, |i| i + 1)
+    } else {
+        section.content_start + section_text.trim_end().len()
+    };
 
     let nl = newline.as_str();
     let mut block = String::new();
@@ -399,7 +623,8 @@ pub fn inject_daily_into_weekly(
     block.push_str(&start_marker);
     block.push_str(nl);
 
-    let normalized_body = normalize_newlines(daily_body, newline);
+    let shifted_body = shift_headings(daily_body, demoted as isize, 1)?;
+    let normalized_body = normalize_newlines(&shifted_body, newline);
     let body_trimmed = normalized_body.trim_end_matches(['\n', '\r']);
     if !body_trimmed.is_empty() {
         block.push_str(body_trimmed);
@@ -422,22 +647,200 @@ pub fn inject_daily_into_weekly(
     out.push_str(&weekly_content[..insert_at]);
     out.push_str(&block);
     out.push_str(&weekly_content[insert_at..]);
-    Ok(out)
+    Ok(preserve_final_newline(weekly_content, out))
 }
 
+/// Daily bodies for each weekday of a weekly note (see [`daily_body_from_section`]).
 pub fn split_weekly_into_daily_bodies(
     weekly_content: &str,
     week_start: NaiveDate,
+    week: WeekStart,
+    numbering: WeekNumbering,
+    locale: Locale,
+    promote_headings: bool,
 ) -> Result<Vec<(NaiveDate, String)>> {
-    let parsed = parse_weekly(weekly_content, week_start)?;
-    validate_weekly_no_outside_content(weekly_content, &parsed)?;
+    let parsed = parse_weekly(weekly_content, week_start, week, numbering, locale)?;
+    validate_weekly_no_outside_content(weekly_content, &parsed, week)?;
 
     let mut out = Vec::with_capacity(7);
     for (i, section) in parsed.days.iter().enumerate() {
         let day = week_start + Duration::days(i as i64);
         let raw = &weekly_content[section.content_start..section.content_end];
-        let cleaned = strip_migration_markers(raw);
-        out.push((day, cleaned));
+        out.push((day, daily_body_from_section(raw, promote_headings)?));
     }
     Ok(out)
 }
+
+/// Replace the sections of the days at `reset` (indexes in week order) with
+/// the blank padding `template` (a rendered built-in weekly note) has for
+/// them; every other byte of `weekly_content` is kept.
+#[allow(clippy::too_many_arguments)]
+pub fn reset_weekly_sections(
+    weekly_content: &str,
+    template: &str,
+    week_start: NaiveDate,
+    week: WeekStart,
+    numbering: WeekNumbering,
+    locale: Locale,
+    reset: &[usize],
+) -> Result<String> {
+    let parsed = parse_weekly(weekly_content, week_start, week, numbering, locale)?;
+    let blank = parse_weekly(template, week_start, week, numbering, locale)?;
+    let newline = parsed.newline;
+
+    let mut out = String::with_capacity(weekly_content.len());
+    let mut copied = 0;
+    for (i, (section, padding)) in parsed.days.iter().zip(&blank.days).enumerate() {
+        if !reset.contains(&i) {
+            continue;
+        }
+        out.push_str(&weekly_content[copied..section.content_start]);
+        out.push_str(&normalize_newlines(
+            &template[padding.content_start..padding.content_end],
+            newline,
+        ));
+        copied = section.content_end;
+    }
+    out.push_str(&weekly_content[copied..]);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shift_headings_skips_fences_and_comments() {
+        let text = "## Top\n```\n## code\n```\n<!--\n## hidden\n-->\n### Deeper\n#hashtag\n";
+        assert_eq!(
+            shift_headings(text, 1, 1).unwrap(),
+            "### Top\n```\n## code\n```\n<!--\n## hidden\n-->\n#### Deeper\n#hashtag\n"
+        );
+        assert_eq!(
+            shift_headings(text, -1, 3).unwrap(),
+            "## Top\n```\n## code\n```\n<!--\n## hidden\n-->\n## Deeper\n#hashtag\n"
+        );
+        assert!(shift_headings("###### Six\n", 1, 1).is_err());
+    }
+
+    #[test]
+    fn test_section_restores_recorded_demotion_and_drops_padding() {
+        let section = "\n<!-- djour:migrated-from=2025-01-14.md:start demoted=1 -->\n\
+            Intro\n\n### Standup\n#### Details\n\
+            <!-- djour:migrated-from=2025-01-14.md:end -->\n\n\n";
+        assert_eq!(
+            daily_body_from_section(section, false).unwrap(),
+            "\nIntro\n\n## Standup\n### Details\n"
+        );
+    }
+
+    #[test]
+    fn test_section_promotes_only_outside_marked_blocks() {
+        let section = "### Loose\n\
+            <!-- djour:migrated-from=a.md:start -->\n### Kept\n\
+            <!-- djour:migrated-from=a.md:end -->\n";
+        assert_eq!(
+            daily_body_from_section(section, true).unwrap(),
+            "## Loose\n### Kept\n"
+        );
+        assert_eq!(
+            daily_body_from_section(section, false).unwrap(),
+            "### Loose\n### Kept\n"
+        );
+    }
+
+    #[test]
+    fn test_demotion_for_weekly() {
+        assert_eq!(demotion_for_weekly("# Title\n## Sub\n"), 2);
+        assert_eq!(demotion_for_weekly("### Already nested\n"), 0);
+        assert_eq!(demotion_for_weekly("```\n# code\n```\nplain\n"), 0);
+    }
+
+    fn weekly_note() -> (NaiveDate, String) {
+        let start = NaiveDate::from_ymd_opt(2025, 1, 13).unwrap();
+        let content = Template::from_builtin_for_week("weekly.md", WeekStart::Monday)
+            .unwrap()
+            .render_for_week(
+                start + Duration::days(3),
+                Locale::English,
+                WeekStart::Monday,
+            );
+        (start, content)
+    }
+
+    fn inject(weekly: &str, start: NaiveDate, day: u32, body: &str) -> String {
+        inject_daily_into_weekly(
+            weekly,
+            start,
+            WeekStart::Monday,
+            WeekNumbering::Iso,
+            NaiveDate::from_ymd_opt(2025, 1, day).unwrap(),
+            &format!("2025-01-{}.md", day),
+            body,
+            Locale::English,
+            true,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_injection_keeps_weekly_final_newline() {
+        let (start, with_newline) = weekly_note();
+        assert!(with_newline.ends_with('\n'));
+        let without_newline = with_newline.trim_end().to_string();
+
+        // Sunday is the last section, so its block lands at the very end
+        let end = "Rest day\n<!-- djour:migrated-from=2025-01-19.md:end -->";
+        let out = inject(&with_newline, start, 19, "Rest day");
+        assert!(out.ends_with(&format!("{}\n", end)), "{:?}", out);
+        assert!(
+            out.contains("## Sunday (January 19, 2025)\n\n<!--"),
+            "{:?}",
+            out
+        );
+        let out = inject(&without_newline, start, 19, "Rest day");
+        assert!(out.ends_with(end), "{:?}", out);
+    }
+
+    #[test]
+    fn test_split_restores_daily_final_newline() {
+        let (start, weekly) = weekly_note();
+        for body in ["Standup #work\n", "Standup #work", "## Notes\n\nStandup"] {
+            let out = inject(&weekly, start, 14, body);
+            let bodies = split_weekly_into_daily_bodies(
+                &out,
+                start,
+                WeekStart::Monday,
+                WeekNumbering::Iso,
+                Locale::English,
+                true,
+            )
+            .unwrap();
+            // The blank line under the weekday heading comes along
+            assert_eq!(bodies[1].1, format!("\n{}", body), "{:?}", out);
+        }
+
+        let out = inject(&weekly, start, 14, "Standup");
+        assert!(
+            out.contains(":start no-final-newline -->\nStandup\n<!--"),
+            "{:?}",
+            out
+        );
+    }
+
+    #[test]
+    fn test_split_of_unmarked_section_ends_with_newline() {
+        let (start, weekly) = weekly_note();
+        let weekly = format!("{}Loose Sunday text", weekly);
+        let bodies = split_weekly_into_daily_bodies(
+            &weekly,
+            start,
+            WeekStart::Monday,
+            WeekNumbering::Iso,
+            Locale::English,
+            false,
+        )
+        .unwrap();
+        assert_eq!(bodies[6].1.trim_start(), "Loose Sunday text\n");
+    }
+}
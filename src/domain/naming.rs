@@ -0,0 +1,177 @@
+//! Note file naming: journal mode plus user-configured filename decorations
+
+use super::mode::JournalMode;
+use super::week::{WeekNumbering, WeekStart};
+use chrono::NaiveDate;
+
+/// Generates and recognizes note filenames for a journal mode.
+///
+/// Dated notes may carry a configured prefix (`journal-2025-01-17.md`). Files
+/// without the prefix are still recognized, so journals can mix both. With
+/// `allow_suffix`, daily notes may also end in a title slug
+/// (`2025-01-17-planning-day.md`). Weekly notes start on `week_start` and are
+/// numbered by `week_numbering`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoteNaming {
+    pub mode: JournalMode,
+    pub prefix: String,
+    pub allow_suffix: bool,
+    pub week_start: WeekStart,
+    pub week_numbering: WeekNumbering,
+}
+
+impl NoteNaming {
+    pub fn new(mode: JournalMode, prefix: impl Into<String>, allow_suffix: bool) -> Self {
+        NoteNaming {
+            mode,
+            prefix: prefix.into(),
+            allow_suffix,
+            week_start: WeekStart::default(),
+            week_numbering: WeekNumbering::default(),
+        }
+    }
+
+    /// Use `week_start` as the first day of weekly notes
+    pub fn with_week_start(mut self, week_start: WeekStart) -> Self {
+        self.week_start = week_start;
+        self
+    }
+
+    /// Number weekly notes by `week_numbering`
+    pub fn with_week_numbering(mut self, week_numbering: WeekNumbering) -> Self {
+        self.week_numbering = week_numbering;
+        self
+    }
+
+    /// Filename for a date; the prefix is applied to dated modes only.
+    pub fn filename_for_date(&self, date: NaiveDate) -> String {
+        let bare = self
+            .mode
+            .filename_for_date_numbered(date, self.week_start, self.week_numbering);
+        if self.prefix.is_empty() || matches!(self.mode, JournalMode::Single) {
+            bare
+        } else {
+            format!("{}{}", self.prefix, bare)
+        }
+    }
+
+    /// Remove the configured prefix from a filename, if present.
+    pub fn strip_prefix<'a>(&self, filename: &'a str) -> &'a str {
+        if self.prefix.is_empty() {
+            return filename;
+        }
+        filename
+            .strip_prefix(self.prefix.as_str())
+            .unwrap_or(filename)
+    }
+
+    /// Date a filename represents, or None if it is not a note for this mode.
+    pub fn date_from_filename(&self, filename: &str) -> Option<NaiveDate> {
+        if matches!(self.mode, JournalMode::Single) {
+            return None;
+        }
+
+        let name = self.strip_prefix(filename);
+        if let Some(date) =
+            self.mode
+                .date_from_filename_numbered(name, self.week_start, self.week_numbering)
+        {
+            return Some(date);
+        }
+
+        if self.allow_suffix && matches!(self.mode, JournalMode::Daily) {
+            // YYYY-MM-DD-<slug>.md
+            let stem = name.strip_suffix(".md")?;
+            let (date_part, slug) = (stem.get(..10)?, stem.get(10..)?);
+            if slug.len() > 1 && slug.starts_with('-') {
+                return self.mode.date_from_filename(&format!("{}.md", date_part));
+            }
+        }
+
+        None
+    }
+}
+
+impl From<JournalMode> for NoteNaming {
+    fn from(mode: JournalMode) -> Self {
+        NoteNaming::new(mode, "", false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn bare_naming_matches_mode() {
+        let naming = NoteNaming::from(JournalMode::Daily);
+        assert_eq!(naming.filename_for_date(date(2025, 1, 17)), "2025-01-17.md");
+        assert_eq!(
+            naming.date_from_filename("2025-01-17.md"),
+            Some(date(2025, 1, 17))
+        );
+        assert_eq!(naming.date_from_filename("2025-01-17-planning.md"), None);
+    }
+
+    #[test]
+    fn prefix_round_trip_for_dated_modes() {
+        for mode in [
+            JournalMode::Daily,
+            JournalMode::Weekly,
+            JournalMode::Monthly,
+        ] {
+            let naming = NoteNaming::new(mode, "journal-", false);
+            let filename = naming.filename_for_date(date(2025, 1, 17));
+            assert!(filename.starts_with("journal-"), "{filename}");
+            let parsed = naming.date_from_filename(&filename).unwrap();
+            assert_eq!(naming.filename_for_date(parsed), filename);
+        }
+    }
+
+    #[test]
+    fn prefixed_and_bare_files_both_recognized() {
+        let naming = NoteNaming::new(JournalMode::Daily, "journal-", false);
+        assert_eq!(
+            naming.date_from_filename("journal-2025-01-17.md"),
+            Some(date(2025, 1, 17))
+        );
+        assert_eq!(
+            naming.date_from_filename("2025-01-16.md"),
+            Some(date(2025, 1, 16))
+        );
+        assert_eq!(naming.date_from_filename("other-2025-01-16.md"), None);
+    }
+
+    #[test]
+    fn single_mode_ignores_prefix() {
+        let naming = NoteNaming::new(JournalMode::Single, "journal-", true);
+        assert_eq!(naming.filename_for_date(date(2025, 1, 17)), "journal.md");
+        assert_eq!(naming.date_from_filename("journal.md"), None);
+    }
+
+    #[test]
+    fn daily_suffix_accepted_only_when_enabled() {
+        let naming = NoteNaming::new(JournalMode::Daily, "journal-", true);
+        assert_eq!(
+            naming.date_from_filename("2025-01-17-planning-day.md"),
+            Some(date(2025, 1, 17))
+        );
+        assert_eq!(
+            naming.date_from_filename("journal-2025-01-17-planning-day.md"),
+            Some(date(2025, 1, 17))
+        );
+        assert_eq!(naming.date_from_filename("2025-01-17-.md"), None);
+        assert_eq!(naming.date_from_filename("2025-01-17planning.md"), None);
+        assert_eq!(naming.date_from_filename("2025-13-17-slug.md"), None);
+
+        let weekly = NoteNaming::new(JournalMode::Weekly, "", true);
+        assert_eq!(
+            weekly.date_from_filename("2025-W03-2025-01-13-sprint.md"),
+            None
+        );
+    }
+}
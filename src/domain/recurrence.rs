@@ -0,0 +1,344 @@
+//! Recurring entries (`.djour/recurring.md`)
+//!
+//! Each line of the directives file is `@every <rule>: <markdown>`. When a new
+//! note is created, the markdown of every directive whose rule matches a day
+//! of the note is added to it.
+
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use std::fmt;
+use std::str::FromStr;
+
+/// Days a recurring entry is added on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rule {
+    /// Every week on this day (`monday` .. `sunday`)
+    On(Weekday),
+    /// Monday to Friday (`weekday`)
+    Weekday,
+    /// This day of every month (`1st` .. `31st`); months without it are skipped
+    DayOfMonth(u32),
+    /// The last day of every month (`last-day`)
+    LastDay,
+}
+
+impl Rule {
+    /// Whether the rule adds its entry on `date`
+    pub fn matches(&self, date: NaiveDate) -> bool {
+        match self {
+            Rule::On(weekday) => date.weekday() == *weekday,
+            Rule::Weekday => date.weekday().num_days_from_monday() < 5,
+            Rule::DayOfMonth(day) => date.day() == *day,
+            Rule::LastDay => date
+                .succ_opt()
+                .is_none_or(|next| next.month() != date.month()),
+        }
+    }
+
+    /// First day on or after `date` the rule matches
+    pub fn next_on_or_after(&self, date: NaiveDate) -> Option<NaiveDate> {
+        // Every rule matches at least once in any 62 consecutive days
+        (0..62)
+            .filter_map(|offset| date.checked_add_signed(Duration::days(offset)))
+            .find(|day| self.matches(*day))
+    }
+}
+
+/// `1st`, `2nd`, `3rd`, `4th`, ... `21st`, `22nd`, `23rd`, ...
+fn ordinal(day: u32) -> String {
+    let suffix = match (day % 10, day % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{}{}", day, suffix)
+}
+
+impl fmt::Display for Rule {
+    /// The rule as written in the directives file
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rule::On(weekday) => f.write_str(match weekday {
+                Weekday::Mon => "monday",
+                Weekday::Tue => "tuesday",
+                Weekday::Wed => "wednesday",
+                Weekday::Thu => "thursday",
+                Weekday::Fri => "friday",
+                Weekday::Sat => "saturday",
+                Weekday::Sun => "sunday",
+            }),
+            Rule::Weekday => f.write_str("weekday"),
+            Rule::DayOfMonth(day) => f.write_str(&ordinal(*day)),
+            Rule::LastDay => f.write_str("last-day"),
+        }
+    }
+}
+
+impl FromStr for Rule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rule = s.trim().to_lowercase();
+        let invalid = || {
+            format!(
+                "Invalid rule: '{}'. Valid rules are: monday .. sunday, weekday, 1st .. 31st, last-day",
+                s.trim()
+            )
+        };
+        match rule.as_str() {
+            "weekday" => return Ok(Rule::Weekday),
+            "last-day" => return Ok(Rule::LastDay),
+            _ => {}
+        }
+        if rule.starts_with(|c: char| c.is_ascii_digit()) {
+            let digits = rule.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+            return match digits.parse::<u32>() {
+                Ok(day @ 1..=31) if rule == ordinal(day) => Ok(Rule::DayOfMonth(day)),
+                _ => Err(invalid()),
+            };
+        }
+        // Full weekday names only; "mon" reads too much like a typo of "month"
+        match rule.parse::<Weekday>() {
+            Ok(weekday) if rule.len() > 3 => Ok(Rule::On(weekday)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// One `@every <rule>: <markdown>` line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Directive {
+    /// 1-based line number in the directives file
+    pub line: usize,
+    pub rule: Rule,
+    /// Markdown added to matching notes
+    pub entry: String,
+}
+
+/// Parsed directives file; lines that could not be parsed are kept as errors
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RecurringDirectives {
+    pub directives: Vec<Directive>,
+    /// `line N: message` for each invalid line
+    pub errors: Vec<String>,
+}
+
+impl RecurringDirectives {
+    /// Parse a directives file.
+    ///
+    /// Blank lines and lines starting with `#` (headings, comments) are skipped;
+    /// every other line must be a directive.
+    pub fn parse(content: &str) -> Self {
+        let mut parsed = RecurringDirectives::default();
+        for (index, raw) in content.lines().enumerate() {
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match parse_directive(line) {
+                Ok((rule, entry)) => parsed.directives.push(Directive {
+                    line: index + 1,
+                    rule,
+                    entry,
+                }),
+                Err(message) => parsed
+                    .errors
+                    .push(format!("line {}: {}", index + 1, message)),
+            }
+        }
+        parsed
+    }
+
+    /// Entries of the directives matching any day in `days`, in file order and
+    /// each at most once
+    pub fn entries_for(&self, days: impl IntoIterator<Item = NaiveDate> + Clone) -> Vec<&str> {
+        self.directives
+            .iter()
+            .filter(|directive| {
+                days.clone()
+                    .into_iter()
+                    .any(|day| directive.rule.matches(day))
+            })
+            .map(|directive| directive.entry.as_str())
+            .collect()
+    }
+}
+
+fn parse_directive(line: &str) -> Result<(Rule, String), String> {
+    let Some(rest) = line
+        .strip_prefix("@every")
+        .filter(|rest| rest.starts_with(' '))
+    else {
+        return Err("expected '@every <rule>: <markdown>'".to_string());
+    };
+    let Some((rule, entry)) = rest.split_once(':') else {
+        return Err("missing ':' between the rule and the entry".to_string());
+    };
+    let rule = Rule::from_str(rule)?;
+    let entry = entry.trim();
+    if entry.is_empty() {
+        return Err(format!("no entry after '@every {}:'", rule));
+    }
+    Ok((rule, entry.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        assert_eq!(Rule::from_str("monday"), Ok(Rule::On(Weekday::Mon)));
+        assert_eq!(Rule::from_str(" Sunday "), Ok(Rule::On(Weekday::Sun)));
+        assert_eq!(Rule::from_str("weekday"), Ok(Rule::Weekday));
+        assert_eq!(Rule::from_str("1st"), Ok(Rule::DayOfMonth(1)));
+        assert_eq!(Rule::from_str("2nd"), Ok(Rule::DayOfMonth(2)));
+        assert_eq!(Rule::from_str("3rd"), Ok(Rule::DayOfMonth(3)));
+        assert_eq!(Rule::from_str("11th"), Ok(Rule::DayOfMonth(11)));
+        assert_eq!(Rule::from_str("15th"), Ok(Rule::DayOfMonth(15)));
+        assert_eq!(Rule::from_str("22nd"), Ok(Rule::DayOfMonth(22)));
+        assert_eq!(Rule::from_str("31st"), Ok(Rule::DayOfMonth(31)));
+        assert_eq!(Rule::from_str("last-day"), Ok(Rule::LastDay));
+
+        for invalid in [
+            "mon", "mondy", "0th", "32nd", "2st", "11st", "15", "last", "",
+        ] {
+            let err = Rule::from_str(invalid).unwrap_err();
+            assert!(err.starts_with("Invalid rule"), "{}: {}", invalid, err);
+        }
+    }
+
+    #[test]
+    fn test_rule_display_round_trips() {
+        for text in ["monday", "weekday", "1st", "12th", "23rd", "last-day"] {
+            assert_eq!(Rule::from_str(text).unwrap().to_string(), text);
+        }
+    }
+
+    #[test]
+    fn test_weekday_rules() {
+        // 2026-10-12 is a Monday
+        let monday = Rule::On(Weekday::Mon);
+        assert!(monday.matches(date(2026, 10, 12)));
+        assert!(monday.matches(date(2026, 10, 19)));
+        assert!(!monday.matches(date(2026, 10, 13)));
+
+        let weekdays: Vec<bool> = (12..=18)
+            .map(|d| Rule::Weekday.matches(date(2026, 10, d)))
+            .collect();
+        assert_eq!(weekdays, [true, true, true, true, true, false, false]);
+    }
+
+    #[test]
+    fn test_day_of_month_rules_respect_month_lengths() {
+        assert!(Rule::DayOfMonth(1).matches(date(2026, 3, 1)));
+        assert!(Rule::DayOfMonth(15).matches(date(2026, 2, 15)));
+        assert!(!Rule::DayOfMonth(15).matches(date(2026, 2, 16)));
+
+        // The 31st only exists in long months
+        let thirty_first = Rule::DayOfMonth(31);
+        assert!(thirty_first.matches(date(2026, 1, 31)));
+        assert!(!thirty_first.matches(date(2026, 4, 30)));
+        assert_eq!(
+            thirty_first.next_on_or_after(date(2026, 4, 1)),
+            Some(date(2026, 5, 31))
+        );
+        assert_eq!(
+            Rule::DayOfMonth(29).next_on_or_after(date(2026, 2, 1)),
+            Some(date(2026, 3, 29))
+        );
+        assert_eq!(
+            Rule::DayOfMonth(29).next_on_or_after(date(2028, 2, 1)),
+            Some(date(2028, 2, 29))
+        );
+    }
+
+    #[test]
+    fn test_last_day_rule() {
+        assert!(Rule::LastDay.matches(date(2026, 1, 31)));
+        assert!(Rule::LastDay.matches(date(2026, 4, 30)));
+        assert!(Rule::LastDay.matches(date(2026, 12, 31)));
+        assert!(!Rule::LastDay.matches(date(2026, 12, 30)));
+
+        // February: 28th in common years, 29th in leap years
+        assert!(Rule::LastDay.matches(date(2026, 2, 28)));
+        assert!(!Rule::LastDay.matches(date(2028, 2, 28)));
+        assert!(Rule::LastDay.matches(date(2028, 2, 29)));
+        assert!(Rule::LastDay.matches(date(2100, 2, 28)));
+        assert!(Rule::LastDay.matches(date(2000, 2, 29)));
+    }
+
+    #[test]
+    fn test_parse_directives_file() {
+        let content = "# Recurring\n\
+            \n\
+            @every 1st: - review budget #finance\n\
+            @every monday: - [ ] plan the week\n\
+            @every mondy: - typo\n\
+            every friday: - missing @\n\
+            @every friday - no colon\n\
+            @every last-day:   \n\
+            @every last-day: - close the books: all of them\n";
+        let parsed = RecurringDirectives::parse(content);
+
+        assert_eq!(
+            parsed.directives,
+            vec![
+                Directive {
+                    line: 3,
+                    rule: Rule::DayOfMonth(1),
+                    entry: "- review budget #finance".to_string(),
+                },
+                Directive {
+                    line: 4,
+                    rule: Rule::On(Weekday::Mon),
+                    entry: "- [ ] plan the week".to_string(),
+                },
+                Directive {
+                    line: 9,
+                    rule: Rule::LastDay,
+                    entry: "- close the books: all of them".to_string(),
+                },
+            ]
+        );
+        assert_eq!(parsed.errors.len(), 4);
+        assert!(parsed.errors[0].starts_with("line 5: Invalid rule: 'mondy'"));
+        assert_eq!(
+            parsed.errors[1],
+            "line 6: expected '@every <rule>: <markdown>'"
+        );
+        assert_eq!(
+            parsed.errors[2],
+            "line 7: missing ':' between the rule and the entry"
+        );
+        assert_eq!(
+            parsed.errors[3],
+            "line 8: no entry after '@every last-day:'"
+        );
+    }
+
+    #[test]
+    fn test_entries_for_days() {
+        let parsed = RecurringDirectives::parse(
+            "@every 1st: - budget\n@every monday: - plan\n@every weekday: - standup\n",
+        );
+        // Thursday 1 October 2026
+        assert_eq!(
+            parsed.entries_for([date(2026, 10, 1)]),
+            ["- budget", "- standup"]
+        );
+        // Sunday
+        assert!(parsed.entries_for([date(2026, 10, 4)]).is_empty());
+        // A whole month matches each directive once
+        let october = (1..=31).map(|d| date(2026, 10, d));
+        assert_eq!(
+            parsed.entries_for(october),
+            ["- budget", "- plan", "- standup"]
+        );
+    }
+}
@@ -0,0 +1,242 @@
+//! Reminders: lines tagged `#remind` with a due date
+//!
+//! The due date is a DD-MM-YYYY word on the same line, written like a tag
+//! (`- call dentist #remind #15-02-2025`), or the value of the reminder tag
+//! (`#remind:15-02-2025`). Date-shaped words are not tags anywhere else, so
+//! reminders are found with their own pattern rather than the tag parser.
+
+use crate::domain::markdown::{html_comment_regions, in_regions, parse_fence_marker};
+use crate::domain::tags::{parse_front_matter, strip_tag_footer};
+use crate::domain::Diagnostics;
+use chrono::NaiveDate;
+use regex::Regex;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Date format of reminder due dates
+const DUE_DATE_FORMAT: &str = "%d-%m-%Y";
+
+/// End of a tag: the end of the line or a character no tag name holds, so
+/// `#remind-later` and `#remind/work` are other tags. Captured so replacing a
+/// match can put it back.
+const TAG_END: &str = r"($|[^a-zA-Z0-9_/-])";
+
+/// `#remind`, with an optional `:DD-MM-YYYY` value
+fn remind_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(&format!(
+            r"(?i)(^|[\s(\[])#remind(?::(\d{{1,2}}-\d{{1,2}}-\d{{4}}))?{}",
+            TAG_END
+        ))
+        .unwrap()
+    })
+}
+
+/// A `#DD-MM-YYYY` date word
+fn due_date_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(&format!(
+            r"(^|[\s(\[])#(\d{{1,2}}-\d{{1,2}}-\d{{4}}){}",
+            TAG_END
+        ))
+        .unwrap()
+    })
+}
+
+/// List marker and task checkbox at the start of a line
+fn item_marker_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| Regex::new(r"^\s*(?:[-*+]|\d+[.)])\s+(?:\[([ xX])\]\s+)?").unwrap())
+}
+
+/// One reminder of a note
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReminderItem {
+    /// The line without its list marker, checkbox and reminder tags
+    pub text: String,
+    pub due: NaiveDate,
+    pub source_file: PathBuf,
+    /// 1-based line of the reminder
+    pub line: usize,
+    /// Days past `due`: 0 when due today, negative when still ahead
+    pub overdue_days: i64,
+}
+
+impl ReminderItem {
+    /// Due today or earlier
+    pub fn is_due(&self) -> bool {
+        self.overdue_days >= 0
+    }
+
+    /// Due before today
+    pub fn is_overdue(&self) -> bool {
+        self.overdue_days > 0
+    }
+}
+
+/// Extract every open reminder of a note, in document order, with its
+/// overdue days counted from `today`.
+///
+/// Checked tasks (`- [x] ... #remind`), code blocks, HTML comments, front
+/// matter and the tag footer are skipped. A reminder without a due date, or
+/// with one that is not on the calendar, is reported in `diagnostics`.
+pub fn extract_reminders(
+    content: &str,
+    source_file: &Path,
+    today: NaiveDate,
+    diagnostics: &mut Diagnostics,
+) -> Vec<ReminderItem> {
+    let content = &*strip_tag_footer(content);
+    let body_offset = parse_front_matter(content).map_or(0, |front_matter| front_matter.len);
+    let comments = html_comment_regions(content);
+    let file = source_file.to_string_lossy().replace('\\', "/");
+
+    let mut reminders = Vec::new();
+    let mut active_fence: Option<(char, usize)> = None;
+    let mut line_start = 0;
+    for (index, line) in content.split_inclusive('\n').enumerate() {
+        let start = line_start;
+        line_start += line.len();
+        if start < body_offset || in_regions(&comments, start) {
+            continue;
+        }
+        if let Some((fence_char, min_len)) = active_fence {
+            if matches!(parse_fence_marker(line), Some((c, len)) if c == fence_char && len >= min_len)
+            {
+                active_fence = None;
+            }
+            continue;
+        }
+        if let Some(marker) = parse_fence_marker(line) {
+            active_fence = Some(marker);
+            continue;
+        }
+
+        let line = line.trim_end();
+        let Some(remind) = remind_regex().captures(line) else {
+            continue;
+        };
+        let marker = item_marker_regex().captures(line);
+        if marker
+            .as_ref()
+            .and_then(|m| m.get(1))
+            .is_some_and(|checkbox| checkbox.as_str() != " ")
+        {
+            continue;
+        }
+
+        let due_text = remind.get(2).map(|value| value.as_str()).or_else(|| {
+            due_date_regex()
+                .captures(line)
+                .and_then(|date| date.get(2))
+                .map(|date| date.as_str())
+        });
+        let Some(due_text) = due_text else {
+            diagnostics.warn(
+                &file,
+                format!("line {}: #remind without a #DD-MM-YYYY due date", index + 1),
+            );
+            continue;
+        };
+        let Ok(due) = NaiveDate::parse_from_str(due_text, DUE_DATE_FORMAT) else {
+            diagnostics.warn(
+                &file,
+                format!(
+                    "line {}: reminder date {} is not a calendar date",
+                    index + 1,
+                    due_text
+                ),
+            );
+            continue;
+        };
+
+        let text = &line[marker.map_or(0, |m| m.get(0).map_or(0, |all| all.end()))..];
+        let text = remind_regex().replace_all(text, "${1}${3}");
+        let text = due_date_regex().replace_all(&text, "${1}${3}");
+        reminders.push(ReminderItem {
+            text: text.split_whitespace().collect::<Vec<_>>().join(" "),
+            due,
+            source_file: source_file.to_path_buf(),
+            line: index + 1,
+            overdue_days: (today - due).num_days(),
+        });
+    }
+    reminders
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn reminders(content: &str, today: NaiveDate) -> (Vec<ReminderItem>, Diagnostics) {
+        let mut diagnostics = Diagnostics::new();
+        let found = extract_reminders(content, Path::new("2025-02-01.md"), today, &mut diagnostics);
+        (found, diagnostics)
+    }
+
+    #[test]
+    fn test_due_date_from_companion_tag_or_value() {
+        let content = "# Day\n\n- call dentist #remind #15-02-2025\n- [ ] renew passport #remind:1-3-2025 #home\n";
+        let (found, diagnostics) = reminders(content, date(2025, 2, 15));
+        assert!(diagnostics.is_empty());
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].text, "call dentist");
+        assert_eq!(found[0].due, date(2025, 2, 15));
+        assert_eq!(found[0].line, 3);
+        assert_eq!(found[1].text, "renew passport #home");
+        assert_eq!(found[1].due, date(2025, 3, 1));
+    }
+
+    #[test]
+    fn test_overdue_days() {
+        let content = "- pay rent #remind #31-01-2025\n";
+        let overdue = |today| reminders(content, today).0[0].overdue_days;
+        assert_eq!(overdue(date(2025, 1, 31)), 0);
+        assert_eq!(overdue(date(2025, 2, 3)), 3);
+        assert_eq!(overdue(date(2025, 1, 30)), -1);
+        // Across a leap day
+        let leap = reminders("#remind:28-02-2024 file taxes\n", date(2024, 3, 1)).0;
+        assert_eq!(leap[0].overdue_days, 2);
+        assert!(leap[0].is_overdue());
+    }
+
+    #[test]
+    fn test_invalid_or_missing_dates_are_reported() {
+        let content = "- a #remind #31-02-2025\n- b #remind\n- c #reminder #01-02-2025\n";
+        let (found, diagnostics) = reminders(content, date(2025, 2, 1));
+        assert!(found.is_empty());
+        let messages: Vec<String> = diagnostics.iter().map(|d| d.message.clone()).collect();
+        assert_eq!(
+            messages,
+            [
+                "line 1: reminder date 31-02-2025 is not a calendar date",
+                "line 2: #remind without a #DD-MM-YYYY due date",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_longer_tags_are_not_reminders() {
+        let content = "- idea #remind-later #01-01-2025\n- sort #remind/work #01-01-2025\n- call (#remind) #01-01-2025-x #02-01-2025.\n";
+        let (found, diagnostics) = reminders(content, date(2025, 2, 1));
+        assert!(diagnostics.is_empty());
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].text, "call () #01-01-2025-x .");
+        assert_eq!(found[0].due, date(2025, 1, 2));
+    }
+
+    #[test]
+    fn test_skips_done_tasks_code_and_comments() {
+        let content = "- [x] done #remind #01-01-2025\n\n```\n#remind #01-01-2025\n```\n\n<!-- #remind #01-01-2025 -->\n";
+        let (found, diagnostics) = reminders(content, date(2025, 2, 1));
+        assert!(found.is_empty());
+        assert!(diagnostics.is_empty());
+    }
+}
@@ -0,0 +1,328 @@
+//! Time-of-day sections of daily notes
+//!
+//! The `[sections]` config table maps section headings to times of day, so
+//! `capture` can add an entry under "Morning" before noon and under "Evening"
+//! at night.
+
+use crate::domain::markdown::{atx_heading, html_comment_regions, in_regions, parse_fence_marker};
+use chrono::NaiveTime;
+use serde::de::{MapAccess, Visitor};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// Times of day from `start` up to, not including, `end`.
+///
+/// A range whose end is not after its start wraps around midnight:
+/// `22:00-05:00` covers the late evening and the early morning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TimeRange {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl TimeRange {
+    /// Whether `time` falls in this range
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start < self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+impl FromStr for TimeRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "Invalid time range '{}'. Use HH:MM-HH:MM, e.g. 05:00-12:00 (22:00-05:00 wraps past midnight)",
+                s
+            )
+        };
+        let (start, end) = s.split_once('-').ok_or_else(invalid)?;
+        let parse = |time: &str| NaiveTime::parse_from_str(time.trim(), "%H:%M").ok();
+        let (start, end) = parse(start).zip(parse(end)).ok_or_else(invalid)?;
+        if start == end {
+            return Err(format!(
+                "Invalid time range '{}': it starts and ends at the same time",
+                s
+            ));
+        }
+        Ok(TimeRange { start, end })
+    }
+}
+
+impl TryFrom<String> for TimeRange {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<TimeRange> for String {
+    fn from(range: TimeRange) -> Self {
+        range.to_string()
+    }
+}
+
+impl fmt::Display for TimeRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}-{}",
+            self.start.format("%H:%M"),
+            self.end.format("%H:%M")
+        )
+    }
+}
+
+/// Section headings with the times of day they are for (`[sections]` table),
+/// in config file order.
+///
+/// Ranges may overlap or leave gaps; the first range containing a time wins.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DaySections(pub Vec<(String, TimeRange)>);
+
+impl DaySections {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Heading of the first section whose range contains `time`
+    pub fn section_at(&self, time: NaiveTime) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(_, range)| range.contains(time))
+            .map(|(heading, _)| heading.as_str())
+    }
+}
+
+impl Serialize for DaySections {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (heading, range) in &self.0 {
+            map.serialize_entry(heading, range)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for DaySections {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct SectionsVisitor;
+
+        impl<'de> Visitor<'de> for SectionsVisitor {
+            type Value = DaySections;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a table of section headings to \"HH:MM-HH:MM\" time ranges")
+            }
+
+            // Entries arrive in file order, which decides overlaps
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<DaySections, A::Error> {
+                let mut sections = Vec::new();
+                while let Some(entry) = map.next_entry::<String, TimeRange>()? {
+                    sections.push(entry);
+                }
+                Ok(DaySections(sections))
+            }
+        }
+
+        deserializer.deserialize_map(SectionsVisitor)
+    }
+}
+
+/// Where a section of a note is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SectionSpan {
+    /// Byte offset of the start of the heading line
+    pub heading: usize,
+    /// Heading level (number of `#`)
+    pub level: usize,
+    /// Byte offset where the section ends: the next heading of the same or a
+    /// higher level, or the end of the note
+    pub end: usize,
+}
+
+/// The section of `content` under the ATX heading titled `title`.
+///
+/// Titles are compared without case and surrounding whitespace, ignoring a
+/// closing `#` sequence. Headings in fenced code blocks and HTML comments do
+/// not count; the first matching heading wins.
+pub fn find_section(content: &str, title: &str) -> Option<SectionSpan> {
+    let wanted = title.trim().to_lowercase();
+    let comments = html_comment_regions(content);
+    let mut active_fence: Option<(char, usize)> = None;
+    let mut found: Option<(usize, usize)> = None;
+    let mut offset = 0;
+
+    for line in content.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+
+        if let Some((fence_char, min_len)) = active_fence {
+            if matches!(parse_fence_marker(line), Some((c, len)) if c == fence_char && len >= min_len)
+            {
+                active_fence = None;
+            }
+            continue;
+        }
+        if in_regions(&comments, line_start) {
+            continue;
+        }
+        if let Some(marker) = parse_fence_marker(line) {
+            active_fence = Some(marker);
+            continue;
+        }
+
+        let Some((indent, level)) = atx_heading(line) else {
+            continue;
+        };
+        match found {
+            Some((heading, found_level)) => {
+                if level <= found_level {
+                    return Some(SectionSpan {
+                        heading,
+                        level: found_level,
+                        end: line_start,
+                    });
+                }
+            }
+            None => {
+                if heading_title(&line[indent + level..]).to_lowercase() == wanted {
+                    found = Some((line_start, level));
+                }
+            }
+        }
+    }
+
+    found.map(|(heading, level)| SectionSpan {
+        heading,
+        level,
+        end: content.len(),
+    })
+}
+
+/// Text of a heading after its `#` marker, without a closing `#` sequence
+fn heading_title(rest: &str) -> &str {
+    let text = rest.trim();
+    let without_closing = text.trim_end_matches('#');
+    if without_closing.is_empty() {
+        ""
+    } else if without_closing.ends_with([' ', '\t']) {
+        without_closing.trim_end()
+    } else {
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn range(s: &str) -> TimeRange {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_time_range_parse_and_display() {
+        let morning = range("05:00-12:00");
+        assert_eq!(morning.start, time(5, 0));
+        assert_eq!(morning.end, time(12, 0));
+        assert_eq!(morning.to_string(), "05:00-12:00");
+        assert_eq!(range(" 9:30 - 17:45 ").to_string(), "09:30-17:45");
+
+        for bad in ["", "05:00", "05:00-", "5-12", "25:00-06:00", "12:00-12:00"] {
+            assert!(bad.parse::<TimeRange>().is_err(), "{:?}", bad);
+        }
+    }
+
+    #[test]
+    fn test_time_range_end_is_exclusive() {
+        let morning = range("05:00-12:00");
+        assert!(morning.contains(time(5, 0)));
+        assert!(morning.contains(time(11, 59)));
+        assert!(!morning.contains(time(12, 0)));
+        assert!(!morning.contains(time(4, 59)));
+    }
+
+    #[test]
+    fn test_time_range_wraps_around_midnight() {
+        let night = range("22:00-05:00");
+        assert!(night.contains(time(22, 0)));
+        assert!(night.contains(time(23, 59)));
+        assert!(night.contains(time(0, 0)));
+        assert!(night.contains(time(4, 59)));
+        assert!(!night.contains(time(5, 0)));
+        assert!(!night.contains(time(12, 0)));
+    }
+
+    #[test]
+    fn test_first_matching_section_wins() {
+        let sections = DaySections(vec![
+            ("Morning".to_string(), range("05:00-12:00")),
+            ("Lunch".to_string(), range("11:30-13:30")),
+            ("Night".to_string(), range("22:00-05:00")),
+        ]);
+        assert_eq!(sections.section_at(time(11, 45)), Some("Morning"));
+        assert_eq!(sections.section_at(time(12, 15)), Some("Lunch"));
+        assert_eq!(sections.section_at(time(2, 0)), Some("Night"));
+        assert_eq!(sections.section_at(time(15, 0)), None);
+    }
+
+    #[test]
+    fn test_sections_keep_file_order() {
+        #[derive(Debug, Deserialize, Serialize)]
+        struct Table {
+            sections: DaySections,
+        }
+        let table: Table =
+            toml::from_str("[sections]\nZulu = \"05:00-12:00\"\nAlpha = \"00:00-23:59\"\n")
+                .unwrap();
+        assert_eq!(table.sections.section_at(time(6, 0)), Some("Zulu"));
+        assert_eq!(
+            toml::to_string(&table).unwrap(),
+            "[sections]\nZulu = \"05:00-12:00\"\nAlpha = \"00:00-23:59\"\n"
+        );
+
+        let err = toml::from_str::<Table>("[sections]\nMorning = \"5am\"\n").unwrap_err();
+        assert!(
+            err.to_string().contains("Invalid time range '5am'"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_find_section_ends_at_next_heading_of_same_level() {
+        let note =
+            "# Day\n\n## Morning\n\nCoffee\n\n### 08:00\n\nRun\n\n## Afternoon\n\n## Evening ##\n";
+        let morning = find_section(note, "morning").unwrap();
+        assert_eq!(
+            &note[morning.heading..morning.end],
+            "## Morning\n\nCoffee\n\n### 08:00\n\nRun\n\n"
+        );
+        assert_eq!(morning.level, 2);
+
+        let evening = find_section(note, "Evening").unwrap();
+        assert_eq!(&note[evening.heading..evening.end], "## Evening ##\n");
+        assert_eq!(find_section(note, "Night"), None);
+    }
+
+    #[test]
+    fn test_find_section_skips_code_and_comments() {
+        let note = "```\n## Morning\n```\n<!--\n## Morning\n-->\n## Morning\n";
+        let morning = find_section(note, "Morning").unwrap();
+        assert_eq!(&note[morning.heading..], "## Morning\n");
+    }
+}
@@ -3,18 +3,84 @@
 //! This module provides functionality to filter, sort, and format tagged content
 //! into markdown compilations.
 
-use super::{TagContext, TagQuery, TaggedContent};
-use chrono::{Datelike, Duration, NaiveDate};
-use std::collections::HashMap;
-use std::path::Path;
+use super::syntax::tag_in_subtree;
+use super::{ContentPayload, MatchContext, TagContext, TagQuery, TaggedContent};
+use crate::domain::{first_heading, Locale};
+use chrono::{Datelike, Duration, NaiveDate, NaiveTime, Timelike};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 
 /// Format for compiled output
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum CompilationFormat {
     /// Chronological order (by date)
     Chronological,
     /// Grouped by source file
     Grouped,
+    /// Chronological, each date's content grouped by source file
+    #[serde(rename = "chrono-grouped")]
+    ChronologicalGrouped,
+}
+
+impl CompilationFormat {
+    /// Name on the command line and in `.djour/rules.toml`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CompilationFormat::Chronological => "chronological",
+            CompilationFormat::Grouped => "grouped",
+            CompilationFormat::ChronologicalGrouped => "chrono-grouped",
+        }
+    }
+}
+
+/// Sub-headers under each date of a chronological compilation (`--group-by`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupBy {
+    /// A `### 09:00–10:00` header per hour of the blocks' leading `HH:MM`
+    /// times; blocks without one follow under `### unspecified`
+    Hour,
+}
+
+impl GroupBy {
+    /// Name on the command line
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GroupBy::Hour => "hour",
+        }
+    }
+}
+
+impl std::str::FromStr for GroupBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "hour" => Ok(GroupBy::Hour),
+            _ => Err(format!("Invalid group-by: {}. Use 'hour'", s)),
+        }
+    }
+}
+
+impl std::str::FromStr for CompilationFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "chronological" => Ok(CompilationFormat::Chronological),
+            "grouped" => Ok(CompilationFormat::Grouped),
+            "chrono-grouped" => Ok(CompilationFormat::ChronologicalGrouped),
+            _ => Err(format!(
+                "Invalid format: {}. Use 'chronological', 'grouped' or 'chrono-grouped'",
+                s
+            )),
+        }
+    }
 }
 
 /// How to display dates in compiled output
@@ -28,6 +94,678 @@ pub enum CompilationDateStyle {
     MonthRange,
 }
 
+/// Dates a compilation is filtered to (`--from`/`--to`), either end open.
+///
+/// Week and month headers are clamped to it, so a report for 15-01 to 16-01
+/// does not claim to cover the whole week.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DateWindow {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+impl DateWindow {
+    pub fn new(from: Option<NaiveDate>, to: Option<NaiveDate>) -> Self {
+        DateWindow { from, to }
+    }
+
+    /// Whether the period `start..=end` has a day inside the window
+    pub fn overlaps(&self, start: NaiveDate, end: NaiveDate) -> bool {
+        self.from.is_none_or(|from| end >= from) && self.to.is_none_or(|to| start <= to)
+    }
+
+    /// The part of the period `start..=end` inside the window; the whole
+    /// period when they do not overlap
+    pub fn clamp(&self, start: NaiveDate, end: NaiveDate) -> (NaiveDate, NaiveDate) {
+        let clamped_start = self.from.map_or(start, |from| from.max(start));
+        let clamped_end = self.to.map_or(end, |to| to.min(end));
+        if clamped_start <= clamped_end {
+            (clamped_start, clamped_end)
+        } else {
+            (start, end)
+        }
+    }
+}
+
+/// How many enclosing headings to show above each compiled block
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContextDepth {
+    /// The innermost `n` headings (0 disables context)
+    Levels(usize),
+    /// The whole heading chain
+    All,
+}
+
+impl std::str::FromStr for ContextDepth {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("all") {
+            return Ok(ContextDepth::All);
+        }
+        s.parse()
+            .map(ContextDepth::Levels)
+            .map_err(|_| format!("Invalid context depth: '{}'. Use a number or 'all'", s))
+    }
+}
+
+/// How the heading chain is rendered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContextStyle {
+    /// One nested heading per level
+    #[default]
+    Headings,
+    /// A single italic `Parent › Child` line
+    Breadcrumb,
+}
+
+/// Section context settings for compiled output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContextOptions {
+    pub depth: ContextDepth,
+    pub style: ContextStyle,
+}
+
+impl ContextOptions {
+    /// No section context
+    pub fn off() -> Self {
+        ContextOptions {
+            depth: ContextDepth::Levels(0),
+            style: ContextStyle::default(),
+        }
+    }
+
+    /// Headings (text, level) to show for a context, outermost first.
+    ///
+    /// A heading reading `title` is left out, as the file header already names it.
+    fn chain<'a>(&self, context: &'a TagContext, title: Option<&str>) -> Vec<(&'a str, usize)> {
+        let TagContext::Section {
+            heading,
+            level,
+            parents,
+        } = context
+        else {
+            return Vec::new();
+        };
+        let chain: Vec<(&str, usize)> = parents
+            .iter()
+            .map(|(h, l)| (h.as_str(), *l))
+            .chain(std::iter::once((heading.as_str(), *level)))
+            .filter(|(h, _)| !h.trim().is_empty() && Some(h.trim()) != title)
+            .collect();
+        let keep = match self.depth {
+            ContextDepth::Levels(n) => n.min(chain.len()),
+            ContextDepth::All => chain.len(),
+        };
+        chain[chain.len() - keep..].to_vec()
+    }
+}
+
+impl Default for ContextOptions {
+    fn default() -> Self {
+        ContextOptions::off()
+    }
+}
+
+/// `true` is the classic `--include-context` behaviour: the nearest heading only.
+impl From<bool> for ContextOptions {
+    fn from(include_context: bool) -> Self {
+        ContextOptions {
+            depth: ContextDepth::Levels(usize::from(include_context)),
+            style: ContextStyle::Headings,
+        }
+    }
+}
+
+/// Which end of the matches a truncated compilation keeps
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeepOrder {
+    /// The most recent blocks; the oldest are dropped first
+    #[default]
+    Newest,
+    /// The earliest blocks; the most recent are dropped first
+    Oldest,
+}
+
+impl std::str::FromStr for KeepOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "newest" => Ok(KeepOrder::Newest),
+            "oldest" => Ok(KeepOrder::Oldest),
+            _ => Err(format!(
+                "Invalid keep order: '{}'. Use 'newest' or 'oldest'",
+                s
+            )),
+        }
+    }
+}
+
+/// Limits on how much content a compilation includes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct CompileBudget {
+    /// Most blocks to include
+    pub max_blocks: Option<usize>,
+    /// Most bytes of block content to include (headers not counted)
+    pub max_bytes: Option<usize>,
+    pub keep: KeepOrder,
+}
+
+impl CompileBudget {
+    /// No limits
+    pub fn unlimited() -> Self {
+        CompileBudget::default()
+    }
+}
+
+/// The budget limit a compilation ran into
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetLimit {
+    Blocks,
+    Bytes,
+}
+
+impl BudgetLimit {
+    /// Command-line flag that raises this limit
+    pub fn flag(self) -> &'static str {
+        match self {
+            BudgetLimit::Blocks => "--max-blocks",
+            BudgetLimit::Bytes => "--max-bytes",
+        }
+    }
+}
+
+/// How much of a compilation [`TagCompiler::apply_budget`] left out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Truncation {
+    /// Matching blocks that were dropped
+    pub omitted: usize,
+    pub limit: BudgetLimit,
+}
+
+impl Truncation {
+    /// Footer closing a truncated compilation
+    pub fn footer(&self) -> String {
+        let (blocks, verb) = if self.omitted == 1 {
+            ("block", "matches")
+        } else {
+            ("blocks", "match")
+        };
+        format!(
+            "---\n*Truncated: {} more {} {} this query. Narrow the date range or raise {}.*\n",
+            self.omitted,
+            blocks,
+            verb,
+            self.limit.flag()
+        )
+    }
+}
+
+/// `*[2 private blocks redacted]*`, standing in for `count` private blocks
+fn redaction_line(count: usize) -> String {
+    format!(
+        "*[{} private {} redacted]*",
+        count,
+        if count == 1 { "block" } else { "blocks" }
+    )
+}
+
+/// Where the blocks tagged with the private tag are, so compile can leave them out.
+///
+/// Matching blocks that are private themselves are replaced by the renderers
+/// (see [`TagCompiler::write_body_markdown`]); [`Redaction::redact`] handles
+/// private blocks inside copied section bodies, which match no query on their own.
+#[derive(Debug, Clone, Default)]
+pub struct Redaction {
+    /// Byte ranges of private blocks, per source file
+    private: HashMap<PathBuf, Vec<Range<usize>>>,
+}
+
+impl Redaction {
+    /// Find the blocks of `content` (every block of the notes, before any
+    /// query) tagged `private_tag` or a tag nested under it
+    pub fn new(content: &[TaggedContent], private_tag: &str) -> Self {
+        let mut private: HashMap<PathBuf, Vec<Range<usize>>> = HashMap::new();
+        for block in content {
+            if !is_private(block, private_tag) {
+                continue;
+            }
+            if let Some(span) = block.source_span() {
+                private
+                    .entry(block.source_file.clone())
+                    .or_default()
+                    .push(span.start..span.end);
+            }
+        }
+        Redaction { private }
+    }
+
+    /// Replace the private blocks inside the section blocks of `content` by
+    /// redaction lines, one per run of adjacent private blocks
+    pub fn redact(&self, content: Vec<TaggedContent>) -> Vec<TaggedContent> {
+        content
+            .into_iter()
+            .map(|block| self.redact_block(block))
+            .collect()
+    }
+
+    fn redact_block(&self, block: TaggedContent) -> TaggedContent {
+        if !matches!(block.context, TagContext::Section { .. }) {
+            return block;
+        }
+        let (Some(span), Some(private)) =
+            (block.source_span(), self.private.get(&block.source_file))
+        else {
+            return block;
+        };
+
+        // Private blocks strictly inside the section, outermost first
+        let mut inside: Vec<Range<usize>> = private
+            .iter()
+            .filter(|range| {
+                span.start <= range.start
+                    && range.end <= span.end
+                    && range.len() < span.end - span.start
+            })
+            .cloned()
+            .collect();
+        if inside.is_empty() {
+            return block;
+        }
+        inside.sort_by_key(|range| (range.start, std::cmp::Reverse(range.end)));
+
+        let text = block.raw_payload_content();
+        let mut out = String::with_capacity(text.len());
+        let mut cursor = span.start;
+        // Redaction line still to write: its list marker and count
+        let mut pending: Option<(String, usize)> = None;
+        for range in inside {
+            if range.start < cursor {
+                continue; // nested in a block already redacted
+            }
+            let between = &text[cursor - span.start..range.start - span.start];
+            let removed = &text[range.start - span.start..range.end - span.start];
+            match pending.as_mut() {
+                Some((_, count)) if between.trim().is_empty() => *count += 1,
+                _ => {
+                    if let Some((marker, count)) = pending.take() {
+                        out.push_str(&marker);
+                        out.push_str(&redaction_line(count));
+                    }
+                    out.push_str(between);
+                    pending = Some((list_marker(removed).to_string(), 1));
+                }
+            }
+            cursor = range.end;
+        }
+        if let Some((marker, count)) = pending {
+            out.push_str(&marker);
+            out.push_str(&redaction_line(count));
+        }
+        out.push_str(&text[cursor - span.start..]);
+
+        TaggedContent {
+            position: block.position,
+            time: block.time,
+            ..TaggedContent::new(
+                block.tags,
+                out,
+                block.source_file,
+                block.date,
+                block.context,
+            )
+        }
+    }
+}
+
+fn is_private(block: &TaggedContent, private_tag: &str) -> bool {
+    block
+        .tags
+        .iter()
+        .any(|tag| tag_in_subtree(tag, private_tag))
+}
+
+/// The `- `, `* `, `+ ` or `1. ` a list item starts with, so its redaction
+/// line stays in the list
+fn list_marker(block: &str) -> &str {
+    let bullet = block.len() - block.trim_start_matches(['-', '*', '+']).len();
+    let numbered = block.len() - block.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let end = match (bullet, numbered) {
+        (1, _) => 1,
+        (0, n) if n > 0 && block[n..].starts_with(['.', ')']) => n + 1,
+        _ => return "",
+    };
+    if block[end..].starts_with(' ') {
+        &block[..=end]
+    } else {
+        ""
+    }
+}
+
+/// Check that `format` renders a date: a strftime string without time or
+/// time zone fields
+pub fn check_date_format(format: &str) -> Result<(), fmt::Error> {
+    let probe = NaiveDate::from_ymd_opt(2025, 1, 15).expect("valid date");
+    Locale::English.format_date(probe, format).map(|_| ())
+}
+
+/// Structural strings of a compiled document (the `[compile]` config table)
+///
+/// Placeholders: `{DATE}` in `date_header`; `{FILENAME}` and `{DATE_RANGE}` in
+/// `file_header`; `{HASHES}` and `{HEADING}` in `context_heading`. Dates use the
+/// strftime `date_format`. When a grouped file has no date range, a
+/// ` ({DATE_RANGE})` group in `file_header` is dropped.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CompileStyle {
+    pub date_header: String,
+    pub file_header: String,
+    pub undated_header: String,
+    pub context_heading: String,
+    pub date_format: String,
+}
+
+impl Default for CompileStyle {
+    fn default() -> Self {
+        CompileStyle {
+            date_header: "## {DATE}".to_string(),
+            file_header: "## From: {FILENAME} ({DATE_RANGE})".to_string(),
+            undated_header: "## Undated".to_string(),
+            context_heading: "{HASHES} {HEADING}".to_string(),
+            date_format: "%d-%m-%Y".to_string(),
+        }
+    }
+}
+
+impl CompileStyle {
+    /// Whether every string is at its default
+    pub fn is_default(&self) -> bool {
+        *self == CompileStyle::default()
+    }
+
+    /// Check that `date_format` is a valid strftime string for dates, by
+    /// rendering a probe date with it
+    pub fn validate(&self) -> Result<(), String> {
+        check_date_format(&self.date_format).map_err(|_| {
+            format!(
+                "Invalid compile.date_format: '{}'. Use a strftime format such as %Y-%m-%d",
+                self.date_format
+            )
+        })
+    }
+
+    /// `date` in `date_format`, with weekday and month names in `locale`
+    fn format_date(&self, date: NaiveDate, locale: Locale) -> String {
+        // Formats are validated when config and flags are read
+        locale
+            .format_date(date, &self.date_format)
+            .unwrap_or_else(|_| date.format("%d-%m-%Y").to_string())
+    }
+
+    fn date_header(&self, date: &str) -> String {
+        self.date_header.replace("{DATE}", date)
+    }
+
+    fn file_header(&self, filename: &str, date_range: Option<&str>) -> String {
+        let template = match date_range {
+            Some(_) => self.file_header.clone(),
+            None => self.file_header.replace(" ({DATE_RANGE})", ""),
+        };
+        template
+            .replace("{FILENAME}", filename)
+            .replace("{DATE_RANGE}", date_range.unwrap_or_default())
+    }
+
+    fn context_heading(&self, level: usize, heading: &str) -> String {
+        // Base level 2 (##) + section level
+        self.context_heading
+            .replace("{HASHES}", &"#".repeat(level + 2))
+            .replace("{HEADING}", heading)
+    }
+
+    /// Context block (with trailing blank line) for one compiled item, without
+    /// a heading reading `title`
+    fn context_block(
+        &self,
+        context: &TagContext,
+        options: ContextOptions,
+        title: Option<&str>,
+    ) -> String {
+        let chain = options.chain(context, title);
+        if chain.is_empty() {
+            return String::new();
+        }
+        match options.style {
+            ContextStyle::Headings => chain
+                .iter()
+                .map(|(heading, level)| format!("{}\n\n", self.context_heading(*level, heading)))
+                .collect(),
+            ContextStyle::Breadcrumb => {
+                let trail: Vec<&str> = chain.iter().map(|(heading, _)| *heading).collect();
+                format!("*{}*\n\n", trail.join(" › "))
+            }
+        }
+    }
+}
+
+/// What a renderer writes next: a block, or a run of private blocks shown as
+/// one placeholder line
+#[derive(Debug, Clone, Copy)]
+enum RenderItem {
+    Block(usize),
+    Redacted { first: usize, count: usize },
+}
+
+impl RenderItem {
+    /// Index of the (first) block
+    fn first(self) -> usize {
+        match self {
+            RenderItem::Block(idx) | RenderItem::Redacted { first: idx, .. } => idx,
+        }
+    }
+}
+
+/// Settings shared by the markdown renderers
+struct Render<'a> {
+    content: &'a [TaggedContent],
+    window: DateWindow,
+    context: ContextOptions,
+    output_file: Option<&'a Path>,
+    style: &'a CompileStyle,
+    private_tag: Option<&'a str>,
+    group_by: Option<GroupBy>,
+    locale: Locale,
+}
+
+impl Render<'_> {
+    fn is_private(&self, block: &TaggedContent) -> bool {
+        self.private_tag
+            .is_some_and(|private| is_private(block, private))
+    }
+
+    /// The blocks at `order` as items, private blocks that follow each other
+    /// and are `same_run` collapsed into one
+    fn items(
+        &self,
+        order: &[usize],
+        same_run: impl Fn(&TaggedContent, &TaggedContent) -> bool,
+    ) -> Vec<RenderItem> {
+        let mut items: Vec<RenderItem> = Vec::with_capacity(order.len());
+        for &idx in order {
+            if !self.is_private(&self.content[idx]) {
+                items.push(RenderItem::Block(idx));
+                continue;
+            }
+            match items.last_mut() {
+                Some(RenderItem::Redacted { first, count })
+                    if same_run(&self.content[*first], &self.content[idx]) =>
+                {
+                    *count += 1
+                }
+                _ => items.push(RenderItem::Redacted {
+                    first: idx,
+                    count: 1,
+                }),
+            }
+        }
+        items
+    }
+
+    /// Write `item` and the separator to the `next` item of its run; the
+    /// document's last item ends with a single newline instead of a blank line.
+    ///
+    /// `file_title` is the first heading of the file of a grouped item: an
+    /// undated block does not repeat it as context under the file header.
+    fn write_item<W: Write + ?Sized>(
+        &self,
+        out: &mut W,
+        item: RenderItem,
+        next: Option<RenderItem>,
+        last: bool,
+        file_title: Option<&str>,
+    ) -> fmt::Result {
+        match item {
+            RenderItem::Block(idx) => {
+                let block = &self.content[idx];
+                // Context headings (if available and requested)
+                let title = file_title.filter(|_| block.date.is_none());
+                out.write_str(
+                    &self
+                        .style
+                        .context_block(&block.context, self.context, title),
+                )?;
+                out.write_str(&block.rendered_content_for_output(self.output_file))?;
+            }
+            RenderItem::Redacted { count, .. } => out.write_str(&redaction_line(count))?,
+        }
+
+        let separator = match (item, next) {
+            (_, None) if last => "\n",
+            (RenderItem::Block(current), Some(RenderItem::Block(next))) => {
+                TagCompiler::content_separator(&self.content[current], &self.content[next])
+            }
+            _ => "\n\n",
+        };
+        out.write_str(separator)
+    }
+}
+
+/// How [`compile_content`] renders the blocks that match a query
+///
+/// # Stability
+///
+/// Part of the supported library API (see the crate docs). Fields may be
+/// added in minor releases, so build it with `..RenderOptions::default()`.
+#[derive(Debug, Clone)]
+pub struct RenderOptions {
+    /// Chronological (one header per date), grouped by source file, or both
+    pub format: CompilationFormat,
+    /// Date headers: single days, or week or month ranges
+    pub date_style: CompilationDateStyle,
+    /// Dates week and month headers are clamped to
+    pub window: DateWindow,
+    /// Which enclosing section headings to include (`--include-context`)
+    pub context: ContextOptions,
+    /// Where the compilation will be written, relative to the journal root.
+    /// Relative links are rebased against it; None leaves them as written.
+    pub output_file: Option<PathBuf>,
+    /// Date and file header formats (`[compile]` config table)
+    pub style: CompileStyle,
+    /// Tag whose blocks are redacted; None writes them like any other block
+    pub private_tag: Option<String>,
+    /// Sub-headers under each date of chronological output
+    pub group_by: Option<GroupBy>,
+    /// Language of weekday and month names in date headers
+    pub locale: Locale,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            format: CompilationFormat::Chronological,
+            date_style: CompilationDateStyle::SingleDate,
+            window: DateWindow::default(),
+            context: ContextOptions::off(),
+            output_file: None,
+            style: CompileStyle::default(),
+            private_tag: None,
+            group_by: None,
+            locale: Locale::default(),
+        }
+    }
+}
+
+impl RenderOptions {
+    /// Write already selected blocks without a title to `out`
+    pub fn write_body<W: Write + ?Sized>(
+        &self,
+        out: &mut W,
+        content: &[TaggedContent],
+    ) -> fmt::Result {
+        let render = Render {
+            content,
+            window: self.window,
+            context: self.context,
+            output_file: self.output_file.as_deref(),
+            style: &self.style,
+            private_tag: self.private_tag.as_deref(),
+            group_by: self.group_by,
+            locale: self.locale,
+        };
+        TagCompiler::write_render(out, &render, self.format, self.date_style)
+    }
+}
+
+/// Compile parsed blocks into a markdown document: the blocks matching
+/// `query`, under a `# Compilation: <query>` title.
+///
+/// This is `djour compile` without the journal: no files are read or written,
+/// and the compilation template, budgets and embeds do not apply. With no
+/// matching block, the body says so.
+///
+/// # Stability
+///
+/// Part of the supported library API (see the crate docs).
+///
+/// # Examples
+///
+/// ```
+/// use djour::domain::tags::{compile_content, RenderOptions, TagParser, TagQuery};
+/// use chrono::NaiveDate;
+/// use std::path::Path;
+///
+/// let note = "# Wednesday\n\n## Standup #work\n\nShipped the release.\n\n## Lunch\n\nWith Ana #personal\n";
+/// let blocks = TagParser::extract_from_markdown_for_output(
+///     note,
+///     Path::new("2025-01-15.md"),
+///     NaiveDate::from_ymd_opt(2025, 1, 15),
+///     None,
+/// );
+///
+/// let query = TagQuery::parse("work").unwrap();
+/// let markdown = compile_content(blocks, &query, &RenderOptions::default());
+/// assert_eq!(
+///     markdown,
+///     "# Compilation: #work\n\n\n## 15-01-2025\n\nShipped the release.\n"
+/// );
+/// ```
+pub fn compile_content(
+    content: Vec<TaggedContent>,
+    query: &TagQuery,
+    options: &RenderOptions,
+) -> String {
+    let selected = TagCompiler::select(content, query, options.private_tag.as_deref());
+    let mut markdown = TagCompiler::title_markdown(query);
+    options
+        .write_body(&mut markdown, &selected)
+        .expect("writing to a String cannot fail");
+    markdown
+}
+
 /// Compiler for filtering and organizing tagged content
 pub struct TagCompiler;
 
@@ -57,12 +795,34 @@ impl TagCompiler {
     pub fn filter(content: Vec<TaggedContent>, query: &TagQuery) -> Vec<TaggedContent> {
         let matched: Vec<TaggedContent> = content
             .into_iter()
-            .filter(|tc| query.matches(&tc.tags))
+            .filter(|tc| {
+                query.matches_context(&MatchContext {
+                    tags: &tc.tags,
+                    source_file: &tc.source_file,
+                })
+            })
             .collect();
 
         Self::dedupe_contained_in_section(matched)
     }
 
+    /// Blocks of `content` (every block of the notes) matching `query`, with
+    /// the blocks tagged `private_tag` inside matching sections redacted
+    /// (see [`Redaction`])
+    pub fn select(
+        content: Vec<TaggedContent>,
+        query: &TagQuery,
+        private_tag: Option<&str>,
+    ) -> Vec<TaggedContent> {
+        // Redaction applies after the query, so `work AND private` still finds blocks
+        let redaction = private_tag.map(|tag| Redaction::new(&content, tag));
+        let matched = Self::filter(content, query);
+        match redaction {
+            Some(redaction) => redaction.redact(matched),
+            None => matched,
+        }
+    }
+
     fn dedupe_contained_in_section(content: Vec<TaggedContent>) -> Vec<TaggedContent> {
         let mut deduped: Vec<TaggedContent> = Vec::new();
 
@@ -97,45 +857,190 @@ impl TagCompiler {
             && container_content.contains(candidate_content)
     }
 
-    /// Sort content chronologically (by date, then by source file)
+    /// Keep the blocks that fit in `budget`, dropping from the oldest end
+    /// (or the newest with [`KeepOrder::Oldest`]).
+    ///
+    /// Blocks are taken in date order, whatever the output format, and only
+    /// whole blocks are dropped. Consecutive items of one list stay together,
+    /// and the first run of blocks is kept even when it alone is over the
+    /// limit, so a compilation is never empty. `output_file` is used to
+    /// measure blocks as they will be written.
+    pub fn apply_budget(
+        content: Vec<TaggedContent>,
+        budget: &CompileBudget,
+        output_file: Option<&Path>,
+    ) -> (Vec<TaggedContent>, Option<Truncation>) {
+        if budget.max_blocks.is_none() && budget.max_bytes.is_none() {
+            return (content, None);
+        }
+        let sorted = Self::sort_chronological(content);
+
+        // Runs of blocks that must not be split, as index ranges
+        let mut runs: Vec<std::ops::Range<usize>> = Vec::new();
+        for idx in 0..sorted.len() {
+            match runs.last_mut() {
+                Some(run) if Self::should_keep_tight_spacing(&sorted[idx - 1], &sorted[idx]) => {
+                    run.end = idx + 1
+                }
+                _ => runs.push(idx..idx + 1),
+            }
+        }
+        if budget.keep == KeepOrder::Newest {
+            runs.reverse();
+        }
+
+        let mut kept = vec![false; sorted.len()];
+        let mut blocks = 0;
+        let mut bytes = 0;
+        let mut limit = None;
+        for run in runs {
+            let run_bytes: usize = sorted[run.clone()]
+                .iter()
+                .map(|tc| tc.rendered_content_for_output(output_file).len() + 2)
+                .sum();
+            let over = if budget
+                .max_blocks
+                .is_some_and(|max| blocks + run.len() > max)
+            {
+                Some(BudgetLimit::Blocks)
+            } else if budget.max_bytes.is_some_and(|max| bytes + run_bytes > max) {
+                Some(BudgetLimit::Bytes)
+            } else {
+                None
+            };
+            if over.is_some() && blocks > 0 {
+                limit = over;
+                break;
+            }
+            blocks += run.len();
+            bytes += run_bytes;
+            for idx in run {
+                kept[idx] = true;
+            }
+        }
+
+        let omitted = sorted.len() - blocks;
+        let kept_content = sorted
+            .into_iter()
+            .zip(kept)
+            .filter_map(|(tc, kept)| kept.then_some(tc))
+            .collect();
+        let truncation = limit.map(|limit| Truncation { omitted, limit });
+        (kept_content, truncation)
+    }
+
+    /// Sort content chronologically (by date, then by time within a day, then
+    /// by source file, then by position in the file)
     ///
-    /// Items without dates are sorted last.
-    pub fn sort_chronological(mut content: Vec<TaggedContent>) -> Vec<TaggedContent> {
-        content.sort_by(|a, b| match (a.date, b.date) {
-            (Some(da), Some(db)) => da.cmp(&db).then_with(|| a.source_file.cmp(&b.source_file)),
-            (Some(_), None) => std::cmp::Ordering::Less,
-            (None, Some(_)) => std::cmp::Ordering::Greater,
-            (None, None) => a.source_file.cmp(&b.source_file),
+    /// A block without a time takes the time of the block before it in its
+    /// note, so it stays where the note puts it. Items without dates are sorted last.
+    pub fn sort_chronological(content: Vec<TaggedContent>) -> Vec<TaggedContent> {
+        let order = Self::chronological_order(&content, false);
+        let mut slots: Vec<Option<TaggedContent>> = content.into_iter().map(Some).collect();
+        order
+            .into_iter()
+            .map(|idx| slots[idx].take().expect("each index comes once"))
+            .collect()
+    }
+
+    /// Indices of `content` in [`sort_chronological`](Self::sort_chronological)
+    /// order; with `untimed_last` (`--group-by hour`), blocks without a time of
+    /// their own come after the timed blocks of their day instead
+    fn chronological_order(content: &[TaggedContent], untimed_last: bool) -> Vec<usize> {
+        let times: Vec<(bool, Option<NaiveTime>)> = if untimed_last {
+            content
+                .iter()
+                .map(|block| (block.time.is_none(), block.time))
+                .collect()
+        } else {
+            Self::inherited_times(content)
+                .into_iter()
+                .map(|time| (false, time))
+                .collect()
+        };
+        let mut order: Vec<usize> = (0..content.len()).collect();
+        order.sort_by(|&a, &b| {
+            let (block_a, block_b) = (&content[a], &content[b]);
+            let by_date = match (block_a.date, block_b.date) {
+                (Some(da), Some(db)) => da.cmp(&db),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            };
+            by_date
+                .then(times[a].cmp(&times[b]))
+                .then_with(|| block_a.source_file.cmp(&block_b.source_file))
+                .then_with(|| block_a.position.cmp(&block_b.position))
         });
-        content
+        order
+    }
+
+    /// Time of each block, or of the last timed block before it in the same
+    /// note and on the same date
+    fn inherited_times(content: &[TaggedContent]) -> Vec<Option<NaiveTime>> {
+        let mut in_notes: Vec<usize> = (0..content.len()).collect();
+        in_notes.sort_by(|&a, &b| {
+            (&content[a].source_file, content[a].position)
+                .cmp(&(&content[b].source_file, content[b].position))
+        });
+
+        let mut times = vec![None; content.len()];
+        let mut previous: Option<(&Path, Option<NaiveDate>, Option<NaiveTime>)> = None;
+        for idx in in_notes {
+            let block = &content[idx];
+            let inherited = previous
+                .filter(|(file, date, _)| *file == block.source_file && *date == block.date)
+                .and_then(|(_, _, time)| time);
+            times[idx] = block.time.or(inherited);
+            previous = Some((&block.source_file, block.date, times[idx]));
+        }
+        times
     }
 
     /// Group content by source file
     ///
-    /// Returns a vector of (filename, content) tuples, sorted by filename.
+    /// Returns a vector of (filename, content) tuples, sorted by filename, each
+    /// file's content in document order.
     pub fn group_by_file(content: Vec<TaggedContent>) -> Vec<(String, Vec<TaggedContent>)> {
-        let mut groups: HashMap<String, Vec<TaggedContent>> = HashMap::new();
-
-        for tc in content {
-            let filename = tc
-                .source_file
-                .to_string_lossy()
-                .replace('\\', "/")
-                .trim()
-                .to_string();
-            let filename = if filename.is_empty() {
-                "unknown".to_string()
-            } else {
-                filename
-            };
+        let groups = Self::file_groups(&content);
+        let mut slots: Vec<Option<TaggedContent>> = content.into_iter().map(Some).collect();
+        groups
+            .into_iter()
+            .map(|(filename, indices)| {
+                let items = indices
+                    .into_iter()
+                    .filter_map(|idx| slots[idx].take())
+                    .collect();
+                (filename, items)
+            })
+            .collect()
+    }
 
-            groups.entry(filename.clone()).or_default().push(tc);
-        }
+    /// Indices of `content` per source file, in [`group_by_file`](Self::group_by_file) order
+    fn file_groups(content: &[TaggedContent]) -> Vec<(String, Vec<usize>)> {
+        Self::file_groups_of(content, 0..content.len())
+    }
 
-        // Sort by filename
-        let mut result: Vec<_> = groups.into_iter().collect();
-        result.sort_by(|a, b| a.0.cmp(&b.0));
-        result
+    /// The blocks of `content` at `indices` per source file, like [`file_groups`](Self::file_groups)
+    fn file_groups_of(
+        content: &[TaggedContent],
+        indices: impl IntoIterator<Item = usize>,
+    ) -> Vec<(String, Vec<usize>)> {
+        let mut groups: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        for idx in indices {
+            let tc = &content[idx];
+            let filename = tc.source_file.to_string_lossy().replace('\\', "/");
+            let filename = match filename.trim() {
+                "" => "unknown".to_string(),
+                trimmed if trimmed.len() == filename.len() => filename,
+                trimmed => trimmed.to_string(),
+            };
+            groups.entry(filename).or_default().push(idx);
+        }
+        for indices in groups.values_mut() {
+            indices.sort_by_key(|&idx| content[idx].position);
+        }
+        groups.into_iter().collect()
     }
 
     /// Generate markdown output for compiled content
@@ -146,7 +1051,7 @@ impl TagCompiler {
     /// * `query` - The query that was used (for the title)
     /// * `format` - Output format (chronological or grouped)
     /// * `date_style` - How to display dates in headers
-    /// * `include_context` - Whether to include parent section headings
+    /// * `context` - Which enclosing section headings to include (`true` = nearest heading)
     ///
     /// # Examples
     ///
@@ -166,6 +1071,7 @@ impl TagCompiler {
     ///         TagContext::Section {
     ///             heading: "Work Notes".to_string(),
     ///             level: 1,
+    ///             parents: Vec::new(),
     ///         },
     ///     ),
     /// ];
@@ -186,154 +1092,300 @@ impl TagCompiler {
         query: &TagQuery,
         format: CompilationFormat,
         date_style: CompilationDateStyle,
-        include_context: bool,
+        context: impl Into<ContextOptions>,
     ) -> String {
-        Self::to_markdown_for_output(content, query, format, date_style, include_context, None)
+        Self::to_markdown_for_output(
+            content,
+            query,
+            format,
+            date_style,
+            DateWindow::default(),
+            context,
+            None,
+            &CompileStyle::default(),
+        )
     }
 
     /// Generate markdown output for compiled content with optional output path context.
+    ///
+    /// Week and month date headers are clamped to `window`.
+    #[allow(clippy::too_many_arguments)]
     pub fn to_markdown_for_output(
         content: Vec<TaggedContent>,
         query: &TagQuery,
         format: CompilationFormat,
         date_style: CompilationDateStyle,
-        include_context: bool,
+        window: DateWindow,
+        context: impl Into<ContextOptions>,
+        output_file: Option<&Path>,
+        style: &CompileStyle,
+    ) -> String {
+        let body = Self::body_markdown_for_output(
+            content,
+            format,
+            date_style,
+            window,
+            context,
+            output_file,
+            style,
+        );
+        format!("{}{}", Self::title_markdown(query), body)
+    }
+
+    /// Default `# Compilation: <query>` title (with trailing blank line)
+    pub fn title_markdown(query: &TagQuery) -> String {
+        format!("# Compilation: {}\n\n", query)
+    }
+
+    /// Generate the compiled markdown without the default title.
+    pub fn body_markdown_for_output(
+        content: Vec<TaggedContent>,
+        format: CompilationFormat,
+        date_style: CompilationDateStyle,
+        window: DateWindow,
+        context: impl Into<ContextOptions>,
         output_file: Option<&Path>,
+        style: &CompileStyle,
     ) -> String {
         let mut output = String::new();
+        Self::write_body_markdown(
+            &mut output,
+            &content,
+            format,
+            date_style,
+            window,
+            context.into(),
+            output_file,
+            style,
+            None,
+        )
+        .expect("writing to a String cannot fail");
+        output
+    }
 
-        // Header
-        output.push_str(&format!("# Compilation: {}\n\n", query));
+    /// Write the compiled markdown without the default title to `out`.
+    ///
+    /// Same output as [`body_markdown_for_output`](Self::body_markdown_for_output),
+    /// rendered block by block from borrowed content, so a large compilation
+    /// can go straight to a file. Blocks tagged `private_tag` (or a tag nested
+    /// under it) are left out: each run of them under one date or file header
+    /// becomes a single `*[N private blocks redacted]*` line.
+    #[allow(clippy::too_many_arguments)]
+    pub fn write_body_markdown<W: Write + ?Sized>(
+        out: &mut W,
+        content: &[TaggedContent],
+        format: CompilationFormat,
+        date_style: CompilationDateStyle,
+        window: DateWindow,
+        context: ContextOptions,
+        output_file: Option<&Path>,
+        style: &CompileStyle,
+        private_tag: Option<&str>,
+    ) -> fmt::Result {
+        let render = Render {
+            content,
+            window,
+            context,
+            output_file,
+            style,
+            private_tag,
+            group_by: None,
+            locale: Locale::default(),
+        };
+        Self::write_render(out, &render, format, date_style)
+    }
 
-        if content.is_empty() {
-            output.push_str("*No matching content found.*\n");
-            return output;
+    /// Write the blocks of `render` in `format`
+    fn write_render<W: Write + ?Sized>(
+        out: &mut W,
+        render: &Render<'_>,
+        format: CompilationFormat,
+        date_style: CompilationDateStyle,
+    ) -> fmt::Result {
+        if render.content.is_empty() {
+            return out.write_str("*No matching content found.*\n");
         }
 
         match format {
             CompilationFormat::Chronological => {
-                Self::markdown_chronological(
-                    content,
-                    date_style,
-                    include_context,
-                    output_file,
-                    &mut output,
-                );
+                Self::markdown_chronological(out, render, date_style)
             }
-            CompilationFormat::Grouped => {
-                Self::markdown_grouped(
-                    content,
-                    date_style,
-                    include_context,
-                    output_file,
-                    &mut output,
-                );
+            CompilationFormat::Grouped => Self::markdown_grouped(out, render, date_style),
+            CompilationFormat::ChronologicalGrouped => {
+                Self::markdown_chronological_grouped(out, render, date_style)
             }
         }
-
-        if output.ends_with("\n\n") {
-            output.pop();
-        }
-
-        output
     }
 
-    /// Generate chronological markdown output
-    fn markdown_chronological(
-        content: Vec<TaggedContent>,
+    /// Write chronological markdown output
+    fn markdown_chronological<W: Write + ?Sized>(
+        out: &mut W,
+        render: &Render<'_>,
         date_style: CompilationDateStyle,
-        include_context: bool,
-        output_file: Option<&Path>,
-        output: &mut String,
-    ) {
-        let sorted = Self::sort_chronological(content);
+    ) -> fmt::Result {
+        let content = render.content;
+        let order = Self::chronological_order(content, render.group_by == Some(GroupBy::Hour));
+        let hour = |block: &TaggedContent| match render.group_by {
+            Some(GroupBy::Hour) => block.time.map(|time| time.hour()),
+            None => None,
+        };
+        let items = render.items(&order, |a, b| a.date == b.date && hour(a) == hour(b));
         let mut current_date: Option<NaiveDate> = None;
+        // The hour header written last under the current date
+        let mut current_hour: Option<Option<u32>> = None;
 
-        for (idx, tc) in sorted.iter().enumerate() {
+        for (position, &item) in items.iter().enumerate() {
+            let block = &content[item.first()];
+            let date = block.date;
             // Date header (if changed)
-            if tc.date != current_date {
-                if let Some(date) = tc.date {
-                    let header = Self::format_date_header(date, date_style);
-                    output.push_str(&format!("\n## {}\n\n", header));
-                    current_date = tc.date;
+            let mut new_date = position == 0;
+            if date != current_date {
+                if let Some(date) = date {
+                    let header = Self::format_date_header(date, date_style, render);
+                    write!(out, "\n{}\n\n", render.style.date_header(&header))?;
+                    current_date = Some(date);
+                    new_date = true;
                 } else if current_date.is_some() {
                     // Switch to undated section
-                    output.push_str("\n## Undated\n\n");
+                    write!(out, "\n{}\n\n", render.style.undated_header)?;
                     current_date = None;
+                    new_date = true;
                 }
             }
 
-            // Context heading (if available and requested)
-            if include_context {
-                if let TagContext::Section { heading, level } = &tc.context {
-                    if !heading.trim().is_empty() {
-                        let prefix = "#".repeat(*level + 2); // Base level 2 (##) + section level
-                        output.push_str(&format!("{} {}\n\n", prefix, heading));
-                    }
+            if render.group_by == Some(GroupBy::Hour) {
+                if new_date {
+                    current_hour = None;
+                }
+                let hour = hour(block);
+                if current_hour != Some(hour) {
+                    let gap = if current_hour.is_some() { "\n" } else { "" };
+                    let header = match hour {
+                        Some(h) => format!("{:02}:00–{:02}:00", h, h + 1),
+                        None => "unspecified".to_string(),
+                    };
+                    write!(out, "{}### {}\n\n", gap, header)?;
+                    current_hour = Some(hour);
                 }
             }
 
-            // Content
-            let rendered_content = tc.rendered_content_for_output(output_file);
-            output.push_str(&rendered_content);
-            output.push_str(&Self::content_separator(&sorted, idx));
+            let next = items.get(position + 1).copied();
+            render.write_item(out, item, next, next.is_none(), None)?;
         }
+        Ok(())
     }
 
-    /// Generate grouped markdown output
-    fn markdown_grouped(
-        content: Vec<TaggedContent>,
+    /// Write grouped markdown output
+    fn markdown_grouped<W: Write + ?Sized>(
+        out: &mut W,
+        render: &Render<'_>,
         date_style: CompilationDateStyle,
-        include_context: bool,
-        output_file: Option<&Path>,
-        output: &mut String,
-    ) {
-        let groups = Self::group_by_file(content);
-
-        for (filename, items) in groups {
-            if date_style != CompilationDateStyle::SingleDate {
-                if let Some(date) = items.iter().find_map(|tc| tc.date) {
-                    let header = Self::format_date_header(date, date_style);
-                    output.push_str(&format!("\n## From: {} ({})\n\n", filename, header));
-                } else {
-                    output.push_str(&format!("\n## From: {}\n\n", filename));
-                }
+    ) -> fmt::Result {
+        let content = render.content;
+        let groups = Self::file_groups(content);
+        let group_count = groups.len();
+
+        for (group_idx, (filename, indices)) in groups.into_iter().enumerate() {
+            let date_range = if date_style != CompilationDateStyle::SingleDate {
+                indices
+                    .iter()
+                    .find_map(|&idx| content[idx].date)
+                    .map(|date| Self::format_date_header(date, date_style, render))
             } else {
-                output.push_str(&format!("\n## From: {}\n\n", filename));
+                None
+            };
+            write!(
+                out,
+                "\n{}\n\n",
+                render.style.file_header(&filename, date_range.as_deref())
+            )?;
+
+            let last_group = group_idx + 1 == group_count;
+            let title = Self::file_title(content, &indices);
+            let items = render.items(&indices, |_, _| true);
+            for (position, &item) in items.iter().enumerate() {
+                let next = items.get(position + 1).copied();
+                render.write_item(
+                    out,
+                    item,
+                    next,
+                    last_group && next.is_none(),
+                    title.as_deref(),
+                )?;
             }
+        }
+        Ok(())
+    }
 
-            for (idx, tc) in items.iter().enumerate() {
-                // Context heading (if available and requested)
-                if include_context {
-                    if let TagContext::Section { heading, level } = &tc.context {
-                        if !heading.trim().is_empty() {
-                            let prefix = "#".repeat(*level + 2); // Base level 2 (##) + section level
-                            output.push_str(&format!("{} {}\n\n", prefix, heading));
-                        }
-                    }
+    /// First heading of the note the blocks at `indices` come from, read from
+    /// the note text of the first block parsed from it
+    fn file_title(content: &[TaggedContent], indices: &[usize]) -> Option<String> {
+        indices.iter().find_map(|&idx| match &content[idx].payload {
+            ContentPayload::Span { source, .. } => first_heading(source),
+            ContentPayload::Text(_) => None,
+        })
+    }
+
+    /// Write chronological markdown with each date's content grouped under a
+    /// `### <file>` header per source file; undated content comes last
+    fn markdown_chronological_grouped<W: Write + ?Sized>(
+        out: &mut W,
+        render: &Render<'_>,
+        date_style: CompilationDateStyle,
+    ) -> fmt::Result {
+        let content = render.content;
+        let mut dates: Vec<(Option<NaiveDate>, Vec<usize>)> = Vec::new();
+        for idx in Self::chronological_order(content, false) {
+            match dates.last_mut() {
+                Some((date, indices)) if *date == content[idx].date => indices.push(idx),
+                _ => dates.push((content[idx].date, vec![idx])),
+            }
+        }
+        let date_count = dates.len();
+
+        for (date_idx, (date, indices)) in dates.into_iter().enumerate() {
+            let header = match date {
+                Some(date) => render
+                    .style
+                    .date_header(&Self::format_date_header(date, date_style, render)),
+                None => render.style.undated_header.clone(),
+            };
+            write!(out, "\n{}\n\n", header)?;
+
+            let groups = Self::file_groups_of(content, indices);
+            let group_count = groups.len();
+            for (group_idx, (filename, indices)) in groups.into_iter().enumerate() {
+                let gap = if group_idx == 0 { "" } else { "\n" };
+                write!(out, "{}### {}\n\n", gap, filename)?;
+
+                let last_group = date_idx + 1 == date_count && group_idx + 1 == group_count;
+                let title = Self::file_title(content, &indices);
+                let items = render.items(&indices, |_, _| true);
+                for (position, &item) in items.iter().enumerate() {
+                    let next = items.get(position + 1).copied();
+                    render.write_item(
+                        out,
+                        item,
+                        next,
+                        last_group && next.is_none(),
+                        title.as_deref(),
+                    )?;
                 }
-
-                // Content
-                let rendered_content = tc.rendered_content_for_output(output_file);
-                output.push_str(&rendered_content);
-                output.push_str(&Self::content_separator(&items, idx));
             }
         }
+        Ok(())
     }
 
-    fn content_separator(items: &[TaggedContent], idx: usize) -> String {
-        if idx + 1 >= items.len() {
-            return "\n\n".to_string();
+    /// Text between `current` and the `next` block of its run
+    fn content_separator<'a>(current: &'a TaggedContent, next: &'a TaggedContent) -> &'a str {
+        if let Some(gap) = current.span_gap_to(next) {
+            return gap;
         }
 
-        if let Some(gap) = items[idx].span_gap_to(&items[idx + 1]) {
-            return gap.to_string();
-        }
-
-        if Self::should_keep_tight_spacing(&items[idx], &items[idx + 1]) {
-            "\n".to_string()
+        if Self::should_keep_tight_spacing(current, next) {
+            "\n"
         } else {
-            "\n\n".to_string()
+            "\n\n"
         }
     }
 
@@ -375,18 +1427,21 @@ impl TagCompiler {
         matches!(chars.next(), Some('.' | ')')) && matches!(chars.next(), Some(' '))
     }
 
-    fn format_date_header(date: NaiveDate, date_style: CompilationDateStyle) -> String {
-        match date_style {
-            CompilationDateStyle::SingleDate => date.format("%d-%m-%Y").to_string(),
-            CompilationDateStyle::WeekRange => {
-                let end = date + Duration::days(6);
-                format!("{} to {}", date.format("%d-%m-%Y"), end.format("%d-%m-%Y"))
-            }
-            CompilationDateStyle::MonthRange => {
-                let end = Self::end_of_month(date);
-                format!("{} to {}", date.format("%d-%m-%Y"), end.format("%d-%m-%Y"))
-            }
-        }
+    /// Header for the note dated `date`: the date, or its week or month
+    /// clamped to the render's date window
+    fn format_date_header(
+        date: NaiveDate,
+        date_style: CompilationDateStyle,
+        render: &Render<'_>,
+    ) -> String {
+        let format = |date| render.style.format_date(date, render.locale);
+        let end = match date_style {
+            CompilationDateStyle::SingleDate => return format(date),
+            CompilationDateStyle::WeekRange => date + Duration::days(6),
+            CompilationDateStyle::MonthRange => Self::end_of_month(date),
+        };
+        let (start, end) = render.window.clamp(date, end);
+        format!("{} to {}", format(start), format(end))
     }
 
     fn end_of_month(date: NaiveDate) -> NaiveDate {
@@ -405,7 +1460,7 @@ impl TagCompiler {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::tags::{ContentPayload, SourceSpan};
+    use crate::domain::tags::{ContentPayload, SourceSpan, TagParser};
     use chrono::NaiveDate;
     use std::path::PathBuf;
     use std::sync::Arc;
@@ -425,6 +1480,118 @@ mod tests {
         )
     }
 
+    fn day(d: u32) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(2025, 1, d)
+    }
+
+    fn budget_dates(content: &[TaggedContent]) -> Vec<u32> {
+        content
+            .iter()
+            .map(|tc| tc.date.unwrap().format("%d").to_string().parse().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_apply_budget_keeps_newest_by_default() {
+        let content: Vec<TaggedContent> = [12, 10, 11, 13]
+            .iter()
+            .map(|&d| {
+                create_test_content(vec!["work"], "note", &format!("2025-01-{}.md", d), day(d))
+            })
+            .collect();
+        let budget = CompileBudget {
+            max_blocks: Some(2),
+            ..CompileBudget::unlimited()
+        };
+
+        let (kept, truncation) = TagCompiler::apply_budget(content.clone(), &budget, None);
+        assert_eq!(budget_dates(&kept), vec![12, 13]);
+        assert_eq!(
+            truncation,
+            Some(Truncation {
+                omitted: 2,
+                limit: BudgetLimit::Blocks
+            })
+        );
+
+        let oldest = CompileBudget {
+            keep: KeepOrder::Oldest,
+            ..budget
+        };
+        let (kept, _) = TagCompiler::apply_budget(content.clone(), &oldest, None);
+        assert_eq!(budget_dates(&kept), vec![10, 11]);
+
+        let (kept, truncation) =
+            TagCompiler::apply_budget(content, &CompileBudget::unlimited(), None);
+        assert_eq!(kept.len(), 4);
+        assert_eq!(truncation, None);
+    }
+
+    #[test]
+    fn test_apply_budget_keeps_list_items_together_and_counts_bytes() {
+        let content = vec![
+            create_test_content(vec!["work"], "- one", "2025-01-10.md", day(10)),
+            create_test_content(vec!["work"], "- two", "2025-01-10.md", day(10)),
+            create_test_content(vec!["work"], "- three", "2025-01-10.md", day(10)),
+            create_test_content(vec!["work"], "late paragraph", "2025-01-11.md", day(11)),
+        ];
+
+        // Two blocks fit, but the list is all or nothing
+        let budget = CompileBudget {
+            max_blocks: Some(3),
+            keep: KeepOrder::Oldest,
+            ..CompileBudget::unlimited()
+        };
+        let (kept, truncation) = TagCompiler::apply_budget(content.clone(), &budget, None);
+        assert_eq!(kept.len(), 3);
+        assert_eq!(truncation.unwrap().omitted, 1);
+
+        let budget = CompileBudget {
+            max_blocks: Some(2),
+            ..CompileBudget::unlimited()
+        };
+        let (kept, truncation) = TagCompiler::apply_budget(content.clone(), &budget, None);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].content, "late paragraph");
+        assert_eq!(truncation.unwrap().omitted, 3);
+
+        // The first run is kept even when it alone is over the limit
+        let budget = CompileBudget {
+            max_bytes: Some(10),
+            keep: KeepOrder::Oldest,
+            ..CompileBudget::unlimited()
+        };
+        let (kept, truncation) = TagCompiler::apply_budget(content, &budget, None);
+        assert_eq!(kept.len(), 3);
+        assert_eq!(
+            truncation,
+            Some(Truncation {
+                omitted: 1,
+                limit: BudgetLimit::Bytes
+            })
+        );
+    }
+
+    #[test]
+    fn test_truncation_footer() {
+        let footer = Truncation {
+            omitted: 412,
+            limit: BudgetLimit::Blocks,
+        }
+        .footer();
+        assert_eq!(
+            footer,
+            "---\n*Truncated: 412 more blocks match this query. Narrow the date range or raise --max-blocks.*\n"
+        );
+        let single = Truncation {
+            omitted: 1,
+            limit: BudgetLimit::Bytes,
+        }
+        .footer();
+        assert!(single.contains("1 more block matches"), "{}", single);
+        assert!(single.contains("--max-bytes"), "{}", single);
+    }
+
     fn create_test_section_content(
         tags: Vec<&str>,
         content: &str,
@@ -441,6 +1608,7 @@ mod tests {
             TagContext::Section {
                 heading: heading.to_string(),
                 level,
+                parents: Vec::new(),
             },
         )
     }
@@ -610,6 +1778,144 @@ mod tests {
         assert_eq!(sorted[1].content, "No date");
     }
 
+    #[test]
+    fn test_sort_chronological_by_time_within_a_day() {
+        let content = vec![
+            create_test_content(vec!["work"], "- 18:30 retro", "a.md", day(15)),
+            create_test_content(vec!["work"], "no time", "a.md", day(15)),
+            create_test_content(vec!["work"], "- 09:30 standup", "b.md", day(15)),
+            create_test_content(vec!["work"], "- 08:00 next day", "a.md", day(16)),
+        ];
+
+        let sorted: Vec<String> = TagCompiler::sort_chronological(content)
+            .into_iter()
+            .map(|tc| tc.content)
+            .collect();
+        assert_eq!(
+            sorted,
+            [
+                "- 09:30 standup",
+                "- 18:30 retro",
+                "no time",
+                "- 08:00 next day"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_untimed_blocks_keep_their_place_in_the_note() {
+        let at = |content: &str, file: &str, position: usize| {
+            let mut block = create_test_content(vec!["work"], content, file, day(15));
+            block.position = position;
+            block
+        };
+        let content = vec![
+            at("intro", "a.md", 0),
+            at("- 18:30 retro", "a.md", 10),
+            at("notes on the retro", "a.md", 30),
+            at("- 09:30 standup", "b.md", 0),
+        ];
+
+        let sorted: Vec<String> = TagCompiler::sort_chronological(content.clone())
+            .into_iter()
+            .map(|tc| tc.content)
+            .collect();
+        assert_eq!(
+            sorted,
+            [
+                "intro",
+                "- 09:30 standup",
+                "- 18:30 retro",
+                "notes on the retro"
+            ]
+        );
+
+        let hourly: Vec<&str> = TagCompiler::chronological_order(&content, true)
+            .into_iter()
+            .map(|idx| content[idx].content.as_str())
+            .collect();
+        assert_eq!(
+            hourly,
+            [
+                "- 09:30 standup",
+                "- 18:30 retro",
+                "intro",
+                "notes on the retro"
+            ]
+        );
+    }
+
+    /// A note mixing sections, paragraphs, lists and nested children
+    const INTERLEAVED_NOTE: &str = "# Day
+
+Intro #work
+
+## Standup #work
+
+Talked about the release.
+
+- Item one #work
+  - child of one
+- Item two
+  - nested #work
+    - deeper #work
+
+Closing thought #work
+
+## Later
+
+- Top item #work
+  - sub item
+
+Last words #work
+";
+
+    #[test]
+    fn test_blocks_of_one_file_sort_in_document_order() {
+        let date = NaiveDate::from_ymd_opt(2025, 1, 15);
+        let blocks =
+            TagParser::extract_from_markdown(INTERLEAVED_NOTE, Path::new("2025-01-15.md"), date);
+        let mut positions: Vec<usize> = blocks.iter().map(|b| b.position).collect();
+        positions.sort_unstable();
+        positions.dedup();
+        assert_eq!(positions.len(), blocks.len(), "{:#?}", blocks);
+        for block in &blocks {
+            assert!(INTERLEAVED_NOTE[block.position..].starts_with(&block.content));
+        }
+
+        // Whatever order the blocks arrive in, they leave in source order
+        let mut shuffled = blocks.clone();
+        shuffled.reverse();
+        shuffled.rotate_left(3);
+        let sorted = TagCompiler::sort_chronological(shuffled.clone());
+        let starts: Vec<&str> = sorted
+            .iter()
+            .map(|b| b.content.lines().next().unwrap())
+            .collect();
+        let expected = vec![
+            "Intro #work",
+            "Talked about the release.",
+            "- Item one #work",
+            "- child of one",
+            "- nested #work",
+            "- deeper #work",
+            "Closing thought #work",
+            "- Top item #work",
+            "- sub item",
+            "Last words #work",
+        ];
+        assert_eq!(starts, expected);
+
+        let groups = TagCompiler::group_by_file(shuffled);
+        assert_eq!(groups.len(), 1);
+        let grouped: Vec<&str> = groups[0]
+            .1
+            .iter()
+            .map(|b| b.content.lines().next().unwrap())
+            .collect();
+        assert_eq!(grouped, expected);
+    }
+
     #[test]
     fn test_group_by_file() {
         let content = vec![
@@ -694,6 +2000,135 @@ mod tests {
         assert!(markdown.contains("Monthly notes"));
     }
 
+    /// Body with one block per `(filename, period start)`, headers clamped to `window`
+    fn windowed_body(
+        notes: &[(&str, NaiveDate)],
+        date_style: CompilationDateStyle,
+        format: CompilationFormat,
+        window: DateWindow,
+    ) -> String {
+        let content = notes
+            .iter()
+            .map(|(file, date)| create_test_content(vec!["work"], "Notes", file, Some(*date)))
+            .collect();
+        TagCompiler::body_markdown_for_output(
+            content,
+            format,
+            date_style,
+            window,
+            false,
+            None,
+            &CompileStyle::default(),
+        )
+    }
+
+    fn ymd(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_date_window_clamp() {
+        let (start, end) = (ymd(2025, 1, 13), ymd(2025, 1, 19));
+        assert_eq!(DateWindow::default().clamp(start, end), (start, end));
+        assert_eq!(
+            DateWindow::new(Some(ymd(2025, 1, 15)), None).clamp(start, end),
+            (ymd(2025, 1, 15), end)
+        );
+        assert_eq!(
+            DateWindow::new(Some(ymd(2025, 1, 1)), Some(ymd(2025, 1, 16))).clamp(start, end),
+            (start, ymd(2025, 1, 16))
+        );
+        // No overlap keeps the whole period
+        assert_eq!(
+            DateWindow::new(Some(ymd(2025, 2, 1)), None).clamp(start, end),
+            (start, end)
+        );
+    }
+
+    #[test]
+    fn test_week_range_clamped_to_filter_inside_one_week() {
+        let week = [("2025-W03-2025-01-13.md", ymd(2025, 1, 13))];
+        let window = DateWindow::new(Some(ymd(2025, 1, 15)), Some(ymd(2025, 1, 16)));
+        for format in [CompilationFormat::Chronological, CompilationFormat::Grouped] {
+            let body = windowed_body(&week, CompilationDateStyle::WeekRange, format, window);
+            assert!(body.contains("15-01-2025 to 16-01-2025"), "{}", body);
+            assert!(!body.contains("13-01-2025"));
+        }
+
+        // Without a filter the whole week is shown
+        let body = windowed_body(
+            &week,
+            CompilationDateStyle::WeekRange,
+            CompilationFormat::Chronological,
+            DateWindow::default(),
+        );
+        assert!(body.contains("## 13-01-2025 to 19-01-2025\n"));
+    }
+
+    #[test]
+    fn test_week_ranges_clamped_across_two_weeks() {
+        let weeks = [
+            ("2025-W03-2025-01-13.md", ymd(2025, 1, 13)),
+            ("2025-W04-2025-01-20.md", ymd(2025, 1, 20)),
+        ];
+        let window = DateWindow::new(Some(ymd(2025, 1, 15)), Some(ymd(2025, 1, 22)));
+        let body = windowed_body(
+            &weeks,
+            CompilationDateStyle::WeekRange,
+            CompilationFormat::Chronological,
+            window,
+        );
+        assert!(body.contains("## 15-01-2025 to 19-01-2025\n"));
+        assert!(body.contains("## 20-01-2025 to 22-01-2025\n"));
+
+        let grouped = windowed_body(
+            &weeks,
+            CompilationDateStyle::WeekRange,
+            CompilationFormat::Grouped,
+            window,
+        );
+        assert!(grouped.contains("## From: 2025-W03-2025-01-13.md (15-01-2025 to 19-01-2025)\n"));
+        assert!(grouped.contains("## From: 2025-W04-2025-01-20.md (20-01-2025 to 22-01-2025)\n"));
+    }
+
+    #[test]
+    fn test_month_ranges_clamped_to_filter() {
+        let february = [("2025-02.md", ymd(2025, 2, 1))];
+        let inside = DateWindow::new(Some(ymd(2025, 2, 10)), Some(ymd(2025, 2, 20)));
+        let body = windowed_body(
+            &february,
+            CompilationDateStyle::MonthRange,
+            CompilationFormat::Chronological,
+            inside,
+        );
+        assert!(body.contains("## 10-02-2025 to 20-02-2025\n"));
+
+        let months = [
+            ("2025-02.md", ymd(2025, 2, 1)),
+            ("2025-03.md", ymd(2025, 3, 1)),
+        ];
+        let across = DateWindow::new(Some(ymd(2025, 2, 10)), Some(ymd(2025, 3, 5)));
+        let body = windowed_body(
+            &months,
+            CompilationDateStyle::MonthRange,
+            CompilationFormat::Chronological,
+            across,
+        );
+        assert!(body.contains("## 10-02-2025 to 28-02-2025\n"));
+        assert!(body.contains("## 01-03-2025 to 05-03-2025\n"));
+
+        // An open end only clamps the other side
+        let from_only = DateWindow::new(Some(ymd(2025, 2, 10)), None);
+        let body = windowed_body(
+            &months,
+            CompilationDateStyle::MonthRange,
+            CompilationFormat::Chronological,
+            from_only,
+        );
+        assert!(body.contains("## 10-02-2025 to 28-02-2025\n"));
+        assert!(body.contains("## 01-03-2025 to 31-03-2025\n"));
+    }
+
     #[test]
     fn test_to_markdown_grouped() {
         let content = vec![create_test_content(
@@ -717,6 +2152,42 @@ mod tests {
         assert!(markdown.contains("Meeting notes"));
     }
 
+    #[test]
+    fn test_to_markdown_chronological_grouped() {
+        let day = NaiveDate::from_ymd_opt(2025, 1, 15);
+        let content = vec![
+            create_test_content(vec!["work"], "Alpha notes", "b/2025-01-15.md", day),
+            create_test_content(vec!["work"], "Root notes", "2025-01-15.md", day),
+            create_test_content(vec!["work"], "Loose notes", "ideas.md", None),
+            create_test_content(vec!["work"], "More root notes", "2025-01-15.md", day),
+        ];
+
+        let query = TagQuery::parse("work").unwrap();
+        let markdown = TagCompiler::to_markdown(
+            content,
+            &query,
+            CompilationFormat::ChronologicalGrouped,
+            CompilationDateStyle::SingleDate,
+            false,
+        );
+
+        let headings: Vec<&str> = markdown.lines().filter(|l| l.starts_with('#')).collect();
+        assert_eq!(
+            headings,
+            [
+                "# Compilation: #work",
+                "## 15-01-2025",
+                "### 2025-01-15.md",
+                "### b/2025-01-15.md",
+                "## Undated",
+                "### ideas.md",
+            ]
+        );
+        let root = markdown.find("Root notes").unwrap();
+        assert!(root < markdown.find("More root notes").unwrap());
+        assert!(markdown.find("More root notes").unwrap() < markdown.find("Alpha notes").unwrap());
+    }
+
     #[test]
     fn test_to_markdown_empty() {
         let content = vec![];
@@ -948,10 +2419,294 @@ mod tests {
             &query,
             CompilationFormat::Chronological,
             CompilationDateStyle::SingleDate,
+            DateWindow::default(),
             false,
             Some(&output_file),
+            &CompileStyle::default(),
         );
 
         assert!(markdown.contains("[Doc](../docs/design.md)"));
     }
+
+    /// Compile `note` for `query` with `#private` blocks redacted
+    fn redacted_body(note: &str, query: &str, format: CompilationFormat) -> String {
+        let blocks = TagParser::extract_from_markdown(
+            note,
+            Path::new("2025-01-15.md"),
+            NaiveDate::from_ymd_opt(2025, 1, 15),
+        );
+        let redaction = Redaction::new(&blocks, "private");
+        let query = TagQuery::parse(query).unwrap();
+        let matched = redaction.redact(TagCompiler::filter(blocks, &query));
+        let mut body = String::new();
+        TagCompiler::write_body_markdown(
+            &mut body,
+            &matched,
+            format,
+            CompilationDateStyle::SingleDate,
+            DateWindow::default(),
+            ContextOptions::off(),
+            None,
+            &CompileStyle::default(),
+            Some("private"),
+        )
+        .unwrap();
+        body
+    }
+
+    #[test]
+    fn test_private_paragraphs_are_redacted() {
+        let note = "Shipped it #work\n\nDoctor visit #work #private\n\nReview #work\n";
+        assert_eq!(
+            redacted_body(note, "work", CompilationFormat::Chronological),
+            "\n## 15-01-2025\n\nShipped it #work\n\n*[1 private block redacted]*\n\nReview #work\n"
+        );
+        // Nested tags count, and a private block matched by the query is redacted too
+        let note = "Therapy #work #private/health\n";
+        assert_eq!(
+            redacted_body(note, "work AND private", CompilationFormat::Grouped),
+            "\n## From: 2025-01-15.md\n\n*[1 private block redacted]*\n"
+        );
+    }
+
+    #[test]
+    fn test_consecutive_private_blocks_share_one_line() {
+        let note = "- one #work\n- two #work #private\n- three #work #private\n- four #work\n\n\
+                    Last #work #private\n";
+        assert_eq!(
+            redacted_body(note, "work", CompilationFormat::Chronological),
+            "\n## 15-01-2025\n\n- one #work\n\n*[2 private blocks redacted]*\n\n\
+             - four #work\n\n*[1 private block redacted]*\n"
+        );
+    }
+
+    #[test]
+    fn test_private_heading_redacts_its_section() {
+        let note = "## Standup #work\n\nDone.\n\n## Feelings #work #private\n\nTired.\n\nSad.\n";
+        assert_eq!(
+            redacted_body(note, "work", CompilationFormat::Chronological),
+            "\n## 15-01-2025\n\nDone.\n\n*[1 private block redacted]*\n"
+        );
+    }
+
+    #[test]
+    fn test_private_blocks_inside_copied_section_are_redacted() {
+        let note = "## Standup #work\n\n1. one\n2. two #private\n3. three #private\n4. four\n\n\
+                    Aside #private\n\nDone.\n";
+        assert_eq!(
+            redacted_body(note, "work", CompilationFormat::Chronological),
+            "\n## 15-01-2025\n\n1. one\n2. *[2 private blocks redacted]*\n\
+             4. four\n\n*[1 private block redacted]*\n\nDone.\n"
+        );
+    }
+
+    #[test]
+    fn test_custom_style_for_headers() {
+        let style = CompileStyle {
+            date_header: "### {DATE}".to_string(),
+            file_header: "## {FILENAME} [{DATE_RANGE}]".to_string(),
+            undated_header: "### Someday".to_string(),
+            context_heading: "{HASHES} In: {HEADING}".to_string(),
+            date_format: "%Y-%m-%d".to_string(),
+        };
+        let content = vec![
+            create_test_section_content(
+                vec!["work"],
+                "Dated",
+                "2025-W03-2025-01-13.md",
+                NaiveDate::from_ymd_opt(2025, 1, 13),
+                "Work Notes",
+                1,
+            ),
+            create_test_content(vec!["work"], "Undated", "journal.md", None),
+        ];
+
+        let chronological = TagCompiler::body_markdown_for_output(
+            content.clone(),
+            CompilationFormat::Chronological,
+            CompilationDateStyle::WeekRange,
+            DateWindow::default(),
+            true,
+            None,
+            &style,
+        );
+        assert!(chronological.contains("### 2025-01-13 to 2025-01-19\n"));
+        assert!(chronological.contains("### In: Work Notes\n"));
+        assert!(chronological.contains("### Someday\n"));
+
+        let grouped = TagCompiler::body_markdown_for_output(
+            content,
+            CompilationFormat::Grouped,
+            CompilationDateStyle::WeekRange,
+            DateWindow::default(),
+            false,
+            None,
+            &style,
+        );
+        assert!(grouped.contains("## 2025-W03-2025-01-13.md [2025-01-13 to 2025-01-19]\n"));
+        assert!(grouped.contains("## journal.md []\n"));
+    }
+
+    #[test]
+    fn test_default_file_header_drops_empty_range() {
+        let style = CompileStyle::default();
+        assert_eq!(style.file_header("a.md", None), "## From: a.md");
+        assert_eq!(
+            style.file_header("a.md", Some("x to y")),
+            "## From: a.md (x to y)"
+        );
+    }
+
+    fn nested_standup() -> Vec<TaggedContent> {
+        let markdown = "# Client A\n\n## Project X\n\n### Standup\n\nShipped the fix #work\n";
+        crate::domain::tags::TagParser::extract_from_markdown(
+            markdown,
+            Path::new("2025-01-15.md"),
+            NaiveDate::from_ymd_opt(2025, 1, 15),
+        )
+    }
+
+    fn render_context(context: ContextOptions) -> String {
+        TagCompiler::body_markdown_for_output(
+            nested_standup(),
+            CompilationFormat::Chronological,
+            CompilationDateStyle::SingleDate,
+            DateWindow::default(),
+            context,
+            None,
+            &CompileStyle::default(),
+        )
+    }
+
+    #[test]
+    fn test_context_depth_all_renders_nested_headings() {
+        let markdown = render_context(ContextOptions {
+            depth: ContextDepth::All,
+            style: ContextStyle::Headings,
+        });
+        assert!(markdown
+            .contains("### Client A\n\n#### Project X\n\n##### Standup\n\nShipped the fix #work"));
+    }
+
+    #[test]
+    fn test_context_depth_limits_chain_from_innermost() {
+        let depth_one = render_context(true.into());
+        assert!(depth_one.contains("\n##### Standup\n\nShipped"));
+        assert!(!depth_one.contains("Project X"));
+
+        let depth_two = render_context(ContextOptions {
+            depth: ContextDepth::Levels(2),
+            style: ContextStyle::Headings,
+        });
+        assert!(depth_two.contains("#### Project X\n\n##### Standup\n\n"));
+        assert!(!depth_two.contains("Client A"));
+
+        let off = render_context(ContextOptions::off());
+        assert!(!off.contains("Standup"));
+    }
+
+    #[test]
+    fn test_context_breadcrumb_style() {
+        let markdown = render_context(ContextOptions {
+            depth: ContextDepth::All,
+            style: ContextStyle::Breadcrumb,
+        });
+        assert!(markdown.contains("*Client A › Project X › Standup*\n\nShipped the fix #work"));
+        assert!(!markdown.contains("### Client A"));
+    }
+
+    #[test]
+    fn test_parse_context_depth() {
+        assert_eq!("all".parse(), Ok(ContextDepth::All));
+        assert_eq!("3".parse(), Ok(ContextDepth::Levels(3)));
+        assert!("deep".parse::<ContextDepth>().is_err());
+    }
+
+    #[test]
+    fn test_validate_date_format() {
+        assert!(CompileStyle::default().validate().is_ok());
+        let style = CompileStyle {
+            date_format: "%Y-%Q".to_string(),
+            ..CompileStyle::default()
+        };
+        assert!(style.validate().is_err());
+        // Valid strftime, but a date has no hour to render
+        assert!(check_date_format("%d-%m-%Y %H:%M").is_err());
+        assert!(check_date_format("%A, %d %B %Y").is_ok());
+    }
+
+    /// Body of one block dated `date` rendered with the date format
+    /// `%A, %d %B %Y` in `date_style` and `locale`
+    fn weekday_body(
+        date: NaiveDate,
+        format: CompilationFormat,
+        date_style: CompilationDateStyle,
+        locale: Locale,
+    ) -> String {
+        let options = RenderOptions {
+            format,
+            date_style,
+            style: CompileStyle {
+                date_format: "%A, %d %B %Y".to_string(),
+                ..CompileStyle::default()
+            },
+            locale,
+            ..RenderOptions::default()
+        };
+        let content = vec![create_test_content(
+            vec!["work"],
+            "Entry",
+            "note.md",
+            Some(date),
+        )];
+        let mut body = String::new();
+        options.write_body(&mut body, &content).unwrap();
+        body
+    }
+
+    #[test]
+    fn test_date_format_for_each_date_style() {
+        let wednesday = NaiveDate::from_ymd_opt(2025, 1, 15).unwrap();
+        let chronological = |date_style| {
+            weekday_body(
+                wednesday,
+                CompilationFormat::Chronological,
+                date_style,
+                Locale::English,
+            )
+        };
+        assert!(chronological(CompilationDateStyle::SingleDate)
+            .starts_with("\n## Wednesday, 15 January 2025\n"));
+        assert!(chronological(CompilationDateStyle::WeekRange)
+            .starts_with("\n## Wednesday, 15 January 2025 to Tuesday, 21 January 2025\n"));
+        assert!(chronological(CompilationDateStyle::MonthRange)
+            .starts_with("\n## Wednesday, 15 January 2025 to Friday, 31 January 2025\n"));
+
+        // The grouped file header's range uses the same format
+        let grouped = weekday_body(
+            wednesday,
+            CompilationFormat::Grouped,
+            CompilationDateStyle::WeekRange,
+            Locale::English,
+        );
+        assert!(grouped.starts_with(
+            "\n## From: note.md (Wednesday, 15 January 2025 to Tuesday, 21 January 2025)\n"
+        ));
+    }
+
+    #[test]
+    fn test_date_format_names_follow_locale() {
+        let wednesday = NaiveDate::from_ymd_opt(2025, 1, 15).unwrap();
+        let body = weekday_body(
+            wednesday,
+            CompilationFormat::Chronological,
+            CompilationDateStyle::SingleDate,
+            Locale::German,
+        );
+        assert!(
+            body.starts_with("\n## Mittwoch, 15 Januar 2025\n"),
+            "{}",
+            body
+        );
+    }
 }
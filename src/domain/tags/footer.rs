@@ -0,0 +1,198 @@
+//! Tag summary footer kept at the end of notes (`auto_tag_footer`)
+//!
+//! ```markdown
+//! <!-- djour:tags -->
+//! Tags in this note: #work #health <!-- /djour:tags -->
+//! ```
+//!
+//! The footer only repeats tags found elsewhere in the note, so tag readers
+//! blank it out with [`strip_tag_footer`] before looking for tags.
+
+use std::borrow::Cow;
+use std::ops::Range;
+
+/// Opening marker; only recognized at the start of a line
+pub const TAG_FOOTER_START: &str = "<!-- djour:tags -->";
+
+/// Closing marker, after the tag list
+pub const TAG_FOOTER_END: &str = "<!-- /djour:tags -->";
+
+/// Byte range of the last complete footer, from its opening marker to the end of its closing one
+fn footer_range(markdown: &str) -> Option<Range<usize>> {
+    let start = markdown
+        .match_indices(TAG_FOOTER_START)
+        .map(|(index, _)| index)
+        .filter(|&index| index == 0 || markdown[..index].ends_with('\n'))
+        .last()?;
+    let end = markdown[start..].find(TAG_FOOTER_END)? + start + TAG_FOOTER_END.len();
+    Some(start..end)
+}
+
+/// Whether the note carries a tag footer
+pub fn has_tag_footer(markdown: &str) -> bool {
+    footer_range(markdown).is_some()
+}
+
+/// Render the footer for `tags` (given without `#`)
+pub fn render_tag_footer(tags: &[String]) -> String {
+    let mut footer = format!("{}\nTags in this note:", TAG_FOOTER_START);
+    for tag in tags {
+        footer.push_str(" #");
+        footer.push_str(tag);
+    }
+    footer.push(' ');
+    footer.push_str(TAG_FOOTER_END);
+    footer
+}
+
+/// Blank out the footer, keeping line breaks and byte offsets of everything else
+pub fn strip_tag_footer(markdown: &str) -> Cow<'_, str> {
+    let Some(range) = footer_range(markdown) else {
+        return Cow::Borrowed(markdown);
+    };
+
+    let mut stripped = String::with_capacity(markdown.len());
+    stripped.push_str(&markdown[..range.start]);
+    for c in markdown[range.clone()].chars() {
+        if c == '\n' || c == '\r' {
+            stripped.push(c);
+        } else {
+            stripped.extend(std::iter::repeat_n(' ', c.len_utf8()));
+        }
+    }
+    stripped.push_str(&markdown[range.end..]);
+    Cow::Owned(stripped)
+}
+
+/// Split a note before its footer: `(body, footer and anything after it)`.
+///
+/// Text appended to `body` stays above the footer once the two are joined again.
+pub fn split_at_tag_footer(markdown: &str) -> (&str, &str) {
+    match footer_range(markdown) {
+        Some(range) => markdown.split_at(range.start),
+        None => (markdown, ""),
+    }
+}
+
+/// Set the footer of `markdown` to list `tags`.
+///
+/// An existing footer is replaced in place; otherwise one is appended after a
+/// blank line. Without tags the footer is removed (or not added).
+pub fn with_tag_footer(markdown: &str, tags: &[String]) -> String {
+    match footer_range(markdown) {
+        Some(range) if tags.is_empty() => {
+            let before = markdown[..range.start].trim_end();
+            let after = markdown[range.end..].trim_start_matches(['\r', '\n']);
+            let mut result = before.to_string();
+            if !result.is_empty() {
+                result.push('\n');
+            }
+            if !after.is_empty() {
+                if !result.is_empty() {
+                    result.push('\n');
+                }
+                result.push_str(after);
+            }
+            result
+        }
+        Some(range) => format!(
+            "{}{}{}",
+            &markdown[..range.start],
+            render_tag_footer(tags),
+            &markdown[range.end..]
+        ),
+        None if tags.is_empty() => markdown.to_string(),
+        None => {
+            let body = markdown.trim_end();
+            if body.is_empty() {
+                format!("{}\n", render_tag_footer(tags))
+            } else {
+                format!("{}\n\n{}\n", body, render_tag_footer(tags))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_render_footer() {
+        assert_eq!(
+            render_tag_footer(&tags(&["work", "health"])),
+            "<!-- djour:tags -->\nTags in this note: #work #health <!-- /djour:tags -->"
+        );
+    }
+
+    #[test]
+    fn test_footer_is_appended_then_replaced_in_place() {
+        let note = "# Day\n\nShip it #work\n\n\n";
+        let with_footer = with_tag_footer(note, &tags(&["work"]));
+        assert_eq!(
+            with_footer,
+            "# Day\n\nShip it #work\n\n<!-- djour:tags -->\nTags in this note: #work <!-- /djour:tags -->\n"
+        );
+        assert!(has_tag_footer(&with_footer));
+
+        let edited = with_footer.replace("Ship it", "Run #health\n\nShip it");
+        let refreshed = with_tag_footer(&edited, &tags(&["health", "work"]));
+        assert!(refreshed.ends_with(
+            "\n\n<!-- djour:tags -->\nTags in this note: #health #work <!-- /djour:tags -->\n"
+        ));
+        assert_eq!(refreshed.matches(TAG_FOOTER_START).count(), 1);
+        assert_eq!(
+            with_tag_footer(&refreshed, &tags(&["health", "work"])),
+            refreshed
+        );
+    }
+
+    #[test]
+    fn test_footer_kept_where_it_is() {
+        let note = "Intro #a\n\n<!-- djour:tags -->\nTags in this note: #old <!-- /djour:tags -->\n\nLater text\n";
+        assert_eq!(
+            with_tag_footer(note, &tags(&["a"])),
+            "Intro #a\n\n<!-- djour:tags -->\nTags in this note: #a <!-- /djour:tags -->\n\nLater text\n"
+        );
+    }
+
+    #[test]
+    fn test_footer_removed_without_tags() {
+        let note = "Plain\n\n<!-- djour:tags -->\nTags in this note: #gone <!-- /djour:tags -->\n";
+        assert_eq!(with_tag_footer(note, &[]), "Plain\n");
+        assert_eq!(with_tag_footer("Plain\n", &[]), "Plain\n");
+    }
+
+    #[test]
+    fn test_strip_keeps_offsets_and_lines() {
+        let note =
+            "Text #a\n<!-- djour:tags -->\nTags in this note: #a #ä <!-- /djour:tags -->\nEnd\n";
+        let stripped = strip_tag_footer(note);
+        assert_eq!(stripped.len(), note.len());
+        assert_eq!(stripped.lines().count(), note.lines().count());
+        assert_eq!(stripped.matches('#').count(), 1);
+        assert!(stripped.ends_with("\nEnd\n"));
+        assert!(matches!(strip_tag_footer("No footer"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_markers_must_start_a_line_and_close() {
+        let inline = "Mention <!-- djour:tags --> here <!-- /djour:tags -->\n";
+        assert!(!has_tag_footer(inline));
+        let unclosed = "<!-- djour:tags -->\nTags in this note: #a\n";
+        assert!(!has_tag_footer(unclosed));
+        assert_eq!(split_at_tag_footer(unclosed), (unclosed, ""));
+    }
+
+    #[test]
+    fn test_split_at_footer() {
+        let note = "Body\n\n<!-- djour:tags -->\nTags in this note: #a <!-- /djour:tags -->\n";
+        let (body, footer) = split_at_tag_footer(note);
+        assert_eq!(body, "Body\n\n");
+        assert!(footer.starts_with(TAG_FOOTER_START));
+    }
+}
@@ -0,0 +1,153 @@
+//! Front matter detection
+//!
+//! Recognizes a leading `---` (YAML-ish) or `+++` (TOML-ish) block at the very
+//! top of a note so it can be kept out of tag parsing and compiled output.
+
+use super::syntax::is_valid_tag_name;
+
+/// A front matter block found at the start of a note
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrontMatter {
+    /// Byte length of the block, including both delimiter lines
+    pub len: usize,
+
+    /// Tags listed under a `tags` key (normalized to lowercase, without `#`)
+    pub tags: Vec<String>,
+}
+
+fn delimiter_line(line: &str) -> &str {
+    line.trim_end_matches(['\r', '\n']).trim_end()
+}
+
+/// Parse tag names out of a `tags` value such as `#work #life`,
+/// `[work, life]` or `["work", "life"]`.
+fn parse_tag_values(value: &str, tags: &mut Vec<String>) {
+    for word in value.split(|c: char| c.is_whitespace() || ",[]\"'".contains(c)) {
+        let name = word.trim_start_matches('#');
+        if !name.is_ascii() || !is_valid_tag_name(name) {
+            continue;
+        }
+        let name = name.to_lowercase();
+        if !tags.contains(&name) {
+            tags.push(name);
+        }
+    }
+}
+
+/// Collect tags from the lines between the delimiters.
+///
+/// Supports `tags: ...` / `tags = ...` on one line and YAML block lists:
+///
+/// ```text
+/// tags:
+///   - work
+///   - life
+/// ```
+fn collect_tags(lines: &[&str]) -> Vec<String> {
+    let mut tags = Vec::new();
+    let mut in_tag_list = false;
+
+    for line in lines {
+        let line = line.trim_end_matches(['\r', '\n']);
+        let trimmed = line.trim();
+
+        if in_tag_list {
+            if let Some(item) = trimmed.strip_prefix('-') {
+                parse_tag_values(item, &mut tags);
+                continue;
+            }
+            if trimmed.is_empty() {
+                continue;
+            }
+            in_tag_list = false;
+        }
+
+        let Some(split_at) = line.find([':', '=']) else {
+            continue;
+        };
+        let key = line[..split_at].trim();
+        if !key.eq_ignore_ascii_case("tags") {
+            continue;
+        }
+
+        let value = line[split_at + 1..].trim();
+        if value.is_empty() {
+            in_tag_list = true;
+        } else {
+            parse_tag_values(value, &mut tags);
+        }
+    }
+
+    tags
+}
+
+/// Detect a front matter block at the very start of `content`.
+///
+/// The opening line must be exactly `---` or `+++`, and the block must be
+/// closed by the same delimiter (YAML blocks may also close with `...`).
+/// Unclosed blocks are not treated as front matter.
+pub fn parse_front_matter(content: &str) -> Option<FrontMatter> {
+    let mut lines = content.split_inclusive('\n');
+    let first = lines.next()?;
+    let delimiter = delimiter_line(first);
+    if delimiter != "---" && delimiter != "+++" {
+        return None;
+    }
+
+    let mut offset = first.len();
+    let mut inner = Vec::new();
+    for line in lines {
+        offset += line.len();
+        let candidate = delimiter_line(line);
+        if candidate == delimiter || (delimiter == "---" && candidate == "...") {
+            return Some(FrontMatter {
+                len: offset,
+                tags: collect_tags(&inner),
+            });
+        }
+        inner.push(line);
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_yaml_front_matter_with_inline_tags() {
+        let content = "---\ntitle: Notes\ntags: #work #Life\n---\nBody\n";
+        let front_matter = parse_front_matter(content).unwrap();
+        assert_eq!(&content[front_matter.len..], "Body\n");
+        assert_eq!(front_matter.tags, vec!["work", "life"]);
+    }
+
+    #[test]
+    fn detects_yaml_block_list_tags() {
+        let content =
+            "---\ntags:\n  - work\n  - \"project-x\"\n  - project/alpha\nauthor: me\n---\n";
+        let front_matter = parse_front_matter(content).unwrap();
+        assert_eq!(front_matter.len, content.len());
+        assert_eq!(
+            front_matter.tags,
+            vec!["work", "project-x", "project/alpha"]
+        );
+    }
+
+    #[test]
+    fn detects_toml_front_matter() {
+        let content = "+++\r\ntags = [\"work\", \"life\"]\r\n+++\r\nBody";
+        let front_matter = parse_front_matter(content).unwrap();
+        assert_eq!(&content[front_matter.len..], "Body");
+        assert_eq!(front_matter.tags, vec!["work", "life"]);
+    }
+
+    #[test]
+    fn ignores_unclosed_or_mismatched_blocks() {
+        assert!(parse_front_matter("---\ntags: work\n").is_none());
+        assert!(parse_front_matter("---\ntags: work\n+++\n").is_none());
+        assert!(parse_front_matter("Intro\n---\ntags: work\n---\n").is_none());
+        assert!(parse_front_matter("# Heading\n").is_none());
+    }
+}
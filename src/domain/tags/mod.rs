@@ -1,12 +1,33 @@
 //! Tag system
 
 pub mod compiler;
+pub mod footer;
+pub mod front_matter;
+pub mod normalize;
 pub mod parser;
 pub mod query;
 pub mod retag;
+pub mod scan;
+pub mod syntax;
+pub mod wikilinks;
 
 // Re-export main types
-pub use compiler::{CompilationDateStyle, CompilationFormat, TagCompiler};
-pub use parser::{ContentPayload, SourceSpan, TagContext, TagParser, TaggedContent};
-pub use query::TagQuery;
-pub use retag::{retag_markdown, RetagResult};
+pub use compiler::{
+    check_date_format, compile_content, BudgetLimit, CompilationDateStyle, CompilationFormat,
+    CompileBudget, CompileStyle, ContextDepth, ContextOptions, ContextStyle, DateWindow, GroupBy,
+    KeepOrder, Redaction, RenderOptions, TagCompiler, Truncation,
+};
+pub use footer::{
+    has_tag_footer, render_tag_footer, split_at_tag_footer, strip_tag_footer, with_tag_footer,
+    TAG_FOOTER_END, TAG_FOOTER_START,
+};
+pub use front_matter::{parse_front_matter, FrontMatter};
+pub use normalize::{normalization_key, TagGroup, TagNormalizer, TagSpelling};
+pub use parser::{
+    leading_time, section_dates, ContentPayload, SectionDate, SourceSpan, TagContext,
+    TagParseOptions, TagParser, TaggedContent, MAX_TAGS_PER_BLOCK, MAX_TAG_SCAN_BYTES,
+};
+pub use query::{MatchContext, TagQuery};
+pub use retag::{retag_markdown, retag_markdown_many, retag_query, retag_query_many, RetagResult};
+pub use scan::tags_in_note;
+pub use wikilinks::{rewrite_wikilinks, WikiLink};
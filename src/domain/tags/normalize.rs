@@ -0,0 +1,191 @@
+//! Grouping of tag spellings that differ only in `-`, `_` or case
+//!
+//! `#one-on-one`, `#one_on_one` and `#OneOnOne` share the normalization key
+//! `oneonone`. The most frequent spelling in a group is its canonical form.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Normalization key of a tag: lowercased with `-` and `_` removed.
+///
+/// Hierarchy separators are kept, so `a-b/c` and `ab/c` match but `ab/c` and `abc` do not.
+pub fn normalization_key(tag: &str) -> String {
+    tag.chars()
+        .filter(|c| *c != '-' && *c != '_')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// One spelling of a tag and how often it is used
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagSpelling {
+    pub name: String,
+    pub count: usize,
+}
+
+/// Spellings sharing a normalization key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagGroup {
+    pub key: String,
+    /// Most frequent spelling (ties go to the alphabetically first)
+    pub canonical: TagSpelling,
+    /// Other spellings, most frequent first
+    pub variants: Vec<TagSpelling>,
+}
+
+impl TagGroup {
+    /// Total uses across all spellings
+    pub fn total(&self) -> usize {
+        self.canonical.count + self.variants.iter().map(|v| v.count).sum::<usize>()
+    }
+}
+
+/// Groups tag spellings by [`normalization_key`].
+#[derive(Debug, Clone, Default)]
+pub struct TagNormalizer {
+    exempt: BTreeSet<String>,
+}
+
+impl TagNormalizer {
+    /// Spellings in `exempt` are never merged with anything else, even when
+    /// their keys collide (for tags that really mean different things).
+    pub fn new<I, S>(exempt: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        TagNormalizer {
+            exempt: exempt
+                .into_iter()
+                .map(|tag| {
+                    let tag = tag.as_ref().trim();
+                    tag.strip_prefix('#').unwrap_or(tag).to_lowercase()
+                })
+                .filter(|tag| !tag.is_empty())
+                .collect(),
+        }
+    }
+
+    /// Group tag counts (keyed by lowercased tag) into spelling groups, ordered by canonical name.
+    pub fn group(&self, counts: &BTreeMap<String, usize>) -> Vec<TagGroup> {
+        let mut by_key: BTreeMap<String, Vec<TagSpelling>> = BTreeMap::new();
+        for (name, count) in counts {
+            let spelling = TagSpelling {
+                name: name.clone(),
+                count: *count,
+            };
+            // Exempt spellings get a key of their own so they always stay separate.
+            let key = if self.exempt.contains(name) {
+                format!("\0{}", name)
+            } else {
+                normalization_key(name)
+            };
+            by_key.entry(key).or_default().push(spelling);
+        }
+
+        let mut groups: Vec<TagGroup> = by_key
+            .into_values()
+            .map(|mut spellings| {
+                spellings.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+                let canonical = spellings.remove(0);
+                TagGroup {
+                    key: normalization_key(&canonical.name),
+                    canonical,
+                    variants: spellings,
+                }
+            })
+            .collect();
+        groups.sort_by(|a, b| a.canonical.name.cmp(&b.canonical.name));
+        groups
+    }
+
+    /// Renames (minority spelling -> canonical spelling) that would merge every group.
+    pub fn renames(&self, counts: &BTreeMap<String, usize>) -> Vec<(String, String)> {
+        self.group(counts)
+            .into_iter()
+            .flat_map(|group| {
+                let canonical = group.canonical.name;
+                group
+                    .variants
+                    .into_iter()
+                    .map(move |variant| (variant.name, canonical.clone()))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counts(entries: &[(&str, usize)]) -> BTreeMap<String, usize> {
+        entries
+            .iter()
+            .map(|(name, count)| (name.to_string(), *count))
+            .collect()
+    }
+
+    #[test]
+    fn key_strips_separators_and_case() {
+        assert_eq!(normalization_key("One-on_One"), "oneonone");
+        assert_eq!(normalization_key("team_ops/on-call"), "teamops/oncall");
+    }
+
+    #[test]
+    fn groups_spellings_under_most_frequent() {
+        let groups = TagNormalizer::default().group(&counts(&[
+            ("one-on-one", 12),
+            ("one_on_one", 3),
+            ("oneonone", 1),
+            ("work", 4),
+        ]));
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].canonical.name, "one-on-one");
+        assert_eq!(
+            groups[0]
+                .variants
+                .iter()
+                .map(|v| (v.name.as_str(), v.count))
+                .collect::<Vec<_>>(),
+            vec![("one_on_one", 3), ("oneonone", 1)]
+        );
+        assert_eq!(groups[0].total(), 16);
+        assert!(groups[1].variants.is_empty());
+    }
+
+    #[test]
+    fn ties_prefer_alphabetically_first_spelling() {
+        let groups = TagNormalizer::default().group(&counts(&[("re_cap", 2), ("re-cap", 2)]));
+        assert_eq!(groups[0].canonical.name, "re-cap");
+    }
+
+    #[test]
+    fn renames_point_minorities_at_canonical() {
+        let renames = TagNormalizer::default().renames(&counts(&[
+            ("one-on-one", 12),
+            ("one_on_one", 3),
+            ("oneonone", 1),
+            ("work", 4),
+        ]));
+        assert_eq!(
+            renames,
+            vec![
+                ("one_on_one".to_string(), "one-on-one".to_string()),
+                ("oneonone".to_string(), "one-on-one".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn exempt_spellings_are_never_merged() {
+        let normalizer = TagNormalizer::new(["#Re-sign"]);
+        let input = counts(&[("re-sign", 1), ("resign", 5), ("re_sign", 2)]);
+
+        let groups = normalizer.group(&input);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(
+            normalizer.renames(&input),
+            vec![("re_sign".to_string(), "resign".to_string())]
+        );
+    }
+}
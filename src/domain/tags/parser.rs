@@ -1,327 +1,252 @@
 //! Tag parsing from markdown
 
-use chrono::NaiveDate;
+use super::footer::strip_tag_footer;
+use super::front_matter::parse_front_matter;
+use super::syntax::{is_literal_hash, strip_hash, tag_name, tag_regex, tag_scan_regex};
+use crate::domain::links::rewrite_markdown_targets;
+use crate::domain::markdown::{atx_heading, html_comment_regions, in_regions, MarkdownRegions};
+use crate::domain::mode_migration::is_marker_line;
+use chrono::{NaiveDate, NaiveTime};
 use pulldown_cmark::{CodeBlockKind, Event, Parser as MdParser, Tag, TagEnd};
 use regex::Regex;
-use std::ffi::{OsStr, OsString};
-use std::path::{Component, Path, PathBuf};
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, OnceLock};
 
-/// Regex for matching hashtags: #word, #word-with-dashes, #word_with_underscores
-fn tag_regex() -> &'static Regex {
-    static REGEX: OnceLock<Regex> = OnceLock::new();
-    REGEX.get_or_init(|| Regex::new(r"#([a-zA-Z0-9_-]+)").unwrap())
-}
+/// Most distinct tags a content block carries, inherited ones included.
+///
+/// Far above anything written by hand; it keeps pasted logs full of `#` from
+/// turning every block into thousands of tags. Compile warns about blocks at the limit.
+pub const MAX_TAGS_PER_BLOCK: usize = 256;
 
-fn html_attr_double_quote_regex() -> &'static Regex {
-    static REGEX: OnceLock<Regex> = OnceLock::new();
-    REGEX.get_or_init(|| Regex::new(r#"(?i)\b(src|href)\s*=\s*"([^"]+)""#).unwrap())
-}
+/// Bytes of a block's text searched for tags; tags further in are left as text
+pub const MAX_TAG_SCAN_BYTES: usize = 1024 * 1024;
 
-fn html_attr_single_quote_regex() -> &'static Regex {
-    static REGEX: OnceLock<Regex> = OnceLock::new();
-    REGEX.get_or_init(|| Regex::new(r#"(?i)\b(src|href)\s*=\s*'([^']+)'"#).unwrap())
-}
-
-fn markdown_link_or_image_regex() -> &'static Regex {
+/// An `HH:MM` or `H:MM` time opening a line, after an optional list marker and checkbox
+fn leading_time_regex() -> &'static Regex {
     static REGEX: OnceLock<Regex> = OnceLock::new();
     REGEX.get_or_init(|| {
-        Regex::new(
-            r#"(?P<prefix>!?\[[^\]\n]*\]\()(?P<dest><[^>\n]+>|[^)\s]+)(?P<title>\s+"[^"\n]*")?(?P<suffix>\))"#,
-        )
-        .unwrap()
+        Regex::new(r"^\s*(?:(?:[-*+]|\d+[.)])\s+)?(?:\[[ xX]\]\s+)?(\d{1,2}):(\d{2})(?:[^\d:]|$)")
+            .unwrap()
     })
 }
 
-fn markdown_reference_definition_regex() -> &'static Regex {
-    static REGEX: OnceLock<Regex> = OnceLock::new();
-    REGEX.get_or_init(|| {
-        Regex::new(
-            r#"(?m)^(?P<prefix>[ \t]{0,3}\[[^\]\n]+\]:[ \t]*)(?P<dest><[^>\n]+>|\S+)(?P<suffix>[^\n]*)$"#,
-        )
-        .unwrap()
-    })
+/// The 24-hour time a block starts with, as in `- 09:30 standup #work` or
+/// `9:30 call with Ana`; `None` when it starts with anything else or the
+/// time is not on the clock (`25:00`)
+pub fn leading_time(text: &str) -> Option<NaiveTime> {
+    let caps = leading_time_regex().captures(text)?;
+    NaiveTime::from_hms_opt(caps[1].parse().ok()?, caps[2].parse().ok()?, 0)
 }
 
-/// Extract all tags from a string (case-insensitive, normalized to lowercase)
-fn extract_tags(text: &str) -> Vec<String> {
-    tag_regex()
-        .captures_iter(text)
-        .map(|cap| cap[1].to_lowercase())
-        .collect()
+/// An `@DD-MM-YYYY` marker in a heading, between spaces or at its ends
+fn section_date_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| Regex::new(r"(?:^|\s)(@(\d{2}-\d{2}-\d{4}))(?:\s|$)").unwrap())
 }
 
-/// Remove tags from text, returning cleaned text
-fn strip_tags(text: &str) -> String {
-    tag_regex().replace_all(text, "").trim().to_string()
+/// The first `@DD-MM-YYYY` marker of a heading: its byte range (`@` included)
+/// and the date, `None` when it is not a valid date
+fn find_section_date(heading: &str) -> Option<(Range<usize>, Option<NaiveDate>)> {
+    let caps = section_date_regex().captures(heading)?;
+    let marker = caps.get(1)?;
+    let date = NaiveDate::parse_from_str(&caps[2], "%d-%m-%Y").ok();
+    Some((marker.range(), date))
 }
 
-#[allow(clippy::too_many_arguments)]
-fn append_to_active(
-    fragment: &str,
-    in_code_block: bool,
-    in_heading: bool,
-    in_paragraph: bool,
-    code_block_text: &mut String,
-    current_heading_text: &mut String,
-    current_paragraph: &mut String,
-    item_stack: &mut [String],
-) {
-    if in_code_block {
-        code_block_text.push_str(fragment);
-    } else if in_heading {
-        current_heading_text.push_str(fragment);
-    } else if in_paragraph {
-        current_paragraph.push_str(fragment);
-    } else if let Some(item_text) = item_stack.last_mut() {
-        item_text.push_str(fragment);
+/// The date a heading's valid `@DD-MM-YYYY` marker gives its section, and the
+/// heading without the marker; other headings are returned as they are
+fn split_section_date(heading: &str) -> (Cow<'_, str>, Option<NaiveDate>) {
+    match find_section_date(heading) {
+        Some((range, Some(date))) => {
+            let text = format!(
+                "{}{}",
+                heading[..range.start].trim_end(),
+                &heading[range.end..]
+            );
+            (Cow::Owned(text), Some(date))
+        }
+        _ => (Cow::Borrowed(heading), None),
     }
 }
 
-fn has_uri_scheme(target: &str) -> bool {
-    let mut chars = target.chars();
-    let Some(first) = chars.next() else {
-        return false;
-    };
+/// A heading's `@DD-MM-YYYY` marker, which dates the blocks of its section
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectionDate {
+    /// 1-based line of the heading in the note
+    pub line: usize,
+    /// The marker as written, e.g. `@14-01-2025`
+    pub marker: String,
+    /// `None` when the marker is not a valid date; it then stays heading text
+    pub date: Option<NaiveDate>,
+}
 
-    if !first.is_ascii_alphabetic() {
-        return false;
-    }
+/// The `@DD-MM-YYYY` markers of the ATX headings of `content`, outside front
+/// matter, fenced code blocks and HTML comments
+pub fn section_dates(content: &str) -> Vec<SectionDate> {
+    let body_start = parse_front_matter(content).map_or(0, |front| front.len);
+    let regions = MarkdownRegions::scan(content);
+    let mut line_start = 0;
+    let mut found = Vec::new();
+
+    for (index, line) in content.split_inclusive('\n').enumerate() {
+        let start = line_start;
+        line_start += line.len();
+        if start < body_start || regions.hides(start) {
+            continue;
+        }
 
-    for c in chars {
-        match c {
-            ':' => return true,
-            'a'..='z' | 'A'..='Z' | '0'..='9' | '+' | '-' | '.' => {}
-            _ => return false,
+        let Some((indent, level)) = atx_heading(line) else {
+            continue;
+        };
+        let heading = line[indent + level..].trim();
+        if let Some((range, date)) = find_section_date(heading) {
+            found.push(SectionDate {
+                line: index + 1,
+                marker: heading[range].to_string(),
+                date,
+            });
         }
     }
-
-    false
+    found
 }
 
-fn split_target_suffix(target: &str) -> (&str, &str) {
-    let query_pos = target.find('?');
-    let fragment_pos = target.find('#');
-
-    let split_pos = match (query_pos, fragment_pos) {
-        (Some(q), Some(f)) => Some(q.min(f)),
-        (Some(q), None) => Some(q),
-        (None, Some(f)) => Some(f),
-        (None, None) => None,
-    };
-
-    match split_pos {
-        Some(pos) => (&target[..pos], &target[pos..]),
-        None => (target, ""),
+/// `text` split where tag scanning stops, at a char boundary
+fn split_scanned(text: &str) -> (&str, &str) {
+    let mut end = text.len().min(MAX_TAG_SCAN_BYTES);
+    while !text.is_char_boundary(end) {
+        end -= 1;
     }
+    text.split_at(end)
 }
 
-fn normalize_components(path: &Path) -> (Option<OsString>, bool, Vec<OsString>) {
-    let mut prefix: Option<OsString> = None;
-    let mut has_root = false;
-    let mut segments: Vec<OsString> = Vec::new();
-
-    for component in path.components() {
-        match component {
-            Component::Prefix(p) => prefix = Some(p.as_os_str().to_os_string()),
-            Component::RootDir => has_root = true,
-            Component::CurDir => {}
-            Component::ParentDir => {
-                if let Some(last) = segments.last() {
-                    if last != OsStr::new("..") {
-                        segments.pop();
-                        continue;
-                    }
-                }
+/// Tags of a text and the text without them, found in one scan so both agree
+struct ScannedTags {
+    /// Distinct tags in order of appearance, lowercased
+    tags: Vec<String>,
+    /// The text with every tag removed and trimmed; removing a tag between
+    /// words leaves a single space
+    stripped: String,
+}
 
-                if !has_root {
-                    segments.push(OsString::from(".."));
-                }
+/// Scan `text` for tags; with `heading`, tags that read as plain text (see
+/// [`is_literal_hash`]) are kept in the stripped text, though still collected
+fn scan_tags(text: &str, tag_re: &Regex, heading: bool) -> ScannedTags {
+    let (scanned, rest) = split_scanned(text);
+    // Every match is `#` followed by the tag; spellings already seen are skipped unlowered
+    let mut spellings = HashSet::new();
+    let mut tags = Vec::new();
+    let mut stripped = String::with_capacity(text.len());
+    let mut last = 0;
+    for found in tag_re.find_iter(scanned) {
+        // Wiki links matched by `tag_scan_regex` are skipped
+        let Some(spelling) = strip_hash(found.as_str()) else {
+            continue;
+        };
+        if !(heading && is_literal_hash(scanned, found.start(), spelling)) {
+            push_collapsed(&mut stripped, &scanned[last..found.start()]);
+            last = found.end();
+        }
+        if tags.len() < MAX_TAGS_PER_BLOCK && spellings.insert(spelling) {
+            let tag = tag_name(spelling).to_lowercase();
+            if !tags.contains(&tag) {
+                tags.push(tag);
             }
-            Component::Normal(seg) => segments.push(seg.to_os_string()),
         }
     }
-
-    (prefix, has_root, segments)
-}
-
-fn relative_path(from: &Path, to: &Path) -> Option<PathBuf> {
-    let (from_prefix, from_root, from_segments) = normalize_components(from);
-    let (to_prefix, to_root, to_segments) = normalize_components(to);
-
-    if from_prefix != to_prefix || from_root != to_root {
-        return None;
-    }
-
-    let mut common_len = 0usize;
-    let max_common = from_segments.len().min(to_segments.len());
-    while common_len < max_common && from_segments[common_len] == to_segments[common_len] {
-        common_len += 1;
-    }
-
-    let mut rel = PathBuf::new();
-    for _ in common_len..from_segments.len() {
-        rel.push("..");
-    }
-    for segment in &to_segments[common_len..] {
-        rel.push(segment);
-    }
-
-    if rel.as_os_str().is_empty() {
-        rel.push(".");
+    push_collapsed(&mut stripped, &scanned[last..]);
+    stripped.push_str(rest);
+    ScannedTags {
+        tags,
+        stripped: stripped.trim().to_string(),
     }
-
-    Some(rel)
 }
 
-fn rewrite_link_target(target: &str, source_file: &Path, output_file: Option<&Path>) -> String {
-    let Some(output_file) = output_file else {
-        return target.to_string();
-    };
-
-    if target.is_empty()
-        || target.starts_with('#')
-        || target.starts_with('?')
-        || target.starts_with("//")
-        || target.starts_with('/')
-        || target.starts_with('\\')
-        || has_uri_scheme(target)
-    {
-        return target.to_string();
-    }
-
-    let (path_part, suffix) = split_target_suffix(target);
-    if path_part.is_empty() {
-        return target.to_string();
-    }
+/// `text` without its tags, as headings appear in compiled output
+pub(crate) fn strip_tags(text: &str) -> String {
+    scan_tags(text, tag_regex(true), true).stripped
+}
 
-    let source_dir = source_file.parent().unwrap_or_else(|| Path::new(""));
-    let output_dir = output_file.parent().unwrap_or_else(|| Path::new(""));
-    let target_abs = source_dir.join(path_part);
+/// Spaces between words; pasted text often has no-break spaces (U+00A0, U+202F)
+const BLANKS: [char; 4] = [' ', '\t', '\u{00A0}', '\u{202F}'];
 
-    if let Some(rel) = relative_path(output_dir, &target_abs) {
-        let mut rewritten = rel.to_string_lossy().replace('\\', "/");
-        rewritten.push_str(suffix);
-        rewritten
+/// Append `piece`, dropping its leading spaces when `out` already ends in one
+fn push_collapsed(out: &mut String, piece: &str) {
+    if out.ends_with(BLANKS) {
+        out.push_str(piece.trim_start_matches(BLANKS));
     } else {
-        target.to_string()
+        out.push_str(piece);
     }
 }
 
-fn rewrite_html_targets(html: &str, source_file: &Path, output_file: Option<&Path>) -> String {
-    let rewritten_double = html_attr_double_quote_regex()
-        .replace_all(html, |caps: &regex::Captures<'_>| {
-            let attr = &caps[1];
-            let destination = rewrite_link_target(&caps[2], source_file, output_file);
-            format!(r#"{attr}="{destination}""#)
-        })
-        .to_string();
-
-    html_attr_single_quote_regex()
-        .replace_all(&rewritten_double, |caps: &regex::Captures<'_>| {
-            let attr = &caps[1];
-            let destination = rewrite_link_target(&caps[2], source_file, output_file);
-            format!(r#"{attr}='{destination}'"#)
-        })
-        .to_string()
+/// Extract the distinct tags of a string in order of appearance (normalized to lowercase)
+fn extract_tags(text: &str, tag_re: &Regex) -> Vec<String> {
+    scan_tags(text, tag_re, false).tags
 }
 
-fn rewrite_outside_fenced_code_blocks<F>(markdown: &str, mut rewrite_chunk: F) -> String
-where
-    F: FnMut(&str) -> String,
-{
-    let mut rewritten = String::new();
-    let mut pending_chunk = String::new();
-    let mut active_fence: Option<(char, usize)> = None;
-
-    for line in markdown.split_inclusive('\n') {
-        if let Some((fence_char, min_len)) = active_fence {
-            rewritten.push_str(line);
-            if let Some((marker_char, marker_len)) = parse_fence_marker(line) {
-                if marker_char == fence_char && marker_len >= min_len {
-                    active_fence = None;
-                }
-            }
-            continue;
-        }
-
-        if let Some((fence_char, marker_len)) = parse_fence_marker(line) {
-            if !pending_chunk.is_empty() {
-                rewritten.push_str(&rewrite_chunk(&pending_chunk));
-                pending_chunk.clear();
+/// Append the tags not yet in `dest`, keeping order and stopping at [`MAX_TAGS_PER_BLOCK`]
+fn extend_unique(dest: &mut Vec<String>, tags: impl IntoIterator<Item = String>) {
+    if dest.len() >= MAX_TAGS_PER_BLOCK {
+        return;
+    }
+    let mut seen: HashSet<String> = dest.iter().cloned().collect();
+    for tag in tags {
+        if !seen.contains(&tag) {
+            seen.insert(tag.clone());
+            dest.push(tag);
+            if dest.len() >= MAX_TAGS_PER_BLOCK {
+                break;
             }
-            rewritten.push_str(line);
-            active_fence = Some((fence_char, marker_len));
-            continue;
         }
-
-        pending_chunk.push_str(line);
     }
+}
 
-    if !pending_chunk.is_empty() {
-        rewritten.push_str(&rewrite_chunk(&pending_chunk));
+#[allow(clippy::too_many_arguments)]
+fn append_to_active(
+    fragment: &str,
+    in_code_block: bool,
+    in_heading: bool,
+    in_paragraph: bool,
+    code_block_text: &mut String,
+    current_heading_text: &mut String,
+    current_paragraph: &mut String,
+    item_stack: &mut [String],
+) {
+    if in_code_block {
+        code_block_text.push_str(fragment);
+    } else if in_heading {
+        current_heading_text.push_str(fragment);
+    } else if in_paragraph {
+        current_paragraph.push_str(fragment);
+    } else if let Some(item_text) = item_stack.last_mut() {
+        item_text.push_str(fragment);
     }
-
-    rewritten
 }
 
-pub(crate) fn rewrite_markdown_targets(
-    markdown: &str,
-    source_file: &Path,
-    output_file: Option<&Path>,
-) -> String {
-    rewrite_outside_fenced_code_blocks(markdown, |chunk| {
-        let rewritten_links = markdown_link_or_image_regex()
-            .replace_all(chunk, |caps: &regex::Captures<'_>| {
-                let prefix = &caps["prefix"];
-                let destination_raw = &caps["dest"];
-                let title = caps.name("title").map_or("", |m| m.as_str());
-                let suffix = &caps["suffix"];
-
-                let (destination_inner, wrapped_in_angle) =
-                    if destination_raw.starts_with('<') && destination_raw.ends_with('>') {
-                        (&destination_raw[1..destination_raw.len() - 1], true)
-                    } else {
-                        (destination_raw, false)
-                    };
-
-                let rewritten = rewrite_link_target(destination_inner, source_file, output_file);
-                let rewritten_destination = if wrapped_in_angle {
-                    format!("<{}>", rewritten)
-                } else {
-                    rewritten
-                };
-
-                format!("{prefix}{rewritten_destination}{title}{suffix}")
-            })
-            .to_string();
-
-        let rewritten_reference_definitions = markdown_reference_definition_regex()
-            .replace_all(&rewritten_links, |caps: &regex::Captures<'_>| {
-                let prefix = &caps["prefix"];
-                let destination_raw = &caps["dest"];
-                let suffix = &caps["suffix"];
-
-                let (destination_inner, wrapped_in_angle) =
-                    if destination_raw.starts_with('<') && destination_raw.ends_with('>') {
-                        (&destination_raw[1..destination_raw.len() - 1], true)
-                    } else {
-                        (destination_raw, false)
-                    };
-
-                let rewritten = rewrite_link_target(destination_inner, source_file, output_file);
-                let rewritten_destination = if wrapped_in_angle {
-                    format!("<{}>", rewritten)
-                } else {
-                    rewritten
-                };
-
-                format!("{prefix}{rewritten_destination}{suffix}")
-            })
-            .to_string();
+/// Whether `text` starts with a list item marker (`-`, `*`, `+`, `1.` or `1)`)
+fn starts_with_list_marker(text: &str) -> bool {
+    let digits = text.len() - text.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let rest = match digits {
+        0 => text.strip_prefix(['-', '*', '+']),
+        1..=9 => text[digits..].strip_prefix(['.', ')']),
+        _ => None,
+    };
+    rest.is_some_and(|rest| rest.is_empty() || rest.starts_with([' ', '\t', '\r', '\n']))
+}
 
-        rewrite_html_targets(&rewritten_reference_definitions, source_file, output_file)
-    })
+/// Remove up to `indent` leading spaces from every line of `text`
+fn dedent_lines(text: &str, indent: usize) -> Cow<'_, str> {
+    if indent == 0 {
+        return Cow::Borrowed(text);
+    }
+    text.split_inclusive('\n')
+        .map(|line| {
+            let spaces = line.len() - line.trim_start_matches(' ').len();
+            &line[spaces.min(indent)..]
+        })
+        .collect()
 }
 
 fn link_or_image_tail(destination: &str, title: &str) -> String {
@@ -336,6 +261,56 @@ fn trim_section_body(raw: &str) -> &str {
     raw.trim_matches(|c| c == '\r' || c == '\n')
 }
 
+/// Text of HTML block events with `<!-- ... -->` comments removed.
+///
+/// A comment may open in one event and close in a later one; `in_comment`
+/// carries that state between calls.
+fn strip_html_comments(html: &str, in_comment: &mut bool) -> String {
+    let mut visible = String::new();
+    let mut rest = html;
+    loop {
+        if *in_comment {
+            match rest.find("-->") {
+                Some(end) => {
+                    *in_comment = false;
+                    rest = &rest[end + 3..];
+                }
+                None => return visible,
+            }
+        } else {
+            match rest.find("<!--") {
+                Some(start) => {
+                    visible.push_str(&rest[..start]);
+                    *in_comment = true;
+                    rest = &rest[start + 4..];
+                }
+                None => {
+                    visible.push_str(rest);
+                    return visible;
+                }
+            }
+        }
+    }
+}
+
+/// Shrink a section body span past migration marker lines at either end, so
+/// a migrated body's last section does not carry the closing marker.
+fn trim_marker_lines(content: &str, start: usize, end: usize) -> (usize, usize) {
+    let (mut start, mut end) = trim_line_break_span(content, start, end);
+    loop {
+        let body = &content[start..end];
+        let first_len = body.find('\n').map_or(body.len(), |i| i + 1);
+        let last_start = body.rfind('\n').map_or(0, |i| i + 1);
+        if is_marker_line(&body[last_start..]) && start < end {
+            (start, end) = trim_line_break_span(content, start, start + last_start);
+        } else if is_marker_line(&body[..first_len]) && start < end {
+            (start, end) = trim_line_break_span(content, start + first_len, end);
+        } else {
+            return (start, end);
+        }
+    }
+}
+
 fn trim_line_break_span(content: &str, mut start: usize, mut end: usize) -> (usize, usize) {
     let bytes = content.as_bytes();
     let len = bytes.len();
@@ -353,49 +328,6 @@ fn trim_line_break_span(content: &str, mut start: usize, mut end: usize) -> (usi
     (start, end)
 }
 
-fn parse_atx_heading_level(line: &str) -> Option<usize> {
-    let line = line.trim_end_matches(['\r', '\n']);
-    let trimmed = line.trim_start_matches(' ');
-    let leading_spaces = line.len().saturating_sub(trimmed.len());
-    if leading_spaces > 3 {
-        return None;
-    }
-
-    let level = trimmed.chars().take_while(|c| *c == '#').count();
-    if level == 0 || level > 6 {
-        return None;
-    }
-
-    let rest = &trimmed[level..];
-    if !rest.starts_with(' ') && !rest.starts_with('\t') {
-        return None;
-    }
-
-    Some(level)
-}
-
-fn parse_fence_marker(line: &str) -> Option<(char, usize)> {
-    let line = line.trim_end_matches(['\r', '\n']);
-    let trimmed = line.trim_start_matches(' ');
-    let leading_spaces = line.len().saturating_sub(trimmed.len());
-    if leading_spaces > 3 {
-        return None;
-    }
-
-    let mut chars = trimmed.chars();
-    let fence_char = chars.next()?;
-    if fence_char != '`' && fence_char != '~' {
-        return None;
-    }
-
-    let count = trimmed.chars().take_while(|c| *c == fence_char).count();
-    if count < 3 {
-        return None;
-    }
-
-    Some((fence_char, count))
-}
-
 #[derive(Debug, Clone)]
 struct HeadingSpan {
     level: usize,
@@ -410,47 +342,58 @@ struct SectionBody {
     text: String,
 }
 
-fn collect_heading_spans(content: &str) -> Vec<HeadingSpan> {
-    let mut spans = Vec::new();
-    let mut offset = 0usize;
-    let mut active_fence: Option<(char, usize)> = None;
-
-    for line in content.split_inclusive('\n') {
-        if let Some((fence_char, min_len)) = active_fence {
-            if let Some((marker_char, marker_len)) = parse_fence_marker(line) {
-                if marker_char == fence_char && marker_len >= min_len {
-                    active_fence = None;
-                }
+/// Drop headings that start inside an HTML comment, with everything inside them.
+///
+/// A comment opened in the middle of a paragraph is not an HTML block, so the
+/// markdown parser still reports a commented-out `## ` line as a heading. Removing
+/// those events before anything else sees them keeps section bodies running over
+/// the comment and heading/body pairing aligned.
+fn drop_commented_headings<'a>(
+    content: &str,
+    events: Vec<(Event<'a>, Range<usize>)>,
+) -> Vec<(Event<'a>, Range<usize>)> {
+    let comments = html_comment_regions(content);
+    if comments.is_empty() {
+        return events;
+    }
+
+    let mut kept = Vec::with_capacity(events.len());
+    let mut skipping = false;
+    for (event, range) in events {
+        match event {
+            Event::Start(Tag::Heading { .. }) if in_regions(&comments, range.start) => {
+                skipping = true;
             }
-
-            offset += line.len();
-            continue;
+            Event::End(TagEnd::Heading(_)) if skipping => skipping = false,
+            _ if skipping => {}
+            event => kept.push((event, range)),
         }
-
-        if let Some((fence_char, marker_len)) = parse_fence_marker(line) {
-            active_fence = Some((fence_char, marker_len));
-            offset += line.len();
-            continue;
-        }
-
-        if let Some(level) = parse_atx_heading_level(line) {
-            let line_start = offset;
-            let line_end = offset + line.len();
-            spans.push(HeadingSpan {
-                level,
-                line_start,
-                line_end,
-            });
-        }
-
-        offset += line.len();
     }
+    kept
+}
 
-    spans
+/// Heading spans taken from the same parser events that drive extraction, so
+/// section bodies can never be paired with the wrong heading (HTML blocks,
+/// blockquotes, setext headings and unterminated fences all agree by construction).
+fn collect_heading_spans(events: &[(Event<'_>, Range<usize>)]) -> Vec<HeadingSpan> {
+    events
+        .iter()
+        .filter_map(|(event, range)| match event {
+            Event::Start(Tag::Heading { level, .. }) => Some(HeadingSpan {
+                level: *level as usize,
+                line_start: range.start,
+                line_end: range.end,
+            }),
+            _ => None,
+        })
+        .collect()
 }
 
-fn extract_section_bodies_in_order(content: &str) -> Vec<SectionBody> {
-    let headings = collect_heading_spans(content);
+fn extract_section_bodies_in_order(
+    content: &str,
+    events: &[(Event<'_>, Range<usize>)],
+) -> Vec<SectionBody> {
+    let headings = collect_heading_spans(events);
     let mut bodies = Vec::with_capacity(headings.len());
 
     for (idx, heading) in headings.iter().enumerate() {
@@ -464,7 +407,7 @@ fn extract_section_bodies_in_order(content: &str) -> Vec<SectionBody> {
             }
         }
 
-        let (trimmed_start, trimmed_end) = trim_line_break_span(content, start, end);
+        let (trimmed_start, trimmed_end) = trim_marker_lines(content, start, end);
         bodies.push(SectionBody {
             start: trimmed_start,
             end: trimmed_end,
@@ -488,12 +431,29 @@ pub enum TagContext {
     Section {
         heading: String, // Original heading text (without tags)
         level: usize,    // Heading level (1-6)
+        /// Enclosing headings (text, level), outermost first
+        parents: Vec<(String, usize)>,
     },
     /// Content from a standalone paragraph
     Paragraph,
 }
 
-/// Source byte span in markdown text.
+/// Byte range of a block in the note it was parsed from.
+///
+/// Offsets index the note text as read from disk: front matter is skipped by
+/// offset, never removed, and the tag footer is blanked without changing its
+/// length, so `start..end` can be used on the file contents directly.
+///
+/// ```
+/// use djour::domain::tags::SourceSpan;
+///
+/// let note = "# Day\n\nShip the release. #work\n";
+/// let span = SourceSpan::new(7, 30);
+/// assert_eq!(span.slice(note), Some("Ship the release. #work"));
+/// assert_eq!(span.len(), 23);
+/// assert_eq!(span.start_line(note), 3);
+/// assert_eq!(span.start_column(note), 1);
+/// ```
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct SourceSpan {
     pub start: usize,
@@ -505,6 +465,42 @@ impl SourceSpan {
         Self { start, end }
     }
 
+    /// First byte of the block
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// Byte just past the end of the block
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    /// Length in bytes (0 for an inverted span)
+    pub fn len(&self) -> usize {
+        self.end.saturating_sub(self.start)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// `start..end`, for slicing or handing to an editor
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    /// 1-based line of `start` in `source`
+    pub fn start_line(&self, source: &str) -> usize {
+        source[..self.start.min(source.len())].matches('\n').count() + 1
+    }
+
+    /// 1-based column of `start` on its line, counted in characters
+    pub fn start_column(&self, source: &str) -> usize {
+        let before = &source[..self.start.min(source.len())];
+        let line_start = before.rfind('\n').map_or(0, |pos| pos + 1);
+        before[line_start..].chars().count() + 1
+    }
+
     pub fn trim_line_breaks(self, content: &str) -> Self {
         let (start, end) = trim_line_break_span(content, self.start, self.end);
         Self { start, end }
@@ -518,11 +514,15 @@ impl SourceSpan {
     }
 }
 
-/// Content payload for tagged output.
+/// Where the text of a [`TaggedContent`] comes from.
 #[derive(Debug, Clone, PartialEq)]
 pub enum ContentPayload {
-    /// Raw byte span into source markdown.
+    /// A byte span of the note the block was parsed from; `source` is the
+    /// whole note. Every block produced by [`TagParser`] has one.
     Span { span: SourceSpan, source: Arc<str> },
+    /// Text not backed by a note, such as blocks built with [`TaggedContent::new`]
+    /// or rewritten after parsing; it has no position in any file.
+    Text(Arc<str>),
 }
 
 /// A piece of content with associated tags
@@ -531,7 +531,9 @@ pub struct TaggedContent {
     /// All tags applying to this content (including inherited)
     pub tags: Vec<String>,
 
-    /// The original content text (tags preserved)
+    /// The original content text (tags preserved).
+    ///
+    /// For span payloads this is exactly `source[span]`.
     pub content: String,
 
     /// Source file this came from
@@ -543,11 +545,24 @@ pub struct TaggedContent {
     /// Context about where this content came from
     pub context: TagContext,
 
-    /// Raw span payload.
+    /// Where `content` comes from (see [`TaggedContent::source_span`])
     pub payload: ContentPayload,
+
+    /// Byte offset of the block's start in its note, giving its place in
+    /// document order; 0 for blocks built with [`TaggedContent::new`]
+    pub position: usize,
+
+    /// Time the block starts with (see [`leading_time`]), ordering blocks within a day;
+    /// None for a whole tagged section
+    pub time: Option<NaiveTime>,
 }
 
 impl TaggedContent {
+    /// A block with no position in a file (a [`ContentPayload::Text`] payload)
+    ///
+    /// # Stability
+    ///
+    /// Part of the supported library API (see the crate docs).
     pub fn new(
         tags: Vec<String>,
         content: String,
@@ -555,20 +570,28 @@ impl TaggedContent {
         date: Option<NaiveDate>,
         context: TagContext,
     ) -> Self {
-        let source: Arc<str> = Arc::from(content.clone());
+        let text: Arc<str> = Arc::from(content.clone());
         Self {
             tags,
+            time: leading_time(&content),
             content,
             source_file,
             date,
             context,
-            payload: ContentPayload::Span {
-                span: SourceSpan::new(0, source.len()),
-                source,
-            },
+            payload: ContentPayload::Text(text),
+            position: 0,
         }
     }
 
+    /// A block whose content is taken from `payload`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a span payload's span is not a valid range of its source.
+    ///
+    /// # Stability
+    ///
+    /// Part of the supported library API (see the crate docs).
     pub fn with_payload(
         tags: Vec<String>,
         payload: ContentPayload,
@@ -576,33 +599,103 @@ impl TaggedContent {
         date: Option<NaiveDate>,
         context: TagContext,
     ) -> Self {
-        let content = match &payload {
-            ContentPayload::Span { span, source } => span
-                .slice(source)
-                .expect("span payload must be valid UTF-8 range")
-                .to_string(),
+        let (content, position) = match &payload {
+            ContentPayload::Span { span, source } => (
+                span.slice(source)
+                    .expect("span payload must be valid UTF-8 range")
+                    .to_string(),
+                span.start,
+            ),
+            ContentPayload::Text(text) => (text.to_string(), 0),
         };
 
         Self {
             tags,
+            time: leading_time(&content),
             content,
             source_file,
             date,
             context,
             payload,
+            position,
         }
     }
 
-    pub(crate) fn raw_payload_content(&self) -> &str {
+    /// Position of the block in its note, for span payloads.
+    ///
+    /// When present, `self.content == source[span]` for the note text the
+    /// parser read, which is the file's content.
+    pub fn source_span(&self) -> Option<SourceSpan> {
         match &self.payload {
-            ContentPayload::Span { span, source } => span
-                .slice(source)
-                .expect("span payload must remain a valid range"),
+            ContentPayload::Span { span, .. } => Some(*span),
+            ContentPayload::Text(_) => None,
+        }
+    }
+
+    /// Byte range of the block in `source_file`, or None when the block has no
+    /// position in a file.
+    ///
+    /// "Jump to source" from a compilation is this range plus a line lookup:
+    ///
+    /// ```
+    /// use djour::domain::tags::TagParser;
+    /// use std::path::Path;
+    ///
+    /// let note = "# Day\n\n## Standup #work\n\nReviewed the plan.\n";
+    /// let blocks = TagParser::extract_from_markdown(note, Path::new("2025-01-15.md"), None);
+    ///
+    /// let range = blocks[0].byte_range_in_file().unwrap();
+    /// assert_eq!(&note[range.clone()], blocks[0].content);
+    /// let line = note[..range.start].matches('\n').count() + 1;
+    /// assert_eq!(line, 5);
+    /// ```
+    pub fn byte_range_in_file(&self) -> Option<Range<usize>> {
+        self.source_span().map(|span| span.range())
+    }
+
+    /// The payload's text and this block's span in it; text payloads span themselves
+    fn span_in_source(&self) -> (SourceSpan, &str) {
+        match &self.payload {
+            ContentPayload::Span { span, source } => (*span, source),
+            ContentPayload::Text(text) => (SourceSpan::new(0, text.len()), text),
+        }
+    }
+
+    pub(crate) fn raw_payload_content(&self) -> &str {
+        let (span, source) = self.span_in_source();
+        span.slice(source)
+            .expect("span payload must remain a valid range")
+    }
+
+    /// The block as written to a compilation at `output_file`: dedented,
+    /// with its relative links rebased. Borrows the source when neither changes it.
+    pub(crate) fn rendered_content_for_output(&self, output_file: Option<&Path>) -> Cow<'_, str> {
+        match dedent_lines(self.raw_payload_content(), self.source_indent()) {
+            Cow::Borrowed(content) => {
+                rewrite_markdown_targets(content, &self.source_file, output_file)
+            }
+            Cow::Owned(content) => Cow::Owned(
+                rewrite_markdown_targets(&content, &self.source_file, output_file).into_owned(),
+            ),
         }
     }
 
-    pub(crate) fn rendered_content_for_output(&self, output_file: Option<&Path>) -> String {
-        rewrite_markdown_targets(self.raw_payload_content(), &self.source_file, output_file)
+    /// Column at which this block starts in its source note, for list items.
+    ///
+    /// Nested list items start mid-line and their continuation lines are
+    /// indented relative to the marker (`3. ` is three columns, `10. ` four),
+    /// so rendering strips this many spaces from every line to keep nested
+    /// content aligned with the marker. Other blocks, and items preceded by
+    /// anything other than spaces on their first line (e.g. a `>` quote
+    /// prefix), report 0, so an indented code block keeps its indent.
+    fn source_indent(&self) -> usize {
+        let (span, source) = self.span_in_source();
+        let line_start = source[..span.start].rfind('\n').map_or(0, |pos| pos + 1);
+        let before = &source[line_start..span.start];
+        if !before.bytes().all(|b| b == b' ') || !starts_with_list_marker(&source[span.start..]) {
+            return 0;
+        }
+        before.len()
     }
 
     pub(crate) fn span_gap_to<'a>(&'a self, next: &'a TaggedContent) -> Option<&'a str> {
@@ -619,7 +712,10 @@ impl TaggedContent {
                 span: next_span,
                 source: next_source,
             },
-        ) = (&self.payload, &next.payload);
+        ) = (&self.payload, &next.payload)
+        else {
+            return None;
+        };
 
         if current_span.end > next_span.start {
             return None;
@@ -629,14 +725,21 @@ impl TaggedContent {
         }
         let gap = current_source.get(current_span.end..next_span.start)?;
         if gap.chars().all(char::is_whitespace) {
-            Some(gap)
+            // The next block is rendered without its source indentation
+            if next.source_indent() > 0 {
+                Some(gap.trim_end_matches(' '))
+            } else {
+                Some(gap)
+            }
         } else {
             None
         }
     }
 
     pub(crate) fn try_extend_span_end(&mut self, new_end: usize) -> bool {
-        let ContentPayload::Span { span, source } = &mut self.payload;
+        let ContentPayload::Span { span, source } = &mut self.payload else {
+            return false;
+        };
         let clamped_end = new_end.min(source.len());
         let updated = if clamped_end <= span.end {
             false
@@ -659,21 +762,34 @@ struct Section {
     level: usize,
     heading: String,
     tags: Vec<String>,
+    /// Date from an `@DD-MM-YYYY` marker in the heading
+    date: Option<NaiveDate>,
 }
 
 /// Tracks the current section hierarchy stack
 #[derive(Debug)]
 struct SectionStack {
     stack: Vec<Section>,
+    /// File-level tags (from front matter) inherited by every block
+    base_tags: Vec<String>,
 }
 
 impl SectionStack {
     fn new() -> Self {
-        Self { stack: Vec::new() }
+        Self {
+            stack: Vec::new(),
+            base_tags: Vec::new(),
+        }
     }
 
     /// Enter a new heading, popping sections at same or higher level
-    fn push_heading(&mut self, level: usize, heading: &str, tags: Vec<String>) {
+    fn push_heading(
+        &mut self,
+        level: usize,
+        heading: &str,
+        tags: Vec<String>,
+        date: Option<NaiveDate>,
+    ) {
         // Pop all sections at the same level or deeper
         self.stack.retain(|s| s.level < level);
 
@@ -682,30 +798,38 @@ impl SectionStack {
             level,
             heading: heading.to_string(),
             tags,
+            date,
         });
     }
 
+    /// Date of the innermost section with an `@DD-MM-YYYY` marker, if any
+    fn current_date(&self) -> Option<NaiveDate> {
+        self.stack.iter().rev().find_map(|section| section.date)
+    }
+
     /// Get all tags from current section hierarchy (union of all parent tags)
     fn current_tags(&self) -> Vec<String> {
-        let mut all_tags = Vec::new();
-        for section in &self.stack {
-            all_tags.extend(section.tags.clone());
-        }
-        // Deduplicate while preserving order
         let mut unique_tags = Vec::new();
-        for tag in all_tags {
-            if !unique_tags.contains(&tag) {
-                unique_tags.push(tag);
-            }
-        }
+        extend_unique(
+            &mut unique_tags,
+            self.base_tags
+                .iter()
+                .chain(self.stack.iter().flat_map(|section| &section.tags))
+                .cloned(),
+        );
         unique_tags
     }
 
     /// Get the current section context (innermost section)
     fn current_context(&self) -> Option<TagContext> {
-        self.stack.last().map(|s| TagContext::Section {
-            heading: s.heading.clone(),
-            level: s.level,
+        let (current, parents) = self.stack.split_last()?;
+        Some(TagContext::Section {
+            heading: current.heading.clone(),
+            level: current.level,
+            parents: parents
+                .iter()
+                .map(|s| (s.heading.clone(), s.level))
+                .collect(),
         })
     }
 
@@ -715,6 +839,52 @@ impl SectionStack {
     }
 }
 
+/// Options controlling tag extraction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TagParseOptions {
+    /// Whether a `tags` key in front matter adds file-level tags
+    pub frontmatter_tags: bool,
+
+    /// Whether `/` is accepted inside tags (`#project/alpha`)
+    pub hierarchical_tags: bool,
+
+    /// Whether tags may end in a `:value` (`#run:5k`)
+    pub value_tags: bool,
+
+    /// Whether `[[...]]` wiki links are links, so `#` inside them is no tag
+    pub wikilinks: bool,
+}
+
+impl Default for TagParseOptions {
+    fn default() -> Self {
+        Self {
+            frontmatter_tags: true,
+            hierarchical_tags: true,
+            value_tags: false,
+            wikilinks: false,
+        }
+    }
+}
+
+/// Scoping of tag-only paragraphs: their tags apply to the list that follows
+/// with nothing but blank lines in between. Any other block-level event
+/// (paragraph, heading, rule, HTML or comment, code block, ...) drops them.
+#[derive(Debug, Default)]
+struct PendingListTags {
+    tags: Option<Vec<String>>,
+}
+
+impl PendingListTags {
+    /// Feed the next event; returns the tags a list starting with it inherits
+    fn next_event(&mut self, event: &Event<'_>) -> Option<Vec<String>> {
+        let tags = self.tags.take();
+        match event {
+            Event::Start(Tag::List(_)) => tags,
+            _ => None,
+        }
+    }
+}
+
 pub struct TagParser;
 
 impl TagParser {
@@ -730,25 +900,82 @@ impl TagParser {
     /// Extract tagged content from markdown for compile output.
     ///
     /// Link/image rebasing is deferred to `TaggedContent::rendered_content_for_output`
-    /// so parsing keeps source spans untouched.
+    /// so parsing keeps source spans untouched. Default parse options apply.
+    ///
+    /// # Stability
+    ///
+    /// Part of the supported library API (see the crate docs).
     pub fn extract_from_markdown_for_output(
+        content: &str,
+        source_file: &Path,
+        date: Option<NaiveDate>,
+        output_file: Option<&Path>,
+    ) -> Vec<TaggedContent> {
+        Self::extract_from_markdown_with_options(
+            content,
+            source_file,
+            date,
+            output_file,
+            TagParseOptions::default(),
+        )
+    }
+
+    /// Extract tagged content from markdown with explicit parse options.
+    ///
+    /// A leading front matter block is never emitted as content. Parsing starts
+    /// after it and all spans are shifted by its byte length, so they keep
+    /// pointing into the original `content`. A tag summary footer is blanked
+    /// out first (keeping offsets), so its tags are never counted twice.
+    pub fn extract_from_markdown_with_options(
         content: &str,
         source_file: &Path,
         date: Option<NaiveDate>,
         _output_file: Option<&Path>,
+        options: TagParseOptions,
     ) -> Vec<TaggedContent> {
-        let section_bodies = extract_section_bodies_in_order(content);
+        Self::extract(content, source_file, date, options)
+    }
+
+    fn extract(
+        content: &str,
+        source_file: &Path,
+        date: Option<NaiveDate>,
+        options: TagParseOptions,
+    ) -> Vec<TaggedContent> {
+        let content = &*strip_tag_footer(content);
+        let (body_offset, file_tags) = match parse_front_matter(content) {
+            Some(front_matter) if options.frontmatter_tags => (front_matter.len, front_matter.tags),
+            Some(front_matter) => (front_matter.len, Vec::new()),
+            None => (0, Vec::new()),
+        };
+        let body = &content[body_offset..];
+        let tag_re = tag_scan_regex(
+            options.hierarchical_tags,
+            options.wikilinks,
+            options.value_tags,
+        );
+
+        let events: Vec<(Event<'_>, Range<usize>)> =
+            drop_commented_headings(body, MdParser::new(body).into_offset_iter().collect());
+        let section_bodies: Vec<SectionBody> = extract_section_bodies_in_order(body, &events)
+            .into_iter()
+            .map(|section| SectionBody {
+                start: section.start + body_offset,
+                end: section.end + body_offset,
+                text: section.text,
+            })
+            .collect();
         let mut results = Vec::new();
         let mut section_stack = SectionStack::new();
+        section_stack.base_tags = file_tags;
         let mut list_tag_stack: Vec<Vec<String>> = Vec::new();
         let mut item_stack: Vec<String> = Vec::new();
         let mut item_span_stack: Vec<SourceSpan> = Vec::new();
         let mut item_tag_stack: Vec<Vec<String>> = Vec::new();
         let mut item_children_stack: Vec<Vec<TaggedContent>> = Vec::new();
-        let mut pending_list_tags: Option<Vec<String>> = None;
+        let mut pending_list_tags = PendingListTags::default();
 
         let source_arc: Arc<str> = Arc::from(content.to_string());
-        let parser = MdParser::new(content).into_offset_iter();
         let mut current_paragraph = String::new();
         let mut current_paragraph_span: Option<SourceSpan> = None;
         let mut in_paragraph = false;
@@ -762,16 +989,10 @@ impl TagParser {
         let mut pending_code_block_target: Option<usize> = None;
         let mut inline_stack: Vec<InlineConstruct> = Vec::new();
         let mut heading_index = 0usize;
+        let mut in_html_comment = false;
 
-        let extend_unique = |dest: &mut Vec<String>, tags: Vec<String>| {
-            for tag in tags {
-                if !dest.contains(&tag) {
-                    dest.push(tag);
-                }
-            }
-        };
-
-        for (event, range) in parser {
+        for (event, range) in events {
+            let range = (range.start + body_offset)..(range.end + body_offset);
             if let Some(span) = current_paragraph_span.as_mut() {
                 span.start = span.start.min(range.start);
                 span.end = span.end.max(range.end);
@@ -785,12 +1006,14 @@ impl TagParser {
                 span.end = span.end.max(range.end);
             }
 
+            let intro_tags = pending_list_tags.next_event(&event);
+
             match event {
                 Event::Start(Tag::List(_)) => {
                     pending_code_block_target = None;
                     // Establish list-level inherited tags (from parent list item, if any)
                     let mut inherited = if item_stack.is_empty() {
-                        pending_list_tags.take().unwrap_or_default()
+                        intro_tags.unwrap_or_default()
                     } else {
                         list_tag_stack.last().cloned().unwrap_or_default()
                     };
@@ -799,7 +1022,7 @@ impl TagParser {
                             extend_unique(&mut inherited, item_tags);
                         }
                         if let Some(item_text) = item_stack.last() {
-                            let text_tags = extract_tags(item_text);
+                            let text_tags = extract_tags(item_text, tag_re);
                             extend_unique(&mut inherited, text_tags);
                         }
                     }
@@ -827,8 +1050,8 @@ impl TagParser {
                         .trim_line_breaks(content);
                     let mut item_tags = item_tag_stack.pop().unwrap_or_default();
                     let child_items = item_children_stack.pop().unwrap_or_default();
-                    let text_tags = extract_tags(&item_text);
-                    extend_unique(&mut item_tags, text_tags);
+                    let scanned = scan_tags(&item_text, tag_re, false);
+                    extend_unique(&mut item_tags, scanned.tags);
 
                     let section_tags = section_stack.current_tags();
                     let list_tags = list_tag_stack.last().cloned().unwrap_or_default();
@@ -845,7 +1068,7 @@ impl TagParser {
                         !all_tags.is_empty()
                     };
 
-                    let content_clean = strip_tags(&item_text);
+                    let content_clean = scanned.stripped;
                     let current_item = if !content_clean.trim().is_empty() && should_emit {
                         let span_text = item_span
                             .slice(content)
@@ -862,7 +1085,7 @@ impl TagParser {
                             all_tags,
                             payload,
                             source_file.to_path_buf(),
-                            date,
+                            section_stack.current_date().or(date),
                             section_stack
                                 .current_context()
                                 .unwrap_or(TagContext::Paragraph),
@@ -892,15 +1115,17 @@ impl TagParser {
                     in_heading = true;
                     current_heading_level = level as usize;
                     current_heading_text.clear();
-                    pending_list_tags = None;
                 }
 
                 Event::End(TagEnd::Heading(_)) => {
                     in_heading = false;
 
-                    // Extract tags from heading
-                    let heading_tags = extract_tags(&current_heading_text);
-                    let heading_clean = strip_tags(&current_heading_text);
+                    // Extract the date marker and tags from heading
+                    let (heading_text, heading_date) = split_section_date(&current_heading_text);
+                    let ScannedTags {
+                        tags: heading_tags,
+                        stripped: heading_clean,
+                    } = scan_tags(&heading_text, tag_re, true);
                     let section_body = section_bodies
                         .get(heading_index)
                         .cloned()
@@ -912,6 +1137,7 @@ impl TagParser {
                         current_heading_level,
                         &heading_clean,
                         heading_tags.clone(),
+                        heading_date,
                     );
 
                     // For tagged headings, compile the full section body verbatim.
@@ -928,16 +1154,19 @@ impl TagParser {
                             span: section_span,
                             source: Arc::clone(&source_arc),
                         };
-                        let tagged = TaggedContent::with_payload(
-                            section_stack.current_tags(),
-                            payload,
-                            source_file.to_path_buf(),
-                            date,
-                            TagContext::Section {
-                                heading: heading_clean,
-                                level: current_heading_level,
-                            },
-                        );
+                        // A whole section is not timed by the first item in it
+                        let tagged = TaggedContent {
+                            time: None,
+                            ..TaggedContent::with_payload(
+                                section_stack.current_tags(),
+                                payload,
+                                source_file.to_path_buf(),
+                                section_stack.current_date().or(date),
+                                section_stack
+                                    .current_context()
+                                    .expect("heading was just pushed"),
+                            )
+                        };
                         results.push(tagged);
                     }
                 }
@@ -947,7 +1176,6 @@ impl TagParser {
                     in_paragraph = true;
                     current_paragraph.clear();
                     current_paragraph_span = Some(SourceSpan::new(range.start, range.end));
-                    pending_list_tags = None;
                 }
 
                 Event::End(TagEnd::Paragraph) => {
@@ -958,13 +1186,15 @@ impl TagParser {
                         .trim_line_breaks(content);
 
                     // Extract paragraph-level tags (at end of paragraph)
-                    let para_tags = extract_tags(&current_paragraph);
+                    let ScannedTags {
+                        tags: para_tags,
+                        stripped: content_clean,
+                    } = scan_tags(&current_paragraph, tag_re, false);
 
                     if let Some(item_tags) = item_tag_stack.last_mut() {
                         extend_unique(item_tags, para_tags.clone());
                     }
 
-                    let content_clean = strip_tags(&current_paragraph);
                     let content_raw = current_paragraph.trim().to_string();
                     let list_tags = list_tag_stack.last().cloned().unwrap_or_default();
                     let mut local_tags = list_tags.clone();
@@ -980,10 +1210,9 @@ impl TagParser {
                                 item_text.push_str(&content_raw);
                             }
                         }
-                        pending_list_tags = None;
                     } else if content_clean.trim().is_empty() && !para_tags.is_empty() {
                         // Tag-only paragraph can act as a list tag context for a following list
-                        pending_list_tags = Some(para_tags);
+                        pending_list_tags.tags = Some(para_tags);
                     } else {
                         // Paragraph has tags or inherits tags from section or list
                         let mut all_tags = section_stack.current_tags();
@@ -1011,7 +1240,7 @@ impl TagParser {
                                 all_tags,
                                 payload,
                                 source_file.to_path_buf(),
-                                date,
+                                section_stack.current_date().or(date),
                                 section_stack
                                     .current_context()
                                     .unwrap_or(TagContext::Paragraph),
@@ -1019,7 +1248,6 @@ impl TagParser {
                             results.push(paragraph);
                             pending_code_block_target = Some(results.len() - 1);
                         }
-                        pending_list_tags = None;
                     }
                 }
 
@@ -1122,7 +1350,8 @@ impl TagParser {
                 }
 
                 Event::Html(html) => {
-                    let html = html.as_ref();
+                    // Comments are invisible: they carry no tags and are never a block of their own
+                    let html = &strip_html_comments(&html, &mut in_html_comment);
                     if in_code_block || in_heading || in_paragraph || !item_stack.is_empty() {
                         append_to_active(
                             html,
@@ -1135,7 +1364,10 @@ impl TagParser {
                             &mut item_stack,
                         );
                     } else {
-                        let html_tags = extract_tags(html);
+                        let ScannedTags {
+                            tags: html_tags,
+                            stripped: content_clean,
+                        } = scan_tags(html, tag_re, false);
                         let list_tags = list_tag_stack.last().cloned().unwrap_or_default();
                         let mut local_tags = list_tags.clone();
                         extend_unique(&mut local_tags, html_tags.clone());
@@ -1144,7 +1376,6 @@ impl TagParser {
                         let mut all_tags = section_stack.current_tags();
                         extend_unique(&mut all_tags, local_tags.clone());
 
-                        let content_clean = strip_tags(html);
                         let should_emit = if inside_explicit_section {
                             !local_tags.is_empty()
                         } else {
@@ -1168,7 +1399,7 @@ impl TagParser {
                                 all_tags,
                                 payload,
                                 source_file.to_path_buf(),
-                                date,
+                                section_stack.current_date().or(date),
                                 section_stack
                                     .current_context()
                                     .unwrap_or(TagContext::Paragraph),
@@ -1246,7 +1477,7 @@ impl TagParser {
                                 all_tags,
                                 payload,
                                 source_file.to_path_buf(),
-                                date,
+                                section_stack.current_date().or(date),
                                 section_stack
                                     .current_context()
                                     .unwrap_or(TagContext::Paragraph),
@@ -1279,25 +1510,181 @@ impl TagParser {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::domain::tags::syntax::tag_regex;
+    use crate::domain::tags::tags_in_note;
     use tempfile::TempDir;
 
     #[test]
-    fn test_extract_tags() {
-        assert_eq!(extract_tags("Hello #world"), vec!["world"]);
-        assert_eq!(extract_tags("#work #urgent"), vec!["work", "urgent"]);
-        assert_eq!(
-            extract_tags("#project-alpha #task_1"),
-            vec!["project-alpha", "task_1"]
-        );
-        assert_eq!(extract_tags("#Work #WORK"), vec!["work", "work"]); // Case insensitive
-        assert_eq!(extract_tags("No tags here"), Vec::<String>::new());
+    fn test_leading_time() {
+        let time = |h, m| NaiveTime::from_hms_opt(h, m, 0);
+        assert_eq!(leading_time("09:30 standup #work"), time(9, 30));
+        assert_eq!(leading_time("  - 14:00 deep work #work"), time(14, 0));
+        assert_eq!(leading_time("1. 9:05 call"), time(9, 5));
+        assert_eq!(leading_time("* [x] 23:59"), time(23, 59));
+        assert_eq!(leading_time("- 07:15, before breakfast"), time(7, 15));
+        // Not a 24-hour time
+        assert_eq!(leading_time("- 24:00 midnight"), None);
+        assert_eq!(leading_time("- 12:60 typo"), None);
+        assert_eq!(leading_time("- 123:00"), None);
+        assert_eq!(leading_time("- 12:30:15 with seconds"), None);
+        assert_eq!(leading_time("- 12:305"), None);
+        // Only at the start
+        assert_eq!(leading_time("standup at 09:30"), None);
+        assert_eq!(leading_time("## 09:30 Standup"), None);
+    }
+
+    #[test]
+    fn test_blocks_record_their_time() {
+        let content = TagParser::extract_from_markdown(
+            "- 14:00 deep work #work\n- 9:30 standup #work\n- lunch #work\n",
+            Path::new("2025-01-15.md"),
+            NaiveDate::from_ymd_opt(2025, 1, 15),
+        );
+        let times: Vec<Option<NaiveTime>> = content.iter().map(|tc| tc.time).collect();
+        assert_eq!(
+            times,
+            [
+                NaiveTime::from_hms_opt(14, 0, 0),
+                NaiveTime::from_hms_opt(9, 30, 0),
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_tags() {
+        assert_eq!(
+            extract_tags("Hello #world", tag_regex(false)),
+            vec!["world"]
+        );
+        assert_eq!(
+            extract_tags("#work #urgent", tag_regex(false)),
+            vec!["work", "urgent"]
+        );
+        assert_eq!(
+            extract_tags("#project-alpha #task_1", tag_regex(false)),
+            vec!["project-alpha", "task_1"]
+        );
+        assert_eq!(extract_tags("#Work #WORK", tag_regex(false)), vec!["work"]); // Case insensitive, deduplicated
+        assert_eq!(
+            extract_tags("No tags here", tag_regex(false)),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn test_extract_hierarchical_tags() {
+        let re = tag_regex(true);
+        assert_eq!(
+            extract_tags("Ship #Project/Alpha/backend and #work/", re),
+            vec!["project/alpha/backend", "work"]
+        );
+        assert_eq!(
+            scan_tags("Deploy #project/alpha", re, false).stripped,
+            "Deploy"
+        );
+    }
+
+    #[test]
+    fn test_hierarchical_tags_disabled_keeps_flat_parsing() {
+        let markdown = "Deploy the service. #project/alpha";
+        let flat = TagParser::extract_from_markdown_with_options(
+            markdown,
+            Path::new("test.md"),
+            None,
+            None,
+            TagParseOptions {
+                hierarchical_tags: false,
+                ..TagParseOptions::default()
+            },
+        );
+        assert_eq!(flat[0].tags, vec!["project"]);
+
+        let nested = TagParser::extract_from_markdown(markdown, Path::new("test.md"), None);
+        assert_eq!(nested[0].tags, vec!["project/alpha"]);
+    }
+
+    #[test]
+    fn test_value_tags_keep_their_value() {
+        let markdown = "Morning run. #Run:5K #mood:good\n\n## Evening #run:10k\n\nStretching #run.";
+        let parse = |value_tags: bool| {
+            TagParser::extract_from_markdown_with_options(
+                markdown,
+                Path::new("test.md"),
+                None,
+                None,
+                TagParseOptions {
+                    value_tags,
+                    ..TagParseOptions::default()
+                },
+            )
+        };
+
+        let valued = parse(true);
+        assert_eq!(valued[0].tags, vec!["run:5k", "mood:good"]);
+        assert!(valued.iter().any(|block| block.tags == vec!["run:10k"]));
+        assert!(valued
+            .iter()
+            .any(|block| block.tags == vec!["run:10k", "run"]));
+
+        let plain = parse(false);
+        assert_eq!(plain[0].tags, vec!["run", "mood"]);
+    }
+
+    #[test]
+    fn test_strip_tags() {
+        assert_eq!(
+            scan_tags("Text #work #urgent", tag_regex(false), false).stripped,
+            "Text"
+        );
+        assert_eq!(
+            scan_tags("#work Meeting notes #urgent", tag_regex(false), false).stripped,
+            "Meeting notes"
+        );
+        assert_eq!(
+            scan_tags("No tags", tag_regex(false), false).stripped,
+            "No tags"
+        );
+    }
+
+    #[test]
+    fn test_scan_tags_keeps_literal_hashes_in_headings() {
+        let re = tag_regex(true);
+        let scanned = scan_tags("Bug #1234 triage #work", re, true);
+        assert_eq!(scanned.tags, vec!["1234", "work"]);
+        assert_eq!(scanned.stripped, "Bug #1234 triage");
+
+        let scanned = scan_tags("Release #urgent plan for C# #2025-q1 and issue#7", re, true);
+        assert_eq!(scanned.tags, vec!["urgent", "2025-q1", "7"]);
+        assert_eq!(scanned.stripped, "Release plan for C# and issue#7");
+
+        let scanned = scan_tags("#work  Standup\t#daily  notes", re, true);
+        assert_eq!(scanned.stripped, "Standup\tnotes");
+    }
+
+    #[test]
+    fn test_scan_tags_reads_pasted_characters() {
+        let re = tag_regex(true);
+        let scanned = scan_tags("Plan\u{00A0}#Work\u{2011}Item and \u{FF03}work", re, false);
+        assert_eq!(scanned.tags, vec!["work-item", "work"]);
+        assert_eq!(scanned.stripped, "Plan\u{00A0}and");
+
+        // No-break spaces around a removed tag collapse like spaces
+        let scanned = scan_tags("Call\u{202F}#home\u{202F}tonight #errand", re, false);
+        assert_eq!(scanned.tags, vec!["home", "errand"]);
+        assert_eq!(scanned.stripped, "Call\u{202F}tonight");
     }
 
     #[test]
-    fn test_strip_tags() {
-        assert_eq!(strip_tags("Text #work #urgent"), "Text");
-        assert_eq!(strip_tags("#work Meeting notes #urgent"), "Meeting notes");
-        assert_eq!(strip_tags("No tags"), "No tags");
+    fn test_section_heading_keeps_issue_numbers() {
+        let markdown = "## Bug #1234 triage #work\n\nRepro steps.\n";
+        let results = TagParser::extract_from_markdown(markdown, Path::new("test.md"), None);
+
+        assert_eq!(results[0].tags, vec!["1234", "work"]);
+        assert!(matches!(
+            &results[0].context,
+            TagContext::Section { heading, .. } if heading == "Bug #1234 triage"
+        ));
     }
 
     #[test]
@@ -1310,6 +1697,7 @@ mod tests {
             TagContext::Section {
                 heading: "Daily Standup".to_string(),
                 level: 2,
+                parents: Vec::new(),
             },
         );
 
@@ -1322,27 +1710,37 @@ mod tests {
         let mut stack = SectionStack::new();
 
         // Push level 1 heading
-        stack.push_heading(1, "Main", vec!["tag1".to_string()]);
+        stack.push_heading(1, "Main", vec!["tag1".to_string()], None);
         assert_eq!(stack.current_tags(), vec!["tag1"]);
 
         // Push level 2 heading - inherits from level 1
-        stack.push_heading(2, "Sub", vec!["tag2".to_string()]);
+        stack.push_heading(2, "Sub", vec!["tag2".to_string()], None);
         assert_eq!(stack.current_tags(), vec!["tag1", "tag2"]);
 
         // Push another level 2 - replaces previous level 2
-        stack.push_heading(2, "Sub2", vec!["tag3".to_string()]);
+        stack.push_heading(2, "Sub2", vec!["tag3".to_string()], None);
         assert_eq!(stack.current_tags(), vec!["tag1", "tag3"]);
 
         // Push level 1 - clears all
-        stack.push_heading(1, "Main2", vec!["tag4".to_string()]);
+        stack.push_heading(1, "Main2", vec!["tag4".to_string()], None);
         assert_eq!(stack.current_tags(), vec!["tag4"]);
     }
 
     #[test]
     fn test_section_stack_deduplication() {
         let mut stack = SectionStack::new();
-        stack.push_heading(1, "Main", vec!["work".to_string(), "urgent".to_string()]);
-        stack.push_heading(2, "Sub", vec!["urgent".to_string(), "meeting".to_string()]);
+        stack.push_heading(
+            1,
+            "Main",
+            vec!["work".to_string(), "urgent".to_string()],
+            None,
+        );
+        stack.push_heading(
+            2,
+            "Sub",
+            vec!["urgent".to_string(), "meeting".to_string()],
+            None,
+        );
 
         let tags = stack.current_tags();
         assert_eq!(tags, vec!["work", "urgent", "meeting"]); // "urgent" not duplicated
@@ -1593,50 +1991,12 @@ See [Design Doc](./docs/design.md). #work
         let rendered = results
             .iter()
             .map(|r| r.rendered_content_for_output(Some(&output)))
-            .collect::<Vec<String>>()
+            .collect::<Vec<_>>()
             .join("\n");
         assert!(rendered.contains("[Design Doc](../docs/design.md)"));
         assert!(rendered.contains("![Diagram](../images/diagram.png)"));
     }
 
-    #[test]
-    fn test_reference_link_definition_rewritten_for_output_file() {
-        let temp = TempDir::new().unwrap();
-        let source = temp.path().join("2025-01-15.md");
-        let output = temp.path().join(".compilations").join("work.md");
-        let markdown = r#"
-[Design Doc][design] #work
-
-[design]: ./docs/design.md
-[site]: https://example.com/docs
-"#;
-
-        let rewritten = rewrite_markdown_targets(markdown, &source, Some(&output));
-
-        assert!(rewritten.contains("[design]: ../docs/design.md"));
-        assert!(rewritten.contains("[site]: https://example.com/docs"));
-    }
-
-    #[test]
-    fn test_fenced_code_block_links_not_rewritten_for_output_file() {
-        let temp = TempDir::new().unwrap();
-        let source = temp.path().join("2025-01-15.md");
-        let output = temp.path().join(".compilations").join("work.md");
-        let markdown = r#"
-See [Design Doc](./docs/design.md). #work
-
-```md
-![Diagram](./images/diagram.png)
-```
-"#;
-
-        let rewritten = rewrite_markdown_targets(markdown, &source, Some(&output));
-
-        assert!(rewritten.contains("[Design Doc](../docs/design.md)"));
-        assert!(rewritten.contains("![Diagram](./images/diagram.png)"));
-        assert!(!rewritten.contains("![Diagram](../images/diagram.png)"));
-    }
-
     #[test]
     fn test_external_link_not_rewritten() {
         let temp = TempDir::new().unwrap();
@@ -1666,6 +2026,120 @@ See [Website](https://example.com/docs). #work
         assert_eq!(results[0].date, date);
     }
 
+    #[test]
+    fn test_section_date_overrides_note_date() {
+        let note_date = NaiveDate::from_ymd_opt(2025, 1, 16);
+        let markdown = "## Tuesday catch-up @14-01-2025 #work\n\n\
+                        Shipped the fix.\n\n\
+                        ### Details\n\n\
+                        - reviewed logs #ops\n\n\
+                        ## Today\n\n\
+                        Planning #work\n";
+
+        let results =
+            TagParser::extract_from_markdown(markdown, Path::new("2025-01-16.md"), note_date);
+        let dates: Vec<_> = results
+            .iter()
+            .map(|tc| (tc.content.lines().next().unwrap(), tc.date))
+            .collect();
+        let tuesday = NaiveDate::from_ymd_opt(2025, 1, 14);
+        assert_eq!(
+            dates,
+            [
+                ("Shipped the fix.", tuesday),
+                ("- reviewed logs #ops", tuesday),
+                ("Planning #work", note_date),
+            ]
+        );
+        assert_eq!(
+            results[0].context,
+            TagContext::Section {
+                heading: "Tuesday catch-up".to_string(),
+                level: 2,
+                parents: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn test_section_date_override_inside_override() {
+        let markdown = "# Catch-up @13-01-2025\n\n\
+                        Monday #work\n\n\
+                        ## Later @14-01-2025\n\n\
+                        Tuesday #work\n\n\
+                        ## Same day\n\n\
+                        Still Monday #work\n";
+
+        let results = TagParser::extract_from_markdown(markdown, Path::new("x.md"), None);
+        let dates: Vec<_> = results.iter().map(|tc| tc.date).collect();
+        assert_eq!(
+            dates,
+            [
+                NaiveDate::from_ymd_opt(2025, 1, 13),
+                NaiveDate::from_ymd_opt(2025, 1, 14),
+                NaiveDate::from_ymd_opt(2025, 1, 13),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_section_date_stays_heading_text() {
+        let note_date = NaiveDate::from_ymd_opt(2025, 1, 16);
+        let markdown = "## Catch-up @31-02-2025 #work\n\nBody\n";
+
+        let results =
+            TagParser::extract_from_markdown(markdown, Path::new("2025-01-16.md"), note_date);
+        assert_eq!(results[0].date, note_date);
+        assert!(matches!(
+            &results[0].context,
+            TagContext::Section { heading, .. } if heading == "Catch-up @31-02-2025"
+        ));
+    }
+
+    #[test]
+    fn test_section_dates() {
+        let content = "---\ntitle: x\n---\n# Week @13-01-2025\n\n\
+                       ```\n## Code @14-01-2025\n```\n\
+                       mail me @15-01-2025\n\
+                       ## Bad @31-02-2025 #work\n";
+        assert_eq!(
+            section_dates(content),
+            [
+                SectionDate {
+                    line: 4,
+                    marker: "@13-01-2025".to_string(),
+                    date: NaiveDate::from_ymd_opt(2025, 1, 13),
+                },
+                SectionDate {
+                    line: 10,
+                    marker: "@31-02-2025".to_string(),
+                    date: None,
+                },
+            ]
+        );
+
+        assert_eq!(
+            section_dates(
+                "Use `<!--` here\n\n## Later @16-01-2025\n<!--\n## Gone @17-01-2025\n-->\n"
+            ),
+            [SectionDate {
+                line: 3,
+                marker: "@16-01-2025".to_string(),
+                date: NaiveDate::from_ymd_opt(2025, 1, 16),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_starts_with_list_marker() {
+        for item in ["- a", "* a", "+ a", "1. a", "10) a", "-", "3.\n"] {
+            assert!(starts_with_list_marker(item), "{item:?}");
+        }
+        for text in ["-a", "let x = 1;", "2025-01-15", "1.5 hours", "#work"] {
+            assert!(!starts_with_list_marker(text), "{text:?}");
+        }
+    }
+
     #[test]
     fn test_multi_line_paragraph_with_tags() {
         // Test that SoftBreak (line continuation in source) is preserved
@@ -1719,6 +2193,57 @@ See [Website](https://example.com/docs). #work
         assert!(has_item_3);
     }
 
+    /// Contents of the blocks carrying `tag`
+    fn tagged_with<'a>(results: &'a [TaggedContent], tag: &str) -> Vec<&'a str> {
+        results
+            .iter()
+            .filter(|r| r.tags.iter().any(|t| t == tag))
+            .map(|r| r.content.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_tag_only_paragraph_tags_the_list_right_after_it() {
+        let markdown = "#tag\n\n\n- item 1\n- item 2\n  - item 2a\n\n- after\n";
+        let results = TagParser::extract_from_markdown(markdown, Path::new("test.md"), None);
+        assert_eq!(
+            tagged_with(&results, "tag"),
+            vec!["- item 1", "- item 2\n  - item 2a", "- item 2a", "- after"]
+        );
+        assert_eq!(
+            tags_in_note(markdown, TagParseOptions::default()),
+            ["tag"; 4]
+        );
+    }
+
+    #[test]
+    fn test_tag_only_paragraph_does_not_reach_past_another_paragraph() {
+        let markdown = "#tag\n\nUntagged note.\n\n- item 1\n- item 2\n";
+        let results = TagParser::extract_from_markdown(markdown, Path::new("test.md"), None);
+        assert!(tagged_with(&results, "tag").is_empty(), "{:?}", results);
+        assert_eq!(tags_in_note(markdown, TagParseOptions::default()), ["tag"]);
+    }
+
+    #[test]
+    fn test_tag_only_paragraph_does_not_reach_past_a_thematic_break() {
+        let markdown = "#tag\n\n---\n\n- item 1\n- item 2\n\n#other\n\n***\n- item 3\n";
+        let results = TagParser::extract_from_markdown(markdown, Path::new("test.md"), None);
+        assert!(tagged_with(&results, "tag").is_empty(), "{:?}", results);
+        assert!(tagged_with(&results, "other").is_empty(), "{:?}", results);
+    }
+
+    #[test]
+    fn test_tag_only_paragraph_does_not_reach_past_an_html_comment() {
+        let markdown = "#tag\n\n<!-- -->\n\n- item 1\n\n#other\n\n<div>x</div>\n\n- item 2\n";
+        let results = TagParser::extract_from_markdown(markdown, Path::new("test.md"), None);
+        assert!(tagged_with(&results, "tag").is_empty(), "{:?}", results);
+        assert!(tagged_with(&results, "other").is_empty(), "{:?}", results);
+        assert_eq!(
+            tags_in_note(markdown, TagParseOptions::default()),
+            ["tag", "other"]
+        );
+    }
+
     #[test]
     fn test_list_item_tag_applies_to_subitems() {
         let markdown = r#"
@@ -1814,13 +2339,125 @@ Some content under the heading.
         let results = TagParser::extract_from_markdown(markdown, Path::new("test.md"), None);
 
         assert_eq!(results.len(), 1);
-        let ContentPayload::Span { span, source } = &results[0].payload;
+        let ContentPayload::Span { span, source } = &results[0].payload else {
+            panic!("parsed blocks carry span payloads");
+        };
         assert_eq!(
             span.slice(source).unwrap_or_default(),
             "Paragraph with [Link](./docs/design.md). #work"
         );
     }
 
+    #[test]
+    fn test_every_parsed_block_is_its_file_span() {
+        let note = concat!(
+            "---\ntags: [home]\n---\n",
+            "# Day\n\n",
+            "## Standup #work\n\nReviewed the plan.\n\n",
+            "## Other\n\n",
+            "Paragraph about a trip. #travel\n\n",
+            "- Errands #personal\n  - buy milk\n\n",
+            "```sh #ops\nmake deploy\n```\n\n",
+            "<!-- djour:tags -->\nTags in this note: #work <!-- /djour:tags -->\n",
+        );
+        let blocks = TagParser::extract_from_markdown(note, Path::new("2025-01-15.md"), None);
+        assert!(blocks.len() >= 4, "{:?}", blocks);
+
+        for block in &blocks {
+            let range = block
+                .byte_range_in_file()
+                .expect("parsed blocks are span-backed");
+            assert_eq!(&note[range.clone()], block.content);
+            assert_eq!(block.source_span().unwrap().range(), range);
+        }
+        let standup = blocks
+            .iter()
+            .find(|block| block.content == "Reviewed the plan.")
+            .unwrap();
+        assert_eq!(standup.source_span().unwrap().start_line(note), 8);
+    }
+
+    #[test]
+    fn test_constructed_blocks_have_no_file_position() {
+        let block = TaggedContent::new(
+            vec!["work".to_string()],
+            "Built by hand #work".to_string(),
+            PathBuf::from("2025-01-15.md"),
+            None,
+            TagContext::Paragraph,
+        );
+        assert_eq!(block.source_span(), None);
+        assert_eq!(block.byte_range_in_file(), None);
+        assert_eq!(block.raw_payload_content(), "Built by hand #work");
+    }
+
+    #[test]
+    fn test_front_matter_not_emitted_and_tags_inherited() {
+        let markdown = "---\ntitle: Retro\ntags: #work #life\n---\n\n## Notes\n\nShipped the release.\n\nFollow up. #urgent";
+        let results = TagParser::extract_from_markdown(markdown, Path::new("test.md"), None);
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].content, "Shipped the release.");
+        assert_eq!(results[0].tags, vec!["work", "life"]);
+        assert_eq!(results[1].content, "Follow up. #urgent");
+        assert_eq!(results[1].tags, vec!["work", "life", "urgent"]);
+        assert!(results.iter().all(|r| !r.content.contains("---")));
+    }
+
+    #[test]
+    fn test_toml_front_matter_section_spans_stay_correct() {
+        let markdown = "+++\ntags = [\"work\"]\n+++\n## Sprint #planning\n\nScope the backlog.";
+        let results = TagParser::extract_from_markdown(markdown, Path::new("test.md"), None);
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].content, "Scope the backlog.");
+        assert_eq!(results[0].tags, vec!["work", "planning"]);
+        let ContentPayload::Span { span, source } = &results[0].payload else {
+            panic!("parsed blocks carry span payloads");
+        };
+        assert_eq!(span.slice(source).unwrap(), "Scope the backlog.");
+    }
+
+    #[test]
+    fn test_wikilinks_option_keeps_link_text_free_of_tags() {
+        let markdown = "See [[#heading]] and [[design notes#Goals|goals]] #work";
+        let options = TagParseOptions {
+            wikilinks: true,
+            ..TagParseOptions::default()
+        };
+        let results = TagParser::extract_from_markdown_with_options(
+            markdown,
+            Path::new("test.md"),
+            None,
+            None,
+            options,
+        );
+        assert_eq!(results[0].tags, vec!["work"]);
+
+        let plain = TagParser::extract_from_markdown(markdown, Path::new("test.md"), None);
+        assert_eq!(plain[0].tags, vec!["heading", "goals", "work"]);
+    }
+
+    #[test]
+    fn test_front_matter_tags_disabled() {
+        let markdown = "---\ntags: #work\n---\nPlain paragraph.\n\nTagged paragraph. #life";
+        let options = TagParseOptions {
+            frontmatter_tags: false,
+            ..TagParseOptions::default()
+        };
+        let results = TagParser::extract_from_markdown_with_options(
+            markdown,
+            Path::new("test.md"),
+            None,
+            None,
+            options,
+        );
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].content, "Tagged paragraph. #life");
+        assert_eq!(results[0].tags, vec!["life"]);
+    }
+
     #[test]
     fn test_list_item_span_preserves_marker_style() {
         let markdown = "* first #work\n* second #work\n";
@@ -1833,6 +2470,51 @@ Some content under the heading.
         assert!(!results[1].content.starts_with("- "));
     }
 
+    fn rendered(markdown: &str) -> Vec<String> {
+        TagParser::extract_from_markdown(markdown, Path::new("test.md"), None)
+            .iter()
+            .map(|r| r.rendered_content_for_output(None).into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn test_ordered_items_keep_numbers_under_unordered_parent() {
+        let markdown = "- errands\n  1. call bank #todo\n  2. renew passport #todo\n";
+        assert_eq!(
+            rendered(markdown),
+            vec!["1. call bank #todo", "2. renew passport #todo"]
+        );
+
+        let markdown = "7. pay rent #todo\n8. file taxes #todo\n";
+        assert_eq!(
+            rendered(markdown),
+            vec!["7. pay rent #todo", "8. file taxes #todo"]
+        );
+    }
+
+    #[test]
+    fn test_nested_item_continuation_aligns_with_marker() {
+        // Unordered under ordered: continuation lines follow the "- " marker
+        let markdown = "3. trip\n   - book hotel #todo\n     near the station\n";
+        assert_eq!(
+            rendered(markdown),
+            vec!["- book hotel #todo\n  near the station"]
+        );
+
+        // Ordered under unordered with a four-column "10. " marker
+        let markdown = "- chores\n\n  10. laundry #todo\n      - whites\n      - colours\n";
+        assert_eq!(
+            rendered(markdown)[0],
+            "10. laundry #todo\n    - whites\n    - colours"
+        );
+    }
+
+    #[test]
+    fn test_top_level_ordered_item_is_unchanged() {
+        let markdown = "10. parent #todo\n    - child\n";
+        assert_eq!(rendered(markdown)[0], "10. parent #todo\n    - child");
+    }
+
     #[test]
     fn test_tagged_paragraph_with_code_block_extends_span() {
         let markdown = r#"Snippet title. #work
@@ -1849,4 +2531,192 @@ fn hi() {}
         assert!(raw.contains("```rust"));
         assert!(raw.contains("fn hi() {}"));
     }
+
+    fn section_content<'a>(results: &'a [TaggedContent], name: &str) -> &'a str {
+        &results
+            .iter()
+            .find(|r| matches!(&r.context, TagContext::Section { heading, .. } if heading == name))
+            .unwrap_or_else(|| panic!("missing section {name}"))
+            .content
+    }
+
+    #[test]
+    fn test_heading_like_line_in_html_block_does_not_shift_sections() {
+        let markdown = r#"<div>
+# Not a heading #ignored
+</div>
+
+## Health #health
+
+Ran 5k.
+
+## Work #work
+
+Shipped the release.
+"#;
+        let results = TagParser::extract_from_markdown(markdown, Path::new("test.md"), None);
+
+        assert_eq!(section_content(&results, "Health"), "Ran 5k.");
+        assert_eq!(section_content(&results, "Work"), "Shipped the release.");
+    }
+
+    #[test]
+    fn test_blockquote_heading_does_not_shift_sections() {
+        let markdown = r#"> ## Quoted #quote
+>
+> Quoted text.
+
+## Health #health
+
+Ran 5k.
+
+## Work #work
+
+Shipped the release.
+"#;
+        let results = TagParser::extract_from_markdown(markdown, Path::new("test.md"), None);
+
+        assert_eq!(section_content(&results, "Health"), "Ran 5k.");
+        assert_eq!(section_content(&results, "Work"), "Shipped the release.");
+    }
+
+    #[test]
+    fn test_fence_closed_by_container_does_not_hide_later_headings() {
+        let markdown = r#"- item
+  ```
+  # inside code
+
+## Health #health
+
+Ran 5k.
+
+## Work #work
+
+Shipped the release.
+"#;
+        let results = TagParser::extract_from_markdown(markdown, Path::new("test.md"), None);
+
+        assert_eq!(section_content(&results, "Health"), "Ran 5k.");
+        assert_eq!(section_content(&results, "Work"), "Shipped the release.");
+    }
+
+    #[test]
+    fn test_heading_inside_inline_html_comment_does_not_end_section() {
+        let markdown = "## Health #health\n\nRan 5k. <!-- old\n## Saturday (January 18, 2025)\n-->\nStretched.\n\n## Work #work\n\nShipped.\n";
+        let results = TagParser::extract_from_markdown(markdown, Path::new("test.md"), None);
+
+        assert_eq!(
+            section_content(&results, "Health"),
+            "Ran 5k. <!-- old\n## Saturday (January 18, 2025)\n-->\nStretched."
+        );
+        assert_eq!(section_content(&results, "Work"), "Shipped.");
+        assert!(results.iter().all(|r| !matches!(
+            &r.context,
+            TagContext::Section { heading, .. } if heading.starts_with("Saturday")
+        )));
+    }
+
+    #[test]
+    fn test_commented_heading_outside_tagged_sections_is_ignored() {
+        let markdown = "# Day\n\nMorning. <!--\n# Evening\n-->\n\n## Work #work\n\nShipped.\n\n<!--\n## Later\n-->\n";
+        let results = TagParser::extract_from_markdown(markdown, Path::new("test.md"), None);
+
+        assert_eq!(
+            section_content(&results, "Work"),
+            "Shipped.\n\n<!--\n## Later\n-->"
+        );
+        match &results[0].context {
+            TagContext::Section { parents, .. } => {
+                assert_eq!(parents, &vec![("Day".to_string(), 1)])
+            }
+            other => panic!("unexpected context {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_setext_heading_section_body() {
+        let markdown =
+            "Health #health\n==============\n\nRan 5k.\n\nWork #work\n----------\n\nShipped.\n";
+        let results = TagParser::extract_from_markdown(markdown, Path::new("test.md"), None);
+
+        assert!(section_content(&results, "Health").starts_with("Ran 5k."));
+        assert_eq!(section_content(&results, "Work"), "Shipped.");
+    }
+
+    #[test]
+    fn test_section_context_records_parent_headings() {
+        let markdown = "# Client A\n\n## Project X #work\n\n### Standup\n\nShipped #urgent\n";
+        let results = TagParser::extract_from_markdown(markdown, Path::new("test.md"), None);
+
+        let paragraph = results
+            .iter()
+            .find(|r| r.tags.contains(&"urgent".to_string()))
+            .unwrap();
+        assert_eq!(
+            paragraph.context,
+            TagContext::Section {
+                heading: "Standup".to_string(),
+                level: 3,
+                parents: vec![("Client A".to_string(), 1), ("Project X".to_string(), 2)],
+            }
+        );
+
+        let section = results
+            .iter()
+            .find(|r| matches!(&r.context, TagContext::Section { heading, .. } if heading == "Project X"))
+            .unwrap();
+        assert!(matches!(
+            &section.context,
+            TagContext::Section { parents, .. } if parents == &vec![("Client A".to_string(), 1)]
+        ));
+    }
+
+    #[test]
+    fn test_comment_only_block_is_invisible() {
+        let markdown = "# Day\n\nNotes\n\n<!-- #draft needs review -->\n\n<!--\n#draft too\n-->\n";
+        let results = TagParser::extract_from_markdown(markdown, Path::new("test.md"), None);
+        assert!(results.is_empty(), "{results:?}");
+
+        // Not even inside a tagged section does the comment become a block of its own
+        let markdown = "## Work #work\n\nShipped.\n\n<!-- #draft -->\n";
+        let results = TagParser::extract_from_markdown(markdown, Path::new("test.md"), None);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].tags, vec!["work"]);
+        assert_eq!(results[0].content, "Shipped.\n\n<!-- #draft -->");
+    }
+
+    #[test]
+    fn test_inline_comment_in_tagged_paragraph_adds_no_tags() {
+        let markdown = "Shipped the release #work <!-- #draft -->\n\nRan 5k <!-- #draft\nstill hidden #secret --> #health\n";
+        let results = TagParser::extract_from_markdown(markdown, Path::new("test.md"), None);
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].tags, vec!["work"]);
+        assert_eq!(
+            results[0].content,
+            "Shipped the release #work <!-- #draft -->"
+        );
+        assert_eq!(results[1].tags, vec!["health"]);
+    }
+
+    #[test]
+    fn test_migrated_weekly_note_has_no_marker_blocks() {
+        let markdown = "# Week 03, 2025\n\n## Tuesday (January 14, 2025)\n\
+            <!-- djour:migrated-from=2025-01-14.md:start demoted=1 -->\n\
+            ### Work #work\n\nShipped.\n\n### Gym #health\n\nRan.\n\
+            <!-- djour:migrated-from=2025-01-14.md:end -->\n\n\n\
+            ## Wednesday (January 15, 2025)\n\
+            <!-- djour:migrated-from=2025-01-15.md:start -->\n\
+            Notes #work\n\
+            <!-- djour:migrated-from=2025-01-15.md:end -->\n";
+        let results = TagParser::extract_from_markdown(markdown, Path::new("test.md"), None);
+
+        let query = crate::domain::tags::TagQuery::parse("health OR work").unwrap();
+        let matched: Vec<&str> = results
+            .iter()
+            .filter(|r| query.matches(&r.tags))
+            .map(|r| r.content.as_str())
+            .collect();
+        assert_eq!(matched, vec!["Shipped.", "Ran.", "Notes #work"]);
+    }
 }
@@ -1,7 +1,8 @@
 //! Tag query parsing and evaluation
 //!
 //! This module implements a boolean query system for filtering tagged content.
-//! Supports AND, OR, and NOT operators with proper precedence.
+//! Supports AND, OR, and NOT operators with proper precedence, plus `dir:` and
+//! `file:` predicates that match where a block's note lives.
 //!
 //! # Examples
 //!
@@ -12,8 +13,19 @@
 //! assert!(query.matches(&vec!["work".to_string(), "urgent".to_string()]));
 //! ```
 
+use super::syntax::{is_valid_tag_name, is_valid_value_tag_name, tag_in_subtree};
 use crate::error::{DjourError, Result};
 use std::collections::HashSet;
+use std::path::{Component, Path};
+
+/// Everything a query can look at when deciding whether a block matches
+#[derive(Debug, Clone, Copy)]
+pub struct MatchContext<'a> {
+    /// Tags of the block (lowercased)
+    pub tags: &'a [String],
+    /// Journal-relative path of the note the block comes from
+    pub source_file: &'a Path,
+}
 
 /// Tag query abstract syntax tree
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -29,6 +41,14 @@ pub enum TagQuery {
 
     /// NOT operation - exclude matches
     Not(Box<TagQuery>),
+
+    /// Note lives in this directory or below it (e.g., "dir:projects/alpha")
+    Dir(String),
+
+    /// Note file name matches this glob; `*` and `?` stop at `/`, and a
+    /// pattern with `/` is matched against the whole relative path
+    /// (e.g., "file:2025-W0*")
+    File(String),
 }
 
 impl TagQuery {
@@ -48,8 +68,18 @@ impl TagQuery {
     /// let query = TagQuery::parse("work AND urgent").unwrap();
     /// assert!(query.matches(&vec!["work".to_string(), "urgent".to_string()]));
     /// ```
+    ///
+    /// # Stability
+    ///
+    /// Part of the supported library API (see the crate docs).
     pub fn parse(query: &str) -> Result<Self> {
-        let tokens = tokenize(query)?;
+        Self::parse_with_value_tags(query, false)
+    }
+
+    /// Parse a query like [`TagQuery::parse`]; with `value_tags`, tags may
+    /// carry a value (`run:5k`). A bare `run` matches every value of `run`.
+    pub fn parse_with_value_tags(query: &str, value_tags: bool) -> Result<Self> {
+        let tokens = tokenize(query, value_tags)?;
         let mut pos = 0;
         let result = parse_or(&tokens, &mut pos)?;
 
@@ -66,6 +96,9 @@ impl TagQuery {
 
     /// Evaluate this query against a set of tags
     ///
+    /// `dir:` and `file:` predicates never match here, since there is no note
+    /// to look at; use [`TagQuery::matches_context`] for those.
+    ///
     /// # Examples
     ///
     /// ```
@@ -75,20 +108,174 @@ impl TagQuery {
     /// assert!(query.matches(&vec!["work".to_string(), "urgent".to_string()]));
     /// assert!(!query.matches(&vec!["work".to_string()]));
     /// ```
+    ///
+    /// # Stability
+    ///
+    /// Part of the supported library API (see the crate docs).
     pub fn matches(&self, tags: &[String]) -> bool {
-        let tag_set: HashSet<&str> = tags.iter().map(|s| s.as_str()).collect();
-        self.matches_set(&tag_set)
+        self.matches_context(&MatchContext {
+            tags,
+            source_file: Path::new(""),
+        })
+    }
+
+    /// Evaluate this query against a block's tags and source note
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use djour::domain::tags::{MatchContext, TagQuery};
+    /// use std::path::Path;
+    ///
+    /// let query = TagQuery::parse("work AND dir:projects/alpha").unwrap();
+    /// let tags = vec!["work".to_string()];
+    /// assert!(query.matches_context(&MatchContext {
+    ///     tags: &tags,
+    ///     source_file: Path::new("projects/alpha/2025-01-15.md"),
+    /// }));
+    /// ```
+    pub fn matches_context(&self, context: &MatchContext<'_>) -> bool {
+        let tag_set: HashSet<&str> = context.tags.iter().map(|s| s.as_str()).collect();
+        self.matches_set(&tag_set, context.source_file)
+    }
+
+    /// Tag names of the query's leaves, negated ones included, in query order
+    ///
+    /// ```
+    /// use djour::domain::tags::TagQuery;
+    ///
+    /// let query = TagQuery::parse("work AND NOT standup OR dir:projects").unwrap();
+    /// assert_eq!(query.tags(), vec!["work", "standup"]);
+    /// ```
+    pub fn tags(&self) -> Vec<&str> {
+        match self {
+            TagQuery::Single(tag) => vec![tag.as_str()],
+            TagQuery::And(left, right) | TagQuery::Or(left, right) => {
+                let mut tags = left.tags();
+                tags.extend(right.tags());
+                tags
+            }
+            TagQuery::Not(inner) => inner.tags(),
+            TagQuery::Dir(_) | TagQuery::File(_) => Vec::new(),
+        }
+    }
+
+    /// Whether the query requires something rather than only excluding things
+    ///
+    /// A tag, `dir:` or `file:` leaf counts when it sits under an even number
+    /// of NOTs. Without one (`NOT a`, `NOT a OR NOT b`) the query matches every
+    /// block except a few.
+    ///
+    /// ```
+    /// use djour::domain::tags::TagQuery;
+    ///
+    /// assert!(TagQuery::parse("work AND NOT meeting").unwrap().has_positive_atom());
+    /// assert!(!TagQuery::parse("NOT meeting").unwrap().has_positive_atom());
+    /// ```
+    pub fn has_positive_atom(&self) -> bool {
+        self.has_atom(true)
+    }
+
+    /// Fail for a query that matches by exclusion only (see [`TagQuery::has_positive_atom`])
+    pub fn require_positive_atom(&self) -> Result<()> {
+        if self.has_positive_atom() {
+            Ok(())
+        } else {
+            Err(DjourError::Config(
+                "Query matches by exclusion only; add at least one tag, or pass --allow-exclusion-only"
+                    .to_string(),
+            ))
+        }
+    }
+
+    /// Whether a leaf sits under an even (`positive`) or odd number of NOTs
+    fn has_atom(&self, positive: bool) -> bool {
+        match self {
+            TagQuery::Single(_) | TagQuery::Dir(_) | TagQuery::File(_) => positive,
+            TagQuery::And(left, right) | TagQuery::Or(left, right) => {
+                left.has_atom(positive) || right.has_atom(positive)
+            }
+            TagQuery::Not(inner) => inner.has_atom(!positive),
+        }
     }
 
     /// Internal evaluation using HashSet for efficiency
-    fn matches_set(&self, tags: &HashSet<&str>) -> bool {
+    fn matches_set(&self, tags: &HashSet<&str>, source_file: &Path) -> bool {
         match self {
-            TagQuery::Single(tag) => tags.contains(tag.as_str()),
-            TagQuery::And(left, right) => left.matches_set(tags) && right.matches_set(tags),
-            TagQuery::Or(left, right) => left.matches_set(tags) || right.matches_set(tags),
-            TagQuery::Not(inner) => !inner.matches_set(tags),
+            TagQuery::Single(tag) => tags.iter().any(|candidate| tag_in_subtree(candidate, tag)),
+            TagQuery::And(left, right) => {
+                left.matches_set(tags, source_file) && right.matches_set(tags, source_file)
+            }
+            TagQuery::Or(left, right) => {
+                left.matches_set(tags, source_file) || right.matches_set(tags, source_file)
+            }
+            TagQuery::Not(inner) => !inner.matches_set(tags, source_file),
+            TagQuery::Dir(prefix) => in_directory(source_file, prefix),
+            TagQuery::File(pattern) => file_matches(source_file, pattern),
+        }
+    }
+}
+
+/// `/`-separated normal components of a path (`.` and empty segments dropped)
+fn path_segments(path: &Path) -> Vec<String> {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy().into_owned()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Whether `source_file` lies inside directory `prefix` (whole segments only)
+fn in_directory(source_file: &Path, prefix: &str) -> bool {
+    let prefix = path_segments(Path::new(&prefix.replace('\\', "/")));
+    let segments = path_segments(source_file);
+    // The last segment is the file name, not a directory
+    segments.len() > prefix.len() && segments.starts_with(&prefix)
+}
+
+/// Whether the note's file name (or, for patterns with `/`, its relative path) matches `pattern`
+fn file_matches(source_file: &Path, pattern: &str) -> bool {
+    let segments = path_segments(source_file);
+    if pattern.contains('/') {
+        glob_match(pattern.trim_start_matches("./"), &segments.join("/"))
+    } else {
+        segments
+            .last()
+            .is_some_and(|name| glob_match(pattern, name))
+    }
+}
+
+/// Match `text` against a glob with `*` (any run) and `?` (one character); neither crosses `/`
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position after the last `*` and the text position it was tried at
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == '*' {
+            p += 1;
+            backtrack = Some((p, t));
+        } else if p < pattern.len()
+            && (pattern[p] == text[t] || (pattern[p] == '?' && text[t] != '/'))
+        {
+            p += 1;
+            t += 1;
+        } else {
+            match backtrack {
+                Some((star_p, star_t)) if text[star_t] != '/' => {
+                    p = star_p;
+                    t = star_t + 1;
+                    backtrack = Some((star_p, star_t + 1));
+                }
+                _ => return false,
+            }
         }
     }
+
+    pattern[p..].iter().all(|c| *c == '*')
 }
 
 impl std::fmt::Display for TagQuery {
@@ -98,21 +285,31 @@ impl std::fmt::Display for TagQuery {
             TagQuery::And(left, right) => write!(f, "{} AND {}", left, right),
             TagQuery::Or(left, right) => write!(f, "({} OR {})", left, right),
             TagQuery::Not(inner) => write!(f, "NOT {}", inner),
+            TagQuery::Dir(prefix) => write!(f, "{}{}", DIR_PREFIX, prefix),
+            TagQuery::File(pattern) => write!(f, "{}{}", FILE_PREFIX, pattern),
         }
     }
 }
 
+/// Query word prefix of a directory predicate
+const DIR_PREFIX: &str = "dir:";
+
+/// Query word prefix of a file name predicate
+const FILE_PREFIX: &str = "file:";
+
 /// Token types for query parsing
 #[derive(Debug, Clone, PartialEq, Eq)]
 enum Token {
     Tag(String),
+    Dir(String),
+    File(String),
     And,
     Or,
     Not,
 }
 
 /// Tokenize a query string
-fn tokenize(query: &str) -> Result<Vec<Token>> {
+fn tokenize(query: &str, value_tags: bool) -> Result<Vec<Token>> {
     let mut tokens = Vec::new();
     let words: Vec<&str> = query.split_whitespace().collect();
 
@@ -121,17 +318,36 @@ fn tokenize(query: &str) -> Result<Vec<Token>> {
             "AND" => tokens.push(Token::And),
             "OR" => tokens.push(Token::Or),
             "NOT" => tokens.push(Token::Not),
+            upper if upper.starts_with("DIR:") || upper.starts_with("FILE:") => {
+                // ':' is only valid here; the value keeps its case, like the path it matches
+                let (prefix, value) = word.split_once(':').unwrap_or((word, ""));
+                let value = value.replace('\\', "/");
+                let value = value.trim_end_matches('/');
+                if value.is_empty() {
+                    return Err(DjourError::Config(format!(
+                        "Missing value in query predicate: {}",
+                        word
+                    )));
+                }
+                if prefix.eq_ignore_ascii_case("dir") {
+                    tokens.push(Token::Dir(value.to_string()));
+                } else {
+                    tokens.push(Token::File(value.to_string()));
+                }
+            }
             _ => {
                 // Remove leading # if present
                 let tag = word.strip_prefix('#').unwrap_or(word);
                 if tag.is_empty() {
                     return Err(DjourError::Config("Invalid tag in query".to_string()));
                 }
-                // Validate tag characters (alphanumeric, hyphens, underscores)
-                if !tag
-                    .chars()
-                    .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
-                {
+                // Validate tag characters (alphanumeric, hyphens, underscores, `/` between segments)
+                let valid = if value_tags {
+                    is_valid_value_tag_name(tag)
+                } else {
+                    is_valid_tag_name(tag)
+                };
+                if !valid {
                     return Err(DjourError::Config(format!("Invalid tag: {}", tag)));
                 }
                 tokens.push(Token::Tag(tag.to_lowercase()));
@@ -206,6 +422,14 @@ fn parse_primary(tokens: &[Token], pos: &mut usize) -> Result<TagQuery> {
             *pos += 1;
             Ok(TagQuery::Single(tag.clone()))
         }
+        Token::Dir(prefix) => {
+            *pos += 1;
+            Ok(TagQuery::Dir(prefix.clone()))
+        }
+        Token::File(pattern) => {
+            *pos += 1;
+            Ok(TagQuery::File(pattern.clone()))
+        }
         _ => Err(DjourError::Config(format!(
             "Expected tag, found {:?}",
             tokens[*pos]
@@ -423,4 +647,183 @@ mod tests {
         assert!(query.matches(&vec!["work".to_string()]));
         assert!(!query.matches(&vec!["other".to_string()]));
     }
+
+    #[test]
+    fn test_has_positive_atom() {
+        let positive = |query: &str| TagQuery::parse(query).unwrap().has_positive_atom();
+        assert!(!positive("NOT meeting"));
+        assert!(!positive("NOT meeting OR NOT standup"));
+        assert!(!positive("NOT dir:archive"));
+        assert!(positive("work AND NOT meeting"));
+        assert!(positive("NOT meeting OR work"));
+        assert!(positive("NOT NOT work"));
+        assert!(positive("dir:projects"));
+
+        // NOT (a AND b), built directly as the parser has no grouping
+        let grouped = TagQuery::Not(Box::new(TagQuery::And(
+            Box::new(TagQuery::Single("a".to_string())),
+            Box::new(TagQuery::Single("b".to_string())),
+        )));
+        assert!(!grouped.has_positive_atom());
+        let err = grouped.require_positive_atom().unwrap_err().to_string();
+        assert!(
+            err.contains("Query matches by exclusion only; add at least one tag, or pass --allow-exclusion-only"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_parent_tag_matches_subtree() {
+        let query = TagQuery::parse("project").unwrap();
+        assert!(query.matches(&vec!["project".to_string()]));
+        assert!(query.matches(&vec!["project/alpha/backend".to_string()]));
+        assert!(!query.matches(&vec!["projects".to_string()]));
+
+        let query = TagQuery::parse("#project/alpha AND NOT project/alpha/backend").unwrap();
+        assert!(query.matches(&vec!["project/alpha/frontend".to_string()]));
+        assert!(!query.matches(&vec!["project/alpha/backend".to_string()]));
+        assert!(!query.matches(&vec!["project/beta".to_string()]));
+    }
+
+    #[test]
+    fn test_prefix_does_not_match_across_segments() {
+        let query = TagQuery::parse("pro").unwrap();
+        assert!(!query.matches(&vec!["project".to_string()]));
+        assert!(!query.matches(&vec!["project/alpha".to_string()]));
+    }
+
+    #[test]
+    fn test_invalid_hierarchical_tags() {
+        assert!(TagQuery::parse("/project").is_err());
+        assert!(TagQuery::parse("project/").is_err());
+        assert!(TagQuery::parse("project//alpha").is_err());
+    }
+
+    #[test]
+    fn test_value_tags_match_by_name_and_exact_value() {
+        let tags = |list: &[&str]| -> Vec<String> { list.iter().map(|t| t.to_string()).collect() };
+        assert!(TagQuery::parse("run:5k").is_err());
+
+        let any_run = TagQuery::parse_with_value_tags("run", true).unwrap();
+        assert!(any_run.matches(&tags(&["run:5k"])));
+        assert!(any_run.matches(&tags(&["run:10k"])));
+        assert!(any_run.matches(&tags(&["run"])));
+        assert!(!any_run.matches(&tags(&["running:5k"])));
+
+        let five = TagQuery::parse_with_value_tags("#Run:5K AND NOT mood:bad", true).unwrap();
+        assert!(five.matches(&tags(&["run:5k", "mood:good"])));
+        assert!(!five.matches(&tags(&["run:10k"])));
+        assert!(!five.matches(&tags(&["run"])));
+        assert!(!five.matches(&tags(&["run:5k", "mood:bad"])));
+
+        assert!(TagQuery::parse_with_value_tags("run:", true).is_err());
+        assert!(TagQuery::parse_with_value_tags("dir:runs", true).is_ok());
+    }
+
+    fn in_note(query: &TagQuery, tags: &[&str], source_file: &str) -> bool {
+        let tags: Vec<String> = tags.iter().map(|t| t.to_string()).collect();
+        query.matches_context(&MatchContext {
+            tags: &tags,
+            source_file: Path::new(source_file),
+        })
+    }
+
+    #[test]
+    fn test_parse_path_predicates() {
+        let query = TagQuery::parse("work AND DIR:projects/alpha/ OR file:2025-W0*").unwrap();
+        assert_eq!(
+            query,
+            TagQuery::Or(
+                Box::new(TagQuery::And(
+                    Box::new(TagQuery::Single("work".to_string())),
+                    Box::new(TagQuery::Dir("projects/alpha".to_string()))
+                )),
+                Box::new(TagQuery::File("2025-W0*".to_string()))
+            )
+        );
+        assert!(TagQuery::parse("dir:").is_err());
+        assert!(TagQuery::parse("work AND file:").is_err());
+        // ':' stays invalid in plain tags
+        assert!(TagQuery::parse("work:urgent").is_err());
+    }
+
+    #[test]
+    fn test_to_string_path_predicates_round_trip() {
+        for text in [
+            "#work AND dir:projects/alpha",
+            "NOT file:2025-W0*",
+            "#retro AND file:notes/*.md",
+        ] {
+            let query = TagQuery::parse(text).unwrap();
+            assert_eq!(query.to_string(), text);
+            assert_eq!(TagQuery::parse(&query.to_string()).unwrap(), query);
+        }
+    }
+
+    #[test]
+    fn test_dir_predicate_matches_whole_segments() {
+        let query = TagQuery::parse("work AND dir:projects/alpha").unwrap();
+        assert!(in_note(&query, &["work"], "projects/alpha/2025-01-15.md"));
+        assert!(in_note(
+            &query,
+            &["work"],
+            "projects/alpha/deep/2025-01-15.md"
+        ));
+        assert!(!in_note(
+            &query,
+            &["work"],
+            "projects/alphabet/2025-01-15.md"
+        ));
+        assert!(!in_note(&query, &["work"], "projects/alpha.md"));
+        assert!(!in_note(&query, &["life"], "projects/alpha/2025-01-15.md"));
+    }
+
+    #[test]
+    fn test_dir_predicate_with_not_and_nested_tags() {
+        let query = TagQuery::parse("project AND NOT dir:archive").unwrap();
+        assert!(in_note(&query, &["project/alpha"], "2025-01-15.md"));
+        assert!(in_note(
+            &query,
+            &["project/alpha"],
+            "archived/2025-01-15.md"
+        ));
+        assert!(!in_note(
+            &query,
+            &["project/alpha"],
+            "archive/2024/2024-01-15.md"
+        ));
+        assert!(!in_note(&query, &["other"], "2025-01-15.md"));
+    }
+
+    #[test]
+    fn test_file_predicate_globs() {
+        let query = TagQuery::parse("retro AND file:2025-W0*").unwrap();
+        assert!(in_note(&query, &["retro"], "2025-W03-2025-01-13.md"));
+        assert!(in_note(&query, &["retro"], "teams/2025-W03-2025-01-13.md"));
+        assert!(!in_note(&query, &["retro"], "2025-W13-2025-03-24.md"));
+
+        let query = TagQuery::parse("file:teams/*-0?-*.md").unwrap();
+        assert!(in_note(&query, &[], "teams/2025-01-15.md"));
+        assert!(!in_note(&query, &[], "teams/sub/2025-01-15.md"));
+        assert!(!in_note(&query, &[], "2025-01-15.md"));
+    }
+
+    #[test]
+    fn test_path_predicates_never_match_without_a_note() {
+        let query = TagQuery::parse("work AND dir:projects").unwrap();
+        assert!(!query.matches(&vec!["work".to_string()]));
+        let query = TagQuery::parse("work AND NOT file:*.md").unwrap();
+        assert!(query.matches(&vec!["work".to_string()]));
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*", "anything.md"));
+        assert!(glob_match("a*b*c", "axxbyyc"));
+        assert!(glob_match("?.md", "a.md"));
+        assert!(!glob_match("*", "a/b"));
+        assert!(!glob_match("a?c", "a/c"));
+        assert!(!glob_match("*.md", "notes.txt"));
+    }
 }
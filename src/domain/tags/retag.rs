@@ -1,15 +1,11 @@
 //! Markdown tag replacement helpers.
 
-use pulldown_cmark::{Event, Parser as MdParser, Tag, TagEnd};
+use super::syntax::{rename_in_subtree, tag_name, tag_scan_regex};
+use crate::domain::markdown::code_ranges;
 use regex::Regex;
-use std::ops::Range;
+use std::collections::BTreeMap;
 use std::sync::OnceLock;
 
-fn markdown_tag_regex() -> &'static Regex {
-    static REGEX: OnceLock<Regex> = OnceLock::new();
-    REGEX.get_or_init(|| Regex::new(r"#([a-zA-Z0-9_-]+)").unwrap())
-}
-
 /// Result of a tag replacement operation.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RetagResult {
@@ -18,15 +14,56 @@ pub struct RetagResult {
 }
 
 /// Replace a tag in markdown while skipping fenced code blocks and inline code spans.
-pub fn retag_markdown(markdown: &str, from_tag: &str, to_tag: &str) -> RetagResult {
-    if markdown.is_empty() || from_tag.eq_ignore_ascii_case(to_tag) {
+///
+/// Nested tags are renamed with their parent: `project` -> `work` turns
+/// `#project/alpha` into `#work/alpha`. With `value_tags`, so are values:
+/// `run` -> `jog` turns `#run:5k` into `#jog:5k`, while `run:5k` -> `run:10k`
+/// renames just that value.
+pub fn retag_markdown(
+    markdown: &str,
+    from_tag: &str,
+    to_tag: &str,
+    value_tags: bool,
+) -> RetagResult {
+    if from_tag.eq_ignore_ascii_case(to_tag) {
         return RetagResult {
             content: markdown.to_string(),
             replacements: 0,
         };
     }
 
-    let excluded = excluded_ranges(markdown);
+    retag_markdown_with(markdown, value_tags, |tag| {
+        rename_in_subtree(tag, from_tag, to_tag)
+    })
+}
+
+/// Apply several exact renames in a single pass over the markdown.
+///
+/// Keys of `renames` are lowercased full tag names; unlike [`retag_markdown`],
+/// nested tags are only renamed when listed themselves.
+pub fn retag_markdown_many(
+    markdown: &str,
+    renames: &BTreeMap<String, String>,
+    value_tags: bool,
+) -> RetagResult {
+    retag_markdown_with(markdown, value_tags, |tag| {
+        renames.get(&tag.to_lowercase()).cloned()
+    })
+}
+
+fn retag_markdown_with<F>(markdown: &str, value_tags: bool, rename: F) -> RetagResult
+where
+    F: Fn(&str) -> Option<String>,
+{
+    if markdown.is_empty() {
+        return RetagResult {
+            content: markdown.to_string(),
+            replacements: 0,
+        };
+    }
+
+    let tag_re = tag_scan_regex(true, false, value_tags);
+    let excluded = code_ranges(markdown);
     let mut replacements = 0usize;
     let mut rewritten = String::with_capacity(markdown.len());
     let mut cursor = 0usize;
@@ -34,7 +71,7 @@ pub fn retag_markdown(markdown: &str, from_tag: &str, to_tag: &str) -> RetagResu
     for range in excluded {
         if range.start > cursor {
             let chunk = &markdown[cursor..range.start];
-            rewritten.push_str(&retag_chunk(chunk, from_tag, to_tag, &mut replacements));
+            rewritten.push_str(&retag_chunk(chunk, tag_re, &rename, &mut replacements));
         }
 
         rewritten.push_str(&markdown[range.start..range.end]);
@@ -43,7 +80,7 @@ pub fn retag_markdown(markdown: &str, from_tag: &str, to_tag: &str) -> RetagResu
 
     if cursor < markdown.len() {
         let chunk = &markdown[cursor..];
-        rewritten.push_str(&retag_chunk(chunk, from_tag, to_tag, &mut replacements));
+        rewritten.push_str(&retag_chunk(chunk, tag_re, &rename, &mut replacements));
     }
 
     RetagResult {
@@ -52,71 +89,94 @@ pub fn retag_markdown(markdown: &str, from_tag: &str, to_tag: &str) -> RetagResu
     }
 }
 
-fn retag_chunk(chunk: &str, from_tag: &str, to_tag: &str, replacements: &mut usize) -> String {
-    markdown_tag_regex()
-        .replace_all(chunk, |captures: &regex::Captures<'_>| {
-            let matched_tag = &captures[1];
-            if matched_tag.eq_ignore_ascii_case(from_tag) {
-                *replacements += 1;
-                format!("#{}", to_tag)
-            } else {
-                captures[0].to_string()
-            }
-        })
-        .to_string()
+/// Replace whole-word occurrences of a tag inside a tag query string.
+///
+/// Both `work` and `#work` forms are recognized; the `#` prefix is kept as written.
+/// Nested tags below `from_tag` are renamed as well.
+pub fn retag_query(query: &str, from_tag: &str, to_tag: &str) -> RetagResult {
+    if from_tag.eq_ignore_ascii_case(to_tag) {
+        return RetagResult {
+            content: query.to_string(),
+            replacements: 0,
+        };
+    }
+
+    retag_query_with(query, |tag| rename_in_subtree(tag, from_tag, to_tag))
 }
 
-fn excluded_ranges(markdown: &str) -> Vec<Range<usize>> {
-    let mut ranges: Vec<Range<usize>> = Vec::new();
-    let mut code_block_start: Option<usize> = None;
+/// Apply several exact renames (keyed by lowercased tag) to a tag query string.
+pub fn retag_query_many(query: &str, renames: &BTreeMap<String, String>) -> RetagResult {
+    retag_query_with(query, |tag| renames.get(&tag.to_lowercase()).cloned())
+}
 
-    for (event, range) in MdParser::new(markdown).into_offset_iter() {
-        match event {
-            Event::Start(Tag::CodeBlock(_)) => {
-                code_block_start = Some(range.start);
+fn retag_query_with<F>(query: &str, rename: F) -> RetagResult
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut replacements = 0usize;
+    let content = query_word_regex()
+        .replace_all(query, |captures: &regex::Captures<'_>| {
+            if in_path_predicate(query, captures.get(0).map_or(0, |m| m.start())) {
+                return captures[0].to_string();
             }
-            Event::End(TagEnd::CodeBlock) => {
-                if let Some(start) = code_block_start.take() {
-                    ranges.push(start..range.end);
+            match rename(&captures[2]) {
+                Some(renamed) => {
+                    replacements += 1;
+                    format!("{}{}", &captures[1], renamed)
                 }
+                None => captures[0].to_string(),
             }
-            Event::Code(_) => {
-                ranges.push(range.start..range.end);
-            }
-            _ => {}
-        }
-    }
+        })
+        .to_string();
 
-    if let Some(start) = code_block_start.take() {
-        ranges.push(start..markdown.len());
+    RetagResult {
+        content,
+        replacements,
     }
-
-    merge_ranges(ranges)
 }
 
-fn merge_ranges(mut ranges: Vec<Range<usize>>) -> Vec<Range<usize>> {
-    if ranges.is_empty() {
-        return ranges;
-    }
+/// Whether byte `offset` of a query falls in a `dir:` or `file:` word, which names paths, not tags
+fn in_path_predicate(query: &str, offset: usize) -> bool {
+    let word_start = query[..offset]
+        .char_indices()
+        .rev()
+        .find(|(_, c)| c.is_whitespace())
+        .map_or(0, |(i, c)| i + c.len_utf8());
+    let word = &query.as_bytes()[word_start..];
+    let has_prefix = |prefix: &[u8]| {
+        word.get(..prefix.len())
+            .is_some_and(|head| head.eq_ignore_ascii_case(prefix))
+    };
+    has_prefix(b"dir:") || has_prefix(b"file:")
+}
 
-    ranges.sort_by(|a, b| a.start.cmp(&b.start).then(a.end.cmp(&b.end)));
+fn query_word_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    // Takes a `:value` along, so value tags are renamed whole; queries only
+    // hold other `:` words in `dir:` and `file:` predicates, which are skipped
+    REGEX.get_or_init(|| {
+        Regex::new(r"(#?)([a-zA-Z0-9_-]+(?:/[a-zA-Z0-9_-]+)*(?::[a-zA-Z0-9_.-]*[a-zA-Z0-9_-])?)")
+            .unwrap()
+    })
+}
 
-    let mut merged: Vec<Range<usize>> = Vec::with_capacity(ranges.len());
-    for range in ranges {
-        if let Some(last) = merged.last_mut() {
-            if range.start <= last.end {
-                if range.end > last.end {
-                    last.end = range.end;
+fn retag_chunk<F>(chunk: &str, tag_re: &Regex, rename: &F, replacements: &mut usize) -> String
+where
+    F: Fn(&str) -> Option<String>,
+{
+    tag_re
+        .replace_all(chunk, |captures: &regex::Captures<'_>| {
+            let name = tag_name(&captures[1]);
+            // The whole match is replaced, so a `＃` or unicode hyphen becomes ASCII
+            match rename(&name) {
+                Some(renamed) => {
+                    *replacements += 1;
+                    format!("#{}", renamed)
                 }
-            } else {
-                merged.push(range);
+                None => captures[0].to_string(),
             }
-        } else {
-            merged.push(range);
-        }
-    }
-
-    merged
+        })
+        .to_string()
 }
 
 #[cfg(test)]
@@ -126,7 +186,7 @@ mod tests {
     #[test]
     fn replaces_matching_tags_case_insensitively() {
         let input = "One #work, two #WORK, keep #workshop.";
-        let result = retag_markdown(input, "work", "project");
+        let result = retag_markdown(input, "work", "project", false);
 
         assert_eq!(
             result.content,
@@ -135,10 +195,28 @@ mod tests {
         assert_eq!(result.replacements, 2);
     }
 
+    #[test]
+    fn renames_fullwidth_and_unicode_hyphen_tags() {
+        let input = "Ünïcode \u{FF03}work, #Work\u{2011}item and \u{FF03}workshop.";
+        let result = retag_markdown(input, "work", "job", false);
+        assert_eq!(
+            result.content,
+            "Ünïcode #job, #Work\u{2011}item and \u{FF03}workshop."
+        );
+        assert_eq!(result.replacements, 1);
+
+        let result = retag_markdown(input, "work-item", "task", false);
+        assert_eq!(
+            result.content,
+            "Ünïcode \u{FF03}work, #task and \u{FF03}workshop."
+        );
+        assert_eq!(result.replacements, 1);
+    }
+
     #[test]
     fn preserves_duplicate_tags() {
         let input = "#work #work #work";
-        let result = retag_markdown(input, "work", "focus");
+        let result = retag_markdown(input, "work", "focus", false);
 
         assert_eq!(result.content, "#focus #focus #focus");
         assert_eq!(result.replacements, 3);
@@ -154,7 +232,7 @@ Outside #work
 ```
 "#;
 
-        let result = retag_markdown(input, "work", "focus");
+        let result = retag_markdown(input, "work", "focus", false);
         assert!(result.content.contains("Outside #focus"));
         assert!(result.content.contains("// #work"));
         assert_eq!(result.replacements, 1);
@@ -163,18 +241,153 @@ Outside #work
     #[test]
     fn skips_inline_code_spans() {
         let input = "Use `#work` here, but change #work.";
-        let result = retag_markdown(input, "work", "focus");
+        let result = retag_markdown(input, "work", "focus", false);
 
         assert_eq!(result.content, "Use `#work` here, but change #focus.");
         assert_eq!(result.replacements, 1);
     }
 
+    #[test]
+    fn keeps_final_newline_or_its_absence() {
+        for input in [
+            "Last line #work\n",
+            "Last line #work",
+            "#work\r\n",
+            "```\n#work",
+        ] {
+            let result = retag_markdown(input, "work", "focus", false);
+            assert_eq!(
+                result.content.ends_with('\n'),
+                input.ends_with('\n'),
+                "{:?}",
+                result.content
+            );
+        }
+        assert_eq!(
+            retag_markdown("Last line #work", "work", "focus", false).content,
+            "Last line #focus"
+        );
+    }
+
+    #[test]
+    fn retag_query_replaces_whole_words_only() {
+        let result = retag_query("work AND #Work OR workshop", "work", "job");
+
+        assert_eq!(result.content, "job AND #job OR workshop");
+        assert_eq!(result.replacements, 2);
+    }
+
+    #[test]
+    fn retag_query_leaves_operators_alone() {
+        let result = retag_query("personal AND NOT meeting", "work", "job");
+
+        assert_eq!(result.content, "personal AND NOT meeting");
+        assert_eq!(result.replacements, 0);
+    }
+
     #[test]
     fn no_op_when_tags_identical() {
         let input = "Keep #work unchanged.";
-        let result = retag_markdown(input, "work", "work");
+        let result = retag_markdown(input, "work", "work", false);
 
         assert_eq!(result.content, input);
         assert_eq!(result.replacements, 0);
     }
+
+    #[test]
+    fn renames_nested_tags_with_their_parent() {
+        let input = "#project #project/alpha #Project/alpha/backend #projects #pro";
+        let result = retag_markdown(input, "project", "work", false);
+
+        assert_eq!(
+            result.content,
+            "#work #work/alpha #work/alpha/backend #projects #pro"
+        );
+        assert_eq!(result.replacements, 3);
+
+        let result = retag_markdown(input, "project/alpha", "project/omega", false);
+        assert_eq!(
+            result.content,
+            "#project #project/omega #project/omega/backend #projects #pro"
+        );
+        assert_eq!(result.replacements, 2);
+    }
+
+    #[test]
+    fn retag_query_renames_subtrees() {
+        let result = retag_query(
+            "project/alpha OR #project AND NOT projects",
+            "project",
+            "work",
+        );
+
+        assert_eq!(result.content, "work/alpha OR #work AND NOT projects");
+        assert_eq!(result.replacements, 2);
+    }
+
+    #[test]
+    fn retag_query_leaves_path_predicates_alone() {
+        let result = retag_query(
+            "project AND dir:project/alpha AND file:project*",
+            "project",
+            "work",
+        );
+
+        assert_eq!(
+            result.content,
+            "work AND dir:project/alpha AND file:project*"
+        );
+        assert_eq!(result.replacements, 1);
+    }
+
+    #[test]
+    fn retag_query_handles_no_break_spaces() {
+        let result = retag_query("a\u{00A0}job AND\u{00A0}DIR:job", "job", "task");
+
+        assert_eq!(result.content, "a\u{00A0}task AND\u{00A0}DIR:job");
+        assert_eq!(result.replacements, 1);
+    }
+
+    #[test]
+    fn retags_value_tags_by_name_or_single_value() {
+        let input = "#run:5k, #run:10k and #run. `#run:5k`";
+        let whole = retag_markdown(input, "run", "jog", true);
+        assert_eq!(whole.content, "#jog:5k, #jog:10k and #jog. `#run:5k`");
+        assert_eq!(whole.replacements, 3);
+
+        let one = retag_markdown(input, "run:5k", "run:6k", true);
+        assert_eq!(one.content, "#run:6k, #run:10k and #run. `#run:5k`");
+        assert_eq!(one.replacements, 1);
+
+        // Without value tags the value is plain text after the tag
+        let off = retag_markdown_many(
+            input,
+            &BTreeMap::from([("run".to_string(), "jog".to_string())]),
+            false,
+        );
+        assert_eq!(off.content, "#jog:5k, #jog:10k and #jog. `#run:5k`");
+
+        let query = retag_query("run:5k OR run:10k", "run:5k", "run:6k");
+        assert_eq!(query.content, "run:6k OR run:10k");
+    }
+
+    #[test]
+    fn applies_many_exact_renames_in_one_pass() {
+        let renames = BTreeMap::from([
+            ("one_on_one".to_string(), "one-on-one".to_string()),
+            ("oneonone".to_string(), "one-on-one".to_string()),
+        ]);
+        let input = "#One_On_One and #oneonone, not #oneonone/child or `#one_on_one`";
+        let result = retag_markdown_many(input, &renames, false);
+
+        assert_eq!(
+            result.content,
+            "#one-on-one and #one-on-one, not #oneonone/child or `#one_on_one`"
+        );
+        assert_eq!(result.replacements, 2);
+
+        let query = retag_query_many("oneonone OR #one_on_one/x", &renames);
+        assert_eq!(query.content, "one-on-one OR #one_on_one/x");
+        assert_eq!(query.replacements, 1);
+    }
 }
@@ -0,0 +1,221 @@
+//! Finding the tags written in a note without parsing it into blocks
+//!
+//! `djour tags`, tag footers and the TUI only need which tags a note uses, so
+//! they share this scan instead of running the full [`TagParser`](super::TagParser).
+//! It skips what the parser skips: the tag footer, HTML comments, code blocks,
+//! inline code and wiki links.
+
+use super::footer::strip_tag_footer;
+use super::front_matter::parse_front_matter;
+use super::parser::TagParseOptions;
+use super::syntax::{tag_name, tag_scan_regex};
+use crate::domain::markdown::{html_comment_regions, in_regions};
+use pulldown_cmark::{Event, Parser as MdParser, Tag, TagEnd};
+use regex::Regex;
+use std::ops::Range;
+
+/// Every tag occurrence in `content`, lowercased, in document order: tags of
+/// the front matter first (with `frontmatter_tags`), then those of the body.
+///
+/// A tag-only paragraph right above a list tags each of its items, as compile
+/// emits them, so its tags occur once per item.
+pub fn tags_in_note(content: &str, options: TagParseOptions) -> Vec<String> {
+    let content = strip_tag_footer(content);
+    let mut tags = Vec::new();
+    let body = match parse_front_matter(&content) {
+        Some(front_matter) => {
+            if options.frontmatter_tags {
+                tags.extend(front_matter.tags);
+            }
+            &content[front_matter.len..]
+        }
+        None => &content,
+    };
+
+    let tag_re = tag_scan_regex(
+        options.hierarchical_tags,
+        options.wikilinks,
+        options.value_tags,
+    );
+    let comments = html_comment_regions(body);
+    let blocks = scan_blocks(body, tag_re);
+    for (found, tag) in tag_matches(body, tag_re) {
+        let start = found.start;
+        if in_regions(&comments, start) || in_regions(&blocks.code, start) {
+            continue;
+        }
+        let repeat = blocks
+            .intros
+            .iter()
+            .find(|(paragraph, _)| paragraph.contains(&start))
+            .map_or(1, |(_, items)| (*items).max(1));
+        tags.extend(std::iter::repeat_n(tag, repeat));
+    }
+    tags
+}
+
+/// Byte range and lowercased name of every tag `tag_re` finds in `text`
+fn tag_matches<'a>(
+    text: &'a str,
+    tag_re: &'a Regex,
+) -> impl Iterator<Item = (Range<usize>, String)> + 'a {
+    tag_re.captures_iter(text).filter_map(move |captures| {
+        let (found, tag) = (captures.get(0)?, captures.get(1)?);
+        Some((found.range(), tag_name(tag.as_str()).to_lowercase()))
+    })
+}
+
+/// Whether `text` holds tags and nothing else but whitespace
+fn only_tags(text: &str, tag_re: &Regex) -> bool {
+    let mut last = 0;
+    let mut any = false;
+    for (found, _) in tag_matches(text, tag_re) {
+        if !text[last..found.start].trim().is_empty() {
+            return false;
+        }
+        last = found.end;
+        any = true;
+    }
+    any && text[last..].trim().is_empty()
+}
+
+/// What one markdown pass over a note's body tells the tag scan
+#[derive(Default)]
+struct Blocks {
+    /// Code blocks and inline code spans
+    code: Vec<Range<usize>>,
+    /// Tag-only paragraphs introducing a list, with the number of its items
+    /// (at any depth) that have text of their own
+    intros: Vec<(Range<usize>, usize)>,
+}
+
+/// Find code and list intros in `body`. A tag-only paragraph introduces the
+/// list right after it, with nothing but blank lines in between; any other
+/// block in between breaks the link, as in the tag parser.
+fn scan_blocks(body: &str, tag_re: &Regex) -> Blocks {
+    let mut blocks = Blocks::default();
+    let mut code_start = None;
+    // The paragraph being read and its text, when outside list items
+    let mut paragraph: Option<(usize, String)> = None;
+    // A tag-only paragraph waiting for the next event to be a list
+    let mut pending: Option<Range<usize>> = None;
+    // Index in `intros` of the top-level list being read, if it has one
+    let mut active: Option<usize> = None;
+    // Whether each open list item has text of its own so far
+    let mut items: Vec<bool> = Vec::new();
+
+    for (event, range) in MdParser::new(body).into_offset_iter() {
+        let intro = pending.take();
+        match &event {
+            Event::Start(Tag::List(_)) if items.is_empty() => {
+                active = intro.map(|paragraph| {
+                    blocks.intros.push((paragraph, 0));
+                    blocks.intros.len() - 1
+                });
+            }
+            Event::End(TagEnd::List(_)) if items.is_empty() => active = None,
+            Event::Start(Tag::Item) => items.push(false),
+            Event::End(TagEnd::Item) => {
+                if let (Some(true), Some(index)) = (items.pop(), active) {
+                    blocks.intros[index].1 += 1;
+                }
+            }
+            Event::Start(Tag::Paragraph) if items.is_empty() => {
+                paragraph = Some((range.start, String::new()));
+            }
+            Event::End(TagEnd::Paragraph) => {
+                if let Some((start, text)) = paragraph.take() {
+                    if only_tags(&text, tag_re) {
+                        pending = Some(start..range.end);
+                    }
+                }
+            }
+            Event::Start(Tag::CodeBlock(_)) => code_start = Some(range.start),
+            Event::End(TagEnd::CodeBlock) => {
+                if let Some(start) = code_start.take() {
+                    blocks.code.push(start..range.end);
+                }
+            }
+            Event::Code(_) => blocks.code.push(range.clone()),
+            _ => {}
+        }
+
+        if let Event::Text(text) | Event::Code(text) = &event {
+            if let Some((_, paragraph)) = paragraph.as_mut() {
+                paragraph.push_str(text);
+            }
+            if let Some(has_text) = items.last_mut() {
+                *has_text |= !text.trim().is_empty() && !only_tags(text, tag_re);
+            }
+        }
+    }
+    if let Some(start) = code_start {
+        blocks.code.push(start..body.len());
+    }
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options() -> TagParseOptions {
+        TagParseOptions {
+            frontmatter_tags: true,
+            hierarchical_tags: true,
+            value_tags: false,
+            wikilinks: true,
+        }
+    }
+
+    #[test]
+    fn test_scan_tags_in_document_order() {
+        let content = "---\ntags: [meta]\n---\n#Work then #team and #work\n";
+        assert_eq!(
+            tags_in_note(content, options()),
+            ["meta", "work", "team", "work"]
+        );
+
+        let no_front_matter = TagParseOptions {
+            frontmatter_tags: false,
+            ..options()
+        };
+        assert_eq!(
+            tags_in_note(content, no_front_matter),
+            ["work", "team", "work"]
+        );
+    }
+
+    #[test]
+    fn test_scan_tags_skips_what_the_parser_skips() {
+        let content = "#kept `#span`\n\n```\n#fenced\n```\n\n    #indented\n\n\
+                       <!-- #comment -->\n[[Note#heading]] C#sharp #1234\n\n\
+                       <!-- djour:tags -->\nTags in this note: #footer <!-- /djour:tags -->\n";
+        assert_eq!(tags_in_note(content, options()), ["kept", "sharp", "1234"]);
+    }
+
+    #[test]
+    fn test_list_intro_tags_count_once_per_item() {
+        let content = "#errand\n\n- milk\n- #errand eggs\n  - bread\n- \n\n#errand alone\n";
+        assert_eq!(tags_in_note(content, options()), ["errand"; 5]);
+
+        // One item or none: the paragraph counts once
+        assert_eq!(
+            tags_in_note("#errand\n\n- #x\n", options()),
+            ["errand", "x"]
+        );
+    }
+
+    #[test]
+    fn test_scan_tags_follows_syntax_options() {
+        let content = "#project/alpha #run:5k";
+        assert_eq!(tags_in_note(content, options()), ["project/alpha", "run"]);
+
+        let options = TagParseOptions {
+            hierarchical_tags: false,
+            value_tags: true,
+            ..options()
+        };
+        assert_eq!(tags_in_note(content, options), ["project", "run:5k"]);
+    }
+}
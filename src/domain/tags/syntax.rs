@@ -0,0 +1,323 @@
+//! Tag syntax shared by parsing, querying, listing and retagging
+//!
+//! Flat tags use letters, numbers, `_` and `-`. Hierarchical tags additionally
+//! allow `/` between segments (`#project/alpha/backend`), never leading or trailing.
+//! With `value_tags`, a tag may end in a `:value` (`#run:5k`, `#weight:72.5`);
+//! values use letters, numbers, `_`, `-` and `.`, never as their last character.
+//!
+//! Text pasted from other apps or typed on mobile keyboards is read as meant: a
+//! fullwidth `＃` starts a tag like `#`, and unicode hyphens (U+2010..U+2015) in a
+//! name read as `-` (see [`tag_name`]). The regexes match the original text, so
+//! match positions stay valid for rewriting it.
+
+use regex::Regex;
+use std::borrow::Cow;
+use std::sync::OnceLock;
+
+/// Regex matching a `#tag` occurrence; capture group 1 is the tag name.
+pub fn tag_regex(hierarchical: bool) -> &'static Regex {
+    tag_scan_regex(hierarchical, false, false)
+}
+
+/// `#` or its fullwidth form `＃`
+const HASH: &str = r"[#\x{FF03}]";
+const FLAT_NAME: &str = r"[a-zA-Z0-9_\-\x{2010}-\x{2015}]+";
+const HIERARCHICAL_NAME: &str =
+    r"[a-zA-Z0-9_\-\x{2010}-\x{2015}]+(?:/[a-zA-Z0-9_\-\x{2010}-\x{2015}]+)*";
+/// Optional `:value` of a value tag; a trailing `.` ends the sentence, not the value
+const VALUE_SUFFIX: &str = r"(?::[a-zA-Z0-9_.-]*[a-zA-Z0-9_-])?";
+
+/// An Obsidian-style `[[target]]` / `[[target|label]]` link
+const WIKILINK: &str = r"\[\[[^\[\]\n]*\]\]";
+
+/// Like [`tag_regex`], but with `wikilinks` it also matches whole `[[...]]`
+/// links, so `[[#heading]]` is not read as a tag (group 1 is absent on those
+/// matches), and with `value_tags` group 1 includes a `:value` suffix.
+pub fn tag_scan_regex(hierarchical: bool, wikilinks: bool, value_tags: bool) -> &'static Regex {
+    static REGEXES: [OnceLock<Regex>; 8] = [const { OnceLock::new() }; 8];
+
+    let index =
+        usize::from(hierarchical) | usize::from(wikilinks) << 1 | usize::from(value_tags) << 2;
+    REGEXES[index].get_or_init(|| {
+        let name = if hierarchical {
+            HIERARCHICAL_NAME
+        } else {
+            FLAT_NAME
+        };
+        let suffix = if value_tags { VALUE_SUFFIX } else { "" };
+        let tag = format!("{}({}{})", HASH, name, suffix);
+        let pattern = if wikilinks {
+            format!("{}|{}", WIKILINK, tag)
+        } else {
+            tag
+        };
+        Regex::new(&pattern).unwrap()
+    })
+}
+
+/// The name of a tag as matched by [`tag_scan_regex`] (group 1), with unicode
+/// hyphens (U+2010..U+2015) replaced by `-`
+pub fn tag_name(matched: &str) -> Cow<'_, str> {
+    if matched.is_ascii() {
+        Cow::Borrowed(matched)
+    } else {
+        Cow::Owned(matched.replace(|c| matches!(c, '\u{2010}'..='\u{2015}'), "-"))
+    }
+}
+
+/// The matched name of a whole [`tag_scan_regex`] match, without its `#` or `＃`;
+/// None for the wiki links it also matches
+pub fn strip_hash(found: &str) -> Option<&str> {
+    found.strip_prefix(['#', '\u{FF03}'])
+}
+
+/// Whether a `#name` match starting at byte `start` of `text` reads as plain
+/// text when tags are stripped from a heading.
+///
+/// A `#` glued to the word before it (`C#sharp`, `notes.md#intro`) and names
+/// made only of digits (`#1234`, an issue number) stay in the heading text.
+/// They are still tags everywhere else.
+pub fn is_literal_hash(text: &str, start: usize, name: &str) -> bool {
+    let glued = text[..start]
+        .chars()
+        .next_back()
+        .is_some_and(|c| c.is_alphanumeric() || c == '_');
+    glued || name.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn is_segment_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '-' || c == '_'
+}
+
+/// Whether `tag` (without `#`) is a valid tag name.
+///
+/// Slashes are accepted only between non-empty segments.
+pub fn is_valid_tag_name(tag: &str) -> bool {
+    !tag.is_empty()
+        && tag
+            .split('/')
+            .all(|segment| !segment.is_empty() && segment.chars().all(is_segment_char))
+}
+
+/// Whether `tag` (without `#`) is a valid tag name, possibly with a `:value`
+/// suffix (accepted where `value_tags` is on).
+pub fn is_valid_value_tag_name(tag: &str) -> bool {
+    match tag.split_once(':') {
+        Some((name, value)) => {
+            is_valid_tag_name(name)
+                && !value.is_empty()
+                && !value.ends_with('.')
+                && value
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+        }
+        None => is_valid_tag_name(tag),
+    }
+}
+
+/// Name and value of a value tag (`run:5k` -> `run`, `5k`); `None` for other tags
+pub fn split_value_tag(tag: &str) -> Option<(&str, &str)> {
+    tag.split_once(':')
+}
+
+/// Whether `tag` equals `pattern` or lies in its subtree.
+///
+/// Matching happens at segment boundaries: `project` matches `project/alpha`,
+/// but `pro` does not. The values of a value tag count as its subtree, so
+/// `run` matches `run:5k`.
+pub fn tag_in_subtree(tag: &str, pattern: &str) -> bool {
+    tag == pattern
+        || (tag.len() > pattern.len()
+            && tag.starts_with(pattern)
+            && matches!(tag.as_bytes()[pattern.len()], b'/' | b':'))
+}
+
+/// Rename `tag` if it is `from` or one of its descendants, keeping the suffix.
+///
+/// Comparison is case-insensitive; returns `None` when the tag is outside the subtree.
+pub fn rename_in_subtree(tag: &str, from: &str, to: &str) -> Option<String> {
+    let lowered = tag.to_lowercase();
+    let from = from.to_lowercase();
+    if !tag_in_subtree(&lowered, &from) {
+        return None;
+    }
+    Some(format!("{}{}", to, &tag[from.len()..]))
+}
+
+/// All ancestors of a hierarchical tag, outermost first (`a/b/c` -> `a`, `a/b`).
+pub fn tag_ancestors(tag: &str) -> impl Iterator<Item = &str> {
+    tag.match_indices('/').map(move |(idx, _)| &tag[..idx])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hierarchical_regex_captures_full_path() {
+        let re = tag_regex(true);
+        let caps = re.captures("see #project/alpha/backend/ now").unwrap();
+        assert_eq!(&caps[1], "project/alpha/backend");
+
+        let flat = tag_regex(false);
+        let caps = flat.captures("see #project/alpha").unwrap();
+        assert_eq!(&caps[1], "project");
+    }
+
+    #[test]
+    fn scan_regex_skips_wikilinks() {
+        let tags = |text: &str| -> Vec<String> {
+            tag_scan_regex(true, true, false)
+                .captures_iter(text)
+                .filter_map(|caps| caps.get(1).map(|tag| tag.as_str().to_string()))
+                .collect()
+        };
+        assert_eq!(
+            tags("see [[#heading]] and [[notes#part|x]] #work"),
+            vec!["work"]
+        );
+        assert_eq!(tags("[[unclosed #work"), vec!["work"]);
+        assert_eq!(
+            tag_scan_regex(true, false, false).as_str(),
+            tag_regex(true).as_str()
+        );
+    }
+
+    #[test]
+    fn pasted_characters_are_read_as_meant() {
+        let tags = |text: &str| -> Vec<String> {
+            tag_scan_regex(true, true, false)
+                .captures_iter(text)
+                .filter_map(|caps| caps.get(1))
+                .map(|name| tag_name(name.as_str()).into_owned())
+                .collect()
+        };
+        // Fullwidth hash, as mobile keyboards type it
+        assert_eq!(tags("Standup \u{FF03}work"), vec!["work"]);
+        assert_eq!(tags("C\u{FF03} notes"), Vec::<String>::new());
+        // No-break spaces separate a tag from the word before it
+        assert_eq!(
+            tags("text\u{00A0}#work and\u{202F}#home"),
+            vec!["work", "home"]
+        );
+        // Every unicode hyphen reads as `-`
+        for hyphen in '\u{2010}'..='\u{2015}' {
+            let text = format!("#work{}item #project/q1{}plan", hyphen, hyphen);
+            assert_eq!(
+                tags(&text),
+                vec!["work-item", "project/q1-plan"],
+                "{:?}",
+                hyphen
+            );
+        }
+
+        assert_eq!(strip_hash("\u{FF03}work"), Some("work"));
+        assert_eq!(strip_hash("#work"), Some("work"));
+        assert_eq!(strip_hash("[[#work]]"), None);
+        assert!(matches!(tag_name("plain-tag"), Cow::Borrowed("plain-tag")));
+    }
+
+    #[test]
+    fn glued_and_numeric_matches_are_literal_hashes() {
+        let literal = |text: &str| -> Vec<String> {
+            tag_regex(true)
+                .captures_iter(text)
+                .filter(|caps| is_literal_hash(text, caps.get(0).unwrap().start(), &caps[1]))
+                .map(|caps| caps[1].to_string())
+                .collect()
+        };
+        assert_eq!(literal("Bug #1234 triage #work"), vec!["1234"]);
+        assert_eq!(
+            literal("C#sharp and notes.md#intro, x_#y"),
+            vec!["sharp", "intro", "y"]
+        );
+        assert_eq!(
+            literal("#task1 (#2025-q1) #1234/notes"),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn validates_tag_names() {
+        assert!(is_valid_tag_name("work"));
+        assert!(is_valid_tag_name("project/alpha"));
+        assert!(!is_valid_tag_name("/project"));
+        assert!(!is_valid_tag_name("project/"));
+        assert!(!is_valid_tag_name("project//alpha"));
+        assert!(!is_valid_tag_name("work@email"));
+    }
+
+    #[test]
+    fn subtree_matching_respects_segment_boundaries() {
+        assert!(tag_in_subtree("project", "project"));
+        assert!(tag_in_subtree("project/alpha", "project"));
+        assert!(tag_in_subtree("project/alpha/backend", "project/alpha"));
+        assert!(!tag_in_subtree("project", "pro"));
+        assert!(!tag_in_subtree("projects/alpha", "project"));
+        assert!(!tag_in_subtree("project/beta", "project/alpha"));
+    }
+
+    #[test]
+    fn value_tags_capture_their_value() {
+        let tags = |text: &str, value_tags: bool| -> Vec<String> {
+            tag_scan_regex(true, false, value_tags)
+                .captures_iter(text)
+                .map(|caps| caps[1].to_string())
+                .collect()
+        };
+        let text = "#run:5k then #weight:72.5. #mood:good, #todo: call #project/alpha:v2";
+        assert_eq!(
+            tags(text, true),
+            vec![
+                "run:5k",
+                "weight:72.5",
+                "mood:good",
+                "todo",
+                "project/alpha:v2"
+            ]
+        );
+        assert_eq!(
+            tags(text, false),
+            vec!["run", "weight", "mood", "todo", "project/alpha"]
+        );
+
+        assert!(is_valid_value_tag_name("run:5k"));
+        assert!(is_valid_value_tag_name("weight:72.5"));
+        assert!(is_valid_value_tag_name("run"));
+        assert!(!is_valid_value_tag_name("run:"));
+        assert!(!is_valid_value_tag_name("run:5k."));
+        assert!(!is_valid_value_tag_name("run:5:k"));
+        assert_eq!(split_value_tag("run:5k"), Some(("run", "5k")));
+        assert_eq!(split_value_tag("run"), None);
+    }
+
+    #[test]
+    fn values_lie_in_their_tags_subtree() {
+        assert!(tag_in_subtree("run:5k", "run"));
+        assert!(tag_in_subtree("run:5k", "run:5k"));
+        assert!(!tag_in_subtree("run:5k", "run:5"));
+        assert!(!tag_in_subtree("run:10k", "run:5k"));
+        assert!(!tag_in_subtree("running:5k", "run"));
+        assert!(tag_in_subtree("project/alpha:v2", "project"));
+        assert_eq!(
+            rename_in_subtree("run:5k", "run", "jog"),
+            Some("jog:5k".to_string())
+        );
+    }
+
+    #[test]
+    fn renames_subtree() {
+        assert_eq!(
+            rename_in_subtree("Project/Alpha/backend", "project/alpha", "project/omega"),
+            Some("project/omega/backend".to_string())
+        );
+        assert_eq!(rename_in_subtree("projectx", "project", "p"), None);
+    }
+
+    #[test]
+    fn lists_ancestors() {
+        let ancestors: Vec<&str> = tag_ancestors("a/b/c").collect();
+        assert_eq!(ancestors, vec!["a", "a/b"]);
+        assert_eq!(tag_ancestors("flat").count(), 0);
+    }
+}
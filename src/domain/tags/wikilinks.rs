@@ -0,0 +1,210 @@
+//! Obsidian-style wiki links (`[[target]]`, `[[target#heading]]`, `[[target|label]]`)
+//!
+//! With the `wikilinks` config key, compile turns the ones that resolve to a
+//! note into standard markdown links before the usual link rewriting, so they
+//! end up relative to the output file like any other link.
+
+use crate::domain::links::{relative_path, rewrite_outside_fenced_code_blocks};
+use regex::Regex;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+fn wikilink_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    // `![[...]]` embeds are matched too, only so they can be left alone
+    REGEX.get_or_init(|| Regex::new(r"(?P<bang>!?)\[\[(?P<inner>[^\[\]\n]*)\]\]").unwrap())
+}
+
+/// The parts of one wiki link
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WikiLink<'a> {
+    /// Note name or path (`design notes`, `2025-01-10`, `projects/alpha.md`)
+    pub target: &'a str,
+    /// Heading after `#`, if any
+    pub heading: Option<&'a str>,
+    /// Text after `|`, if any
+    pub label: Option<&'a str>,
+}
+
+impl<'a> WikiLink<'a> {
+    /// Parse the text between `[[` and `]]`; `None` when there is no target
+    /// (a same-note `[[#heading]]` anchor)
+    pub fn parse(inner: &'a str) -> Option<Self> {
+        let (reference, label) = match inner.split_once('|') {
+            Some((reference, label)) => (reference, Some(label.trim())),
+            None => (inner, None),
+        };
+        let (target, heading) = match reference.split_once('#') {
+            Some((target, heading)) => (target, Some(heading.trim())),
+            None => (reference, None),
+        };
+        let target = target.trim();
+        if target.is_empty() {
+            return None;
+        }
+        Some(WikiLink {
+            target,
+            heading: heading.filter(|heading| !heading.is_empty()),
+            label: label.filter(|label| !label.is_empty()),
+        })
+    }
+
+    /// Text shown for the link: the label, or the link as written
+    fn text(&self) -> String {
+        match (self.label, self.heading) {
+            (Some(label), _) => label.to_string(),
+            (None, Some(heading)) => format!("{} > {}", self.target, heading),
+            (None, None) => self.target.to_string(),
+        }
+    }
+}
+
+/// GitHub-style anchor of a heading: lowercase, spaces as `-`, punctuation dropped
+fn heading_anchor(heading: &str) -> String {
+    heading
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Rewrite the wiki links of `markdown` (outside fenced code blocks) that
+/// `resolve` maps to a note into markdown links.
+///
+/// `resolve` gets the target without heading and returns the journal-relative
+/// path of the note; the link destination is made relative to `source_file`.
+/// Unresolved links and `![[...]]` embeds are left as they are.
+///
+/// ```
+/// use djour::domain::tags::rewrite_wikilinks;
+/// use std::path::{Path, PathBuf};
+///
+/// let rewritten = rewrite_wikilinks(
+///     "See [[design notes|the design]].",
+///     Path::new("2025-01-15.md"),
+///     |target| (target == "design notes").then(|| PathBuf::from("design notes.md")),
+/// );
+/// assert_eq!(rewritten, "See [the design](<design notes.md>).");
+/// ```
+pub fn rewrite_wikilinks<F>(markdown: &str, source_file: &Path, mut resolve: F) -> String
+where
+    F: FnMut(&str) -> Option<PathBuf>,
+{
+    let source_dir = source_file.parent().unwrap_or_else(|| Path::new(""));
+    rewrite_outside_fenced_code_blocks(markdown, |chunk| {
+        wikilink_regex()
+            .replace_all(chunk, |caps: &regex::Captures<'_>| {
+                let original = caps[0].to_string();
+                if !caps["bang"].is_empty() {
+                    return original;
+                }
+                let Some(link) = WikiLink::parse(&caps["inner"]) else {
+                    return original;
+                };
+                let Some(note) = resolve(link.target) else {
+                    return original;
+                };
+                let Some(relative) = relative_path(source_dir, &note) else {
+                    return original;
+                };
+
+                let mut destination = relative.to_string_lossy().replace('\\', "/");
+                if let Some(heading) = link.heading {
+                    destination.push('#');
+                    destination.push_str(&heading_anchor(heading));
+                }
+                if destination.contains([' ', '(', ')']) {
+                    destination = format!("<{}>", destination);
+                }
+                format!("[{}]({})", link.text(), destination)
+            })
+            .into_owned()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolve(target: &str) -> Option<PathBuf> {
+        match target {
+            "2025-01-10" => Some(PathBuf::from("2025-01-10.md")),
+            "design notes" => Some(PathBuf::from("design notes.md")),
+            "alpha" => Some(PathBuf::from("projects/alpha.md")),
+            _ => None,
+        }
+    }
+
+    fn rewrite(markdown: &str, source: &str) -> String {
+        rewrite_wikilinks(markdown, Path::new(source), resolve)
+    }
+
+    #[test]
+    fn test_parse_link_forms() {
+        assert_eq!(
+            WikiLink::parse("2025-01-10"),
+            Some(WikiLink {
+                target: "2025-01-10",
+                heading: None,
+                label: None
+            })
+        );
+        assert_eq!(
+            WikiLink::parse("design notes#Goals|the design"),
+            Some(WikiLink {
+                target: "design notes",
+                heading: Some("Goals"),
+                label: Some("the design")
+            })
+        );
+        assert_eq!(WikiLink::parse("#heading"), None);
+        assert_eq!(WikiLink::parse(" |label"), None);
+    }
+
+    #[test]
+    fn test_plain_link_uses_target_as_text() {
+        assert_eq!(
+            rewrite("Back to [[2025-01-10]].", "2025-01-15.md"),
+            "Back to [2025-01-10](2025-01-10.md)."
+        );
+    }
+
+    #[test]
+    fn test_labelled_link_with_spaces_is_wrapped() {
+        assert_eq!(
+            rewrite("See [[design notes|the design]]", "2025-01-15.md"),
+            "See [the design](<design notes.md>)"
+        );
+    }
+
+    #[test]
+    fn test_heading_link_gets_anchor() {
+        assert_eq!(
+            rewrite("[[alpha#Next Steps!]]", "2025-01-15.md"),
+            "[alpha > Next Steps!](projects/alpha.md#next-steps)"
+        );
+    }
+
+    #[test]
+    fn test_destination_is_relative_to_the_source_note() {
+        assert_eq!(
+            rewrite("[[2025-01-10]]", "2025/01/2025-01-15.md"),
+            "[2025-01-10](../../2025-01-10.md)"
+        );
+        assert_eq!(
+            rewrite("[[alpha]]", "projects/beta.md"),
+            "[alpha](alpha.md)"
+        );
+    }
+
+    #[test]
+    fn test_unresolved_anchors_embeds_and_code_are_left_alone() {
+        let markdown = "[[missing]] [[#heading]] ![[alpha]]\n\n```\n[[alpha]]\n```\n";
+        assert_eq!(rewrite(markdown, "2025-01-15.md"), markdown);
+    }
+}
@@ -0,0 +1,132 @@
+//! Task list items (`- [ ] ...` / `- [x] ...`) found in notes
+
+use crate::domain::tags::{parse_front_matter, strip_tag_footer, TagParseOptions, TagParser};
+use chrono::NaiveDate;
+use pulldown_cmark::{Event, Options, Parser as MdParser};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// One task list item of a note
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TaskItem {
+    /// The item's first line after the checkbox, tags kept
+    pub text: String,
+    pub checked: bool,
+    /// Tags of the item, including those inherited from headings, lists and front matter
+    pub tags: Vec<String>,
+    pub source_file: PathBuf,
+    pub date: Option<NaiveDate>,
+    /// 1-based line of the checkbox
+    pub line: usize,
+}
+
+/// Extract every task list item of a note, in document order.
+///
+/// Tags follow the same inheritance rules as `compile`: a task under
+/// `## Client X #clientx` carries `clientx`, as does one nested below a
+/// tagged list item.
+pub fn extract_tasks(
+    content: &str,
+    source_file: &Path,
+    date: Option<NaiveDate>,
+    options: TagParseOptions,
+) -> Vec<TaskItem> {
+    let content = &*strip_tag_footer(content);
+    let body_offset = parse_front_matter(content).map_or(0, |front_matter| front_matter.len);
+    let body = &content[body_offset..];
+
+    let markers: Vec<(usize, bool)> = MdParser::new_ext(body, Options::ENABLE_TASKLISTS)
+        .into_offset_iter()
+        .filter_map(|(event, range)| match event {
+            Event::TaskListMarker(checked) => Some((range.end + body_offset, checked)),
+            _ => None,
+        })
+        .collect();
+    if markers.is_empty() {
+        return Vec::new();
+    }
+
+    // Tagged blocks with their spans; a task takes the tags of the smallest one around it
+    let blocks: Vec<(usize, usize, Vec<String>)> =
+        TagParser::extract_from_markdown_with_options(content, source_file, date, None, options)
+            .into_iter()
+            .filter_map(|block| {
+                let span = block.source_span()?;
+                Some((span.start, span.end, block.tags))
+            })
+            .collect();
+
+    markers
+        .into_iter()
+        .map(|(offset, checked)| {
+            let line_end = content[offset..]
+                .find('\n')
+                .map_or(content.len(), |i| offset + i);
+            let tags = blocks
+                .iter()
+                .filter(|(start, end, _)| *start <= offset && offset < *end)
+                .min_by_key(|(start, end, _)| end - start)
+                .map(|(_, _, tags)| tags.clone())
+                .unwrap_or_default();
+            TaskItem {
+                text: content[offset..line_end].trim().to_string(),
+                checked,
+                tags,
+                source_file: source_file.to_path_buf(),
+                date,
+                line: content[..offset].matches('\n').count() + 1,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tasks(content: &str) -> Vec<TaskItem> {
+        extract_tasks(
+            content,
+            Path::new("2025-01-15.md"),
+            NaiveDate::from_ymd_opt(2025, 1, 15),
+            TagParseOptions::default(),
+        )
+    }
+
+    #[test]
+    fn test_finds_open_and_done_tasks_with_lines() {
+        let found =
+            tasks("# Day\n\n- [ ] follow up with legal #work\n- [x] pay rent\n- plain item\n");
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].text, "follow up with legal #work");
+        assert!(!found[0].checked);
+        assert_eq!(found[0].tags, vec!["work".to_string()]);
+        assert_eq!(found[0].line, 3);
+        assert_eq!(found[1].text, "pay rent");
+        assert!(found[1].checked);
+        assert!(found[1].tags.is_empty());
+        assert_eq!(found[1].line, 4);
+    }
+
+    #[test]
+    fn test_tasks_inherit_heading_and_list_tags() {
+        let content = "---\ntags: [home]\n---\n## Client X #clientx\n\n- [ ] send invoice\n\n## Other\n\n- Errands #personal\n  - [X] buy milk\n";
+        let found = tasks(content);
+        assert_eq!(found.len(), 2);
+        assert_eq!(
+            found[0].tags,
+            vec!["home".to_string(), "clientx".to_string()]
+        );
+        assert_eq!(found[0].line, 6);
+        assert!(found[1].checked);
+        assert_eq!(found[1].text, "buy milk");
+        assert!(found[1].tags.contains(&"personal".to_string()));
+    }
+
+    #[test]
+    fn test_ignores_code_blocks_and_plain_brackets() {
+        let content =
+            "```\n- [ ] not a task #work\n```\n\nText with [ ] brackets\n\n- [] nor this\n";
+        assert!(tasks(content).is_empty());
+    }
+}
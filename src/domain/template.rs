@@ -1,20 +1,184 @@
 //! Template system for note generation
 
+use crate::domain::markdown::parse_fence_marker;
+use crate::domain::mode::week_label;
+use crate::domain::{Locale, WeekNumbering, WeekStart};
 use crate::error::{DjourError, Result};
-use chrono::{Datelike, Duration, NaiveDate};
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use regex::{Captures, Regex};
+use std::fmt::{self, Write as _};
 use std::fs;
 use std::path::Path;
+use std::sync::OnceLock;
 
 // Built-in template constants
 const DAILY_TEMPLATE: &str = "# {DATE}\n\n";
-const WEEKLY_TEMPLATE: &str = "# Week {WEEK_NUMBER}, {YEAR} ({WEEK_START_DATE} - {WEEK_END_DATE})\n\n## Monday ({MONDAY_DATE})\n\n\n## Tuesday ({TUESDAY_DATE})\n\n\n## Wednesday ({WEDNESDAY_DATE})\n\n\n## Thursday ({THURSDAY_DATE})\n\n\n## Friday ({FRIDAY_DATE})\n\n\n## Saturday ({SATURDAY_DATE})\n\n\n## Sunday ({SUNDAY_DATE})\n\n";
+const WEEKLY_TEMPLATE: &str = "# Week {WEEK_NUMBER}, {YEAR} ({WEEK_START_DATE} - {WEEK_END_DATE})\n\n## {MONDAY_NAME} ({MONDAY_DATE})\n\n\n## {TUESDAY_NAME} ({TUESDAY_DATE})\n\n\n## {WEDNESDAY_NAME} ({WEDNESDAY_DATE})\n\n\n## {THURSDAY_NAME} ({THURSDAY_DATE})\n\n\n## {FRIDAY_NAME} ({FRIDAY_DATE})\n\n\n## {SATURDAY_NAME} ({SATURDAY_DATE})\n\n\n## {SUNDAY_NAME} ({SUNDAY_DATE})\n\n";
 const MONTHLY_TEMPLATE: &str =
     "# {MONTH} {YEAR}\n\n## Week 1\n\n\n## Week 2\n\n\n## Week 3\n\n\n## Week 4\n\n";
 const ENTRY_TEMPLATE: &str = "---\n\n# {DATE}\n\n";
+const COMPILATION_TEMPLATE: &str = "# Compilation: {QUERY}\n\n{BODY}";
+// `djour capture` adds only its timestamp heading unless capture.md is customized
+const CAPTURE_TEMPLATE: &str = "";
+
+/// Templates djour uses, by file name under `.djour/templates/`
+pub const TEMPLATE_NAMES: &[&str] = &[
+    "daily.md",
+    "weekly.md",
+    "monthly.md",
+    "entry.md",
+    "compilation.md",
+    "capture.md",
+];
+
+/// Placeholders of note templates, the ones [`Template::render_with_context`] substitutes
+pub const NOTE_PLACEHOLDERS: &[&str] = &[
+    "DATE",
+    "ISO_DATE",
+    "YEAR",
+    "MONTH",
+    "DAY_NAME",
+    "WEEK_NUMBER",
+    "WEEK_START_DATE",
+    "WEEK_END_DATE",
+    "WEEK_START_ISO",
+    "WEEK_END_ISO",
+    "MONDAY_DATE",
+    "MONDAY_ISO",
+    "MONDAY_NAME",
+    "TUESDAY_DATE",
+    "TUESDAY_ISO",
+    "TUESDAY_NAME",
+    "WEDNESDAY_DATE",
+    "WEDNESDAY_ISO",
+    "WEDNESDAY_NAME",
+    "THURSDAY_DATE",
+    "THURSDAY_ISO",
+    "THURSDAY_NAME",
+    "FRIDAY_DATE",
+    "FRIDAY_ISO",
+    "FRIDAY_NAME",
+    "SATURDAY_DATE",
+    "SATURDAY_ISO",
+    "SATURDAY_NAME",
+    "SUNDAY_DATE",
+    "SUNDAY_ISO",
+    "SUNDAY_NAME",
+    "JOURNAL_NAME",
+    "AUTHOR",
+];
+
+/// Placeholders of the compilation template
+pub const COMPILATION_PLACEHOLDERS: &[&str] = &[
+    "QUERY",
+    "DATE_RANGE",
+    "GENERATED_AT",
+    "BODY",
+    "JOURNAL_NAME",
+    "AUTHOR",
+];
+
+/// Prefixes of the per-day placeholders (`{MONDAY_DATE}`, ...)
+const WEEKDAYS: [(&str, Weekday); 7] = [
+    ("MONDAY", Weekday::Mon),
+    ("TUESDAY", Weekday::Tue),
+    ("WEDNESDAY", Weekday::Wed),
+    ("THURSDAY", Weekday::Thu),
+    ("FRIDAY", Weekday::Fri),
+    ("SATURDAY", Weekday::Sat),
+    ("SUNDAY", Weekday::Sun),
+];
+
+/// A `{NAME}` placeholder
+fn placeholder_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| Regex::new(r"\{([A-Z_]+)\}").unwrap())
+}
+
+/// A problem [`Template::lint`] finds in a template
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateIssue {
+    /// `{NAME}` that rendering leaves as written
+    UnknownPlaceholder { line: usize, placeholder: String },
+    /// A `{` without its `}`, or a `}` without its `{`, on a 1-based line
+    UnbalancedBrace { line: usize, brace: char },
+    /// A weekly template without the heading of one day, e.g. `MONDAY`
+    MissingDayHeading { day: &'static str },
+}
+
+impl fmt::Display for TemplateIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TemplateIssue::UnknownPlaceholder { line, placeholder } => {
+                write!(f, "line {}: unknown placeholder {{{}}}", line, placeholder)
+            }
+            TemplateIssue::UnbalancedBrace { line, brace: '{' } => {
+                write!(f, "line {}: '{{' is never closed", line)
+            }
+            TemplateIssue::UnbalancedBrace { line, brace } => {
+                write!(f, "line {}: '{}' has no opening brace", line, brace)
+            }
+            TemplateIssue::MissingDayHeading { day } => write!(
+                f,
+                "no heading with {{{day}_NAME}} and {{{day}_DATE}}; mode migration and note checks need one per day"
+            ),
+        }
+    }
+}
+
+/// Values substituted into the compilation template
+#[derive(Debug, Clone)]
+pub struct CompilationTemplateVars<'a> {
+    /// Query as displayed in the default title (e.g., "#work AND #urgent")
+    pub query: &'a str,
+    /// Covered date range (e.g., "2025-01-01 - 2025-01-31")
+    pub date_range: &'a str,
+    /// Local timestamp of the compilation
+    pub generated_at: &'a str,
+    /// Default `# Compilation:` title, used when the template has no {BODY}
+    pub title: &'a str,
+    /// Compiled markdown without the default title
+    pub body: &'a str,
+    /// Journal display name (`name` config key), for {JOURNAL_NAME}
+    pub journal_name: &'a str,
+    /// Journal author (`author` config key), for {AUTHOR}
+    pub author: &'a str,
+}
+
+/// Values substituted into a note template
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderContext<'a> {
+    /// Date of the note; week placeholders cover the configured week containing it
+    pub date: NaiveDate,
+    pub locale: Locale,
+    pub week: WeekStart,
+    /// Numbering of {WEEK_NUMBER}
+    pub numbering: WeekNumbering,
+    /// For {JOURNAL_NAME} (empty when the journal has no name)
+    pub journal_name: &'a str,
+    /// For {AUTHOR} (empty when no author is configured)
+    pub author: &'a str,
+}
+
+impl RenderContext<'_> {
+    /// A context without journal details
+    pub fn new(date: NaiveDate, locale: Locale, week: WeekStart) -> Self {
+        RenderContext {
+            date,
+            locale,
+            week,
+            numbering: WeekNumbering::Iso,
+            journal_name: "",
+            author: "",
+        }
+    }
+}
 
 /// Template for note generation
 #[derive(Debug)]
 pub struct Template {
+    /// File name under `.djour/templates/`, e.g. `weekly.md`
+    name: String,
     content: String,
 }
 
@@ -26,6 +190,8 @@ impl Template {
             "weekly.md" => WEEKLY_TEMPLATE,
             "monthly.md" => MONTHLY_TEMPLATE,
             "entry.md" => ENTRY_TEMPLATE,
+            "compilation.md" => COMPILATION_TEMPLATE,
+            "capture.md" => CAPTURE_TEMPLATE,
             _ => {
                 return Err(DjourError::Template(format!(
                     "Unknown template: {}",
@@ -35,80 +201,275 @@ impl Template {
         };
 
         Ok(Template {
+            name: template_name.to_string(),
             content: content.to_string(),
         })
     }
 
+    /// Like `from_builtin`, with the weekly day sections ordered from `week`'s first day
+    pub fn from_builtin_for_week(template_name: &str, week: WeekStart) -> Result<Self> {
+        if template_name != "weekly.md" || week.is_default() {
+            return Self::from_builtin(template_name);
+        }
+
+        let (header, days) = WEEKLY_TEMPLATE
+            .split_once("\n\n")
+            .expect("weekly template has a header");
+        let mut sections: Vec<&str> = days.trim_end().split("\n\n\n").collect();
+        // Built-in sections run Monday..Sunday
+        sections.rotate_left(week.weekday().num_days_from_monday() as usize);
+        Ok(Template {
+            name: template_name.to_string(),
+            content: format!("{}\n\n{}\n\n", header, sections.join("\n\n\n")),
+        })
+    }
+
     /// Create template from custom template file
     pub fn from_file(path: &Path) -> Result<Self> {
         let content = fs::read_to_string(path)
             .map_err(|e| DjourError::Template(format!("Failed to read template file: {}", e)))?;
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
 
-        Ok(Template { content })
+        Ok(Template { name, content })
     }
 
-    /// Render template with date variable substitution
-    pub fn render(&self, date: NaiveDate) -> String {
-        let mut result = self.content.clone();
-
-        let week_start = date - Duration::days(date.weekday().num_days_from_monday() as i64);
-        let week_end = week_start + Duration::days(6);
+    /// File name of the template, e.g. `daily.md`
+    pub fn name(&self) -> &str {
+        &self.name
+    }
 
-        // Replace {DATE} with formatted date (e.g., "January 17, 2025")
-        result = result.replace("{DATE}", &date.format("%B %d, %Y").to_string());
+    /// The template text, placeholders unrendered
+    pub fn content(&self) -> &str {
+        &self.content
+    }
 
-        // Replace {ISO_DATE} with ISO format (e.g., "2025-01-17")
-        result = result.replace("{ISO_DATE}", &date.format("%Y-%m-%d").to_string());
+    /// Problems that would show up in rendered notes: placeholders rendering
+    /// does not know, unbalanced braces and, for `weekly.md`, days without the
+    /// `## {MONDAY_NAME} ({MONDAY_DATE})` style heading.
+    ///
+    /// Fenced code blocks are not checked.
+    pub fn lint(&self) -> Vec<TemplateIssue> {
+        let supported = if self.name == "compilation.md" {
+            COMPILATION_PLACEHOLDERS
+        } else {
+            NOTE_PLACEHOLDERS
+        };
 
-        // Replace {YEAR} with year (e.g., "2025")
-        result = result.replace("{YEAR}", &date.format("%Y").to_string());
+        let mut issues = Vec::new();
+        let mut active_fence: Option<(char, usize)> = None;
+        let mut headings: Vec<&str> = Vec::new();
+        for (index, line) in self.content.lines().enumerate() {
+            if let Some((fence_char, min_len)) = active_fence {
+                if matches!(parse_fence_marker(line), Some((c, len)) if c == fence_char && len >= min_len)
+                {
+                    active_fence = None;
+                }
+                continue;
+            }
+            if let Some(marker) = parse_fence_marker(line) {
+                active_fence = Some(marker);
+                continue;
+            }
+            if line.trim_start().starts_with('#') {
+                headings.push(line);
+            }
 
-        // Replace {MONTH} with month name (e.g., "January")
-        result = result.replace("{MONTH}", &date.format("%B").to_string());
+            let line_number = index + 1;
+            let mut open = false;
+            for c in line.chars() {
+                match c {
+                    '{' if open => issues.push(TemplateIssue::UnbalancedBrace {
+                        line: line_number,
+                        brace: '{',
+                    }),
+                    '{' => open = true,
+                    '}' if open => open = false,
+                    '}' => issues.push(TemplateIssue::UnbalancedBrace {
+                        line: line_number,
+                        brace: '}',
+                    }),
+                    _ => {}
+                }
+            }
+            if open {
+                issues.push(TemplateIssue::UnbalancedBrace {
+                    line: line_number,
+                    brace: '{',
+                });
+            }
 
-        // Replace {WEEK_NUMBER} with ISO week number (e.g., "03")
-        let week_num = date.iso_week().week();
-        result = result.replace("{WEEK_NUMBER}", &format!("{:02}", week_num));
+            for caps in placeholder_regex().captures_iter(line) {
+                if !supported.contains(&&caps[1]) {
+                    issues.push(TemplateIssue::UnknownPlaceholder {
+                        line: line_number,
+                        placeholder: caps[1].to_string(),
+                    });
+                }
+            }
+        }
 
-        // Replace {WEEK_START_DATE}/{WEEK_END_DATE} with formatted dates
-        result = result.replace(
-            "{WEEK_START_DATE}",
-            &week_start.format("%B %d, %Y").to_string(),
-        );
-        result = result.replace("{WEEK_END_DATE}", &week_end.format("%B %d, %Y").to_string());
-        result = result.replace(
-            "{WEEK_START_ISO}",
-            &week_start.format("%Y-%m-%d").to_string(),
-        );
-        result = result.replace("{WEEK_END_ISO}", &week_end.format("%Y-%m-%d").to_string());
-
-        // Replace weekday date placeholders based on ISO week start (Monday)
-        let weekdays = [
-            ("MONDAY", 0),
-            ("TUESDAY", 1),
-            ("WEDNESDAY", 2),
-            ("THURSDAY", 3),
-            ("FRIDAY", 4),
-            ("SATURDAY", 5),
-            ("SUNDAY", 6),
-        ];
-        for (name, offset) in weekdays {
-            let day = week_start + Duration::days(offset);
-            let long_key = format!("{{{}_DATE}}", name);
-            let iso_key = format!("{{{}_ISO}}", name);
-            result = result.replace(&long_key, &day.format("%B %d, %Y").to_string());
-            result = result.replace(&iso_key, &day.format("%Y-%m-%d").to_string());
+        if self.name == "weekly.md" {
+            for (day, _) in WEEKDAYS {
+                let name = format!("{{{}_NAME}}", day);
+                let date = format!("{{{}_DATE}}", day);
+                if !headings
+                    .iter()
+                    .any(|heading| heading.contains(&name) && heading.contains(&date))
+                {
+                    issues.push(TemplateIssue::MissingDayHeading { day });
+                }
+            }
         }
+        issues
+    }
+
+    /// Replace each `{NAME}` of the template listed in `supported` with its
+    /// value; other placeholders, and those without a value, stay as written
+    fn substitute(&self, supported: &[&str], value: impl Fn(&str) -> Option<String>) -> String {
+        placeholder_regex()
+            .replace_all(&self.content, |caps: &Captures| {
+                let key = &caps[1];
+                supported
+                    .contains(&key)
+                    .then(|| value(key))
+                    .flatten()
+                    .unwrap_or_else(|| caps[0].to_string())
+            })
+            .into_owned()
+    }
 
-        // Replace {DAY_NAME} with day name (e.g., "Friday")
-        result = result.replace("{DAY_NAME}", &date.format("%A").to_string());
+    /// Render template with date variable substitution (English names)
+    pub fn render(&self, date: NaiveDate) -> String {
+        self.render_with_locale(date, Locale::English)
+    }
 
-        result
+    /// Render template with date variable substitution using localized month/day names
+    pub fn render_with_locale(&self, date: NaiveDate, locale: Locale) -> String {
+        self.render_for_week(date, locale, WeekStart::Monday)
+    }
+
+    /// Render template for a journal whose weeks start on `week`.
+    ///
+    /// Week placeholders cover the configured week containing `date`; `{MONDAY_DATE}`
+    /// and friends name the days of that week.
+    pub fn render_for_week(&self, date: NaiveDate, locale: Locale, week: WeekStart) -> String {
+        self.render_with_context(&RenderContext::new(date, locale, week))
+    }
+
+    /// Render template with every placeholder, journal details included
+    pub fn render_with_context(&self, context: &RenderContext) -> String {
+        let RenderContext {
+            date,
+            locale,
+            week,
+            numbering,
+            ..
+        } = *context;
+
+        let week_start = week.start_of_week(date);
+        let week_end = week_start + Duration::days(6);
+        let iso = |day: NaiveDate| day.format("%Y-%m-%d").to_string();
+
+        self.substitute(NOTE_PLACEHOLDERS, |key| {
+            Some(match key {
+                // Long date, e.g. "January 17, 2025"
+                "DATE" => locale.format_long_date(date),
+                "ISO_DATE" => iso(date),
+                "YEAR" => date.format("%Y").to_string(),
+                "MONTH" => locale.month_name(date.month()).to_string(),
+                "DAY_NAME" => locale.weekday_name(date.weekday()).to_string(),
+                // The week's number (e.g., "03"), ISO by default
+                "WEEK_NUMBER" => format!("{:02}", week_label(week_start, week, numbering).1),
+                "WEEK_START_DATE" => locale.format_long_date(week_start),
+                "WEEK_END_DATE" => locale.format_long_date(week_end),
+                "WEEK_START_ISO" => iso(week_start),
+                "WEEK_END_ISO" => iso(week_end),
+                "JOURNAL_NAME" => context.journal_name.to_string(),
+                "AUTHOR" => context.author.to_string(),
+                // The days of the configured week: {MONDAY_DATE}, {MONDAY_ISO}, {MONDAY_NAME}, ...
+                _ => {
+                    let (weekday, field) = WEEKDAYS.iter().find_map(|&(name, weekday)| {
+                        let field = key.strip_prefix(name)?.strip_prefix('_')?;
+                        Some((weekday, field))
+                    })?;
+                    let day = week_start + Duration::days(week.day_index(weekday) as i64);
+                    match field {
+                        "DATE" => locale.format_long_date(day),
+                        "ISO" => iso(day),
+                        "NAME" => locale.weekday_name(day.weekday()).to_string(),
+                        _ => return None,
+                    }
+                }
+            })
+        })
+    }
+
+    /// Render a compilation template around compiled output.
+    ///
+    /// When the template contains {BODY}, the compiled markdown replaces it and the
+    /// template supplies its own title. Otherwise the template acts as a preamble and
+    /// the full compilation (default title included) follows it.
+    pub fn render_compilation(&self, vars: &CompilationTemplateVars) -> String {
+        let mut output = String::with_capacity(self.content.len() + vars.body.len());
+        self.write_compilation(&mut output, vars, |out| out.write_str(vars.body))
+            .expect("writing to a String cannot fail");
+        output
+    }
+
+    /// Write the output of [`render_compilation`](Self::render_compilation) to
+    /// `out`, with `write_body` producing the compiled markdown in place of
+    /// `vars.body`.
+    pub fn write_compilation<W: fmt::Write + ?Sized>(
+        &self,
+        out: &mut W,
+        vars: &CompilationTemplateVars,
+        write_body: impl FnOnce(&mut W) -> fmt::Result,
+    ) -> fmt::Result {
+        // {BODY} has no value here; it is split on below
+        let header = self.substitute(COMPILATION_PLACEHOLDERS, |key| {
+            let value = match key {
+                "QUERY" => vars.query,
+                "DATE_RANGE" => vars.date_range,
+                "GENERATED_AT" => vars.generated_at,
+                "JOURNAL_NAME" => vars.journal_name,
+                "AUTHOR" => vars.author,
+                _ => return None,
+            };
+            Some(value.to_string())
+        });
+
+        // The body goes in last so text inside notes is never treated as a placeholder.
+        if let Some((before, after)) = header.split_once("{BODY}") {
+            out.write_str(before)?;
+            write_body(out)?;
+            out.write_str(&after.replace("{BODY}", ""))
+        } else {
+            let preamble = header.trim_end();
+            if !preamble.is_empty() {
+                write!(out, "{}\n\n", preamble)?;
+            }
+            out.write_str(vars.title)?;
+            write_body(out)
+        }
     }
 }
 
 /// Load template from custom location or fall back to built-in
 pub fn load_template(repo_root: &Path, template_name: &str) -> Result<Template> {
+    load_template_for_week(repo_root, template_name, WeekStart::Monday)
+}
+
+/// Like `load_template`, using the built-in weekly layout for `week`.
+///
+/// Custom templates are used as written.
+pub fn load_template_for_week(
+    repo_root: &Path,
+    template_name: &str,
+    week: WeekStart,
+) -> Result<Template> {
     let custom_path = repo_root
         .join(".djour")
         .join("templates")
@@ -117,7 +478,7 @@ pub fn load_template(repo_root: &Path, template_name: &str) -> Result<Template>
     if custom_path.exists() {
         Template::from_file(&custom_path)
     } else {
-        Template::from_builtin(template_name)
+        Template::from_builtin_for_week(template_name, week)
     }
 }
 
@@ -127,6 +488,67 @@ mod tests {
     use chrono::NaiveDate;
     use tempfile::TempDir;
 
+    fn compilation_vars<'a>(body: &'a str) -> CompilationTemplateVars<'a> {
+        CompilationTemplateVars {
+            query: "#work",
+            date_range: "2025-01-15 - 2025-01-17",
+            generated_at: "2025-01-18 09:30",
+            title: "# Compilation: #work\n\n",
+            body,
+            journal_name: "Work Log",
+            author: "Tunç",
+        }
+    }
+
+    #[test]
+    fn test_builtin_compilation_template_reproduces_default_output() {
+        let template = Template::from_builtin("compilation.md").unwrap();
+        let body = "\n## 15-01-2025\n\nShip it. #work\n";
+        assert_eq!(
+            template.render_compilation(&compilation_vars(body)),
+            format!("# Compilation: #work\n\n{}", body)
+        );
+    }
+
+    #[test]
+    fn test_compilation_template_with_body_replaces_title() {
+        let template = Template {
+            name: "compilation.md".to_string(),
+            content: "# Report {QUERY} ({DATE_RANGE})\n\n{BODY}\n---\nGenerated {GENERATED_AT} {UNKNOWN}\n"
+                .to_string(),
+        };
+        let rendered = template.render_compilation(&compilation_vars("Body {QUERY}\n"));
+        assert_eq!(
+            rendered,
+            "# Report #work (2025-01-15 - 2025-01-17)\n\nBody {QUERY}\n\n---\nGenerated 2025-01-18 09:30 {UNKNOWN}\n"
+        );
+        assert!(!rendered.contains("# Compilation:"));
+    }
+
+    #[test]
+    fn test_compilation_template_without_body_is_preamble() {
+        let template = Template {
+            name: "compilation.md".to_string(),
+            content: "ACME Corp - Confidential\n".to_string(),
+        };
+        assert_eq!(
+            template.render_compilation(&compilation_vars("Body\n")),
+            "ACME Corp - Confidential\n\n# Compilation: #work\n\nBody\n"
+        );
+    }
+
+    #[test]
+    fn test_compilation_template_journal_details() {
+        let template = Template {
+            name: "compilation.md".to_string(),
+            content: "# {JOURNAL_NAME}: {QUERY}\n\n{BODY}\n-- {AUTHOR}\n".to_string(),
+        };
+        assert_eq!(
+            template.render_compilation(&compilation_vars("Body\n")),
+            "# Work Log: #work\n\nBody\n\n-- Tunç\n"
+        );
+    }
+
     #[test]
     fn test_load_builtin_daily() {
         let template = Template::from_builtin("daily.md").unwrap();
@@ -140,8 +562,12 @@ mod tests {
         assert!(template
             .content
             .contains("# Week {WEEK_NUMBER}, {YEAR} ({WEEK_START_DATE} - {WEEK_END_DATE})"));
-        assert!(template.content.contains("## Monday ({MONDAY_DATE})"));
-        assert!(template.content.contains("## Sunday ({SUNDAY_DATE})"));
+        assert!(template
+            .content
+            .contains("## {MONDAY_NAME} ({MONDAY_DATE})"));
+        assert!(template
+            .content
+            .contains("## {SUNDAY_NAME} ({SUNDAY_DATE})"));
     }
 
     #[test]
@@ -179,6 +605,7 @@ mod tests {
     #[test]
     fn test_render_replaces_all_variables() {
         let template = Template {
+            name: "daily.md".to_string(),
             content: "{DATE} {ISO_DATE} {YEAR} {MONTH} {WEEK_NUMBER} {DAY_NAME}".to_string(),
         };
 
@@ -193,9 +620,68 @@ mod tests {
         assert!(rendered.contains("Friday"));
     }
 
+    #[test]
+    fn test_render_weekly_english_headings() {
+        let template = Template::from_builtin("weekly.md").unwrap();
+        let date = NaiveDate::from_ymd_opt(2025, 1, 17).unwrap();
+        let rendered = template.render(date);
+
+        assert!(rendered.contains("## Monday (January 13, 2025)"));
+        assert!(rendered.contains("## Sunday (January 19, 2025)"));
+    }
+
+    #[test]
+    fn test_render_weekly_sunday_start() {
+        let template = Template::from_builtin_for_week("weekly.md", WeekStart::Sunday).unwrap();
+        let date = NaiveDate::from_ymd_opt(2025, 1, 17).unwrap();
+        let rendered = template.render_for_week(date, Locale::English, WeekStart::Sunday);
+
+        assert!(rendered.starts_with(
+            "# Week 03, 2025 (January 12, 2025 - January 18, 2025)\n\n## Sunday (January 12, 2025)\n\n\n## Monday (January 13, 2025)\n"
+        ));
+        assert!(rendered.ends_with("## Saturday (January 18, 2025)\n\n"));
+    }
+
+    #[test]
+    fn test_monday_week_builtin_is_unchanged() {
+        let template = Template::from_builtin_for_week("weekly.md", WeekStart::Monday).unwrap();
+        assert_eq!(template.content, WEEKLY_TEMPLATE);
+        let rotated = Template::from_builtin_for_week("weekly.md", WeekStart::Saturday).unwrap();
+        assert_eq!(rotated.content.len(), WEEKLY_TEMPLATE.len());
+    }
+
+    #[test]
+    fn test_render_weekly_template_german() {
+        let template = Template::from_builtin("weekly.md").unwrap();
+        let date = NaiveDate::from_ymd_opt(2025, 1, 17).unwrap();
+        let rendered = template.render_with_locale(date, Locale::German);
+
+        assert!(rendered.starts_with("# Week 03, 2025 (Januar 13, 2025 - Januar 19, 2025)"));
+        assert!(rendered.contains("## Montag (Januar 13, 2025)"));
+        assert!(rendered.contains("## Freitag (Januar 17, 2025)"));
+        assert!(rendered.contains("## Sonntag (Januar 19, 2025)"));
+        assert!(!rendered.contains("Monday"));
+    }
+
+    #[test]
+    fn test_render_month_and_day_name_localized() {
+        let template = Template {
+            name: "daily.md".to_string(),
+            content: "{MONTH} {DAY_NAME}".to_string(),
+        };
+        let date = NaiveDate::from_ymd_opt(2025, 1, 17).unwrap();
+
+        assert_eq!(
+            template.render_with_locale(date, Locale::German),
+            "Januar Freitag"
+        );
+        assert_eq!(template.render(date), "January Friday");
+    }
+
     #[test]
     fn test_render_week_number_zero_padded() {
         let template = Template {
+            name: "daily.md".to_string(),
             content: "Week {WEEK_NUMBER}".to_string(),
         };
 
@@ -210,9 +696,30 @@ mod tests {
         assert!(rendered2.contains("Week 52"));
     }
 
+    #[test]
+    fn test_render_journal_details() {
+        let template = Template {
+            name: "daily.md".to_string(),
+            content: "# {DATE} · {JOURNAL_NAME}\n\nby {AUTHOR}\n".to_string(),
+        };
+        let date = NaiveDate::from_ymd_opt(2025, 1, 17).unwrap();
+        let context = RenderContext {
+            journal_name: "Work Log",
+            author: "Tunç",
+            ..RenderContext::new(date, Locale::English, WeekStart::Monday)
+        };
+        assert_eq!(
+            template.render_with_context(&context),
+            "# January 17, 2025 · Work Log\n\nby Tunç\n"
+        );
+        // Without journal details the placeholders render empty
+        assert_eq!(template.render(date), "# January 17, 2025 · \n\nby \n");
+    }
+
     #[test]
     fn test_render_preserves_unknown_variables() {
         let template = Template {
+            name: "daily.md".to_string(),
             content: "{DATE} {UNKNOWN}".to_string(),
         };
 
@@ -223,6 +730,87 @@ mod tests {
         assert!(rendered.contains("{UNKNOWN}")); // Unknown variables left unchanged
     }
 
+    fn lint(name: &str, content: &str) -> Vec<String> {
+        let template = Template {
+            name: name.to_string(),
+            content: content.to_string(),
+        };
+        template.lint().iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn test_builtin_templates_lint_clean() {
+        for name in TEMPLATE_NAMES {
+            assert!(
+                Template::from_builtin(name).unwrap().lint().is_empty(),
+                "{name}"
+            );
+        }
+        let sunday = Template::from_builtin_for_week("weekly.md", WeekStart::Sunday).unwrap();
+        assert!(sunday.lint().is_empty());
+    }
+
+    #[test]
+    fn test_lint_unknown_placeholders() {
+        assert_eq!(
+            lint("daily.md", "# {DATE} ({WEEKDAY})\n\n{QUERY}\n"),
+            [
+                "line 1: unknown placeholder {WEEKDAY}",
+                "line 3: unknown placeholder {QUERY}",
+            ]
+        );
+        // Each kind of template has its own placeholders
+        assert!(lint("compilation.md", "# {QUERY} {DATE_RANGE}\n{BODY}").is_empty());
+        assert_eq!(
+            lint("compilation.md", "# {DATE}\n{BODY}"),
+            ["line 1: unknown placeholder {DATE}"]
+        );
+        // Code blocks are left alone
+        assert!(lint("daily.md", "# {DATE}\n\n```\n{EXAMPLE} {\n```\n").is_empty());
+    }
+
+    #[test]
+    fn test_every_note_placeholder_renders() {
+        let content: String = NOTE_PLACEHOLDERS
+            .iter()
+            .map(|key| format!("{{{}}}\n", key))
+            .collect();
+        let template = Template {
+            name: "daily.md".to_string(),
+            content,
+        };
+        let rendered = template.render(NaiveDate::from_ymd_opt(2025, 1, 17).unwrap());
+        assert!(!rendered.contains('{'), "{rendered}");
+    }
+
+    #[test]
+    fn test_lint_unbalanced_braces() {
+        assert_eq!(
+            lint("daily.md", "# {DATE\n\nDone} {ISO_DATE}\n{{YEAR}}\n"),
+            [
+                "line 1: '{' is never closed",
+                "line 3: '}' has no opening brace",
+                "line 4: '{' is never closed",
+                "line 4: '}' has no opening brace",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lint_weekly_day_headings() {
+        let builtin = Template::from_builtin("weekly.md").unwrap().content;
+        let without_tuesday = builtin.replace("## {TUESDAY_NAME} ({TUESDAY_DATE})\n\n\n", "");
+        assert_eq!(
+            lint("weekly.md", &without_tuesday),
+            ["no heading with {TUESDAY_NAME} and {TUESDAY_DATE}; mode migration and note checks need one per day"]
+        );
+        // Placeholders outside a heading do not count
+        let in_text = builtin.replace("## {SUNDAY_NAME}", "{SUNDAY_NAME}");
+        assert_eq!(lint("weekly.md", &in_text).len(), 1);
+        // Only weekly templates need day headings
+        assert!(lint("daily.md", "# {DATE}\n").is_empty());
+    }
+
     #[test]
     fn test_load_custom_template() {
         let temp = TempDir::new().unwrap();
@@ -0,0 +1,314 @@
+//! Line-based unified diffs of note contents, for previews such as `mode --show-diff`
+
+const RESET: &str = "\x1b[0m";
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const CYAN: &str = "\x1b[36m";
+const BOLD: &str = "\x1b[1m";
+
+/// Past this many line pairs the changed region is shown as one replacement
+/// instead of searching for the shortest edit
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+/// How [`unified_diff`] renders its output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiffOptions {
+    /// Unchanged lines shown around each change
+    pub context: usize,
+    /// Lines shown after the file headers (None = no limit); the rest is
+    /// summarized as `… (+N more lines)`
+    pub max_lines: Option<usize>,
+    /// Emit ANSI colors
+    pub color: bool,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        DiffOptions {
+            context: 3,
+            max_lines: None,
+            color: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op<'a> {
+    Equal(&'a str),
+    Delete(&'a str),
+    Insert(&'a str),
+}
+
+/// Unified diff from `old` to `new`, labelled `--- old_label` / `+++ new_label`.
+///
+/// Returns an empty string when the contents have the same lines. Distant
+/// changes get separate `@@` hunks.
+///
+/// ```
+/// use djour::domain::text_diff::{unified_diff, DiffOptions};
+///
+/// let diff = unified_diff("a\nb\n", "a\nc\n", "old.md", "new.md", &DiffOptions::default());
+/// assert_eq!(diff, "--- old.md\n+++ new.md\n@@ -1,2 +1,2 @@\n a\n-b\n+c\n");
+/// ```
+pub fn unified_diff(
+    old: &str,
+    new: &str,
+    old_label: &str,
+    new_label: &str,
+    options: &DiffOptions,
+) -> String {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let ops = diff_lines(&old_lines, &new_lines);
+    if ops.iter().all(|op| matches!(op, Op::Equal(_))) {
+        return String::new();
+    }
+
+    let paint = |code: &str, text: String| {
+        if options.color {
+            format!("{}{}{}", code, text, RESET)
+        } else {
+            text
+        }
+    };
+
+    let mut body = Vec::new();
+    for hunk in hunks(&ops, options.context) {
+        body.push(paint(CYAN, hunk.header()));
+        for op in &ops[hunk.ops.clone()] {
+            body.push(match op {
+                Op::Equal(line) => format!(" {}", line),
+                Op::Delete(line) => paint(RED, format!("-{}", line)),
+                Op::Insert(line) => paint(GREEN, format!("+{}", line)),
+            });
+        }
+    }
+
+    let mut out = String::new();
+    out.push_str(&paint(BOLD, format!("--- {}", old_label)));
+    out.push('\n');
+    out.push_str(&paint(BOLD, format!("+++ {}", new_label)));
+    out.push('\n');
+    let shown = options.max_lines.unwrap_or(body.len()).min(body.len());
+    for line in &body[..shown] {
+        out.push_str(line);
+        out.push('\n');
+    }
+    if shown < body.len() {
+        out.push_str(&format!("… (+{} more lines)\n", body.len() - shown));
+    }
+    out
+}
+
+/// Shortest edit script: common prefix and suffix, then an LCS over the rest
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<Op<'a>> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    let mut ops: Vec<Op> = old[..prefix].iter().map(|line| Op::Equal(line)).collect();
+    if old_mid.len().saturating_mul(new_mid.len()) > MAX_LCS_CELLS {
+        ops.extend(old_mid.iter().map(|line| Op::Delete(line)));
+        ops.extend(new_mid.iter().map(|line| Op::Insert(line)));
+    } else {
+        ops.extend(lcs_ops(old_mid, new_mid));
+    }
+    ops.extend(old[old.len() - suffix..].iter().map(|line| Op::Equal(line)));
+    ops
+}
+
+fn lcs_ops<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<Op<'a>> {
+    // lengths[i][j]: LCS length of old[i..] and new[j..]
+    let width = new.len() + 1;
+    let mut lengths = vec![0usize; (old.len() + 1) * width];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i * width + j] = if old[i] == new[j] {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut ops = Vec::with_capacity(old.len() + new.len());
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            ops.push(Op::Equal(old[i]));
+            i += 1;
+            j += 1;
+        } else if lengths[(i + 1) * width + j] >= lengths[i * width + j + 1] {
+            ops.push(Op::Delete(old[i]));
+            i += 1;
+        } else {
+            ops.push(Op::Insert(new[j]));
+            j += 1;
+        }
+    }
+    ops.extend(old[i..].iter().map(|line| Op::Delete(line)));
+    ops.extend(new[j..].iter().map(|line| Op::Insert(line)));
+    ops
+}
+
+struct Hunk {
+    /// Range into the edit script
+    ops: std::ops::Range<usize>,
+    old_start: usize,
+    old_len: usize,
+    new_start: usize,
+    new_len: usize,
+}
+
+impl Hunk {
+    fn header(&self) -> String {
+        // An empty side is reported at the line before it, as diff(1) does
+        let start = |start: usize, len: usize| if len == 0 { start } else { start + 1 };
+        format!(
+            "@@ -{},{} +{},{} @@",
+            start(self.old_start, self.old_len),
+            self.old_len,
+            start(self.new_start, self.new_len),
+            self.new_len
+        )
+    }
+}
+
+/// Group changes closer than `2 * context` lines into one hunk
+fn hunks(ops: &[Op], context: usize) -> Vec<Hunk> {
+    let changed: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| !matches!(op, Op::Equal(_)))
+        .map(|(index, _)| index)
+        .collect();
+
+    let mut ranges: Vec<std::ops::Range<usize>> = Vec::new();
+    for index in changed {
+        let start = index.saturating_sub(context);
+        let end = (index + 1 + context).min(ops.len());
+        match ranges.last_mut() {
+            Some(last) if start <= last.end => last.end = end,
+            _ => ranges.push(start..end),
+        }
+    }
+
+    // Line numbers before each op
+    let mut old_line = 0;
+    let mut new_line = 0;
+    let mut positions = Vec::with_capacity(ops.len());
+    for op in ops {
+        positions.push((old_line, new_line));
+        match op {
+            Op::Equal(_) => {
+                old_line += 1;
+                new_line += 1;
+            }
+            Op::Delete(_) => old_line += 1,
+            Op::Insert(_) => new_line += 1,
+        }
+    }
+
+    ranges
+        .into_iter()
+        .map(|range| {
+            let (old_start, new_start) = positions[range.start];
+            let old_len = ops[range.clone()]
+                .iter()
+                .filter(|op| !matches!(op, Op::Insert(_)))
+                .count();
+            let new_len = ops[range.clone()]
+                .iter()
+                .filter(|op| !matches!(op, Op::Delete(_)))
+                .count();
+            Hunk {
+                ops: range,
+                old_start,
+                old_len,
+                new_start,
+                new_len,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diff(old: &str, new: &str) -> String {
+        unified_diff(old, new, "a", "b", &DiffOptions::default())
+    }
+
+    #[test]
+    fn test_identical_contents_have_no_diff() {
+        assert_eq!(diff("x\ny\n", "x\ny\n"), "");
+        assert_eq!(diff("", ""), "");
+    }
+
+    #[test]
+    fn test_new_file_is_one_insertion_hunk() {
+        assert_eq!(
+            diff("", "# Week\n\nhello\n"),
+            "--- a\n+++ b\n@@ -0,0 +1,3 @@\n+# Week\n+\n+hello\n"
+        );
+    }
+
+    #[test]
+    fn test_distant_changes_get_separate_hunks() {
+        let old: String = (1..=20).map(|n| format!("line {}\n", n)).collect();
+        let new = old
+            .replace("line 2\n", "line two\n")
+            .replace("line 18\n", "line 18\nadded\n");
+
+        let expected = concat!(
+            "--- a\n+++ b\n",
+            "@@ -1,5 +1,5 @@\n",
+            " line 1\n-line 2\n+line two\n line 3\n line 4\n line 5\n",
+            "@@ -16,5 +16,6 @@\n",
+            " line 16\n line 17\n line 18\n+added\n line 19\n line 20\n",
+        );
+        assert_eq!(diff(&old, &new), expected);
+    }
+
+    #[test]
+    fn test_nearby_changes_share_a_hunk() {
+        let old = "a\nb\nc\nd\ne\nf\n";
+        let new = "a\nB\nc\nd\nE\nf\n";
+        let out = diff(old, new);
+        assert_eq!(out.matches("@@").count(), 2, "{}", out);
+        assert!(out.contains("@@ -1,6 +1,6 @@"), "{}", out);
+    }
+
+    #[test]
+    fn test_line_cap_adds_trailer() {
+        let new: String = (1..=10).map(|n| format!("{}\n", n)).collect();
+        let options = DiffOptions {
+            max_lines: Some(4),
+            ..DiffOptions::default()
+        };
+        let out = unified_diff("", &new, "a", "b", &options);
+        assert_eq!(
+            out,
+            "--- a\n+++ b\n@@ -0,0 +1,10 @@\n+1\n+2\n+3\n… (+7 more lines)\n"
+        );
+    }
+
+    #[test]
+    fn test_color_marks_removed_and_added_lines() {
+        let options = DiffOptions {
+            color: true,
+            ..DiffOptions::default()
+        };
+        let out = unified_diff("old\n", "new\n", "a", "b", &options);
+        assert!(out.contains("\x1b[31m-old\x1b[0m"), "{:?}", out);
+        assert!(out.contains("\x1b[32m+new\x1b[0m"), "{:?}", out);
+        assert!(!diff("old\n", "new\n").contains('\x1b'));
+    }
+}
@@ -1,5 +1,6 @@
 //! Time reference parsing and resolution
 
+use crate::domain::Locale;
 use crate::error::{DjourError, Result};
 use chrono::{Datelike, Duration, NaiveDate, Weekday};
 
@@ -23,57 +24,111 @@ pub enum TimeReference {
 }
 
 impl TimeReference {
-    /// Parse a time reference string
+    /// Parse a time reference string (English keywords only)
     pub fn parse(input: &str) -> Result<Self> {
+        Self::parse_with_locale(input, Locale::English)
+    }
+
+    /// Parse a time reference string, accepting English and the given locale's keywords
+    pub fn parse_with_locale(input: &str, locale: Locale) -> Result<Self> {
         let normalized = input.trim().to_lowercase();
 
-        match normalized.as_str() {
-            "today" | "now" => Ok(TimeReference::Today),
-            "yesterday" => Ok(TimeReference::Yesterday),
-            "tomorrow" => Ok(TimeReference::Tomorrow),
-            "monday" => Ok(TimeReference::Weekday(Weekday::Mon)),
-            "tuesday" => Ok(TimeReference::Weekday(Weekday::Tue)),
-            "wednesday" => Ok(TimeReference::Weekday(Weekday::Wed)),
-            "thursday" => Ok(TimeReference::Weekday(Weekday::Thu)),
-            "friday" => Ok(TimeReference::Weekday(Weekday::Fri)),
-            "saturday" => Ok(TimeReference::Weekday(Weekday::Sat)),
-            "sunday" => Ok(TimeReference::Weekday(Weekday::Sun)),
-            _ if normalized.starts_with("last ") => {
-                Self::parse_offset_weekday(&normalized[5..], TimeReference::LastWeekday)
-            }
-            _ if normalized.starts_with("next ") => {
-                Self::parse_offset_weekday(&normalized[5..], TimeReference::NextWeekday)
-            }
-            _ => {
-                // Try parsing as DD-MM-YYYY
-                NaiveDate::parse_from_str(&normalized, "%d-%m-%Y")
-                    .map(TimeReference::SpecificDate)
-                    .map_err(|_| DjourError::InvalidTimeReference(input.to_string()))
+        for candidate in [Locale::English, locale] {
+            if let Some(time_ref) = Self::parse_keywords(&normalized, candidate) {
+                return Ok(time_ref);
             }
         }
+
+        // Try parsing as DD-MM-YYYY
+        NaiveDate::parse_from_str(&normalized, "%d-%m-%Y")
+            .map(TimeReference::SpecificDate)
+            .map_err(|_| {
+                Self::calendar_error(input)
+                    .unwrap_or_else(|| DjourError::InvalidTimeReference(input.to_string()))
+            })
     }
 
-    /// Helper to parse weekday names with offsets (last/next)
-    fn parse_offset_weekday<F>(day_str: &str, f: F) -> Result<Self>
-    where
-        F: FnOnce(Weekday) -> TimeReference,
-    {
-        let weekday = match day_str {
-            "monday" => Weekday::Mon,
-            "tuesday" => Weekday::Tue,
-            "wednesday" => Weekday::Wed,
-            "thursday" => Weekday::Thu,
-            "friday" => Weekday::Fri,
-            "saturday" => Weekday::Sat,
-            "sunday" => Weekday::Sun,
-            _ => {
-                return Err(DjourError::InvalidTimeReference(format!(
-                    "last/next {}",
-                    day_str
-                )))
+    /// Error for input written as DD-MM-YYYY that names no calendar day
+    /// (29-02-2025, 10-13-2025), naming the day, month and year it read.
+    ///
+    /// None when the input is not in that form or is a valid date.
+    pub fn calendar_error(input: &str) -> Option<DjourError> {
+        let trimmed = input.trim();
+        let parts: Vec<&str> = trimmed.split('-').collect();
+        let [day, month, year] = parts[..] else {
+            return None;
+        };
+        let numeric = |part: &str, max_len: usize| {
+            !part.is_empty() && part.len() <= max_len && part.bytes().all(|b| b.is_ascii_digit())
+        };
+        if !(numeric(day, 2) && numeric(month, 2) && year.len() == 4 && numeric(year, 4)) {
+            return None;
+        }
+        let (day, month, year): (u32, u32, i32) =
+            (day.parse().ok()?, month.parse().ok()?, year.parse().ok()?);
+        if NaiveDate::from_ymd_opt(year, month, day).is_some() {
+            return None;
+        }
+
+        let problem = if !(1..=12).contains(&month) {
+            format!("there is no month {}", month)
+        } else {
+            let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+            let days = crate::domain::JournalMode::Monthly.period_end(first).day();
+            let month_name = Locale::English.month_name(month);
+            if day == 0 {
+                "there is no day 0".to_string()
+            } else {
+                format!("{} {} has only {} days", month_name, year, days)
             }
         };
-        Ok(f(weekday))
+        Some(DjourError::InvalidDate(format!(
+            "'{}' is day {}, month {}, year {}, but {}",
+            trimmed, day, month, year, problem
+        )))
+    }
+
+    /// Match relative day keywords and (last/next) weekday names of a single locale
+    fn parse_keywords(normalized: &str, locale: Locale) -> Option<Self> {
+        let (today, yesterday, tomorrow) = locale.relative_day_keywords();
+        if today.contains(&normalized) {
+            return Some(TimeReference::Today);
+        }
+        if normalized == yesterday {
+            return Some(TimeReference::Yesterday);
+        }
+        if normalized == tomorrow {
+            return Some(TimeReference::Tomorrow);
+        }
+
+        if let Some(weekday) = locale.parse_weekday(normalized) {
+            return Some(TimeReference::Weekday(weekday));
+        }
+
+        Self::parse_offset_weekday(normalized, locale.last_keywords(), locale)
+            .map(TimeReference::LastWeekday)
+            .or_else(|| {
+                Self::parse_offset_weekday(normalized, locale.next_keywords(), locale)
+                    .map(TimeReference::NextWeekday)
+            })
+    }
+
+    /// Helper to parse weekday names with offsets ("last friday", "vendredi dernier")
+    fn parse_offset_weekday(
+        normalized: &str,
+        keywords: &[&str],
+        locale: Locale,
+    ) -> Option<Weekday> {
+        let (first, second) = normalized.split_once(' ')?;
+        let (first, second) = (first.trim(), second.trim());
+
+        if keywords.contains(&first) {
+            return locale.parse_weekday(second);
+        }
+        if keywords.contains(&second) {
+            return locale.parse_weekday(first);
+        }
+        None
     }
 
     /// Resolve this time reference to an actual date
@@ -95,6 +150,42 @@ impl TimeReference {
         }
     }
 
+    /// Closest valid time reference to an input that did not parse: the
+    /// `DD-MM-YYYY` spelling of a date written another way, or the English
+    /// keyword at most two typos away
+    pub fn nearest_example(input: &str) -> Option<String> {
+        let normalized = input.trim().to_lowercase();
+
+        const DATE_FORMATS: [&str; 6] = [
+            "%Y-%m-%d", "%Y/%m/%d", "%d/%m/%Y", "%d.%m.%Y", "%m-%d-%Y", "%m/%d/%Y",
+        ];
+        if let Some(date) = DATE_FORMATS
+            .iter()
+            .find_map(|format| NaiveDate::parse_from_str(&normalized, format).ok())
+        {
+            return Some(date.format("%d-%m-%Y").to_string());
+        }
+
+        let mut keywords: Vec<String> = ["today", "yesterday", "tomorrow"]
+            .iter()
+            .map(|keyword| keyword.to_string())
+            .collect();
+        let mut day = Weekday::Mon;
+        for _ in 0..7 {
+            let weekday = Locale::English.weekday_name(day).to_lowercase();
+            keywords.push(format!("last {}", weekday));
+            keywords.push(format!("next {}", weekday));
+            keywords.push(weekday);
+            day = day.succ();
+        }
+        keywords
+            .into_iter()
+            .map(|keyword| (edit_distance(&normalized, &keyword), keyword))
+            .filter(|(distance, keyword)| *distance <= 2 && *distance < keyword.len() / 2)
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, keyword)| keyword)
+    }
+
     /// Find a specific weekday relative to the base date
     fn find_weekday(base_date: NaiveDate, target_day: Weekday, offset: WeekdayOffset) -> NaiveDate {
         let current_day = base_date.weekday();
@@ -142,6 +233,21 @@ impl TimeReference {
     }
 }
 
+/// Levenshtein distance between two strings, counted in chars
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
 /// Offset for weekday resolution
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum WeekdayOffset {
@@ -158,6 +264,47 @@ mod tests {
     use super::*;
     use chrono::NaiveDate;
 
+    #[test]
+    fn test_parse_calendar_invalid_date_names_its_parts() {
+        let err = TimeReference::parse("29-02-2025").unwrap_err();
+        assert!(matches!(err, DjourError::InvalidDate(_)));
+        assert_eq!(
+            err.to_string(),
+            "Invalid date: '29-02-2025' is day 29, month 2, year 2025, but February 2025 has only 28 days"
+        );
+        assert!(TimeReference::parse("29-02-2024").is_ok());
+
+        let err = TimeReference::parse("10-13-2025").unwrap_err();
+        assert!(
+            err.to_string().ends_with("but there is no month 13"),
+            "{err}"
+        );
+        let err = TimeReference::parse("00-01-2025").unwrap_err();
+        assert!(err.to_string().ends_with("but there is no day 0"), "{err}");
+        let err = TimeReference::parse("31-04-2025").unwrap_err();
+        assert!(
+            err.to_string().contains("April 2025 has only 30 days"),
+            "{err}"
+        );
+
+        // Input not in DD-MM-YYYY form stays an invalid time reference
+        for input in [
+            "2025-02-29",
+            "29/02/2025",
+            "someday",
+            "32-01-2025x",
+            "a-02-2025",
+        ] {
+            assert!(
+                matches!(
+                    TimeReference::parse(input),
+                    Err(DjourError::InvalidTimeReference(_))
+                ),
+                "{input}"
+            );
+        }
+    }
+
     #[test]
     fn test_parse_simple_refs() {
         assert_eq!(TimeReference::parse("today").unwrap(), TimeReference::Today);
@@ -221,6 +368,48 @@ mod tests {
         assert!(TimeReference::parse("last invalidday").is_err());
     }
 
+    #[test]
+    fn test_parse_localized_refs() {
+        assert_eq!(
+            TimeReference::parse_with_locale("letzter montag", Locale::German).unwrap(),
+            TimeReference::LastWeekday(Weekday::Mon)
+        );
+        assert_eq!(
+            TimeReference::parse_with_locale("Montag", Locale::German).unwrap(),
+            TimeReference::Weekday(Weekday::Mon)
+        );
+        assert_eq!(
+            TimeReference::parse_with_locale("nächsten freitag", Locale::German).unwrap(),
+            TimeReference::NextWeekday(Weekday::Fri)
+        );
+        assert_eq!(
+            TimeReference::parse_with_locale("gestern", Locale::German).unwrap(),
+            TimeReference::Yesterday
+        );
+        assert_eq!(
+            TimeReference::parse_with_locale("vendredi dernier", Locale::French).unwrap(),
+            TimeReference::LastWeekday(Weekday::Fri)
+        );
+    }
+
+    #[test]
+    fn test_parse_localized_still_accepts_english() {
+        assert_eq!(
+            TimeReference::parse_with_locale("last monday", Locale::German).unwrap(),
+            TimeReference::LastWeekday(Weekday::Mon)
+        );
+        assert_eq!(
+            TimeReference::parse_with_locale("today", Locale::Turkish).unwrap(),
+            TimeReference::Today
+        );
+    }
+
+    #[test]
+    fn test_parse_locale_keywords_require_configured_locale() {
+        assert!(TimeReference::parse("montag").is_err());
+        assert!(TimeReference::parse("letzter montag").is_err());
+    }
+
     #[test]
     fn test_resolve_today() {
         let base = NaiveDate::from_ymd_opt(2025, 1, 17).unwrap();
@@ -336,4 +525,25 @@ mod tests {
         let target = NaiveDate::from_ymd_opt(2024, 12, 25).unwrap();
         assert_eq!(TimeReference::SpecificDate(target).resolve(base), target);
     }
+
+    #[test]
+    fn test_nearest_example() {
+        assert_eq!(
+            TimeReference::nearest_example("2025-01-17").as_deref(),
+            Some("17-01-2025")
+        );
+        assert_eq!(
+            TimeReference::nearest_example("01/17/2025").as_deref(),
+            Some("17-01-2025")
+        );
+        assert_eq!(
+            TimeReference::nearest_example("yesteday").as_deref(),
+            Some("yesterday")
+        );
+        assert_eq!(
+            TimeReference::nearest_example("last fridy").as_deref(),
+            Some("last friday")
+        );
+        assert_eq!(TimeReference::nearest_example("someday"), None);
+    }
 }
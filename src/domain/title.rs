@@ -0,0 +1,104 @@
+//! Note titles, taken from the first heading of a note
+
+use crate::domain::markdown::{atx_heading, html_comment_regions, in_regions, parse_fence_marker};
+use crate::domain::tags::parse_front_matter;
+use crate::domain::tags::parser::strip_tags;
+
+/// Title of a note: the text of its first ATX heading (`# Retro`) without tags.
+///
+/// Front matter, fenced code blocks and HTML comments are skipped, and so is a
+/// heading with nothing left once its tags are removed. Setext headings
+/// (a line underlined with `===` or `---`) are not titles.
+pub fn first_heading(content: &str) -> Option<String> {
+    let body = &content[parse_front_matter(content).map_or(0, |front| front.len)..];
+    let comments = html_comment_regions(body);
+    let mut active_fence: Option<(char, usize)> = None;
+    let mut line_start = 0;
+
+    for line in body.split_inclusive('\n') {
+        let start = line_start;
+        line_start += line.len();
+
+        if let Some((fence_char, min_len)) = active_fence {
+            if matches!(parse_fence_marker(line), Some((c, len)) if c == fence_char && len >= min_len)
+            {
+                active_fence = None;
+            }
+            continue;
+        }
+        if in_regions(&comments, start) {
+            continue;
+        }
+        if let Some(marker) = parse_fence_marker(line) {
+            active_fence = Some(marker);
+            continue;
+        }
+
+        let Some((indent, level)) = atx_heading(line) else {
+            continue;
+        };
+        let title = strip_tags(without_closing_sequence(line[indent + level..].trim()));
+        if !title.is_empty() {
+            return Some(title);
+        }
+    }
+    None
+}
+
+/// Heading text without an optional closing `#` sequence (`Friday ##`)
+fn without_closing_sequence(text: &str) -> &str {
+    let without = text.trim_end_matches('#');
+    if without.is_empty() || without.ends_with([' ', '\t']) {
+        without.trim_end()
+    } else {
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_heading_strips_tags_and_closing_hashes() {
+        assert_eq!(
+            first_heading("#work notes\n\n## Friday 17 January ##\n# Later\n"),
+            Some("Friday 17 January".to_string())
+        );
+        assert_eq!(
+            first_heading("# #retro Sprint 12 retro #team\n"),
+            Some("Sprint 12 retro".to_string())
+        );
+        assert_eq!(
+            first_heading("# Bug #1234 in C#\n"),
+            Some("Bug #1234 in C#".to_string())
+        );
+        // A heading of tags only is no title
+        assert_eq!(
+            first_heading("# #work\n\n## Standup\n"),
+            Some("Standup".to_string())
+        );
+    }
+
+    #[test]
+    fn test_first_heading_skips_front_matter_fences_and_comments() {
+        let content = "---\ntitle: ignored\n---\n```sh\n# not a heading\n```\n<!--\n# draft\n-->\n~~~\n# also code\n~~~\n# Retro\n";
+        assert_eq!(first_heading(content), Some("Retro".to_string()));
+    }
+
+    #[test]
+    fn test_first_heading_ignores_setext_headings() {
+        assert_eq!(
+            first_heading("Planning\n========\n\nText\n\n## Goals\n"),
+            Some("Goals".to_string())
+        );
+        assert_eq!(first_heading("Planning\n--------\n"), None);
+    }
+
+    #[test]
+    fn test_first_heading_without_headings() {
+        assert_eq!(first_heading(""), None);
+        assert_eq!(first_heading("no headings #tag\n#hashtag line\n"), None);
+        assert_eq!(first_heading("```\n# only in code\n"), None);
+    }
+}
@@ -0,0 +1,190 @@
+//! First day and numbering of the weeks of weekly notes
+
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Weekday a journal week starts on (the `week_start` config key)
+///
+/// Under the default ISO numbering a week is labelled with the ISO week of the
+/// Monday it contains, so Monday-start journals keep their filenames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum WeekStart {
+    #[default]
+    Monday,
+    Sunday,
+    Saturday,
+}
+
+impl WeekStart {
+    /// Name as written in config.toml
+    pub fn code(&self) -> &'static str {
+        match self {
+            WeekStart::Monday => "monday",
+            WeekStart::Sunday => "sunday",
+            WeekStart::Saturday => "saturday",
+        }
+    }
+
+    /// Whether this is the default (Monday) start
+    pub fn is_default(&self) -> bool {
+        *self == WeekStart::Monday
+    }
+
+    pub fn weekday(&self) -> Weekday {
+        match self {
+            WeekStart::Monday => Weekday::Mon,
+            WeekStart::Sunday => Weekday::Sun,
+            WeekStart::Saturday => Weekday::Sat,
+        }
+    }
+
+    /// Position of `weekday` within the week (0 = first day)
+    pub fn day_index(&self, weekday: Weekday) -> usize {
+        let start = self.weekday().num_days_from_monday();
+        ((weekday.num_days_from_monday() + 7 - start) % 7) as usize
+    }
+
+    /// First day of the week containing `date`
+    pub fn start_of_week(&self, date: NaiveDate) -> NaiveDate {
+        date - Duration::days(self.day_index(date.weekday()) as i64)
+    }
+
+    /// Monday within the week starting on `start`; its ISO week names the week.
+    pub fn iso_anchor(&self, start: NaiveDate) -> NaiveDate {
+        start + Duration::days(self.day_index(Weekday::Mon) as i64)
+    }
+
+    /// Weekdays in week order, starting with the configured first day
+    pub fn days(&self) -> [Weekday; 7] {
+        let mut day = self.weekday();
+        std::array::from_fn(|_| {
+            let current = day;
+            day = day.succ();
+            current
+        })
+    }
+}
+
+impl FromStr for WeekStart {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "monday" => Ok(WeekStart::Monday),
+            "sunday" => Ok(WeekStart::Sunday),
+            "saturday" => Ok(WeekStart::Saturday),
+            _ => Err(format!(
+                "Invalid week start: '{}'. Valid values are: monday, sunday, saturday",
+                s
+            )),
+        }
+    }
+}
+
+/// How weeks are numbered (the `week_numbering` config key)
+///
+/// `Iso` labels a week with the ISO week of its Monday. `Us` counts from the
+/// week containing January 1 as week 1, labelling each week with the year of
+/// its last day, so the week Sun 31-12-2023..Sat 06-01-2024 is 2024-W01. Both
+/// use the configured `week_start`; US numbering is usually paired with
+/// `week_start = "sunday"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum WeekNumbering {
+    #[default]
+    Iso,
+    Us,
+}
+
+impl WeekNumbering {
+    /// Name as written in config.toml
+    pub fn code(&self) -> &'static str {
+        match self {
+            WeekNumbering::Iso => "iso",
+            WeekNumbering::Us => "us",
+        }
+    }
+
+    /// Whether this is the default (ISO) numbering
+    pub fn is_default(&self) -> bool {
+        *self == WeekNumbering::Iso
+    }
+
+    /// The other numbering system
+    pub fn other(&self) -> WeekNumbering {
+        match self {
+            WeekNumbering::Iso => WeekNumbering::Us,
+            WeekNumbering::Us => WeekNumbering::Iso,
+        }
+    }
+}
+
+impl FromStr for WeekNumbering {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "iso" => Ok(WeekNumbering::Iso),
+            "us" => Ok(WeekNumbering::Us),
+            _ => Err(format!(
+                "Invalid week numbering: '{}'. Valid values are: iso, us",
+                s
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_start_of_week() {
+        // 17-01-2025 is a Friday
+        let friday = date(2025, 1, 17);
+        assert_eq!(WeekStart::Monday.start_of_week(friday), date(2025, 1, 13));
+        assert_eq!(WeekStart::Sunday.start_of_week(friday), date(2025, 1, 12));
+        assert_eq!(WeekStart::Saturday.start_of_week(friday), date(2025, 1, 11));
+        assert_eq!(
+            WeekStart::Sunday.start_of_week(date(2025, 1, 12)),
+            date(2025, 1, 12)
+        );
+    }
+
+    #[test]
+    fn test_iso_anchor_is_monday_in_week() {
+        assert_eq!(
+            WeekStart::Sunday.iso_anchor(date(2025, 1, 12)),
+            date(2025, 1, 13)
+        );
+        assert_eq!(
+            WeekStart::Saturday.iso_anchor(date(2025, 1, 11)),
+            date(2025, 1, 13)
+        );
+        assert_eq!(
+            WeekStart::Monday.iso_anchor(date(2025, 1, 13)),
+            date(2025, 1, 13)
+        );
+    }
+
+    #[test]
+    fn test_days_order() {
+        assert_eq!(WeekStart::Sunday.days()[0], Weekday::Sun);
+        assert_eq!(WeekStart::Sunday.days()[6], Weekday::Sat);
+        assert_eq!(WeekStart::Saturday.day_index(Weekday::Fri), 6);
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("Sunday".parse(), Ok(WeekStart::Sunday));
+        assert!("friday".parse::<WeekStart>().is_err());
+        assert_eq!(" US ".parse(), Ok(WeekNumbering::Us));
+        assert!("iso8601".parse::<WeekNumbering>().is_err());
+    }
+}
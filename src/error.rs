@@ -1,8 +1,28 @@
 //! Error types for djour
 
-use std::path::PathBuf;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
+/// Process exit codes returned by the CLI.
+///
+/// | Code | Meaning |
+/// |---|---|
+/// | 0 | Success |
+/// | 1 | User error (bad query, date, time reference, config value, I/O failure) |
+/// | 2 | Not a djour directory |
+/// | 3 | Nothing matched (compile with no results, `list --fail-empty` with no notes) |
+/// | 4 | Note does not exist (`note path`, `note exists`) |
+/// | 5 | Reminders are overdue (`remind`) |
+pub mod exit_code {
+    pub const SUCCESS: i32 = 0;
+    pub const USER_ERROR: i32 = 1;
+    pub const NOT_A_JOURNAL: i32 = 2;
+    pub const NOTHING_MATCHED: i32 = 3;
+    pub const NOTE_MISSING: i32 = 4;
+    pub const REMINDERS_OVERDUE: i32 = 5;
+}
+
 /// Main error type for djour application
 #[derive(Debug, Error)]
 pub enum DjourError {
@@ -12,9 +32,16 @@ pub enum DjourError {
     #[error("Invalid time reference: {0}")]
     InvalidTimeReference(String),
 
+    /// A DD-MM-YYYY date that is well-formed but not on the calendar (29-02-2025)
+    #[error("Invalid date: {0}")]
+    InvalidDate(String),
+
     #[error("Tag not found: {0}")]
     TagNotFound(String),
 
+    #[error("Nothing matched: {0}")]
+    NothingMatched(String),
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
@@ -27,6 +54,22 @@ pub enum DjourError {
     #[error("Editor error: {0}")]
     Editor(String),
 
+    /// An explicit update check that could not reach or read the release endpoint
+    #[error("Cannot check for updates: {0}")]
+    Update(String),
+
+    /// A desktop notification that could not be shown (`remind --notify`)
+    #[error("Cannot send notification: {0}")]
+    Notification(String),
+
+    /// A write that was refused (`--read-only`) or not permitted by the file system
+    #[error("Cannot {operation} {}: {reason}", path.display())]
+    ReadOnly {
+        operation: String,
+        path: PathBuf,
+        reason: String,
+    },
+
     #[error("TOML deserialization error: {0}")]
     TomlDeserialize(#[from] toml::de::Error),
 
@@ -34,14 +77,41 @@ pub enum DjourError {
     TomlSerialize(#[from] toml::ser::Error),
 }
 
+/// Reason given for writes refused by the `--read-only` flag
+pub const READ_ONLY_FLAG_REASON: &str = "--read-only is set";
+
 impl DjourError {
-    /// Get the exit code for this error
+    /// A write refused by the `--read-only` flag
+    pub fn read_only(operation: &str, path: &Path) -> Self {
+        DjourError::ReadOnly {
+            operation: operation.to_string(),
+            path: path.to_path_buf(),
+            reason: READ_ONLY_FLAG_REASON.to_string(),
+        }
+    }
+
+    /// Error for a failed write of `path`; permission problems become [`DjourError::ReadOnly`]
+    pub fn write_failed(error: std::io::Error, operation: &str, path: &Path) -> Self {
+        let reason = match error.kind() {
+            ErrorKind::PermissionDenied => "permission denied",
+            ErrorKind::ReadOnlyFilesystem => "read-only file system",
+            _ => return DjourError::Io(error),
+        };
+        DjourError::ReadOnly {
+            operation: operation.to_string(),
+            path: path.to_path_buf(),
+            reason: reason.to_string(),
+        }
+    }
+
+    /// Get the exit code for this error (see [`exit_code`])
     pub fn exit_code(&self) -> i32 {
         match self {
-            DjourError::NotDjourDirectory(_) => 2,
-            DjourError::InvalidTimeReference(_) => 3,
-            DjourError::TagNotFound(_) => 4,
-            _ => 1,
+            DjourError::NotDjourDirectory(_) => exit_code::NOT_A_JOURNAL,
+            DjourError::TagNotFound(_) | DjourError::NothingMatched(_) => {
+                exit_code::NOTHING_MATCHED
+            }
+            _ => exit_code::USER_ERROR,
         }
     }
 
@@ -59,18 +129,24 @@ impl DjourError {
                 )
             }
             DjourError::InvalidTimeReference(ref_str) => {
+                let nearest = crate::domain::TimeReference::nearest_example(ref_str)
+                    .map(|example| format!("Nearest valid reference: djour {}\n\n", example))
+                    .unwrap_or_default();
                 format!(
                     "Invalid time reference: '{}'\n\n\
+                    {}\
                     Valid time references:\n\
                     • today, yesterday, tomorrow\n\
                     • monday, tuesday, ..., sunday (most recent)\n\
                     • last monday, next friday, etc.\n\
-                    • Specific dates: YYYY-MM-DD (e.g., 2025-01-17)\n\n\
+                    • Specific dates: DD-MM-YYYY (e.g., 17-01-2025)\n\
+                    • Note names of the journal's mode: YYYY-MM-DD (daily), YYYY-Www (weekly), YYYY-MM (monthly)\n\
+                    • Files in the journal root, with or without .md (e.g., ideas)\n\n\
                     Examples:\n\
                     djour today\n\
                     djour last monday\n\
-                    djour 2025-01-15",
-                    ref_str
+                    djour 15-01-2025",
+                    ref_str, nearest
                 )
             }
             DjourError::TagNotFound(tag) => {
@@ -95,6 +171,21 @@ impl DjourError {
                     msg
                 )
             }
+            DjourError::ReadOnly { reason, .. } => {
+                let last = if reason == READ_ONLY_FLAG_REASON {
+                    "• Drop --read-only to allow writes"
+                } else {
+                    "• Check the permissions of the journal directory, or work on a writable copy"
+                };
+                format!(
+                    "{}\n\n\
+                    Nothing was written. Read-only alternatives:\n\
+                    • djour compile <QUERY> --stdout prints a compilation instead of writing it\n\
+                    • djour list, tags, todos and note path only read the journal\n\
+                    {}",
+                    self, last
+                )
+            }
             DjourError::Config(msg) => {
                 if msg.contains("Invalid mode") {
                     format!(
@@ -145,6 +236,20 @@ mod tests {
         assert!(msg.contains("djour today"));
     }
 
+    #[test]
+    fn test_invalid_time_reference_shows_nearest_example() {
+        let msg =
+            DjourError::InvalidTimeReference("17/01/2025".to_string()).display_with_suggestions();
+        assert!(
+            msg.contains("Nearest valid reference: djour 17-01-2025"),
+            "{}",
+            msg
+        );
+        let msg =
+            DjourError::InvalidTimeReference("someday".to_string()).display_with_suggestions();
+        assert!(!msg.contains("Nearest"), "{}", msg);
+    }
+
     #[test]
     fn test_tag_not_found_suggestions() {
         let err = DjourError::TagNotFound("nonexistent".to_string());
@@ -179,6 +284,55 @@ mod tests {
         assert!(msg.contains("17-01-2025"));
     }
 
+    #[test]
+    fn test_read_only_names_operation_and_path() {
+        let denied = std::io::Error::from(ErrorKind::PermissionDenied);
+        let err = DjourError::write_failed(denied, "write", Path::new("/j/2025-01-17.md"));
+        assert_eq!(
+            err.to_string(),
+            "Cannot write /j/2025-01-17.md: permission denied"
+        );
+        let msg = err.display_with_suggestions();
+        assert!(msg.contains("--stdout"));
+        assert!(msg.contains("permissions of the journal directory"));
+        assert_eq!(err.exit_code(), exit_code::USER_ERROR);
+
+        let flagged = DjourError::read_only("write", Path::new("/j/x.md"));
+        assert!(flagged
+            .display_with_suggestions()
+            .contains("Drop --read-only"));
+
+        let other = std::io::Error::from(ErrorKind::NotFound);
+        assert!(matches!(
+            DjourError::write_failed(other, "write", Path::new("/j")),
+            DjourError::Io(_)
+        ));
+    }
+
+    #[test]
+    fn test_exit_codes() {
+        assert_eq!(
+            DjourError::NotDjourDirectory(PathBuf::from("/tmp")).exit_code(),
+            exit_code::NOT_A_JOURNAL
+        );
+        assert_eq!(
+            DjourError::TagNotFound("work".to_string()).exit_code(),
+            exit_code::NOTHING_MATCHED
+        );
+        assert_eq!(
+            DjourError::NothingMatched("no notes".to_string()).exit_code(),
+            exit_code::NOTHING_MATCHED
+        );
+        assert_eq!(
+            DjourError::InvalidTimeReference("someday".to_string()).exit_code(),
+            exit_code::USER_ERROR
+        );
+        assert_eq!(
+            DjourError::Config("Invalid date format".to_string()).exit_code(),
+            exit_code::USER_ERROR
+        );
+    }
+
     #[test]
     fn test_other_errors_fallback() {
         let err = DjourError::Template("Template error".to_string());
@@ -1,16 +1,261 @@
 //! Configuration management
 
-use crate::domain::JournalMode;
+use crate::domain::tags::syntax::is_valid_tag_name;
+use crate::domain::tags::{CompileStyle, TagParseOptions, TagQuery};
+use crate::domain::{
+    ArchiveLayout, DaySections, ExcludePatterns, JournalMode, Locale, NoteNaming, RenderContext,
+    WeekNumbering, WeekStart,
+};
 use crate::error::{DjourError, Result};
+use crate::infrastructure::resolved::{process_env, resolve_editor, resolve_mode, Resolved};
+use crate::infrastructure::state::StateLocation;
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::Path;
-use std::str::FromStr;
+use std::path::{Path, PathBuf};
+
+fn default_true() -> bool {
+    true
+}
+
+fn is_true(value: &bool) -> bool {
+    *value
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+/// Default distance (in days) from today beyond which creating a note needs confirmation
+pub const DEFAULT_DATE_GUARD_DAYS: u32 = 365;
+
+fn default_date_guard_days() -> u32 {
+    DEFAULT_DATE_GUARD_DAYS
+}
+
+fn is_default_date_guard_days(value: &u32) -> bool {
+    *value == DEFAULT_DATE_GUARD_DAYS
+}
+
+/// Default tag whose blocks compile redacts
+pub const DEFAULT_PRIVATE_TAG: &str = "private";
+
+fn default_private_tag() -> String {
+    DEFAULT_PRIVATE_TAG.to_string()
+}
+
+fn is_default_private_tag(value: &String) -> bool {
+    value == DEFAULT_PRIVATE_TAG
+}
+
+/// Default size cap for each note embedded by `compile --embed-links`
+pub const DEFAULT_EMBED_MAX_BYTES: usize = 4096;
+
+fn default_embed_max_bytes() -> usize {
+    DEFAULT_EMBED_MAX_BYTES
+}
+
+fn is_default_embed_max_bytes(value: &usize) -> bool {
+    *value == DEFAULT_EMBED_MAX_BYTES
+}
+
+/// Default cap on the files one editor request may open
+pub const DEFAULT_EDITOR_MAX_FILES: usize = 5;
+
+fn default_editor_max_files() -> usize {
+    DEFAULT_EDITOR_MAX_FILES
+}
+
+fn is_default_editor_max_files(value: &usize) -> bool {
+    *value == DEFAULT_EDITOR_MAX_FILES
+}
+
+/// Check an `editor_batch_template`: the command must name where the files go
+pub fn check_batch_template(template: &str) -> std::result::Result<(), String> {
+    if template.contains("{files}") {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid value for editor_batch_template: '{}'. Use {{files}} where the file paths go, e.g. 'code {{files}}'",
+            template
+        ))
+    }
+}
+
+/// Suggestion printed with errors about a config.toml that does not load
+const REPAIR_HINT: &str =
+    "Run 'djour config --repair' to keep the valid settings and comment out the rest";
+
+/// `path` made absolute, for messages
+fn absolute(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Describe a config.toml parse error with the file's absolute path and the line and column
+fn parse_error(path: &Path, contents: &str, error: &toml::de::Error) -> DjourError {
+    let location = match error.span() {
+        Some(span) => {
+            let before = &contents[..span.start.min(contents.len())];
+            let line = before.matches('\n').count() + 1;
+            let column = before
+                .rsplit('\n')
+                .next()
+                .map_or(0, |text| text.chars().count())
+                + 1;
+            format!(" at line {}, column {}", line, column)
+        }
+        None => String::new(),
+    };
+    DjourError::Config(format!(
+        "Failed to parse {}{}: {}\n{}",
+        absolute(path).display(),
+        location,
+        error.message(),
+        REPAIR_HINT
+    ))
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub mode: JournalMode,
     pub editor: String,
+
+    /// Run the editor and viewer commands through the system shell
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub editor_shell: bool,
+
+    /// Command that opens compilations (None = the editor)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub viewer: Option<String>,
+
+    /// Most files one request may open in the editor at once
+    #[serde(
+        default = "default_editor_max_files",
+        skip_serializing_if = "is_default_editor_max_files"
+    )]
+    pub editor_max_files: usize,
+
+    /// Shell command opening several files in one launch, `{files}` standing for their quoted paths
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub editor_batch_template: Option<String>,
+
+    /// Display name of the journal, for compilation headers and {JOURNAL_NAME}
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+
+    /// Default author, for compilation headers and {AUTHOR}
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub author: String,
+
+    /// Language for weekday/month names and time reference keywords
+    #[serde(default, skip_serializing_if = "Locale::is_default")]
+    pub locale: Locale,
+
+    /// Whether a `tags` key in note front matter adds file-level tags
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub frontmatter_tags: bool,
+
+    /// Whether `/` in a tag nests it under a parent namespace (`#project/alpha`)
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub hierarchical_tags: bool,
+
+    /// Whether tags may carry a value (`#run:5k`), matched by their bare name too
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub value_tags: bool,
+
+    /// Creating a note further than this many days from today needs confirmation (0 disables)
+    #[serde(
+        default = "default_date_guard_days",
+        skip_serializing_if = "is_default_date_guard_days"
+    )]
+    pub date_guard_days: u32,
+
+    /// Whether `djour <TIME_REF>` without `--open`/`--create` creates a missing note (deprecated)
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub create_on_resolve: bool,
+
+    /// Prefix for dated note filenames (e.g. "journal-" -> journal-2025-01-17.md)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub filename_prefix: String,
+
+    /// Accept daily filenames with a trailing title slug (2025-01-17-planning-day.md)
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub allow_filename_suffix: bool,
+
+    /// Treat `[[target]]` wiki links as links: no tags inside, rewritten by compile
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub wikilinks: bool,
+
+    /// Follow symlinked notes and directories when listing notes
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub follow_symlinks: bool,
+
+    /// How `djour archive` groups notes below `archive/`
+    #[serde(default, skip_serializing_if = "ArchiveLayout::is_default")]
+    pub archive_layout: ArchiveLayout,
+
+    /// First day of weekly notes
+    #[serde(default, skip_serializing_if = "WeekStart::is_default")]
+    pub week_start: WeekStart,
+
+    /// How weekly notes are numbered (iso or us)
+    #[serde(default, skip_serializing_if = "WeekNumbering::is_default")]
+    pub week_numbering: WeekNumbering,
+
+    /// Largest note (in bytes) `compile --embed-links` embeds before truncating (0 = no limit)
+    #[serde(
+        default = "default_embed_max_bytes",
+        skip_serializing_if = "is_default_embed_max_bytes"
+    )]
+    pub embed_max_bytes: usize,
+
+    /// Tag spellings that `--normalize` must never merge with look-alikes
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub normalize_exempt: Vec<String>,
+
+    /// Glob patterns of notes `compile` and `tags` always leave out
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub compile_exclude: Vec<String>,
+
+    /// Notes `list` always shows first, by path relative to the journal root (`djour pin`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pinned: Vec<String>,
+
+    /// Tag whose blocks compile replaces by a redaction line (empty disables)
+    #[serde(
+        default = "default_private_tag",
+        skip_serializing_if = "is_default_private_tag"
+    )]
+    pub private_tag: String,
+
+    /// Keep a tag summary footer at the end of notes djour writes
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub auto_tag_footer: bool,
+
+    /// Where caches and trash live: in `.djour/` or the per-user state directory
+    #[serde(default, skip_serializing_if = "StateLocation::is_default")]
+    pub state_dir: StateLocation,
+
+    /// Check for a newer djour release at most once a day and print a notice
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub update_check: bool,
+
+    /// Release endpoint for update checks (empty: the GitHub releases API)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub update_url: String,
+
+    /// Headers used in compiled documents (`[compile]` table)
+    #[serde(default, skip_serializing_if = "CompileStyle::is_default")]
+    pub compile: CompileStyle,
+
+    /// Named tag queries saved by the user (`[queries]` table)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub queries: BTreeMap<String, String>,
+
+    /// Daily note sections by time of day, e.g. `Morning = "05:00-12:00"` (`[sections]` table)
+    #[serde(default, skip_serializing_if = "DaySections::is_empty")]
+    pub sections: DaySections,
 }
 
 impl Config {
@@ -19,6 +264,37 @@ impl Config {
         Config {
             mode,
             editor: Self::detect_default_editor(),
+            editor_shell: false,
+            viewer: None,
+            editor_max_files: DEFAULT_EDITOR_MAX_FILES,
+            editor_batch_template: None,
+            name: String::new(),
+            author: String::new(),
+            locale: Locale::default(),
+            frontmatter_tags: true,
+            hierarchical_tags: true,
+            value_tags: false,
+            date_guard_days: DEFAULT_DATE_GUARD_DAYS,
+            create_on_resolve: true,
+            filename_prefix: String::new(),
+            allow_filename_suffix: false,
+            wikilinks: false,
+            follow_symlinks: false,
+            archive_layout: ArchiveLayout::default(),
+            week_start: WeekStart::default(),
+            week_numbering: WeekNumbering::default(),
+            embed_max_bytes: DEFAULT_EMBED_MAX_BYTES,
+            normalize_exempt: Vec::new(),
+            compile_exclude: Vec::new(),
+            pinned: Vec::new(),
+            private_tag: default_private_tag(),
+            auto_tag_footer: false,
+            state_dir: StateLocation::default(),
+            update_check: false,
+            update_url: String::new(),
+            compile: CompileStyle::default(),
+            queries: BTreeMap::new(),
+            sections: DaySections::default(),
         }
     }
 
@@ -34,8 +310,97 @@ impl Config {
             }
         })?;
 
-        toml::from_str(&contents)
-            .map_err(|e| DjourError::Config(format!("Failed to parse config.toml: {}", e)))
+        let config: Config =
+            toml::from_str(&contents).map_err(|e| parse_error(&config_path, &contents, &e))?;
+        config.compile.validate().map_err(|e| {
+            DjourError::Config(format!(
+                "{} in {}\n{}",
+                e,
+                absolute(&config_path).display(),
+                REPAIR_HINT
+            ))
+        })?;
+        Ok(config)
+    }
+
+    /// Parse config.toml contents and check every value, collecting all problems found.
+    ///
+    /// A syntax error or an unknown value for an enum key (such as `mode`) stops
+    /// parsing and is reported alone; everything else comes from [`Config::validate`].
+    pub fn parse_validated(contents: &str) -> std::result::Result<Self, Vec<String>> {
+        let config: Config = toml::from_str(contents)
+            .map_err(|e| vec![format!("Failed to parse config.toml: {}", e)])?;
+        let problems = config.validate();
+        if problems.is_empty() {
+            Ok(config)
+        } else {
+            Err(problems)
+        }
+    }
+
+    /// Every problem with the values of a parsed config (empty when valid)
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        if self.editor.trim().is_empty() {
+            problems.push("Invalid value for editor: the editor command is empty".to_string());
+        }
+        if self
+            .viewer
+            .as_ref()
+            .is_some_and(|viewer| viewer.trim().is_empty())
+        {
+            problems.push("Invalid value for viewer: the viewer command is empty".to_string());
+        }
+        if self.editor_max_files == 0 {
+            problems.push(
+                "Invalid value for editor_max_files: '0'. Use a number of files of at least 1"
+                    .to_string(),
+            );
+        }
+        if let Some(template) = &self.editor_batch_template {
+            if let Err(e) = check_batch_template(template) {
+                problems.push(e);
+            }
+        }
+        if self.filename_prefix.contains(['/', '\\']) {
+            problems.push(format!(
+                "Invalid value for filename_prefix: '{}'. The prefix cannot contain path separators",
+                self.filename_prefix
+            ));
+        }
+        for tag in &self.normalize_exempt {
+            if !is_valid_tag_name(tag.strip_prefix('#').unwrap_or(tag)) {
+                problems.push(format!("Invalid tag in normalize_exempt: '{}'", tag));
+            }
+        }
+        if let Err(e) = ExcludePatterns::new(&self.compile_exclude) {
+            problems.push(format!("Invalid value for compile_exclude: {}", e));
+        }
+        if !self.private_tag.is_empty() && !is_valid_tag_name(&self.private_tag) {
+            problems.push(format!(
+                "Invalid value for private_tag: '{}'. Use a tag name, or an empty value to disable redaction",
+                self.private_tag
+            ));
+        }
+        if let Err(message) = self.compile.validate() {
+            problems.push(message);
+        }
+        for (name, query) in &self.queries {
+            if let Err(e) = TagQuery::parse_with_value_tags(query, self.value_tags) {
+                problems.push(format!("Invalid saved query '{}': {}", name, e));
+            }
+        }
+        if self
+            .sections
+            .0
+            .iter()
+            .any(|(heading, _)| heading.trim().is_empty())
+        {
+            problems.push("Invalid [sections] entry: the section heading is empty".to_string());
+        }
+
+        problems
     }
 
     /// Save config to .djour/config.toml in the given directory
@@ -56,39 +421,84 @@ impl Config {
         Ok(())
     }
 
-    /// Get the editor command, checking environment variables first
+    /// Get the editor command: `EDITOR` > `VISUAL` > `editor`
     pub fn get_editor(&self) -> String {
-        std::env::var("EDITOR")
-            .or_else(|_| std::env::var("VISUAL"))
-            .unwrap_or_else(|_| self.editor.clone())
+        self.effective_editor().value
+    }
+
+    /// The editor command and where it came from
+    pub fn effective_editor(&self) -> Resolved<String> {
+        resolve_editor(Some(self), &process_env)
+    }
+
+    /// Get the command that opens compilations, falling back to the editor
+    pub fn get_viewer(&self) -> String {
+        match &self.viewer {
+            Some(viewer) => viewer.clone(),
+            None => self.get_editor(),
+        }
     }
 
     /// Get the effective mode, checking DJOUR_MODE environment variable first
     pub fn get_mode(&self) -> JournalMode {
-        if let Ok(mode_str) = std::env::var("DJOUR_MODE") {
-            if let Ok(mode) = JournalMode::from_str(&mode_str) {
-                return mode;
-            }
-            // If invalid, log warning and fall back to config
-            eprintln!(
-                "Warning: Invalid DJOUR_MODE '{}', using configured mode '{:?}'",
-                mode_str, self.mode
-            );
+        let mode = self.effective_mode();
+        if let Some(warning) = &mode.warning {
+            eprintln!("Warning: {}", warning);
+        }
+        mode.value
+    }
+
+    /// The journal mode and where it came from (`DJOUR_MODE` > `mode`)
+    pub fn effective_mode(&self) -> Resolved<JournalMode> {
+        resolve_mode(Some(self), &process_env)
+    }
+
+    /// Tag whose blocks compile redacts, None when redaction is disabled
+    pub fn private_tag(&self) -> Option<String> {
+        let tag = self.private_tag.trim();
+        let tag = tag.strip_prefix('#').unwrap_or(tag);
+        (!tag.is_empty()).then(|| tag.to_lowercase())
+    }
+
+    /// How notes are parsed for tags
+    pub fn tag_parse_options(&self) -> TagParseOptions {
+        TagParseOptions {
+            frontmatter_tags: self.frontmatter_tags,
+            hierarchical_tags: self.hierarchical_tags,
+            value_tags: self.value_tags,
+            wikilinks: self.wikilinks,
+        }
+    }
+
+    /// Filename naming for the effective mode
+    pub fn naming(&self) -> NoteNaming {
+        self.naming_for(self.get_mode())
+    }
+
+    /// Filename naming for an explicit mode, using the configured prefix/suffix settings
+    pub fn naming_for(&self, mode: JournalMode) -> NoteNaming {
+        NoteNaming::new(
+            mode,
+            self.filename_prefix.clone(),
+            self.allow_filename_suffix,
+        )
+        .with_week_start(self.week_start)
+        .with_week_numbering(self.week_numbering)
+    }
+
+    /// Values a note template for `date` is rendered with
+    pub fn render_context(&self, date: NaiveDate) -> RenderContext<'_> {
+        RenderContext {
+            journal_name: &self.name,
+            author: &self.author,
+            numbering: self.week_numbering,
+            ..RenderContext::new(date, self.locale, self.week_start)
         }
-        self.mode
     }
 
     /// Detect default editor from environment or system
-    fn detect_default_editor() -> String {
-        std::env::var("EDITOR")
-            .or_else(|_| std::env::var("VISUAL"))
-            .unwrap_or_else(|_| {
-                if cfg!(windows) {
-                    "notepad".to_string()
-                } else {
-                    "nano".to_string()
-                }
-            })
+    pub fn detect_default_editor() -> String {
+        resolve_editor(None, &process_env).value
     }
 }
 
@@ -156,6 +566,75 @@ mod tests {
         assert_eq!(loaded.editor, config.editor);
     }
 
+    #[test]
+    fn test_saved_queries_roundtrip() {
+        let temp = TempDir::new().unwrap();
+        let mut config = Config::new(JournalMode::Daily);
+        config
+            .queries
+            .insert("standup".to_string(), "work AND NOT meeting".to_string());
+
+        config.save_to_dir(temp.path()).unwrap();
+        let loaded = Config::load_from_dir(temp.path()).unwrap();
+
+        assert_eq!(
+            loaded.queries.get("standup").map(String::as_str),
+            Some("work AND NOT meeting")
+        );
+    }
+
+    #[test]
+    fn test_empty_queries_not_serialized() {
+        let temp = TempDir::new().unwrap();
+        Config::new(JournalMode::Daily)
+            .save_to_dir(temp.path())
+            .unwrap();
+
+        let raw = fs::read_to_string(temp.path().join(".djour/config.toml")).unwrap();
+        assert!(!raw.contains("queries"));
+    }
+
+    #[test]
+    fn test_compile_table_partial_and_invalid_date_format() {
+        let temp = TempDir::new().unwrap();
+        fs::create_dir(temp.path().join(".djour")).unwrap();
+        let config_path = temp.path().join(".djour/config.toml");
+
+        fs::write(
+            &config_path,
+            "mode = \"daily\"\neditor = \"vim\"\n\n[compile]\ndate_format = \"%Y-%m-%d\"\n",
+        )
+        .unwrap();
+        let loaded = Config::load_from_dir(temp.path()).unwrap();
+        assert_eq!(loaded.compile.date_format, "%Y-%m-%d");
+        assert_eq!(loaded.compile.date_header, "## {DATE}");
+
+        fs::write(
+            &config_path,
+            "mode = \"daily\"\neditor = \"vim\"\n\n[compile]\ndate_format = \"%Y-%\"\n",
+        )
+        .unwrap();
+        let err = Config::load_from_dir(temp.path()).unwrap_err();
+        assert!(err.to_string().contains("compile.date_format"), "{err}");
+    }
+
+    #[test]
+    fn test_validate_collects_every_problem() {
+        let mut config = Config::new(JournalMode::Daily);
+        assert!(config.validate().is_empty());
+
+        config.filename_prefix = "notes/".to_string();
+        config.normalize_exempt = vec!["bad tag".to_string()];
+        config.compile.date_format = "%Y-%".to_string();
+        let problems = config.validate();
+        assert_eq!(problems.len(), 3, "{problems:?}");
+        assert!(problems[0].contains("filename_prefix"));
+
+        let err = Config::parse_validated("mode = \"hourly\"\neditor = \"vim\"\n").unwrap_err();
+        assert_eq!(err.len(), 1);
+        assert!(err[0].starts_with("Failed to parse config.toml"));
+    }
+
     #[test]
     fn test_load_missing_config() {
         let temp = TempDir::new().unwrap();
@@ -170,11 +649,88 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_load_corrupt_config_names_path_position_and_repair() {
+        let temp = TempDir::new().unwrap();
+        fs::create_dir(temp.path().join(".djour")).unwrap();
+        let config_path = temp.path().join(".djour/config.toml");
+        let absolute_path = fs::canonicalize(temp.path())
+            .unwrap()
+            .join(".djour/config.toml");
+
+        let fixtures = [
+            // Dangling quote
+            (
+                "mode = \"daily\"\neditor = \"vim\nname = \"Log\"\n",
+                "at line 2, column 14: invalid basic string",
+            ),
+            // Wrong type for mode
+            ("editor = \"vim\"\nmode = 7\n", "at line 2, column 8"),
+            // Value of an enum key that does not exist
+            (
+                "mode = \"hourly\"\neditor = \"vim\"\n",
+                "at line 1, column 8: unknown variant `hourly`",
+            ),
+        ];
+        for (contents, position) in fixtures {
+            fs::write(&config_path, contents).unwrap();
+            let err = Config::load_from_dir(temp.path()).unwrap_err().to_string();
+            assert!(
+                err.contains(&format!(
+                    "Failed to parse {} {}",
+                    absolute_path.display(),
+                    position
+                )),
+                "{err}"
+            );
+            assert!(err.contains("djour config --repair"), "{err}");
+        }
+
+        // Unknown tables are left alone when loading
+        fs::write(
+            &config_path,
+            "mode = \"daily\"\neditor = \"vim\"\n\n[colors]\naccent = \"blue\"\n",
+        )
+        .unwrap();
+        assert!(Config::load_from_dir(temp.path()).is_ok());
+    }
+
     #[test]
     fn test_get_editor_uses_env() {
         let config = Config {
             mode: JournalMode::Daily,
             editor: "default-editor".to_string(),
+            editor_shell: false,
+            viewer: None,
+            editor_max_files: DEFAULT_EDITOR_MAX_FILES,
+            editor_batch_template: None,
+            name: String::new(),
+            author: String::new(),
+            locale: Locale::default(),
+            frontmatter_tags: true,
+            hierarchical_tags: true,
+            value_tags: false,
+            date_guard_days: DEFAULT_DATE_GUARD_DAYS,
+            create_on_resolve: true,
+            filename_prefix: String::new(),
+            allow_filename_suffix: false,
+            wikilinks: false,
+            follow_symlinks: false,
+            archive_layout: ArchiveLayout::default(),
+            week_start: WeekStart::default(),
+            week_numbering: WeekNumbering::default(),
+            embed_max_bytes: DEFAULT_EMBED_MAX_BYTES,
+            normalize_exempt: Vec::new(),
+            compile_exclude: Vec::new(),
+            pinned: Vec::new(),
+            private_tag: default_private_tag(),
+            auto_tag_footer: false,
+            state_dir: StateLocation::default(),
+            update_check: false,
+            update_url: String::new(),
+            compile: CompileStyle::default(),
+            queries: BTreeMap::new(),
+            sections: DaySections::default(),
         };
 
         // Without environment variables, should use config value
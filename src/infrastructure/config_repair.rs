@@ -0,0 +1,324 @@
+//! Lenient reading of a damaged config.toml for `djour config --repair`
+//!
+//! Lines are taken one statement at a time, so a syntax error only costs the
+//! statement it is in. Each recognized key is then checked on its own (and
+//! each key of a table such as `[compile]`), keeping every value that loads.
+//! Unparseable lines and keys that are unknown or hold a wrong value are
+//! returned so they can be written back as comments.
+
+use crate::domain::JournalMode;
+use crate::error::{DjourError, Result};
+use crate::infrastructure::Config;
+use serde::de::{self, Deserialize, Visitor};
+use std::collections::BTreeSet;
+
+/// Prefix of the lines `--repair` comments out
+pub const REPAIR_COMMENT_PREFIX: &str = "# djour-repair: ";
+
+/// What could be kept from a config.toml
+#[derive(Debug, Clone)]
+pub struct ConfigRepair {
+    /// The config built from every value that loads (defaults for the rest)
+    pub config: Config,
+    /// Original lines that could not be kept, in file order
+    pub commented: Vec<String>,
+}
+
+impl ConfigRepair {
+    /// Whether every line of the file was kept
+    pub fn is_clean(&self) -> bool {
+        self.commented.is_empty()
+    }
+
+    /// The repaired file: the kept values, then the dropped lines as comments
+    pub fn contents(&self) -> Result<String> {
+        let mut contents = toml::to_string_pretty(&self.config)
+            .map_err(|e| DjourError::Config(format!("Failed to serialize config: {}", e)))?;
+        if !self.commented.is_empty() {
+            contents.push('\n');
+            for line in &self.commented {
+                contents.push_str(REPAIR_COMMENT_PREFIX);
+                contents.push_str(line);
+                contents.push('\n');
+            }
+        }
+        Ok(contents)
+    }
+}
+
+/// Read what can be read from config.toml `contents`
+pub fn repair(contents: &str) -> ConfigRepair {
+    let lines: Vec<&str> = contents.lines().collect();
+    let (groups, mut bad) = parse_statements(&lines);
+
+    let kept: String = groups
+        .iter()
+        .flat_map(|group| group.iter())
+        .map(|&i| format!("{}\n", lines[i]))
+        .collect();
+    let table: toml::Table = toml::from_str(&kept).unwrap_or_default();
+    let (config, dropped) = keep_loadable(table);
+
+    // Attribute every kept statement to the key it sets and drop the rejected ones
+    let mut section: Option<String> = None;
+    for group in &groups {
+        let Some(owner) = group
+            .iter()
+            .find_map(|&i| statement_owner(lines[i], &mut section))
+        else {
+            continue;
+        };
+        if dropped.contains(&(owner.0.clone(), None)) || dropped.contains(&owner) {
+            bad.extend(group.iter().copied());
+        }
+    }
+
+    bad.sort_unstable();
+    bad.dedup();
+    let commented = bad
+        .into_iter()
+        .map(|i| lines[i])
+        .filter(|line| !line.trim().is_empty())
+        .map(str::to_string)
+        .collect();
+    ConfigRepair { config, commented }
+}
+
+/// Split `lines` into statements that parse (line indexes per statement) and lines that do not.
+///
+/// A line that does not parse is held back in case the following lines
+/// complete it (a multi-line array or string); once a later line parses on
+/// its own, the held-back lines are given up.
+fn parse_statements(lines: &[&str]) -> (Vec<Vec<usize>>, Vec<usize>) {
+    let parses = |text: &str| toml::from_str::<toml::Table>(text).is_ok();
+    let mut kept = String::new();
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    let mut pending: Vec<usize> = Vec::new();
+    let mut bad = Vec::new();
+
+    for (i, line) in lines.iter().enumerate() {
+        let trimmed = line.trim();
+        if !pending.is_empty() && (trimmed.is_empty() || trimmed.starts_with('#')) {
+            pending.push(i);
+            continue;
+        }
+        let with_pending: String = pending
+            .iter()
+            .map(|&p| format!("{}\n", lines[p]))
+            .chain(std::iter::once(format!("{}\n", line)))
+            .collect();
+        if parses(&format!("{}{}", kept, with_pending)) {
+            kept.push_str(&with_pending);
+            pending.push(i);
+            groups.push(std::mem::take(&mut pending));
+        } else if !pending.is_empty() && parses(&format!("{}{}\n", kept, line)) {
+            bad.append(&mut pending);
+            kept.push_str(line);
+            kept.push('\n');
+            groups.push(vec![i]);
+        } else {
+            pending.push(i);
+        }
+    }
+    bad.append(&mut pending);
+    (groups, bad)
+}
+
+/// A top-level key and, inside a table, the key below it
+type Owner = (String, Option<String>);
+
+/// Build a config from the values of `table` that load, returning the keys that do not
+fn keep_loadable(table: toml::Table) -> (Config, BTreeSet<Owner>) {
+    let fields = config_fields();
+    let mut accepted = toml::Table::try_from(Config::new(JournalMode::default()))
+        .expect("the default config serializes to a table");
+    let mut dropped = BTreeSet::new();
+
+    for (key, value) in table {
+        if !fields.contains(&key.as_str()) {
+            dropped.insert((key, None));
+            continue;
+        }
+        let mut candidate = accepted.clone();
+        candidate.insert(key.clone(), value.clone());
+        if loads(&candidate) {
+            accepted = candidate;
+            continue;
+        }
+        let toml::Value::Table(entries) = value else {
+            dropped.insert((key, None));
+            continue;
+        };
+        // Keep the good entries of a table with a bad one
+        for (sub, entry) in entries {
+            let mut candidate = accepted.clone();
+            let section = candidate
+                .entry(key.clone())
+                .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+            if let toml::Value::Table(section) = section {
+                section.insert(sub.clone(), entry);
+            }
+            if loads(&candidate) {
+                accepted = candidate;
+            } else {
+                dropped.insert((key.clone(), Some(sub)));
+            }
+        }
+    }
+
+    let config = Config::deserialize(toml::Value::Table(accepted))
+        .expect("every kept value was checked to load");
+    (config, dropped)
+}
+
+/// Whether `table` loads the way `Config::load_from_dir` would accept it
+fn loads(table: &toml::Table) -> bool {
+    Config::deserialize(toml::Value::Table(table.clone()))
+        .is_ok_and(|config| config.compile.validate().is_ok())
+}
+
+/// The key a statement line sets, tracking `[table]` headers in `section`
+fn statement_owner(line: &str, section: &mut Option<String>) -> Option<Owner> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    if line.starts_with('[') {
+        let header = line.trim_start_matches('[');
+        let header = header.split(']').next().unwrap_or_default();
+        let mut path = key_path(header);
+        let top = path.remove(0);
+        *section = Some(top.clone());
+        return Some((top, path.into_iter().next()));
+    }
+    let (key, _) = line.split_once('=')?;
+    let mut path = key_path(key);
+    match section {
+        Some(top) => Some((top.clone(), Some(path.remove(0)))),
+        None => {
+            let top = path.remove(0);
+            Some((top, path.into_iter().next()))
+        }
+    }
+}
+
+/// Components of a dotted key, without quotes
+fn key_path(key: &str) -> Vec<String> {
+    key.split('.')
+        .map(|part| {
+            part.trim()
+                .trim_matches(|c| c == '"' || c == '\'')
+                .to_string()
+        })
+        .collect()
+}
+
+/// Names of the top-level keys `Config` reads, as its `Deserialize` impl lists them
+fn config_fields() -> &'static [&'static str] {
+    struct FieldProbe<'a>(&'a mut &'static [&'static str]);
+
+    impl<'de> de::Deserializer<'de> for FieldProbe<'_> {
+        type Error = de::value::Error;
+
+        fn deserialize_any<V: Visitor<'de>>(
+            self,
+            _: V,
+        ) -> std::result::Result<V::Value, Self::Error> {
+            Err(de::Error::custom("not a struct"))
+        }
+
+        fn deserialize_struct<V: Visitor<'de>>(
+            self,
+            _name: &'static str,
+            fields: &'static [&'static str],
+            _: V,
+        ) -> std::result::Result<V::Value, Self::Error> {
+            *self.0 = fields;
+            Err(de::Error::custom("fields recorded"))
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+            bytes byte_buf option unit unit_struct newtype_struct seq tuple
+            tuple_struct map enum identifier ignored_any
+        }
+    }
+
+    let mut fields: &'static [&'static str] = &[];
+    let _ = Config::deserialize(FieldProbe(&mut fields));
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_fields_lists_every_key() {
+        let fields = config_fields();
+        for key in ["mode", "editor", "compile_exclude", "compile", "sections"] {
+            assert!(fields.contains(&key), "{:?}", fields);
+        }
+    }
+
+    #[test]
+    fn test_repair_bad_syntax_keeps_other_lines() {
+        let repair = repair(
+            "mode = \"weekly\"\neditor = \"vim\nname = \"Work log\"\n\n[compile]\ndate_format = \"%d.%m.%Y\"\n",
+        );
+        assert_eq!(repair.commented, vec!["editor = \"vim"]);
+        assert_eq!(repair.config.mode, JournalMode::Weekly);
+        assert_eq!(repair.config.name, "Work log");
+        assert_eq!(repair.config.compile.date_format, "%d.%m.%Y");
+
+        let contents = repair.contents().unwrap();
+        assert!(
+            contents.ends_with("\n# djour-repair: editor = \"vim\n"),
+            "{}",
+            contents
+        );
+        let reloaded: Config = toml::from_str(&contents).unwrap();
+        assert_eq!(reloaded.name, "Work log");
+    }
+
+    #[test]
+    fn test_repair_wrong_type_for_mode_falls_back_to_default() {
+        let repair = repair("mode = 7\neditor = \"nano\"\nwikilinks = true\n");
+        assert_eq!(repair.commented, vec!["mode = 7"]);
+        assert_eq!(repair.config.mode, JournalMode::default());
+        assert_eq!(repair.config.editor, "nano");
+        assert!(repair.config.wikilinks);
+    }
+
+    #[test]
+    fn test_repair_unknown_table_and_bad_table_entry() {
+        let repair = repair(
+            "mode = \"daily\"\neditor = \"vim\"\n\n[colors]\naccent = \"blue\"\n\n[queries]\nwork = \"work AND NOT meeting\"\nbroken = 3\n",
+        );
+        assert_eq!(
+            repair.commented,
+            vec!["[colors]", "accent = \"blue\"", "broken = 3"]
+        );
+        assert_eq!(
+            repair.config.queries.get("work").map(String::as_str),
+            Some("work AND NOT meeting")
+        );
+        assert!(!repair.config.queries.contains_key("broken"));
+    }
+
+    #[test]
+    fn test_repair_keeps_multiline_values() {
+        let repair = repair(
+            "mode = \"daily\"\neditor = \"vim\"\nnormalize_exempt = [\n  \"C++\",\n\n  \"c\",\n]\nname = oops\n",
+        );
+        assert_eq!(repair.commented, vec!["name = oops"]);
+        assert_eq!(repair.config.normalize_exempt, vec!["C++", "c"]);
+    }
+
+    #[test]
+    fn test_repair_of_a_valid_file_is_clean() {
+        let repair = repair("mode = \"monthly\"\neditor = \"vim\"\n# a comment\n");
+        assert!(repair.is_clean());
+        assert_eq!(repair.config.mode, JournalMode::Monthly);
+    }
+}
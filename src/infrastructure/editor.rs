@@ -1,12 +1,64 @@
 //! Editor integration for opening note files
 
 use crate::error::{DjourError, Result};
-use std::path::Path;
-use std::process::Command;
+use crate::infrastructure::config::DEFAULT_EDITOR_MAX_FILES;
+use crate::infrastructure::resolved::EnvLookup;
+use crate::infrastructure::Config;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::iter::Peekable;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus};
+use std::str::Chars;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Which configured command a session runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditorRole {
+    /// `editor`: notes and config are edited with it
+    Editor,
+    /// `viewer`: compilations are opened with it (falls back to the editor)
+    Viewer,
+}
+
+impl EditorRole {
+    fn label(self) -> &'static str {
+        match self {
+            EditorRole::Editor => "editor",
+            EditorRole::Viewer => "viewer",
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            EditorRole::Editor => "Editor",
+            EditorRole::Viewer => "Viewer",
+        }
+    }
+}
+
+/// Editors that open every file named on one command line
+const MULTI_FILE_EDITORS: &[&str] = &[
+    "vim",
+    "nvim",
+    "vi",
+    "gvim",
+    "code",
+    "code-insiders",
+    "codium",
+    "subl",
+];
 
 /// Session for opening files in an external editor
 pub struct EditorSession {
     command: String,
+    role: EditorRole,
+    /// Run the command through the system shell (`editor_shell`)
+    shell: bool,
+    /// Most files `open_all` opens at once (`editor_max_files`)
+    max_files: usize,
+    /// Shell command opening several files, `{files}` standing for them (`editor_batch_template`)
+    batch_template: Option<String>,
 }
 
 impl EditorSession {
@@ -14,54 +66,467 @@ impl EditorSession {
     pub fn new(editor_command: String) -> Self {
         EditorSession {
             command: editor_command,
+            role: EditorRole::Editor,
+            shell: false,
+            max_files: DEFAULT_EDITOR_MAX_FILES,
+            batch_template: None,
         }
     }
 
+    /// Session running the command configured for `role`
+    pub fn for_role(config: &Config, role: EditorRole) -> Self {
+        let command = match role {
+            EditorRole::Editor => config.get_editor(),
+            EditorRole::Viewer => config.get_viewer(),
+        };
+        let batch_template = match role {
+            EditorRole::Editor => config.editor_batch_template.clone(),
+            EditorRole::Viewer => None,
+        };
+        Self::new(command)
+            .with_role(role)
+            .with_shell(config.editor_shell)
+            .with_max_files(config.editor_max_files)
+            .with_batch_template(batch_template)
+    }
+
+    /// Name the session's role in error messages
+    pub fn with_role(mut self, role: EditorRole) -> Self {
+        self.role = role;
+        self
+    }
+
+    /// Hand the command to `sh -c` (`cmd /C` on Windows) instead of splitting it
+    pub fn with_shell(mut self, shell: bool) -> Self {
+        self.shell = shell;
+        self
+    }
+
+    /// Refuse `open_all` with more than `max_files` files
+    pub fn with_max_files(mut self, max_files: usize) -> Self {
+        self.max_files = max_files;
+        self
+    }
+
+    /// Open the files of `open_all` with one shell command, `{files}` replaced by their quoted paths
+    pub fn with_batch_template(mut self, template: Option<String>) -> Self {
+        self.batch_template = template;
+        self
+    }
+
     /// Open a file in the editor and return immediately
     pub fn open(&self, file_path: &Path) -> Result<()> {
-        let (program, args) = self.parse_command();
+        let (program, mut command) = self.command_for(&[file_path])?;
+        command.spawn().map_err(|e| {
+            DjourError::Editor(format!(
+                "Failed to launch {} '{}': {}",
+                self.role.label(),
+                program,
+                e
+            ))
+        })?;
+
+        Ok(())
+    }
+
+    /// Open a file in the editor and wait until the editor exits
+    ///
+    /// Fails when the editor cannot be launched or exits unsuccessfully.
+    pub fn edit(&self, file_path: &Path) -> Result<()> {
+        let (program, status) = self.wait_for(file_path)?;
+        if !status.success() {
+            return Err(DjourError::Editor(format!(
+                "{} '{}' exited with {}",
+                self.role.title(),
+                program,
+                status
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Open several files and wait until the editor is done with them.
+    ///
+    /// With a batch template, or an editor known to take several files (vim,
+    /// code, subl, ...), every file goes to one launch; other editors get the
+    /// files one after another, each waited for. More files than `max_files`
+    /// is an error, before anything is launched.
+    pub fn open_all(&self, paths: &[PathBuf]) -> Result<()> {
+        if paths.len() > self.max_files {
+            return Err(DjourError::Editor(format!(
+                "Refusing to open {} files at once (editor_max_files is {}); narrow the selection",
+                paths.len(),
+                self.max_files
+            )));
+        }
+        let files: Vec<&Path> = paths.iter().map(PathBuf::as_path).collect();
+        match self.batch_command(&files)? {
+            Some((program, mut command)) => {
+                let status = command.status().map_err(|e| {
+                    DjourError::Editor(format!(
+                        "Failed to launch {} '{}': {}",
+                        self.role.label(),
+                        program,
+                        e
+                    ))
+                })?;
+                if !status.success() {
+                    return Err(DjourError::Editor(format!(
+                        "{} '{}' exited with {}",
+                        self.role.title(),
+                        program,
+                        status
+                    )));
+                }
+                Ok(())
+            }
+            None => files.iter().try_for_each(|file| self.edit(file)),
+        }
+    }
+
+    /// Edit `initial` in a temporary file and return what was saved.
+    ///
+    /// The file lives in the system temp dir and is removed afterwards, whatever
+    /// happens. Returns None when the editor exits unsuccessfully (an aborted
+    /// edit); fails when it cannot be launched.
+    pub fn edit_buffer(&self, initial: &str) -> Result<Option<String>> {
+        let buffer = TempBuffer::create(initial)?;
+        let (_, status) = self.wait_for(&buffer.path)?;
+        if !status.success() {
+            return Ok(None);
+        }
+        Ok(Some(fs::read_to_string(&buffer.path)?))
+    }
+
+    /// Run the editor on a file until it exits, returning the program name and exit status
+    fn wait_for(&self, file_path: &Path) -> Result<(String, ExitStatus)> {
+        let (program, mut command) = self.command_for(&[file_path])?;
+        let status = command.status().map_err(|e| {
+            DjourError::Editor(format!(
+                "Failed to launch {} '{}': {}",
+                self.role.label(),
+                program,
+                e
+            ))
+        })?;
+        Ok((program, status))
+    }
+
+    /// One command opening all of `files` (more than one), if the editor takes them together
+    fn batch_command(&self, files: &[&Path]) -> Result<Option<(String, Command)>> {
+        if files.len() < 2 {
+            return Ok(None);
+        }
+        if let Some(template) = &self.batch_template {
+            return Ok(Some((
+                template.clone(),
+                batch_template_command(template, files),
+            )));
+        }
+        let program = if self.shell {
+            self.command
+                .split_whitespace()
+                .next()
+                .unwrap_or_default()
+                .to_string()
+        } else {
+            self.parse_command()?.0
+        };
+        if !accepts_many_files(&program) {
+            return Ok(None);
+        }
+        self.command_for(files).map(Some)
+    }
+
+    /// Build the editor command for files, along with the program name for error messages
+    fn command_for(&self, files: &[&Path]) -> Result<(String, Command)> {
+        if self.shell {
+            return Ok((self.command.clone(), shell_command(&self.command, files)));
+        }
 
-        // Add file path as final argument
+        let (program, args) = self.parse_command()?;
+
+        // Add file paths as final arguments
         let mut all_args = args;
-        all_args.push(file_path.to_string_lossy().to_string());
+        all_args.extend(files.iter().map(|file| file.to_string_lossy().to_string()));
 
         // On Windows, use cmd /c to ensure .bat and .cmd files are found
         #[cfg(windows)]
-        {
+        let command = {
             let mut cmd = Command::new("cmd");
             cmd.arg("/C").arg(&program).args(&all_args);
-            cmd.spawn().map_err(|e| {
-                DjourError::Editor(format!("Failed to launch editor '{}': {}", program, e))
-            })?;
-        }
+            cmd
+        };
 
         // On Unix, use the program directly
         #[cfg(not(windows))]
-        {
-            Command::new(&program)
-                .args(&all_args)
-                .spawn()
-                .map_err(|e| {
-                    DjourError::Editor(format!("Failed to launch editor '{}': {}", program, e))
-                })?;
-        }
+        let command = {
+            let mut cmd = Command::new(&program);
+            cmd.args(&all_args);
+            cmd
+        };
 
-        Ok(())
+        Ok((program, command))
     }
 
-    /// Parse command into program and arguments
-    fn parse_command(&self) -> (String, Vec<String>) {
-        let parts: Vec<&str> = self.command.split_whitespace().collect();
+    /// Parse command into program and arguments, expanding `~` and variables
+    ///
+    /// Variables that are not set expand to nothing, with a warning.
+    fn parse_command(&self) -> Result<(String, Vec<String>)> {
+        let expanded =
+            expand_command(&self.command, &|name| std::env::var(name).ok()).map_err(|e| {
+                DjourError::Editor(format!(
+                    "Invalid {} command '{}': {}",
+                    self.role.label(),
+                    self.command,
+                    e
+                ))
+            })?;
+        for name in &expanded.unset {
+            eprintln!(
+                "Warning: {} command references ${}, which is not set; it expands to nothing",
+                self.role.label(),
+                name
+            );
+        }
 
-        if parts.is_empty() {
+        let mut parts = expanded.words.into_iter();
+        match parts.next() {
+            Some(program) => Ok((program, parts.collect())),
             // Fallback to notepad if command is empty
-            return ("notepad".to_string(), vec![]);
+            None => Ok(("notepad".to_string(), vec![])),
+        }
+    }
+}
+
+/// Whether `program` (a name or a path) is an editor that opens several files at once
+fn accepts_many_files(program: &str) -> bool {
+    let name = Path::new(program)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    MULTI_FILE_EDITORS.contains(&name.as_str())
+}
+
+/// The shell running `command` with each file path appended as one quoted word
+fn shell_command(command: &str, files: &[&Path]) -> Command {
+    let mut script = command.to_string();
+    for file in files {
+        script.push(' ');
+        script.push_str(&shell_quote(file));
+    }
+    shell(script)
+}
+
+/// The shell running `template` with `{files}` replaced by the quoted file paths
+fn batch_template_command(template: &str, files: &[&Path]) -> Command {
+    let quoted: Vec<String> = files.iter().map(|file| shell_quote(file)).collect();
+    shell(template.replace("{files}", &quoted.join(" ")))
+}
+
+/// `path` as one single-quoted `sh` word
+#[cfg(not(windows))]
+fn shell_quote(path: &Path) -> String {
+    format!("'{}'", path.to_string_lossy().replace('\'', "'\\''"))
+}
+
+/// `path` in double quotes for `cmd`
+#[cfg(windows)]
+fn shell_quote(path: &Path) -> String {
+    // `"` cannot occur in Windows paths, so quoting is enough
+    format!("\"{}\"", path.to_string_lossy())
+}
+
+/// `sh -c` running `script`
+#[cfg(not(windows))]
+fn shell(script: String) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").arg(script);
+    cmd
+}
+
+/// `cmd /C` running `script`
+#[cfg(windows)]
+fn shell(script: String) -> Command {
+    use std::os::windows::process::CommandExt;
+
+    let mut cmd = Command::new("cmd");
+    cmd.arg("/C").raw_arg(script);
+    cmd
+}
+
+/// Words of an editor command after expansion, and the unset variables it named
+#[derive(Debug, PartialEq)]
+struct ExpandedCommand {
+    words: Vec<String>,
+    unset: Vec<String>,
+}
+
+/// Split an editor command into words, expanding a leading `~` and `$VAR`/`${VAR}`.
+///
+/// Words are separated by whitespace. `'...'` is taken literally and `"..."`
+/// keeps spaces but still expands variables; backslashes are ordinary
+/// characters so Windows paths work. Values of unquoted variables are split
+/// into words like the rest of the command. `~` starting a word, alone or
+/// before `/`, is the home directory. Unset variables expand to nothing.
+fn expand_command(command: &str, env: EnvLookup) -> std::result::Result<ExpandedCommand, String> {
+    let mut expanded = ExpandedCommand {
+        words: Vec::new(),
+        unset: Vec::new(),
+    };
+    // None until something starts a word, so `""` is an empty word but `$UNSET` none
+    let mut word: Option<String> = None;
+    let lookup = |name: String, unset: &mut Vec<String>| {
+        let value = env(&name);
+        if value.is_none() && !unset.contains(&name) {
+            unset.push(name);
         }
+        value
+    };
 
-        let program = parts[0].to_string();
-        let args = parts[1..].iter().map(|s| s.to_string()).collect();
+    let mut chars = command.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => expanded.words.extend(word.take()),
+            '\'' => {
+                let quoted = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => quoted.push(c),
+                        None => return Err("unclosed single quote".to_string()),
+                    }
+                }
+            }
+            '"' => {
+                word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('$') => match variable_name(&mut chars)? {
+                            Some(name) => {
+                                if let Some(value) = lookup(name, &mut expanded.unset) {
+                                    word.get_or_insert_with(String::new).push_str(&value);
+                                }
+                            }
+                            None => word.get_or_insert_with(String::new).push('$'),
+                        },
+                        Some(c) => word.get_or_insert_with(String::new).push(c),
+                        None => return Err("unclosed double quote".to_string()),
+                    }
+                }
+            }
+            '$' => match variable_name(&mut chars)? {
+                Some(name) => {
+                    for c in lookup(name, &mut expanded.unset)
+                        .unwrap_or_default()
+                        .chars()
+                    {
+                        if c.is_whitespace() {
+                            expanded.words.extend(word.take());
+                        } else {
+                            word.get_or_insert_with(String::new).push(c);
+                        }
+                    }
+                }
+                None => word.get_or_insert_with(String::new).push('$'),
+            },
+            '~' if word.is_none() && ends_tilde_prefix(chars.peek()) => {
+                let home = env(if cfg!(windows) { "USERPROFILE" } else { "HOME" });
+                word = Some(home.unwrap_or_else(|| "~".to_string()));
+            }
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    expanded.words.extend(word);
+
+    Ok(expanded)
+}
 
-        (program, args)
+/// Whether `~` followed by `next` names the home directory
+fn ends_tilde_prefix(next: Option<&char>) -> bool {
+    match next {
+        None | Some('/') => true,
+        Some('\\') => cfg!(windows),
+        Some(c) => c.is_whitespace(),
+    }
+}
+
+/// Read the variable name after a `$`; None when no name follows (a literal `$`)
+fn variable_name(chars: &mut Peekable<Chars>) -> std::result::Result<Option<String>, String> {
+    let is_name_char = |c: char, first: bool| {
+        c == '_' || c.is_ascii_alphabetic() || (!first && c.is_ascii_digit())
+    };
+
+    if chars.peek() == Some(&'{') {
+        chars.next();
+        let mut name = String::new();
+        loop {
+            match chars.next() {
+                Some('}') => break,
+                Some(c) => name.push(c),
+                None => return Err("unclosed '${'".to_string()),
+            }
+        }
+        let valid = name
+            .chars()
+            .enumerate()
+            .all(|(i, c)| is_name_char(c, i == 0));
+        if name.is_empty() || !valid {
+            return Err(format!("invalid variable name '${{{}}}'", name));
+        }
+        return Ok(Some(name));
+    }
+
+    let mut name = String::new();
+    while let Some(&c) = chars.peek() {
+        if !is_name_char(c, name.is_empty()) {
+            break;
+        }
+        name.push(c);
+        chars.next();
+    }
+    Ok((!name.is_empty()).then_some(name))
+}
+
+/// Markdown file in the system temp dir, removed when dropped
+struct TempBuffer {
+    path: PathBuf,
+}
+
+impl TempBuffer {
+    fn create(content: &str) -> Result<Self> {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.subsec_nanos());
+        let mut attempt = 0u32;
+        loop {
+            let path = std::env::temp_dir().join(format!(
+                "djour-capture-{}-{}-{}.md",
+                std::process::id(),
+                nanos,
+                attempt
+            ));
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    // From here on, Drop cleans up even if the write fails
+                    let buffer = TempBuffer { path };
+                    file.write_all(content.as_bytes())?;
+                    return Ok(buffer);
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists && attempt < 100 => {
+                    attempt += 1;
+                }
+                Err(e) => return Err(DjourError::Io(e)),
+            }
+        }
+    }
+}
+
+impl Drop for TempBuffer {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
     }
 }
 
@@ -72,7 +537,7 @@ mod tests {
     #[test]
     fn test_parse_command_simple() {
         let session = EditorSession::new("vim".to_string());
-        let (program, args) = session.parse_command();
+        let (program, args) = session.parse_command().unwrap();
 
         assert_eq!(program, "vim");
         assert_eq!(args.len(), 0);
@@ -81,7 +546,7 @@ mod tests {
     #[test]
     fn test_parse_command_with_args() {
         let session = EditorSession::new("code -w".to_string());
-        let (program, args) = session.parse_command();
+        let (program, args) = session.parse_command().unwrap();
 
         assert_eq!(program, "code");
         assert_eq!(args, vec!["-w"]);
@@ -90,7 +555,7 @@ mod tests {
     #[test]
     fn test_parse_command_multiple_args() {
         let session = EditorSession::new("vim +10 -c startinsert".to_string());
-        let (program, args) = session.parse_command();
+        let (program, args) = session.parse_command().unwrap();
 
         assert_eq!(program, "vim");
         assert_eq!(args, vec!["+10", "-c", "startinsert"]);
@@ -99,19 +564,256 @@ mod tests {
     #[test]
     fn test_parse_command_empty() {
         let session = EditorSession::new("".to_string());
-        let (program, args) = session.parse_command();
+        let (program, args) = session.parse_command().unwrap();
 
         // Empty command falls back to notepad
         assert_eq!(program, "notepad");
         assert_eq!(args.len(), 0);
     }
 
+    #[test]
+    fn test_viewer_role_falls_back_to_editor() {
+        let mut config = Config::new(crate::domain::JournalMode::Daily);
+        config.editor = "vim".to_string();
+        let viewer = EditorSession::for_role(&config, EditorRole::Viewer);
+        assert_eq!(viewer.command, config.get_editor());
+
+        config.viewer = Some("glow -p".to_string());
+        let viewer = EditorSession::for_role(&config, EditorRole::Viewer);
+        assert_eq!(
+            viewer.parse_command().unwrap(),
+            ("glow".to_string(), vec!["-p".to_string()])
+        );
+        assert_eq!(viewer.role, EditorRole::Viewer);
+    }
+
+    #[test]
+    fn test_temp_buffer_is_removed_when_dropped() {
+        let buffer = TempBuffer::create("## 09:30\n").unwrap();
+        let path = buffer.path.clone();
+        assert!(path.starts_with(std::env::temp_dir()));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "## 09:30\n");
+
+        drop(buffer);
+        assert!(!path.exists());
+    }
+
     #[test]
     fn test_parse_command_with_spaces() {
         let session = EditorSession::new("  vim  -n  ".to_string());
-        let (program, args) = session.parse_command();
+        let (program, args) = session.parse_command().unwrap();
 
         assert_eq!(program, "vim");
         assert_eq!(args, vec!["-n"]);
     }
+
+    fn expand(command: &str) -> ExpandedCommand {
+        let env = |name: &str| match name {
+            "HOME" | "USERPROFILE" => Some("/home/ana".to_string()),
+            "EDITOR_DIR" => Some("/opt/editors".to_string()),
+            "EDITOR_FLAGS" => Some("-w  --new-window".to_string()),
+            "SPACED" => Some("My Editors".to_string()),
+            _ => None,
+        };
+        expand_command(command, &env).unwrap()
+    }
+
+    fn words(command: &str) -> Vec<String> {
+        expand(command).words
+    }
+
+    #[test]
+    fn test_expand_tilde() {
+        assert_eq!(words("~/bin/edit"), vec!["/home/ana/bin/edit"]);
+        assert_eq!(words("~"), vec!["/home/ana"]);
+        assert_eq!(
+            words("edit ~ ~/notes"),
+            vec!["edit", "/home/ana", "/home/ana/notes"]
+        );
+        // Only a word-leading `~` alone or before `/` is the home directory
+        assert_eq!(words("~ana/edit a~/b"), vec!["~ana/edit", "a~/b"]);
+        assert_eq!(words("'~/bin/edit'"), vec!["~/bin/edit"]);
+    }
+
+    #[test]
+    fn test_expand_variables() {
+        assert_eq!(words("$EDITOR_DIR/code"), vec!["/opt/editors/code"]);
+        assert_eq!(
+            words("${EDITOR_DIR}/code -w"),
+            vec!["/opt/editors/code", "-w"]
+        );
+        assert_eq!(words("run-${EDITOR_DIR}x"), vec!["run-/opt/editorsx"]);
+        // Unquoted values split into words, quoted ones stay whole
+        assert_eq!(
+            words("code $EDITOR_FLAGS"),
+            vec!["code", "-w", "--new-window"]
+        );
+        assert_eq!(words("\"$SPACED/code\""), vec!["My Editors/code"]);
+        assert_eq!(words("$SPACED"), vec!["My", "Editors"]);
+        // A `$` without a name is literal
+        assert_eq!(words("ed $ $1 a$"), vec!["ed", "$", "$1", "a$"]);
+    }
+
+    #[test]
+    fn test_expand_unset_variables_to_nothing() {
+        let expanded = expand("$NOPE/vim ${NOPE} \"$ALSO_NOPE\" -n");
+        assert_eq!(expanded.words, vec!["/vim", "", "-n"]);
+        assert_eq!(expanded.unset, vec!["NOPE", "ALSO_NOPE"]);
+        assert!(expand("code $EDITOR_FLAGS").unset.is_empty());
+    }
+
+    #[test]
+    fn test_expand_quoting() {
+        assert_eq!(
+            words("'C:\\Program Files\\Editor\\edit.exe' -n"),
+            vec!["C:\\Program Files\\Editor\\edit.exe", "-n"]
+        );
+        assert_eq!(words("'$EDITOR_DIR' \"~/x\""), vec!["$EDITOR_DIR", "~/x"]);
+        assert_eq!(words("a'b c'\"d\"e"), vec!["ab cde"]);
+        assert_eq!(words("vim ''"), vec!["vim", ""]);
+
+        let env = |_: &str| None;
+        assert!(expand_command("vim 'x", &env).is_err());
+        assert!(expand_command("vim \"x", &env).is_err());
+        assert!(expand_command("${HOME", &env).is_err());
+        assert!(expand_command("${A-B}", &env).is_err());
+    }
+
+    #[test]
+    fn test_expand_without_home_keeps_tilde() {
+        let env = |_: &str| None;
+        assert_eq!(
+            expand_command("~/edit", &env).unwrap().words,
+            vec!["~/edit"]
+        );
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_shell_command_quotes_path() {
+        let command = shell_command("code -w", &[Path::new("/tmp/it's here.md")]);
+        let args: Vec<_> = command.get_args().collect();
+        assert_eq!(command.get_program(), "sh");
+        assert_eq!(args[0], "-c");
+        assert_eq!(args[1], "code -w '/tmp/it'\\''s here.md'");
+    }
+
+    #[cfg(not(windows))]
+    fn batch(session: &EditorSession) -> Option<(String, Vec<String>)> {
+        let files = [Path::new("/notes/a.md"), Path::new("/notes/b c.md")];
+        session.batch_command(&files).unwrap().map(|(_, command)| {
+            (
+                command.get_program().to_string_lossy().into_owned(),
+                command
+                    .get_args()
+                    .map(|arg| arg.to_string_lossy().into_owned())
+                    .collect(),
+            )
+        })
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_batch_command_for_known_editors() {
+        let batch_for = |command: &str| batch(&EditorSession::new(command.to_string()));
+        assert_eq!(
+            batch_for("vim"),
+            Some((
+                "vim".to_string(),
+                vec!["/notes/a.md".to_string(), "/notes/b c.md".to_string()]
+            ))
+        );
+        assert_eq!(
+            batch_for("code -w").unwrap().1,
+            vec!["-w", "/notes/a.md", "/notes/b c.md"]
+        );
+        assert_eq!(
+            batch_for("/usr/local/bin/subl").unwrap().0,
+            "/usr/local/bin/subl"
+        );
+        assert!(batch_for("nvim -p").is_some());
+        // Other editors open the files one at a time
+        assert_eq!(batch_for("nano"), None);
+        assert_eq!(batch_for("vimdiff"), None);
+
+        // With editor_shell the command goes to the shell, paths quoted
+        let shell = EditorSession::new("code --wait".to_string()).with_shell(true);
+        assert_eq!(
+            batch(&shell).unwrap().1,
+            vec!["-c", "code --wait '/notes/a.md' '/notes/b c.md'"]
+        );
+        let shell = EditorSession::new("nano".to_string()).with_shell(true);
+        assert_eq!(batch(&shell), None);
+
+        // One file needs no batch
+        let vim = EditorSession::new("vim".to_string());
+        assert!(vim
+            .batch_command(&[Path::new("/notes/a.md")])
+            .unwrap()
+            .is_none());
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_batch_template_expands_files_quoted() {
+        let session = EditorSession::new("nano".to_string())
+            .with_batch_template(Some("tmux new-window vim -p {files}".to_string()));
+        assert_eq!(
+            batch(&session),
+            Some((
+                "sh".to_string(),
+                vec![
+                    "-c".to_string(),
+                    "tmux new-window vim -p '/notes/a.md' '/notes/b c.md'".to_string()
+                ]
+            ))
+        );
+
+        let command = batch_template_command("code {files}", &[Path::new("/tmp/it's.md")]);
+        assert_eq!(
+            command.get_args().nth(1).unwrap(),
+            "code '/tmp/it'\\''s.md'"
+        );
+    }
+
+    #[test]
+    fn test_open_all_refuses_more_than_max_files() {
+        // A launch would fail differently, so the cap is checked first
+        let session = EditorSession::new("djour-test-no-such-editor".to_string()).with_max_files(2);
+        let paths: Vec<PathBuf> = ["a.md", "b.md", "c.md"].iter().map(PathBuf::from).collect();
+        let err = session.open_all(&paths).unwrap_err().to_string();
+        assert!(
+            err.contains("Refusing to open 3 files at once (editor_max_files is 2)"),
+            "{}",
+            err
+        );
+        assert!(err.contains("narrow the selection"), "{}", err);
+        assert!(session.open_all(&[]).is_ok());
+    }
+
+    #[test]
+    fn test_for_role_takes_batch_settings_from_config() {
+        let mut config = Config::new(crate::domain::JournalMode::Daily);
+        assert_eq!(
+            EditorSession::for_role(&config, EditorRole::Editor).max_files,
+            DEFAULT_EDITOR_MAX_FILES
+        );
+        config.editor_max_files = 8;
+        config.editor_batch_template = Some("code {files}".to_string());
+        let editor = EditorSession::for_role(&config, EditorRole::Editor);
+        assert_eq!(editor.max_files, 8);
+        assert_eq!(editor.batch_template.as_deref(), Some("code {files}"));
+        // The template is for the editor only
+        assert!(EditorSession::for_role(&config, EditorRole::Viewer)
+            .batch_template
+            .is_none());
+    }
+
+    #[test]
+    fn test_for_role_uses_editor_shell() {
+        let mut config = Config::new(crate::domain::JournalMode::Daily);
+        assert!(!EditorSession::for_role(&config, EditorRole::Editor).shell);
+        config.editor_shell = true;
+        assert!(EditorSession::for_role(&config, EditorRole::Viewer).shell);
+    }
 }
@@ -1,9 +1,22 @@
 //! Infrastructure layer - External I/O and persistence
 
 pub mod config;
+pub mod config_repair;
 pub mod editor;
+pub mod notifications;
 pub mod repository;
+pub mod resolved;
+pub mod rules;
+pub mod state;
+pub mod update;
+pub mod user_config;
 
 pub use config::Config;
-pub use editor::EditorSession;
+pub use config_repair::ConfigRepair;
+pub use editor::{EditorRole, EditorSession};
+pub use notifications::{DesktopNotifier, MemoryNotifier, Notifier};
 pub use repository::{FileSystemRepository, JournalRepository, NoteEntry};
+pub use resolved::{Resolved, Source};
+pub use rules::{CompileRule, RuleSet};
+pub use state::{StateLocation, StatePaths, STATE_SUBDIRS};
+pub use user_config::JournalRegistry;
@@ -0,0 +1,49 @@
+//! Desktop notifications (`djour remind --notify`)
+//!
+//! Showing them needs the `notifications` cargo feature; without it every
+//! send fails. [`MemoryNotifier`] keeps notifications instead of showing them.
+
+use crate::error::{DjourError, Result};
+
+/// Where notifications go
+pub trait Notifier {
+    /// Show one notification
+    fn send(&mut self, summary: &str, body: &str) -> Result<()>;
+}
+
+/// Notifications shown by the desktop's notification service
+#[derive(Debug, Default)]
+pub struct DesktopNotifier;
+
+impl Notifier for DesktopNotifier {
+    #[cfg(feature = "notifications")]
+    fn send(&mut self, summary: &str, body: &str) -> Result<()> {
+        notify_rust::Notification::new()
+            .appname("djour")
+            .summary(summary)
+            .body(body)
+            .show()
+            .map(|_| ())
+            .map_err(|e| DjourError::Notification(e.to_string()))
+    }
+
+    #[cfg(not(feature = "notifications"))]
+    fn send(&mut self, _summary: &str, _body: &str) -> Result<()> {
+        Err(DjourError::Notification(
+            "this djour was built without the notifications feature".to_string(),
+        ))
+    }
+}
+
+/// Notifications kept in memory, as `(summary, body)`
+#[derive(Debug, Default)]
+pub struct MemoryNotifier {
+    pub sent: Vec<(String, String)>,
+}
+
+impl Notifier for MemoryNotifier {
+    fn send(&mut self, summary: &str, body: &str) -> Result<()> {
+        self.sent.push((summary.to_string(), body.to_string()));
+        Ok(())
+    }
+}
@@ -1,23 +1,114 @@
 //! File system repository
 
-use crate::domain::JournalMode;
+use crate::domain::{path_date_from_components, Diagnostics, JournalMode, NoteNaming};
 use crate::error::{DjourError, Result};
+use crate::infrastructure::resolved::{process_env, EnvLookup, Resolved, Source};
+use crate::infrastructure::state::StatePaths;
+use crate::infrastructure::user_config::JournalRegistry;
 use crate::infrastructure::Config;
-use chrono::NaiveDate;
+use chrono::{DateTime, Local, NaiveDate};
+use std::collections::hash_map::RandomState;
+use std::collections::HashSet;
+use std::fmt;
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::hash::BuildHasher;
+use std::io::{self, BufWriter, Write as _};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime};
 use walkdir::WalkDir;
 
+/// Marker in the names of [`FileSystemRepository::write_note_atomic`]'s temp
+/// files: `<name>.djour-tmp-<pid>-<suffix>`
+const TEMP_FILE_MARKER: &str = ".djour-tmp-";
+
+/// Age after which a temp file is taken to be left by an interrupted write;
+/// a running write renames its temp file within moments
+pub const ORPHANED_TEMP_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// Removes a temp file when dropped, unless disarmed once it was renamed into place
+struct TempFileGuard<'a> {
+    path: &'a Path,
+    armed: bool,
+}
+
+impl Drop for TempFileGuard<'_> {
+    fn drop(&mut self) {
+        if self.armed {
+            let _ = fs::remove_file(self.path);
+        }
+    }
+}
+
+/// Whether `name` is a temp file name of [`FileSystemRepository::write_note_atomic`]
+fn is_temp_file_name(name: &str) -> bool {
+    let Some((note, suffix)) = name.rsplit_once(TEMP_FILE_MARKER) else {
+        return false;
+    };
+    let mut parts = suffix.splitn(2, '-');
+    let pid = parts.next().unwrap_or_default();
+    let random = parts.next();
+    !note.is_empty()
+        && !pid.is_empty()
+        && pid.bytes().all(|b| b.is_ascii_digit())
+        && random.is_none_or(|random| {
+            !random.is_empty() && random.bytes().all(|b| b.is_ascii_hexdigit())
+        })
+}
+
+/// `fmt::Write` over an `io::Write`, keeping the I/O error that stopped it
+struct IoWriter<W: io::Write> {
+    inner: W,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write> fmt::Write for IoWriter<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|e| {
+            self.error = Some(e);
+            fmt::Error
+        })
+    }
+}
+
 /// Represents a note file with its metadata
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct NoteEntry {
     pub filename: String,
     pub date: Option<NaiveDate>,
+    /// File modification time, when the file system reports one
+    pub modified: Option<SystemTime>,
+    /// First heading of the note (see [`crate::domain::first_heading`]); only set by
+    /// commands that read the note for it
+    pub title: Option<String>,
 }
 
 impl NoteEntry {
     pub fn new(filename: String, date: Option<NaiveDate>) -> Self {
-        NoteEntry { filename, date }
+        NoteEntry {
+            filename,
+            date,
+            modified: None,
+            title: None,
+        }
+    }
+
+    pub fn with_modified(mut self, modified: Option<SystemTime>) -> Self {
+        self.modified = modified;
+        self
+    }
+
+    /// Modification time in local time
+    pub fn modified_local(&self) -> Option<DateTime<Local>> {
+        self.modified.map(DateTime::<Local>::from)
+    }
+
+    /// Whether the file was modified on or after local midnight of `since`.
+    ///
+    /// Notes without a modification time never match.
+    pub fn changed_since(&self, since: NaiveDate) -> bool {
+        self.modified_local()
+            .is_some_and(|modified| modified.date_naive() >= since)
     }
 }
 
@@ -43,34 +134,192 @@ pub trait JournalRepository {
 #[derive(Debug, Clone)]
 pub struct FileSystemRepository {
     pub root: PathBuf,
+    /// Refuse every write (`--read-only`)
+    read_only: bool,
+    /// Follow symlinked files and directories when listing notes (`follow_symlinks`)
+    follow_symlinks: bool,
 }
 
 impl FileSystemRepository {
     /// Create a new repository with the given root directory
     pub fn new(root: PathBuf) -> Self {
-        FileSystemRepository { root }
+        FileSystemRepository {
+            root,
+            read_only: false,
+            follow_symlinks: false,
+        }
+    }
+
+    /// A discovered journal at `root`, with listing settings from its config
+    /// (defaults when the config cannot be read; commands report that themselves)
+    fn open(root: PathBuf) -> Self {
+        let follow_symlinks =
+            Config::load_from_dir(&root).is_ok_and(|config| config.follow_symlinks);
+        FileSystemRepository::new(root).with_follow_symlinks(follow_symlinks)
+    }
+
+    /// Follow symlinks to notes and directories when listing notes.
+    ///
+    /// Off by default: symlinked notes in the root and symlinked directories are
+    /// skipped. When on, a directory reached a second time (a symlink loop, or two
+    /// links to one directory) is skipped with a warning.
+    pub fn with_follow_symlinks(mut self, follow_symlinks: bool) -> Self {
+        self.follow_symlinks = follow_symlinks;
+        self
+    }
+
+    /// Refuse every write with [`DjourError::ReadOnly`] when `read_only` is set
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Whether writes are refused (`--read-only`)
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Fail with [`DjourError::ReadOnly`] if `--read-only` is set.
+    ///
+    /// Commands that write through other means than this repository (an
+    /// editor, a relocated state directory) check this before starting.
+    pub fn ensure_writable(&self, operation: &str, path: &Path) -> Result<()> {
+        if self.read_only {
+            return Err(DjourError::read_only(operation, path));
+        }
+        Ok(())
     }
 
-    /// Discover journal root by walking up from current directory
-    /// First checks DJOUR_ROOT environment variable, then falls back to discovery
+    /// Run a write of `path`, reporting permission problems as [`DjourError::ReadOnly`]
+    fn write_op<T>(
+        &self,
+        operation: &str,
+        path: &Path,
+        write: impl FnOnce() -> std::io::Result<T>,
+    ) -> Result<T> {
+        self.ensure_writable(operation, path)?;
+        write().map_err(|e| DjourError::write_failed(e, operation, path))
+    }
+
+    /// Discover the journal root: `DJOUR_JOURNAL` > `DJOUR_ROOT` > walking up
+    /// from the current directory
     pub fn discover() -> Result<Self> {
-        // 1. Check DJOUR_ROOT environment variable first
-        if let Ok(root_path) = std::env::var("DJOUR_ROOT") {
-            let path = PathBuf::from(root_path);
+        Self::discover_journal(None)
+    }
+
+    /// Discover the journal root, optionally by registered journal name.
+    ///
+    /// Precedence: `journal` (the `--journal` flag) > `DJOUR_JOURNAL` >
+    /// `DJOUR_ROOT` > walking up from the current directory.
+    pub fn discover_journal(journal: Option<&str>) -> Result<Self> {
+        let current_dir = std::env::current_dir()?;
+        let root = Self::resolve_root(journal, &process_env, &current_dir)?;
+        Ok(FileSystemRepository::open(root.value))
+    }
+
+    /// The journal root `discover_journal` would open and where it came from,
+    /// looking variables up in `env` and walking up from `current_dir`.
+    pub fn resolve_root(
+        journal: Option<&str>,
+        env: EnvLookup,
+        current_dir: &Path,
+    ) -> Result<Resolved<PathBuf>> {
+        // 1. Named journal from --journal or DJOUR_JOURNAL, resolved through the registry
+        let named = match journal {
+            Some(name) => Some((name.to_string(), Source::Flag("--journal"))),
+            None => env("DJOUR_JOURNAL").map(|name| (name, Source::Env("DJOUR_JOURNAL"))),
+        };
+        if let Some((name, source)) = named {
+            return Ok(Resolved::new(Self::registered_root(&name)?, source));
+        }
+
+        // 2. DJOUR_ROOT environment variable
+        if let Some(root_path) = env("DJOUR_ROOT") {
+            let path = Self::root_from_env(&root_path, env, current_dir);
             if Self::has_djour_dir(&path) {
-                return Ok(FileSystemRepository::new(path));
+                return Ok(Resolved::new(path, Source::Env("DJOUR_ROOT")));
+            }
+            // Name the value as set too when it differs from the resolved path
+            let shown = if Path::new(&root_path) == path {
+                format!("'{}'", path.display())
             } else {
-                return Err(DjourError::Config(format!(
-                    "DJOUR_ROOT is set to '{}' but no .djour directory found. \
+                format!("'{}' (resolved to '{}')", root_path, path.display())
+            };
+            return Err(DjourError::Config(if path.exists() {
+                format!(
+                    "DJOUR_ROOT is set to {} but no .djour directory found. \
                     Run 'djour init' in that directory or unset DJOUR_ROOT.",
-                    path.display()
-                )));
+                    shown
+                )
+            } else {
+                format!(
+                    "DJOUR_ROOT is set to {} which does not exist. \
+                    Check the path for a typo or unset DJOUR_ROOT.",
+                    shown
+                )
+            }));
+        }
+
+        // 3. Fall back to walking up from current directory
+        let root = Self::discover_from(current_dir)?.root;
+        Ok(Resolved::new(root, Source::Discovered))
+    }
+
+    /// The journal root a `DJOUR_ROOT` value names: a leading `~` is expanded
+    /// to the home directory, a relative path is taken from `current_dir`,
+    /// `.` components and trailing separators are dropped, and a path to the
+    /// `.djour` directory itself stands for its parent.
+    fn root_from_env(value: &str, env: EnvLookup, current_dir: &Path) -> PathBuf {
+        let home_relative = value
+            .strip_prefix('~')
+            .filter(|rest| rest.is_empty() || rest.starts_with(['/', std::path::MAIN_SEPARATOR]));
+        let home = env(if cfg!(windows) { "USERPROFILE" } else { "HOME" });
+        let path = match (home_relative, home) {
+            (Some(rest), Some(home)) => {
+                PathBuf::from(home).join(rest.trim_start_matches(['/', std::path::MAIN_SEPARATOR]))
+            }
+            _ => PathBuf::from(value),
+        };
+
+        let path: PathBuf = current_dir
+            .join(path)
+            .components()
+            .filter(|component| *component != Component::CurDir)
+            .collect();
+        match path.parent() {
+            Some(parent) if path.file_name() == Some(".djour".as_ref()) && path.is_dir() => {
+                parent.to_path_buf()
             }
+            _ => path,
         }
+    }
 
-        // 2. Fall back to walking up from current directory
-        let current_dir = std::env::current_dir()?;
-        Self::discover_from(&current_dir)
+    /// Root of the journal registered under `name` in the user journal registry
+    fn registered_root(name: &str) -> Result<PathBuf> {
+        let registry = JournalRegistry::load()?;
+        let path = registry.get(name).ok_or_else(|| {
+            let known: Vec<&str> = registry.journals.keys().map(String::as_str).collect();
+            DjourError::Config(format!(
+                "Unknown journal '{}'. Registered journals: {}. Add one with 'djour journal add {} <PATH>'.",
+                name,
+                if known.is_empty() {
+                    "none".to_string()
+                } else {
+                    known.join(", ")
+                },
+                name
+            ))
+        })?;
+        if !Self::has_djour_dir(path) {
+            return Err(DjourError::Config(format!(
+                "Journal '{}' is registered at '{}' but no .djour directory found there. \
+                Run 'djour init' in that directory or 'djour journal remove {}'.",
+                name,
+                path.display(),
+                name
+            )));
+        }
+        Ok(path.to_path_buf())
     }
 
     /// Discover journal root by walking up from a specific starting directory
@@ -79,7 +328,7 @@ impl FileSystemRepository {
 
         loop {
             if Self::has_djour_dir(&current) {
-                return Ok(FileSystemRepository::new(current));
+                return Ok(FileSystemRepository::open(current));
             }
 
             // Try to move to parent directory
@@ -109,7 +358,12 @@ impl JournalRepository for FileSystemRepository {
     }
 
     fn save_config(&self, config: &Config) -> Result<()> {
-        config.save_to_dir(&self.root)
+        let path = self.config_path();
+        self.ensure_writable("write", &path)?;
+        config.save_to_dir(&self.root).map_err(|e| match e {
+            DjourError::Io(e) => DjourError::write_failed(e, "write", &path),
+            other => other,
+        })
     }
 
     fn is_initialized(&self) -> bool {
@@ -126,9 +380,64 @@ impl JournalRepository for FileSystemRepository {
             )));
         }
 
-        fs::create_dir(&djour_dir)?;
+        self.write_op("create", &djour_dir, || fs::create_dir(&djour_dir))
+    }
+}
+
+// Config file operations (not part of trait - filesystem-specific)
+impl FileSystemRepository {
+    /// Path of .djour/config.toml
+    pub fn config_path(&self) -> PathBuf {
+        self.root.join(".djour").join("config.toml")
+    }
+
+    /// Cache and trash directories for this journal, per the `state_dir` setting.
+    ///
+    /// Features keeping machine-local state must get their directories here;
+    /// it also moves state left behind by a previous `state_dir` setting.
+    pub fn state_paths(&self, config: &Config) -> Result<StatePaths> {
+        StatePaths::for_journal(&self.root, config.state_dir)
+    }
+
+    /// Path of the backup written by [`FileSystemRepository::backup_config`]
+    pub fn config_backup_path(&self) -> PathBuf {
+        self.root.join(".djour").join("config.toml.bak")
+    }
+
+    /// Copy config.toml to config.toml.bak, replacing an older backup
+    pub fn backup_config(&self) -> Result<()> {
+        let backup = self.config_backup_path();
+        self.write_op("write", &backup, || fs::copy(self.config_path(), &backup))?;
         Ok(())
     }
+
+    /// Copy config.toml to config.toml.broken-<timestamp> and return the copy's path
+    pub fn backup_broken_config(&self) -> Result<PathBuf> {
+        let timestamp = Local::now().format("%Y%m%d-%H%M%S");
+        let backup = self
+            .root
+            .join(".djour")
+            .join(format!("config.toml.broken-{}", timestamp));
+        self.write_op("write", &backup, || fs::copy(self.config_path(), &backup))?;
+        Ok(backup)
+    }
+
+    /// Put config.toml.bak back in place of config.toml (the backup is consumed)
+    pub fn restore_config_backup(&self) -> Result<()> {
+        let path = self.config_path();
+        self.write_op("restore", &path, || {
+            fs::rename(self.config_backup_path(), &path)
+        })
+    }
+
+    /// Remove config.toml.bak if it exists
+    pub fn discard_config_backup(&self) -> Result<()> {
+        let backup = self.config_backup_path();
+        self.write_op("remove", &backup, || match fs::remove_file(&backup) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        })
+    }
 }
 
 // Note operations (not part of trait - filesystem-specific)
@@ -153,20 +462,107 @@ impl FileSystemRepository {
     pub fn write_note(&self, filename: &str, content: &str) -> Result<()> {
         let path = self.root.join(filename);
 
-        // Create parent directories if needed
-        if let Some(parent) = path.parent() {
-            if !parent.exists() {
+        self.write_op("write", &path, || {
+            // Create parent directories if needed
+            if let Some(parent) = path.parent() {
+                if !parent.exists() {
+                    fs::create_dir_all(parent)?;
+                }
+            }
+
+            fs::write(&path, content)
+        })
+    }
+
+    /// Create a note holding `content`, unless a file is already there
+    ///
+    /// Checking and creating is one step (`create_new`), so when several
+    /// processes create the same note at once exactly one writes it and the
+    /// others leave it alone. Returns whether this call created the note.
+    pub fn create_note_exclusive(&self, filename: &str, content: &str) -> Result<bool> {
+        let path = self.root.join(filename);
+
+        self.write_op("create", &path, || {
+            if let Some(parent) = path.parent() {
+                if !parent.exists() {
+                    fs::create_dir_all(parent)?;
+                }
+            }
+
+            let mut file = match fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+            {
+                Ok(file) => file,
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => return Ok(false),
+                Err(e) => return Err(e),
+            };
+            file.write_all(content.as_bytes())?;
+            Ok(true)
+        })
+    }
+
+    /// Write note content produced by `write` through a buffered file, so
+    /// large output is never held in memory whole
+    pub fn write_note_streamed(
+        &self,
+        filename: &str,
+        write: impl FnOnce(&mut dyn fmt::Write) -> fmt::Result,
+    ) -> Result<()> {
+        let path = self.root.join(filename);
+
+        self.write_op("write", &path, || {
+            if let Some(parent) = path.parent() {
+                if !parent.exists() {
+                    fs::create_dir_all(parent)?;
+                }
+            }
+
+            let mut out = IoWriter {
+                inner: BufWriter::new(fs::File::create(&path)?),
+                error: None,
+            };
+            if write(&mut out).is_err() {
+                return Err(out
+                    .error
+                    .unwrap_or_else(|| io::Error::other("formatting failed")));
+            }
+            out.inner.flush()
+        })
+    }
+
+    /// Create a new, empty directory named `dir`, or `dir-2`, `dir-3`, ...
+    /// when that name is taken. Returns the name of the directory created.
+    pub fn create_dir_unique(&self, dir: &str) -> Result<String> {
+        let base = self.root.join(dir);
+
+        self.write_op("create", &base, || {
+            if let Some(parent) = base.parent() {
                 fs::create_dir_all(parent)?;
             }
-        }
 
-        fs::write(&path, content).map_err(DjourError::Io)
+            let mut attempt = 1;
+            loop {
+                let name = match attempt {
+                    1 => dir.to_string(),
+                    n => format!("{}-{}", dir, n),
+                };
+                match fs::create_dir(self.root.join(&name)) {
+                    Ok(()) => return Ok(name),
+                    Err(e) if e.kind() == io::ErrorKind::AlreadyExists && attempt < 100 => {
+                        attempt += 1;
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+        })
     }
 
     /// Create a directory (and parents) relative to the repository root.
     pub fn create_dir_all(&self, dir: &str) -> Result<()> {
         let path = self.root.join(dir);
-        fs::create_dir_all(path).map_err(DjourError::Io)
+        self.write_op("create", &path, || fs::create_dir_all(&path))
     }
 
     /// Copy a note file (relative paths) within the repository.
@@ -181,13 +577,15 @@ impl FileSystemRepository {
             )));
         }
 
-        if let Some(parent) = to_path.parent() {
-            if !parent.exists() {
-                fs::create_dir_all(parent)?;
+        self.write_op("write", &to_path, || {
+            if let Some(parent) = to_path.parent() {
+                if !parent.exists() {
+                    fs::create_dir_all(parent)?;
+                }
             }
-        }
 
-        fs::copy(from_path, to_path)?;
+            fs::copy(&from_path, &to_path)
+        })?;
         Ok(())
     }
 
@@ -210,14 +608,15 @@ impl FileSystemRepository {
             )));
         }
 
-        if let Some(parent) = to_path.parent() {
-            if !parent.exists() {
-                fs::create_dir_all(parent)?;
+        self.write_op("move", &from_path, || {
+            if let Some(parent) = to_path.parent() {
+                if !parent.exists() {
+                    fs::create_dir_all(parent)?;
+                }
             }
-        }
 
-        fs::rename(from_path, to_path)?;
-        Ok(())
+            fs::rename(&from_path, &to_path)
+        })
     }
 
     /// Write note content using a best-effort atomic replace:
@@ -227,30 +626,136 @@ impl FileSystemRepository {
     pub fn write_note_atomic(&self, filename: &str, content: &str) -> Result<()> {
         let path = self.root.join(filename);
 
-        if let Some(parent) = path.parent() {
-            if !parent.exists() {
-                fs::create_dir_all(parent)?;
+        self.write_op("write", &path, || {
+            if let Some(parent) = path.parent() {
+                if !parent.exists() {
+                    fs::create_dir_all(parent)?;
+                }
+            }
+
+            // The pid tells processes apart, the suffix threads and calls within one
+            static WRITES: AtomicU64 = AtomicU64::new(0);
+            let suffix = RandomState::new().hash_one(WRITES.fetch_add(1, Ordering::Relaxed));
+            let tmp_name = format!(
+                "{}{}{}-{:016x}",
+                path.file_name()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or("note.md"),
+                TEMP_FILE_MARKER,
+                std::process::id(),
+                suffix
+            );
+            let tmp_path = path.with_file_name(tmp_name);
+            let mut guard = TempFileGuard {
+                path: &tmp_path,
+                armed: true,
+            };
+
+            fs::write(&tmp_path, content)?;
+
+            if path.exists() {
+                // Best-effort atomic-ish replacement; we rely on archive backups for rollback.
+                fs::remove_file(&path)?;
+                // With the old note gone the temp file holds the only copy of
+                // the content, so a failed rename must leave it in place
+                guard.armed = false;
+            }
+
+            fs::rename(&tmp_path, &path)?;
+            guard.armed = false;
+            Ok(())
+        })
+    }
+
+    /// Temp files of [`write_note_atomic`](Self::write_note_atomic) calls that
+    /// never finished: repository-relative paths (with `/` separators), sorted,
+    /// of those last modified more than [`ORPHANED_TEMP_AGE`] ago.
+    ///
+    /// Younger ones may belong to a write still running and are left out.
+    /// `.git/` is not searched and symlinks are not followed.
+    pub fn find_orphaned_temp_files(&self) -> Result<Vec<String>> {
+        self.find_temp_files_older_than(ORPHANED_TEMP_AGE)
+    }
+
+    fn find_temp_files_older_than(&self, age: Duration) -> Result<Vec<String>> {
+        let now = SystemTime::now();
+        let mut files = Vec::new();
+        let walker = WalkDir::new(&self.root)
+            .into_iter()
+            .filter_entry(|entry| entry.depth() == 0 || entry.file_name() != ".git");
+        for entry in walker {
+            let entry = entry.map_err(|e| DjourError::Io(e.into()))?;
+            if !entry.file_type().is_file()
+                || !entry.file_name().to_str().is_some_and(is_temp_file_name)
+            {
+                continue;
+            }
+            // A file without a readable mtime counts as old
+            let old = entry
+                .metadata()
+                .ok()
+                .and_then(|metadata| metadata.modified().ok())
+                .and_then(|modified| now.duration_since(modified).ok())
+                .is_none_or(|elapsed| elapsed >= age);
+            if !old {
+                continue;
+            }
+            let Ok(rel) = entry.path().strip_prefix(&self.root) else {
+                continue;
+            };
+            if let Some(filename) = Self::normalize_relative_path(rel) {
+                files.push(filename);
             }
         }
+        files.sort();
+        Ok(files)
+    }
 
-        let tmp_name = format!(
-            "{}.djour-tmp-{}",
-            path.file_name()
-                .and_then(|s| s.to_str())
-                .unwrap_or("note.md"),
-            std::process::id()
-        );
-        let tmp_path = path.with_file_name(tmp_name);
+    /// Delete a file found by [`find_orphaned_temp_files`](Self::find_orphaned_temp_files);
+    /// one already gone is fine
+    pub fn remove_temp_file(&self, filename: &str) -> Result<()> {
+        self.remove_file(filename)
+    }
 
-        fs::write(&tmp_path, content)?;
+    /// Delete a file relative to the repository root; one already gone is fine
+    pub fn remove_file(&self, filename: &str) -> Result<()> {
+        let path = self.root.join(filename);
+        self.write_op("remove", &path, || match fs::remove_file(&path) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        })
+    }
 
-        if path.exists() {
-            // Best-effort atomic-ish replacement; we rely on archive backups for rollback.
-            fs::remove_file(&path)?;
+    /// List markdown files under a directory relative to the repository root.
+    ///
+    /// Returns repository-relative paths (with `/` separators) sorted by name.
+    /// A missing directory yields an empty list.
+    pub fn list_markdown_files_in(&self, dir: &str) -> Result<Vec<String>> {
+        let dir_path = self.root.join(dir);
+        if !dir_path.is_dir() {
+            return Ok(Vec::new());
         }
 
-        fs::rename(&tmp_path, &path)?;
-        Ok(())
+        let mut files = Vec::new();
+        for entry in WalkDir::new(&dir_path) {
+            let entry = entry.map_err(|e| DjourError::Io(e.into()))?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("md") {
+                continue;
+            }
+            let Ok(rel) = path.strip_prefix(&self.root) else {
+                continue;
+            };
+            if let Some(filename) = Self::normalize_relative_path(rel) {
+                files.push(filename);
+            }
+        }
+
+        files.sort();
+        Ok(files)
     }
 
     fn normalize_relative_path(path: &Path) -> Option<String> {
@@ -261,7 +766,7 @@ impl FileSystemRepository {
         Some(parts.join("/"))
     }
 
-    fn note_entry_from_relative_path(mode: JournalMode, rel: &Path) -> Option<NoteEntry> {
+    fn note_entry_from_relative_path(naming: &NoteNaming, rel: &Path) -> Option<NoteEntry> {
         let filename = Self::normalize_relative_path(rel)?;
         let leaf = rel.file_name()?.to_str()?;
 
@@ -270,7 +775,7 @@ impl FileSystemRepository {
             return None;
         }
 
-        match mode {
+        match naming.mode {
             JournalMode::Single => {
                 if leaf == "journal.md" {
                     Some(NoteEntry::new(filename, None))
@@ -278,13 +783,18 @@ impl FileSystemRepository {
                     None
                 }
             }
-            _ => mode
+            _ => naming
                 .date_from_filename(leaf)
+                .or_else(|| {
+                    // Dated folders such as 2025/01/17.md
+                    let parts: Vec<&str> = filename.split('/').collect();
+                    path_date_from_components(naming.mode, &parts)
+                })
                 .map(|d| NoteEntry::new(filename, Some(d))),
         }
     }
 
-    fn collect_root_note_entries(&self, mode: JournalMode) -> Result<Vec<NoteEntry>> {
+    fn collect_root_note_entries(&self, naming: &NoteNaming) -> Result<Vec<NoteEntry>> {
         let entries = fs::read_dir(&self.root)?;
         let mut notes = Vec::new();
 
@@ -293,39 +803,78 @@ impl FileSystemRepository {
                 continue;
             };
             let path = entry.path();
-            if !path.is_file() {
+            let is_symlink = entry.file_type().is_ok_and(|kind| kind.is_symlink());
+            if !path.is_file() || (is_symlink && !self.follow_symlinks) {
                 continue;
             }
             let Ok(rel) = path.strip_prefix(&self.root) else {
                 continue;
             };
-            if let Some(note) = Self::note_entry_from_relative_path(mode, rel) {
-                notes.push(note);
+            if let Some(note) = Self::note_entry_from_relative_path(naming, rel) {
+                // Through the link, like the recursive walk
+                let modified = fs::metadata(&path).ok().and_then(|m| m.modified().ok());
+                notes.push(note.with_modified(modified));
             }
         }
 
         Ok(notes)
     }
 
-    fn collect_recursive_note_entries(&self, mode: JournalMode) -> Vec<NoteEntry> {
-        let mut notes = Vec::new();
+    fn warn_symlink_revisit(&self, path: &Path, diagnostics: &mut Diagnostics) {
+        let rel = path.strip_prefix(&self.root).unwrap_or(path);
+        diagnostics.warn(
+            Self::normalize_relative_path(rel).unwrap_or_else(|| rel.display().to_string()),
+            "symlinked directory was already scanned (symlink loop or duplicate link); skipped",
+        );
+    }
 
-        let walker = WalkDir::new(&self.root).into_iter().filter_entry(|entry| {
-            if entry.depth() == 0 {
-                return true;
-            }
-            if !entry.file_type().is_dir() {
-                return true;
-            }
-            entry
-                .file_name()
-                .to_str()
-                .is_none_or(|name| !name.starts_with('.'))
-        });
+    fn collect_recursive_note_entries(
+        &self,
+        naming: &NoteNaming,
+        diagnostics: &mut Diagnostics,
+    ) -> Vec<NoteEntry> {
+        let mut notes = Vec::new();
+        // Revisited directories found by the filter, warned about after the walk
+        let mut revisited = Vec::new();
+
+        // Canonical directories already walked, so a followed symlink loop ends
+        let mut visited: HashSet<PathBuf> = HashSet::new();
+        let walker = WalkDir::new(&self.root)
+            .follow_links(self.follow_symlinks)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(|entry| {
+                if !entry.file_type().is_dir() {
+                    return true;
+                }
+                let hidden = entry.depth() > 0
+                    && entry
+                        .file_name()
+                        .to_str()
+                        .is_none_or(|name| name.starts_with('.'));
+                if hidden || !self.follow_symlinks {
+                    return !hidden;
+                }
+                let Ok(canonical) = entry.path().canonicalize() else {
+                    return false;
+                };
+                if visited.insert(canonical) {
+                    return true;
+                }
+                revisited.push(entry.path().to_path_buf());
+                false
+            });
 
         for entry in walker {
-            let Ok(entry) = entry else {
-                continue;
+            let entry = match entry {
+                Ok(entry) => entry,
+                // walkdir stops links back to an ancestor itself
+                Err(e) => {
+                    if let (Some(_), Some(path)) = (e.loop_ancestor(), e.path()) {
+                        self.warn_symlink_revisit(path, diagnostics);
+                    }
+                    continue;
+                }
             };
             if !entry.file_type().is_file() {
                 continue;
@@ -333,33 +882,172 @@ impl FileSystemRepository {
             let Ok(rel) = entry.path().strip_prefix(&self.root) else {
                 continue;
             };
-            if let Some(note) = Self::note_entry_from_relative_path(mode, rel) {
-                notes.push(note);
+            if let Some(note) = Self::note_entry_from_relative_path(naming, rel) {
+                let modified = entry.metadata().ok().and_then(|m| m.modified().ok());
+                notes.push(note.with_modified(modified));
             }
         }
+        for path in revisited {
+            self.warn_symlink_revisit(&path, diagnostics);
+        }
 
         notes
     }
 
-    /// List all note files for the given mode
+    /// Check whether a repository-relative path would be picked up by `list_notes`
+    /// for the given naming and recursion setting (date filters are not applied).
+    pub fn is_note_path(&self, naming: impl Into<NoteNaming>, rel: &Path, recursive: bool) -> bool {
+        let components: Vec<_> = rel.components().collect();
+        if components.is_empty() {
+            return false;
+        }
+        if !recursive && components.len() > 1 {
+            return false;
+        }
+        let in_dot_dir = components[..components.len() - 1].iter().any(|part| {
+            part.as_os_str()
+                .to_str()
+                .is_none_or(|name| name.starts_with('.'))
+        });
+        if in_dot_dir {
+            return false;
+        }
+        Self::note_entry_from_relative_path(&naming.into(), rel).is_some()
+    }
+
+    /// List all note files for the given mode (or full naming settings)
     /// Filters and sorts by date, applying optional date range and limit
+    ///
+    /// Warnings about skipped symlinked directories are dropped; use
+    /// [`list_notes_excluding`](Self::list_notes_excluding) to get them.
     pub fn list_notes(
         &self,
-        mode: JournalMode,
+        naming: impl Into<NoteNaming>,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+        limit: Option<usize>,
+        recursive: bool,
+    ) -> Result<Vec<NoteEntry>> {
+        let mut diagnostics = Diagnostics::new();
+        self.list_notes_excluding(naming, from, to, limit, recursive, &[], &mut diagnostics)
+    }
+
+    /// Like [`list_notes`](Self::list_notes), but never returns a path in `exclude`.
+    ///
+    /// Entries are relative to the journal root; a directory excludes everything
+    /// below it, the same way dot-directories are always skipped. Symlinked
+    /// directories skipped because they were already scanned are reported in
+    /// `diagnostics`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn list_notes_excluding(
+        &self,
+        naming: impl Into<NoteNaming>,
         from: Option<NaiveDate>,
         to: Option<NaiveDate>,
         limit: Option<usize>,
         recursive: bool,
+        exclude: &[PathBuf],
+        diagnostics: &mut Diagnostics,
     ) -> Result<Vec<NoteEntry>> {
+        let naming = naming.into();
         let mut notes = if recursive {
-            self.collect_recursive_note_entries(mode)
+            self.collect_recursive_note_entries(&naming, diagnostics)
         } else {
-            self.collect_root_note_entries(mode)?
+            self.collect_root_note_entries(&naming)?
         };
+        if !exclude.is_empty() {
+            notes.retain(|note| {
+                let path = Path::new(&note.filename);
+                !exclude.iter().any(|excluded| path.starts_with(excluded))
+            });
+        }
 
-        // Apply date range filters
+        let mut notes = Self::filter_and_sort(&naming, notes, from, to);
+
+        // Apply limit
+        if let Some(n) = limit {
+            notes.truncate(n);
+        }
+
+        Ok(notes)
+    }
+
+    /// Entries for exactly the named files (root-relative, or absolute inside the
+    /// root), filtered by date and sorted like [`FileSystemRepository::list_notes`].
+    ///
+    /// Dates come from the file names as for listed notes; a name the mode does
+    /// not recognize gives an undated note rather than an error.
+    pub fn named_notes(
+        &self,
+        naming: impl Into<NoteNaming>,
+        filenames: &[String],
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+    ) -> Result<Vec<NoteEntry>> {
+        let naming = naming.into();
+        let mut notes: Vec<NoteEntry> = Vec::with_capacity(filenames.len());
+        for name in filenames {
+            let path = Path::new(name);
+            let rel = if path.is_absolute() {
+                path.strip_prefix(&self.root).ok()
+            } else {
+                Some(path)
+            };
+            // Only plain names below the root: no `..`, no other drive or root
+            let rel: Option<PathBuf> = rel.and_then(|rel| {
+                rel.components()
+                    .filter(|c| *c != Component::CurDir)
+                    .map(|c| matches!(c, Component::Normal(_)).then_some(c))
+                    .collect()
+            });
+            let Some(filename) = rel
+                .filter(|rel| rel.file_name().is_some())
+                .and_then(|rel| Self::normalize_relative_path(&rel))
+            else {
+                return Err(DjourError::Config(format!(
+                    "Note is outside the journal: {}",
+                    name
+                )));
+            };
+
+            let full_path = self.root.join(&filename);
+            let Ok(metadata) = fs::metadata(&full_path) else {
+                return Err(DjourError::Config(format!(
+                    "Note does not exist: {}",
+                    full_path.display()
+                )));
+            };
+            if !metadata.is_file() {
+                return Err(DjourError::Config(format!(
+                    "Not a note file: {}",
+                    full_path.display()
+                )));
+            }
+            if notes.iter().any(|note| note.filename == filename) {
+                continue;
+            }
+
+            let date = Self::note_entry_from_relative_path(&naming, Path::new(&filename))
+                .and_then(|entry| entry.date);
+            notes.push(NoteEntry::new(filename, date).with_modified(metadata.modified().ok()));
+        }
+
+        Ok(Self::filter_and_sort(&naming, notes, from, to))
+    }
+
+    fn filter_and_sort(
+        naming: &NoteNaming,
+        mut notes: Vec<NoteEntry>,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+    ) -> Vec<NoteEntry> {
+        // Apply date range filters: a note is in range if the period it covers
+        // (a day, week or month) intersects the range.
         if let Some(from_date) = from {
-            notes.retain(|e| e.date.is_none_or(|d| d >= from_date));
+            notes.retain(|e| {
+                e.date
+                    .is_none_or(|d| naming.mode.period_end(d) >= from_date)
+            });
         }
         if let Some(to_date) = to {
             notes.retain(|e| e.date.is_none_or(|d| d <= to_date));
@@ -373,12 +1061,7 @@ impl FileSystemRepository {
             (None, None) => a.filename.cmp(&b.filename),
         });
 
-        // Apply limit
-        if let Some(n) = limit {
-            notes.truncate(n);
-        }
-
-        Ok(notes)
+        notes
     }
 }
 
@@ -507,6 +1190,42 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_resolve_root_provenance() {
+        let env_root = TempDir::new().unwrap();
+        fs::create_dir(env_root.path().join(".djour")).unwrap();
+        let cwd_root = TempDir::new().unwrap();
+        fs::create_dir(cwd_root.path().join(".djour")).unwrap();
+        let root_var = env_root.path().to_string_lossy().to_string();
+        let with_root = move |name: &str| (name == "DJOUR_ROOT").then(|| root_var.clone());
+        let no_env = |_: &str| None;
+
+        let discovered =
+            FileSystemRepository::resolve_root(None, &no_env, cwd_root.path()).unwrap();
+        assert_eq!(
+            discovered,
+            Resolved::new(cwd_root.path().to_path_buf(), Source::Discovered)
+        );
+
+        let from_env =
+            FileSystemRepository::resolve_root(None, &with_root, cwd_root.path()).unwrap();
+        assert_eq!(
+            from_env,
+            Resolved::new(env_root.path().to_path_buf(), Source::Env("DJOUR_ROOT"))
+        );
+
+        let outside = TempDir::new().unwrap();
+        assert!(matches!(
+            FileSystemRepository::resolve_root(None, &no_env, outside.path()),
+            Err(DjourError::NotDjourDirectory(_))
+        ));
+        let missing = |name: &str| (name == "DJOUR_ROOT").then(|| "/nonexistent".to_string());
+        let err = FileSystemRepository::resolve_root(None, &missing, cwd_root.path()).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("DJOUR_ROOT is set to '/nonexistent'"));
+    }
+
     #[test]
     fn test_save_and_load_config() {
         let temp = TempDir::new().unwrap();
@@ -610,6 +1329,65 @@ mod tests {
         assert!(temp.path().join(".djour/archive/a.md").exists());
     }
 
+    /// Create `name` under `root`, last modified `age` ago
+    fn temp_file(root: &Path, name: &str, age: Duration) {
+        let path = root.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let file = fs::File::create(&path).unwrap();
+        file.set_modified(SystemTime::now() - age).unwrap();
+    }
+
+    #[test]
+    fn test_find_orphaned_temp_files() {
+        let temp = TempDir::new().unwrap();
+        let repo = FileSystemRepository::new(temp.path().to_path_buf());
+        let old = ORPHANED_TEMP_AGE + Duration::from_secs(60);
+        temp_file(temp.path(), "2025-01-15.md.djour-tmp-4242", old);
+        temp_file(temp.path(), "work/2025-01-16.md.djour-tmp-77-00ab12cd", old);
+        temp_file(temp.path(), ".git/objects/x.md.djour-tmp-1", old);
+        temp_file(temp.path(), "2025-01-17.md.djour-tmp-notapid", old);
+        // A write still running
+        temp_file(temp.path(), "2025-01-18.md.djour-tmp-99-ff", Duration::ZERO);
+
+        assert_eq!(
+            repo.find_orphaned_temp_files().unwrap(),
+            [
+                "2025-01-15.md.djour-tmp-4242",
+                "work/2025-01-16.md.djour-tmp-77-00ab12cd",
+            ]
+        );
+        assert_eq!(
+            repo.find_temp_files_older_than(Duration::ZERO)
+                .unwrap()
+                .len(),
+            3
+        );
+
+        repo.remove_temp_file("2025-01-15.md.djour-tmp-4242")
+            .unwrap();
+        repo.remove_temp_file("2025-01-15.md.djour-tmp-4242")
+            .unwrap();
+        assert_eq!(repo.find_orphaned_temp_files().unwrap().len(), 1);
+        assert!(temp.path().join("2025-01-18.md.djour-tmp-99-ff").exists());
+    }
+
+    #[test]
+    fn test_write_note_atomic_leaves_no_temp_file() {
+        let temp = TempDir::new().unwrap();
+        let repo = FileSystemRepository::new(temp.path().to_path_buf());
+
+        repo.write_note_atomic("a.md", "one").unwrap();
+        // The temp file is removed when the rename fails: a directory is in the way
+        fs::create_dir(temp.path().join("b.md")).unwrap();
+        fs::write(temp.path().join("b.md/keep"), "").unwrap();
+        assert!(repo.write_note_atomic("b.md", "two").is_err());
+
+        assert!(repo
+            .find_temp_files_older_than(Duration::ZERO)
+            .unwrap()
+            .is_empty());
+    }
+
     #[test]
     fn test_write_note_atomic_overwrites() {
         let temp = TempDir::new().unwrap();
@@ -622,6 +1400,24 @@ mod tests {
         assert_eq!(final_content, "two");
     }
 
+    #[test]
+    fn test_list_markdown_files_in_directory() {
+        let temp = TempDir::new().unwrap();
+        let repo = FileSystemRepository::new(temp.path().to_path_buf());
+
+        repo.write_note(".compilations/work.md", "a").unwrap();
+        repo.write_note(".compilations/nested/urgent.md", "b")
+            .unwrap();
+        repo.write_note(".compilations/notes.txt", "c").unwrap();
+
+        let files = repo.list_markdown_files_in(".compilations").unwrap();
+        assert_eq!(
+            files,
+            vec![".compilations/nested/urgent.md", ".compilations/work.md"]
+        );
+        assert!(repo.list_markdown_files_in("missing").unwrap().is_empty());
+    }
+
     #[test]
     fn test_write_note_overwrites() {
         let temp = TempDir::new().unwrap();
@@ -638,6 +1434,42 @@ mod tests {
         assert_eq!(content, "updated");
     }
 
+    #[test]
+    fn test_create_note_exclusive_leaves_existing_note_alone() {
+        let temp = TempDir::new().unwrap();
+        let repo = FileSystemRepository::new(temp.path().to_path_buf());
+        repo.write_note("17-10-2026.md", "typed by hand").unwrap();
+
+        assert!(!repo
+            .create_note_exclusive("17-10-2026.md", "# template")
+            .unwrap());
+        assert_eq!(repo.read_note("17-10-2026.md").unwrap(), "typed by hand");
+
+        assert!(repo
+            .create_note_exclusive("sub/18-10-2026.md", "# template")
+            .unwrap());
+        assert_eq!(repo.read_note("sub/18-10-2026.md").unwrap(), "# template");
+    }
+
+    #[test]
+    fn test_create_dir_unique_never_reuses_a_directory() {
+        let temp = TempDir::new().unwrap();
+        let repo = FileSystemRepository::new(temp.path().to_path_buf());
+
+        assert_eq!(
+            repo.create_dir_unique("archive/run").unwrap(),
+            "archive/run"
+        );
+        assert_eq!(
+            repo.create_dir_unique("archive/run").unwrap(),
+            "archive/run-2"
+        );
+        assert_eq!(
+            repo.create_dir_unique("archive/run").unwrap(),
+            "archive/run-3"
+        );
+    }
+
     #[test]
     fn test_write_note_creates_parent_dirs() {
         let temp = TempDir::new().unwrap();
@@ -720,6 +1552,81 @@ mod tests {
         assert_eq!(notes[0].filename, "2025-01-17.md");
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinks_are_skipped_unless_followed() {
+        use std::os::unix::fs::symlink;
+
+        let temp = TempDir::new().unwrap();
+        let journal = temp.path().join("journal");
+        let team = temp.path().join("team-notes");
+        fs::create_dir_all(journal.join("projects")).unwrap();
+        fs::create_dir_all(&team).unwrap();
+        fs::write(journal.join("2025-01-15.md"), "root").unwrap();
+        fs::write(team.join("2025-01-16.md"), "shared").unwrap();
+        fs::write(temp.path().join("2025-01-17.md"), "linked file").unwrap();
+        symlink(&team, journal.join("projects").join("shared")).unwrap();
+        symlink(
+            temp.path().join("2025-01-17.md"),
+            journal.join("2025-01-17.md"),
+        )
+        .unwrap();
+
+        let names = |repo: &FileSystemRepository, recursive: bool| -> Vec<String> {
+            repo.list_notes(JournalMode::Daily, None, None, None, recursive)
+                .unwrap()
+                .into_iter()
+                .map(|note| note.filename)
+                .collect()
+        };
+
+        let repo = FileSystemRepository::new(journal.clone());
+        assert_eq!(names(&repo, false), vec!["2025-01-15.md"]);
+        assert_eq!(names(&repo, true), vec!["2025-01-15.md"]);
+
+        // Followed notes keep their path inside the journal
+        let repo = repo.with_follow_symlinks(true);
+        assert_eq!(names(&repo, false), vec!["2025-01-17.md", "2025-01-15.md"]);
+        assert_eq!(
+            names(&repo, true),
+            vec![
+                "2025-01-17.md",
+                "projects/shared/2025-01-16.md",
+                "2025-01-15.md"
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_followed_symlink_loop_is_walked_once() {
+        use std::os::unix::fs::symlink;
+
+        let temp = TempDir::new().unwrap();
+        fs::create_dir_all(temp.path().join("a")).unwrap();
+        fs::write(temp.path().join("a").join("2025-01-15.md"), "note").unwrap();
+        symlink(temp.path(), temp.path().join("a").join("back")).unwrap();
+        symlink(temp.path().join("a"), temp.path().join("b")).unwrap();
+
+        let repo = FileSystemRepository::new(temp.path().to_path_buf()).with_follow_symlinks(true);
+        let mut diagnostics = Diagnostics::new();
+        let notes = repo
+            .list_notes_excluding(
+                JournalMode::Daily,
+                None,
+                None,
+                None,
+                true,
+                &[],
+                &mut diagnostics,
+            )
+            .unwrap();
+        let names: Vec<&str> = notes.iter().map(|n| n.filename.as_str()).collect();
+        assert_eq!(names, vec!["a/2025-01-15.md"]);
+        let skipped: Vec<&str> = diagnostics.iter().map(|d| d.file.as_str()).collect();
+        assert_eq!(skipped, vec!["a/back", "b"]);
+    }
+
     #[test]
     fn test_list_notes_recursive_includes_nested_and_skips_dot_dirs() {
         let temp = TempDir::new().unwrap();
@@ -762,6 +1669,44 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_is_note_path() {
+        let repo = FileSystemRepository::new(PathBuf::from("/tmp/journal"));
+        let mode = JournalMode::Daily;
+
+        assert!(repo.is_note_path(mode, Path::new("2025-01-17.md"), false));
+        assert!(!repo.is_note_path(mode, Path::new("notes.md"), false));
+        assert!(!repo.is_note_path(mode, Path::new("work/2025-01-17.md"), false));
+        assert!(repo.is_note_path(mode, Path::new("work/2025-01-17.md"), true));
+        assert!(!repo.is_note_path(mode, Path::new(".compilations/2025-01-17.md"), true));
+    }
+
+    #[test]
+    fn test_list_notes_excluding_files_and_directories() {
+        let temp = TempDir::new().unwrap();
+        let repo = FileSystemRepository::new(temp.path().to_path_buf());
+
+        fs::write(temp.path().join("2025-01-15.md"), "note").unwrap();
+        fs::write(temp.path().join("2025-01-20.md"), "compiled").unwrap();
+        fs::create_dir_all(temp.path().join("out")).unwrap();
+        fs::write(temp.path().join("out").join("2025-01-16.md"), "compiled").unwrap();
+
+        let notes = repo
+            .list_notes_excluding(
+                JournalMode::Daily,
+                None,
+                None,
+                None,
+                true,
+                &[PathBuf::from("2025-01-20.md"), PathBuf::from("out")],
+                &mut Diagnostics::new(),
+            )
+            .unwrap();
+
+        let filenames: Vec<_> = notes.iter().map(|n| n.filename.as_str()).collect();
+        assert_eq!(filenames, vec!["2025-01-15.md"]);
+    }
+
     #[test]
     fn test_list_notes_with_date_range() {
         let temp = TempDir::new().unwrap();
@@ -782,6 +1727,36 @@ mod tests {
         assert_eq!(notes[0].filename, "2025-01-15.md");
     }
 
+    #[test]
+    fn test_list_notes_date_range_intersects_weekly_and_monthly_periods() {
+        let temp = TempDir::new().unwrap();
+        let repo = FileSystemRepository::new(temp.path().to_path_buf());
+        let d = |m, day| NaiveDate::from_ymd_opt(2025, m, day);
+
+        fs::write(temp.path().join("2025-W03-2025-01-13.md"), "week").unwrap();
+        fs::write(temp.path().join("2025-01.md"), "month").unwrap();
+
+        let weekly = |from, to| {
+            repo.list_notes(JournalMode::Weekly, from, to, None, false)
+                .unwrap()
+                .len()
+        };
+        // Thursday/Friday of week 03 still select the week's note.
+        assert_eq!(weekly(d(1, 16), d(1, 17)), 1);
+        assert_eq!(weekly(d(1, 19), None), 1);
+        assert_eq!(weekly(d(1, 20), None), 0);
+        assert_eq!(weekly(None, d(1, 13)), 1);
+        assert_eq!(weekly(None, d(1, 12)), 0);
+
+        let monthly = |from, to| {
+            repo.list_notes(JournalMode::Monthly, from, to, None, false)
+                .unwrap()
+                .len()
+        };
+        assert_eq!(monthly(d(1, 31), None), 1);
+        assert_eq!(monthly(d(2, 1), None), 0);
+    }
+
     #[test]
     fn test_list_notes_with_limit() {
         let temp = TempDir::new().unwrap();
@@ -801,6 +1776,33 @@ mod tests {
         assert_eq!(notes[1].filename, "2025-01-16.md");
     }
 
+    #[test]
+    fn test_list_notes_records_modified_time() {
+        use chrono::TimeZone;
+
+        let temp = TempDir::new().unwrap();
+        let repo = FileSystemRepository::new(temp.path().to_path_buf());
+
+        let path = temp.path().join("2025-01-17.md");
+        fs::write(&path, "note").unwrap();
+        let modified = Local.with_ymd_and_hms(2025, 2, 3, 9, 30, 0).unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified.into())
+            .unwrap();
+
+        for recursive in [false, true] {
+            let notes = repo
+                .list_notes(JournalMode::Daily, None, None, None, recursive)
+                .unwrap();
+            assert_eq!(notes[0].modified_local(), Some(modified));
+            assert!(notes[0].changed_since(NaiveDate::from_ymd_opt(2025, 2, 3).unwrap()));
+            assert!(!notes[0].changed_since(NaiveDate::from_ymd_opt(2025, 2, 4).unwrap()));
+        }
+    }
+
     #[test]
     fn test_list_notes_single_mode() {
         let temp = TempDir::new().unwrap();
@@ -838,6 +1840,44 @@ mod tests {
         assert_eq!(notes[1].filename, "2025-W02-2025-01-06.md");
     }
 
+    #[test]
+    fn test_list_notes_with_prefixed_and_suffixed_names() {
+        let temp = TempDir::new().unwrap();
+        let repo = FileSystemRepository::new(temp.path().to_path_buf());
+
+        fs::write(temp.path().join("journal-2025-01-17.md"), "prefixed").unwrap();
+        fs::write(temp.path().join("2025-01-16.md"), "bare").unwrap();
+        fs::write(temp.path().join("2025-01-15-planning-day.md"), "slug").unwrap();
+        fs::write(temp.path().join("work-2025-01-14.md"), "other prefix").unwrap();
+
+        let naming = NoteNaming::new(JournalMode::Daily, "journal-", true);
+        let notes = repo
+            .list_notes(naming.clone(), None, None, None, false)
+            .unwrap();
+        let names: Vec<&str> = notes.iter().map(|n| n.filename.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "journal-2025-01-17.md",
+                "2025-01-16.md",
+                "2025-01-15-planning-day.md"
+            ]
+        );
+        assert!(repo.is_note_path(naming, Path::new("journal-2025-01-17.md"), false));
+
+        // Without suffix support, slugged names are not notes
+        let notes = repo
+            .list_notes(
+                NoteNaming::new(JournalMode::Daily, "journal-", false),
+                None,
+                None,
+                None,
+                false,
+            )
+            .unwrap();
+        assert_eq!(notes.len(), 2);
+    }
+
     #[test]
     fn test_list_notes_monthly_mode() {
         let temp = TempDir::new().unwrap();
@@ -893,6 +1933,101 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_discover_djour_root_trailing_separator_and_djour_dir() {
+        let _env_lock = env_test_lock().lock().unwrap();
+        let _restore = EnvVarRestore::capture("DJOUR_ROOT");
+
+        let temp = TempDir::new().unwrap();
+        fs::create_dir(temp.path().join(".djour")).unwrap();
+
+        std::env::set_var("DJOUR_ROOT", format!("{}/", temp.path().display()));
+        assert_eq!(FileSystemRepository::discover().unwrap().root, temp.path());
+
+        std::env::set_var("DJOUR_ROOT", temp.path().join(".djour"));
+        assert_eq!(FileSystemRepository::discover().unwrap().root, temp.path());
+
+        std::env::set_var("DJOUR_ROOT", format!("{}/.djour/", temp.path().display()));
+        assert_eq!(FileSystemRepository::discover().unwrap().root, temp.path());
+    }
+
+    #[test]
+    fn test_discover_djour_root_missing_path() {
+        let _env_lock = env_test_lock().lock().unwrap();
+        let _restore = EnvVarRestore::capture("DJOUR_ROOT");
+
+        let temp = TempDir::new().unwrap();
+        let missing = temp.path().join("jounral");
+        std::env::set_var("DJOUR_ROOT", &missing);
+
+        let msg = FileSystemRepository::discover().unwrap_err().to_string();
+        assert!(msg.contains("does not exist"), "{}", msg);
+        assert!(msg.contains("typo"), "{}", msg);
+        assert!(!msg.contains("djour init"), "{}", msg);
+    }
+
+    #[test]
+    fn test_djour_root_relative_and_tilde_values() {
+        let home = TempDir::new().unwrap();
+        let journal = home.path().join("journal");
+        fs::create_dir_all(journal.join(".djour")).unwrap();
+        let home_var = home.path().to_string_lossy().to_string();
+        let resolve = |value: &str, current_dir: &Path| {
+            let value = value.to_string();
+            let home_var = home_var.clone();
+            let env = move |name: &str| match name {
+                "DJOUR_ROOT" => Some(value.clone()),
+                "HOME" | "USERPROFILE" => Some(home_var.clone()),
+                _ => None,
+            };
+            FileSystemRepository::resolve_root(None, &env, current_dir)
+        };
+        let elsewhere = TempDir::new().unwrap();
+
+        // A relative value is resolved once against the current directory
+        for value in ["journal", "./journal", "journal/", "./journal/./"] {
+            assert_eq!(
+                resolve(value, home.path()).unwrap().value,
+                journal,
+                "{}",
+                value
+            );
+        }
+        for value in ["~/journal", "~/journal/", "~/journal/.djour"] {
+            assert_eq!(
+                resolve(value, elsewhere.path()).unwrap().value,
+                journal,
+                "{}",
+                value
+            );
+        }
+
+        // Errors report the absolute path the value resolved to
+        let msg = resolve("~/notes", elsewhere.path())
+            .unwrap_err()
+            .to_string();
+        assert!(
+            msg.contains(&format!(
+                "'~/notes' (resolved to '{}')",
+                home.path().join("notes").display()
+            )),
+            "{}",
+            msg
+        );
+        assert!(msg.contains("does not exist"), "{}", msg);
+
+        let msg = resolve(".", elsewhere.path()).unwrap_err().to_string();
+        assert!(
+            msg.contains(&format!(
+                "(resolved to '{}') but no .djour directory found",
+                elsewhere.path().display()
+            )),
+            "{}",
+            msg
+        );
+        assert!(msg.contains("djour init"), "{}", msg);
+    }
+
     #[test]
     fn test_discover_without_djour_root_env() {
         let _env_lock = env_test_lock().lock().unwrap();
@@ -0,0 +1,249 @@
+//! Effective settings with the place each value came from
+//!
+//! Root, mode and editor can each come from a flag, an environment variable,
+//! `.djour/config.toml` or a built-in default. Resolving them records which one
+//! won, so `djour env` can explain the result.
+
+use crate::domain::JournalMode;
+use crate::infrastructure::Config;
+use serde::Serialize;
+use std::fmt;
+use std::str::FromStr;
+
+/// Looks up an environment variable; the process environment is [`process_env`]
+pub type EnvLookup<'a> = &'a dyn Fn(&str) -> Option<String>;
+
+/// A variable from the process environment; set but empty counts as unset
+pub fn process_env(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+/// Where an effective setting came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    /// A command-line flag, e.g. `--journal`
+    Flag(&'static str),
+    /// An environment variable, e.g. `DJOUR_ROOT`
+    Env(&'static str),
+    /// `.djour/config.toml`
+    Config,
+    /// The nearest directory with `.djour`, walking up from the current directory
+    Discovered,
+    /// Built-in default
+    Default,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Flag(flag) => f.write_str(flag),
+            Source::Env(var) => f.write_str(var),
+            Source::Config => f.write_str("config.toml"),
+            Source::Discovered => f.write_str("current directory"),
+            Source::Default => f.write_str("default"),
+        }
+    }
+}
+
+impl Serialize for Source {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// An effective value and where it came from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Resolved<T> {
+    pub value: T,
+    pub source: Source,
+    /// The config.toml value this one takes precedence over, when they differ
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overrides: Option<String>,
+    /// A setting that was ignored on the way, e.g. an invalid `DJOUR_MODE`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+}
+
+impl<T> Resolved<T> {
+    pub fn new(value: T, source: Source) -> Self {
+        Resolved {
+            value,
+            source,
+            overrides: None,
+            warning: None,
+        }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Resolved<T> {
+    /// `nvim (from EDITOR, overriding config 'vi')`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (from {}", self.value, self.source)?;
+        if let Some(config_value) = &self.overrides {
+            write!(f, ", overriding config '{}'", config_value)?;
+        }
+        f.write_str(")")
+    }
+}
+
+/// Journal mode: `DJOUR_MODE` > config.toml > daily.
+///
+/// An invalid `DJOUR_MODE` is ignored with a warning.
+pub fn resolve_mode(config: Option<&Config>, env: EnvLookup) -> Resolved<JournalMode> {
+    let fallback = match config {
+        Some(config) => Resolved::new(config.mode, Source::Config),
+        None => Resolved::new(JournalMode::default(), Source::Default),
+    };
+    let Some(raw) = env("DJOUR_MODE") else {
+        return fallback;
+    };
+    match JournalMode::from_str(&raw) {
+        Ok(mode) => Resolved {
+            overrides: config
+                .filter(|config| config.mode != mode)
+                .map(|config| config.mode.to_string()),
+            ..Resolved::new(mode, Source::Env("DJOUR_MODE"))
+        },
+        Err(_) => Resolved {
+            warning: Some(format!(
+                "Invalid DJOUR_MODE '{}', using {} mode '{:?}'",
+                raw,
+                if config.is_some() {
+                    "configured"
+                } else {
+                    "default"
+                },
+                fallback.value
+            )),
+            ..fallback
+        },
+    }
+}
+
+/// Editor command: `EDITOR` > `VISUAL` > config.toml > system default
+/// (`notepad` on Windows, `nano` elsewhere).
+pub fn resolve_editor(config: Option<&Config>, env: EnvLookup) -> Resolved<String> {
+    for var in ["EDITOR", "VISUAL"] {
+        if let Some(editor) = env(var) {
+            return Resolved {
+                overrides: config
+                    .filter(|config| config.editor != editor)
+                    .map(|config| config.editor.clone()),
+                ..Resolved::new(editor, Source::Env(var))
+            };
+        }
+    }
+    match config {
+        Some(config) => Resolved::new(config.editor.clone(), Source::Config),
+        None => {
+            let editor = if cfg!(windows) { "notepad" } else { "nano" };
+            Resolved::new(editor.to_string(), Source::Default)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    fn config(mode: JournalMode, editor: &str) -> Config {
+        Config {
+            editor: editor.to_string(),
+            ..Config::new(mode)
+        }
+    }
+
+    #[test]
+    fn test_mode_provenance() {
+        let weekly = config(JournalMode::Weekly, "vi");
+
+        let from_config = resolve_mode(Some(&weekly), &env(&[]));
+        assert_eq!(
+            from_config,
+            Resolved::new(JournalMode::Weekly, Source::Config)
+        );
+        assert_eq!(from_config.to_string(), "weekly (from config.toml)");
+
+        let from_env = resolve_mode(Some(&weekly), &env(&[("DJOUR_MODE", "monthly")]));
+        assert_eq!(from_env.value, JournalMode::Monthly);
+        assert_eq!(from_env.source, Source::Env("DJOUR_MODE"));
+        assert_eq!(
+            from_env.to_string(),
+            "monthly (from DJOUR_MODE, overriding config 'weekly')"
+        );
+
+        let same = resolve_mode(Some(&weekly), &env(&[("DJOUR_MODE", "weekly")]));
+        assert_eq!(same.source, Source::Env("DJOUR_MODE"));
+        assert_eq!(same.overrides, None);
+
+        let invalid = resolve_mode(Some(&weekly), &env(&[("DJOUR_MODE", "hourly")]));
+        assert_eq!(invalid.value, JournalMode::Weekly);
+        assert_eq!(invalid.source, Source::Config);
+        assert_eq!(
+            invalid.warning.as_deref(),
+            Some("Invalid DJOUR_MODE 'hourly', using configured mode 'Weekly'")
+        );
+    }
+
+    #[test]
+    fn test_mode_provenance_without_config() {
+        assert_eq!(
+            resolve_mode(None, &env(&[])),
+            Resolved::new(JournalMode::Daily, Source::Default)
+        );
+        let from_env = resolve_mode(None, &env(&[("DJOUR_MODE", "weekly")]));
+        assert_eq!(
+            from_env,
+            Resolved::new(JournalMode::Weekly, Source::Env("DJOUR_MODE"))
+        );
+        let invalid = resolve_mode(None, &env(&[("DJOUR_MODE", "hourly")]));
+        assert_eq!(invalid.source, Source::Default);
+        assert!(invalid.warning.unwrap().contains("using default mode"));
+    }
+
+    #[test]
+    fn test_editor_provenance() {
+        let vi = config(JournalMode::Daily, "vi");
+        let both = [("EDITOR", "nvim"), ("VISUAL", "code -w")];
+
+        let from_editor = resolve_editor(Some(&vi), &env(&both));
+        assert_eq!(
+            from_editor.to_string(),
+            "nvim (from EDITOR, overriding config 'vi')"
+        );
+        let from_visual = resolve_editor(Some(&vi), &env(&both[1..]));
+        assert_eq!(
+            from_visual.to_string(),
+            "code -w (from VISUAL, overriding config 'vi')"
+        );
+        let same = resolve_editor(Some(&vi), &env(&[("EDITOR", "vi")]));
+        assert_eq!(same, Resolved::new("vi".to_string(), Source::Env("EDITOR")));
+        assert_eq!(
+            resolve_editor(Some(&vi), &env(&[])),
+            Resolved::new("vi".to_string(), Source::Config)
+        );
+    }
+
+    #[test]
+    fn test_editor_provenance_without_config() {
+        assert_eq!(
+            resolve_editor(None, &env(&[("VISUAL", "code -w")])),
+            Resolved::new("code -w".to_string(), Source::Env("VISUAL"))
+        );
+        let default = resolve_editor(None, &env(&[]));
+        assert_eq!(default.source, Source::Default);
+        assert_eq!(
+            default.value,
+            if cfg!(windows) { "notepad" } else { "nano" }
+        );
+    }
+}
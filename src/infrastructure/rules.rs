@@ -0,0 +1,261 @@
+//! Standing compilation rules (`.djour/rules.toml`)
+//!
+//! ```toml
+//! [[rule]]
+//! name = "1on1s"
+//! query = "1on1"
+//! output = "people/1on1s.md"
+//! format = "grouped"
+//! include_context = true
+//! ```
+
+use crate::domain::tags::{
+    CompilationFormat, ContextDepth, ContextOptions, ContextStyle, TagQuery,
+};
+use crate::error::{DjourError, Result};
+use serde::Deserialize;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Rules file, relative to the journal root
+pub const RULES_FILE: &str = ".djour/rules.toml";
+
+/// One named compilation, run with `djour compile --rule <name>` or `--all-rules`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompileRule {
+    pub name: String,
+    /// Tag query, as for `djour compile <QUERY>`
+    pub query: String,
+    /// Output file (None = default: .compilations/<query>.md)
+    pub output: Option<PathBuf>,
+    pub format: CompilationFormat,
+    pub context: ContextOptions,
+    /// Also write `<output>.manifest.json`
+    pub manifest: bool,
+}
+
+/// On-disk form of a rule; values are checked by [`RawRule::validate`]
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawRule {
+    name: String,
+    query: String,
+    output: Option<PathBuf>,
+    #[serde(default)]
+    format: Option<String>,
+    #[serde(default)]
+    include_context: bool,
+    #[serde(default)]
+    context_depth: Option<String>,
+    #[serde(default)]
+    context_style: Option<String>,
+    #[serde(default)]
+    manifest: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RulesFile {
+    #[serde(default)]
+    rule: Vec<RawRule>,
+}
+
+impl RawRule {
+    fn validate(self) -> std::result::Result<CompileRule, String> {
+        // Value tags depend on the journal config; compile checks them again
+        TagQuery::parse_with_value_tags(&self.query, true)
+            .map_err(|e| format!("invalid query: {}", e))?;
+
+        let format = match self.format.as_deref() {
+            None => CompilationFormat::Chronological,
+            Some(format) => format.parse::<CompilationFormat>().map_err(|_| {
+                format!(
+                    "invalid format '{}'. Use 'chronological', 'grouped' or 'chrono-grouped'",
+                    format
+                )
+            })?,
+        };
+
+        // include_context alone means the nearest heading only, as on the command line
+        let depth = match self.context_depth {
+            Some(depth) => depth
+                .parse::<ContextDepth>()
+                .map_err(|e| format!("invalid context_depth: {}", e))?,
+            None => ContextDepth::Levels(usize::from(self.include_context)),
+        };
+        let style = match self
+            .context_style
+            .as_deref()
+            .map(str::to_lowercase)
+            .as_deref()
+        {
+            None | Some("headings") => ContextStyle::Headings,
+            Some("breadcrumb") => ContextStyle::Breadcrumb,
+            Some(_) => {
+                return Err(format!(
+                    "invalid context_style '{}'. Use 'headings' or 'breadcrumb'",
+                    self.context_style.unwrap_or_default()
+                ))
+            }
+        };
+
+        Ok(CompileRule {
+            name: self.name,
+            query: self.query,
+            output: self.output,
+            format,
+            context: ContextOptions { depth, style },
+            manifest: self.manifest,
+        })
+    }
+}
+
+/// The rules of a journal, in file order
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RuleSet {
+    pub rules: Vec<CompileRule>,
+}
+
+impl RuleSet {
+    /// Load `.djour/rules.toml` from the journal at `root`.
+    ///
+    /// A missing file is an error, since it is only read when rules were asked for.
+    pub fn load_from_dir(root: &Path) -> Result<Self> {
+        let path = root.join(RULES_FILE);
+        let contents = fs::read_to_string(&path).map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                DjourError::Config(format!(
+                    "No compilation rules: {} does not exist. Add [[rule]] entries with a name and a query",
+                    RULES_FILE
+                ))
+            } else {
+                DjourError::Io(e)
+            }
+        })?;
+        Self::parse(&contents)
+    }
+
+    /// Parse rules.toml contents; the first invalid rule is reported by name
+    pub fn parse(contents: &str) -> Result<Self> {
+        let file: RulesFile = toml::from_str(contents)
+            .map_err(|e| DjourError::Config(format!("Failed to parse {}: {}", RULES_FILE, e)))?;
+
+        let mut names = BTreeSet::new();
+        let mut rules = Vec::with_capacity(file.rule.len());
+        for (index, raw) in file.rule.into_iter().enumerate() {
+            let name = raw.name.trim().to_string();
+            if name.is_empty() {
+                return Err(DjourError::Config(format!(
+                    "Invalid rule #{} in {}: the name is empty",
+                    index + 1,
+                    RULES_FILE
+                )));
+            }
+            if !names.insert(name.clone()) {
+                return Err(DjourError::Config(format!(
+                    "Invalid rule '{}' in {}: another rule has the same name",
+                    name, RULES_FILE
+                )));
+            }
+            let rule = raw.validate().map_err(|problem| {
+                DjourError::Config(format!(
+                    "Invalid rule '{}' in {}: {}",
+                    name, RULES_FILE, problem
+                ))
+            })?;
+            rules.push(CompileRule { name, ..rule });
+        }
+        Ok(RuleSet { rules })
+    }
+
+    /// The rule called `name`
+    pub fn get(&self, name: &str) -> Result<&CompileRule> {
+        self.rules
+            .iter()
+            .find(|rule| rule.name == name)
+            .ok_or_else(|| {
+                let known: Vec<&str> = self.rules.iter().map(|rule| rule.name.as_str()).collect();
+                DjourError::Config(format!(
+                    "Unknown rule '{}'. Rules in {}: {}",
+                    name,
+                    RULES_FILE,
+                    if known.is_empty() {
+                        "(none)".to_string()
+                    } else {
+                        known.join(", ")
+                    }
+                ))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rules_with_defaults() {
+        let rules = RuleSet::parse(
+            r#"
+[[rule]]
+name = "1on1s"
+query = "1on1"
+output = "people/1on1s.md"
+format = "grouped"
+include_context = true
+
+[[rule]]
+name = "reading"
+query = "reading"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(rules.rules.len(), 2);
+        let one_on_one = rules.get("1on1s").unwrap();
+        assert_eq!(one_on_one.output, Some(PathBuf::from("people/1on1s.md")));
+        assert_eq!(one_on_one.format, CompilationFormat::Grouped);
+        assert_eq!(one_on_one.context.depth, ContextDepth::Levels(1));
+
+        let reading = rules.get("reading").unwrap();
+        assert_eq!(reading.output, None);
+        assert_eq!(reading.format, CompilationFormat::Chronological);
+        assert_eq!(reading.context, ContextOptions::off());
+        assert!(!reading.manifest);
+    }
+
+    #[test]
+    fn test_parse_errors_name_the_rule() {
+        let bad_query = "[[rule]]\nname = \"standup\"\nquery = \"work AND\"\n";
+        let err = RuleSet::parse(bad_query).unwrap_err().to_string();
+        assert!(err.contains("Invalid rule 'standup'"), "{}", err);
+        assert!(err.contains("invalid query"), "{}", err);
+
+        let bad_format = "[[rule]]\nname = \"log\"\nquery = \"reading\"\nformat = \"by-file\"\n";
+        let err = RuleSet::parse(bad_format).unwrap_err().to_string();
+        assert!(err.contains("Invalid rule 'log'"), "{}", err);
+        assert!(err.contains("'by-file'"), "{}", err);
+
+        let duplicate =
+            "[[rule]]\nname = \"a\"\nquery = \"x\"\n[[rule]]\nname = \"a\"\nquery = \"y\"\n";
+        let err = RuleSet::parse(duplicate).unwrap_err().to_string();
+        assert!(err.contains("same name"), "{}", err);
+
+        let unnamed = "[[rule]]\nname = \" \"\nquery = \"x\"\n";
+        let err = RuleSet::parse(unnamed).unwrap_err().to_string();
+        assert!(err.contains("rule #1"), "{}", err);
+
+        let unknown_key = "[[rule]]\nname = \"a\"\nquery = \"x\"\nouptut = \"a.md\"\n";
+        let err = RuleSet::parse(unknown_key).unwrap_err().to_string();
+        assert!(err.contains("ouptut"), "{}", err);
+    }
+
+    #[test]
+    fn test_unknown_rule_lists_known_ones() {
+        let rules = RuleSet::parse("[[rule]]\nname = \"a\"\nquery = \"x\"\n").unwrap();
+        let err = rules.get("b").unwrap_err().to_string();
+        assert!(err.contains("Unknown rule 'b'"));
+        assert!(err.contains(": a"));
+    }
+}
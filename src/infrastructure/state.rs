@@ -0,0 +1,330 @@
+//! Machine-local state (caches, trash) and where it lives
+//!
+//! With `state_dir = "journal"` (the default) state sits in `.djour/` next to
+//! the config. With `state_dir = "xdg"` it moves to
+//! `$XDG_STATE_HOME/djour/<journal-id>/` (`~/.local/state` when unset),
+//! `~/Library/Application Support/djour/...` on macOS or
+//! `%LOCALAPPDATA%\djour\...` on Windows, so synced journal folders do not
+//! churn. Config, templates and mode-migration archives always stay in the
+//! journal.
+
+use crate::error::{DjourError, Result};
+use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Subdirectories of the state directory, moved as a whole when `state_dir` changes
+pub const STATE_SUBDIRS: &[&str] = &["cache", "trash"];
+
+/// Where machine-local state is kept (`state_dir` in config.toml)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum StateLocation {
+    /// `.djour/` inside the journal
+    #[default]
+    Journal,
+    /// The per-user state directory, outside the journal
+    Xdg,
+}
+
+impl StateLocation {
+    /// Name as written in config.toml
+    pub fn code(&self) -> &'static str {
+        match self {
+            StateLocation::Journal => "journal",
+            StateLocation::Xdg => "xdg",
+        }
+    }
+
+    /// Whether this is the default (in-journal) location
+    pub fn is_default(&self) -> bool {
+        *self == StateLocation::Journal
+    }
+}
+
+impl FromStr for StateLocation {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "journal" => Ok(StateLocation::Journal),
+            "xdg" => Ok(StateLocation::Xdg),
+            _ => Err(format!(
+                "Invalid state_dir: '{}'. Valid values are: journal, xdg",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Platform {
+    Unix,
+    MacOs,
+    Windows,
+}
+
+impl Platform {
+    fn current() -> Self {
+        if cfg!(windows) {
+            Platform::Windows
+        } else if cfg!(target_os = "macos") {
+            Platform::MacOs
+        } else {
+            Platform::Unix
+        }
+    }
+}
+
+/// The djour per-user state directory, if the environment allows locating one
+pub fn state_home() -> Option<PathBuf> {
+    state_home_from(|key| std::env::var_os(key), Platform::current())
+}
+
+/// Resolve the per-user state directory from environment lookups
+fn state_home_from<F>(var: F, platform: Platform) -> Option<PathBuf>
+where
+    F: Fn(&str) -> Option<OsString>,
+{
+    // Relative values are ignored, as the XDG spec asks.
+    let absolute = |key: &str| var(key).map(PathBuf::from).filter(|p| p.is_absolute());
+
+    let base = match platform {
+        Platform::Windows => absolute("LOCALAPPDATA")?,
+        // An explicit XDG_STATE_HOME wins everywhere else
+        Platform::MacOs => match absolute("XDG_STATE_HOME") {
+            Some(dir) => dir,
+            None => absolute("HOME")?
+                .join("Library")
+                .join("Application Support"),
+        },
+        Platform::Unix => match absolute("XDG_STATE_HOME") {
+            Some(dir) => dir,
+            None => absolute("HOME")?.join(".local").join("state"),
+        },
+    };
+    Some(base.join("djour"))
+}
+
+/// Stable directory name for a journal: its folder name plus a hash of its
+/// canonical root, e.g. `work-3f2a9c0d1b4e5f60`
+pub fn journal_id(root: &Path) -> String {
+    let root = fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+    let name: String = root
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let hash = fnv1a(root.to_string_lossy().as_bytes());
+    if name.is_empty() {
+        format!("{:016x}", hash)
+    } else {
+        format!("{}-{:016x}", name, hash)
+    }
+}
+
+/// 64-bit FNV-1a; unlike `DefaultHasher` it never changes between Rust releases
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in bytes {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+/// Resolved state directories of one journal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatePaths {
+    root: PathBuf,
+}
+
+impl StatePaths {
+    /// State directories of the journal at `journal_root` for `location`.
+    ///
+    /// State left at the other location (from before `state_dir` changed) is
+    /// moved here first: copied, then removed.
+    pub fn for_journal(journal_root: &Path, location: StateLocation) -> Result<Self> {
+        Self::for_journal_in(journal_root, location, state_home().as_deref())
+    }
+
+    /// Like [`StatePaths::for_journal`], with an explicit per-user state directory
+    fn for_journal_in(
+        journal_root: &Path,
+        location: StateLocation,
+        state_home: Option<&Path>,
+    ) -> Result<Self> {
+        let in_journal = journal_root.join(".djour");
+        let outside = state_home.map(|home| home.join(journal_id(journal_root)));
+
+        let (root, previous) = match location {
+            StateLocation::Journal => (in_journal, outside),
+            StateLocation::Xdg => {
+                let root = outside.ok_or_else(|| {
+                    DjourError::Config(
+                        "Cannot locate the user state directory for state_dir = xdg. \
+                        Set XDG_STATE_HOME or HOME (LOCALAPPDATA on Windows)."
+                            .to_string(),
+                    )
+                })?;
+                (root, Some(in_journal))
+            }
+        };
+
+        let paths = StatePaths { root };
+        if let Some(previous) = previous {
+            paths.adopt(&previous)?;
+        }
+        Ok(paths)
+    }
+
+    /// Directory holding this journal's state subdirectories
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Caches that can be rebuilt from the notes at any time
+    pub fn cache_dir(&self) -> PathBuf {
+        self.root.join("cache")
+    }
+
+    /// Deleted notes kept for recovery
+    pub fn trash_dir(&self) -> PathBuf {
+        self.root.join("trash")
+    }
+
+    /// Move the state subdirectories found under `previous` into this location
+    fn adopt(&self, previous: &Path) -> Result<()> {
+        for name in STATE_SUBDIRS {
+            let from = previous.join(name);
+            if !from.is_dir() {
+                continue;
+            }
+            copy_missing(&from, &self.root.join(name))?;
+            fs::remove_dir_all(&from)?;
+        }
+        Ok(())
+    }
+}
+
+/// Copy the tree at `from` into `to`, keeping files that already exist there
+fn copy_missing(from: &Path, to: &Path) -> Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_missing(&entry.path(), &target)?;
+        } else if !target.exists() {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn env<'a>(vars: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<OsString> + 'a {
+        move |key| {
+            vars.iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| OsString::from(v))
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_state_home_per_platform() {
+        let home = [("HOME", "/home/ana")];
+        assert_eq!(
+            state_home_from(env(&home), Platform::Unix),
+            Some(PathBuf::from("/home/ana/.local/state/djour"))
+        );
+        assert_eq!(
+            state_home_from(
+                env(&[("HOME", "/home/ana"), ("XDG_STATE_HOME", "/state")]),
+                Platform::Unix
+            ),
+            Some(PathBuf::from("/state/djour"))
+        );
+        assert_eq!(
+            state_home_from(
+                env(&[("HOME", "/home/ana"), ("XDG_STATE_HOME", "state")]),
+                Platform::Unix
+            ),
+            Some(PathBuf::from("/home/ana/.local/state/djour"))
+        );
+        assert_eq!(
+            state_home_from(env(&home), Platform::MacOs),
+            Some(PathBuf::from("/home/ana/Library/Application Support/djour"))
+        );
+        assert_eq!(state_home_from(env(&home), Platform::Windows), None);
+        assert_eq!(state_home_from(env(&[]), Platform::Unix), None);
+    }
+
+    #[test]
+    fn test_journal_id_is_stable_and_readable() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path().join("my journal");
+        fs::create_dir(&root).unwrap();
+
+        let id = journal_id(&root);
+        assert!(id.starts_with("my_journal-"), "{id}");
+        assert_eq!(id, journal_id(&root.join(".")));
+        assert_ne!(id, journal_id(temp.path()));
+        assert_eq!(fnv1a(b"djour"), 0x8c05_4c8e_9053_a05b);
+    }
+
+    #[test]
+    fn test_location_parses_and_prints() {
+        assert_eq!(StateLocation::from_str("XDG"), Ok(StateLocation::Xdg));
+        assert_eq!(StateLocation::Journal.code(), "journal");
+        assert!(StateLocation::from_str("cloud").is_err());
+    }
+
+    #[test]
+    fn test_state_moves_between_locations() {
+        let temp = TempDir::new().unwrap();
+        let journal = temp.path().join("journal");
+        let state_home = temp.path().join("state");
+        fs::create_dir_all(journal.join(".djour/cache/nested")).unwrap();
+        fs::write(journal.join(".djour/cache/nested/index"), "tags").unwrap();
+        fs::write(journal.join(".djour/config.toml"), "mode = \"daily\"").unwrap();
+
+        let xdg =
+            StatePaths::for_journal_in(&journal, StateLocation::Xdg, Some(&state_home)).unwrap();
+        assert_eq!(xdg.root(), state_home.join(journal_id(&journal)));
+        assert_eq!(
+            fs::read_to_string(xdg.cache_dir().join("nested/index")).unwrap(),
+            "tags"
+        );
+        assert!(!journal.join(".djour/cache").exists());
+        assert!(journal.join(".djour/config.toml").exists());
+
+        let back = StatePaths::for_journal_in(&journal, StateLocation::Journal, Some(&state_home))
+            .unwrap();
+        assert_eq!(back.cache_dir(), journal.join(".djour/cache"));
+        assert!(back.cache_dir().join("nested/index").exists());
+        assert!(!xdg.cache_dir().exists());
+    }
+
+    #[test]
+    fn test_xdg_without_state_home_is_an_error() {
+        let temp = TempDir::new().unwrap();
+        assert!(StatePaths::for_journal_in(temp.path(), StateLocation::Xdg, None).is_err());
+        assert!(StatePaths::for_journal_in(temp.path(), StateLocation::Journal, None).is_ok());
+    }
+}
@@ -0,0 +1,417 @@
+//! Update notifications: is a newer djour release available?
+//!
+//! Only ever reports; nothing is downloaded. The latest version comes from
+//! `update_url` (the GitHub releases API by default), read either as a release
+//! JSON object with a `tag_name` or as a plain-text version number. Fetching
+//! needs the `update-check` cargo feature; without it every check fails.
+
+use crate::error::{DjourError, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Release endpoint used when `update_url` is empty
+pub const DEFAULT_UPDATE_URL: &str = "https://api.github.com/repos/tuncb/djour/releases/latest";
+
+/// How long a fetch may take before the check gives up
+pub const FETCH_TIMEOUT_SECS: u64 = 5;
+
+/// Cache file (in the state cache directory) of the passive check
+pub const UPDATE_CACHE_FILE: &str = "update-check.json";
+
+/// A semantic version: `MAJOR.MINOR.PATCH` with an optional `-prerelease`.
+///
+/// A leading `v` and `+build` metadata are accepted and ignored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    pub pre: Vec<String>,
+}
+
+impl Version {
+    /// The version of this djour binary
+    pub fn current() -> Self {
+        env!("CARGO_PKG_VERSION")
+            .parse()
+            .expect("CARGO_PKG_VERSION is a valid version")
+    }
+}
+
+impl FromStr for Version {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let invalid = || format!("Invalid version: '{}'. Expected MAJOR.MINOR.PATCH", s);
+        let text = s.trim();
+        let text = text.strip_prefix(['v', 'V']).unwrap_or(text);
+        let text = text.split_once('+').map_or(text, |(version, _)| version);
+        let (core, pre) = match text.split_once('-') {
+            Some((core, pre)) => (core, Some(pre)),
+            None => (text, None),
+        };
+
+        let numbers = core
+            .split('.')
+            .map(|part| {
+                if part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()) {
+                    return None;
+                }
+                part.parse::<u64>().ok()
+            })
+            .collect::<Option<Vec<u64>>>()
+            .ok_or_else(invalid)?;
+        let [major, minor, patch] = numbers[..] else {
+            return Err(invalid());
+        };
+
+        let pre = match pre {
+            Some(pre) => {
+                let identifiers: Vec<String> = pre.split('.').map(str::to_string).collect();
+                if identifiers.iter().any(|id| {
+                    id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+                }) {
+                    return Err(invalid());
+                }
+                identifiers
+            }
+            None => Vec::new(),
+        };
+
+        Ok(Version {
+            major,
+            minor,
+            patch,
+            pre,
+        })
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if !self.pre.is_empty() {
+            write!(f, "-{}", self.pre.join("."))?;
+        }
+        Ok(())
+    }
+}
+
+/// Precedence of two prerelease identifiers: numbers compare numerically and
+/// sort before words
+fn compare_identifier(a: &str, b: &str) -> Ordering {
+    match (a.parse::<u64>(), b.parse::<u64>()) {
+        (Ok(a), Ok(b)) => a.cmp(&b),
+        (Ok(_), Err(_)) => Ordering::Less,
+        (Err(_), Ok(_)) => Ordering::Greater,
+        (Err(_), Err(_)) => a.cmp(b),
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (self.pre.is_empty(), other.pre.is_empty()) {
+                // A release outranks its prereleases
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => {
+                    for (a, b) in self.pre.iter().zip(&other.pre) {
+                        let order = compare_identifier(a, b);
+                        if order != Ordering::Equal {
+                            return order;
+                        }
+                    }
+                    self.pre.len().cmp(&other.pre.len())
+                }
+            })
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Result of comparing the running version with the latest release
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UpdateStatus {
+    Available { latest: Version, current: Version },
+    UpToDate { current: Version },
+}
+
+impl UpdateStatus {
+    /// Compare `current` with the `latest` release
+    pub fn compare(current: Version, latest: Version) -> Self {
+        if latest > current {
+            UpdateStatus::Available { latest, current }
+        } else {
+            UpdateStatus::UpToDate { current }
+        }
+    }
+}
+
+impl fmt::Display for UpdateStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UpdateStatus::Available { latest, current } => {
+                write!(f, "djour {} is available (you have {})", latest, current)
+            }
+            UpdateStatus::UpToDate { current } => write!(f, "djour {} is up to date", current),
+        }
+    }
+}
+
+/// The release endpoint for an `update_url` setting (empty means the default)
+pub fn update_url(configured: &str) -> &str {
+    match configured.trim() {
+        "" => DEFAULT_UPDATE_URL,
+        url => url,
+    }
+}
+
+/// Read the latest version from a response body: a release JSON object with a
+/// `tag_name` (or `version`), or a bare version number
+pub fn parse_latest(body: &str) -> std::result::Result<Version, String> {
+    let body = body.trim();
+    if body.starts_with('{') {
+        let release: serde_json::Value = serde_json::from_str(body)
+            .map_err(|e| format!("Invalid release information: {}", e))?;
+        let tag = ["tag_name", "version"]
+            .iter()
+            .find_map(|key| release.get(key).and_then(|value| value.as_str()))
+            .ok_or_else(|| "Release information has no tag_name".to_string())?;
+        return tag.parse();
+    }
+    body.parse()
+}
+
+/// Fetch the latest released version from `url`
+#[cfg(feature = "update-check")]
+pub fn fetch_latest(url: &str) -> Result<Version> {
+    let agent = ureq::AgentBuilder::new()
+        .timeout(std::time::Duration::from_secs(FETCH_TIMEOUT_SECS))
+        .user_agent(concat!("djour/", env!("CARGO_PKG_VERSION")))
+        .build();
+    let failed = |reason: String| DjourError::Update(format!("{}: {}", url, reason));
+    let body = agent
+        .get(url)
+        .set("Accept", "application/vnd.github+json")
+        .call()
+        .map_err(|e| failed(e.to_string()))?
+        .into_string()
+        .map_err(|e| failed(e.to_string()))?;
+    parse_latest(&body).map_err(failed)
+}
+
+/// Fetch the latest released version from `url`
+#[cfg(not(feature = "update-check"))]
+pub fn fetch_latest(_url: &str) -> Result<Version> {
+    Err(DjourError::Update(
+        "this djour was built without the update-check feature".to_string(),
+    ))
+}
+
+/// Explicit check (`djour version --check`): failures are errors
+pub fn check_now(url: &str) -> Result<UpdateStatus> {
+    Ok(UpdateStatus::compare(
+        Version::current(),
+        fetch_latest(url)?,
+    ))
+}
+
+/// When the passive check last ran, and what it found
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct UpdateCache {
+    checked_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    latest: Option<String>,
+}
+
+/// Passive check (`update_check = true`), run at most once a day.
+///
+/// `cache_file` records when the last check ran, so calls within a day of it
+/// return `None` without fetching. A failed fetch counts as a check and is
+/// silent, as is a cache that cannot be read or written. Only an available
+/// update is reported.
+pub fn passive_check<F>(cache_file: &Path, now: DateTime<Utc>, fetch: F) -> Option<UpdateStatus>
+where
+    F: FnOnce() -> Result<Version>,
+{
+    let cache: UpdateCache = fs::read_to_string(cache_file)
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default();
+    // A check time in the future (clock moved back) does not block checks
+    if let Some(checked_at) = cache.checked_at {
+        if checked_at <= now && now - checked_at < Duration::days(1) {
+            return None;
+        }
+    }
+
+    let latest = fetch().ok();
+    let cache = UpdateCache {
+        checked_at: Some(now),
+        latest: latest.as_ref().map(Version::to_string),
+    };
+    if let Some(parent) = cache_file.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    if let Ok(text) = serde_json::to_string(&cache) {
+        let _ = fs::write(cache_file, text);
+    }
+
+    match UpdateStatus::compare(Version::current(), latest?) {
+        status @ UpdateStatus::Available { .. } => Some(status),
+        UpdateStatus::UpToDate { .. } => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::cell::Cell;
+    use tempfile::TempDir;
+
+    fn v(text: &str) -> Version {
+        text.parse().unwrap()
+    }
+
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 10, day, hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(v("0.4.1"), v("v0.4.1"));
+        assert_eq!(v("1.2.3+build.7").to_string(), "1.2.3");
+        assert_eq!(v("1.0.0-rc.1").pre, vec!["rc", "1"]);
+        for bad in [
+            "",
+            "1.2",
+            "1.2.3.4",
+            "1.x.3",
+            "1.2.3-",
+            "1.2.3-a..b",
+            "01a.2.3",
+        ] {
+            assert!(bad.parse::<Version>().is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_version_precedence() {
+        let ordered = [
+            "0.3.2",
+            "0.3.10",
+            "0.4.0-alpha",
+            "0.4.0-alpha.1",
+            "0.4.0-alpha.beta",
+            "0.4.0-beta.2",
+            "0.4.0-beta.11",
+            "0.4.0-rc.1",
+            "0.4.0",
+            "0.4.1",
+            "1.0.0",
+        ];
+        for pair in ordered.windows(2) {
+            assert!(v(pair[0]) < v(pair[1]), "{} < {}", pair[0], pair[1]);
+        }
+        assert_eq!(v("1.0.0").cmp(&v("v1.0.0+ci")), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_status_messages() {
+        assert_eq!(
+            UpdateStatus::compare(v("0.3.2"), v("0.4.1")).to_string(),
+            "djour 0.4.1 is available (you have 0.3.2)"
+        );
+        assert_eq!(
+            UpdateStatus::compare(v("0.4.1"), v("0.4.1")).to_string(),
+            "djour 0.4.1 is up to date"
+        );
+        assert_eq!(
+            UpdateStatus::compare(v("0.5.0"), v("0.4.1")).to_string(),
+            "djour 0.5.0 is up to date"
+        );
+    }
+
+    #[test]
+    fn test_parse_latest_release_body() {
+        let github = r#"{"tag_name": "v0.4.1", "name": "djour 0.4.1", "draft": false}"#;
+        assert_eq!(parse_latest(github).unwrap(), v("0.4.1"));
+        assert_eq!(parse_latest("0.5.0\n").unwrap(), v("0.5.0"));
+        assert!(parse_latest(r#"{"message": "Not Found"}"#).is_err());
+        assert!(parse_latest("<html>").is_err());
+        assert_eq!(update_url(" "), DEFAULT_UPDATE_URL);
+        assert_eq!(update_url("https://example.com/v"), "https://example.com/v");
+    }
+
+    #[test]
+    fn test_passive_check_runs_at_most_once_a_day() {
+        let temp = TempDir::new().unwrap();
+        let cache = temp.path().join("cache").join(UPDATE_CACHE_FILE);
+        let fetches = Cell::new(0);
+        let newer = || {
+            fetches.set(fetches.get() + 1);
+            Ok(v("999.0.0"))
+        };
+
+        let status = passive_check(&cache, at(17, 9), newer).unwrap();
+        assert_eq!(
+            status.to_string(),
+            format!(
+                "djour 999.0.0 is available (you have {})",
+                Version::current()
+            )
+        );
+        assert_eq!(fetches.get(), 1);
+
+        // Same day and just under 24 hours later: cached, no fetch
+        assert_eq!(passive_check(&cache, at(17, 18), newer), None);
+        assert_eq!(passive_check(&cache, at(18, 8), newer), None);
+        assert_eq!(fetches.get(), 1);
+
+        assert!(passive_check(&cache, at(18, 9), newer).is_some());
+        assert_eq!(fetches.get(), 2);
+
+        // A clock that went back does not suppress checks forever
+        assert!(passive_check(&cache, at(1, 9), newer).is_some());
+        assert_eq!(fetches.get(), 3);
+    }
+
+    #[test]
+    fn test_passive_check_is_silent_on_failure_and_when_current() {
+        let temp = TempDir::new().unwrap();
+        let cache = temp.path().join(UPDATE_CACHE_FILE);
+
+        let offline = || Err(DjourError::Update("offline".to_string()));
+        assert_eq!(passive_check(&cache, at(17, 9), offline), None);
+        // The failure counts as today's check
+        let fetched = Cell::new(false);
+        let probe = || {
+            fetched.set(true);
+            Ok(v("999.0.0"))
+        };
+        assert_eq!(passive_check(&cache, at(17, 12), probe), None);
+        assert!(!fetched.get());
+
+        assert_eq!(
+            passive_check(&cache, at(19, 9), || Ok(Version::current())),
+            None
+        );
+
+        // An unreadable cache is treated as "never checked"
+        fs::write(&cache, "not json").unwrap();
+        assert!(passive_check(&cache, at(19, 10), || Ok(v("999.0.0"))).is_some());
+    }
+}
@@ -0,0 +1,197 @@
+//! Per-user settings stored outside any journal
+//!
+//! The journal registry (`journals.toml`) maps names to journal roots so
+//! `--journal work` works from any directory. It lives in the user config
+//! directory: `$XDG_CONFIG_HOME/djour` or `~/.config/djour`, and
+//! `%APPDATA%\djour` on Windows.
+
+use crate::error::{DjourError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File name of the journal registry inside the user config directory
+pub const REGISTRY_FILE: &str = "journals.toml";
+
+/// The djour user config directory, if the environment allows locating one
+pub fn user_config_dir() -> Option<PathBuf> {
+    config_dir_from(|key| std::env::var_os(key), cfg!(windows))
+}
+
+/// Resolve the user config directory from environment lookups (`windows` picks `%APPDATA%`)
+fn config_dir_from<F>(var: F, windows: bool) -> Option<PathBuf>
+where
+    F: Fn(&str) -> Option<OsString>,
+{
+    // Relative values are ignored, as the XDG spec asks.
+    let absolute = |key: &str| var(key).map(PathBuf::from).filter(|p| p.is_absolute());
+
+    let base = if windows {
+        absolute("APPDATA")?
+    } else {
+        match absolute("XDG_CONFIG_HOME") {
+            Some(dir) => dir,
+            None => absolute("HOME")?.join(".config"),
+        }
+    };
+    Some(base.join("djour"))
+}
+
+/// Named journals registered with `djour journal add`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalRegistry {
+    #[serde(default)]
+    pub journals: BTreeMap<String, PathBuf>,
+}
+
+impl JournalRegistry {
+    /// Path of journals.toml in the user config directory
+    pub fn default_path() -> Result<PathBuf> {
+        user_config_dir()
+            .map(|dir| dir.join(REGISTRY_FILE))
+            .ok_or_else(|| {
+                DjourError::Config(
+                    "Cannot locate the user config directory for the journal registry. \
+                    Set HOME (or APPDATA on Windows)."
+                        .to_string(),
+                )
+            })
+    }
+
+    /// Load the registry from the user config directory (empty if it does not exist yet)
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::default_path()?)
+    }
+
+    /// Save the registry to the user config directory
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&Self::default_path()?)
+    }
+
+    /// Load a registry file (empty if it does not exist)
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(DjourError::Io(e)),
+        };
+        toml::from_str(&contents)
+            .map_err(|e| DjourError::Config(format!("Failed to parse {}: {}", path.display(), e)))
+    }
+
+    /// Save a registry file, creating its directory if needed
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let contents = toml::to_string_pretty(self).map_err(|e| {
+            DjourError::Config(format!("Failed to serialize journal registry: {}", e))
+        })?;
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    /// Root of the journal registered as `name`
+    pub fn get(&self, name: &str) -> Option<&Path> {
+        self.journals.get(name).map(PathBuf::as_path)
+    }
+
+    /// Name of the journal registered at `root`, if any
+    pub fn name_of(&self, root: &Path) -> Option<&str> {
+        let root = fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+        self.journals
+            .iter()
+            .find(|(_, path)| fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf()) == root)
+            .map(|(name, _)| name.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn env<'a>(vars: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<OsString> + 'a {
+        move |key| {
+            vars.iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| OsString::from(v))
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_config_dir_unix() {
+        assert_eq!(
+            config_dir_from(env(&[("HOME", "/home/ana")]), false),
+            Some(PathBuf::from("/home/ana/.config/djour"))
+        );
+        assert_eq!(
+            config_dir_from(
+                env(&[("HOME", "/home/ana"), ("XDG_CONFIG_HOME", "/cfg")]),
+                false
+            ),
+            Some(PathBuf::from("/cfg/djour"))
+        );
+        // A relative XDG_CONFIG_HOME is ignored
+        assert_eq!(
+            config_dir_from(
+                env(&[("HOME", "/home/ana"), ("XDG_CONFIG_HOME", "cfg")]),
+                false
+            ),
+            Some(PathBuf::from("/home/ana/.config/djour"))
+        );
+        assert_eq!(config_dir_from(env(&[]), false), None);
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn test_config_dir_windows() {
+        assert_eq!(
+            config_dir_from(env(&[("APPDATA", r"C:\Users\ana\AppData\Roaming")]), true),
+            Some(PathBuf::from(r"C:\Users\ana\AppData\Roaming\djour"))
+        );
+        assert_eq!(
+            config_dir_from(env(&[("HOME", r"C:\Users\ana")]), true),
+            None
+        );
+    }
+
+    #[test]
+    fn test_missing_registry_is_empty() {
+        let temp = TempDir::new().unwrap();
+        let registry = JournalRegistry::load_from(&temp.path().join(REGISTRY_FILE)).unwrap();
+        assert!(registry.journals.is_empty());
+    }
+
+    #[test]
+    fn test_registry_roundtrip_and_lookup() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("nested").join(REGISTRY_FILE);
+        let journal = temp.path().join("work");
+        fs::create_dir(&journal).unwrap();
+
+        let mut registry = JournalRegistry::default();
+        registry
+            .journals
+            .insert("work".to_string(), journal.clone());
+        registry.save_to(&path).unwrap();
+
+        let loaded = JournalRegistry::load_from(&path).unwrap();
+        assert_eq!(loaded, registry);
+        assert_eq!(loaded.get("work"), Some(journal.as_path()));
+        assert_eq!(loaded.name_of(&journal.join(".")), Some("work"));
+        assert_eq!(loaded.name_of(temp.path()), None);
+    }
+
+    #[test]
+    fn test_invalid_registry_reports_path() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join(REGISTRY_FILE);
+        fs::write(&path, "journals = 3\n").unwrap();
+        let err = JournalRegistry::load_from(&path).unwrap_err();
+        assert!(err.to_string().contains(REGISTRY_FILE), "{err}");
+    }
+}
@@ -2,6 +2,73 @@
 //!
 //! A command-line note-taking application that manages markdown diary entries
 //! with support for multiple time-based formats and tag-based compilation.
+//!
+//! # Library use
+//!
+//! The tag pipeline can be used without a journal on disk: parse markdown into
+//! tagged blocks, select them with a query and compile them into a document.
+//!
+//! ```
+//! use djour::domain::tags::{
+//!     compile_content, CompilationFormat, RenderOptions, TagParser, TagQuery,
+//! };
+//! use chrono::NaiveDate;
+//! use std::path::{Path, PathBuf};
+//!
+//! let notes = [
+//!     ("2025-01-14.md", "# Tuesday\n\nReviewed the [spec](specs/api.md). #work\n"),
+//!     ("2025-01-15.md", "# Wednesday\n\n## Release #work #urgent\n\nShipped 1.2.\n"),
+//!     ("2025-01-16.md", "# Thursday\n\nGym. #health\n"),
+//! ];
+//! let mut blocks = Vec::new();
+//! for (file, text) in notes {
+//!     let date = NaiveDate::parse_from_str(&file[..10], "%Y-%m-%d").ok();
+//!     blocks.extend(TagParser::extract_from_markdown_for_output(
+//!         text,
+//!         Path::new(file),
+//!         date,
+//!         None,
+//!     ));
+//! }
+//!
+//! let query = TagQuery::parse("work AND NOT health").unwrap();
+//! assert!(query.matches(&blocks[1].tags));
+//!
+//! let options = RenderOptions {
+//!     format: CompilationFormat::Grouped,
+//!     // Links are rebased against the output file
+//!     output_file: Some(PathBuf::from("reports/work.md")),
+//!     ..RenderOptions::default()
+//! };
+//! let markdown = compile_content(blocks, &query, &options);
+//! assert_eq!(
+//!     markdown,
+//!     [
+//!         "# Compilation: #work AND NOT #health\n\n",
+//!         "\n## From: 2025-01-14.md\n\n",
+//!         "Reviewed the [spec](../specs/api.md). #work\n\n",
+//!         "\n## From: 2025-01-15.md\n\n",
+//!         "Shipped 1.2.\n",
+//!     ]
+//!     .concat()
+//! );
+//! ```
+//!
+//! ## Stability
+//!
+//! These items, marked "Stability" in their docs, are the supported library API:
+//!
+//! - [`domain::tags::TagParser::extract_from_markdown_for_output`]
+//! - [`domain::tags::TagQuery::parse`] and [`domain::tags::TagQuery::matches`]
+//! - [`domain::tags::TaggedContent::new`] and [`domain::tags::TaggedContent::with_payload`]
+//! - [`domain::tags::compile_content`] and [`domain::tags::RenderOptions`]
+//!
+//! Their signatures and documented behaviour only change in a release that
+//! bumps the first non-zero version component (0.4 to 0.5, later 1.x to 2.0),
+//! and such changes are listed in the release notes. Patch releases may fix
+//! output that contradicts the docs. `RenderOptions` may gain fields in any
+//! release, so build it with `..RenderOptions::default()`. Everything else is
+//! public for the `djour` binary and may change in any release.
 
 pub mod application;
 pub mod cli;
@@ -1,23 +1,67 @@
 use chrono::NaiveDate;
-use clap::Parser;
 use djour::application::{
-    compile_tags, get_config, init, list_config, list_notes, list_tags, migrate_mode, open_note,
-    retag_notes, set_config, CompileOptions, ModeMigrationOptions, RetagOptions,
+    add_journal, archive_notes, capture, check_slot_name, check_templates, choose_init_mode,
+    clean_temp_files, command_line, compile_document, compile_rules, compile_tags,
+    compile_to_string, count_tags, due_reminders, edit_config, effective_settings,
+    enclosing_journal, exclude_patterns, find_compilations, get_config, init_with_config,
+    journal_stats, list_config, list_journals, list_notes, list_recurring, list_tag_groups,
+    list_todos, load_compile, locate_note, migrate_mode, mode_mismatch_hint, nested_journals,
+    notify_reminders, open_note_with_options, pin_note, pinned_notes, read_titles, refresh_options,
+    remove_compilations, remove_journal, rename_note, repair_config, retag_notes, save_compile,
+    saved_compile_path, seed_examples, set_config, sort_by_titles, template_warnings, undo_init,
+    unpin_note, watch_compilation, write_stats_block, ArchiveOptions, CaptureOutcome,
+    CompileOptions, ModeMigrationOptions, OpenNoteOptions, RefKind, RetagOptions, RuleSelection,
+    SavedCompile, TagOptions, TaskStatus, TodoOptions, CONFIG_KEYS, RECURRING_FILE, TEMPLATES_DIR,
 };
-use djour::cli::{format_note_list, format_tag_list, Cli, Commands};
-use djour::domain::tags::CompilationFormat;
-use djour::domain::JournalMode;
-use djour::error::DjourError;
-use djour::infrastructure::{EditorSession, FileSystemRepository, JournalRepository};
+use djour::cli::{
+    format_env, format_env_json, format_note_list_grouped, format_note_table, format_pinned_notes,
+    format_reminder_list, format_stats, format_stats_badges, format_tag_csv, format_tag_groups,
+    format_tag_table, format_todo_csv, format_todo_json, format_todo_list, Cli, Commands,
+    ExportFormat, JournalCommand, NoteCommand, NoteListContext, OutputStyle, ProgressLine,
+    RecurringCommand, TemplateCommand,
+};
+use djour::domain::tags::{
+    check_date_format, CompilationFormat, CompileBudget, ContextDepth, ContextOptions,
+    ContextStyle, GroupBy, KeepOrder,
+};
+use djour::domain::{first_heading, Diagnostics, DiffOptions, JournalMode, Locale, TimeReference};
+use djour::error::{exit_code, DjourError};
+use djour::infrastructure::resolved::process_env;
+use djour::infrastructure::update;
+use djour::infrastructure::{
+    Config, DesktopNotifier, EditorRole, EditorSession, FileSystemRepository, JournalRegistry,
+    JournalRepository, NoteEntry,
+};
+use std::io::{IsTerminal, Read, Write};
 use std::str::FromStr;
 
 fn main() {
-    let cli = Cli::parse();
+    let cli = match Cli::try_parse_validated_from(std::env::args_os()) {
+        Ok(cli) => cli,
+        Err(e) => {
+            // Usage errors are user errors; --help and --version are successes.
+            let _ = e.print();
+            let code = if e.use_stderr() {
+                exit_code::USER_ERROR
+            } else {
+                exit_code::SUCCESS
+            };
+            std::process::exit(code);
+        }
+    };
 
+    let passive_update_check =
+        !cli.quiet && !cli.read_only && !matches!(cli.command, Some(Commands::Version { .. }));
+    let journal = cli.journal.clone();
     let result = run(cli);
 
     match result {
-        Ok(_) => std::process::exit(0),
+        Ok(_) => {
+            if passive_update_check {
+                notify_update(journal.as_deref());
+            }
+            std::process::exit(exit_code::SUCCESS)
+        }
         Err(e) => {
             eprintln!("{}", e.display_with_suggestions());
             std::process::exit(e.exit_code());
@@ -25,30 +69,221 @@ fn main() {
     }
 }
 
+/// Passive update check after a successful command (`update_check = true`).
+///
+/// Runs at most once a day per journal; failures of any kind stay silent.
+#[cfg(feature = "update-check")]
+fn notify_update(journal: Option<&str>) {
+    let Ok(repo) = FileSystemRepository::discover_journal(journal) else {
+        return;
+    };
+    let Ok(config) = repo.load_config() else {
+        return;
+    };
+    if !config.update_check {
+        return;
+    }
+    let Ok(paths) = repo.state_paths(&config) else {
+        return;
+    };
+    let cache_file = paths.cache_dir().join(update::UPDATE_CACHE_FILE);
+    let url = update::update_url(&config.update_url);
+    if let Some(status) = update::passive_check(&cache_file, chrono::Utc::now(), || {
+        update::fetch_latest(url)
+    }) {
+        eprintln!("{}", status);
+    }
+}
+
+#[cfg(not(feature = "update-check"))]
+fn notify_update(_journal: Option<&str>) {}
+
 fn run(cli: Cli) -> Result<(), DjourError> {
+    let quiet = cli.quiet;
+    let no_color = cli.no_color;
+    let read_only = cli.read_only;
+    let journal = cli.journal.clone();
+    let discover = || {
+        FileSystemRepository::discover_journal(journal.as_deref())
+            .map(|repo| repo.with_read_only(read_only))
+    };
+
     match cli.command {
-        Some(Commands::Init { path, mode }) => {
-            // Parse mode string to enum
-            let journal_mode = JournalMode::from_str(&mode).map_err(DjourError::Config)?;
+        Some(Commands::Init {
+            path,
+            mode,
+            with_examples,
+            name,
+            author,
+            nested,
+            undo,
+        }) => {
+            if read_only {
+                return Err(DjourError::read_only("initialize a journal in", &path));
+            }
+            if undo {
+                undo_init(&path)?;
+                if !quiet {
+                    println!("Removed djour journal at {}", path.display());
+                }
+                return Ok(());
+            }
+            if !nested {
+                if let Some(outer) = enclosing_journal(&path)? {
+                    return Err(DjourError::Config(format!(
+                        "{} is inside the journal at {}; commands run there would use the new \
+                        journal instead. Pass --nested to create it anyway.",
+                        path.display(),
+                        outer.display()
+                    )));
+                }
+            }
+            for inner in nested_journals(&path) {
+                eprintln!(
+                    "warning: {} is already a journal; commands run there will keep using it",
+                    inner.display()
+                );
+            }
+            // Parse mode string to enum; without --mode, existing notes decide
+            let requested = match mode {
+                Some(mode) => Some(JournalMode::from_str(&mode).map_err(DjourError::Config)?),
+                None => None,
+            };
+            let choice = choose_init_mode(&path, requested)?;
+            let journal_mode = choice.mode;
 
             // Execute init
-            init(&path, journal_mode)
+            let config = Config {
+                name: name.unwrap_or_default().trim().to_string(),
+                author: author.unwrap_or_default().trim().to_string(),
+                ..Config::new(journal_mode)
+            };
+            init_with_config(&path, &config)?;
+            if let Some((detected, count)) = choice.mismatch() {
+                let detected = format!("{:?}", detected).to_lowercase();
+                eprintln!(
+                    "warning: {} file(s) in {} are named like {} notes, but the journal mode is {}; \
+                    djour will not see them. Run 'djour config mode {}' to switch.",
+                    count,
+                    path.display(),
+                    detected,
+                    format!("{:?}", journal_mode).to_lowercase(),
+                    detected
+                );
+            }
+            let repo = FileSystemRepository::new(path.clone());
+            let examples = if with_examples {
+                seed_examples(&repo, chrono::Local::now().date_naive())?
+            } else {
+                Vec::new()
+            };
+            // Lint the custom templates the new journal will use, as 'djour template check' does
+            let checks = check_templates(&repo, &config, None)?;
+            print_template_warnings(&template_warnings(&checks), quiet);
+
+            if !quiet {
+                println!("Initialized djour journal at {}", path.display());
+                if let (false, Some((detected, count))) = (choice.explicit, choice.detected) {
+                    println!(
+                        "Detected {} existing {} note(s); pass --mode to choose another mode",
+                        count,
+                        format!("{:?}", detected).to_lowercase()
+                    );
+                }
+                println!("Mode: {:?}", journal_mode);
+                if !examples.is_empty() {
+                    println!("Added examples: {}", examples.join(", "));
+                    println!("Try 'djour list', 'djour tags' and 'djour compile example'");
+                }
+            }
+            Ok(())
         }
-        Some(Commands::Config { key, value, list }) => {
+        Some(Commands::Config {
+            key,
+            value,
+            list,
+            edit,
+            repair,
+        }) => {
             // Discover repository
-            let repo = FileSystemRepository::discover()?;
+            let repo = discover()?;
 
-            if list {
+            if repair {
+                let report = repair_config(&repo)?;
+                if !quiet {
+                    match report {
+                        None => println!("config.toml loads fine; nothing to repair"),
+                        Some(report) => {
+                            match &report.backup {
+                                Some(backup) => println!(
+                                    "Repaired config.toml (original saved as {})",
+                                    backup.display()
+                                ),
+                                None => println!("Wrote config.toml from defaults"),
+                            }
+                            for line in &report.commented {
+                                println!("  commented out: {}", line);
+                            }
+                        }
+                    }
+                }
+                Ok(())
+            } else if edit {
+                let changes = edit_config(&repo, confirm_reopen_config)?;
+                if !quiet {
+                    if changes.is_empty() {
+                        println!("No changes to config.toml");
+                    } else {
+                        println!("Updated config.toml:");
+                        for change in &changes {
+                            println!("  {}", change);
+                        }
+                    }
+                }
+                Ok(())
+            } else if list {
                 // List all config
                 let config = list_config(&repo)?;
                 println!("mode = {}", format!("{:?}", config.mode).to_lowercase());
                 println!("editor = {}", config.editor);
+                println!("editor_shell = {}", config.editor_shell);
+                println!("viewer = {}", config.viewer.as_deref().unwrap_or(""));
+                println!("editor_max_files = {}", config.editor_max_files);
+                println!(
+                    "editor_batch_template = {}",
+                    config.editor_batch_template.as_deref().unwrap_or("")
+                );
+                println!("name = {}", config.name);
+                println!("author = {}", config.author);
+                println!("locale = {}", config.locale.code());
+                println!("frontmatter_tags = {}", config.frontmatter_tags);
+                println!("hierarchical_tags = {}", config.hierarchical_tags);
+                println!("value_tags = {}", config.value_tags);
+                println!("date_guard_days = {}", config.date_guard_days);
+                println!("create_on_resolve = {}", config.create_on_resolve);
+                println!("archive_layout = {}", config.archive_layout.code());
+                println!("week_start = {}", config.week_start.code());
+                println!("week_numbering = {}", config.week_numbering.code());
+                println!("filename_prefix = {}", config.filename_prefix);
+                println!("allow_filename_suffix = {}", config.allow_filename_suffix);
+                println!("wikilinks = {}", config.wikilinks);
+                println!("follow_symlinks = {}", config.follow_symlinks);
+                println!("embed_max_bytes = {}", config.embed_max_bytes);
+                println!("normalize_exempt = {}", config.normalize_exempt.join(","));
+                println!("compile_exclude = {}", config.compile_exclude.join(","));
+                println!("private_tag = {}", config.private_tag);
+                println!("auto_tag_footer = {}", config.auto_tag_footer);
+                println!("state_dir = {}", config.state_dir.code());
+                println!("update_check = {}", config.update_check);
+                println!("update_url = {}", config.update_url);
                 Ok(())
             } else if let Some(k) = key {
                 if let Some(v) = value {
                     // Set config value
                     set_config(&repo, &k, &v)?;
-                    println!("Set {} = {}", k, v);
+                    if !quiet {
+                        println!("Set {} = {}", k, v);
+                    }
                     Ok(())
                 } else {
                     // Get config value
@@ -58,49 +293,163 @@ fn run(cli: Cli) -> Result<(), DjourError> {
                 }
             } else {
                 // No key provided, show usage
-                println!("Usage: djour config [--list | <key> [<value>]]");
-                println!("Valid keys: mode, editor");
+                println!("Usage: djour config [--list | --edit | --repair | <key> [<value>]]");
+                println!("Valid keys: {}", CONFIG_KEYS.join(", "));
                 Ok(())
             }
         }
+        Some(Commands::Capture) => {
+            let repo = discover()?;
+            let config = repo.load_config()?;
+            let editor = EditorSession::for_role(&config, EditorRole::Editor);
+
+            let mut diagnostics = Diagnostics::new();
+            let outcome = capture(
+                &repo,
+                chrono::Local::now().naive_local(),
+                &mut diagnostics,
+                |initial| editor.edit_buffer(initial),
+            )?;
+            print_template_warnings(&diagnostics, quiet);
+            if !quiet {
+                match outcome {
+                    Some(CaptureOutcome {
+                        filename,
+                        section: Some(section),
+                    }) => println!("Captured to {} (under {})", filename, section),
+                    Some(CaptureOutcome { filename, .. }) => println!("Captured to {}", filename),
+                    None => println!("Nothing captured"),
+                }
+            }
+            Ok(())
+        }
         Some(Commands::Folder { open }) => {
-            let repo = FileSystemRepository::discover()?;
+            let repo = discover()?;
 
             if open {
                 let config = repo.load_config()?;
-                let editor = EditorSession::new(config.get_editor());
+                let editor = EditorSession::for_role(&config, EditorRole::Editor);
                 editor.open(repo.root())?;
             }
 
             println!("{}", repo.root().display());
             Ok(())
         }
+        #[cfg(feature = "tui")]
+        Some(Commands::Browse { recursive }) => {
+            let repo = discover()?;
+            djour::cli::tui::run(&repo, recursive)
+        }
         Some(Commands::List {
             from,
             to,
             limit,
             recursive,
+            include_archived,
+            fail_empty,
+            strict,
+            changed_since,
+            show_modified,
+            group,
+            titles,
+            sort,
         }) => {
             // Discover repository
-            let repo = FileSystemRepository::discover()?;
+            let repo = discover()?;
             let config = repo.load_config()?;
 
+            let sort_by_title = match sort.to_lowercase().as_str() {
+                "date" => false,
+                "title" => true,
+                _ => {
+                    return Err(DjourError::Config(format!(
+                        "Invalid sort: {}. Use 'date' or 'title'",
+                        sort
+                    )))
+                }
+            };
             let from_date = parse_cli_date(from)?;
             let to_date = parse_cli_date(to)?;
+            let changed_since = parse_cli_since(changed_since, config.locale)?;
+
+            // Pinned notes come first and do not count against --limit
+            let pins = pinned_notes(&repo, &config);
 
             // Execute list
-            let notes = list_notes(
+            let mut diagnostics = Diagnostics::new();
+            let mut notes = list_notes(
                 &repo,
-                config.get_mode(),
+                config.naming(),
                 from_date,
                 to_date,
-                Some(limit),
-                recursive,
+                Some(limit.saturating_add(pins.len())),
+                recursive || include_archived,
+                include_archived,
+                changed_since,
+                &mut diagnostics,
             )?;
 
+            if strict && !diagnostics.is_empty() {
+                diagnostics.escalate();
+                print_diagnostics(&diagnostics);
+                return Err(DjourError::Config(format!(
+                    "{} note(s) do not match the built-in template (--strict)",
+                    diagnostics.len()
+                )));
+            }
+
+            if notes.is_empty() {
+                print_mode_hint(&repo);
+            }
+
+            if fail_empty && notes.is_empty() {
+                return Err(DjourError::NothingMatched("No notes found".to_string()));
+            }
+            notes.retain(|note| !config.pinned.contains(&note.filename));
+            notes.truncate(limit);
+
+            // Notes are only read for their titles when the output needs them
+            let titles_read = titles || sort_by_title;
+            if titles_read {
+                read_titles(&repo, &mut notes);
+            }
+            if sort_by_title {
+                sort_by_titles(&mut notes);
+            }
+
             // Format and print output
-            let output = format_note_list(&notes);
-            print!("{}", output);
+            let context = NoteListContext {
+                mode: config.get_mode(),
+                locale: config.locale,
+                today: chrono::Local::now().date_naive(),
+                show_modified,
+            };
+            let style = OutputStyle::detect(no_color);
+            let format_notes = |notes: &[NoteEntry]| {
+                format_note_table(notes, &context, style, |entry| {
+                    if titles_read {
+                        return entry.title.clone();
+                    }
+                    // Titles are decoration only; unreadable notes just go without one.
+                    repo.read_note(&entry.filename)
+                        .ok()
+                        .and_then(|content| first_heading(&content))
+                })
+            };
+            let pinned_output = format_pinned_notes(&pins, style, |entry| {
+                repo.read_note(&entry.filename)
+                    .ok()
+                    .and_then(|content| first_heading(&content))
+            });
+            let output = if notes.is_empty() && !pins.is_empty() {
+                String::new()
+            } else if group {
+                format_note_list_grouped(&notes, context.today, config.week_start, format_notes)
+            } else {
+                format_notes(&notes)
+            };
+            print!("{}{}", pinned_output, output);
+            print_diagnostics(&diagnostics);
 
             Ok(())
         }
@@ -108,71 +457,721 @@ fn run(cli: Cli) -> Result<(), DjourError> {
             from,
             to,
             recursive,
+            include_archived,
+            normalize,
+            exclude,
+            format,
         }) => {
-            let repo = FileSystemRepository::discover()?;
-            let from_date = parse_cli_date(from)?;
-            let to_date = parse_cli_date(to)?;
+            let format = format.parse::<ExportFormat>().map_err(DjourError::Config)?;
+            let repo = discover()?;
+            let options = TagOptions {
+                from: parse_cli_date(from)?,
+                to: parse_cli_date(to)?,
+                recursive: recursive || include_archived,
+                include_archived,
+                exclude,
+            };
+            let progress = ProgressLine::for_stderr(quiet);
+            let update = |done, total| progress.update(done, total);
+
+            if normalize {
+                let (groups, tags) = list_tag_groups(&repo, &options, Some(&update))?;
+                progress.finish();
+                print_excluded(tags.excluded, quiet);
+                if groups.is_empty() {
+                    print_mode_hint(&repo);
+                }
+                print!("{}", format_tag_groups(&groups));
+                print_diagnostics(&tags.diagnostics);
+                return Ok(());
+            }
 
-            let tags = list_tags(&repo, from_date, to_date, recursive)?;
-            let output = format_tag_list(&tags);
+            let tags = count_tags(&repo, &options, Some(&update))?;
+            progress.finish();
+            print_excluded(tags.excluded, quiet);
+            if format == ExportFormat::Csv {
+                print!("{}", format_tag_csv(&tags));
+                print_diagnostics(&tags.diagnostics);
+                return Ok(());
+            }
+            if tags.counts.is_empty() {
+                print_mode_hint(&repo);
+            }
+            let output = format_tag_table(&tags.counts, OutputStyle::detect(no_color));
             print!("{}", output);
+            print_diagnostics(&tags.diagnostics);
 
             Ok(())
         }
+        Some(Commands::Todos {
+            from,
+            to,
+            recursive,
+            query,
+            done,
+            all,
+            json,
+            format,
+        }) => {
+            let format = format.parse::<ExportFormat>().map_err(DjourError::Config)?;
+            let repo = discover()?;
+            let options = TodoOptions {
+                from: parse_cli_date(from)?,
+                to: parse_cli_date(to)?,
+                recursive,
+                query,
+                status: if all {
+                    TaskStatus::All
+                } else if done {
+                    TaskStatus::Done
+                } else {
+                    TaskStatus::Open
+                },
+            };
+
+            let progress = ProgressLine::for_stderr(quiet);
+            let mut diagnostics = Diagnostics::new();
+            let tasks = list_todos(
+                &repo,
+                &options,
+                &mut diagnostics,
+                Some(&|done, total| progress.update(done, total)),
+            )?;
+            progress.finish();
+
+            if json {
+                print!("{}", format_todo_json(&tasks)?);
+            } else if format == ExportFormat::Csv {
+                print!("{}", format_todo_csv(&tasks));
+            } else {
+                print!("{}", format_todo_list(&tasks, all));
+            }
+            print_diagnostics(&diagnostics);
+            Ok(())
+        }
         Some(Commands::Compile {
             query,
+            rule,
+            all_rules,
             output,
             from,
             to,
+            changed_since,
             format,
+            group_by,
+            date_format,
             include_context,
+            context_depth,
+            context_style,
             open,
+            with_viewer,
             recursive,
+            include_archived,
+            watch,
+            manifest,
+            strict,
+            max_blocks,
+            max_bytes,
+            keep,
+            suggest_limit,
+            embed_links,
+            stdin,
+            assume_date,
+            notes,
+            exclude,
+            title_filter,
+            stdout,
+            no_attribution,
+            include_private,
+            allow_exclusion_only,
+            again,
+            save_as,
+            clean,
+            yes,
+            refresh_all,
         }) => {
-            // Discover repository
-            let repo = FileSystemRepository::discover()?;
-
+            if let Some(name) = &save_as {
+                check_slot_name(name)?;
+            }
+            let assume_date = parse_cli_date(assume_date)?;
             let from_date = parse_cli_date(from)?;
             let to_date = parse_cli_date(to)?;
 
             // Parse format string
-            let compilation_format = match format.to_lowercase().as_str() {
-                "chronological" => CompilationFormat::Chronological,
-                "grouped" => CompilationFormat::Grouped,
+            let compilation_format = format
+                .parse::<CompilationFormat>()
+                .map_err(DjourError::Config)?;
+            let group_by = group_by
+                .map(|unit| unit.parse::<GroupBy>())
+                .transpose()
+                .map_err(DjourError::Config)?;
+            if group_by.is_some() && compilation_format != CompilationFormat::Chronological {
+                return Err(DjourError::Config(format!(
+                    "--group-by only applies to the chronological format, not '{}'",
+                    compilation_format.as_str()
+                )));
+            }
+
+            if let Some(format) = &date_format {
+                check_date_format(format).map_err(|_| {
+                    DjourError::Config(format!(
+                        "Invalid --date-format: '{}'. Use a strftime format such as %Y-%m-%d",
+                        format
+                    ))
+                })?;
+            }
+
+            // --include-context alone means the nearest heading only
+            let depth = match context_depth {
+                Some(depth) => depth.parse::<ContextDepth>().map_err(DjourError::Config)?,
+                None => ContextDepth::Levels(usize::from(include_context)),
+            };
+            let style = match context_style.to_lowercase().as_str() {
+                "headings" => ContextStyle::Headings,
+                "breadcrumb" => ContextStyle::Breadcrumb,
                 _ => {
                     return Err(DjourError::Config(format!(
-                        "Invalid format: {}. Use 'chronological' or 'grouped'",
-                        format
+                        "Invalid context style: {}. Use 'headings' or 'breadcrumb'",
+                        context_style
                     )))
                 }
             };
 
             // Create compile options
-            let options = CompileOptions {
-                query,
+            let mut options = CompileOptions {
+                query: query.unwrap_or_default(),
                 output,
                 from: from_date,
                 to: to_date,
+                changed_since: None,
                 format: compilation_format,
-                include_context,
-                recursive,
+                group_by,
+                date_format,
+                context: ContextOptions { depth, style },
+                recursive: recursive || include_archived,
+                include_archived,
+                manifest,
+                strict,
+                embed_links,
+                notes,
+                exclude,
+                title_filter,
+                attribution: !no_attribution,
+                budget: CompileBudget {
+                    max_blocks: max_blocks.map(|n| n as usize),
+                    max_bytes: max_bytes.map(|n| n as usize),
+                    keep: keep.parse::<KeepOrder>().map_err(DjourError::Config)?,
+                },
+                suggest_limit,
+                include_private,
+                allow_exclusion_only,
             };
 
-            // Execute compilation
-            let output_path = compile_tags(&repo, options)?;
+            // A document on stdin needs no journal
+            if stdin {
+                let mut document = String::new();
+                std::io::stdin().read_to_string(&mut document)?;
+                print!("{}", compile_document(&document, &options, assume_date)?);
+                return Ok(());
+            }
+
+            // Discover repository
+            let repo = discover()?;
+            let config = repo.load_config()?;
+            if refresh_all {
+                return refresh_compilations(&repo, quiet);
+            }
+            if clean {
+                if !clean_compilations(&repo, yes, quiet)? {
+                    return Ok(());
+                }
+                // --clean alone only cleans
+                if options.query.is_empty() && rule.is_none() && !all_rules && again.is_none() {
+                    return Ok(());
+                }
+            }
+            match &again {
+                Some(slot) => {
+                    options = load_replay(&repo, &config, slot.as_deref())?;
+                    if !quiet {
+                        println!("Replaying: {}", command_line(&options));
+                    }
+                }
+                None => options.changed_since = parse_cli_since(changed_since, config.locale)?,
+            }
+
+            // Files named with --note are compiled even when a pattern excludes them
+            let excludes = exclude_patterns(&config, &options.exclude)?;
+            for note in &options.notes {
+                if let Some(pattern) = excludes.matching(note.trim_start_matches("./")) {
+                    eprintln!(
+                        "warning: {} matches exclude pattern '{}'; compiling it because --note names it",
+                        note, pattern
+                    );
+                }
+            }
+
+            // Execute compilation; structural warnings are printed after the normal output
+            let mut diagnostics = Diagnostics::new();
+            let progress = ProgressLine::for_stderr(quiet);
+            if rule.is_some() || all_rules {
+                let selection = match rule {
+                    Some(name) => RuleSelection::One(name),
+                    None => RuleSelection::All,
+                };
+                let result = compile_rules(
+                    &repo,
+                    &selection,
+                    &options,
+                    &mut diagnostics,
+                    Some(&|done, total| progress.update(done, total)),
+                );
+                progress.finish();
+                if result.is_err() {
+                    print_diagnostics(&diagnostics);
+                }
+                for rule in result? {
+                    match rule.report {
+                        Some(report) if !quiet => {
+                            let printable = report
+                                .output_path
+                                .strip_prefix(repo.root())
+                                .unwrap_or(&report.output_path)
+                                .to_string_lossy()
+                                .into_owned();
+                            println!("{}: {}", rule.name, printable);
+                            eprintln!("  {}", report.summary());
+                        }
+                        Some(_) => {}
+                        None => eprintln!(
+                            "warning: rule '{}' matched nothing; its output was not written",
+                            rule.name
+                        ),
+                    }
+                }
+                print_diagnostics(&diagnostics);
+                return Ok(());
+            }
+            if stdout {
+                let result = compile_to_string(
+                    &repo,
+                    &options,
+                    &mut diagnostics,
+                    Some(&|done, total| progress.update(done, total)),
+                );
+                progress.finish();
+                if matches!(result, Err(DjourError::TagNotFound(_))) {
+                    print_mode_hint(&repo);
+                }
+                print_diagnostics(&diagnostics);
+                let (markdown, report) = result?;
+                print_excluded(report.excluded, quiet);
+                print!("{}", markdown);
+                return Ok(());
+            }
+            let result = compile_tags(
+                &repo,
+                &options,
+                &mut diagnostics,
+                Some(&|done, total| progress.update(done, total)),
+            );
+            progress.finish();
+            if result.is_err() {
+                print_diagnostics(&diagnostics);
+            }
+            if matches!(result, Err(DjourError::TagNotFound(_))) {
+                print_mode_hint(&repo);
+            }
+            let report = result?;
+            let output_path = &report.output_path;
+            print_excluded(report.excluded, quiet);
+            if let Err(e) = repo
+                .state_paths(&config)
+                .and_then(|paths| save_compile(&paths.cache_dir(), save_as.as_deref(), &options))
+            {
+                eprintln!("warning: could not save this compile for --again: {}", e);
+            }
 
             if open {
-                let config = repo.load_config()?;
-                let editor = EditorSession::new(config.get_editor());
-                editor.open(&output_path)?;
-            } else {
+                let viewer = match with_viewer {
+                    Some(command) if command.trim().is_empty() => {
+                        return Err(DjourError::Config(
+                            "Invalid value for --with: the viewer command is empty".to_string(),
+                        ))
+                    }
+                    Some(command) => EditorSession::new(command).with_role(EditorRole::Viewer),
+                    None => EditorSession::for_role(&repo.load_config()?, EditorRole::Viewer),
+                };
+                viewer.open(output_path)?;
+            } else if !quiet {
                 let printable = output_path
                     .strip_prefix(repo.root())
-                    .unwrap_or(&output_path)
+                    .unwrap_or(output_path)
                     .to_string_lossy();
                 println!("{}", printable);
+                eprintln!("{}", report.summary());
+            }
+            if let Some(related) = report.related_line().filter(|_| !quiet) {
+                eprintln!("{}", related);
+            }
+            print_diagnostics(&diagnostics);
+
+            if watch {
+                if !quiet {
+                    println!("Watching for changes (Ctrl-C to stop)...");
+                }
+                watch_compilation(&repo, &options, |result, diagnostics| {
+                    let timestamp = chrono::Local::now().format("%H:%M:%S");
+                    match result {
+                        Ok(_) if quiet => {}
+                        Ok(outcome) => {
+                            println!(
+                                "[{}] recompiled ({} blocks)",
+                                timestamp, outcome.block_count
+                            )
+                        }
+                        Err(e) => eprintln!("[{}] {}", timestamp, e),
+                    }
+                    print_diagnostics(diagnostics);
+                })?;
+            }
+
+            Ok(())
+        }
+        Some(Commands::Rename { old, new, dry_run }) => {
+            let repo = discover()?;
+            let report = rename_note(&repo, &old, &new, dry_run)?;
+            if quiet && !report.dry_run {
+                return Ok(());
+            }
+
+            let (rename_verb, update_verb) = if report.dry_run {
+                ("Would rename", "would update")
+            } else {
+                ("Renamed", "updated")
+            };
+            println!(
+                "{} {} -> {}; {} {} link(s) in {} file(s).",
+                rename_verb,
+                report.from,
+                report.to,
+                update_verb,
+                report.total_links(),
+                report.changes.len()
+            );
+            for change in &report.changes {
+                println!("{} ({})", change.filename, change.links);
+            }
+            Ok(())
+        }
+        Some(Commands::Archive { before, dry_run }) => {
+            let repo = discover()?;
+            let before = parse_cli_date(Some(before))?.expect("--before is required");
+
+            if !dry_run {
+                repo.ensure_writable("archive notes in", repo.root())?;
+            }
+            let report = archive_notes(&repo, ArchiveOptions { before, dry_run })?;
+            if quiet && !report.dry_run {
+                return Ok(());
+            }
+
+            if report.dry_run {
+                println!("Dry run: {} note(s) would be archived.", report.moves.len());
+            } else {
+                println!("Archived {} note(s).", report.moves.len());
+            }
+            for planned in report.moves {
+                println!("{} -> {}", planned.from, planned.to);
+            }
+
+            Ok(())
+        }
+        Some(Commands::Pin { note: None }) => {
+            let repo = discover()?;
+            let config = repo.load_config()?;
+            let pins = pinned_notes(&repo, &config);
+            if pins.is_empty() {
+                if !quiet {
+                    println!("No pinned notes");
+                }
+                return Ok(());
+            }
+            let style = OutputStyle::detect(no_color);
+            print!(
+                "{}",
+                format_pinned_notes(&pins, style, |entry| {
+                    repo.read_note(&entry.filename)
+                        .ok()
+                        .and_then(|content| first_heading(&content))
+                })
+            );
+            Ok(())
+        }
+        Some(Commands::Pin { note: Some(note) }) => {
+            let repo = discover()?;
+            let outcome = pin_note(&repo, &note)?;
+            if !quiet {
+                if outcome.added {
+                    println!("Pinned {}", outcome.filename);
+                } else {
+                    println!("{} is already pinned", outcome.filename);
+                }
+            }
+            Ok(())
+        }
+        Some(Commands::Unpin { note }) => {
+            let repo = discover()?;
+            let filename = unpin_note(&repo, &note)?;
+            if !quiet {
+                println!("Unpinned {}", filename);
+            }
+            Ok(())
+        }
+        Some(Commands::Doctor) => {
+            let repo = discover()?;
+            let orphans = repo.find_orphaned_temp_files()?;
+            for filename in &orphans {
+                println!("{}: temp file left by an interrupted write", filename);
+            }
+            if !quiet {
+                if orphans.is_empty() {
+                    println!("No problems found");
+                } else {
+                    println!("Run 'djour clean' to delete the temp files.");
+                }
+            }
+            Ok(())
+        }
+        Some(Commands::Clean { dry_run }) => {
+            let repo = discover()?;
+            if !dry_run {
+                repo.ensure_writable("clean up", repo.root())?;
+            }
+            let report = clean_temp_files(&repo, dry_run)?;
+            if report.removed.is_empty() {
+                if !quiet {
+                    println!("No temp files left by interrupted writes");
+                }
+                return Ok(());
             }
+            for filename in &report.removed {
+                println!("{}", filename);
+            }
+            if !quiet {
+                if report.dry_run {
+                    println!(
+                        "Dry run: {} temp file(s) would be removed.",
+                        report.removed.len()
+                    );
+                } else {
+                    println!("Removed {} temp file(s).", report.removed.len());
+                }
+            }
+            Ok(())
+        }
+        Some(Commands::Stats {
+            badge_markdown,
+            write_badge,
+        }) => {
+            let repo = discover()?;
+            let stats = journal_stats(&repo, chrono::Local::now().date_naive())?;
+            match write_badge {
+                Some(path) => {
+                    let changed = write_stats_block(&repo, &path, &format_stats_badges(&stats))?;
+                    if !quiet {
+                        if changed {
+                            println!("Updated stats in {}", path.display());
+                        } else {
+                            println!("Stats in {} are up to date", path.display());
+                        }
+                    }
+                }
+                None if badge_markdown => print!("{}", format_stats_badges(&stats)),
+                None => print!("{}", format_stats(&stats)),
+            }
+            Ok(())
+        }
+        Some(Commands::Env { json }) => {
+            let current_dir = std::env::current_dir()?;
+            let settings = effective_settings(journal.as_deref(), &process_env, &current_dir);
+            if json {
+                print!("{}", format_env_json(&settings)?);
+            } else {
+                print!("{}", format_env(&settings));
+            }
+            Ok(())
+        }
+        Some(Commands::Version { check }) => {
+            if !check {
+                println!("djour {}", env!("CARGO_PKG_VERSION"));
+                return Ok(());
+            }
+            // Outside a journal the default endpoint is used
+            let configured = discover()
+                .and_then(|repo| repo.load_config())
+                .map(|config| config.update_url)
+                .unwrap_or_default();
+            println!("{}", update::check_now(update::update_url(&configured))?);
+            Ok(())
+        }
+        Some(Commands::Template {
+            action: TemplateCommand::Check { name },
+        }) => {
+            let repo = discover()?;
+            let config = repo.load_config()?;
+            let checks = check_templates(&repo, &config, name.as_deref())?;
+            if checks.is_empty() {
+                println!("No custom templates in {}", TEMPLATES_DIR);
+            }
+            let mut issue_count = 0;
+            for check in &checks {
+                let label = if check.custom {
+                    format!("{}/{}", TEMPLATES_DIR, check.name)
+                } else {
+                    format!("{} (built-in)", check.name)
+                };
+                if check.issues.is_empty() {
+                    println!("{}: ok", label);
+                }
+                for issue in &check.issues {
+                    println!("{}: {}", label, issue);
+                }
+                issue_count += check.issues.len();
+            }
+            if issue_count == 0 {
+                Ok(())
+            } else {
+                Err(DjourError::Template(format!(
+                    "{} issue(s) found; rendered notes would show them as written",
+                    issue_count
+                )))
+            }
+        }
 
+        Some(Commands::Recurring {
+            action: RecurringCommand::List,
+        }) => {
+            let repo = discover()?;
+            let recurring = list_recurring(&repo)?;
+            if recurring.directives.is_empty() && recurring.errors.is_empty() {
+                println!("No recurring entries in {}", RECURRING_FILE);
+            }
+            let today = chrono::Local::now().date_naive();
+            let width = recurring
+                .directives
+                .iter()
+                .map(|directive| directive.rule.to_string().len())
+                .max()
+                .unwrap_or(0);
+            for directive in &recurring.directives {
+                let next = directive
+                    .rule
+                    .next_on_or_after(today)
+                    .map(|date| date.format("%d-%m-%Y").to_string())
+                    .unwrap_or_default();
+                println!(
+                    "{:width$}  next {}  {}",
+                    directive.rule.to_string(),
+                    next,
+                    directive.entry
+                );
+            }
+            for error in &recurring.errors {
+                eprintln!("{}: {}", RECURRING_FILE, error);
+            }
+            if recurring.errors.is_empty() {
+                Ok(())
+            } else {
+                Err(DjourError::Config(format!(
+                    "{} invalid line(s) in {}; new notes cannot be created until they are fixed",
+                    recurring.errors.len(),
+                    RECURRING_FILE
+                )))
+            }
+        }
+        Some(Commands::Journal { action }) => {
+            let mut registry = JournalRegistry::load()?;
+            match action {
+                JournalCommand::Add {
+                    name,
+                    path,
+                    replace,
+                } => {
+                    let root = add_journal(&mut registry, &name, &path, replace)?;
+                    registry.save()?;
+                    if !quiet {
+                        println!("Registered journal '{}' at {}", name, root.display());
+                    }
+                }
+                JournalCommand::List => {
+                    // Mark the journal the other commands would use from here, if any
+                    let current = discover().ok();
+                    let listings =
+                        list_journals(&registry, current.as_ref().map(|repo| repo.root()));
+                    if listings.is_empty() {
+                        println!("No journals registered. Add one with 'djour journal add <NAME> <PATH>'");
+                    }
+                    let width = listings.iter().map(|j| j.name.len()).max().unwrap_or(0);
+                    for listing in &listings {
+                        println!(
+                            "{} {:width$}  {}",
+                            if listing.current { "*" } else { " " },
+                            listing.name,
+                            listing.path.display()
+                        );
+                    }
+                }
+                JournalCommand::Remove { name } => {
+                    let root = remove_journal(&mut registry, &name)?;
+                    registry.save()?;
+                    if !quiet {
+                        println!("Removed journal '{}' ({})", name, root.display());
+                    }
+                }
+            }
+            Ok(())
+        }
+        Some(Commands::Remind { recursive, notify }) => {
+            let repo = discover()?;
+            let progress = ProgressLine::for_stderr(quiet);
+            let update = |done, total| progress.update(done, total);
+            let mut diagnostics = Diagnostics::new();
+            let reminders = due_reminders(
+                &repo,
+                recursive,
+                chrono::Local::now().date_naive(),
+                &mut diagnostics,
+                Some(&update),
+            )?;
+            progress.finish();
+
+            if !(quiet && reminders.is_empty()) {
+                print!("{}", format_reminder_list(&reminders));
+            }
+            print_diagnostics(&diagnostics);
+            if notify {
+                notify_reminders(&mut DesktopNotifier, &reminders)?;
+            }
+            if reminders.iter().any(|reminder| reminder.is_overdue()) {
+                std::process::exit(exit_code::REMINDERS_OVERDUE);
+            }
+            Ok(())
+        }
+        Some(Commands::Note { action }) => {
+            let repo = discover()?;
+            let (time_ref, print_path) = match action {
+                NoteCommand::Path { time_ref } => (time_ref, true),
+                NoteCommand::Exists { time_ref } => (time_ref, false),
+            };
+
+            let location = locate_note(&repo, &time_ref)?;
+            if print_path {
+                println!("{}", location.path.display());
+            }
+            if !location.exists {
+                std::process::exit(exit_code::NOTE_MISSING);
+            }
             Ok(())
         }
         Some(Commands::Mode {
@@ -180,11 +1179,20 @@ fn run(cli: Cli) -> Result<(), DjourError> {
             from,
             dry_run,
             archive_dir,
+            promote_headings,
+            show_diff,
+            diff_lines,
+            force,
         }) => {
-            let repo = FileSystemRepository::discover()?;
-            eprintln!(
-                "Warning: mode migration is non-recursive; --recursive is omitted for this command."
-            );
+            let repo = discover()?;
+            if !dry_run {
+                repo.ensure_writable("migrate", repo.root())?;
+            }
+            if !quiet {
+                eprintln!(
+                    "Warning: mode migration is non-recursive; --recursive is omitted for this command."
+                );
+            }
 
             let to_mode = JournalMode::from_str(&to).map_err(DjourError::Config)?;
             let from_mode = match from {
@@ -197,6 +1205,14 @@ fn run(cli: Cli) -> Result<(), DjourError> {
                 from_mode,
                 dry_run,
                 archive_dir,
+                quiet,
+                promote_headings,
+                show_diff: show_diff.then(|| DiffOptions {
+                    max_lines: Some(diff_lines),
+                    color: OutputStyle::detect(no_color).color,
+                    ..DiffOptions::default()
+                }),
+                force,
             };
 
             migrate_mode(&repo, options)
@@ -208,21 +1224,52 @@ fn run(cli: Cli) -> Result<(), DjourError> {
             to,
             recursive,
             dry_run,
+            update_compilations,
+            update_queries,
+            auto_normalize,
+            yes,
+            no_backup,
         }) => {
-            let repo = FileSystemRepository::discover()?;
+            if auto_normalize && !dry_run && !yes {
+                return Err(DjourError::Config(
+                    "--auto-normalize renames tags across notes. Review the plan with --dry-run, then apply it with --yes".to_string(),
+                ));
+            }
+
+            let repo = discover()?;
+            if !dry_run {
+                repo.ensure_writable("retag notes in", repo.root())?;
+            }
             let from_date = parse_cli_date(from)?;
             let to_date = parse_cli_date(to)?;
 
             let options = RetagOptions {
-                from_tag,
-                to_tag,
+                from_tag: from_tag.unwrap_or_default(),
+                to_tag: to_tag.unwrap_or_default(),
                 from: from_date,
                 to: to_date,
                 recursive,
                 dry_run,
+                update_compilations,
+                update_queries,
+                auto_normalize,
+                backup: !no_backup,
             };
 
             let report = retag_notes(&repo, options)?;
+            print_diagnostics(&report.diagnostics);
+            if quiet && !report.dry_run {
+                return Ok(());
+            }
+
+            if auto_normalize {
+                println!("Spelling variants: {} rename(s).", report.renames.len());
+                for (from_tag, to_tag) in &report.renames {
+                    println!("#{} -> #{}", from_tag, to_tag);
+                }
+                println!();
+            }
+
             if report.dry_run {
                 println!(
                     "Dry run: {} file(s) would be updated with {} replacement(s).",
@@ -238,16 +1285,85 @@ fn run(cli: Cli) -> Result<(), DjourError> {
             for change in report.changes {
                 println!("{} ({})", change.filename, change.replacements);
             }
+            if let Some(archive_dir) = &report.archive_dir {
+                println!("Originals backed up in {}", archive_dir);
+            }
+
+            let verb = if report.dry_run {
+                "would be updated"
+            } else {
+                "updated"
+            };
+
+            if update_compilations {
+                println!();
+                println!(
+                    "Compilations: {} file(s) {}.",
+                    report.compilation_files_changed.len(),
+                    verb
+                );
+                for change in report.compilation_files_changed {
+                    println!("{} ({})", change.filename, change.replacements);
+                }
+            }
+
+            if update_queries {
+                println!();
+                println!(
+                    "Saved queries: {} query(ies) {}.",
+                    report.queries_changed.len(),
+                    verb
+                );
+                for change in report.queries_changed {
+                    println!("{}: {} -> {}", change.name, change.before, change.after);
+                }
+            }
 
             Ok(())
         }
         None => {
             // Check if time_ref provided (open command)
             if let Some(time_ref) = cli.time_ref {
-                // Resolve/create note and print filename
-                let repo = FileSystemRepository::discover()?;
-                let filename = open_note(&repo, &time_ref, cli.open)?;
-                println!("{}", filename);
+                // Resolve (and create when asked) the note, then print its filename
+                let repo = discover()?;
+                let assume_yes = cli.yes;
+                let options = OpenNoteOptions {
+                    open_in_editor: cli.open,
+                    create: cli.create,
+                    interpret_as: cli
+                        .interpret_as
+                        .map(|kind| kind.parse::<RefKind>())
+                        .transpose()
+                        .map_err(DjourError::Config)?,
+                };
+                let mut diagnostics = Diagnostics::new();
+                let opened = open_note_with_options(
+                    &repo,
+                    &time_ref,
+                    options,
+                    &mut diagnostics,
+                    |date, distance| assume_yes || confirm_far_date(date, distance),
+                )?;
+                print_template_warnings(&diagnostics, quiet);
+                if opened.implicitly_created && !quiet {
+                    eprintln!(
+                        "Note: created {} because create_on_resolve is on. This default will change; \
+                        pass --create to create notes, or run 'djour config create_on_resolve false' \
+                        to only print the filename.",
+                        opened.filename
+                    );
+                }
+                // A weekly or monthly note covers more than the day asked for
+                if let (Some(period), Some(date), false) = (&opened.period, opened.date, quiet) {
+                    eprintln!(
+                        "{} ({}) — resolved from '{}' = {}",
+                        opened.filename,
+                        period,
+                        time_ref,
+                        date.format("%d-%m-%Y")
+                    );
+                }
+                println!("{}", opened.filename);
                 Ok(())
             } else {
                 // No command and no time_ref, show help
@@ -259,6 +1375,210 @@ fn run(cli: Cli) -> Result<(), DjourError> {
     }
 }
 
+/// Report on stderr how many notes exclude patterns left out
+fn print_excluded(excluded: usize, quiet: bool) {
+    if excluded > 0 && !quiet {
+        let noun = if excluded == 1 { "file" } else { "files" };
+        eprintln!("excluded {} {}", excluded, noun);
+    }
+}
+
+/// Point at the likely cause when a command found nothing because the notes
+/// are named for another mode. Best effort: lookup problems print nothing.
+fn print_mode_hint(repository: &FileSystemRepository) {
+    let hint = repository
+        .load_config()
+        .and_then(|config| mode_mismatch_hint(repository, &config));
+    if let Ok(Some(hint)) = hint {
+        eprintln!("hint: {}", hint);
+    }
+}
+
+/// Print collected diagnostics to stderr, one line each.
+fn print_diagnostics(diagnostics: &Diagnostics) {
+    for diagnostic in diagnostics.iter() {
+        eprintln!("{}", diagnostic);
+    }
+}
+
+/// Print template issues found while creating a note or initializing a
+/// journal, with a pointer to `djour template check` unless `quiet`.
+fn print_template_warnings(diagnostics: &Diagnostics, quiet: bool) {
+    print_diagnostics(diagnostics);
+    if !diagnostics.is_empty() && !quiet {
+        eprintln!("Run 'djour template check' to list them again.");
+    }
+}
+
+/// Delete the compilations djour generated, after listing them and asking
+/// unless `yes`. Returns false when the deletion was declined.
+fn clean_compilations(
+    repo: &FileSystemRepository,
+    yes: bool,
+    quiet: bool,
+) -> Result<bool, DjourError> {
+    let files = find_compilations(repo)?;
+    for path in files.other.iter().filter(|_| !quiet) {
+        eprintln!("Keeping {}: not generated by djour", path);
+    }
+    let generated = files.generated;
+    if generated.is_empty() {
+        if !quiet {
+            println!("No generated compilations to delete");
+        }
+        return Ok(true);
+    }
+    if !quiet || !yes {
+        for compilation in &generated {
+            println!("{}", compilation.path);
+        }
+    }
+    if !yes {
+        if !std::io::stdin().is_terminal() {
+            return Err(DjourError::Config(format!(
+                "Deleting {} compilation(s) needs confirmation; run again with --yes",
+                generated.len()
+            )));
+        }
+        eprint!(
+            "Delete {} generated compilation(s)? [y/N] ",
+            generated.len()
+        );
+        let _ = std::io::stderr().flush();
+        let mut answer = String::new();
+        if std::io::stdin().read_line(&mut answer).is_err()
+            || !matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+        {
+            println!("Nothing deleted");
+            return Ok(false);
+        }
+    }
+    remove_compilations(repo, &generated)?;
+    if !quiet {
+        println!("Deleted {} compilation(s)", generated.len());
+    }
+    Ok(true)
+}
+
+/// Regenerate every generated compilation from the command recorded in it,
+/// warning about the ones that cannot be
+fn refresh_compilations(repo: &FileSystemRepository, quiet: bool) -> Result<(), DjourError> {
+    let generated = find_compilations(repo)?.generated;
+    if generated.is_empty() {
+        if !quiet {
+            println!("No generated compilations to refresh");
+        }
+        return Ok(());
+    }
+    let mut failed = 0;
+    for compilation in &generated {
+        let mut diagnostics = Diagnostics::new();
+        let result = refresh_options(repo, compilation)
+            .and_then(|options| compile_tags(repo, &options, &mut diagnostics, None));
+        match result {
+            Ok(report) if !quiet => {
+                println!("{}", compilation.path);
+                eprintln!("  {}", report.summary());
+            }
+            Ok(_) => {}
+            Err(e) => {
+                failed += 1;
+                eprintln!("warning: {} left unchanged: {}", compilation.path, e);
+            }
+        }
+        print_diagnostics(&diagnostics);
+    }
+    if failed > 0 {
+        return Err(DjourError::Config(format!(
+            "{} of {} compilation(s) could not be refreshed",
+            failed,
+            generated.len()
+        )));
+    }
+    Ok(())
+}
+
+/// Ask on the terminal before creating a note far from today.
+/// Non-interactive sessions are never confirmed implicitly.
+fn confirm_far_date(date: NaiveDate, distance: i64) -> bool {
+    if !std::io::stdin().is_terminal() {
+        return false;
+    }
+
+    let direction = if distance > 0 {
+        "in the future"
+    } else {
+        "in the past"
+    };
+    eprint!(
+        "{} is {} day(s) {}. Create the note? [y/N] ",
+        date.format("%d-%m-%Y"),
+        distance.abs(),
+        direction
+    );
+    let _ = std::io::stderr().flush();
+
+    let mut answer = String::new();
+    if std::io::stdin().read_line(&mut answer).is_err() {
+        return false;
+    }
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+/// Report problems in an edited config.toml and ask whether to edit it again.
+///
+/// The answer is read from stdin even when it is not a terminal, so scripts can
+/// pipe it; end of input means no.
+fn confirm_reopen_config(problems: &[String]) -> bool {
+    eprintln!("config.toml has {} problem(s):", problems.len());
+    for problem in problems {
+        eprintln!("  {}", problem);
+    }
+    eprint!("Re-open config.toml in the editor? (No restores the previous version) [y/N] ");
+    let _ = std::io::stderr().flush();
+
+    let mut answer = String::new();
+    if std::io::stdin().read_line(&mut answer).is_err() {
+        return false;
+    }
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+/// The compile options saved in `slot` (`None` = the last compile), for `--again`
+fn load_replay(
+    repo: &FileSystemRepository,
+    config: &Config,
+    slot: Option<&str>,
+) -> Result<CompileOptions, DjourError> {
+    let cache_dir = repo.state_paths(config)?.cache_dir();
+    match load_compile(&cache_dir, slot)? {
+        SavedCompile::Found(options) => return Ok(options),
+        SavedCompile::Missing => {}
+        SavedCompile::Stale(reason) => {
+            let path = saved_compile_path(&cache_dir, slot);
+            let path = path.strip_prefix(repo.root()).unwrap_or(&path);
+            eprintln!("warning: ignoring {}: {}", path.display(), reason);
+        }
+    }
+    Err(DjourError::Config(match slot {
+        Some(name) => format!(
+            "No compile saved as '{}'; save one with: djour compile <query> --save-as {}",
+            name, name
+        ),
+        None => "No compile to replay; run djour compile <query> first".to_string(),
+    }))
+}
+
+/// Resolve a `--changed-since` value (TIME_REF or DD-MM-YYYY) against today
+fn parse_cli_since(value: Option<String>, locale: Locale) -> Result<Option<NaiveDate>, DjourError> {
+    value
+        .map(|s| {
+            TimeReference::parse_with_locale(&s, locale)
+                .map(|time_ref| time_ref.resolve(chrono::Local::now().date_naive()))
+        })
+        .transpose()
+}
+
 fn parse_cli_date(value: Option<String>) -> Result<Option<NaiveDate>, DjourError> {
     value
         .map(|s| {
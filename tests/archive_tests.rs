@@ -0,0 +1,221 @@
+//! Integration tests for archive command
+
+#![allow(deprecated)]
+
+use predicates::prelude::*;
+use std::fs;
+use tempfile::TempDir;
+
+mod common;
+use common::djour_cmd;
+
+fn init_journal(temp: &TempDir) {
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+}
+
+fn create_note(temp: &TempDir, filename: &str, content: &str) {
+    fs::write(temp.path().join(filename), content).unwrap();
+}
+
+#[test]
+fn test_archive_moves_old_notes_into_yearly_dirs() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    create_note(&temp, "2022-11-02.md", "Old #work");
+    create_note(&temp, "2023-05-17.md", "Older #work");
+    create_note(&temp, "2024-01-01.md", "Current #work");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["archive", "--before", "01-01-2024"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Archived 2 note(s)."))
+        .stdout(predicate::str::contains(
+            "2023-05-17.md -> archive/2023/2023-05-17.md",
+        ));
+
+    assert!(temp.path().join("archive/2022/2022-11-02.md").exists());
+    assert!(temp.path().join("archive/2023/2023-05-17.md").exists());
+    assert!(!temp.path().join("2023-05-17.md").exists());
+    assert!(temp.path().join("2024-01-01.md").exists());
+}
+
+#[test]
+fn test_archive_dry_run_prints_plan_without_moving() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    create_note(&temp, "2023-05-17.md", "Older");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["archive", "--before", "01-01-2024", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Dry run: 1 note(s) would be archived.",
+        ))
+        .stdout(predicate::str::contains(
+            "2023-05-17.md -> archive/2023/2023-05-17.md",
+        ));
+
+    assert!(temp.path().join("2023-05-17.md").exists());
+    assert!(!temp.path().join("archive").exists());
+}
+
+#[test]
+fn test_archive_collision_aborts_whole_run() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    create_note(&temp, "2022-11-02.md", "Old");
+    create_note(&temp, "2023-05-17.md", "Older");
+    fs::create_dir_all(temp.path().join("archive/2023")).unwrap();
+    create_note(&temp, "archive/2023/2023-05-17.md", "Already archived");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["archive", "--before", "01-01-2024"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("archive/2023/2023-05-17.md"));
+
+    assert!(temp.path().join("2022-11-02.md").exists());
+    assert!(temp.path().join("2023-05-17.md").exists());
+    assert!(!temp.path().join("archive/2022").exists());
+}
+
+#[test]
+fn test_archive_year_month_layout() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    create_note(&temp, "2023-05-17.md", "Older");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "archive_layout", "year-month"])
+        .assert()
+        .success();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["archive", "--before", "01-01-2024"])
+        .assert()
+        .success();
+
+    assert!(temp.path().join("archive/2023/05/2023-05-17.md").exists());
+}
+
+#[test]
+fn test_compile_with_include_archived_finds_archived_content() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    create_note(&temp, "2023-12-31.md", "Year-end review #work");
+    create_note(&temp, "2024-01-02.md", "New year planning #work");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["archive", "--before", "01-01-2024"])
+        .assert()
+        .success();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--include-archived"])
+        .assert()
+        .success();
+
+    let content = fs::read_to_string(temp.path().join(".compilations/work.md")).unwrap();
+    assert!(content.contains("Year-end review"));
+    assert!(content.contains("New year planning"));
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["list", "--include-archived"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("archive/2023/2023-12-31.md"));
+}
+
+/// A journal with two live notes and notes under archive/ and trash/
+fn journal_with_archived_notes(temp: &TempDir) {
+    init_journal(temp);
+    fs::create_dir_all(temp.path().join("archive/2023")).unwrap();
+    fs::create_dir_all(temp.path().join("trash")).unwrap();
+    fs::create_dir_all(temp.path().join("notes")).unwrap();
+    create_note(temp, "2026-10-16.md", "Live #work #home");
+    create_note(temp, "notes/2026-10-10.md", "Nested #work");
+    create_note(temp, "archive/2023/2023-05-17.md", "Old #work #legacy");
+    create_note(temp, "trash/2024-01-02.md", "Deleted #work");
+}
+
+#[test]
+fn test_list_skips_archived_notes_unless_included() {
+    let temp = TempDir::new().unwrap();
+    journal_with_archived_notes(&temp);
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["list", "--recursive"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("notes/2026-10-10.md"))
+        .stdout(predicate::str::contains("archive/").not())
+        .stdout(predicate::str::contains("trash/").not());
+
+    // --include-archived implies --recursive
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["list", "--include-archived"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("notes/2026-10-10.md"))
+        .stdout(predicate::str::contains("archive/2023/2023-05-17.md"))
+        .stdout(predicate::str::contains("trash/2024-01-02.md"));
+}
+
+#[test]
+fn test_tag_counts_and_compile_skip_archived_notes_unless_included() {
+    let temp = TempDir::new().unwrap();
+    journal_with_archived_notes(&temp);
+    let tags = |args: &[&str]| {
+        let output = djour_cmd()
+            .current_dir(temp.path())
+            .env("CLICOLOR_FORCE", "1")
+            .arg("--no-color")
+            .arg("tags")
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    let live = tags(&["--recursive"]);
+    let count = |output: &str, tag: &str| -> Option<String> {
+        output
+            .lines()
+            .find_map(|line| line.strip_prefix(tag))
+            .and_then(|rest| rest.split_whitespace().next().map(str::to_string))
+    };
+    assert_eq!(count(&live, "#work "), Some("2".to_string()), "{}", live);
+    assert!(!live.contains("#legacy"));
+    let all = tags(&["--include-archived"]);
+    assert_eq!(count(&all, "#work "), Some("4".to_string()), "{}", all);
+    assert!(all.contains("#legacy"));
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--recursive", "--stdout"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Nested"))
+        .stdout(predicate::str::contains("Old").not())
+        .stdout(predicate::str::contains("Deleted").not());
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--include-archived", "--stdout"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Old"))
+        .stdout(predicate::str::contains("Deleted"));
+}
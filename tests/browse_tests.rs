@@ -0,0 +1,24 @@
+//! Integration tests for browse command
+
+#![allow(deprecated)]
+#![cfg(feature = "tui")]
+
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+mod common;
+use common::djour_cmd;
+
+#[test]
+fn test_browse_requires_terminal() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("browse")
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("interactive terminal"))
+        .stderr(predicate::str::contains("djour list"));
+}
@@ -0,0 +1,266 @@
+//! Integration tests for the capture command
+
+#![allow(deprecated)]
+
+use chrono::NaiveDate;
+use djour::application::{capture, init};
+use djour::domain::{Diagnostics, JournalMode};
+use djour::infrastructure::FileSystemRepository;
+use predicates::prelude::*;
+use std::fs;
+use tempfile::TempDir;
+
+mod common;
+use common::djour_cmd;
+
+#[test]
+fn test_capture_outside_journal_fails() {
+    let temp = TempDir::new().unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("capture")
+        .assert()
+        .code(2);
+}
+
+#[test]
+fn test_capture_appends_to_section_for_time_of_day() {
+    let temp = TempDir::new().unwrap();
+    init(temp.path(), JournalMode::Daily).unwrap();
+    fs::create_dir_all(temp.path().join(".djour/templates")).unwrap();
+    fs::write(
+        temp.path().join(".djour/templates/daily.md"),
+        "# {DATE}\n\n## Morning\n\n## Afternoon\n\n## Evening\n",
+    )
+    .unwrap();
+    let config = fs::read_to_string(temp.path().join(".djour/config.toml")).unwrap();
+    fs::write(
+        temp.path().join(".djour/config.toml"),
+        format!(
+            "{}\n[sections]\nMorning = \"05:00-12:00\"\nAfternoon = \"12:00-18:00\"\nEvening = \"18:00-05:00\"\n",
+            config
+        ),
+    )
+    .unwrap();
+    let repo = FileSystemRepository::new(temp.path().to_path_buf());
+    let day = NaiveDate::from_ymd_opt(2025, 1, 15).unwrap();
+
+    for (hour, minute, text) in [
+        (13, 5, "Lunch with Sam"),
+        (0, 30, "Late reading"),
+        (7, 45, "Run"),
+    ] {
+        let outcome = capture(
+            &repo,
+            day.and_hms_opt(hour, minute, 0).unwrap(),
+            &mut Diagnostics::new(),
+            |initial| Ok(Some(format!("{}{}\n", initial, text))),
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(outcome.filename, "2025-01-15.md");
+        assert!(outcome.section.is_some());
+    }
+
+    let note = fs::read_to_string(temp.path().join("2025-01-15.md")).unwrap();
+    assert!(
+        note.ends_with(
+            "## Morning\n\n### 07:45\n\nRun\n\n\
+            ## Afternoon\n\n### 13:05\n\nLunch with Sam\n\n\
+            ## Evening\n\n### 00:30\n\nLate reading\n"
+        ),
+        "{:?}",
+        note
+    );
+}
+
+#[test]
+fn test_capture_without_matching_heading_appends_at_end() {
+    let temp = TempDir::new().unwrap();
+    init(temp.path(), JournalMode::Daily).unwrap();
+    let config = fs::read_to_string(temp.path().join(".djour/config.toml")).unwrap();
+    fs::write(
+        temp.path().join(".djour/config.toml"),
+        format!("{}\n[sections]\nMorning = \"05:00-12:00\"\n", config),
+    )
+    .unwrap();
+    fs::write(
+        temp.path().join("2025-01-15.md"),
+        "# Wednesday\n\nEarlier\n",
+    )
+    .unwrap();
+    let repo = FileSystemRepository::new(temp.path().to_path_buf());
+
+    let now = NaiveDate::from_ymd_opt(2025, 1, 15)
+        .unwrap()
+        .and_hms_opt(9, 0, 0)
+        .unwrap();
+    let outcome = capture(&repo, now, &mut Diagnostics::new(), |initial| {
+        Ok(Some(format!("{}Standup\n", initial)))
+    })
+    .unwrap()
+    .unwrap();
+
+    assert_eq!(outcome.section, None);
+    assert_eq!(
+        fs::read_to_string(temp.path().join("2025-01-15.md")).unwrap(),
+        "# Wednesday\n\nEarlier\n\n## 09:00\n\nStandup\n"
+    );
+}
+
+#[cfg(unix)]
+mod scripted_editor {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::{Path, PathBuf};
+
+    /// Write an "editor" running `body` with the buffer as `$1`; it also
+    /// records the buffer's path in `buffer-path`
+    fn write_editor(dir: &Path, body: &str) -> PathBuf {
+        let script = dir.join("editor.sh");
+        fs::write(
+            &script,
+            format!(
+                "#!/bin/sh\nprintf '%s' \"$1\" > '{}'\n{}\n",
+                dir.join("buffer-path").display(),
+                body
+            ),
+        )
+        .unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+        script
+    }
+
+    fn today_note() -> String {
+        chrono::Local::now().format("%Y-%m-%d.md").to_string()
+    }
+
+    /// Run `djour capture` in a fresh daily journal with an editor running `body`
+    fn capture_with(body: &str) -> (TempDir, TempDir, assert_cmd::assert::Assert) {
+        let temp = TempDir::new().unwrap();
+        let scripts = TempDir::new().unwrap();
+        djour_cmd().arg("init").arg(temp.path()).assert().success();
+        let editor = write_editor(scripts.path(), body);
+
+        let assert = djour_cmd()
+            .current_dir(temp.path())
+            .env("EDITOR", &editor)
+            .arg("capture")
+            .assert();
+        (temp, scripts, assert)
+    }
+
+    /// The temp buffer the editor was given, which must be gone by now
+    fn assert_buffer_removed(scripts: &TempDir) {
+        let buffer = fs::read_to_string(scripts.path().join("buffer-path")).unwrap();
+        assert!(buffer.ends_with(".md"), "{}", buffer);
+        assert!(!Path::new(&buffer).exists(), "{} was left behind", buffer);
+    }
+
+    #[test]
+    fn test_capture_appends_written_text_to_today() {
+        let (temp, scripts, assert) = capture_with("printf 'Call the plumber #home\\n' >> \"$1\"");
+
+        assert
+            .success()
+            .stdout(format!("Captured to {}\n", today_note()));
+        let note = fs::read_to_string(temp.path().join(today_note())).unwrap();
+        assert!(note.starts_with("# "), "{}", note);
+        let time_heading = note.find("\n## ").unwrap();
+        assert!(
+            note[time_heading..].ends_with("\n\nCall the plumber #home\n"),
+            "{}",
+            note
+        );
+        assert_buffer_removed(&scripts);
+    }
+
+    #[test]
+    fn test_unchanged_buffer_captures_nothing() {
+        let (temp, scripts, assert) = capture_with("exit 0");
+
+        assert.success().stdout("Nothing captured\n");
+        assert!(!temp.path().join(today_note()).exists());
+        assert_buffer_removed(&scripts);
+    }
+
+    #[test]
+    fn test_failing_editor_captures_nothing() {
+        let (temp, scripts, assert) = capture_with("printf 'half a thought\\n' >> \"$1\"\nexit 1");
+
+        assert
+            .success()
+            .stdout(predicate::str::contains("Nothing captured"));
+        assert!(!temp.path().join(today_note()).exists());
+        assert_buffer_removed(&scripts);
+    }
+
+    #[test]
+    fn test_capture_is_refused_when_read_only() {
+        let (temp, scripts, _) = capture_with("exit 0");
+        let editor = scripts.path().join("editor.sh");
+        fs::remove_file(scripts.path().join("buffer-path")).unwrap();
+
+        djour_cmd()
+            .current_dir(temp.path())
+            .env("EDITOR", &editor)
+            .args(["--read-only", "capture"])
+            .assert()
+            .code(1);
+        // The editor never ran
+        assert!(!scripts.path().join("buffer-path").exists());
+    }
+
+    #[test]
+    fn test_editor_command_expands_variables_and_tilde() {
+        let (temp, scripts, _) = capture_with("printf 'From the stub\\n' >> \"$1\"");
+
+        djour_cmd()
+            .current_dir(temp.path())
+            .env("STUB_DIR", scripts.path())
+            .env("EDITOR", "${STUB_DIR}/editor.sh $UNSET_STUB_FLAG")
+            .arg("capture")
+            .assert()
+            .success()
+            .stdout(format!("Captured to {}\n", today_note()))
+            .stderr(predicate::str::contains(
+                "references $UNSET_STUB_FLAG, which is not set",
+            ));
+
+        djour_cmd()
+            .current_dir(temp.path())
+            .env("HOME", scripts.path())
+            .env("EDITOR", "~/editor.sh")
+            .arg("capture")
+            .assert()
+            .success()
+            .stdout(format!("Captured to {}\n", today_note()));
+
+        let note = fs::read_to_string(temp.path().join(today_note())).unwrap();
+        assert_eq!(note.matches("From the stub").count(), 3, "{}", note);
+    }
+
+    #[test]
+    fn test_editor_shell_runs_command_through_sh() {
+        let (temp, scripts, _) = capture_with("printf 'Via sh\\n' >> \"$1\"");
+        djour_cmd()
+            .current_dir(temp.path())
+            .args(["config", "editor_shell", "true"])
+            .assert()
+            .success();
+
+        // Only a shell understands `&&`
+        djour_cmd()
+            .current_dir(temp.path())
+            .env("STUB_DIR", scripts.path())
+            .env("EDITOR", "true && \"$STUB_DIR/editor.sh\"")
+            .arg("capture")
+            .assert()
+            .success()
+            .stdout(format!("Captured to {}\n", today_note()));
+        let note = fs::read_to_string(temp.path().join(today_note())).unwrap();
+        assert_eq!(note.matches("Via sh").count(), 2, "{}", note);
+        assert_buffer_removed(&scripts);
+    }
+}
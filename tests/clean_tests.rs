@@ -0,0 +1,89 @@
+//! Integration tests for clean and doctor commands
+
+#![allow(deprecated)]
+
+use predicates::prelude::*;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+use tempfile::TempDir;
+
+mod common;
+use common::djour_cmd;
+
+/// Create `name` under `root`, last modified `age` ago
+fn temp_file(root: &Path, name: &str, age: Duration) {
+    let file = fs::File::create(root.join(name)).unwrap();
+    file.set_modified(SystemTime::now() - age).unwrap();
+}
+
+#[test]
+fn test_clean_removes_orphaned_temp_files_only() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+    let day = Duration::from_secs(24 * 60 * 60);
+    temp_file(temp.path(), "2025-01-15.md.djour-tmp-4242", day);
+    temp_file(temp.path(), "2025-01-16.md.djour-tmp-77-00ab12cd", day);
+    temp_file(temp.path(), "2025-01-17.md.djour-tmp-99-ff", Duration::ZERO);
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["clean", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(
+            "2025-01-15.md.djour-tmp-4242\n2025-01-16.md.djour-tmp-77-00ab12cd\n\
+             Dry run: 2 temp file(s) would be removed.\n",
+        );
+    assert!(temp.path().join("2025-01-15.md.djour-tmp-4242").exists());
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("clean")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Removed 2 temp file(s)."));
+    assert!(!temp.path().join("2025-01-15.md.djour-tmp-4242").exists());
+    assert!(!temp
+        .path()
+        .join("2025-01-16.md.djour-tmp-77-00ab12cd")
+        .exists());
+    // A write that may still be running is spared
+    assert!(temp.path().join("2025-01-17.md.djour-tmp-99-ff").exists());
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("clean")
+        .assert()
+        .success()
+        .stdout("No temp files left by interrupted writes\n");
+}
+
+#[test]
+fn test_doctor_reports_orphaned_temp_files() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("doctor")
+        .assert()
+        .success()
+        .stdout("No problems found\n");
+
+    temp_file(
+        temp.path(),
+        "2025-01-15.md.djour-tmp-4242",
+        Duration::from_secs(24 * 60 * 60),
+    );
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("doctor")
+        .assert()
+        .success()
+        .stdout(
+            "2025-01-15.md.djour-tmp-4242: temp file left by an interrupted write\n\
+             Run 'djour clean' to delete the temp files.\n",
+        );
+    assert!(temp.path().join("2025-01-15.md.djour-tmp-4242").exists());
+}
@@ -4,6 +4,9 @@
 //! - `input/`    initial journal tree copied to a temp directory
 //! - `scenario.toml` command list and command-level assertions
 //! - `expected/` expected final journal tree after executing scenario
+//!
+//! A scenario with `round_trip = true` must also leave every note outside
+//! `.djour/` byte for byte as it was in `input/`.
 
 use serde::Deserialize;
 use std::collections::BTreeSet;
@@ -14,6 +17,8 @@ use walkdir::WalkDir;
 
 #[derive(Debug, Deserialize)]
 struct Scenario {
+    #[serde(default)]
+    round_trip: bool,
     #[serde(rename = "command")]
     commands: Vec<CommandSpec>,
 }
@@ -176,6 +181,9 @@ fn run_case(case_dir: &Path) {
     }
 
     assert_trees_match(case_name, &expected_dir, temp.path());
+    if scenario.round_trip {
+        assert_trees_match_strict(case_name, &input_dir, temp.path());
+    }
 }
 
 fn run_djour(cwd: &Path, args: &[String]) -> Output {
@@ -239,6 +247,49 @@ fn assert_trees_match(case_name: &str, expected_root: &Path, actual_root: &Path)
     }
 }
 
+/// Like `assert_trees_match` for the notes outside `.djour/`, reporting the
+/// first differing byte so whitespace-only differences show up
+fn assert_trees_match_strict(case_name: &str, expected_root: &Path, actual_root: &Path) {
+    let notes = |root: &Path| -> BTreeSet<PathBuf> {
+        collect_relative_files(root)
+            .into_iter()
+            .filter(|rel| !rel.starts_with(".djour"))
+            .collect()
+    };
+    let expected_files = notes(expected_root);
+    let actual_files = notes(actual_root);
+    assert_eq!(
+        expected_files, actual_files,
+        "Case '{}' round trip changed the set of notes",
+        case_name
+    );
+
+    for rel in expected_files {
+        let expected = fs::read(expected_root.join(&rel)).unwrap();
+        let actual = fs::read(actual_root.join(&rel)).unwrap();
+        if let Some(offset) = expected
+            .iter()
+            .zip(&actual)
+            .position(|(a, b)| a != b)
+            .or_else(|| (expected.len() != actual.len()).then(|| expected.len().min(actual.len())))
+        {
+            let context = |bytes: &[u8]| {
+                let from = offset.saturating_sub(20);
+                let to = (offset + 20).min(bytes.len());
+                String::from_utf8_lossy(&bytes[from..to]).into_owned()
+            };
+            panic!(
+                "Case '{}' round trip changed {} at byte {}.\nexpected: {:?}\nactual:   {:?}",
+                case_name,
+                rel.display(),
+                offset,
+                context(&expected),
+                context(&actual)
+            );
+        }
+    }
+}
+
 fn collect_relative_files(root: &Path) -> BTreeSet<PathBuf> {
     WalkDir::new(root)
         .into_iter()
@@ -250,28 +301,23 @@ fn collect_relative_files(root: &Path) -> BTreeSet<PathBuf> {
 
 fn assert_file_matches(case_name: &str, rel: &Path, expected_path: &Path, actual_path: &Path) {
     let expected = fs::read(expected_path).unwrap();
-    let actual = fs::read(actual_path).unwrap();
+    let actual = mask_generated_at(fs::read(actual_path).unwrap());
 
     if expected == actual {
         return;
     }
 
+    // Byte-exact, line breaks included: djour keeps each note's newline style
+    // and its final newline or the lack of one
     if let (Ok(expected_text), Ok(actual_text)) = (
         String::from_utf8(expected.clone()),
         String::from_utf8(actual.clone()),
     ) {
-        let normalized_expected = normalize_newlines(&expected_text);
-        let normalized_actual = normalize_newlines(&actual_text);
-
-        if normalized_expected == normalized_actual {
-            return;
-        }
-
         panic!(
             "Case '{}' file mismatch at {}.\n{}",
             case_name,
             rel.display(),
-            first_text_diff(&normalized_expected, &normalized_actual)
+            first_text_diff(&expected_text, &actual_text)
         );
     }
 
@@ -284,8 +330,27 @@ fn assert_file_matches(case_name: &str, rel: &Path, expected_path: &Path, actual
     );
 }
 
-fn normalize_newlines(s: &str) -> String {
-    s.replace("\r\n", "\n")
+/// `bytes` with the time in compilation provenance comments
+/// (`<!-- generated by djour compile ... at 2025-01-17T09:00:00 -->`) replaced
+/// by `YYYY-MM-DDTHH:MM:SS`, so expected compilations do not depend on the clock
+fn mask_generated_at(bytes: Vec<u8>) -> Vec<u8> {
+    const TIME_LEN: usize = "YYYY-MM-DDTHH:MM:SS".len();
+    let Ok(text) = String::from_utf8(bytes.clone()) else {
+        return bytes;
+    };
+    let mut masked = String::with_capacity(text.len());
+    for line in text.split_inclusive('\n') {
+        let content = line.trim_end_matches(['\r', '\n']);
+        match content.strip_suffix(" -->") {
+            Some(rest) if content.starts_with("<!-- generated by ") && rest.len() >= TIME_LEN => {
+                masked.push_str(&rest[..rest.len() - TIME_LEN]);
+                masked.push_str("YYYY-MM-DDTHH:MM:SS -->");
+                masked.push_str(&line[content.len()..]);
+            }
+            _ => masked.push_str(line),
+        }
+    }
+    masked.into_bytes()
 }
 
 fn first_text_diff(expected: &str, actual: &str) -> String {
@@ -312,5 +377,13 @@ fn first_text_diff(expected: &str, actual: &str) -> String {
         );
     }
 
-    "Content differs, but no line-level difference could be determined.".to_string()
+    if expected.ends_with('\n') != actual.ends_with('\n') {
+        return format!(
+            "Final newline differs.\nexpected: {}\nactual:   {}",
+            expected.ends_with('\n'),
+            actual.ends_with('\n')
+        );
+    }
+
+    "Line breaks differ (LF vs CRLF).".to_string()
 }
@@ -0,0 +1,250 @@
+//! Integration tests for compile --clean and compile --refresh-all
+
+#![allow(deprecated)]
+
+use predicates::prelude::*;
+use std::fs;
+use tempfile::TempDir;
+
+mod common;
+use common::djour_cmd;
+
+/// A daily journal with work and errand notes, compiled as `work` and `errand`
+/// (grouped), plus a hand-written file in .compilations/
+fn journal() -> TempDir {
+    let temp = TempDir::new().unwrap();
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+    fs::write(temp.path().join("2025-01-15.md"), "Kickoff. #work\n").unwrap();
+    fs::write(temp.path().join("2025-01-16.md"), "Groceries. #errand\n").unwrap();
+    for args in [
+        &["compile", "work"][..],
+        &["compile", "errand", "--format", "grouped"][..],
+    ] {
+        djour_cmd()
+            .current_dir(temp.path())
+            .args(args)
+            .assert()
+            .success();
+    }
+    fs::write(
+        temp.path().join(".compilations/reading-list.md"),
+        "# Reading list\n",
+    )
+    .unwrap();
+    temp
+}
+
+fn read(temp: &TempDir, path: &str) -> String {
+    fs::read_to_string(temp.path().join(path)).unwrap()
+}
+
+#[test]
+fn test_compile_records_command_on_line_two() {
+    let temp = journal();
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work AND NOT errand"])
+        .assert()
+        .success();
+
+    let content = read(&temp, ".compilations/work-and-not-errand.md");
+    let second = content.lines().nth(1).unwrap();
+    let time = second
+        .strip_prefix("<!-- generated by djour compile 'work AND NOT errand' at ")
+        .and_then(|rest| rest.strip_suffix(" -->"))
+        .unwrap_or_else(|| panic!("{}", content));
+    assert!(
+        chrono::NaiveDateTime::parse_from_str(time, "%Y-%m-%dT%H:%M:%S").is_ok(),
+        "{}",
+        second
+    );
+
+    // Printed compilations change nothing in the journal and carry no comment
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--stdout"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("<!--").not());
+}
+
+#[test]
+fn test_recompiling_unchanged_compilation_keeps_its_timestamp() {
+    let temp = journal();
+    let path = temp.path().join(".compilations/work.md");
+    // Backdate the provenance comment, leaving the body as compiled
+    let content = read(&temp, ".compilations/work.md");
+    let (first, rest) = content.split_once('\n').unwrap();
+    let (_, rest) = rest.split_once('\n').unwrap();
+    let stale = format!(
+        "{}\n<!-- generated by djour compile work at 2000-01-01T00:00:00 -->\n{}",
+        first, rest
+    );
+    fs::write(&path, &stale).unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work"])
+        .assert()
+        .success();
+    assert_eq!(read(&temp, ".compilations/work.md"), stale);
+
+    fs::write(temp.path().join("2025-01-17.md"), "Retro. #work\n").unwrap();
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work"])
+        .assert()
+        .success();
+    let work = read(&temp, ".compilations/work.md");
+    assert!(work.contains("Retro. #work"), "{}", work);
+    assert!(!work.contains("2000-01-01"), "{}", work);
+}
+
+#[test]
+fn test_provenance_follows_template_front_matter() {
+    let temp = journal();
+    fs::create_dir_all(temp.path().join(".djour/templates")).unwrap();
+    fs::write(
+        temp.path().join(".djour/templates/compilation.md"),
+        "---\ntitle: {QUERY}\n---\n\n{BODY}",
+    )
+    .unwrap();
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work"])
+        .assert()
+        .success();
+
+    let work = read(&temp, ".compilations/work.md");
+    assert!(
+        work.starts_with("---\ntitle: #work\n---\n<!-- generated by djour compile work at "),
+        "{}",
+        work
+    );
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "--clean", "--yes"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(".compilations/work.md\n"));
+}
+
+#[test]
+fn test_clean_deletes_only_generated_compilations() {
+    let temp = journal();
+    fs::write(
+        temp.path().join(".compilations/old.md"),
+        "# Compilation: #retired\n\nStale.\n",
+    )
+    .unwrap();
+
+    // Without a terminal to confirm on, nothing is deleted
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "--clean"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("run again with --yes"));
+    assert!(temp.path().join(".compilations/work.md").exists());
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "--clean", "--yes"])
+        .assert()
+        .success()
+        .stdout(
+            ".compilations/errand.md\n\
+             .compilations/old.md\n\
+             .compilations/work.md\n\
+             Deleted 3 compilation(s)\n",
+        )
+        .stderr(predicate::str::contains(
+            "Keeping .compilations/reading-list.md: not generated by djour",
+        ));
+
+    let mut left: Vec<_> = fs::read_dir(temp.path().join(".compilations"))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    left.sort();
+    assert_eq!(left, ["reading-list.md"]);
+    assert_eq!(
+        read(&temp, ".compilations/reading-list.md"),
+        "# Reading list\n"
+    );
+}
+
+#[test]
+fn test_clean_with_query_cleans_then_compiles() {
+    let temp = journal();
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--clean", "-y", "--quiet"])
+        .assert()
+        .success();
+
+    assert!(!temp.path().join(".compilations/errand.md").exists());
+    assert!(read(&temp, ".compilations/work.md").contains("Kickoff. #work"));
+    assert!(temp.path().join(".compilations/reading-list.md").exists());
+}
+
+#[test]
+fn test_refresh_all_regenerates_from_recorded_commands() {
+    let temp = journal();
+    fs::write(
+        temp.path().join("2025-01-17.md"),
+        "Retro. #work\n\nPost office. #errand\n",
+    )
+    .unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "--refresh-all"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(".compilations/errand.md\n"))
+        .stdout(predicate::str::contains(".compilations/work.md\n"));
+
+    let work = read(&temp, ".compilations/work.md");
+    assert!(work.contains("Kickoff. #work") && work.contains("Retro. #work"));
+    assert!(!work.contains("#errand"), "{}", work);
+    assert!(work.contains("<!-- generated by djour compile work at "));
+
+    // The grouped format was recorded and kept
+    let errand = read(&temp, ".compilations/errand.md");
+    assert!(errand.contains("Post office. #errand"), "{}", errand);
+    assert!(errand.contains("## From: 2025-01-16.md"), "{}", errand);
+    assert!(errand.contains("<!-- generated by djour compile errand --format grouped at "));
+
+    assert_eq!(
+        read(&temp, ".compilations/reading-list.md"),
+        "# Reading list\n"
+    );
+}
+
+#[test]
+fn test_refresh_all_reports_compilations_it_cannot_refresh() {
+    let temp = journal();
+    fs::write(
+        temp.path().join(".compilations/legacy.md"),
+        "# Compilation: #work\n\nOld.\n",
+    )
+    .unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "--refresh-all"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "warning: .compilations/legacy.md left unchanged: Configuration error: No query recorded",
+        ))
+        .stderr(predicate::str::contains(
+            "1 of 3 compilation(s) could not be refreshed",
+        ));
+    assert_eq!(
+        read(&temp, ".compilations/legacy.md"),
+        "# Compilation: #work\n\nOld.\n"
+    );
+}
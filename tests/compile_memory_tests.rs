@@ -0,0 +1,165 @@
+//! Memory use of compiling a large journal: output written to a file is
+//! streamed there, never held in memory whole
+
+use chrono::{Duration, NaiveDate};
+use djour::application::{compile_tags, compile_to_string, init, CompileOptions};
+use djour::domain::tags::{CompilationFormat, CompileBudget, ContextOptions};
+use djour::domain::{Diagnostics, JournalMode};
+use djour::infrastructure::FileSystemRepository;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::fs;
+use tempfile::TempDir;
+
+/// Tracks the live and peak bytes of the current thread, so parallel tests do not mix
+struct PeakAllocator;
+
+thread_local! {
+    static LIVE: Cell<usize> = const { Cell::new(0) };
+    static PEAK: Cell<usize> = const { Cell::new(0) };
+}
+
+fn grow(bytes: usize) {
+    let _ = LIVE.try_with(|live| {
+        live.set(live.get() + bytes);
+        let _ = PEAK.try_with(|peak| peak.set(peak.get().max(live.get())));
+    });
+}
+
+fn shrink(bytes: usize) {
+    let _ = LIVE.try_with(|live| live.set(live.get().saturating_sub(bytes)));
+}
+
+unsafe impl GlobalAlloc for PeakAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        grow(layout.size());
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        shrink(layout.size());
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        shrink(layout.size());
+        grow(new_size);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: PeakAllocator = PeakAllocator;
+
+/// Bytes allocated on top of what was live when `run` started, at its peak
+fn peak_during<T>(run: impl FnOnce() -> T) -> (T, usize) {
+    let base = LIVE.with(Cell::get);
+    PEAK.with(|peak| peak.set(base));
+    let value = run();
+    (value, PEAK.with(Cell::get) - base)
+}
+
+const NOTES: usize = 120;
+const BLOCKS_PER_NOTE: usize = 20;
+
+/// A long paragraph, so the output outweighs the per-block bookkeeping
+fn entry(note: usize, block: usize) -> String {
+    format!(
+        "Entry {:03}-{:02}: {} #work",
+        note,
+        block,
+        "shipped the quarterly numbers and wrote them up. ".repeat(16)
+    )
+}
+
+fn large_journal() -> (TempDir, FileSystemRepository) {
+    let temp = TempDir::new().unwrap();
+    init(temp.path(), JournalMode::Daily).unwrap();
+    let first = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+    for note in 0..NOTES {
+        let date = first + Duration::days(note as i64);
+        let body: String = (0..BLOCKS_PER_NOTE)
+            .map(|block| format!("{}\n\n", entry(note, block)))
+            .collect();
+        fs::write(
+            temp.path().join(date.format("%Y-%m-%d.md").to_string()),
+            format!("# {}\n\n{}", date, body),
+        )
+        .unwrap();
+    }
+    let repo = FileSystemRepository::new(temp.path().to_path_buf());
+    (temp, repo)
+}
+
+fn options() -> CompileOptions {
+    CompileOptions {
+        query: "work".to_string(),
+        output: Some("large.md".into()),
+        from: None,
+        to: None,
+        changed_since: None,
+        format: CompilationFormat::Chronological,
+        group_by: None,
+        date_format: None,
+        context: ContextOptions::off(),
+        recursive: false,
+        include_archived: false,
+        manifest: false,
+        strict: false,
+        embed_links: false,
+        notes: Vec::new(),
+        exclude: Vec::new(),
+        title_filter: None,
+        attribution: true,
+        budget: CompileBudget::unlimited(),
+        suggest_limit: 0,
+        include_private: false,
+        allow_exclusion_only: false,
+    }
+}
+
+#[test]
+fn test_large_compilation_is_streamed_to_the_file() {
+    let (temp, repo) = large_journal();
+    let options = options();
+
+    let (in_memory, string_peak) =
+        peak_during(|| compile_to_string(&repo, &options, &mut Diagnostics::new(), None).unwrap());
+    let (report, streamed_peak) =
+        peak_during(|| compile_tags(&repo, &options, &mut Diagnostics::new(), None).unwrap());
+
+    let written = fs::read_to_string(temp.path().join("large.md")).unwrap();
+    // The file also records the command that wrote it, on line two
+    let mut lines = written.splitn(3, '\n');
+    let (title, provenance, rest) = (
+        lines.next().unwrap(),
+        lines.next().unwrap(),
+        lines.next().unwrap(),
+    );
+    assert!(
+        provenance.starts_with("<!-- generated by djour compile work --output large.md"),
+        "{}",
+        provenance
+    );
+    assert_eq!(format!("{}\n{}", title, rest), in_memory.0);
+    assert_eq!(report.block_count, NOTES * BLOCKS_PER_NOTE);
+
+    // Holding the whole document costs at least its size; streaming does not
+    assert!(
+        streamed_peak + written.len() / 4 <= string_peak,
+        "streamed peak {} bytes, in-memory peak {} bytes, output {} bytes",
+        streamed_peak,
+        string_peak,
+        written.len()
+    );
+
+    // Every block is written once
+    let entries: Vec<&str> = written
+        .lines()
+        .filter(|line| line.starts_with("Entry "))
+        .collect();
+    let expected: Vec<String> = (0..NOTES)
+        .flat_map(|note| (0..BLOCKS_PER_NOTE).map(move |block| entry(note, block)))
+        .collect();
+    assert_eq!(entries, expected);
+}
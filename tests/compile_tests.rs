@@ -20,6 +20,18 @@ fn create_note(temp: &TempDir, filename: &str, content: &str) {
     fs::write(note_path, content).unwrap();
 }
 
+/// A compilation without the provenance comment djour writes on its second line
+fn without_provenance(content: &str) -> String {
+    let (first, rest) = content.split_once('\n').unwrap();
+    let (second, rest) = rest.split_once('\n').unwrap();
+    assert!(
+        second.starts_with("<!-- generated by djour compile "),
+        "{}",
+        content
+    );
+    format!("{}\n{}", first, rest)
+}
+
 fn editor_command_for_test() -> &'static str {
     if cfg!(windows) {
         "cmd /c exit 0"
@@ -108,6 +120,122 @@ Meeting at 10am with team. #work",
     assert!(output.exists());
 }
 
+#[cfg(unix)]
+mod viewer {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::{Path, PathBuf};
+    use std::time::{Duration, Instant};
+
+    /// A command that writes the path it was given to `sentinel`
+    fn write_stub(dir: &Path, name: &str, sentinel: &Path) -> PathBuf {
+        let script = dir.join(name);
+        fs::write(
+            &script,
+            format!("#!/bin/sh\necho \"$1\" > '{}'\n", sentinel.display()),
+        )
+        .unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+        script
+    }
+
+    /// `--open` does not wait for the command, so poll for its sentinel
+    fn wait_for(sentinel: &Path) -> String {
+        let started = Instant::now();
+        while started.elapsed() < Duration::from_secs(10) {
+            if let Ok(contents) = fs::read_to_string(sentinel) {
+                if contents.ends_with('\n') {
+                    return contents;
+                }
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        panic!("{} was never written", sentinel.display());
+    }
+
+    #[test]
+    fn test_compile_open_uses_viewer_and_notes_use_editor() {
+        let temp = TempDir::new().unwrap();
+        let scripts = TempDir::new().unwrap();
+        init_journal(&temp);
+        create_note(&temp, "2025-01-15.md", "Meeting #work\n");
+
+        let editor = write_stub(scripts.path(), "editor.sh", &scripts.path().join("edited"));
+        let viewer = write_stub(scripts.path(), "viewer.sh", &scripts.path().join("viewed"));
+        djour_cmd()
+            .current_dir(temp.path())
+            .args(["config", "editor"])
+            .arg(&editor)
+            .assert()
+            .success();
+        djour_cmd()
+            .current_dir(temp.path())
+            .args(["config", "viewer"])
+            .arg(&viewer)
+            .assert()
+            .success();
+
+        djour_cmd()
+            .current_dir(temp.path())
+            .args(["compile", "work", "--open"])
+            .assert()
+            .success();
+        assert!(wait_for(&scripts.path().join("viewed")).contains(".compilations/work.md"));
+
+        djour_cmd()
+            .current_dir(temp.path())
+            .args(["today", "--open"])
+            .assert()
+            .success();
+        assert!(wait_for(&scripts.path().join("edited")).ends_with(".md\n"));
+        assert!(!fs::read_to_string(scripts.path().join("viewed"))
+            .unwrap()
+            .contains("2026"));
+    }
+
+    #[test]
+    fn test_compile_open_with_overrides_viewer() {
+        let temp = TempDir::new().unwrap();
+        let scripts = TempDir::new().unwrap();
+        init_journal(&temp);
+        create_note(&temp, "2025-01-15.md", "Meeting #work\n");
+        let other = write_stub(scripts.path(), "other.sh", &scripts.path().join("other"));
+
+        djour_cmd()
+            .current_dir(temp.path())
+            .args(["config", "viewer", "definitely-not-a-real-viewer"])
+            .assert()
+            .success();
+        djour_cmd()
+            .current_dir(temp.path())
+            .args(["compile", "work", "--open", "--with"])
+            .arg(&other)
+            .assert()
+            .success();
+        assert!(wait_for(&scripts.path().join("other")).contains(".compilations/work.md"));
+
+        djour_cmd()
+            .current_dir(temp.path())
+            .args(["compile", "work", "--open"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(
+                "Failed to launch viewer 'definitely-not-a-real-viewer'",
+            ));
+    }
+}
+
+#[test]
+fn test_compile_with_requires_open() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--with", "glow"])
+        .assert()
+        .failure();
+}
+
 #[test]
 fn test_compile_and_query() {
     let temp = TempDir::new().unwrap();
@@ -288,6 +416,34 @@ fn test_compile_weekly_date_range() {
     assert!(content.contains("## 13-01-2025 to 19-01-2025"));
 }
 
+#[test]
+fn test_compile_weekly_date_range_clamped_to_filter() {
+    let temp = TempDir::new().unwrap();
+
+    djour_cmd()
+        .arg("init")
+        .arg(temp.path())
+        .arg("--mode")
+        .arg("weekly")
+        .assert()
+        .success();
+
+    create_note(
+        &temp,
+        "2025-W03-2025-01-13.md",
+        "## Work #work\nWeekly note. #work",
+    );
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--stdout"])
+        .args(["--from", "15-01-2025", "--to", "16-01-2025"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("## 15-01-2025 to 16-01-2025\n"))
+        .stdout(predicate::str::contains("13-01-2025 to 19-01-2025").not());
+}
+
 #[test]
 fn test_compile_monthly_date_range() {
     let temp = TempDir::new().unwrap();
@@ -340,6 +496,233 @@ fn test_compile_format_grouped() {
     assert!(content.contains("## From: 2025-01-16.md"));
 }
 
+#[test]
+fn test_compile_format_chrono_grouped() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+
+    create_note(
+        &temp,
+        "2025-01-15.md",
+        "## Work #work\nRoot entry.\n\n## More #work\nSecond root.",
+    );
+    fs::create_dir_all(temp.path().join("projects")).unwrap();
+    create_note(
+        &temp,
+        "projects/2025-01-15.md",
+        "## Alpha #work\nAlpha entry.",
+    );
+    create_note(&temp, "ideas.md", "## Idea #work\nUndated entry.");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--format", "chrono-grouped"])
+        .args([
+            "--note",
+            "2025-01-15.md",
+            "--note",
+            "projects/2025-01-15.md",
+        ])
+        .args(["--note", "ideas.md"])
+        .assert()
+        .success();
+
+    let content = fs::read_to_string(temp.path().join(".compilations/work.md")).unwrap();
+    assert_eq!(
+        without_provenance(&content),
+        "# Compilation: #work\n\n\n## 15-01-2025\n\n### 2025-01-15.md\n\nRoot entry.\n\nSecond root.\n\n\n\
+         ### projects/2025-01-15.md\n\nAlpha entry.\n\n\n## Undated\n\n### ideas.md\n\nUndated entry.\n"
+    );
+}
+
+#[test]
+fn test_compile_section_date_override() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+
+    create_note(&temp, "2025-01-14.md", "## Standup #work\nTuesday standup.");
+    create_note(
+        &temp,
+        "2025-01-16.md",
+        "## Tuesday catch-up @14-01-2025 #work\nShipped the fix.\n\n\
+         ## Today #work\nThursday planning.\n\n\
+         ## Typo @31-02-2025 #work\nStill Thursday.",
+    );
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--stdout"])
+        .assert()
+        .success()
+        .stdout(
+            "# Compilation: #work\n\n\n## 14-01-2025\n\nTuesday standup.\n\nShipped the fix.\n\n\n\
+             ## 16-01-2025\n\nThursday planning.\n\nStill Thursday.\n",
+        )
+        .stderr(predicate::str::contains(
+            "2025-01-16.md: heading at line 7: @31-02-2025 is not a valid date",
+        ));
+
+    // Date filters use the section's date, not the note's
+    djour_cmd()
+        .current_dir(temp.path())
+        .args([
+            "compile",
+            "work",
+            "--stdout",
+            "--from",
+            "14-01-2025",
+            "--to",
+            "14-01-2025",
+        ])
+        .assert()
+        .success()
+        .stdout(
+            "# Compilation: #work\n\n\n## 14-01-2025\n\nTuesday standup.\n\nShipped the fix.\n",
+        );
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--stdout", "--from", "16-01-2025"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Shipped the fix.").not());
+
+    // Notes dated before the range are not searched for markers
+    create_note(
+        &temp,
+        "2025-01-10.md",
+        "## Planned ahead @14-01-2025 #work\nWritten ahead.",
+    );
+    djour_cmd()
+        .current_dir(temp.path())
+        .args([
+            "compile",
+            "work",
+            "--stdout",
+            "--from",
+            "14-01-2025",
+            "--to",
+            "14-01-2025",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Written ahead.").not());
+}
+
+#[test]
+fn test_compile_date_format_flag() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    create_note(&temp, "2025-01-15.md", "## Work #work\nShipped.");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--date-format", "%A, %d %B %Y"])
+        .assert()
+        .success();
+    let content = fs::read_to_string(temp.path().join(".compilations/work.md")).unwrap();
+    assert!(
+        content.contains("## Wednesday, 15 January 2025\n"),
+        "{content}"
+    );
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--stdout", "--date-format", "%d %H"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid --date-format: '%d %H'"));
+}
+
+#[test]
+fn test_compile_group_by_hour() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+
+    create_note(
+        &temp,
+        "2025-01-15.md",
+        "## Evening\n\n- 18:30 retro #work\n\n## Morning\n\n- 09:30 standup #work\n- 9:45 triage #work\n\nNo time here #work\n",
+    );
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--group-by", "hour", "--stdout"])
+        .assert()
+        .success()
+        .stdout(
+            "# Compilation: #work\n\n\n## 15-01-2025\n\n\
+             ### 09:00–10:00\n\n- 09:30 standup #work\n- 9:45 triage #work\n\n\n\
+             ### 18:00–19:00\n\n- 18:30 retro #work\n\n\n\
+             ### unspecified\n\nNo time here #work\n",
+        );
+
+    // Without the flag, timed entries are still in time order and an untimed
+    // one stays after the entry before it in the note
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--stdout"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "- 09:30 standup #work\n- 9:45 triage #work\n\nNo time here #work\n\n- 18:30 retro #work",
+        ));
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args([
+            "compile",
+            "work",
+            "--group-by",
+            "hour",
+            "--format",
+            "grouped",
+        ])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains(
+            "only applies to the chronological format",
+        ));
+}
+
+#[test]
+fn test_compile_group_by_hour_leaves_tagged_sections_untimed() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+
+    create_note(
+        &temp,
+        "2025-01-15.md",
+        "## Evening #work\n\n- 18:10 wrap\n\nLoose ends.\n\n## Morning\n\n- 09:30 standup #work\n",
+    );
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args([
+            "compile",
+            "work",
+            "--group-by",
+            "hour",
+            "--include-context",
+            "--stdout",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::ends_with(
+            "### 09:00–10:00\n\n#### Morning\n\n- 09:30 standup #work\n\n\n\
+             ### unspecified\n\n#### Evening\n\n- 18:10 wrap\n\nLoose ends.\n",
+        ));
+
+    // Without the flag, the section keeps its place in the note
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--stdout"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "- 18:10 wrap\n\nLoose ends.\n\n- 09:30 standup #work",
+        ));
+}
+
 #[test]
 fn test_compile_with_context() {
     let temp = TempDir::new().unwrap();
@@ -372,6 +755,115 @@ Meeting notes here.",
     assert!(content.contains("Work Section"));
 }
 
+#[test]
+fn test_compile_grouped_context_skips_the_file_title() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd()
+        .args(["init", "--mode", "single"])
+        .arg(temp.path())
+        .assert()
+        .success();
+    create_note(
+        &temp,
+        "journal.md",
+        "# Journal\n\nQuick idea #work\n\n## Meetings\n\nSynced with the team #work\n",
+    );
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args([
+            "compile",
+            "work",
+            "--format",
+            "grouped",
+            "--include-context",
+        ])
+        .assert()
+        .success();
+
+    let content = fs::read_to_string(temp.path().join(".compilations/work.md")).unwrap();
+    let headings: Vec<&str> = content.lines().filter(|l| l.starts_with('#')).collect();
+    assert_eq!(
+        headings,
+        [
+            "# Compilation: #work",
+            "## From: journal.md",
+            "#### Meetings"
+        ],
+        "{}",
+        content
+    );
+    assert!(content.contains("## From: journal.md\n\nQuick idea #work\n"));
+
+    // The query turns context off for this run only
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work !nocontext", "--format", "grouped"])
+        .args(["--include-context", "-o", "plain.md"])
+        .assert()
+        .success();
+    let content = fs::read_to_string(temp.path().join("plain.md")).unwrap();
+    assert!(content.starts_with("# Compilation: #work\n"), "{}", content);
+    assert!(!content.contains("Meetings"), "{}", content);
+}
+
+#[test]
+fn test_compile_context_heading_keeps_literal_hashes() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    create_note(
+        &temp,
+        "2025-01-15.md",
+        "# Daily Log\n\n## Bug #1234 triage #work for C#\n\nReproduced the crash.\n",
+    );
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--include-context"])
+        .assert()
+        .success();
+
+    let content = fs::read_to_string(temp.path().join(".compilations/work.md")).unwrap();
+    assert!(content.contains("Bug #1234 triage for C#\n"), "{}", content);
+    assert!(content.contains("Reproduced the crash."));
+}
+
+#[test]
+fn test_compile_context_depth_and_breadcrumb() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    create_note(
+        &temp,
+        "2025-01-15.md",
+        "# Client A\n\n## Project X\n\n### Standup\n\nShipped the fix #work\n",
+    );
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--context-depth", "all"])
+        .assert()
+        .success();
+    let content = fs::read_to_string(temp.path().join(".compilations/work.md")).unwrap();
+    assert!(content.contains("### Client A\n\n#### Project X\n\n##### Standup\n\nShipped the fix"));
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--context-depth", "2"])
+        .args(["--context-style", "breadcrumb"])
+        .assert()
+        .success();
+    let content = fs::read_to_string(temp.path().join(".compilations/work.md")).unwrap();
+    assert!(content.contains("*Project X › Standup*\n\nShipped the fix"));
+    assert!(!content.contains("Client A"));
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--context-depth", "deep"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("Invalid context depth"));
+}
+
 #[test]
 fn test_compile_custom_output_path() {
     let temp = TempDir::new().unwrap();
@@ -472,6 +964,53 @@ fn test_compile_recursive_includes_nested_notes_and_skips_dot_dirs() {
     assert!(!content.contains("Hidden task. #work"));
 }
 
+#[test]
+fn test_compile_scopes_by_dir_and_file_predicates() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+
+    create_note(&temp, "2025-01-15.md", "Root task. #work");
+    for (dir, body) in [
+        ("projects/alpha", "Alpha task. #work"),
+        ("projects/beta", "Beta task. #work"),
+    ] {
+        fs::create_dir_all(temp.path().join(dir)).unwrap();
+        fs::write(temp.path().join(dir).join("2025-01-16.md"), body).unwrap();
+    }
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work AND dir:projects/alpha", "--recursive"])
+        .args(["-o", "alpha.md"])
+        .assert()
+        .success();
+    let content = fs::read_to_string(temp.path().join("alpha.md")).unwrap();
+    assert!(content.starts_with("# Compilation: #work AND dir:projects/alpha\n"));
+    assert!(content.contains("Alpha task."));
+    assert!(!content.contains("Beta task."));
+    assert!(!content.contains("Root task."));
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work AND NOT dir:projects", "--recursive"])
+        .args(["-o", "root.md"])
+        .assert()
+        .success();
+    let content = fs::read_to_string(temp.path().join("root.md")).unwrap();
+    assert!(content.contains("Root task."));
+    assert!(!content.contains("Alpha task."));
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work AND file:2025-01-15*", "--recursive"])
+        .args(["-o", "file.md"])
+        .assert()
+        .success();
+    let content = fs::read_to_string(temp.path().join("file.md")).unwrap();
+    assert!(content.contains("Root task."));
+    assert!(!content.contains("Beta task."));
+}
+
 #[test]
 fn test_compile_empty_query_fails() {
     let temp = TempDir::new().unwrap();
@@ -887,3 +1426,1497 @@ This inherits the work tag from parent section. #note",
     let content = fs::read_to_string(output).unwrap();
     assert!(content.contains("This inherits the work tag from parent section. #note"));
 }
+
+#[test]
+fn test_compile_watch_recompiles_on_note_change() {
+    use std::process::{Command, Stdio};
+    use std::time::{Duration, Instant};
+
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    create_note(&temp, "2025-01-15.md", "First entry #work");
+
+    let mut child = Command::new(assert_cmd::cargo::cargo_bin("djour"))
+        .current_dir(temp.path())
+        .env_remove("DJOUR_ROOT")
+        .env_remove("DJOUR_MODE")
+        .args(["compile", "work", "--watch"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+
+    let output = temp.path().join(".compilations/work.md");
+    let wait_for = |check: &dyn Fn(&str) -> bool| {
+        let deadline = Instant::now() + Duration::from_secs(15);
+        while Instant::now() < deadline {
+            if let Ok(content) = fs::read_to_string(&output) {
+                if check(&content) {
+                    return true;
+                }
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        false
+    };
+
+    assert!(wait_for(&|content| content.contains("First entry")));
+
+    // Give the watcher a moment to start before editing.
+    std::thread::sleep(Duration::from_millis(500));
+    create_note(&temp, "2025-01-16.md", "Second entry #work");
+    let recompiled = wait_for(&|content| content.contains("Second entry"));
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+
+    assert!(
+        recompiled,
+        "compilation was not refreshed after note change"
+    );
+}
+
+#[test]
+fn test_compile_front_matter_tags_and_config_toggle() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+
+    create_note(
+        &temp,
+        "2025-01-15.md",
+        "---\ntitle: Planning\ntags: #work\n---\n\nDraft the roadmap.\n",
+    );
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work"])
+        .assert()
+        .success();
+
+    let content = fs::read_to_string(temp.path().join(".compilations/work.md")).unwrap();
+    assert!(content.contains("Draft the roadmap."));
+    assert!(!content.contains("title: Planning"));
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "frontmatter_tags", "false"])
+        .assert()
+        .success();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work"])
+        .assert()
+        .failure()
+        .code(3);
+}
+
+#[test]
+fn test_compile_with_custom_compilation_template() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    create_note(&temp, "2025-01-15.md", "Kickoff meeting. #work");
+    create_note(&temp, "2025-01-17.md", "Retro notes. #work");
+
+    let templates = temp.path().join(".djour/templates");
+    fs::create_dir_all(&templates).unwrap();
+    fs::write(
+        templates.join("compilation.md"),
+        "# ACME report: {QUERY}\n\nPeriod: {DATE_RANGE}\n\n{BODY}\n---\nGenerated {GENERATED_AT} {KEEP_ME}\n",
+    )
+    .unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work"])
+        .assert()
+        .success();
+
+    let content = fs::read_to_string(temp.path().join(".compilations/work.md")).unwrap();
+    assert!(without_provenance(&content)
+        .starts_with("# ACME report: #work\n\nPeriod: 2025-01-15 - 2025-01-17\n"));
+    assert!(!content.contains("# Compilation:"));
+    assert!(content.contains("Kickoff meeting. #work"));
+    assert!(content.contains("{KEEP_ME}"));
+    assert!(!content.contains("{GENERATED_AT}"));
+}
+
+#[test]
+fn test_compile_template_without_body_keeps_default_title() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    create_note(&temp, "2025-01-15.md", "Kickoff meeting. #work");
+
+    let default_output = {
+        djour_cmd()
+            .current_dir(temp.path())
+            .args(["compile", "work"])
+            .assert()
+            .success();
+        fs::read_to_string(temp.path().join(".compilations/work.md")).unwrap()
+    };
+
+    let templates = temp.path().join(".djour/templates");
+    fs::create_dir_all(&templates).unwrap();
+    fs::write(templates.join("compilation.md"), "Confidential\n").unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work"])
+        .assert()
+        .success();
+
+    let content = fs::read_to_string(temp.path().join(".compilations/work.md")).unwrap();
+    assert_eq!(
+        without_provenance(&content),
+        format!("Confidential\n\n{}", without_provenance(&default_output))
+    );
+}
+
+#[test]
+fn test_compile_parent_tag_includes_nested_tags() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    create_note(
+        &temp,
+        "2025-01-15.md",
+        "Alpha backend work #project/alpha/backend\n\nBeta launch #project/beta\n\nProtocol review #protocol\n",
+    );
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "project"])
+        .assert()
+        .success();
+    let content = fs::read_to_string(temp.path().join(".compilations/project.md")).unwrap();
+    assert!(content.contains("Alpha backend work"));
+    assert!(content.contains("Beta launch"));
+    assert!(!content.contains("Protocol review"));
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "project/alpha"])
+        .assert()
+        .success();
+    let content = fs::read_to_string(temp.path().join(".compilations/project_alpha.md")).unwrap();
+    assert!(content.contains("Alpha backend work"));
+    assert!(!content.contains("Beta launch"));
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "pro"])
+        .assert()
+        .code(3);
+}
+
+#[test]
+fn test_compile_prints_summary_line() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    create_note(&temp, "2025-01-02.md", "First #work\n\nSecond #work");
+    create_note(&temp, "2025-03-28.md", "Third #work");
+    create_note(&temp, "2025-02-10.md", "Unrelated #home");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(".compilations/work.md"))
+        .stderr(predicate::str::contains(
+            "Compiled 3 blocks from 2 notes (02-01-2025 .. 28-03-2025)",
+        ));
+
+    assert!(!temp
+        .path()
+        .join(".compilations/work.md.manifest.json")
+        .exists());
+}
+
+#[test]
+fn test_compile_manifest_written_next_to_output() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    fs::create_dir(temp.path().join("projects")).unwrap();
+    create_note(&temp, "2025-01-02.md", "First #work\n\nSecond #work");
+    create_note(&temp, "projects/2025-03-28.md", "Third #work");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--recursive", "--manifest"])
+        .assert()
+        .success();
+
+    let raw = fs::read_to_string(temp.path().join(".compilations/work.md.manifest.json")).unwrap();
+    let manifest: serde_json::Value = serde_json::from_str(&raw).unwrap();
+
+    assert_eq!(manifest["query"], "work");
+    assert_eq!(manifest["output"], ".compilations/work.md");
+    assert_eq!(manifest["block_count"], 3);
+    assert_eq!(manifest["note_count"], 2);
+    assert_eq!(manifest["min_date"], "2025-01-02");
+    assert_eq!(manifest["max_date"], "2025-03-28");
+    assert_eq!(
+        manifest["files"],
+        serde_json::json!([
+            {"path": "2025-01-02.md", "blocks": 2},
+            {"path": "projects/2025-03-28.md", "blocks": 1}
+        ])
+    );
+}
+
+#[test]
+fn test_compile_from_mid_week_includes_weekly_note() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd()
+        .arg("init")
+        .arg(temp.path())
+        .args(["--mode", "weekly"])
+        .assert()
+        .success();
+    create_note(&temp, "2025-W03-2025-01-13.md", "Friday review #work");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--from", "17-01-2025"])
+        .assert()
+        .success();
+
+    let content = fs::read_to_string(temp.path().join(".compilations/work.md")).unwrap();
+    assert!(content.contains("Friday review"));
+}
+
+#[test]
+fn test_compile_headers_follow_compile_config_table() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    let config_path = temp.path().join(".djour/config.toml");
+    let mut config = fs::read_to_string(&config_path).unwrap();
+    config.push_str("\n[compile]\ndate_format = \"%Y-%m-%d\"\nfile_header = \"## {FILENAME}\"\n");
+    fs::write(&config_path, config).unwrap();
+    create_note(&temp, "2025-01-15.md", "Meeting #work");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work"])
+        .assert()
+        .success();
+    let content = fs::read_to_string(temp.path().join(".compilations/work.md")).unwrap();
+    assert!(content.contains("\n## 2025-01-15\n"), "{content}");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--format", "grouped"])
+        .assert()
+        .success();
+    let content = fs::read_to_string(temp.path().join(".compilations/work.md")).unwrap();
+    assert!(content.contains("\n## 2025-01-15.md\n"), "{content}");
+}
+
+#[test]
+fn test_compile_warns_about_mangled_weekly_note() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd()
+        .args(["init", "--mode", "weekly"])
+        .arg(temp.path())
+        .assert()
+        .success();
+    for date in ["08-01-2025", "15-01-2025"] {
+        djour_cmd()
+            .current_dir(temp.path())
+            .args(["--yes", "--create", date])
+            .assert()
+            .success();
+    }
+    let good = temp.path().join("2025-W02-2025-01-06.md");
+    let content = fs::read_to_string(&good).unwrap();
+    fs::write(&good, format!("{}Planning #work\n", content)).unwrap();
+    let mangled = temp.path().join("2025-W03-2025-01-13.md");
+    let content = fs::read_to_string(&mangled)
+        .unwrap()
+        .replace("## Tuesday (January 14, 2025)", "## Tuesday 14 Jan");
+    fs::write(&mangled, format!("{}Retro #work\n", content)).unwrap();
+
+    // Warnings come after the summary and do not change the result.
+    let output = djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let lines: Vec<&str> = stderr.lines().collect();
+    assert_eq!(lines.len(), 2, "{stderr}");
+    assert!(lines[0].starts_with("Compiled "), "{stderr}");
+    assert!(lines[1].starts_with("warning: 2025-W03-2025-01-13.md: "));
+    let compiled = fs::read_to_string(temp.path().join(".compilations/work.md")).unwrap();
+    assert!(compiled.contains("Planning #work"));
+    assert!(compiled.contains("Retro #work"));
+
+    fs::remove_file(temp.path().join(".compilations/work.md")).unwrap();
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--strict"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains(
+            "error: 2025-W03-2025-01-13.md: Weekly note is missing expected heading",
+        ))
+        .stderr(predicate::str::contains("(--strict)"));
+    assert!(!temp.path().join(".compilations/work.md").exists());
+}
+
+#[test]
+fn test_compile_changed_since_filters_by_modification_time() {
+    use chrono::TimeZone;
+
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+
+    create_note(&temp, "2025-01-15.md", "Edited later #work");
+    create_note(&temp, "2025-01-16.md", "Left alone #work");
+    for (filename, day) in [("2025-01-15.md", 22), ("2025-01-16.md", 16)] {
+        let modified = chrono::Local
+            .with_ymd_and_hms(2025, 1, day, 12, 0, 0)
+            .unwrap();
+        fs::File::options()
+            .write(true)
+            .open(temp.path().join(filename))
+            .unwrap()
+            .set_modified(modified.into())
+            .unwrap();
+    }
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--changed-since", "20-01-2025"])
+        .assert()
+        .success();
+
+    let compiled = fs::read_to_string(temp.path().join(".compilations/work.md")).unwrap();
+    assert!(compiled.contains("Edited later"));
+    assert!(!compiled.contains("Left alone"));
+}
+
+#[test]
+fn test_compile_embed_links_quotes_linked_notes_once() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    fs::create_dir(temp.path().join("design")).unwrap();
+    create_note(
+        &temp,
+        "design/auth.md",
+        "# Auth\n\nToken flow #work\n\nBack to [today](../2025-01-15.md) and [spec](spec.md).",
+    );
+    create_note(&temp, "design/spec.md", "Spec body");
+    create_note(
+        &temp,
+        "2025-01-15.md",
+        "See [design notes](design/auth.md) #work\n\nDocs at [site](https://example.com/a.md) #work\n",
+    );
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--embed-links"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Compiled 2 blocks from 1 note"));
+
+    let content = fs::read_to_string(temp.path().join(".compilations/work.md")).unwrap();
+    assert!(
+        content.contains("> *Embedded from design/auth.md*"),
+        "{content}"
+    );
+    // Tags inside the embed are quoted, not extracted as their own block
+    assert_eq!(content.matches("Token flow #work").count(), 1, "{content}");
+    assert!(content.contains("> Token flow #work"), "{content}");
+    // Depth 1: the embed's own links (including the cycle back) stay links,
+    // rewritten relative to the output file
+    assert!(content.contains("[spec](../design/spec.md)"), "{content}");
+    assert!(!content.contains("Spec body"), "{content}");
+    assert!(
+        !content.contains("Embedded from 2025-01-15.md"),
+        "{content}"
+    );
+    // External links are untouched
+    assert!(
+        content.contains("[site](https://example.com/a.md)"),
+        "{content}"
+    );
+    assert_eq!(content.matches("Embedded from").count(), 1, "{content}");
+}
+
+#[test]
+fn test_compile_embed_links_redacts_private_blocks_of_linked_notes() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    fs::create_dir(temp.path().join("design")).unwrap();
+    create_note(
+        &temp,
+        "design/auth.md",
+        "Token flow\n\nMy salary is 100k #private\n\nRollout plan",
+    );
+    create_note(
+        &temp,
+        "2025-01-15.md",
+        "See [design](design/auth.md) #work\n",
+    );
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--embed-links", "--stdout"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "> Token flow\n>\n> *[1 private block redacted]*\n>\n> Rollout plan",
+        ))
+        .stdout(predicate::str::contains("100k").not());
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args([
+            "compile",
+            "work",
+            "--embed-links",
+            "--include-private",
+            "--stdout",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("> My salary is 100k #private"));
+}
+
+#[test]
+fn test_compile_embed_links_truncates_to_embed_max_bytes() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    create_note(&temp, "long.md", &"word ".repeat(100));
+    create_note(&temp, "2025-01-15.md", "See [long](long.md) #work\n");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "embed_max_bytes", "20"])
+        .assert()
+        .success();
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--embed-links"])
+        .assert()
+        .success();
+
+    let content = fs::read_to_string(temp.path().join(".compilations/work.md")).unwrap();
+    assert!(
+        content.contains("> word word word word\n>\n> *(truncated)*"),
+        "{content}"
+    );
+
+    // Without the flag the link is left alone
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work"])
+        .assert()
+        .success();
+    let content = fs::read_to_string(temp.path().join(".compilations/work.md")).unwrap();
+    assert!(!content.contains("Embedded from"), "{content}");
+}
+
+#[test]
+fn test_compile_keeps_indented_code_blocks_indented() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    create_note(
+        &temp,
+        "2025-01-15.md",
+        "## Snippets #code\n\n    let x = 1;\n    let y = x + 1;\n",
+    );
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "code", "--stdout"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "\n\n    let x = 1;\n    let y = x + 1;\n",
+        ));
+}
+
+#[test]
+fn test_compile_keeps_ordered_list_numbers() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    create_note(
+        &temp,
+        "2025-01-15.md",
+        "- errands\n  1. call bank #todo\n  2. renew passport #todo\n\n7. pay rent #todo\n8. file taxes #todo\n",
+    );
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "todo"])
+        .assert()
+        .success();
+
+    let content = fs::read_to_string(temp.path().join(".compilations/todo.md")).unwrap();
+    assert!(
+        content.contains("1. call bank #todo\n2. renew passport #todo"),
+        "{content}"
+    );
+    assert!(
+        content.contains("7. pay rent #todo\n8. file taxes #todo"),
+        "{content}"
+    );
+    assert!(!content.contains("- call bank"), "{content}");
+}
+
+#[test]
+fn test_compile_never_reads_back_its_own_output() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    create_note(&temp, "2025-01-15.md", "Planning #work\n");
+    create_note(&temp, "2025-01-16.md", "Review #work\n");
+
+    let compile = || {
+        djour_cmd()
+            .current_dir(temp.path())
+            .args([
+                "compile",
+                "work",
+                "--recursive",
+                "--output",
+                "2025-01-20.md",
+            ])
+            .assert()
+            .success()
+            .stderr(predicate::str::contains("Compiled 2 blocks from 2 notes"))
+            .stderr(predicate::str::contains(
+                "warning: 2025-01-20.md: compile output is named like a journal note",
+            ));
+        fs::read_to_string(temp.path().join("2025-01-20.md")).unwrap()
+    };
+
+    let first = compile();
+    let second = compile();
+    assert_eq!(first, second);
+    assert_eq!(second.matches("Planning #work").count(), 1, "{second}");
+}
+
+#[test]
+fn test_compile_stdin_without_journal() {
+    let temp = TempDir::new().unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--stdin", "--assume-date", "15-01-2025"])
+        .write_stdin("## Standup #work\n\n- Shipped [it](notes/a.md)\n\n## Gym #life\n\nLegs\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("# Compilation: #work\n"))
+        .stdout(predicate::str::contains("## 15-01-2025"))
+        .stdout(predicate::str::contains("- Shipped [it](notes/a.md)"))
+        .stdout(predicate::str::contains("Legs").not());
+
+    // Nothing was written and no journal was needed
+    assert!(!temp.path().join(".compilations").exists());
+    assert!(!temp.path().join(".djour").exists());
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--stdin"])
+        .write_stdin("Gym #life\n")
+        .assert()
+        .code(3);
+}
+
+#[test]
+fn test_compile_stdin_rejects_journal_options() {
+    for extra in [
+        &["--from", "01-01-2025"][..],
+        &["--to", "01-01-2025"],
+        &["--recursive"],
+        &["--output", "out.md"],
+    ] {
+        djour_cmd()
+            .args(["compile", "work", "--stdin"])
+            .args(extra)
+            .write_stdin("Line #work\n")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("cannot be used with"));
+    }
+
+    djour_cmd()
+        .args(["compile", "work", "--assume-date", "15-01-2025"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--stdin"));
+}
+
+#[test]
+fn test_compile_all_rules_reports_each_rule() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    create_note(
+        &temp,
+        "2025-01-15.md",
+        "## Alex #1on1\n\nTalked about goals.\n\nFinished a book. #reading\n",
+    );
+    create_note(
+        &temp,
+        ".djour/rules.toml",
+        concat!(
+            "[[rule]]\nname = \"1on1s\"\nquery = \"1on1\"\noutput = \"people/1on1s.md\"\n",
+            "format = \"grouped\"\ninclude_context = true\n\n",
+            "[[rule]]\nname = \"reading\"\nquery = \"reading\"\noutput = \"reading-log.md\"\n\n",
+            "[[rule]]\nname = \"travel\"\nquery = \"travel\"\n",
+        ),
+    );
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "--all-rules"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1on1s: people/1on1s.md\n"))
+        .stderr(predicate::str::contains(
+            "  Compiled 1 block from 1 note (15-01-2025)",
+        ))
+        .stdout(predicate::str::contains("reading: reading-log.md"))
+        .stderr(predicate::str::contains("rule 'travel' matched nothing"));
+
+    assert!(temp.path().join("people/1on1s.md").exists());
+    assert!(temp.path().join("reading-log.md").exists());
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "--rule", "travel"])
+        .assert()
+        .code(3);
+}
+
+#[test]
+fn test_compile_rule_reports_invalid_rules_file() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    create_note(&temp, "2025-01-15.md", "Work #work\n");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "--all-rules"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains(".djour/rules.toml does not exist"));
+
+    create_note(
+        &temp,
+        ".djour/rules.toml",
+        "[[rule]]\nname = \"standup\"\nquery = \"work AND\"\n",
+    );
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "--rule", "standup"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains(
+            "Invalid rule 'standup' in .djour/rules.toml",
+        ));
+}
+
+#[test]
+fn test_compile_note_reads_only_named_files() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    create_note(&temp, "2025-01-15.md", "Skipped entry. #work\n");
+    create_note(&temp, "2025-01-16.md", "Named entry. #work\n");
+    fs::create_dir(temp.path().join("projects")).unwrap();
+    create_note(
+        &temp,
+        "projects/2025-01-10.md",
+        "Nested entry, see [spec](spec.md). #work\n",
+    );
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args([
+            "compile",
+            "work",
+            "--note",
+            "2025-01-16.md",
+            "--note",
+            "projects/2025-01-10.md",
+        ])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "Compiled 2 blocks from 2 notes (10-01-2025 .. 16-01-2025)",
+        ));
+
+    let output = fs::read_to_string(temp.path().join(".compilations/work.md")).unwrap();
+    assert!(output.contains("Named entry. #work"));
+    assert!(output.contains("[spec](../projects/spec.md)"));
+    assert!(!output.contains("Skipped entry"));
+}
+
+#[test]
+fn test_compile_note_errors_and_date_filters() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    create_note(&temp, "2025-01-16.md", "Named entry. #work\n");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--note", "2025-01-17.md"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("Note does not exist"))
+        .stderr(predicate::str::contains("2025-01-17.md"));
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--note", "../elsewhere.md"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains(
+            "Note is outside the journal: ../elsewhere.md",
+        ));
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args([
+            "compile",
+            "work",
+            "--note",
+            "2025-01-16.md",
+            "--from",
+            "01-02-2025",
+        ])
+        .assert()
+        .code(3);
+}
+
+#[test]
+fn test_compile_warns_about_blocks_at_the_tag_limit() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+    let pasted: String = (0..300).map(|n| format!("#t{} ", n)).collect();
+    fs::write(
+        temp.path().join("2025-01-15.md"),
+        format!("Chat export: {}\n\nShip it #work\n", pasted),
+    )
+    .unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "t0"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "warning: 2025-01-15.md: a block has 256 or more tags; only the first 256 are used",
+        ));
+
+    // Notes with ordinary blocks compile without the warning
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("256").not());
+}
+
+#[test]
+fn test_compile_attribution_line_for_named_journals() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd()
+        .arg("init")
+        .arg(temp.path())
+        .args(["--name", "Work Log", "--author", "Tunç"])
+        .assert()
+        .success();
+    fs::write(temp.path().join("2025-01-15.md"), "Ship it #work\n").unwrap();
+    let output = temp.path().join(".compilations/work.md");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work"])
+        .assert()
+        .success();
+    let compiled = without_provenance(&fs::read_to_string(&output).unwrap());
+    let today = chrono::Local::now().date_naive().format("%Y-%m-%d");
+    assert!(
+        compiled.starts_with(&format!(
+            "# Compilation: #work\n\n*Work Log — compiled by Tunç on {}*\n\n## 15-01-2025",
+            today
+        )),
+        "{}",
+        compiled
+    );
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--no-attribution"])
+        .assert()
+        .success();
+    let compiled = fs::read_to_string(&output).unwrap();
+    assert!(!compiled.contains("compiled by"), "{}", compiled);
+    assert!(compiled.contains("Ship it #work"));
+}
+
+#[test]
+fn test_compile_redacts_private_blocks_unless_included() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    create_note(
+        &temp,
+        "2025-01-15.md",
+        "Ship it #work\n\nDoctor at 3 #work #private\n\nDentist at 5 #work #private\n",
+    );
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--stdout"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Ship it #work\n\n*[2 private blocks redacted]*\n",
+        ))
+        .stdout(predicate::str::contains("Doctor").not());
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args([
+            "compile",
+            "work AND private",
+            "--stdout",
+            "--include-private",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Doctor at 3 #work #private\n\nDentist at 5 #work #private\n",
+        ))
+        .stdout(predicate::str::contains("Ship it").not());
+
+    // With another private tag, #private blocks are shown
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "private_tag", "#Secret"])
+        .assert()
+        .success();
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "private_tag"])
+        .assert()
+        .success()
+        .stdout("secret\n");
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--stdout"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Doctor at 3"));
+}
+
+#[test]
+fn test_compile_rewrites_wikilinks_relative_to_output() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    create_note(&temp, "2025-01-10.md", "Kickoff #work\n");
+    create_note(&temp, "design notes.md", "# Design\n");
+    create_note(
+        &temp,
+        "2025-01-15.md",
+        "Follow-up of [[2025-01-10]], see [[design notes|the design]] and [[missing]]. \
+         Back to [[#top]]. #work\n",
+    );
+
+    // Off by default: wiki links are plain text
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "-o", "reports/work.md"])
+        .assert()
+        .success();
+    let output = fs::read_to_string(temp.path().join("reports/work.md")).unwrap();
+    assert!(output.contains("Follow-up of [[2025-01-10]]"), "{}", output);
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "wikilinks", "true"])
+        .assert()
+        .success();
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "-o", "reports/work.md"])
+        .assert()
+        .success();
+    let output = fs::read_to_string(temp.path().join("reports/work.md")).unwrap();
+    assert!(
+        output.contains("Follow-up of [2025-01-10](../2025-01-10.md)"),
+        "{}",
+        output
+    );
+    assert!(
+        output.contains("see [the design](<../design notes.md>)"),
+        "{}",
+        output
+    );
+    assert!(output.contains("[[missing]]"), "{}", output);
+    assert!(output.contains("[[#top]]"), "{}", output);
+
+    // The anchor is not a tag
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("tags")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("top").not());
+}
+
+#[test]
+fn test_compile_max_blocks_truncates_with_footer() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    for day in 10..=13 {
+        create_note(
+            &temp,
+            &format!("2025-01-{}.md", day),
+            &format!("Entry of day {} #work\n", day),
+        );
+    }
+    create_note(
+        &temp,
+        "2025-01-14.md",
+        "## Snippet #work\n\nUse this:\n\n```rust\nfn main() {\n\n    println!(\"hi\");\n}\n```\n",
+    );
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--max-blocks", "2"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "truncated, 3 more blocks omitted (--max-blocks)",
+        ));
+    let output = fs::read_to_string(temp.path().join(".compilations/work.md")).unwrap();
+    assert!(output.contains("Entry of day 13"), "{}", output);
+    assert!(!output.contains("Entry of day 12"), "{}", output);
+    // The code block is written whole
+    assert!(
+        output.contains("```rust\nfn main() {\n\n    println!(\"hi\");\n}\n```"),
+        "{}",
+        output
+    );
+    assert!(
+        output.contains(
+            "\n---\n*Truncated: 3 more blocks match this query. Narrow the date range or raise --max-blocks.*\n"
+        ),
+        "{}",
+        output
+    );
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--max-blocks", "2", "--keep", "oldest"])
+        .assert()
+        .success();
+    let output = fs::read_to_string(temp.path().join(".compilations/work.md")).unwrap();
+    assert!(output.contains("Entry of day 10"), "{}", output);
+    assert!(output.contains("Entry of day 11"), "{}", output);
+    assert!(!output.contains("Snippet"), "{}", output);
+
+    // A limit the compilation fits in changes nothing
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--max-bytes", "100000"])
+        .assert()
+        .success();
+    let output = fs::read_to_string(temp.path().join(".compilations/work.md")).unwrap();
+    assert!(!output.contains("Truncated"), "{}", output);
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--max-blocks", "0"])
+        .assert()
+        .failure();
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--max-blocks", "1", "--keep", "middle"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("Invalid keep order"));
+}
+
+#[test]
+fn test_compile_prints_related_tags_to_stderr() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    create_note(
+        &temp,
+        "2025-01-13.md",
+        "Daily sync #work #standup\n\nPlanning #work #standup #review\n",
+    );
+    create_note(
+        &temp,
+        "2025-01-14.md",
+        "Sync again #work #standup #1on1\n\nDeep work #work/focus\n\nHome #garden\n",
+    );
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("related:").not())
+        .stderr(predicate::str::contains(
+            "related: #standup (3) #1on1 (1) #review (1)\n",
+        ));
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--suggest-limit", "1"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("related: #standup (3)\n"));
+
+    for args in [
+        &["compile", "work", "--suggest-limit", "0"][..],
+        &["--quiet", "compile", "work"][..],
+        &["compile", "work AND NOT standup"][..],
+    ] {
+        djour_cmd()
+            .current_dir(temp.path())
+            .args(args)
+            .assert()
+            .success()
+            .stderr(predicate::str::contains("related:").not());
+    }
+}
+
+#[test]
+fn test_tags_in_html_comments_are_invisible() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    create_note(
+        &temp,
+        "2025-01-14.md",
+        "# January 14, 2025\n\n## Work #work\n\nShipped.\n\n## Gym #health\n\nRan.\n",
+    );
+    create_note(
+        &temp,
+        "2025-01-15.md",
+        "# January 15, 2025\n\nNotes <!-- #secret -->\n\n<!-- #draft needs review -->\n",
+    );
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["mode", "weekly", "--promote-headings"])
+        .write_stdin("y\n")
+        .assert()
+        .success();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("tags")
+        .assert()
+        .success()
+        .stdout("#health\n#work\n");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "draft OR secret"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No content found"));
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "health"])
+        .assert()
+        .success();
+    let content = fs::read_to_string(temp.path().join(".compilations/health.md")).unwrap();
+    assert!(content.ends_with("\n\nRan.\n"), "{}", content);
+    assert!(!content.contains("djour:migrated-from"), "{}", content);
+}
+
+#[test]
+fn test_compile_exclude_globs_union_with_config() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    create_note(&temp, "2025-01-01.md", "Year planning #work #health\n");
+    create_note(&temp, "2025-01-15.md", "Standup #work\n");
+    fs::create_dir_all(temp.path().join("projects/alpha")).unwrap();
+    create_note(&temp, "projects/alpha/2025-01-16.md", "Alpha sync #work\n");
+    create_note(&temp, "projects/2025-01-17.md", "Roadmap #work\n");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "compile_exclude", "2025-01-01.md"])
+        .assert()
+        .success();
+
+    // `*` stays within one directory, `**` spans them
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--recursive", "--stdout"])
+        .args(["--exclude", "projects/*/*.md"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Standup"))
+        .stdout(predicate::str::contains("Roadmap"))
+        .stdout(predicate::str::contains("Alpha sync").not())
+        .stdout(predicate::str::contains("Year planning").not())
+        .stderr(predicate::str::contains("excluded 2 files"));
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--recursive", "--stdout"])
+        .args(["--exclude", "projects/**"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Standup"))
+        .stdout(predicate::str::contains("Roadmap").not())
+        .stderr(predicate::str::contains("excluded 3 files"));
+}
+
+#[test]
+fn test_compile_title_filter_narrows_notes() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    create_note(
+        &temp,
+        "2025-01-10.md",
+        "# Sprint 4 Retro\n\nShip smaller PRs #work\n",
+    );
+    create_note(&temp, "2025-01-15.md", "# Standup\n\nFixed CI #work\n");
+    create_note(&temp, "2025-01-24.md", "Retro in the body only #work\n");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--stdout", "--title-filter", "retro"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Ship smaller PRs"))
+        .stdout(predicate::str::contains("Fixed CI").not())
+        .stdout(predicate::str::contains("Retro in the body only").not());
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--stdin", "--title-filter", "retro"])
+        .assert()
+        .code(1);
+}
+
+#[test]
+fn test_compile_rejects_exclusion_only_query() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    create_note(
+        &temp,
+        "2025-01-15.md",
+        "Standup #meeting
+
+Fixed CI #work
+",
+    );
+
+    for query in ["NOT meeting", "NOT meeting OR NOT work"] {
+        djour_cmd()
+            .current_dir(temp.path())
+            .args(["compile", query, "--stdout"])
+            .assert()
+            .code(1)
+            .stdout("")
+            .stderr(predicate::str::contains(
+                "Query matches by exclusion only; add at least one tag, or pass --allow-exclusion-only",
+            ));
+    }
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args([
+            "compile",
+            "NOT meeting",
+            "--stdout",
+            "--allow-exclusion-only",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Fixed CI"))
+        .stdout(predicate::str::contains("Standup").not());
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work AND NOT meeting", "--stdout"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Fixed CI"));
+}
+
+#[test]
+fn test_compile_note_flag_wins_over_exclude() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    create_note(&temp, "2025-01-01.md", "Year planning #work\n");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--stdout", "--note", "2025-01-01.md"])
+        .args(["--exclude", "*-01-01.md"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Year planning"))
+        .stderr(predicate::str::contains(
+            "warning: 2025-01-01.md matches exclude pattern '*-01-01.md'; compiling it because --note names it",
+        ));
+}
+
+#[test]
+fn test_compile_rejects_invalid_exclude_pattern() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    create_note(&temp, "2025-01-15.md", "Standup #work\n");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work", "--exclude", "notes/[a-"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains(
+            "Invalid exclude pattern 'notes/[a-'",
+        ));
+    assert!(!temp.path().join(".compilations/work.md").exists());
+}
+
+#[test]
+fn test_compile_again_replays_last_options() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    create_note(&temp, "2025-01-10.md", "Old work #work\n");
+    create_note(&temp, "2025-01-15.md", "Fixed CI #work\n");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args([
+            "compile",
+            "work",
+            "--from",
+            "12-01-2025",
+            "--format",
+            "grouped",
+            "-o",
+            "reports/work.md",
+        ])
+        .assert()
+        .success();
+    let saved = fs::read_to_string(temp.path().join(".djour/cache/last_compile.toml")).unwrap();
+    assert!(saved.contains("from = \"2025-01-12\""), "{}", saved);
+
+    // Replayed from a subdirectory: the output path stays journal-relative
+    create_note(&temp, "2025-01-15.md", "Fixed CI and deployed #work\n");
+    fs::create_dir_all(temp.path().join("reports")).unwrap();
+    djour_cmd()
+        .current_dir(temp.path().join("reports"))
+        .args(["compile", "--again"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(
+            "Replaying: djour compile work --output reports/work.md --from 12-01-2025 --format grouped\n",
+        ));
+
+    let content = fs::read_to_string(temp.path().join("reports/work.md")).unwrap();
+    assert!(content.contains("Fixed CI and deployed"), "{}", content);
+    assert!(!content.contains("Old work"), "{}", content);
+    assert!(content.contains("## From: 2025-01-15.md"), "{}", content);
+}
+
+#[test]
+fn test_compile_again_named_slots_and_stale_files() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    create_note(
+        &temp,
+        "2025-01-15.md",
+        "Fixed CI #work\n\nPlanning #meeting\n",
+    );
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "--again"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains(
+            "No compile to replay; run djour compile <query> first",
+        ));
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "meeting", "--save-as", "meetings"])
+        .assert()
+        .success();
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work"])
+        .assert()
+        .success();
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "--again", "meetings"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(
+            "Replaying: djour compile meeting\n",
+        ));
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "--again", "weekly"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("No compile saved as 'weekly'"));
+
+    // A file from another format version is ignored with a warning
+    let last = temp.path().join(".djour/cache/last_compile.toml");
+    let saved = fs::read_to_string(&last).unwrap();
+    fs::write(&last, saved.replace("version = 1", "version = 99")).unwrap();
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "--again"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains(
+            "warning: ignoring .djour/cache/last_compile.toml: saved in format version 99",
+        ));
+}
+
+#[test]
+fn test_compile_keeps_document_order_within_a_note() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    create_note(&temp, "design.md", "The design.\n");
+    create_note(
+        &temp,
+        "2025-01-15.md",
+        "# Day
+
+First [design](design.md) #work
+
+## Standup #work
+
+Second
+
+- Third #work
+  - Fourth
+- Untagged
+  - Fifth #work
+
+Sixth #work
+",
+    );
+
+    for format in ["chronological", "grouped"] {
+        let output = djour_cmd()
+            .current_dir(temp.path())
+            .args([
+                "compile",
+                "work",
+                "--stdout",
+                "--embed-links",
+                "--format",
+                format,
+            ])
+            .output()
+            .unwrap();
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let positions: Vec<usize> = ["First", "Second", "Third", "Fourth", "Fifth", "Sixth"]
+            .iter()
+            .map(|marker| stdout.find(marker).expect(marker))
+            .collect();
+        assert!(positions.windows(2).all(|w| w[0] < w[1]), "{}", stdout);
+        assert!(stdout.contains("The design."), "{}", stdout);
+    }
+}
+
+#[test]
+fn test_compile_warns_about_unclosed_code_fence() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    create_note(
+        &temp,
+        "2025-02-10.md",
+        "## Morning #health\n\nRan 5k.\n\n```sh\nuptime\n\n## Evening #health\n\nSlept well.\n",
+    );
+    create_note(
+        &temp,
+        "2025-02-11.md",
+        "## Morning #health\n\n```sh\nuptime\n```\n\nStretched.\n",
+    );
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "health"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "warning: 2025-02-10.md: unclosed code fence opened at line 5; \
+             content after it is treated as code",
+        ))
+        .stderr(predicate::str::contains("2025-02-11.md").not());
+
+    // The warning changes nothing: the text after the fence stays in the code block
+    let content = fs::read_to_string(temp.path().join(".compilations/health.md")).unwrap();
+    assert!(
+        content.contains("Ran 5k.\n\n```sh\nuptime\n\n## Evening #health\n\nSlept well.\n"),
+        "{}",
+        content
+    );
+    assert_eq!(content.matches("Slept well.").count(), 1, "{}", content);
+    assert!(content.contains("Stretched."), "{}", content);
+}
+
+#[test]
+fn test_compile_value_tags_by_name_and_by_value() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    create_note(
+        &temp,
+        "2025-01-15.md",
+        "Easy loop #run:5k\n\nHill repeats #run:10k\n\nRest day #mood:tired\n",
+    );
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "value_tags", "true"])
+        .assert()
+        .success();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "run"])
+        .assert()
+        .success();
+    let all_runs = fs::read_to_string(temp.path().join(".compilations/run.md")).unwrap();
+    assert!(all_runs.contains("Easy loop"), "{}", all_runs);
+    assert!(all_runs.contains("Hill repeats"), "{}", all_runs);
+    assert!(!all_runs.contains("Rest day"), "{}", all_runs);
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "#run:5k"])
+        .assert()
+        .success();
+    let five = fs::read_to_string(temp.path().join(".compilations/run_5k.md")).unwrap();
+    assert!(five.contains("Easy loop"), "{}", five);
+    assert!(!five.contains("Hill repeats"), "{}", five);
+}
+
+#[test]
+fn test_compile_comment_opener_in_inline_code_hides_nothing() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    create_note(
+        &temp,
+        "2025-01-15.md",
+        "Use `<!--` to open a comment #work\n\n## Later #home\n\nEvening walk.\n",
+    );
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "home", "--stdout"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Evening walk."));
+}
@@ -0,0 +1,378 @@
+//! Integration tests for config command
+
+#![allow(deprecated)]
+
+use predicates::prelude::*;
+use std::fs;
+use tempfile::TempDir;
+
+mod common;
+use common::djour_cmd;
+
+#[test]
+fn test_config_set_and_get() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "week_start", "sunday"])
+        .assert()
+        .success();
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "week_start"])
+        .assert()
+        .success()
+        .stdout("sunday\n");
+}
+
+#[test]
+fn test_config_name_and_author_round_trip() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "name", "Work Log"])
+        .assert()
+        .success();
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "name"])
+        .assert()
+        .success()
+        .stdout("Work Log\n");
+
+    // An empty value clears the key
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "name", ""])
+        .assert()
+        .success();
+    let config = fs::read_to_string(temp.path().join(".djour/config.toml")).unwrap();
+    assert!(!config.contains("name"), "{}", config);
+}
+
+#[test]
+fn test_config_viewer_round_trip_and_rejects_empty() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "viewer", "glow -p"])
+        .assert()
+        .success();
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "viewer"])
+        .assert()
+        .success()
+        .stdout("glow -p\n");
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "--list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("viewer = glow -p"));
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "viewer", " "])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("the viewer command is empty"));
+}
+
+#[test]
+fn test_config_editor_batch_keys() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "editor_max_files"])
+        .assert()
+        .success()
+        .stdout("5\n");
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "editor_max_files", "0"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("at least 1"));
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "editor_batch_template", "code"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains(
+            "Use {files} where the file paths go",
+        ));
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "editor_batch_template", "code {files}"])
+        .assert()
+        .success();
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "--list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "editor_batch_template = code {files}",
+        ));
+}
+
+#[test]
+fn test_config_edit_with_missing_editor_restores_config() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+    let config_path = temp.path().join(".djour/config.toml");
+    let original = fs::read_to_string(&config_path).unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .env("EDITOR", "djour-test-no-such-editor")
+        .args(["config", "--edit"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("djour-test-no-such-editor"));
+
+    assert_eq!(fs::read_to_string(&config_path).unwrap(), original);
+    assert!(!temp.path().join(".djour/config.toml.bak").exists());
+}
+
+#[test]
+fn test_config_edit_rejects_conflicting_arguments() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "--edit", "--list"])
+        .assert()
+        .code(1);
+}
+
+#[test]
+fn test_config_repair_recovers_a_corrupt_config() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+    let config_path = temp.path().join(".djour/config.toml");
+    let broken = "mode = \"weekly\"\neditor = \"vim\nname = \"Work log\"\n";
+    fs::write(&config_path, broken).unwrap();
+
+    // Every command names the file, the position and the way out
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "mode", "daily"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("config.toml at line 2, column"))
+        .stderr(predicate::str::contains("djour config --repair"));
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "--repair"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("original saved as"))
+        .stdout(predicate::str::contains("commented out: editor = \"vim"));
+
+    let repaired = fs::read_to_string(&config_path).unwrap();
+    assert!(
+        repaired.contains("# djour-repair: editor = \"vim\n"),
+        "{}",
+        repaired
+    );
+    let backups: Vec<_> = fs::read_dir(temp.path().join(".djour"))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .filter(|name| name.starts_with("config.toml.broken-"))
+        .collect();
+    assert_eq!(backups.len(), 1, "{:?}", backups);
+    assert_eq!(
+        fs::read_to_string(temp.path().join(".djour").join(&backups[0])).unwrap(),
+        broken
+    );
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "name"])
+        .assert()
+        .success()
+        .stdout("Work log\n");
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "--repair"])
+        .assert()
+        .success()
+        .stdout("config.toml loads fine; nothing to repair\n");
+}
+
+#[test]
+fn test_config_set_bootstraps_missing_config() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+    let config_path = temp.path().join(".djour/config.toml");
+    fs::remove_file(&config_path).unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "name", "Work log"])
+        .assert()
+        .success();
+    let config = fs::read_to_string(&config_path).unwrap();
+    assert!(config.contains("mode = \"daily\""), "{}", config);
+    assert!(config.contains("name = \"Work log\""), "{}", config);
+}
+
+#[cfg(unix)]
+mod scripted_editor {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::{Path, PathBuf};
+
+    const BAD_CONFIG: &str = "mode = \"daily\"\neditor = \"vim\"\nfilename_prefix = \"notes/\"\n\n[queries]\nbroken = \"work AND\"\n";
+    const GOOD_CONFIG: &str = "mode = \"weekly\"\neditor = \"vim\"\n";
+
+    /// Write an "editor" that writes BAD_CONFIG on its first run and GOOD_CONFIG afterwards
+    fn write_editor(dir: &Path) -> PathBuf {
+        fs::write(dir.join("bad.toml"), BAD_CONFIG).unwrap();
+        fs::write(dir.join("good.toml"), GOOD_CONFIG).unwrap();
+        let script = dir.join("editor.sh");
+        fs::write(
+            &script,
+            format!(
+                "#!/bin/sh\nif [ -e '{runs}' ]; then cp '{good}' \"$1\"; else touch '{runs}'; cp '{bad}' \"$1\"; fi\n",
+                runs = dir.join("ran-once").display(),
+                good = dir.join("good.toml").display(),
+                bad = dir.join("bad.toml").display(),
+            ),
+        )
+        .unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+        script
+    }
+
+    #[test]
+    fn test_config_edit_reopens_after_invalid_save() {
+        let temp = TempDir::new().unwrap();
+        let scripts = TempDir::new().unwrap();
+        djour_cmd().arg("init").arg(temp.path()).assert().success();
+        let editor = write_editor(scripts.path());
+
+        djour_cmd()
+            .current_dir(temp.path())
+            .env("EDITOR", &editor)
+            .args(["config", "--edit"])
+            .write_stdin("y\n")
+            .assert()
+            .success()
+            .stderr(predicate::str::contains("2 problem(s)"))
+            .stderr(predicate::str::contains("filename_prefix"))
+            .stderr(predicate::str::contains("Invalid saved query 'broken'"))
+            .stdout(predicate::str::contains("~ mode: \"daily\" -> \"weekly\""));
+
+        assert_eq!(
+            fs::read_to_string(temp.path().join(".djour/config.toml")).unwrap(),
+            GOOD_CONFIG
+        );
+        assert!(!temp.path().join(".djour/config.toml.bak").exists());
+    }
+
+    #[test]
+    fn test_config_edit_declined_restores_backup() {
+        let temp = TempDir::new().unwrap();
+        let scripts = TempDir::new().unwrap();
+        djour_cmd().arg("init").arg(temp.path()).assert().success();
+        let config_path = temp.path().join(".djour/config.toml");
+        let original = fs::read_to_string(&config_path).unwrap();
+        let editor = write_editor(scripts.path());
+
+        djour_cmd()
+            .current_dir(temp.path())
+            .env("EDITOR", &editor)
+            .args(["config", "--edit"])
+            .write_stdin("n\n")
+            .assert()
+            .code(1)
+            .stderr(predicate::str::contains("restored the previous version"));
+
+        assert_eq!(fs::read_to_string(&config_path).unwrap(), original);
+        assert!(!temp.path().join(".djour/config.toml.bak").exists());
+    }
+}
+
+#[test]
+fn test_config_state_dir_moves_state_to_xdg_and_back() {
+    let temp = TempDir::new().unwrap();
+    let journal = temp.path().join("journal");
+    let state_home = temp.path().join("state");
+    djour_cmd().arg("init").arg(&journal).assert().success();
+    fs::create_dir_all(journal.join(".djour/cache")).unwrap();
+    fs::write(journal.join(".djour/cache/tags.idx"), "index").unwrap();
+    fs::create_dir_all(journal.join(".djour/trash")).unwrap();
+    fs::write(journal.join(".djour/trash/2025-01-15.md"), "gone").unwrap();
+
+    djour_cmd()
+        .current_dir(&journal)
+        .env("XDG_STATE_HOME", &state_home)
+        .args(["config", "state_dir", "xdg"])
+        .assert()
+        .success();
+
+    // One directory per journal under $XDG_STATE_HOME/djour
+    let journal_dirs: Vec<_> = fs::read_dir(state_home.join("djour"))
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .collect();
+    assert_eq!(journal_dirs.len(), 1);
+    let state = &journal_dirs[0];
+    assert!(state
+        .file_name()
+        .unwrap()
+        .to_string_lossy()
+        .starts_with("journal-"));
+    assert_eq!(
+        fs::read_to_string(state.join("cache/tags.idx")).unwrap(),
+        "index"
+    );
+    assert!(state.join("trash/2025-01-15.md").exists());
+    assert!(!journal.join(".djour/cache").exists());
+    assert!(!journal.join(".djour/trash").exists());
+    assert!(journal.join(".djour/config.toml").exists());
+
+    let config = fs::read_to_string(journal.join(".djour/config.toml")).unwrap();
+    assert!(config.contains("state_dir = \"xdg\""));
+    djour_cmd()
+        .current_dir(&journal)
+        .args(["config", "state_dir"])
+        .assert()
+        .success()
+        .stdout("xdg\n");
+
+    djour_cmd()
+        .current_dir(&journal)
+        .env("XDG_STATE_HOME", &state_home)
+        .args(["config", "state_dir", "journal"])
+        .assert()
+        .success();
+    assert!(journal.join(".djour/cache/tags.idx").exists());
+    assert!(!state.join("cache").exists());
+}
+
+#[test]
+fn test_config_state_dir_rejects_unknown_value() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "state_dir", "cloud"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Valid values are: journal, xdg"));
+}
@@ -0,0 +1,106 @@
+//! Integration tests for opening several files with `EditorSession::open_all`
+
+#![cfg(unix)]
+
+use djour::infrastructure::EditorSession;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// Write an executable `name` in `dir` that logs each launch's arguments, one launch per line
+fn write_stub_editor(dir: &Path, name: &str) -> (PathBuf, PathBuf) {
+    let log = dir.join(format!("{}.log", name));
+    let script = dir.join(name);
+    fs::write(
+        &script,
+        format!(
+            "#!/bin/sh\nprintf '%s|' \"$@\" >> '{}'\necho >> '{}'\n",
+            log.display(),
+            log.display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+    (script, log)
+}
+
+fn notes(dir: &Path, names: &[&str]) -> Vec<PathBuf> {
+    names.iter().map(|name| dir.join(name)).collect()
+}
+
+fn launches(log: &Path) -> Vec<String> {
+    fs::read_to_string(log)
+        .unwrap()
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+#[test]
+fn test_open_all_batches_known_editor_in_one_launch() {
+    let temp = TempDir::new().unwrap();
+    let (vim, log) = write_stub_editor(temp.path(), "vim");
+    let paths = notes(temp.path(), &["2025-01-13.md", "2025-01-14 draft.md"]);
+
+    EditorSession::new(format!("{} -p", vim.display()))
+        .open_all(&paths)
+        .unwrap();
+
+    assert_eq!(
+        launches(&log),
+        vec![format!("-p|{}|{}|", paths[0].display(), paths[1].display())]
+    );
+}
+
+#[test]
+fn test_open_all_opens_other_editors_one_by_one() {
+    let temp = TempDir::new().unwrap();
+    let (nano, log) = write_stub_editor(temp.path(), "nano");
+    let paths = notes(temp.path(), &["a.md", "b.md", "c.md"]);
+
+    EditorSession::new(nano.display().to_string())
+        .open_all(&paths)
+        .unwrap();
+
+    let expected: Vec<String> = paths
+        .iter()
+        .map(|path| format!("{}|", path.display()))
+        .collect();
+    assert_eq!(launches(&log), expected);
+}
+
+#[test]
+fn test_open_all_batch_template_launches_once() {
+    let temp = TempDir::new().unwrap();
+    let (nano, log) = write_stub_editor(temp.path(), "nano");
+    let paths = notes(temp.path(), &["a.md", "it's b.md"]);
+
+    EditorSession::new(nano.display().to_string())
+        .with_batch_template(Some(format!("'{}' --tabs {{files}}", nano.display())))
+        .open_all(&paths)
+        .unwrap();
+
+    assert_eq!(
+        launches(&log),
+        vec![format!(
+            "--tabs|{}|{}|",
+            paths[0].display(),
+            paths[1].display()
+        )]
+    );
+}
+
+#[test]
+fn test_open_all_over_the_cap_launches_nothing() {
+    let temp = TempDir::new().unwrap();
+    let (vim, log) = write_stub_editor(temp.path(), "vim");
+    let paths = notes(temp.path(), &["a.md", "b.md", "c.md"]);
+
+    let err = EditorSession::new(vim.display().to_string())
+        .with_max_files(2)
+        .open_all(&paths)
+        .unwrap_err();
+    assert!(err.to_string().contains("narrow the selection"), "{}", err);
+    assert!(!log.exists());
+}
@@ -0,0 +1,80 @@
+//! Integration tests for `djour env`
+
+#![allow(deprecated)]
+
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+mod common;
+use common::djour_cmd;
+
+#[test]
+fn test_env_reports_provenance() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd()
+        .arg("init")
+        .arg(temp.path())
+        .args(["--mode", "weekly"])
+        .assert()
+        .success();
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "editor", "vi"])
+        .assert()
+        .success();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .env("EDITOR", "nvim")
+        .arg("env")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("(from current directory)\n"))
+        .stdout(predicate::str::contains(
+            "mode = weekly (from config.toml)\n",
+        ))
+        .stdout(predicate::str::contains(
+            "editor = nvim (from EDITOR, overriding config 'vi')\n",
+        ));
+
+    let outside = TempDir::new().unwrap();
+    djour_cmd()
+        .current_dir(outside.path())
+        .env("DJOUR_ROOT", temp.path())
+        .env("DJOUR_MODE", "daily")
+        .arg("env")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("(from DJOUR_ROOT)\n"))
+        .stdout(predicate::str::contains(
+            "mode = daily (from DJOUR_MODE, overriding config 'weekly')\n",
+        ))
+        .stdout(predicate::str::contains("editor = vi (from config.toml)\n"));
+}
+
+#[test]
+fn test_env_outside_journal() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("env")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("root = <not found>\n"))
+        .stdout(predicate::str::contains("mode = daily (from default)\n"));
+
+    let output = djour_cmd()
+        .current_dir(temp.path())
+        .env("VISUAL", "code -w")
+        .args(["env", "--json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(json["root"].is_null());
+    assert!(json["problem"].is_string());
+    assert_eq!(json["mode"]["value"], "daily");
+    assert_eq!(json["mode"]["source"], "default");
+    assert_eq!(json["editor"]["value"], "code -w");
+    assert_eq!(json["editor"]["source"], "VISUAL");
+}
@@ -0,0 +1,173 @@
+//! Integration tests for exit codes and the global --quiet flag
+
+#![allow(deprecated)]
+
+use predicates::prelude::*;
+use std::fs;
+use tempfile::TempDir;
+
+mod common;
+use common::djour_cmd;
+
+fn init_journal(temp: &TempDir) {
+    djour_cmd()
+        .arg("init")
+        .arg(temp.path())
+        .arg("--quiet")
+        .assert()
+        .success()
+        .stdout("");
+}
+
+fn create_note(temp: &TempDir, filename: &str, content: &str) {
+    fs::write(temp.path().join(filename), content).unwrap();
+}
+
+#[test]
+fn test_exit_code_not_a_journal() {
+    let temp = TempDir::new().unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("list")
+        .assert()
+        .code(2);
+}
+
+#[test]
+fn test_exit_code_user_errors() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    create_note(&temp, "2025-01-15.md", "Note #work");
+
+    // Bad date
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["list", "--from", "2025-01-01"])
+        .assert()
+        .code(1);
+
+    // Bad query
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work AND"])
+        .assert()
+        .code(1);
+
+    // Bad time reference
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("someday")
+        .assert()
+        .code(1);
+
+    // Usage error
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["today", "list"])
+        .assert()
+        .code(1);
+}
+
+#[test]
+fn test_exit_code_nothing_matched() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+
+    // No notes: plain list succeeds, --fail-empty reports nothing matched
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("list")
+        .assert()
+        .code(0);
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["list", "--fail-empty"])
+        .assert()
+        .code(3);
+
+    create_note(&temp, "2025-01-15.md", "Note #work");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["list", "--fail-empty"])
+        .assert()
+        .code(0);
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "personal"])
+        .assert()
+        .code(3);
+}
+
+#[test]
+fn test_quiet_silences_success_chatter() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    create_note(&temp, "2025-01-15.md", "Note #work");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["--quiet", "config", "editor", "vim"])
+        .assert()
+        .success()
+        .stdout("");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["-q", "compile", "work"])
+        .assert()
+        .success()
+        .stdout("");
+    assert!(temp.path().join(".compilations/work.md").exists());
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["retag", "work", "project", "--quiet"])
+        .assert()
+        .success()
+        .stdout("");
+
+    create_note(
+        &temp,
+        "2025-01-15.md",
+        "# January 15, 2025\n\nNote #project\n",
+    );
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["-q", "mode", "weekly", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Mode migration plan"))
+        .stderr("");
+}
+
+#[test]
+fn test_quiet_keeps_data_output() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    create_note(&temp, "2025-01-15.md", "Note #work");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["-q", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("2025-01-15.md"));
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["-q", "tags"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("work"));
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["-q", "config", "mode"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("daily"));
+}
@@ -130,3 +130,203 @@ fn test_config_get_created_fails() {
         .failure()
         .stderr(predicate::str::contains("Unknown config key: 'created'"));
 }
+
+#[test]
+fn test_init_without_examples_creates_no_notes() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+
+    let entries: Vec<_> = fs::read_dir(temp.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    assert_eq!(entries, vec![".djour"]);
+}
+
+#[test]
+fn test_init_with_examples_can_be_compiled_right_away() {
+    let temp = TempDir::new().unwrap();
+
+    djour_cmd()
+        .arg("init")
+        .arg(temp.path())
+        .arg("--with-examples")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("README-NOTES.md"));
+    assert!(temp.path().join("README-NOTES.md").exists());
+
+    // Two dated notes, and the onboarding note is not one of them
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("list")
+        .assert()
+        .success()
+        .stdout(
+            predicate::function(|out: &str| out.lines().count() == 2)
+                .and(predicate::str::contains("README").not()),
+        );
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "example"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Compiled 2 blocks from 2 notes"));
+    let compiled = fs::read_to_string(temp.path().join(".compilations/example.md")).unwrap();
+    assert!(compiled.contains("Tags on a heading apply"), "{compiled}");
+}
+
+#[test]
+fn test_init_detects_mode_from_existing_notes() {
+    let temp = TempDir::new().unwrap();
+    for name in ["2025-W02-2025-01-06.md", "2025-W03-2025-01-13.md"] {
+        fs::write(temp.path().join(name), "# Week\n").unwrap();
+    }
+
+    djour_cmd()
+        .arg("init")
+        .arg(temp.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Detected 2 existing weekly note(s)",
+        ))
+        .stdout(predicate::str::contains("Mode: Weekly"));
+
+    let config = fs::read_to_string(temp.path().join(".djour/config.toml")).unwrap();
+    assert!(config.contains("mode = \"weekly\""));
+}
+
+#[test]
+fn test_init_warns_when_mode_disagrees_with_existing_notes() {
+    let temp = TempDir::new().unwrap();
+    for name in ["2025-01-15.md", "2025-01-16.md", "2025-01-17.md"] {
+        fs::write(temp.path().join(name), "# Day\n").unwrap();
+    }
+
+    djour_cmd()
+        .arg("init")
+        .arg(temp.path())
+        .args(["--mode", "weekly"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("warning: 3 file(s) in"))
+        .stderr(predicate::str::contains(
+            "named like daily notes, but the journal mode is weekly",
+        ));
+
+    let config = fs::read_to_string(temp.path().join(".djour/config.toml")).unwrap();
+    assert!(config.contains("mode = \"weekly\""));
+}
+
+#[test]
+fn test_init_stores_journal_name_and_author() {
+    let temp = TempDir::new().unwrap();
+
+    djour_cmd()
+        .arg("init")
+        .arg(temp.path())
+        .args(["--name", "Work Log", "--author", "Tunç"])
+        .assert()
+        .success();
+
+    let config = fs::read_to_string(temp.path().join(".djour/config.toml")).unwrap();
+    assert!(config.contains("name = \"Work Log\""), "{}", config);
+    assert!(config.contains("author = \"Tunç\""), "{}", config);
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "--list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("name = Work Log\n"))
+        .stdout(predicate::str::contains("author = Tunç\n"));
+
+    // Journals without them keep the keys out of config.toml
+    let plain = TempDir::new().unwrap();
+    djour_cmd().arg("init").arg(plain.path()).assert().success();
+    let config = fs::read_to_string(plain.path().join(".djour/config.toml")).unwrap();
+    assert!(!config.contains("name"), "{}", config);
+}
+
+#[test]
+fn test_init_inside_a_journal_needs_nested() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+    let inner = temp.path().join("work");
+
+    djour_cmd()
+        .arg("init")
+        .arg(&inner)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("is inside the journal at"))
+        .stderr(predicate::str::contains("--nested"));
+    assert!(!inner.join(".djour").exists());
+
+    djour_cmd()
+        .arg("init")
+        .arg(&inner)
+        .arg("--nested")
+        .assert()
+        .success();
+    assert!(inner.join(".djour/config.toml").exists());
+}
+
+#[test]
+fn test_init_warns_about_journals_below() {
+    let temp = TempDir::new().unwrap();
+    let inner = temp.path().join("projects").join("alpha");
+    djour_cmd().arg("init").arg(&inner).assert().success();
+
+    djour_cmd()
+        .arg("init")
+        .arg(temp.path())
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("warning:"))
+        .stderr(predicate::str::contains("alpha is already a journal"));
+}
+
+#[test]
+fn test_init_undo_removes_a_pristine_journal() {
+    let temp = TempDir::new().unwrap();
+    fs::write(temp.path().join("2025-01-15.md"), "# Notes #work\n").unwrap();
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+    fs::create_dir(temp.path().join(".djour/templates")).unwrap();
+
+    djour_cmd()
+        .arg("init")
+        .arg(temp.path())
+        .arg("--undo")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Removed djour journal at"));
+    assert!(!temp.path().join(".djour").exists());
+    assert_eq!(
+        fs::read_to_string(temp.path().join("2025-01-15.md")).unwrap(),
+        "# Notes #work\n"
+    );
+}
+
+#[test]
+fn test_init_undo_refuses_a_populated_journal() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+    fs::create_dir(temp.path().join(".djour/templates")).unwrap();
+    fs::write(temp.path().join(".djour/templates/daily.md"), "# {DATE}\n").unwrap();
+    fs::write(temp.path().join(".djour/config.toml.bak"), "").unwrap();
+
+    djour_cmd()
+        .arg("init")
+        .arg(temp.path())
+        .arg("--undo")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "it holds more than a new journal does:\n  config.toml.bak\n  templates/daily.md",
+        ));
+    assert!(temp.path().join(".djour/config.toml").exists());
+    assert!(temp.path().join(".djour/templates/daily.md").exists());
+}
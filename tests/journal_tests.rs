@@ -0,0 +1,172 @@
+//! Integration tests for the journal registry and --journal
+
+#![allow(deprecated)]
+
+use predicates::prelude::*;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+mod common;
+use common::djour_cmd;
+
+/// A command using `config_home` as the user config directory
+fn djour(config_home: &Path) -> assert_cmd::Command {
+    let mut cmd = djour_cmd();
+    cmd.env("XDG_CONFIG_HOME", config_home);
+    cmd
+}
+
+fn init_with_note(root: &Path, body: &str) {
+    djour_cmd().arg("init").arg(root).assert().success();
+    fs::write(root.join("2026-10-17.md"), body).unwrap();
+}
+
+#[test]
+fn test_journal_add_list_remove() {
+    let temp = TempDir::new().unwrap();
+    let config_home = temp.path().join("config");
+    let work = temp.path().join("work");
+    init_with_note(&work, "# Work\n");
+
+    djour(&config_home)
+        .args(["journal", "add", "work"])
+        .arg(&work)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Registered journal 'work'"));
+    assert!(config_home.join("djour/journals.toml").exists());
+
+    // Listed from inside the journal, it is marked as current
+    djour(&config_home)
+        .current_dir(&work)
+        .args(["journal", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("* work  "));
+    djour(&config_home)
+        .current_dir(temp.path())
+        .args(["journal", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("  work  "));
+
+    djour(&config_home)
+        .args(["journal", "remove", "work"])
+        .assert()
+        .success();
+    djour(&config_home)
+        .args(["journal", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No journals registered"));
+    assert!(work.join(".djour").exists());
+}
+
+#[test]
+fn test_journal_add_rejects_uninitialized_and_duplicate() {
+    let temp = TempDir::new().unwrap();
+    let config_home = temp.path().join("config");
+    let work = temp.path().join("work");
+
+    djour(&config_home)
+        .args(["journal", "add", "work"])
+        .arg(&work)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("djour init"));
+
+    init_with_note(&work, "# Work\n");
+    djour(&config_home)
+        .args(["journal", "add", "work"])
+        .arg(&work)
+        .assert()
+        .success();
+    djour(&config_home)
+        .args(["journal", "add", "work"])
+        .arg(&work)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--replace"));
+}
+
+#[test]
+fn test_journal_flag_and_env_work_from_any_directory() {
+    let temp = TempDir::new().unwrap();
+    let config_home = temp.path().join("config");
+    let work = temp.path().join("work");
+    let elsewhere = temp.path().join("elsewhere");
+    fs::create_dir(&elsewhere).unwrap();
+    init_with_note(&work, "# Work\n");
+
+    djour(&config_home)
+        .args(["journal", "add", "work"])
+        .arg(&work)
+        .assert()
+        .success();
+
+    djour(&config_home)
+        .current_dir(&elsewhere)
+        .args(["list", "--journal", "work"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("2026-10-17.md"));
+    djour(&config_home)
+        .current_dir(&elsewhere)
+        .env("DJOUR_JOURNAL", "work")
+        .arg("list")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("2026-10-17.md"));
+}
+
+#[test]
+fn test_journal_precedence_over_djour_root() {
+    let temp = TempDir::new().unwrap();
+    let config_home = temp.path().join("config");
+    let work = temp.path().join("work");
+    let home = temp.path().join("home");
+    init_with_note(&work, "# Work #alpha\n");
+    init_with_note(&home, "# Home #beta\n");
+
+    djour(&config_home)
+        .args(["journal", "add", "work"])
+        .arg(&work)
+        .assert()
+        .success();
+    djour(&config_home)
+        .args(["journal", "add", "home"])
+        .arg(&home)
+        .assert()
+        .success();
+
+    // DJOUR_JOURNAL beats DJOUR_ROOT
+    djour(&config_home)
+        .env("DJOUR_ROOT", &home)
+        .env("DJOUR_JOURNAL", "work")
+        .arg("tags")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("alpha").and(predicate::str::contains("beta").not()));
+
+    // --journal beats DJOUR_JOURNAL
+    djour(&config_home)
+        .env("DJOUR_JOURNAL", "work")
+        .args(["tags", "--journal", "home"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("beta").and(predicate::str::contains("alpha").not()));
+}
+
+#[test]
+fn test_unknown_journal_is_an_error() {
+    let temp = TempDir::new().unwrap();
+    let config_home = temp.path().join("config");
+
+    djour(&config_home)
+        .current_dir(temp.path())
+        .args(["list", "--journal", "nope"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("Unknown journal 'nope'"));
+}
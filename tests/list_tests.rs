@@ -393,3 +393,477 @@ fn test_list_recursive_includes_nested_notes_and_skips_dot_dirs() {
     assert!(stdout.contains("projects/2025-01-16.md"));
     assert!(!stdout.contains("2025-01-17.md"));
 }
+
+#[cfg(unix)]
+#[test]
+fn test_list_recursive_follows_symlinks_only_when_configured() {
+    use std::os::unix::fs::symlink;
+
+    let temp = TempDir::new().unwrap();
+    let journal = temp.path().join("journal");
+    let shared = temp.path().join("team-notes");
+    fs::create_dir_all(&shared).unwrap();
+    fs::write(shared.join("2025-01-16.md"), "shared").unwrap();
+
+    djour_cmd().arg("init").arg(&journal).assert().success();
+    fs::write(journal.join("2025-01-15.md"), "root").unwrap();
+    fs::create_dir_all(journal.join("projects")).unwrap();
+    symlink(&shared, journal.join("projects").join("shared")).unwrap();
+    symlink(&journal, journal.join("projects").join("loop")).unwrap();
+
+    djour_cmd()
+        .current_dir(&journal)
+        .args(["list", "--recursive"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("2025-01-15.md"))
+        .stdout(predicate::str::contains("2025-01-16.md").not())
+        .stderr(predicate::str::contains("already scanned").not());
+
+    djour_cmd()
+        .current_dir(&journal)
+        .args(["config", "follow_symlinks", "true"])
+        .assert()
+        .success();
+
+    let output = djour_cmd()
+        .current_dir(&journal)
+        .args(["list", "--recursive"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stdout.contains("projects/shared/2025-01-16.md"),
+        "{}",
+        stdout
+    );
+    assert_eq!(stdout.matches("2025-01-15.md").count(), 1, "{}", stdout);
+    assert!(stderr.contains("warning: projects/loop"), "{}", stderr);
+    assert!(stderr.contains("already scanned"), "{}", stderr);
+}
+
+#[test]
+fn test_filename_prefix_and_suffix_are_recognized() {
+    let temp = TempDir::new().unwrap();
+
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "filename_prefix", "journal-"])
+        .assert()
+        .success();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["--yes", "17-01-2025"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("journal-2025-01-17.md"));
+    assert!(temp.path().join("journal-2025-01-17.md").exists());
+
+    fs::write(temp.path().join("2025-01-16.md"), "bare").unwrap();
+    fs::write(temp.path().join("2025-01-15-planning-day.md"), "slug").unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("list")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "17-01-2025  journal-2025-01-17.md",
+        ))
+        .stdout(predicate::str::contains("16-01-2025  2025-01-16.md"))
+        .stdout(predicate::str::contains("planning-day").not());
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "allow_filename_suffix", "true"])
+        .assert()
+        .success();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("list")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "15-01-2025  2025-01-15-planning-day.md",
+        ));
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "filename_prefix", "a/b-"])
+        .assert()
+        .code(1);
+}
+
+#[test]
+fn test_list_piped_output_is_plain() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+    fs::write(temp.path().join("2025-01-17.md"), "# Planning\n").unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("list")
+        .assert()
+        .success()
+        .stdout("17-01-2025  2025-01-17.md\n");
+}
+
+#[test]
+fn test_list_forced_color_shows_columns_and_titles() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+    fs::write(temp.path().join("2025-01-17.md"), "# Planning\n").unwrap();
+    fs::write(temp.path().join("2025-01-16.md"), [0xff, 0xfe]).unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .env("CLICOLOR_FORCE", "1")
+        .arg("list")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\x1b[36m17-01-2025\x1b[0m  Fri"))
+        .stdout(predicate::str::contains("2025-01-17.md  Planning"))
+        .stdout(predicate::str::contains("2025-01-16.md"));
+
+    for args in [vec!["list", "--no-color"], vec!["list"]] {
+        let mut cmd = djour_cmd();
+        cmd.current_dir(temp.path()).env("CLICOLOR_FORCE", "1");
+        if args.len() == 1 {
+            cmd.env("NO_COLOR", "1");
+        }
+        cmd.args(&args)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("\x1b[").not())
+            .stdout(predicate::str::contains("2025-01-17.md  Planning"));
+    }
+}
+
+/// Weekly journal with a template-conforming week 2 and a hand-edited week 3
+fn weekly_journal_with_mangled_week() -> TempDir {
+    let temp = TempDir::new().unwrap();
+    djour_cmd()
+        .args(["init", "--mode", "weekly"])
+        .arg(temp.path())
+        .assert()
+        .success();
+    for date in ["08-01-2025", "15-01-2025"] {
+        djour_cmd()
+            .current_dir(temp.path())
+            .args(["--yes", "--create", date])
+            .assert()
+            .success();
+    }
+    let mangled = temp.path().join("2025-W03-2025-01-13.md");
+    let content = fs::read_to_string(&mangled)
+        .unwrap()
+        .replace("## Tuesday (January 14, 2025)", "## Tuesday 14 Jan");
+    fs::write(&mangled, content).unwrap();
+    temp
+}
+
+#[test]
+fn test_list_warns_about_mangled_weekly_note() {
+    let temp = weekly_journal_with_mangled_week();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("list")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("2025-W02-2025-01-06.md"))
+        .stdout(predicate::str::contains("2025-W03-2025-01-13.md"))
+        .stderr(predicate::str::starts_with(
+            "warning: 2025-W03-2025-01-13.md: Weekly note is missing expected heading '## Tuesday (January 14, 2025)'\n",
+        ))
+        .stderr(predicate::str::contains("W02").not());
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["list", "--strict"])
+        .assert()
+        .code(1)
+        .stdout("")
+        .stderr(predicate::str::contains(
+            "error: 2025-W03-2025-01-13.md: Weekly note is missing expected heading",
+        ));
+}
+
+/// Set a file's modification time to the given local date and time
+fn set_mtime(path: &std::path::Path, y: i32, m: u32, d: u32, hour: u32) {
+    use chrono::TimeZone;
+    let modified = chrono::Local.with_ymd_and_hms(y, m, d, hour, 0, 0).unwrap();
+    fs::File::options()
+        .write(true)
+        .open(path)
+        .unwrap()
+        .set_modified(modified.into())
+        .unwrap();
+}
+
+#[test]
+fn test_list_changed_since_uses_modification_time() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+
+    // The older note was edited recently; the newer one has not been touched since.
+    fs::write(temp.path().join("2025-01-02.md"), "old note").unwrap();
+    fs::write(temp.path().join("2025-01-17.md"), "new note").unwrap();
+    set_mtime(&temp.path().join("2025-01-02.md"), 2025, 1, 20, 0);
+    set_mtime(&temp.path().join("2025-01-17.md"), 2025, 1, 17, 23);
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["list", "--changed-since", "20-01-2025"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("2025-01-02.md"))
+        .stdout(predicate::str::contains("2025-01-17.md").not());
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["list", "--changed-since", "21-01-2025"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No notes found"));
+}
+
+#[test]
+fn test_list_changed_since_applies_before_limit() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+
+    fs::write(temp.path().join("2025-01-17.md"), "untouched").unwrap();
+    fs::write(temp.path().join("2025-01-16.md"), "edited").unwrap();
+    set_mtime(&temp.path().join("2025-01-17.md"), 2025, 1, 17, 12);
+    set_mtime(&temp.path().join("2025-01-16.md"), 2025, 1, 25, 12);
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["list", "--changed-since", "20-01-2025", "--limit", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("2025-01-16.md"));
+}
+
+#[test]
+fn test_list_show_modified() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+
+    fs::write(temp.path().join("2025-01-17.md"), "note").unwrap();
+    set_mtime(&temp.path().join("2025-01-17.md"), 2025, 2, 3, 9);
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["list", "--show-modified"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "17-01-2025  2025-02-03 09:00  2025-01-17.md",
+        ));
+}
+
+#[test]
+fn test_list_changed_since_accepts_time_ref() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+
+    fs::write(temp.path().join("2025-01-17.md"), "written just now").unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["list", "--changed-since", "yesterday"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("2025-01-17.md"));
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["list", "--changed-since", "someday"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_mode_mismatch_hint_for_list_tags_and_compile() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd()
+        .arg("init")
+        .arg(temp.path())
+        .args(["--mode", "weekly"])
+        .assert()
+        .success();
+    fs::write(temp.path().join("2025-01-15.md"), "Daily note #work\n").unwrap();
+    fs::write(temp.path().join("2025-01-16.md"), "Daily note #work\n").unwrap();
+
+    let hint = "hint: Found 2 daily-looking note(s) but mode is weekly; \
+        see 'djour mode' or 'djour config mode'";
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("list")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No notes found"))
+        .stderr(predicate::str::contains(hint).count(1));
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("tags")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(hint).count(1));
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(hint).count(1));
+
+    // Once the mode matches, the hint is gone
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "mode", "daily"])
+        .assert()
+        .success();
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("list")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("hint:").not());
+}
+
+#[test]
+fn test_list_group_buckets_by_relative_date() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+
+    let today = chrono::Local::now().date_naive();
+    let today_file = format!("{}.md", today.format("%Y-%m-%d"));
+    fs::write(temp.path().join(&today_file), "# Today\n").unwrap();
+    fs::write(temp.path().join("2020-03-04.md"), "# Old\n").unwrap();
+    fs::write(temp.path().join("2020-03-02.md"), "# Older\n").unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["list", "--group"])
+        .assert()
+        .success()
+        .stdout(format!(
+            "Today\n{}  {}\n\nOlder\n04-03-2020  2020-03-04.md\n02-03-2020  2020-03-02.md\n",
+            today.format("%d-%m-%Y"),
+            today_file
+        ));
+
+    // --limit picks the newest notes before grouping
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["list", "--group", "--limit", "1"])
+        .assert()
+        .success()
+        .stdout(format!(
+            "Today\n{}  {}\n",
+            today.format("%d-%m-%Y"),
+            today_file
+        ));
+}
+
+#[test]
+fn test_list_warns_about_notes_named_by_the_other_week_numbering() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd()
+        .args(["init", "--mode", "weekly"])
+        .arg(temp.path())
+        .assert()
+        .success();
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "week_start", "sunday"])
+        .assert()
+        .success();
+    // The week of 26-12-2021 is ISO 2021-W52 but US 2022-W01
+    fs::write(temp.path().join("2021-W52-2021-12-26.md"), "").unwrap();
+    fs::write(temp.path().join("2022-W01-2021-12-26.md"), "").unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("list")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("2021-W52-2021-12-26.md"))
+        .stderr(predicate::str::contains(
+            "warning: 2022-W01-2021-12-26.md: named by us week numbering, but week_numbering is iso",
+        ));
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "week_numbering", "us"])
+        .assert()
+        .success();
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["list", "--strict"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "error: 2021-W52-2021-12-26.md: named by iso week numbering, but week_numbering is us",
+        ));
+}
+
+#[test]
+fn test_list_titles_and_sort_by_title() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+    fs::write(temp.path().join("2025-01-17.md"), "# Retro #team\n").unwrap();
+    fs::write(temp.path().join("2025-01-16.md"), "no heading\n").unwrap();
+    fs::write(
+        temp.path().join("2025-01-15.md"),
+        "```\n# code\n```\n## 1:1 with Sam\n",
+    )
+    .unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["list", "--titles"])
+        .assert()
+        .success()
+        .stdout(
+            "17-01-2025  2025-01-17.md  Retro\n\
+             16-01-2025  2025-01-16.md\n\
+             15-01-2025  2025-01-15.md  1:1 with Sam\n",
+        );
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["list", "--sort", "title"])
+        .assert()
+        .success()
+        .stdout(
+            "15-01-2025  2025-01-15.md  1:1 with Sam\n\
+             17-01-2025  2025-01-17.md  Retro\n\
+             16-01-2025  2025-01-16.md\n",
+        );
+
+    // Without --titles plain output never shows (or reads) titles
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("list")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Retro").not());
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["list", "--sort", "size"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains(
+            "Invalid sort: size. Use 'date' or 'title'",
+        ));
+}
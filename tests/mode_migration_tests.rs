@@ -141,6 +141,106 @@ fn test_mode_daily_to_weekly_migrates_and_archives() {
     assert!(archive_dir.join("config.toml").exists());
 }
 
+#[test]
+fn test_mode_daily_to_weekly_with_sunday_week_start() {
+    let temp = TempDir::new().unwrap();
+
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "week_start", "sunday"])
+        .assert()
+        .success();
+
+    let sunday = NaiveDate::from_ymd_opt(2025, 1, 12).unwrap();
+    let saturday = NaiveDate::from_ymd_opt(2025, 1, 18).unwrap();
+    write_daily(temp.path(), sunday, "Sunday reflections\n");
+    write_daily(temp.path(), saturday, "Saturday chores\n");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["mode", "weekly"])
+        .assert()
+        .success();
+
+    // Both days land in one week named after its Sunday, labelled with the ISO week of its Monday.
+    let weekly = fs::read_to_string(temp.path().join("2025-W03-2025-01-12.md")).unwrap();
+    assert!(weekly.starts_with(
+        "# Week 03, 2025 (January 12, 2025 - January 18, 2025)\n\n## Sunday (January 12, 2025)\n"
+    ));
+    let sunday_at = weekly.find("Sunday reflections").unwrap();
+    let monday_at = weekly.find("## Monday (January 13, 2025)").unwrap();
+    let saturday_at = weekly.find("Saturday chores").unwrap();
+    assert!(sunday_at < monday_at && monday_at < saturday_at);
+    assert!(!temp.path().join("2025-W03-2025-01-13.md").exists());
+
+    // And back again.
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["mode", "daily"])
+        .assert()
+        .success();
+    let daily = fs::read_to_string(temp.path().join("2025-01-12.md")).unwrap();
+    assert!(daily.contains("Sunday reflections"));
+    assert!(temp.path().join("2025-01-18.md").exists());
+}
+
+#[test]
+fn test_mode_daily_to_weekly_with_us_week_numbering() {
+    let temp = TempDir::new().unwrap();
+
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+    for (key, value) in [("week_start", "sunday"), ("week_numbering", "us")] {
+        djour_cmd()
+            .current_dir(temp.path())
+            .args(["config", key, value])
+            .assert()
+            .success();
+    }
+
+    // Sunday 31-12-2023 starts the US week containing January 1, 2024
+    let sunday = NaiveDate::from_ymd_opt(2023, 12, 31).unwrap();
+    let saturday = NaiveDate::from_ymd_opt(2024, 1, 6).unwrap();
+    write_daily(temp.path(), sunday, "Year end review #work\n");
+    write_daily(temp.path(), saturday, "First weekend\n");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["mode", "weekly"])
+        .assert()
+        .success();
+
+    let weekly = fs::read_to_string(temp.path().join("2024-W01-2023-12-31.md")).unwrap();
+    assert!(
+        weekly.starts_with("# Week 01, 2024 (December 31, 2023 - January 06, 2024)\n"),
+        "{}",
+        weekly
+    );
+    assert!(weekly.contains("Year end review") && weekly.contains("First weekend"));
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work"])
+        .assert()
+        .success();
+    let compiled = fs::read_to_string(temp.path().join(".compilations/work.md")).unwrap();
+    assert!(
+        compiled.contains("## 31-12-2023 to 06-01-2024"),
+        "{}",
+        compiled
+    );
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["mode", "daily"])
+        .assert()
+        .success();
+    assert!(fs::read_to_string(temp.path().join("2023-12-31.md"))
+        .unwrap()
+        .contains("Year end review"));
+    assert!(temp.path().join("2024-01-06.md").exists());
+}
+
 #[test]
 fn test_mode_weekly_to_daily_splits_and_archives() {
     let temp = TempDir::new().unwrap();
@@ -250,3 +350,296 @@ fn test_mode_warns_recursive_is_omitted() {
         .success()
         .stderr(predicate::str::contains("--recursive is omitted"));
 }
+
+#[test]
+fn test_mode_round_trip_with_filename_prefix() {
+    let temp = TempDir::new().unwrap();
+
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "filename_prefix", "journal-"])
+        .assert()
+        .success();
+
+    let monday = NaiveDate::from_ymd_opt(2025, 1, 13).unwrap();
+    let tuesday = NaiveDate::from_ymd_opt(2025, 1, 14).unwrap();
+    write_daily(temp.path(), monday, "Bare Monday note\n");
+    let content = format!(
+        "# {}\n\nPrefixed Tuesday note\n",
+        tuesday.format("%B %d, %Y")
+    );
+    fs::write(temp.path().join("journal-2025-01-14.md"), content).unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["mode", "weekly"])
+        .assert()
+        .success();
+
+    let weekly_name = format!("journal-{}", expected_weekly_filename(monday));
+    let weekly = fs::read_to_string(temp.path().join(&weekly_name)).unwrap();
+    assert!(weekly.contains("Bare Monday note"));
+    assert!(weekly.contains("Prefixed Tuesday note"));
+    assert!(!temp.path().join("2025-01-13.md").exists());
+    assert!(!temp.path().join("journal-2025-01-14.md").exists());
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["mode", "daily"])
+        .assert()
+        .success();
+
+    assert!(!temp.path().join(&weekly_name).exists());
+    let monday_note = fs::read_to_string(temp.path().join("journal-2025-01-13.md")).unwrap();
+    assert!(monday_note.contains("Bare Monday note"));
+    let tuesday_note = fs::read_to_string(temp.path().join("journal-2025-01-14.md")).unwrap();
+    assert!(tuesday_note.contains("Prefixed Tuesday note"));
+}
+
+#[test]
+fn test_mode_round_trip_is_byte_stable() {
+    let daily_body =
+        "Intro line\n\n## Standup\n\n- shipped it\n\n### Details\n\n```\n## not a heading\n```\n";
+
+    for promote in [false, true] {
+        let temp = TempDir::new().unwrap();
+        djour_cmd().arg("init").arg(temp.path()).assert().success();
+
+        let tuesday = NaiveDate::from_ymd_opt(2025, 1, 14).unwrap();
+        write_daily(temp.path(), tuesday, daily_body);
+        let original = fs::read_to_string(temp.path().join("2025-01-14.md")).unwrap();
+
+        let mut to_weekly = djour_cmd();
+        to_weekly.current_dir(temp.path()).args(["mode", "weekly"]);
+        if promote {
+            to_weekly.arg("--promote-headings");
+        }
+        to_weekly.assert().success();
+
+        let monday = NaiveDate::from_ymd_opt(2025, 1, 13).unwrap();
+        let weekly =
+            fs::read_to_string(temp.path().join(expected_weekly_filename(monday))).unwrap();
+        if promote {
+            assert!(
+                weekly.contains("2025-01-14.md:start demoted=1 -->"),
+                "{weekly}"
+            );
+            assert!(weekly.contains("\n### Standup\n"), "{weekly}");
+            assert!(weekly.contains("\n#### Details\n"), "{weekly}");
+            assert!(weekly.contains("\n## not a heading\n"), "{weekly}");
+        } else {
+            assert!(weekly.contains("\n## Standup\n"), "{weekly}");
+        }
+
+        djour_cmd()
+            .current_dir(temp.path())
+            .args(["mode", "daily"])
+            .assert()
+            .success();
+
+        let restored = fs::read_to_string(temp.path().join("2025-01-14.md")).unwrap();
+        assert_eq!(restored, original, "promote_headings = {promote}");
+    }
+}
+
+#[test]
+fn test_mode_weekly_to_daily_promotes_sub_headings() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd()
+        .arg("init")
+        .arg(temp.path())
+        .args(["--mode", "weekly"])
+        .assert()
+        .success();
+
+    let ws = NaiveDate::from_ymd_opt(2025, 1, 13).unwrap();
+    let weekly_name = expected_weekly_filename(ws);
+    let tuesday_body = "### Standup\n\nSynced with the team\n\n#### Deep work\n\nParser rewrite\n";
+    let original_weekly = build_weekly_template(ws, tuesday_body, false);
+    fs::write(temp.path().join(&weekly_name), &original_weekly).unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["mode", "daily", "--promote-headings"])
+        .assert()
+        .success();
+
+    let daily = fs::read_to_string(temp.path().join("2025-01-14.md")).unwrap();
+    assert!(daily.contains("\n## Standup\n"), "{daily}");
+    assert!(daily.contains("\n### Deep work\n"), "{daily}");
+    assert!(daily.contains("Parser rewrite"), "{daily}");
+
+    // Going back to weekly with the daily note untouched restores the
+    // original weekly note, and back to daily restores the daily note exactly.
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["mode", "weekly", "--promote-headings"])
+        .assert()
+        .success();
+    let weekly = fs::read_to_string(temp.path().join(&weekly_name)).unwrap();
+    assert_eq!(weekly, original_weekly);
+
+    // The first migration archived a weekly note with the same name this
+    // second, so archive this one elsewhere.
+    djour_cmd()
+        .current_dir(temp.path())
+        .args([
+            "mode",
+            "daily",
+            "--promote-headings",
+            "--archive-dir",
+            "archive-again",
+        ])
+        .assert()
+        .success();
+    let restored = fs::read_to_string(temp.path().join("2025-01-14.md")).unwrap();
+    assert_eq!(restored, daily);
+}
+
+#[test]
+fn test_mode_weekly_round_trip_keeps_untouched_days() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd()
+        .arg("init")
+        .arg(temp.path())
+        .args(["--mode", "weekly"])
+        .assert()
+        .success();
+
+    let ws = NaiveDate::from_ymd_opt(2025, 1, 13).unwrap();
+    let weekly_name = expected_weekly_filename(ws);
+    // Spacing the template would not produce: extra blank lines under
+    // Monday and none under Sunday
+    let original = build_weekly_template(ws, "Tuesday body line\n", false).replacen(
+        "(January 13, 2025)\n\n\n",
+        "(January 13, 2025)\n\n\n\n\n",
+        1,
+    );
+    let original = format!("{}\n", original.trim_end());
+    fs::write(temp.path().join(&weekly_name), &original).unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["mode", "daily", "--archive-dir", "archive/to-daily"])
+        .assert()
+        .success();
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["mode", "weekly", "--archive-dir", "archive/to-weekly"])
+        .assert()
+        .success();
+    assert_eq!(
+        fs::read_to_string(temp.path().join(&weekly_name)).unwrap(),
+        original
+    );
+    assert!(temp
+        .path()
+        .join("archive/to-weekly/split-weekly")
+        .join(&weekly_name)
+        .exists());
+
+    // An edited day is injected with the template's spacing; the others stay put
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["mode", "daily", "--archive-dir", "archive/to-daily-2"])
+        .assert()
+        .success();
+    write_daily(
+        temp.path(),
+        ws + chrono::Duration::days(3),
+        "Thursday line\n",
+    );
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["mode", "weekly", "--archive-dir", "archive/to-weekly-2"])
+        .assert()
+        .success();
+    let weekly = fs::read_to_string(temp.path().join(&weekly_name)).unwrap();
+    let expected = original.replacen(
+        "## Thursday (January 16, 2025)\n\n\n",
+        "## Thursday (January 16, 2025)\n\n\
+         <!-- djour:migrated-from=2025-01-16.md:start -->\nThursday line\n\
+         <!-- djour:migrated-from=2025-01-16.md:end -->\n\n",
+        1,
+    );
+    assert_eq!(weekly, expected);
+}
+
+#[test]
+fn test_mode_show_diff_previews_weekly_files() {
+    let temp = TempDir::new().unwrap();
+
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+
+    let monday = NaiveDate::from_ymd_opt(2025, 1, 13).unwrap();
+    write_daily(temp.path(), monday, "Some Monday note\n");
+    let weekly_name = expected_weekly_filename(monday);
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["mode", "weekly", "--dry-run", "--show-diff"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "--- /dev/null\n+++ b/{}\n@@ -0,0 +1,",
+            weekly_name
+        )))
+        .stdout(predicate::str::contains("\n+Some Monday note\n"))
+        .stdout(predicate::str::contains("\x1b[").not());
+
+    // Nothing was written
+    assert!(!temp.path().join(&weekly_name).exists());
+    assert!(temp.path().join("2025-01-13.md").exists());
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args([
+            "mode",
+            "weekly",
+            "--dry-run",
+            "--show-diff",
+            "--diff-lines",
+            "2",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("more lines)"));
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["mode", "weekly", "--dry-run", "--diff-lines", "2"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_mode_show_diff_previews_daily_files() {
+    let temp = TempDir::new().unwrap();
+
+    djour_cmd()
+        .args(["init", "--mode", "weekly"])
+        .arg(temp.path())
+        .assert()
+        .success();
+
+    let ws = NaiveDate::from_ymd_opt(2025, 1, 13).unwrap();
+    let weekly_name = expected_weekly_filename(ws);
+    fs::write(
+        temp.path().join(&weekly_name),
+        build_weekly_template(ws, "Tuesday body line\n", false),
+    )
+    .unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["mode", "daily", "--dry-run", "--show-diff"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "--- /dev/null\n+++ b/2025-01-14.md\n",
+        ))
+        .stdout(predicate::str::contains("+Tuesday body line\n"));
+
+    assert!(!temp.path().join("2025-01-14.md").exists());
+}
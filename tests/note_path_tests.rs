@@ -0,0 +1,113 @@
+//! Integration tests for note path/exists plumbing commands
+
+#![allow(deprecated)]
+
+use std::fs;
+use tempfile::TempDir;
+
+mod common;
+use common::djour_cmd;
+
+fn init_journal(temp: &TempDir, mode: &str) {
+    djour_cmd()
+        .arg("init")
+        .arg(temp.path())
+        .args(["--mode", mode])
+        .assert()
+        .success();
+}
+
+fn root(temp: &TempDir) -> std::path::PathBuf {
+    temp.path().canonicalize().unwrap()
+}
+
+#[test]
+fn test_note_path_for_missing_daily_note() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp, "daily");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["note", "path", "17-01-2025"])
+        .assert()
+        .code(4)
+        .stdout(format!("{}\n", root(&temp).join("2025-01-17.md").display()));
+
+    assert!(!temp.path().join("2025-01-17.md").exists());
+}
+
+#[test]
+fn test_note_path_for_existing_daily_note_from_subdirectory() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp, "daily");
+    fs::write(temp.path().join("2025-01-17.md"), "# Note").unwrap();
+    fs::create_dir(temp.path().join("sub")).unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path().join("sub"))
+        .args(["note", "path", "17-01-2025"])
+        .assert()
+        .code(0)
+        .stdout(format!("{}\n", root(&temp).join("2025-01-17.md").display()));
+}
+
+#[test]
+fn test_note_path_weekly_mode() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp, "weekly");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["note", "path", "17-01-2025"])
+        .assert()
+        .code(4)
+        .stdout(format!(
+            "{}\n",
+            root(&temp).join("2025-W03-2025-01-13.md").display()
+        ));
+
+    fs::write(temp.path().join("2025-W03-2025-01-13.md"), "# Week").unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["note", "path", "15-01-2025"])
+        .assert()
+        .code(0);
+}
+
+#[test]
+fn test_note_exists_only_sets_exit_code() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp, "daily");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["note", "exists", "17-01-2025"])
+        .assert()
+        .code(4)
+        .stdout("");
+
+    fs::write(temp.path().join("2025-01-17.md"), "# Note").unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["note", "exists", "17-01-2025"])
+        .assert()
+        .code(0)
+        .stdout("");
+
+    let entries = fs::read_dir(temp.path()).unwrap().count();
+    assert_eq!(entries, 2);
+}
+
+#[test]
+fn test_note_path_invalid_time_ref_is_user_error() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp, "daily");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["note", "path", "someday"])
+        .assert()
+        .code(1);
+}
@@ -0,0 +1,384 @@
+//! Integration tests for opening notes by time reference
+
+#![allow(deprecated)]
+
+use predicates::prelude::*;
+use std::fs;
+use tempfile::TempDir;
+
+mod common;
+use common::djour_cmd;
+
+fn init_journal(temp: &TempDir) {
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+}
+
+#[test]
+fn test_far_future_note_blocked_without_yes() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("17-01-2152")
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("date_guard_days"))
+        .stderr(predicate::str::contains("--yes"));
+
+    assert!(!temp.path().join("2152-01-17.md").exists());
+}
+
+#[test]
+fn test_far_note_created_with_yes_or_force() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["--yes", "17-01-2152"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("2152-01-17.md"));
+    assert!(temp.path().join("2152-01-17.md").exists());
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["17-01-1952", "--force"])
+        .assert()
+        .success();
+    assert!(temp.path().join("1952-01-17.md").exists());
+}
+
+#[test]
+fn test_near_and_existing_far_notes_open_without_confirmation() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("tomorrow")
+        .assert()
+        .success();
+
+    fs::write(temp.path().join("2152-01-17.md"), "# Existing").unwrap();
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("17-01-2152")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("2152-01-17.md"));
+    assert_eq!(
+        fs::read_to_string(temp.path().join("2152-01-17.md")).unwrap(),
+        "# Existing"
+    );
+}
+
+#[test]
+fn test_date_guard_can_be_disabled() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "date_guard_days", "0"])
+        .assert()
+        .success();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("17-01-2152")
+        .assert()
+        .success();
+    assert!(temp.path().join("2152-01-17.md").exists());
+}
+
+fn set_config(temp: &TempDir, key: &str, value: &str) {
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", key, value])
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_bare_time_ref_creates_with_notice_by_default() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["--yes", "17-01-2025"])
+        .assert()
+        .success()
+        .stdout("2025-01-17.md\n")
+        .stderr(predicate::str::contains("create_on_resolve"));
+    assert!(temp.path().join("2025-01-17.md").exists());
+
+    // Explicit --create and existing notes print no notice.
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["--yes", "--create", "18-01-2025"])
+        .assert()
+        .success()
+        .stderr("");
+    assert!(temp.path().join("2025-01-18.md").exists());
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["--yes", "17-01-2025"])
+        .assert()
+        .success()
+        .stderr("");
+}
+
+#[test]
+fn test_quiet_hides_implicit_create_notice() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["--quiet", "--yes", "17-01-2025"])
+        .assert()
+        .success()
+        .stderr("");
+    assert!(temp.path().join("2025-01-17.md").exists());
+}
+
+#[test]
+fn test_bare_time_ref_only_resolves_when_create_on_resolve_off() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    set_config(&temp, "create_on_resolve", "false");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["--yes", "17-01-2025"])
+        .assert()
+        .success()
+        .stdout("2025-01-17.md\n")
+        .stderr("");
+    assert!(!temp.path().join("2025-01-17.md").exists());
+
+    // Far-away dates are only guarded when something would be created.
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("17-01-2152")
+        .assert()
+        .success()
+        .stdout("2152-01-17.md\n");
+    assert!(!temp.path().join("2152-01-17.md").exists());
+}
+
+#[test]
+fn test_create_flag_creates_when_create_on_resolve_off() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    set_config(&temp, "create_on_resolve", "false");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["--yes", "--create", "17-01-2025"])
+        .assert()
+        .success()
+        .stdout("2025-01-17.md\n")
+        .stderr("");
+    assert!(fs::read_to_string(temp.path().join("2025-01-17.md"))
+        .unwrap()
+        .starts_with("# January 17, 2025"));
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["--create", "17-01-2152"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("date_guard_days"));
+}
+
+#[test]
+fn test_open_creates_regardless_of_create_on_resolve() {
+    for create_on_resolve in ["true", "false"] {
+        let temp = TempDir::new().unwrap();
+        init_journal(&temp);
+        set_config(&temp, "create_on_resolve", create_on_resolve);
+        set_config(&temp, "editor", "true");
+
+        djour_cmd()
+            .current_dir(temp.path())
+            .args(["--yes", "--open", "17-01-2025"])
+            .assert()
+            .success()
+            .stdout("2025-01-17.md\n")
+            .stderr("");
+        assert!(temp.path().join("2025-01-17.md").exists());
+    }
+}
+
+#[test]
+fn test_create_requires_time_ref() {
+    djour_cmd()
+        .arg("--create")
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("TIME_REF"));
+}
+
+#[test]
+fn test_note_template_uses_journal_name_and_author() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd()
+        .arg("init")
+        .arg(temp.path())
+        .args(["--name", "Work Log", "--author", "Tunç"])
+        .assert()
+        .success();
+    fs::create_dir_all(temp.path().join(".djour/templates")).unwrap();
+    fs::write(
+        temp.path().join(".djour/templates/daily.md"),
+        "# {JOURNAL_NAME}: {DATE}\n\nAuthor: {AUTHOR}\n",
+    )
+    .unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["--yes", "--create", "17-01-2025"])
+        .assert()
+        .success();
+    assert_eq!(
+        fs::read_to_string(temp.path().join("2025-01-17.md")).unwrap(),
+        "# Work Log: January 17, 2025\n\nAuthor: Tunç\n"
+    );
+}
+
+#[test]
+fn test_time_ref_matching_a_date_and_a_file_must_be_chosen() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    fs::write(temp.path().join("tomorrow.md"), "# Plans\n").unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("tomorrow")
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("Ambiguous TIME_REF 'tomorrow'"))
+        .stderr(predicate::str::contains(
+            "--as file: the existing file tomorrow.md",
+        ));
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["--as", "file", "tomorrow"])
+        .assert()
+        .success()
+        .stdout("tomorrow.md\n");
+    assert_eq!(
+        fs::read_to_string(temp.path().join("tomorrow.md")).unwrap(),
+        "# Plans\n"
+    );
+}
+
+#[test]
+fn test_note_name_of_another_mode_suggests_a_date() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("2025-01")
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("'2025-01' is a monthly note name"))
+        .stderr(predicate::str::contains(
+            "Nearest valid reference: 01-01-2025",
+        ));
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["--create", "--yes", "2025-01-15"])
+        .assert()
+        .success()
+        .stdout("2025-01-15.md\n");
+}
+
+#[test]
+fn test_unparseable_time_ref_lists_forms_and_nearest_example() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("yesterdy")
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains(
+            "Nearest valid reference: djour yesterday",
+        ))
+        .stderr(predicate::str::contains("DD-MM-YYYY"));
+}
+
+#[test]
+fn test_monthly_note_reports_covered_period() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd()
+        .arg("init")
+        .arg(temp.path())
+        .args(["--mode", "monthly"])
+        .assert()
+        .success();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["--create", "--yes", "01-02-2025"])
+        .assert()
+        .success()
+        .stdout("2025-02.md\n")
+        .stderr("2025-02.md (February 2025) — resolved from '01-02-2025' = 01-02-2025\n");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["--quiet", "--create", "--yes", "01-02-2025"])
+        .assert()
+        .success()
+        .stdout("2025-02.md\n")
+        .stderr("");
+}
+
+#[test]
+fn test_daily_note_prints_filename_only() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["--create", "--yes", "17-01-2025"])
+        .assert()
+        .success()
+        .stdout("2025-01-17.md\n")
+        .stderr("");
+}
+
+#[test]
+fn test_calendar_invalid_date_is_named() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["--create", "29-02-2025"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains(
+            "'29-02-2025' is day 29, month 2, year 2025, but February 2025 has only 28 days",
+        ))
+        .stderr(predicate::str::contains("Invalid time reference").not());
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["--create", "29-2025"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains(
+            "Invalid time reference: '29-2025'",
+        ));
+}
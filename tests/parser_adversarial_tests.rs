@@ -0,0 +1,185 @@
+//! Fuzz-style tests: the tag parser must finish adversarial notes quickly, with
+//! bounded allocation, and without panicking
+
+use djour::domain::tags::{TagParser, MAX_TAGS_PER_BLOCK};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Counts the bytes allocated by the current thread, so parallel tests do not mix
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATED: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATED.try_with(|total| total.set(total.get() + layout.size()));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATED.try_with(|total| total.set(total.get() + new_size));
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Generous for debug builds; the pathological cases used to take minutes
+const TIME_BUDGET: Duration = Duration::from_secs(20);
+
+/// Total bytes allocated while parsing: this multiple of the input size...
+const ALLOCATION_FACTOR: usize = 64;
+
+/// ...plus this much, for small notes with many blocks
+const ALLOCATION_SLACK: usize = 32 * 1024 * 1024;
+
+/// Parse `content`, checking the time and allocation budgets; returns the tags of each block
+fn parse_within_budget(name: &str, content: &str) -> Vec<Vec<String>> {
+    let before = ALLOCATED.with(Cell::get);
+    let started = Instant::now();
+    let blocks = TagParser::extract_from_markdown(content, Path::new("2025-01-15.md"), None);
+    let elapsed = started.elapsed();
+    let allocated = ALLOCATED.with(Cell::get) - before;
+
+    assert!(
+        elapsed < TIME_BUDGET,
+        "{}: parsing took {:?} (budget {:?})",
+        name,
+        elapsed,
+        TIME_BUDGET
+    );
+    let budget = ALLOCATION_FACTOR * content.len() + ALLOCATION_SLACK;
+    assert!(
+        allocated < budget,
+        "{}: parsing allocated {} bytes (budget {})",
+        name,
+        allocated,
+        budget
+    );
+    for block in &blocks {
+        assert!(block.tags.len() <= MAX_TAGS_PER_BLOCK, "{}", name);
+    }
+    blocks.into_iter().map(|block| block.tags).collect()
+}
+
+#[test]
+fn test_megabyte_line_of_repeated_tags() {
+    let content = "#a".repeat(1024 * 1024);
+    let blocks = parse_within_budget("repeated tag", &content);
+    assert_eq!(blocks, vec![vec!["a".to_string()]]);
+}
+
+#[test]
+fn test_thousands_of_distinct_tags_in_one_block_are_capped() {
+    let tags: String = (0..20_000).map(|n| format!("#t{} ", n)).collect();
+    let content = format!("Chat log: {}\n", tags);
+    let blocks = parse_within_budget("distinct tags", &content);
+    assert_eq!(blocks.len(), 1);
+    assert_eq!(blocks[0].len(), MAX_TAGS_PER_BLOCK);
+    assert_eq!(blocks[0][0], "t0");
+    assert_eq!(blocks[0][MAX_TAGS_PER_BLOCK - 1], "t255");
+}
+
+#[test]
+fn test_megabyte_paragraph() {
+    let content = format!("{} #work\n", "lorem ipsum ".repeat(80_000));
+    let blocks = parse_within_budget("big paragraph", &content);
+    assert_eq!(blocks, vec![vec!["work".to_string()]]);
+
+    // Tags past the scan limit are text, not tags
+    let late = format!("#early {} #late\n", "x".repeat(2 * 1024 * 1024));
+    let blocks = parse_within_budget("late tag", &late);
+    assert_eq!(blocks, vec![vec!["early".to_string()]]);
+}
+
+#[test]
+fn test_lists_nested_a_hundred_levels_deep() {
+    let content: String = (0..100)
+        .map(|depth| format!("{}- level {} #l{}\n", "  ".repeat(depth), depth, depth))
+        .collect();
+    let blocks = parse_within_budget("nested lists", &content);
+    assert!(!blocks.is_empty());
+    assert!(blocks.iter().all(|tags| !tags.is_empty()));
+}
+
+#[test]
+fn test_ten_thousand_sibling_headings() {
+    let content: String = (0..10_000)
+        .map(|n| format!("## Heading {} #h{}\n\nBody {}\n\n", n, n % 500, n))
+        .collect();
+    let blocks = parse_within_budget("sibling headings", &content);
+    assert!(blocks.len() >= 10_000);
+}
+
+#[test]
+fn test_deeply_nested_headings_and_quotes() {
+    let headings: String = (1..=6)
+        .cycle()
+        .take(6_000)
+        .enumerate()
+        .map(|(n, level)| format!("{} H{} #x{}\n", "#".repeat(level), n, n))
+        .collect();
+    parse_within_budget("heading ladder", &headings);
+
+    let quotes = format!("{} deep #q\n", ">".repeat(1_000));
+    parse_within_budget("nested quotes", &quotes);
+}
+
+#[test]
+fn test_pseudo_random_markdown_never_panics() {
+    const PIECES: &[&str] = &[
+        "#",
+        "#tag",
+        "#a/b",
+        " ",
+        "\n",
+        "\n\n",
+        "- ",
+        "  - ",
+        "1. ",
+        "> ",
+        "```",
+        "~~~",
+        "`",
+        "<!--",
+        "-->",
+        "[",
+        "](",
+        ")",
+        "**",
+        "_",
+        "## ",
+        "###### ",
+        "---\n",
+        "tags: [x]\n",
+        "|",
+        "\\",
+        "é",
+        "日本",
+        "\t",
+        "[ ] ",
+        "<div>",
+        "</div>",
+    ];
+    // xorshift, so failures reproduce
+    let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+    for round in 0..300 {
+        let mut content = String::new();
+        for _ in 0..400 {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            content.push_str(PIECES[(state % PIECES.len() as u64) as usize]);
+        }
+        parse_within_budget(&format!("random round {}", round), &content);
+    }
+}
@@ -0,0 +1,146 @@
+//! Integration tests for pin and unpin commands
+
+#![allow(deprecated)]
+
+use predicates::prelude::*;
+use std::fs;
+use tempfile::TempDir;
+
+mod common;
+use common::djour_cmd;
+
+/// A daily journal with notes for 13..=17 January 2025 and an undated goals note
+fn journal() -> TempDir {
+    let temp = TempDir::new().unwrap();
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+    for day in 13..=17 {
+        fs::write(temp.path().join(format!("2025-01-{}.md", day)), "").unwrap();
+    }
+    fs::write(temp.path().join("goals.md"), "# Q1 goals\n").unwrap();
+    temp
+}
+
+#[test]
+fn test_pin_lists_pinned_notes_first_outside_limit() {
+    let temp = journal();
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["pin", "goals"])
+        .assert()
+        .success()
+        .stdout("Pinned goals.md\n");
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["pin", "13-01-2025"])
+        .assert()
+        .success()
+        .stdout("Pinned 2025-01-13.md\n");
+
+    let config = fs::read_to_string(temp.path().join(".djour/config.toml")).unwrap();
+    assert!(config.contains("pinned = [\n    \"goals.md\",\n    \"2025-01-13.md\",\n]"));
+
+    // Pins do not count against --limit
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["list", "--limit", "2"])
+        .assert()
+        .success()
+        .stdout(
+            "*             goals.md       Q1 goals\n\
+             * 13-01-2025  2025-01-13.md\n\
+             17-01-2025  2025-01-17.md\n\
+             16-01-2025  2025-01-16.md\n",
+        );
+
+    // A pinned note in range is listed once, at the top
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["list", "--from", "13-01-2025", "--to", "14-01-2025"])
+        .assert()
+        .success()
+        .stdout(
+            "*             goals.md       Q1 goals\n\
+             * 13-01-2025  2025-01-13.md\n\
+             14-01-2025  2025-01-14.md\n",
+        );
+}
+
+#[test]
+fn test_pin_twice_and_unpin() {
+    let temp = journal();
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["pin", "goals.md"])
+        .assert()
+        .success();
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["pin", "goals"])
+        .assert()
+        .success()
+        .stdout("goals.md is already pinned\n");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["unpin", "goals"])
+        .assert()
+        .success()
+        .stdout("Unpinned goals.md\n");
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("pin")
+        .assert()
+        .success()
+        .stdout("No pinned notes\n");
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["unpin", "goals"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("'goals' is not pinned"));
+}
+
+#[test]
+fn test_pin_refuses_missing_note() {
+    let temp = journal();
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["pin", "01-02-2025"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("2025-02-01.md does not exist"));
+}
+
+#[test]
+fn test_missing_pinned_note_is_flagged() {
+    let temp = journal();
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["pin", "17-01-2025"])
+        .assert()
+        .success();
+    fs::remove_file(temp.path().join("2025-01-17.md")).unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("pin")
+        .assert()
+        .success()
+        .stdout("* 17-01-2025  2025-01-17.md  (missing)\n");
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["list", "--limit", "1"])
+        .assert()
+        .success()
+        .stdout(
+            "* 17-01-2025  2025-01-17.md  (missing)\n\
+             16-01-2025  2025-01-16.md\n",
+        );
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["unpin", "17-01-2025"])
+        .assert()
+        .success()
+        .stdout("Unpinned 2025-01-17.md\n");
+}
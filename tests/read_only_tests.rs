@@ -0,0 +1,148 @@
+//! Integration tests for read-only journals (`--read-only` and unwritable directories)
+
+#![allow(deprecated)]
+
+use predicates::prelude::*;
+use std::fs;
+use tempfile::TempDir;
+
+mod common;
+use common::djour_cmd;
+
+fn journal() -> TempDir {
+    let temp = TempDir::new().unwrap();
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+    fs::write(
+        temp.path().join("2025-01-15.md"),
+        "# Day\n\nShip it #work\n",
+    )
+    .unwrap();
+    temp
+}
+
+#[test]
+fn test_read_only_flag_refuses_compile_and_writes_nothing() {
+    let temp = journal();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["--read-only", "compile", "work"])
+        .assert()
+        .failure()
+        .code(1)
+        .stderr(predicate::str::contains("Cannot write"))
+        .stderr(predicate::str::contains("--read-only is set"))
+        .stderr(predicate::str::contains("djour compile <QUERY> --stdout"));
+
+    assert!(!temp.path().join(".compilations").exists());
+}
+
+#[test]
+fn test_compile_stdout_prints_without_writing() {
+    let temp = journal();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["--read-only", "compile", "work", "--stdout"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Ship it #work"));
+
+    assert!(!temp.path().join(".compilations").exists());
+}
+
+#[test]
+fn test_read_only_flag_refuses_note_creation_and_config_changes() {
+    let temp = journal();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["--read-only", "17-01-2025", "--create"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Cannot open"));
+    assert!(!temp.path().join("2025-01-17.md").exists());
+
+    let config = fs::read_to_string(temp.path().join(".djour/config.toml")).unwrap();
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["--read-only", "config", "editor", "nano"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Drop --read-only"));
+    assert_eq!(
+        fs::read_to_string(temp.path().join(".djour/config.toml")).unwrap(),
+        config
+    );
+
+    // Reading still works
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["--read-only", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("2025-01-15.md"));
+}
+
+#[cfg(unix)]
+mod unwritable {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::Path;
+
+    /// Make `dir` read-only; false when writes still succeed (e.g. running as root)
+    fn make_read_only(dir: &Path) -> bool {
+        fs::set_permissions(dir, fs::Permissions::from_mode(0o555)).unwrap();
+        let probe = dir.join(".probe");
+        if fs::write(&probe, "").is_ok() {
+            fs::remove_file(probe).unwrap();
+            restore(dir);
+            return false;
+        }
+        true
+    }
+
+    fn restore(dir: &Path) {
+        fs::set_permissions(dir, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    #[test]
+    fn test_compile_in_unwritable_journal_reports_permission_denied() {
+        let temp = journal();
+        if !make_read_only(temp.path()) {
+            return;
+        }
+
+        let assert = djour_cmd()
+            .current_dir(temp.path())
+            .args(["compile", "work"])
+            .assert();
+        restore(temp.path());
+
+        assert
+            .failure()
+            .code(1)
+            .stderr(predicate::str::contains("Cannot write"))
+            .stderr(predicate::str::contains("permission denied"))
+            .stderr(predicate::str::contains("Nothing was written"));
+        assert!(!temp.path().join(".compilations").exists());
+    }
+
+    #[test]
+    fn test_reading_unwritable_journal_still_works() {
+        let temp = journal();
+        if !make_read_only(temp.path()) {
+            return;
+        }
+
+        let assert = djour_cmd()
+            .current_dir(temp.path())
+            .args(["compile", "work", "--stdout"])
+            .assert();
+        restore(temp.path());
+
+        assert
+            .success()
+            .stdout(predicate::str::contains("Ship it #work"));
+    }
+}
@@ -0,0 +1,132 @@
+//! Integration tests for recurring entries (.djour/recurring.md)
+
+#![allow(deprecated)]
+
+use predicates::prelude::*;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+mod common;
+use common::djour_cmd;
+
+const DIRECTIVES: &str = "# Recurring entries\n\
+    @every 1st: - review budget #finance\n\
+    @every monday: - [ ] plan the week\n\
+    @every last-day: - close the month\n";
+
+fn init_with_directives(root: &Path, mode: &str, directives: &str) {
+    djour_cmd()
+        .arg("init")
+        .arg(root)
+        .args(["--mode", mode])
+        .assert()
+        .success();
+    fs::write(root.join(".djour/recurring.md"), directives).unwrap();
+}
+
+fn create(root: &Path, time_ref: &str) {
+    djour_cmd()
+        .current_dir(root)
+        .args(["--create", "--yes", time_ref])
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_daily_notes_get_matching_entries() {
+    let temp = TempDir::new().unwrap();
+    init_with_directives(temp.path(), "daily", DIRECTIVES);
+
+    // Monday 1 February 2027
+    create(temp.path(), "01-02-2027");
+    let note = fs::read_to_string(temp.path().join("2027-02-01.md")).unwrap();
+    assert!(note.contains("- review budget #finance\n- [ ] plan the week\n"));
+    assert!(!note.contains("close the month"));
+
+    // Sunday 28 February 2027 is the last day of February
+    create(temp.path(), "28-02-2027");
+    let note = fs::read_to_string(temp.path().join("2027-02-28.md")).unwrap();
+    assert!(note.contains("- close the month"));
+    assert!(!note.contains("review budget"));
+    assert!(!note.contains("plan the week"));
+
+    // Wednesday 17 February 2027 matches nothing
+    create(temp.path(), "17-02-2027");
+    let note = fs::read_to_string(temp.path().join("2027-02-17.md")).unwrap();
+    assert!(!note.contains("- "));
+}
+
+#[test]
+fn test_existing_notes_never_get_entries_again() {
+    let temp = TempDir::new().unwrap();
+    init_with_directives(temp.path(), "daily", DIRECTIVES);
+
+    create(temp.path(), "01-02-2027");
+    let path = temp.path().join("2027-02-01.md");
+    fs::write(&path, "# Edited\n").unwrap();
+    create(temp.path(), "01-02-2027");
+    assert_eq!(fs::read_to_string(&path).unwrap(), "# Edited\n");
+}
+
+#[test]
+fn test_weekly_note_gets_entries_under_each_day() {
+    let temp = TempDir::new().unwrap();
+    init_with_directives(temp.path(), "weekly", DIRECTIVES);
+
+    // Week of Monday 1 February 2027
+    create(temp.path(), "03-02-2027");
+    let note = fs::read_to_string(temp.path().join("2027-W05-2027-02-01.md")).unwrap();
+    let monday = note.find("## Monday").unwrap();
+    let tuesday = note.find("## Tuesday").unwrap();
+    let budget = note.find("- review budget").unwrap();
+    let plan = note.find("- [ ] plan the week").unwrap();
+    assert!(monday < budget && budget < plan && plan < tuesday);
+    assert!(!note.contains("close the month"));
+}
+
+#[test]
+fn test_invalid_directives_block_creation() {
+    let temp = TempDir::new().unwrap();
+    init_with_directives(temp.path(), "daily", "@every 2st: - typo\n");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["--create", "today"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("line 1: Invalid rule: '2st'"))
+        .stderr(predicate::str::contains("djour recurring list"));
+    let today = chrono::Local::now().format("%Y-%m-%d.md").to_string();
+    assert!(!temp.path().join(today).exists());
+}
+
+#[test]
+fn test_recurring_list_validates_and_prints_rules() {
+    let temp = TempDir::new().unwrap();
+    init_with_directives(temp.path(), "daily", DIRECTIVES);
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["recurring", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1st       next "))
+        .stdout(predicate::str::contains("last-day  next "))
+        .stdout(predicate::str::contains("  - [ ] plan the week\n"));
+
+    fs::write(
+        temp.path().join(".djour/recurring.md"),
+        "@every monday: - ok\n@every fortnight: - bad\n",
+    )
+    .unwrap();
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["recurring", "list"])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("monday  next "))
+        .stderr(predicate::str::contains(
+            ".djour/recurring.md: line 2: Invalid rule: 'fortnight'",
+        ));
+}
@@ -0,0 +1,112 @@
+//! Integration tests for remind command
+
+#![allow(deprecated)]
+
+use chrono::{Duration, NaiveDate};
+use predicates::prelude::*;
+use std::fs;
+use tempfile::TempDir;
+
+mod common;
+use common::djour_cmd;
+
+fn today() -> NaiveDate {
+    chrono::Local::now().date_naive()
+}
+
+fn due(date: NaiveDate) -> String {
+    date.format("%d-%m-%Y").to_string()
+}
+
+fn init_journal() -> TempDir {
+    let temp = TempDir::new().unwrap();
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+    temp
+}
+
+#[test]
+fn test_remind_without_reminders_succeeds() {
+    let temp = init_journal();
+    fs::write(
+        temp.path().join("2025-01-15.md"),
+        "# Day\n\nNothing due #work\n",
+    )
+    .unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("remind")
+        .assert()
+        .code(0)
+        .stdout("No reminders due\n");
+}
+
+#[test]
+fn test_remind_lists_items_due_today_but_not_later_ones() {
+    let temp = init_journal();
+    fs::write(
+        temp.path().join("2025-01-15.md"),
+        format!(
+            "- call dentist #remind #{}\n- book flights #remind:{}\n",
+            due(today()),
+            due(today() + Duration::days(2))
+        ),
+    )
+    .unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("remind")
+        .assert()
+        .code(0)
+        .stdout(format!(
+            "call dentist ({}, due today) [2025-01-15.md:1]\n",
+            due(today())
+        ));
+}
+
+#[test]
+fn test_remind_exits_with_distinct_code_when_overdue() {
+    let temp = init_journal();
+    fs::write(
+        temp.path().join("2025-01-15.md"),
+        format!(
+            "- pay rent #remind #{}\n- [x] done already #remind #{}\n- water plants #remind\n",
+            due(today() - Duration::days(3)),
+            due(today() - Duration::days(9))
+        ),
+    )
+    .unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("remind")
+        .assert()
+        .code(5)
+        .stdout(predicate::str::contains("pay rent ("))
+        .stdout(predicate::str::contains("3 days overdue"))
+        .stdout(predicate::str::contains("done already").not())
+        .stderr(predicate::str::contains(
+            "2025-01-15.md: line 3: #remind without a #DD-MM-YYYY due date",
+        ));
+}
+
+#[cfg(not(feature = "notifications"))]
+#[test]
+fn test_remind_notify_needs_the_notifications_feature() {
+    let temp = init_journal();
+    fs::write(
+        temp.path().join("2025-01-15.md"),
+        format!("- call dentist #remind #{}\n", due(today())),
+    )
+    .unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["remind", "--notify"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains(
+            "built without the notifications feature",
+        ));
+}
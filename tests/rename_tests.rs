@@ -0,0 +1,135 @@
+//! Integration tests for the rename command
+
+#![allow(deprecated)]
+
+use predicates::prelude::*;
+use std::fs;
+use tempfile::TempDir;
+
+mod common;
+use common::djour_cmd;
+
+/// Daily journal where 2025-01-18.md is linked from the root and from projects/
+fn linked_journal() -> TempDir {
+    let temp = TempDir::new().unwrap();
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+    fs::write(temp.path().join("2025-01-18.md"), "# Standup\n").unwrap();
+    fs::write(
+        temp.path().join("2025-01-20.md"),
+        "Follow-up to [standup](2025-01-18.md#blockers).\n\n[ref]: ./2025-01-18.md?raw\n",
+    )
+    .unwrap();
+    fs::create_dir_all(temp.path().join("projects")).unwrap();
+    fs::write(
+        temp.path().join("projects/alpha.md"),
+        "Kickoff: [notes](../2025-01-18.md)\n\n<a href=\"../2025-01-18.md\">raw</a>\n",
+    )
+    .unwrap();
+    temp
+}
+
+#[test]
+fn test_rename_moves_note_and_updates_links() {
+    let temp = linked_journal();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["rename", "18-01-2025", "17-01-2025"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Renamed 2025-01-18.md -> 2025-01-17.md; updated 4 link(s) in 2 file(s).",
+        ))
+        .stdout(predicate::str::contains("projects/alpha.md (2)"));
+
+    assert!(!temp.path().join("2025-01-18.md").exists());
+    assert_eq!(
+        fs::read_to_string(temp.path().join("2025-01-17.md")).unwrap(),
+        "# Standup\n"
+    );
+    assert_eq!(
+        fs::read_to_string(temp.path().join("2025-01-20.md")).unwrap(),
+        "Follow-up to [standup](2025-01-17.md#blockers).\n\n[ref]: 2025-01-17.md?raw\n"
+    );
+    assert_eq!(
+        fs::read_to_string(temp.path().join("projects/alpha.md")).unwrap(),
+        "Kickoff: [notes](../2025-01-17.md)\n\n<a href=\"../2025-01-17.md\">raw</a>\n"
+    );
+}
+
+#[test]
+fn test_rename_dry_run_changes_nothing() {
+    let temp = linked_journal();
+    let before = fs::read_to_string(temp.path().join("projects/alpha.md")).unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["rename", "2025-01-18.md", "2025-01-17.md", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Would rename 2025-01-18.md -> 2025-01-17.md; would update 4 link(s) in 2 file(s).",
+        ));
+
+    assert!(temp.path().join("2025-01-18.md").exists());
+    assert!(!temp.path().join("2025-01-17.md").exists());
+    assert_eq!(
+        fs::read_to_string(temp.path().join("projects/alpha.md")).unwrap(),
+        before
+    );
+}
+
+#[test]
+fn test_rename_refuses_existing_destination() {
+    let temp = linked_journal();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["rename", "2025-01-18.md", "2025-01-20.md"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("Destination already exists"));
+
+    assert!(temp.path().join("2025-01-18.md").exists());
+    assert!(fs::read_to_string(temp.path().join("2025-01-20.md"))
+        .unwrap()
+        .contains("2025-01-18.md#blockers"));
+}
+
+#[test]
+fn test_rename_updates_html_img_src() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+    fs::write(temp.path().join("diagram.png"), "png").unwrap();
+    fs::write(
+        temp.path().join("2025-01-15.md"),
+        "<img src=\"diagram.png\" width=\"200\"> and ![again](diagram.png)\n",
+    )
+    .unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["rename", "diagram.png", "images/diagram.png"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("updated 2 link(s) in 1 file(s)"));
+
+    assert!(temp.path().join("images/diagram.png").is_file());
+    assert_eq!(
+        fs::read_to_string(temp.path().join("2025-01-15.md")).unwrap(),
+        "<img src=\"images/diagram.png\" width=\"200\"> and ![again](images/diagram.png)\n"
+    );
+}
+
+#[test]
+fn test_rename_missing_note_fails() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["rename", "2025-01-18.md", "2025-01-17.md"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("Note does not exist"));
+}
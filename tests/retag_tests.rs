@@ -177,3 +177,277 @@ fn test_retag_invalid_tag_fails() {
         .failure()
         .stderr(predicate::str::contains("Invalid tag"));
 }
+
+fn write_compilation_and_saved_query(temp: &TempDir) {
+    fs::write(temp.path().join("2025-01-15.md"), "Task #work").unwrap();
+
+    let compilations = temp.path().join(".compilations");
+    fs::create_dir_all(&compilations).unwrap();
+    fs::write(
+        compilations.join("work.md"),
+        "# Compilation: #work\n\nTask #work",
+    )
+    .unwrap();
+
+    let config_path = temp.path().join(".djour").join("config.toml");
+    let mut config = fs::read_to_string(&config_path).unwrap();
+    config.push_str("\n[queries]\nstandup = \"work AND NOT meeting\"\n");
+    fs::write(&config_path, config).unwrap();
+}
+
+#[test]
+fn test_retag_leaves_compilations_and_queries_by_default() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    write_compilation_and_saved_query(&temp);
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("retag")
+        .arg("work")
+        .arg("job")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Compilations:").not())
+        .stdout(predicate::str::contains("Saved queries:").not());
+
+    let compiled = fs::read_to_string(temp.path().join(".compilations/work.md")).unwrap();
+    let config = fs::read_to_string(temp.path().join(".djour/config.toml")).unwrap();
+    assert!(compiled.contains("Task #work"));
+    assert!(config.contains("standup = \"work AND NOT meeting\""));
+}
+
+#[test]
+fn test_retag_updates_compilations_and_queries_when_requested() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    write_compilation_and_saved_query(&temp);
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("retag")
+        .arg("work")
+        .arg("job")
+        .arg("--update-compilations")
+        .arg("--update-queries")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Compilations: 1 file(s) updated."))
+        .stdout(predicate::str::contains(".compilations/work.md (2)"))
+        .stdout(predicate::str::contains(
+            "Saved queries: 1 query(ies) updated.",
+        ))
+        .stdout(predicate::str::contains(
+            "standup: work AND NOT meeting -> job AND NOT meeting",
+        ));
+
+    let compiled = fs::read_to_string(temp.path().join(".compilations/work.md")).unwrap();
+    let config = fs::read_to_string(temp.path().join(".djour/config.toml")).unwrap();
+    assert_eq!(compiled, "# Compilation: #job\n\nTask #job");
+    assert!(config.contains("standup = \"job AND NOT meeting\""));
+}
+
+#[test]
+fn test_retag_dry_run_reports_compilations_and_queries_without_writing() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    write_compilation_and_saved_query(&temp);
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("retag")
+        .arg("work")
+        .arg("job")
+        .arg("--dry-run")
+        .arg("--update-compilations")
+        .arg("--update-queries")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Compilations: 1 file(s) would be updated.",
+        ))
+        .stdout(predicate::str::contains(
+            "Saved queries: 1 query(ies) would be updated.",
+        ));
+
+    let compiled = fs::read_to_string(temp.path().join(".compilations/work.md")).unwrap();
+    let config = fs::read_to_string(temp.path().join(".djour/config.toml")).unwrap();
+    assert!(compiled.contains("Task #work"));
+    assert!(config.contains("standup = \"work AND NOT meeting\""));
+}
+
+#[test]
+fn test_retag_renames_entire_subtree() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+
+    fs::write(
+        temp.path().join("2025-01-15.md"),
+        "#project/alpha #project/alpha/backend #project/alphabet #project/beta",
+    )
+    .unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["retag", "project/alpha", "#project/omega"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Updated 1 file(s) with 2 replacement(s).",
+        ));
+
+    let content = fs::read_to_string(temp.path().join("2025-01-15.md")).unwrap();
+    assert_eq!(
+        content,
+        "#project/omega #project/omega/backend #project/alphabet #project/beta"
+    );
+}
+
+fn write_spelling_variants(temp: &TempDir) {
+    fs::write(
+        temp.path().join("2025-01-15.md"),
+        "#one-on-one with Sam #one-on-one #re-sign",
+    )
+    .unwrap();
+    fs::write(
+        temp.path().join("2025-01-16.md"),
+        "#one_on_one then #OneOnOne and #one-on-one #resign #resign",
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_tags_normalize_groups_spellings() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    write_spelling_variants(&temp);
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["tags", "--normalize"])
+        .assert()
+        .success()
+        .stdout("#one-on-one (3) [also: one_on_one (1), oneonone (1)]\n#resign (2) [also: re-sign (1)]\n");
+}
+
+#[test]
+fn test_retag_auto_normalize_requires_dry_run_or_yes() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    write_spelling_variants(&temp);
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["retag", "--auto-normalize"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("--dry-run"));
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["retag", "--auto-normalize", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("#one_on_one -> #one-on-one"))
+        .stdout(predicate::str::contains("#oneonone -> #one-on-one"))
+        .stdout(predicate::str::contains("#re-sign -> #resign"))
+        .stdout(predicate::str::contains(
+            "Dry run: 2 file(s) would be updated with 3 replacement(s).",
+        ));
+
+    let content = fs::read_to_string(temp.path().join("2025-01-16.md")).unwrap();
+    assert!(content.contains("#one_on_one"));
+}
+
+#[test]
+fn test_retag_auto_normalize_applies_with_yes_and_respects_exempt_list() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    write_spelling_variants(&temp);
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "normalize_exempt", "re-sign"])
+        .assert()
+        .success();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["retag", "--auto-normalize", "--yes"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Spelling variants: 2 rename(s)."));
+
+    assert_eq!(
+        fs::read_to_string(temp.path().join("2025-01-15.md")).unwrap(),
+        "#one-on-one with Sam #one-on-one #re-sign"
+    );
+    assert_eq!(
+        fs::read_to_string(temp.path().join("2025-01-16.md")).unwrap(),
+        "#one-on-one then #one-on-one and #one-on-one #resign #resign"
+    );
+}
+
+#[test]
+fn test_retag_runs_in_the_same_second_keep_separate_backups() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    fs::write(temp.path().join("2025-01-15.md"), "Standup #work\n").unwrap();
+
+    for (from, to) in [("work", "project"), ("project", "client")] {
+        djour_cmd()
+            .current_dir(temp.path())
+            .args(["retag", from, to])
+            .assert()
+            .success();
+    }
+
+    let mut backups: Vec<_> = fs::read_dir(temp.path().join(".djour/archive"))
+        .unwrap()
+        .map(|e| fs::read_to_string(e.unwrap().path().join("2025-01-15.md")).unwrap())
+        .collect();
+    backups.sort();
+    assert_eq!(backups, ["Standup #project\n", "Standup #work\n"]);
+}
+
+#[test]
+fn test_retag_backs_up_originals_unless_no_backup() {
+    let temp = TempDir::new().unwrap();
+    init_journal(&temp);
+    fs::write(temp.path().join("2025-01-15.md"), "Standup #work\n").unwrap();
+    fs::write(temp.path().join("2025-01-16.md"), "Nothing tagged\n").unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["retag", "work", "project"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Originals backed up in .djour/archive/retag-",
+        ));
+
+    let archive = temp.path().join(".djour/archive");
+    let runs: Vec<_> = fs::read_dir(&archive)
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .collect();
+    assert_eq!(runs.len(), 1);
+    assert_eq!(
+        fs::read_to_string(runs[0].join("2025-01-15.md")).unwrap(),
+        "Standup #work\n"
+    );
+    assert!(!runs[0].join("2025-01-16.md").exists());
+
+    fs::remove_dir_all(&archive).unwrap();
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["retag", "project", "focus", "--no-backup"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("backed up").not());
+    assert!(!archive.exists());
+    assert_eq!(
+        fs::read_to_string(temp.path().join("2025-01-15.md")).unwrap(),
+        "Standup #focus\n"
+    );
+}
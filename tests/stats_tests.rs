@@ -0,0 +1,109 @@
+//! Integration tests for stats command
+
+#![allow(deprecated)]
+
+use predicates::prelude::*;
+use std::fs;
+use tempfile::TempDir;
+
+mod common;
+use common::djour_cmd;
+
+fn journal_with_notes() -> TempDir {
+    let temp = TempDir::new().unwrap();
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+    fs::write(temp.path().join("2025-01-15.md"), "#work #health #work\n").unwrap();
+    fs::write(temp.path().join("2025-01-16.md"), "#work #reading #focus\n").unwrap();
+    temp
+}
+
+#[test]
+fn test_stats_prints_counts_and_top_tags() {
+    let temp = journal_with_notes();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("stats")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Notes: 2\n"))
+        .stdout(predicate::str::contains(
+            "Top tags: #work (3), #focus (1), #health (1)\n",
+        ));
+}
+
+#[test]
+fn test_stats_badge_markdown() {
+    let temp = journal_with_notes();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["stats", "--badge-markdown"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "![notes: 2](https://img.shields.io/static/v1?label=notes&message=2&color=blue)\n",
+        ));
+}
+
+#[test]
+fn test_stats_write_badge_is_idempotent_and_keeps_the_rest() {
+    let temp = journal_with_notes();
+    let readme = temp.path().join("README.md");
+    let before = "# My journal\r\n\r\nPrivate notes.  \r\n";
+    fs::write(&readme, before).unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["stats", "--write-badge", "README.md"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Updated stats in README.md"));
+    let first = fs::read_to_string(&readme).unwrap();
+    assert!(first.starts_with(before), "{first}");
+    assert!(
+        first.contains("\r\n<!-- djour-stats:start -->\r\n![notes: 2]("),
+        "{first}"
+    );
+    assert!(first.ends_with("<!-- djour-stats:end -->\r\n"), "{first}");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["stats", "--write-badge", "README.md"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("up to date"));
+    assert_eq!(fs::read_to_string(&readme).unwrap(), first);
+
+    // Text after the block stays where it is when the stats change
+    let edited = format!("{}Footer\r\n", first);
+    fs::write(&readme, &edited).unwrap();
+    fs::write(temp.path().join("2025-01-17.md"), "#work\n").unwrap();
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["stats", "--write-badge", "README.md"])
+        .assert()
+        .success();
+    let updated = fs::read_to_string(&readme).unwrap();
+    assert!(updated.contains("![notes: 3]("), "{updated}");
+    assert!(updated.starts_with(before), "{updated}");
+    assert!(updated.ends_with("<!-- djour-stats:end -->\r\nFooter\r\n"));
+}
+
+#[test]
+fn test_stats_write_badge_refuses_an_orphan_start_marker() {
+    let temp = journal_with_notes();
+    let readme = temp.path().join("README.md");
+    let before = "# My journal\n\n<!-- djour-stats:start -->\nKeep this paragraph\n";
+    fs::write(&readme, before).unwrap();
+
+    for _ in 0..2 {
+        djour_cmd()
+            .current_dir(temp.path())
+            .args(["stats", "--write-badge", "README.md"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("found 1 and 0"));
+        assert_eq!(fs::read_to_string(&readme).unwrap(), before);
+    }
+}
@@ -173,3 +173,505 @@ fn test_tags_recursive_includes_nested_notes_and_skips_dot_dirs() {
         .stdout(predicate::str::contains("#nested"))
         .stdout(predicate::str::contains("#hidden").not());
 }
+
+#[test]
+fn test_tags_renders_hierarchy_with_rolled_up_counts() {
+    let temp = TempDir::new().unwrap();
+
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+
+    fs::write(
+        temp.path().join("2025-01-15.md"),
+        "Planning #project/alpha and #project/alpha/backend #work",
+    )
+    .unwrap();
+    fs::write(
+        temp.path().join("2025-01-16.md"),
+        "Review #project/alpha/backend #project",
+    )
+    .unwrap();
+
+    let output = djour_cmd()
+        .current_dir(temp.path())
+        .arg("tags")
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(
+        lines,
+        vec![
+            "#project",
+            "#project/alpha",
+            "#project/alpha/backend",
+            "#work"
+        ]
+    );
+
+    let output = djour_cmd()
+        .current_dir(temp.path())
+        .env("CLICOLOR_FORCE", "1")
+        .args(["tags", "--no-color"])
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let rows: Vec<&str> = stdout
+        .lines()
+        .map(|line| line.trim_end_matches(['\u{2588}', ' ']))
+        .collect();
+    assert_eq!(
+        rows,
+        vec![
+            "#project     4",
+            "  alpha      3",
+            "    backend  2",
+            "#work        1"
+        ]
+    );
+}
+
+#[test]
+fn test_tags_piped_output_keeps_flat_tags_one_per_line() {
+    let temp = TempDir::new().unwrap();
+
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+
+    fs::write(
+        temp.path().join("2025-01-15.md"),
+        "Standup #work\n\nPlanning #project/alpha #work\n",
+    )
+    .unwrap();
+    fs::write(temp.path().join("2025-01-16.md"), "Review #work #home\n").unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("tags")
+        .assert()
+        .success()
+        .stdout("#home\n#project/alpha\n#work\n");
+}
+
+#[test]
+fn test_tags_hierarchy_can_be_disabled() {
+    let temp = TempDir::new().unwrap();
+
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "hierarchical_tags", "false"])
+        .assert()
+        .success();
+
+    fs::write(temp.path().join("2025-01-15.md"), "Planning #project/alpha").unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("tags")
+        .assert()
+        .success()
+        .stdout("#project\n");
+}
+
+#[test]
+fn test_tags_date_range_selects_weekly_notes_by_week_window() {
+    let temp = TempDir::new().unwrap();
+
+    djour_cmd()
+        .arg("init")
+        .arg(temp.path())
+        .args(["--mode", "weekly"])
+        .assert()
+        .success();
+
+    fs::write(temp.path().join("2025-W02-2025-01-06.md"), "Earlier #w02").unwrap();
+    fs::write(temp.path().join("2025-W03-2025-01-13.md"), "Thursday #w03").unwrap();
+    fs::write(temp.path().join("2025-W04-2025-01-20.md"), "Later #w04").unwrap();
+
+    // Range starts mid-week: week 03 overlaps it.
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["tags", "--from", "16-01-2025", "--to", "17-01-2025"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("#w03"))
+        .stdout(predicate::str::contains("#w02").not())
+        .stdout(predicate::str::contains("#w04").not());
+
+    // Range ends the Sunday before week 03.
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["tags", "--to", "12-01-2025"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("#w02"))
+        .stdout(predicate::str::contains("#w03").not());
+}
+
+#[test]
+fn test_tags_forced_color_right_aligns_counts() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+    fs::write(
+        temp.path().join("2025-01-15.md"),
+        "#work #work #work #work #work #work #work #work #work #work #home",
+    )
+    .unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .env("CLICOLOR_FORCE", "1")
+        .args(["tags", "--no-color"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("#home   1  \u{2588}\u{2588}\n"))
+        .stdout(predicate::str::contains("#work  10  "));
+}
+
+#[test]
+fn test_tag_footer_does_not_change_tag_counts() {
+    let temp = TempDir::new().unwrap();
+
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "auto_tag_footer", "true"])
+        .assert()
+        .success();
+
+    fs::write(
+        temp.path().join("2025-01-15.md"),
+        "# Day\n\nRun #health, then #work and more #work\n",
+    )
+    .unwrap();
+    fs::write(temp.path().join("2025-01-16.md"), "Untouched #work\n").unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["retag", "work", "job"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Updated 2 file(s) with 3 replacement(s).",
+        ));
+
+    let content = fs::read_to_string(temp.path().join("2025-01-15.md")).unwrap();
+    assert_eq!(
+        content,
+        "# Day\n\nRun #health, then #job and more #job\n\n<!-- djour:tags -->\nTags in this note: #health #job <!-- /djour:tags -->\n"
+    );
+
+    // Retagging again rewrites the footer in place instead of adding another
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["retag", "health", "fitness"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Updated 1 file(s) with 1 replacement(s).",
+        ));
+    let content = fs::read_to_string(temp.path().join("2025-01-15.md")).unwrap();
+    assert_eq!(content.matches("<!-- djour:tags -->").count(), 1);
+    assert!(content.contains("Tags in this note: #fitness #job <!-- /djour:tags -->"));
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["tags", "--normalize"])
+        .assert()
+        .success()
+        .stdout("#fitness (1)\n#job (3)\n");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "job"])
+        .assert()
+        .success();
+    let compiled = fs::read_to_string(temp.path().join(".compilations/job.md")).unwrap();
+    assert!(compiled.contains("then #job and more #job"), "{compiled}");
+    assert!(!compiled.contains("Tags in this note"), "{compiled}");
+}
+
+#[test]
+fn test_tag_footer_not_added_to_untouched_notes() {
+    let temp = TempDir::new().unwrap();
+
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "auto_tag_footer", "true"])
+        .assert()
+        .success();
+    fs::write(temp.path().join("2025-01-15.md"), "Plain #work\n").unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["retag", "missing", "other"])
+        .assert()
+        .success();
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["tags", "--normalize"])
+        .assert()
+        .success()
+        .stdout("#work (1)\n");
+
+    assert_eq!(
+        fs::read_to_string(temp.path().join("2025-01-15.md")).unwrap(),
+        "Plain #work\n"
+    );
+}
+
+#[test]
+fn test_tags_exclude_globs() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+    fs::write(
+        temp.path().join("2025-01-01.md"),
+        "Plans #work #health #home\n",
+    )
+    .unwrap();
+    fs::write(temp.path().join("2025-01-15.md"), "Standup #work\n").unwrap();
+    fs::create_dir(temp.path().join("drafts")).unwrap();
+    fs::write(temp.path().join("drafts/2025-01-16.md"), "Idea #draft\n").unwrap();
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "compile_exclude", "drafts/**"])
+        .assert()
+        .success();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["tags", "--recursive", "--exclude", "2025-01-01.md"])
+        .assert()
+        .success()
+        .stdout("#work\n")
+        .stderr(predicate::str::contains("excluded 2 files"));
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["tags", "--normalize", "--quiet", "--exclude", "*-01-01.md"])
+        .assert()
+        .success()
+        .stdout("#work (1)\n")
+        .stderr("");
+}
+
+#[test]
+fn test_tags_counts_match_compile_for_tag_only_paragraph_lists() {
+    let temp = TempDir::new().unwrap();
+
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+    fs::write(
+        temp.path().join("2025-01-15.md"),
+        "#errand\n\n- buy milk\n- post letter\n  - stamps\n\nCall the bank #errand\n\n- not an errand\n",
+    )
+    .unwrap();
+
+    let tags = djour_cmd()
+        .current_dir(temp.path())
+        .args(["tags", "--normalize"])
+        .output()
+        .unwrap();
+    let tags = String::from_utf8(tags.stdout).unwrap();
+    assert_eq!(tags, "#errand (4)\n");
+
+    let compile = djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "errand"])
+        .output()
+        .unwrap();
+    let compile = String::from_utf8(compile.stderr).unwrap();
+    assert!(
+        compile.contains("Compiled 4 blocks from 1 note"),
+        "{}",
+        compile
+    );
+    let content = fs::read_to_string(temp.path().join(".compilations/errand.md")).unwrap();
+    assert!(!content.contains("not an errand"), "{}", content);
+}
+
+#[test]
+fn test_tags_csv_export() {
+    let temp = TempDir::new().unwrap();
+
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+    fs::write(temp.path().join("2025-01-10.md"), "Plan #work #ops\n").unwrap();
+    fs::write(temp.path().join("2025-01-15.md"), "Ship #work and #work\n").unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["tags", "--format", "csv"])
+        .assert()
+        .success()
+        .stdout(
+            "tag,count,first_date,last_date\r\n\
+             ops,1,2025-01-10,2025-01-10\r\n\
+             work,3,2025-01-10,2025-01-15\r\n",
+        );
+
+    // The default output is unchanged; an unknown format is an error
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("tags")
+        .assert()
+        .success()
+        .stdout("#ops\n#work\n");
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["tags", "--format", "xlsx"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains(
+            "Invalid format: 'xlsx'. Use 'text' or 'csv'",
+        ));
+}
+
+#[test]
+fn test_tags_warns_about_unclosed_code_fence() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+    fs::write(
+        temp.path().join("2025-02-10.md"),
+        "Walked #health\n\n~~~\nnotes\n",
+    )
+    .unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("tags")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("#health"))
+        .stderr(predicate::str::contains(
+            "warning: 2025-02-10.md: unclosed code fence opened at line 3; \
+             content after it is treated as code",
+        ));
+}
+
+#[test]
+fn test_tags_groups_value_tags_under_their_name() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+    fs::write(
+        temp.path().join("2025-01-15.md"),
+        "Ran #run:5k, felt #mood:good\n",
+    )
+    .unwrap();
+    fs::write(
+        temp.path().join("2025-01-16.md"),
+        "Long one #run:10k\n\nShort one #run:5k\n",
+    )
+    .unwrap();
+
+    // Off by default: the value is not part of the tag
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("tags")
+        .assert()
+        .success()
+        .stdout("#mood\n#run\n");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "value_tags", "true"])
+        .assert()
+        .success();
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("tags")
+        .assert()
+        .success()
+        .stdout("#mood:good\n#run:10k\n#run:5k\n");
+    djour_cmd()
+        .current_dir(temp.path())
+        .env("CLICOLOR_FORCE", "1")
+        .args(["tags", "--no-color"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("5k (2), 10k (1)"));
+}
+
+#[test]
+fn test_pasted_characters_give_the_same_tags_everywhere() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+    fs::write(
+        temp.path().join("2025-01-15.md"),
+        "Typed on the phone \u{FF03}work\n\nPasted\u{00A0}#work\u{2011}item from a doc\n",
+    )
+    .unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("tags")
+        .assert()
+        .success()
+        .stdout("#work\n#work-item\n");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "work-item", "--stdout"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Pasted\u{00A0}#work\u{2011}item from a doc",
+        ))
+        .stdout(predicate::str::contains("Typed on the phone").not());
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["retag", "work", "job"])
+        .assert()
+        .success();
+    assert_eq!(
+        fs::read_to_string(temp.path().join("2025-01-15.md")).unwrap(),
+        "Typed on the phone #job\n\nPasted\u{00A0}#work\u{2011}item from a doc\n"
+    );
+}
+
+#[test]
+fn test_tags_lists_numeric_and_glued_tags() {
+    let temp = TempDir::new().unwrap();
+
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+    fs::write(
+        temp.path().join("2025-01-15.md"),
+        "## Bug #1234 triage #work\n\nPorted to C#sharp.\n",
+    )
+    .unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("tags")
+        .assert()
+        .success()
+        .stdout("#1234\n#sharp\n#work\n");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["compile", "1234", "--include-context"])
+        .assert()
+        .success();
+    let compiled = fs::read_to_string(temp.path().join(".compilations/1234.md")).unwrap();
+    assert!(compiled.contains("Bug #1234 triage"), "{compiled}");
+}
+
+#[test]
+fn test_tags_counts_tags_after_a_comment_opener_in_code() {
+    let temp = TempDir::new().unwrap();
+
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+    fs::write(
+        temp.path().join("2025-01-15.md"),
+        "Use `<!--` to open a comment #work\n\n## Later #home\n\n<!-- #hidden -->\n",
+    )
+    .unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("tags")
+        .assert()
+        .success()
+        .stdout("#home\n#work\n");
+}
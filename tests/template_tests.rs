@@ -0,0 +1,89 @@
+//! Integration tests for template checks (djour template check)
+
+#![allow(deprecated)]
+
+use predicates::prelude::*;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+mod common;
+use common::djour_cmd;
+
+fn init_with_template(root: &Path, name: &str, content: &str) {
+    djour_cmd().arg("init").arg(root).assert().success();
+    let templates = root.join(".djour/templates");
+    fs::create_dir_all(&templates).unwrap();
+    fs::write(templates.join(name), content).unwrap();
+}
+
+#[test]
+fn test_template_check_reports_issues() {
+    let temp = TempDir::new().unwrap();
+    init_with_template(temp.path(), "daily.md", "# {DATE} {WEEKDAY}\n\n{DATE\n");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["template", "check"])
+        .assert()
+        .code(1)
+        .stdout(
+            ".djour/templates/daily.md: line 1: unknown placeholder {WEEKDAY}\n\
+             .djour/templates/daily.md: line 3: '{' is never closed\n",
+        )
+        .stderr(predicate::str::contains("2 issue(s) found"));
+}
+
+#[test]
+fn test_template_check_named_builtin_is_ok() {
+    let temp = TempDir::new().unwrap();
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["template", "check"])
+        .assert()
+        .success()
+        .stdout("No custom templates in .djour/templates\n");
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["template", "check", "weekly"])
+        .assert()
+        .success()
+        .stdout("weekly.md (built-in): ok\n");
+}
+
+#[test]
+fn test_new_note_warns_about_template_issues_once() {
+    let temp = TempDir::new().unwrap();
+    init_with_template(temp.path(), "daily.md", "# {DATE} {WEEKDAY}\n");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["--create", "--yes", "17-01-2025"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "warning: .djour/templates/daily.md: line 1: unknown placeholder {WEEKDAY}",
+        ));
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["--create", "--yes", "18-01-2025"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("WEEKDAY").not());
+}
+
+#[test]
+fn test_new_note_hint_is_hidden_by_quiet() {
+    let temp = TempDir::new().unwrap();
+    init_with_template(temp.path(), "daily.md", "# {DATE} {WEEKDAY}\n");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["--quiet", "--create", "--yes", "17-01-2025"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("unknown placeholder {WEEKDAY}"))
+        .stderr(predicate::str::contains("djour template check").not());
+}
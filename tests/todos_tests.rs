@@ -0,0 +1,127 @@
+//! Integration tests for todos command
+
+#![allow(deprecated)]
+
+use std::fs;
+use tempfile::TempDir;
+
+mod common;
+use common::djour_cmd;
+
+fn journal_with_tasks() -> TempDir {
+    let temp = TempDir::new().unwrap();
+    djour_cmd().arg("init").arg(temp.path()).assert().success();
+
+    fs::write(
+        temp.path().join("2025-01-16.md"),
+        "# Thursday\n\n## Client X #clientx\n\n- [ ] send invoice\n- [x] call back\n",
+    )
+    .unwrap();
+    fs::write(
+        temp.path().join("2025-01-10.md"),
+        "# Friday\n\n- [ ] follow up with legal #work\n- plain item #work\n",
+    )
+    .unwrap();
+    temp
+}
+
+#[test]
+fn test_todos_lists_open_tasks_oldest_first() {
+    let temp = journal_with_tasks();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .arg("todos")
+        .assert()
+        .success()
+        .stdout(
+            "10-01-2025  2025-01-10.md:3  follow up with legal #work\n\
+             16-01-2025  2025-01-16.md:5  send invoice\n",
+        );
+}
+
+#[test]
+fn test_todos_done_all_and_query() {
+    let temp = journal_with_tasks();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["todos", "--done"])
+        .assert()
+        .success()
+        .stdout("16-01-2025  2025-01-16.md:6  call back\n");
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["todos", "--all", "--query", "clientx"])
+        .assert()
+        .success()
+        .stdout(
+            "[ ] 16-01-2025  2025-01-16.md:5  send invoice\n\
+             [x] 16-01-2025  2025-01-16.md:6  call back\n",
+        );
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["todos", "--done", "--all"])
+        .assert()
+        .code(1);
+}
+
+#[test]
+fn test_todos_json() {
+    let temp = journal_with_tasks();
+
+    let output = djour_cmd()
+        .current_dir(temp.path())
+        .args(["todos", "--json", "--query", "work"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let tasks: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        tasks,
+        serde_json::json!([{
+            "text": "follow up with legal #work",
+            "checked": false,
+            "tags": ["work"],
+            "source_file": "2025-01-10.md",
+            "date": "2025-01-10",
+            "line": 3
+        }])
+    );
+}
+
+#[test]
+fn test_todos_csv_export() {
+    let temp = journal_with_tasks();
+    fs::write(
+        temp.path().join("2025-01-17.md"),
+        "- [ ] reply to \"Re: budget, Q1\" #work\n",
+    )
+    .unwrap();
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["todos", "--all", "--format", "csv"])
+        .assert()
+        .success()
+        .stdout(
+            "date,file,line,status,text,tags\r\n\
+             2025-01-10,2025-01-10.md,3,open,follow up with legal #work,work\r\n\
+             2025-01-16,2025-01-16.md,5,open,send invoice,clientx\r\n\
+             2025-01-16,2025-01-16.md,6,done,call back,clientx\r\n\
+             2025-01-17,2025-01-17.md,1,open,\"reply to \"\"Re: budget, Q1\"\" #work\",work\r\n",
+        );
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["todos", "--format", "csv", "--json"])
+        .assert()
+        .code(1)
+        .stdout("")
+        .stderr(predicates::str::contains(
+            "the argument '--format <FORMAT>' cannot be used with '--json'",
+        ));
+}
@@ -0,0 +1,194 @@
+//! Integration tests for `djour version` and update notifications
+
+#![allow(deprecated)]
+
+use predicates::prelude::*;
+use std::path::Path;
+use tempfile::TempDir;
+
+mod common;
+use common::djour_cmd;
+
+fn init_journal(path: &Path, settings: &[(&str, &str)]) {
+    djour_cmd().arg("init").arg(path).assert().success();
+    for (key, value) in settings {
+        djour_cmd()
+            .current_dir(path)
+            .args(["config", key, value])
+            .assert()
+            .success();
+    }
+}
+
+#[test]
+fn test_version_prints_built_version() {
+    djour_cmd()
+        .arg("version")
+        .assert()
+        .success()
+        .stdout(format!("djour {}\n", env!("CARGO_PKG_VERSION")));
+}
+
+#[test]
+fn test_update_check_config_keys() {
+    let temp = TempDir::new().unwrap();
+    init_journal(temp.path(), &[]);
+
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "update_check"])
+        .assert()
+        .success()
+        .stdout("false\n");
+    djour_cmd()
+        .current_dir(temp.path())
+        .args(["config", "update_check", "sometimes"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Invalid value for update_check: 'sometimes'. Use 'true' or 'false'",
+        ));
+}
+
+#[cfg(not(feature = "update-check"))]
+#[test]
+fn test_version_check_without_feature() {
+    djour_cmd()
+        .args(["version", "--check"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains(
+            "built without the update-check feature",
+        ));
+}
+
+#[cfg(feature = "update-check")]
+mod update_check {
+    use super::*;
+    use std::fs;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// Serve `body` as a release endpoint for `requests` requests; returns its URL
+    fn serve_release(body: &'static str, requests: usize) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/releases/latest", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            for stream in listener.incoming().take(requests) {
+                let mut stream = stream.unwrap();
+                let mut request = [0u8; 4096];
+                let _ = stream.read(&mut request);
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        url
+    }
+
+    /// A URL nothing listens on
+    fn closed_url() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/latest", listener.local_addr().unwrap());
+        drop(listener);
+        url
+    }
+
+    #[test]
+    fn test_version_check_reports_newer_release() {
+        let temp = TempDir::new().unwrap();
+        let url = serve_release(r#"{"tag_name": "v999.1.0", "draft": false}"#, 1);
+        init_journal(temp.path(), &[("update_url", &url)]);
+
+        djour_cmd()
+            .current_dir(temp.path())
+            .args(["version", "--check"])
+            .assert()
+            .success()
+            .stdout(format!(
+                "djour 999.1.0 is available (you have {})\n",
+                env!("CARGO_PKG_VERSION")
+            ));
+    }
+
+    #[test]
+    fn test_version_check_up_to_date() {
+        let temp = TempDir::new().unwrap();
+        let url = serve_release("0.0.1\n", 1);
+        init_journal(temp.path(), &[("update_url", &url)]);
+
+        djour_cmd()
+            .current_dir(temp.path())
+            .args(["version", "--check"])
+            .assert()
+            .success()
+            .stdout(format!(
+                "djour {} is up to date\n",
+                env!("CARGO_PKG_VERSION")
+            ));
+    }
+
+    #[test]
+    fn test_version_check_fails_clearly_when_unreachable() {
+        let temp = TempDir::new().unwrap();
+        let url = closed_url();
+        init_journal(temp.path(), &[("update_url", &url)]);
+
+        djour_cmd()
+            .current_dir(temp.path())
+            .args(["version", "--check"])
+            .assert()
+            .code(1)
+            .stdout("")
+            .stderr(predicate::str::contains(format!(
+                "Cannot check for updates: {}",
+                url
+            )));
+    }
+
+    #[test]
+    fn test_passive_check_notifies_once_a_day() {
+        let temp = TempDir::new().unwrap();
+        let url = serve_release(r#"{"tag_name": "v999.1.0"}"#, 1);
+        init_journal(temp.path(), &[("update_url", &url)]);
+
+        // The first successful command with the key on runs the check
+        djour_cmd()
+            .current_dir(temp.path())
+            .args(["config", "update_check", "true"])
+            .assert()
+            .success()
+            .stderr(predicate::str::contains("djour 999.1.0 is available"));
+        let cache = fs::read_to_string(temp.path().join(".djour/cache/update-check.json")).unwrap();
+        assert!(cache.contains("999.1.0"), "{}", cache);
+
+        // Checked today already: no notice and no request
+        djour_cmd()
+            .current_dir(temp.path())
+            .arg("list")
+            .assert()
+            .success()
+            .stderr(predicate::str::contains("available").not());
+    }
+
+    #[test]
+    fn test_passive_check_is_silent_when_unreachable() {
+        let temp = TempDir::new().unwrap();
+        let url = closed_url();
+        init_journal(
+            temp.path(),
+            &[("update_url", &url), ("update_check", "true")],
+        );
+
+        djour_cmd()
+            .current_dir(temp.path())
+            .arg("list")
+            .assert()
+            .success()
+            .stderr("");
+    }
+}